@@ -0,0 +1,25 @@
+class Node {
+    constructor(left, right) {
+        this.left = left;
+        this.right = right;
+    }
+
+    check() {
+        if (this.left === null) return 1;
+        return 1 + this.left.check() + this.right.check();
+    }
+}
+
+function make(depth) {
+    if (depth === 0) return new Node(null, null);
+    return new Node(make(depth - 1), make(depth - 1));
+}
+
+let total = 0;
+for (let depth = 4; depth <= 14; depth += 2) {
+    const iterations = 2 ** (14 - depth);
+    for (let i = 0; i < iterations; i++) {
+        total += make(depth).check();
+    }
+}
+console.log(total);
@@ -0,0 +1,25 @@
+class Node:
+    def __init__(self, left, right):
+        self.left = left
+        self.right = right
+
+    def check(self):
+        if self.left is None:
+            return 1
+        return 1 + self.left.check() + self.right.check()
+
+
+def make(depth):
+    if depth == 0:
+        return Node(None, None)
+    return Node(make(depth - 1), make(depth - 1))
+
+
+total = 0
+depth = 4
+while depth <= 14:
+    iterations = 2 ** (14 - depth)
+    for i in range(iterations):
+        total += make(depth).check()
+    depth += 2
+print(total)
@@ -0,0 +1,39 @@
+class Account {
+    constructor(owner) {
+        this.owner = owner;
+        this.balance = 0;
+        this.history = new Map();
+    }
+
+    record(kind, amount) {
+        this.balance = this.balance + amount;
+        let seen = this.history.get(kind);
+        if (seen === undefined) seen = 0;
+        this.history.set(kind, seen + amount);
+    }
+
+    total(kind) {
+        const amount = this.history.get(kind);
+        if (amount === undefined) return 0;
+        return amount;
+    }
+}
+
+const kinds = ["food", "rent", "travel", "salary", "gifts", "tax"];
+const accounts = new Map();
+for (let i = 0; i < 200000; i++) {
+    const owner = "user" + (i % 500);
+    let account = accounts.get(owner);
+    if (account === undefined) {
+        account = new Account(owner);
+        accounts.set(owner, account);
+    }
+    account.record(kinds[i % 6], i % 97);
+}
+
+let total = 0;
+for (const owner of accounts.keys()) {
+    const account = accounts.get(owner);
+    total = total + account.balance + account.total("rent") * 2;
+}
+console.log(total);
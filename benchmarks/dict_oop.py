@@ -0,0 +1,35 @@
+class Account:
+    def __init__(self, owner):
+        self.owner = owner
+        self.balance = 0
+        self.history = {}
+
+    def record(self, kind, amount):
+        self.balance = self.balance + amount
+        seen = self.history.get(kind)
+        if seen is None:
+            seen = 0
+        self.history[kind] = seen + amount
+
+    def total(self, kind):
+        amount = self.history.get(kind)
+        if amount is None:
+            return 0
+        return amount
+
+
+kinds = ["food", "rent", "travel", "salary", "gifts", "tax"]
+accounts = {}
+for i in range(200000):
+    owner = "user" + str(i % 500)
+    account = accounts.get(owner)
+    if account is None:
+        account = Account(owner)
+        accounts[owner] = account
+    account.record(kinds[i % 6], i % 97)
+
+total = 0
+for owner in accounts.keys():
+    account = accounts[owner]
+    total = total + account.balance + account.total("rent") * 2
+print(total)
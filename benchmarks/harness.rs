@@ -0,0 +1,38 @@
+// `cargo bench` : lance les programmes de benchmarks/ avec le binaire `aegis` compilé en release.
+//   cargo bench                      tous les programmes, comparés à Python / Node
+//   cargo bench -- fib nbody         seulement ceux dont le nom est donné
+//   cargo bench -- --save            ajoute les résultats à benchmarks/history.json
+
+use std::path::{Path, PathBuf};
+use aegis_core::bench;
+
+fn main() {
+    let mut save = false;
+    let mut names = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            // Ajouté par cargo bench
+            "--bench" => {},
+            "--save" => save = true,
+            _ => names.push(arg),
+        }
+    }
+
+    let result = bench::discover(&[]).and_then(|files| {
+        let files: Vec<PathBuf> = files.into_iter()
+            .filter(|file| names.is_empty() || file.file_stem().is_some_and(|stem| names.iter().any(|n| stem == n.as_str())))
+            .collect();
+        let options = bench::Options {
+            iterations: 5,
+            opt_level: 0,
+            compare: true,
+            history: PathBuf::from(bench::HISTORY),
+            save,
+        };
+        bench::run(Path::new(env!("CARGO_BIN_EXE_aegis")), &files, &options)
+    });
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
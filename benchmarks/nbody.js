@@ -0,0 +1,92 @@
+class Body {
+    constructor(x, y, z, vx, vy, vz, mass) {
+        this.x = x;
+        this.y = y;
+        this.z = z;
+        this.vx = vx;
+        this.vy = vy;
+        this.vz = vz;
+        this.mass = mass;
+    }
+}
+
+const SOLAR_MASS = 4.0 * 3.141592653589793 * 3.141592653589793;
+const DAYS_PER_YEAR = 365.24;
+
+function system() {
+    const bodies = [
+        new Body(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, SOLAR_MASS),
+        new Body(4.84143144246472090, -1.16032004402742839, -0.103622044471123109,
+            0.00166007664274403694 * DAYS_PER_YEAR, 0.00769901118419740425 * DAYS_PER_YEAR,
+            -0.0000690460016972063023 * DAYS_PER_YEAR, 0.000954791938424326609 * SOLAR_MASS),
+        new Body(8.34336671824457987, 4.12479856412430479, -0.403523417114321381,
+            -0.00276742510726862411 * DAYS_PER_YEAR, 0.00499852801234917238 * DAYS_PER_YEAR,
+            0.0000230417297573763929 * DAYS_PER_YEAR, 0.000285885980666130812 * SOLAR_MASS),
+        new Body(12.8943695621391310, -15.1111514016986312, -0.223307578892655734,
+            0.00296460137564761618 * DAYS_PER_YEAR, 0.00237847173959480950 * DAYS_PER_YEAR,
+            -0.0000296589568540237556 * DAYS_PER_YEAR, 0.0000436624404335156298 * SOLAR_MASS),
+        new Body(15.3796971148509165, -25.9193146099879641, 0.179258772950371181,
+            0.00268067772490389322 * DAYS_PER_YEAR, 0.00162824170038242295 * DAYS_PER_YEAR,
+            -0.0000951592254519715870 * DAYS_PER_YEAR, 0.0000515138902046611451 * SOLAR_MASS)
+    ];
+    let px = 0.0, py = 0.0, pz = 0.0;
+    for (const b of bodies) {
+        px = px + b.vx * b.mass;
+        py = py + b.vy * b.mass;
+        pz = pz + b.vz * b.mass;
+    }
+    const sun = bodies[0];
+    sun.vx = 0.0 - px / SOLAR_MASS;
+    sun.vy = 0.0 - py / SOLAR_MASS;
+    sun.vz = 0.0 - pz / SOLAR_MASS;
+    return bodies;
+}
+
+function advance(bodies, dt) {
+    const n = bodies.length;
+    for (let i = 0; i < n; i++) {
+        const a = bodies[i];
+        for (let j = i + 1; j < n; j++) {
+            const b = bodies[j];
+            const dx = a.x - b.x;
+            const dy = a.y - b.y;
+            const dz = a.z - b.z;
+            const d2 = dx * dx + dy * dy + dz * dz;
+            const mag = dt / (d2 * Math.sqrt(d2));
+            a.vx = a.vx - dx * b.mass * mag;
+            a.vy = a.vy - dy * b.mass * mag;
+            a.vz = a.vz - dz * b.mass * mag;
+            b.vx = b.vx + dx * a.mass * mag;
+            b.vy = b.vy + dy * a.mass * mag;
+            b.vz = b.vz + dz * a.mass * mag;
+        }
+    }
+    for (const b of bodies) {
+        b.x = b.x + dt * b.vx;
+        b.y = b.y + dt * b.vy;
+        b.z = b.z + dt * b.vz;
+    }
+}
+
+function energy(bodies) {
+    let e = 0.0;
+    const n = bodies.length;
+    for (let i = 0; i < n; i++) {
+        const a = bodies[i];
+        e = e + 0.5 * a.mass * (a.vx * a.vx + a.vy * a.vy + a.vz * a.vz);
+        for (let j = i + 1; j < n; j++) {
+            const b = bodies[j];
+            const dx = a.x - b.x;
+            const dy = a.y - b.y;
+            const dz = a.z - b.z;
+            e = e - a.mass * b.mass / Math.sqrt(dx * dx + dy * dy + dz * dz);
+        }
+    }
+    return e;
+}
+
+const bodies = system();
+for (let step = 0; step < 10000; step++) {
+    advance(bodies, 0.01);
+}
+console.log(Math.trunc(energy(bodies) * 1000000000.0));
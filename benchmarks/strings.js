@@ -0,0 +1,17 @@
+const words = ["lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit"];
+const parts = [];
+for (let i = 0; i < 20000; i++) {
+    parts.push(words[i % 8] + i);
+}
+const text = parts.join(" ");
+
+let total = 0;
+for (let round = 0; round < 20; round++) {
+    const tokens = text.toUpperCase().replaceAll("O", "0").split(" ");
+    for (const token of tokens) {
+        if (token.startsWith("L0REM")) {
+            total = total + token.length;
+        }
+    }
+}
+console.log(total);
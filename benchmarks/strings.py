@@ -0,0 +1,13 @@
+words = ["lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit"]
+parts = []
+for i in range(20000):
+    parts.append(words[i % 8] + str(i))
+text = " ".join(parts)
+
+total = 0
+for round in range(20):
+    tokens = text.upper().replace("O", "0").split(" ")
+    for token in tokens:
+        if token.startswith("L0REM"):
+            total = total + len(token)
+print(total)
@@ -0,0 +1,16 @@
+const colors = ["red", "green", "blue", "yellow"];
+const counts = { red: 0, green: 0, blue: 0, yellow: 0 };
+let matches = 0;
+let length = 0;
+for (let i = 0; i < 300000; i++) {
+    const color = colors[i % 4];
+    if (color === "green" || color === "blue") {
+        matches = matches + 1;
+    }
+    const label = color + ":" + i;
+    length = length + label.length;
+    counts[color] = counts[color] + 1;
+}
+console.log(matches);
+console.log(length);
+console.log(counts["yellow"]);
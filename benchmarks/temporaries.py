@@ -0,0 +1,14 @@
+colors = ["red", "green", "blue", "yellow"]
+counts = {"red": 0, "green": 0, "blue": 0, "yellow": 0}
+matches = 0
+length = 0
+for i in range(300000):
+    color = colors[i % 4]
+    if color == "green" or color == "blue":
+        matches = matches + 1
+    label = color + ":" + str(i)
+    length = length + len(label)
+    counts[color] = counts[color] + 1
+print(matches)
+print(length)
+print(counts["yellow"])
@@ -0,0 +1,10 @@
+#![no_main]
+
+// Source arbitraire -> lexer + parser. Une erreur est attendue, une panique est un bug.
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(source) = std::str::from_utf8(data) {
+        let _ = aegis_core::compiler::compile(source);
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+// JSON arbitraire (l'AST peut aussi être fourni sous forme de fichier .json) -> loader.
+// Les sources valides passent aussi par le parser pour tester des AST réalistes.
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(data) {
+        let _ = aegis_core::loader::parse_block(&json);
+    }
+
+    if let Ok(source) = std::str::from_utf8(data)
+        && let Ok(json) = aegis_core::compiler::compile(source) {
+        let _ = aegis_core::loader::parse_block(&json);
+    }
+});
@@ -4,10 +4,11 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 // Note : on utilise super:: pour remonter dans le module AST
 use super::{ClassDefinition, Value}; 
+use crate::native::NativeError;
 
 pub type SharedEnv = Rc<RefCell<Environment>>;
 
-pub type NativeFn = fn(Vec<Value>) -> Result<Value, String>;
+pub type NativeFn = fn(Vec<Value>) -> Result<Value, NativeError>;
 
 #[derive(Debug, PartialEq)]
 pub struct Environment {
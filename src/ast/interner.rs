@@ -0,0 +1,235 @@
+// Chaînes des valeurs Aegis. Une chaîne construite à l'exécution (concaténation, lecture de
+// fichier...) possède son tampon, que l'arena de la VM peut recycler. Les chaînes qui reviennent
+// sans cesse (littéraux du code, clés de dict) sont internées : une seule copie par thread,
+// partagée par Rc, et deux chaînes internées sont égales si et seulement si elles pointent au
+// même endroit (comparaison en O(1), sans lire les octets).
+
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::rc::Rc;
+
+// Taille de table à partir de laquelle on retire les chaînes que plus personne ne tient
+// (clés d'un gros JSON déjà libéré...). Le seuil double si la table reste pleine.
+const PRUNE_THRESHOLD: usize = 4096;
+
+struct Interner {
+    strings: HashSet<Rc<str>>,
+    prune_at: usize,
+}
+
+thread_local! {
+    // Rc n'est pas Send : chaque thread (workers, parallel_map) a sa table
+    static INTERNER: RefCell<Interner> = RefCell::new(Interner { strings: HashSet::new(), prune_at: PRUNE_THRESHOLD });
+}
+
+fn intern(text: &str) -> Rc<str> {
+    INTERNER.with(|interner| {
+        let mut interner = interner.borrow_mut();
+        if let Some(shared) = interner.strings.get(text) {
+            return shared.clone();
+        }
+        if interner.strings.len() >= interner.prune_at {
+            // Tenue seulement par la table : plus aucune valeur ne l'utilise
+            interner.strings.retain(|s| Rc::strong_count(s) > 1);
+            interner.prune_at = (interner.strings.len() * 2).max(PRUNE_THRESHOLD);
+        }
+        let shared: Rc<str> = Rc::from(text);
+        interner.strings.insert(shared.clone());
+        shared
+    })
+}
+
+/// Contenu d'une `Value::String`. Se lit comme un `&str` (Deref) ; se construit depuis une
+/// `String` ou un `&str` (`Value::String("abc".into())`).
+#[derive(Clone)]
+pub enum Str {
+    Owned(String),
+    // Toujours issue de `intern` : deux Interned de même contenu partagent le même Rc
+    Interned(Rc<str>),
+}
+
+impl Str {
+    /// La copie partagée de `text`, créée au premier appel.
+    pub fn intern(text: &str) -> Str {
+        Str::Interned(intern(text))
+    }
+
+    /// Cette chaîne, internée (sans copie si elle l'est déjà).
+    pub fn interned(self) -> Str {
+        match self {
+            Str::Owned(s) => Str::intern(&s),
+            shared => shared,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Str::Owned(s) => s,
+            Str::Interned(s) => s,
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self {
+            Str::Owned(s) => s,
+            Str::Interned(s) => s.to_string(),
+        }
+    }
+
+    /// Accès en écriture : une chaîne internée est d'abord recopiée (les autres valeurs qui la
+    /// partagent ne changent pas).
+    pub fn make_mut(&mut self) -> &mut String {
+        if let Str::Interned(s) = self {
+            *self = Str::Owned(s.to_string());
+        }
+        match self {
+            Str::Owned(s) => s,
+            Str::Interned(_) => unreachable!(),
+        }
+    }
+}
+
+impl Default for Str {
+    fn default() -> Self {
+        Str::Owned(String::new())
+    }
+}
+
+impl Deref for Str {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Str {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for Str {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for Str {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            // Chemin rapide : l'interner garantit une seule copie par contenu
+            (Str::Interned(a), Str::Interned(b)) => Rc::ptr_eq(a, b),
+            _ => self.as_str() == other.as_str(),
+        }
+    }
+}
+
+impl Eq for Str {}
+
+impl PartialEq<str> for Str {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Str {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Str {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialOrd for Str {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Str {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+// Même hash que le &str : une table indexée par Str se consulte avec un &str
+impl Hash for Str {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl From<String> for Str {
+    fn from(s: String) -> Self {
+        Str::Owned(s)
+    }
+}
+
+impl From<&str> for Str {
+    fn from(s: &str) -> Self {
+        Str::Owned(s.to_string())
+    }
+}
+
+impl From<&String> for Str {
+    fn from(s: &String) -> Self {
+        Str::Owned(s.clone())
+    }
+}
+
+impl From<Str> for String {
+    fn from(s: Str) -> Self {
+        s.into_string()
+    }
+}
+
+impl fmt::Display for Str {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Debug for Str {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl FromIterator<char> for Str {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Str::Owned(iter.into_iter().collect())
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Str {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        Str::Owned(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<String> for Str {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Str::Owned(iter.into_iter().collect())
+    }
+}
+
+impl AsRef<std::path::Path> for Str {
+    fn as_ref(&self) -> &std::path::Path {
+        self.as_str().as_ref()
+    }
+}
+
+impl AsRef<std::ffi::OsStr> for Str {
+    fn as_ref(&self) -> &std::ffi::OsStr {
+        self.as_str().as_ref()
+    }
+}
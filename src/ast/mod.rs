@@ -1,8 +1,12 @@
 pub mod value;
 pub mod nodes;
 pub mod environment;
+pub mod types;
+pub mod interner;
 
 // Re-export pour faciliter l'accès : use crate::ast::{Value, Instruction, ...}
-pub use value::{Value, InstanceData};
-pub use nodes::{Expression, Instruction, ClassDefinition, Statement};
+pub use value::{Value, InstanceData, Zone, DictMap, ValueKey, TypedArray};
+pub use interner::Str;
+pub use nodes::{Expression, Instruction, ClassDefinition, Statement, CompoundOp};
 pub use environment::{Environment, NativeFn};
+pub use types::TypeExpr;
@@ -1,6 +1,7 @@
 use crate::ast::value::Visibility;
 
-use super::value::Value; // Import Value from sibling module
+use super::value::{Value, ValueKey}; // Import Value from sibling module
+use super::types::TypeExpr;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -44,7 +45,8 @@ pub struct ClassDefinition {
     pub properties: Vec<ClassProperty>,
 
     pub visibilities: HashMap<String, Visibility>,
-    pub is_final: bool
+    pub is_final: bool,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -54,7 +56,9 @@ pub enum Expression {
     Function {
         params: Vec<(String, Option<String>)>,
         ret_type: Option<String>,
-        body: Vec<Statement>
+        body: Vec<Statement>,
+        // func fact(n) { ... } en expression : le nom n'est visible que dans son propre corps
+        name: Option<String>
     },
 
     // Arithmetic
@@ -63,10 +67,14 @@ pub enum Expression {
     Mul(Box<Expression>, Box<Expression>),
     Div(Box<Expression>, Box<Expression>),
     Modulo(Box<Expression>, Box<Expression>),
+    // a ~/ b : quotient arrondi vers -inf
+    FloorDiv(Box<Expression>, Box<Expression>),
 
     // Comparison
     Equal(Box<Expression>, Box<Expression>),
     NotEqual(Box<Expression>, Box<Expression>),
+    Same(Box<Expression>, Box<Expression>),
+    NotSame(Box<Expression>, Box<Expression>),
     LessThan(Box<Expression>, Box<Expression>),
     GreaterThan(Box<Expression>, Box<Expression>),
     LessEqual(Box<Expression>, Box<Expression>),
@@ -76,6 +84,8 @@ pub enum Expression {
     And(Box<Expression>, Box<Expression>),
     Or(Box<Expression>, Box<Expression>),
     Not(Box<Expression>),
+    // await <tâche> : attend la fin de la tâche et vaut son résultat
+    Await(Box<Expression>),
     Ternary(Box<Expression>, Box<Expression>, Box<Expression>),
     NullCoalescing(Box<Expression>, Box<Expression>),
     BitAnd(Box<Expression>, Box<Expression>),
@@ -88,9 +98,13 @@ pub enum Expression {
     Call(Box<Expression>, Vec<Expression>),
     New(Box<Expression>, Vec<Expression>),
     GetAttr(Box<Expression>, String),
+    // objet[index]
+    GetIndex(Box<Expression>, Box<Expression>),
     CallMethod(Box<Expression>, String, Vec<Expression>),
     List(Vec<Expression>),
-    Dict(Vec<(String, Expression)>),
+    // "a ${b} c" : chaque partie est convertie en texte puis concaténée
+    Interpolation(Vec<Expression>),
+    Dict(Vec<(ValueKey, Expression)>),
     SuperCall(String, Vec<Expression>),
     Range(Box<Expression>, Box<Expression>)
 }
@@ -98,6 +112,8 @@ pub enum Expression {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Instruction {
     Set(String, Option<String>, Expression),
+    // `x = ...` sans var/let (aussi x += 1, x++) : réaffectation d'une variable existante
+    Assign(String, Expression),
     Print(Expression),
     If {
         condition: Expression,
@@ -114,17 +130,27 @@ pub enum Instruction {
         name: String,
         params: Vec<(String, Option<String>)>,
         ret_type: Option<String>,
-        body: Vec<Statement>
+        body: Vec<Statement>,
+        // async func : l'appel renvoie une tâche
+        is_async: bool
     },
     Input(String, Expression),
     Class(ClassDefinition),
     SetAttr(Box<Expression>, String, Expression),
+    // objet[index] = valeur
+    SetIndex(Box<Expression>, Expression, Expression),
+    // objet.attr op= valeur et objet[index] op= valeur (aussi ++ / --) : l'objet et l'index
+    // ne sont évalués qu'une fois
+    CompoundSetAttr(Box<Expression>, String, CompoundOp, Expression),
+    CompoundSetIndex(Box<Expression>, Expression, CompoundOp, Expression),
     Enum(String, Vec<String>),
-    Import(String),
+    Import(String, Option<String>),
+    // from "x" import a, b as c : (nom exporté par le module, nom local)
+    FromImport(String, Vec<(String, String)>),
     TryCatch {
         try_body: Vec<Statement>,
-        error_var: String,
-        catch_body: Vec<Statement>,
+        // Clauses essayées dans l'ordre : (variable, type attendu (None : toute erreur), corps)
+        catches: Vec<(String, Option<String>, Vec<Statement>)>,
     },
     Switch {
         value: Expression,
@@ -136,11 +162,42 @@ pub enum Instruction {
         body: Vec<Statement>
     },
     Throw(Expression),
+    // Code exécuté à la sortie de la fonction englobante (return, fin du corps ou erreur)
+    Defer(Vec<Statement>),
+    // with <ressource> as <nom> { ... } : close()/__exit__() appelé à la sortie du bloc
+    With {
+        resource: Expression,
+        name: String,
+        body: Vec<Statement>,
+    },
     Break,
     Continue,
-    Const(String, Expression),
+    Const(String, Option<String>, Expression),
     ForEach(String, Expression, Vec<Statement>),
-    Interface(InterfaceDefinition)
+    Interface(InterfaceDefinition),
+    TypeAlias(String, TypeExpr),
+    // Déclaration de premier niveau non exportée par le module (private func ...)
+    Private(Box<Instruction>),
+    // Déclaration exportée explicitement (export func ...) : le module n'expose plus qu'elles
+    Export(Box<Instruction>),
+    // Directive en tête de fichier (`use strict`)
+    Use(String),
+    // Instructions produites par une seule ligne source (destructuring), dans la portée courante :
+    // contrairement à un bloc, les variables déclarées restent visibles après
+    Group(Vec<Statement>),
+    // Signature d'une fonction, placée en tête de son corps (voir param_prologue) : `b = 10`
+    // est calculé à l'appel quand l'argument manque, `...rest` reçoit les arguments en trop
+    DefaultParam(String, Expression),
+    RestParam(String)
+}
+
+// Opérateur d'une assignation composée (+=, -=, *=, /=)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompoundOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -148,3 +205,53 @@ pub struct Statement {
     pub kind: Instruction,
     pub line: usize
 }
+
+/// Docstring d'un corps de fonction ou d'un module : la chaîne littérale qui l'ouvre
+/// (les commentaires `///` y sont convertis par le parser).
+pub fn docstring(body: &[Statement]) -> Option<&str> {
+    match body.iter().find(|s| !matches!(s.kind, Instruction::Use(_))).map(|s| &s.kind) {
+        Some(Instruction::ExpressionStatement(Expression::Literal(Value::String(doc)))) => Some(doc),
+        _ => None,
+    }
+}
+
+/// Retire la docstring du corps (elle n'a pas à être exécutée) et la renvoie.
+pub fn take_docstring(body: &mut Vec<Statement>) -> Option<String> {
+    let doc = docstring(body)?.to_string();
+    let index = body.iter().position(|s| !matches!(s.kind, Instruction::Use(_)))?;
+    body.remove(index);
+    Some(doc)
+}
+
+fn is_param_statement(stmt: &Statement) -> bool {
+    matches!(stmt.kind, Instruction::DefaultParam(..) | Instruction::RestParam(_))
+}
+
+// Index qui suit la docstring (0 sans docstring)
+fn after_docstring(body: &[Statement]) -> usize {
+    if docstring(body).is_none() {
+        return 0;
+    }
+    body.iter().position(|s| !matches!(s.kind, Instruction::Use(_))).map_or(0, |i| i + 1)
+}
+
+/// Ajoute au corps d'une fonction les DefaultParam / RestParam de sa signature, juste après
+/// la docstring (qui reste la première instruction).
+pub fn with_param_prologue(mut body: Vec<Statement>, prologue: Vec<Statement>) -> Vec<Statement> {
+    let start = after_docstring(&body);
+    body.splice(start..start, prologue);
+    body
+}
+
+/// Valeurs par défaut et paramètre `...rest` de la signature, en tête du corps (après la docstring).
+pub fn param_prologue(body: &[Statement]) -> &[Statement] {
+    let start = after_docstring(body);
+    let len = body[start..].iter().take_while(|s| is_param_statement(s)).count();
+    &body[start..start + len]
+}
+
+/// Retire le prologue de la signature du corps (une fois la docstring retirée) et le renvoie.
+pub fn take_param_prologue(body: &mut Vec<Statement>) -> Vec<Statement> {
+    let len = body.iter().take_while(|s| is_param_statement(s)).count();
+    body.drain(..len).collect()
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// Expression de type d'une annotation : `int`, `Animal`, `int|string|null`...
+/// Les annotations circulent sous forme de texte dans l'AST JSON et le bytecode,
+/// et sont reparsées ici quand on a besoin de leur structure.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExpr {
+    Named(String),
+    Union(Vec<TypeExpr>),
+}
+
+impl TypeExpr {
+    pub fn parse(source: &str) -> Result<TypeExpr, String> {
+        let mut members = Vec::new();
+
+        for part in source.split('|') {
+            let name = part.trim();
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return Err(format!("Invalid type expression '{}'", source));
+            }
+            members.push(TypeExpr::Named(name.to_string()));
+        }
+
+        if members.len() == 1 {
+            Ok(members.remove(0))
+        } else {
+            Ok(TypeExpr::Union(members))
+        }
+    }
+
+    /// Remplace les alias connus par leur définition (les unions imbriquées sont aplaties).
+    pub fn resolve(&self, aliases: &HashMap<String, TypeExpr>) -> TypeExpr {
+        match self {
+            TypeExpr::Named(n) => aliases.get(n).cloned().unwrap_or_else(|| self.clone()),
+            TypeExpr::Union(members) => {
+                let mut flat: Vec<TypeExpr> = Vec::new();
+                for m in members {
+                    for name in m.resolve(aliases).names() {
+                        let named = TypeExpr::Named(name.to_string());
+                        if !flat.contains(&named) {
+                            flat.push(named);
+                        }
+                    }
+                }
+                if flat.len() == 1 { flat.remove(0) } else { TypeExpr::Union(flat) }
+            }
+        }
+    }
+
+    /// Les noms de types acceptés (un seul, sauf pour une union).
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            TypeExpr::Named(n) => vec![n.as_str()],
+            TypeExpr::Union(members) => members.iter().flat_map(|m| m.names()).collect(),
+        }
+    }
+}
+
+impl fmt::Display for TypeExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.names().join("|"))
+    }
+}
@@ -1,11 +1,16 @@
 use std::fmt;
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use indexmap::{Equivalent, IndexMap};
 use serde::{Deserialize, Serialize};
 
 use crate::ast::Environment;
+use crate::ast::interner::Str;
 use crate::chunk::Chunk;
+use crate::vm::gc;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionData {
@@ -13,6 +18,89 @@ pub struct FunctionData {
     pub ret_type: Option<String>,
     pub chunk: Chunk,
     pub env: Option<Rc<RefCell<Environment>>>, // SharedEnv
+    // Docstring (chaîne littérale en tête du corps, ou commentaires `///`), lue par Reflect.doc
+    pub doc: Option<String>,
+    // func log(...args) : le dernier paramètre reçoit la liste des arguments en trop
+    pub variadic: bool,
+    // Paramètres avec valeur par défaut : le début du chunk calcule, dans l'ordre, celles des
+    // arguments manquants. entry_points[k] est l'ip de départ quand k d'entre eux sont passés
+    // (vide sans valeur par défaut : on part de 0).
+    pub entry_points: Vec<usize>,
+}
+
+impl FunctionData {
+    /// Nombre d'arguments obligatoires (ni valeur par défaut, ni `...rest`)
+    pub fn required(&self) -> usize {
+        self.params.len() - self.variadic as usize - self.entry_points.len().saturating_sub(1)
+    }
+
+    pub fn accepts(&self, arg_count: usize) -> bool {
+        arg_count >= self.required() && (self.variadic || arg_count <= self.params.len())
+    }
+
+    /// Arguments acceptés, pour les messages : "2", "1 to 3", "at least 1" (sans `this` pour
+    /// une méthode)
+    pub fn arity(&self) -> String {
+        let this = self.params.first().is_some_and(|(name, _)| name == "this") as usize;
+        let (required, total) = (self.required() - this, self.params.len() - this);
+        if self.variadic {
+            format!("at least {}", required)
+        } else if required < total {
+            format!("{} to {}", required, total)
+        } else {
+            total.to_string()
+        }
+    }
+}
+
+/// Nom du constructeur d'une classe.
+pub const CONSTRUCTOR: &str = "init";
+
+/// Surcharges du constructeur : le premier `init` d'une classe garde son nom, les suivants sont
+/// rangés sous `init#2`, `init#3`... (un `#` ne peut pas apparaître dans un nom écrit en Aegis).
+pub fn constructor_key(index: usize) -> String {
+    if index <= 1 { CONSTRUCTOR.to_string() } else { format!("{}#{}", CONSTRUCTOR, index) }
+}
+
+/// Vrai pour `init` et ses surcharges.
+pub fn is_constructor(name: &str) -> bool {
+    name == CONSTRUCTOR || name.strip_prefix(CONSTRUCTOR).is_some_and(|rest| rest.starts_with('#'))
+}
+
+/// Nom d'une méthode tel qu'écrit dans le source (`init#2` -> `init`).
+pub fn method_name(key: &str) -> &str {
+    key.split('#').next().unwrap_or(key)
+}
+
+/// Tâche asynchrone : appel d'une fonction `async`, ou opération native (HTTP, socket...)
+/// menée sur un thread d'arrière-plan. L'ordonnanceur de la VM la fait avancer.
+#[derive(Debug)]
+pub struct TaskData {
+    pub id: usize,
+    pub name: String,
+    pub state: TaskState,
+    // Vrai une fois qu'un `await` l'a attendue : une erreur jamais attendue est signalée
+    pub observed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum TaskState {
+    Pending,
+    Done(Value),
+    // Valeur de l'erreur : message, ou objet levé par `throw`
+    Failed(Value),
+}
+
+static NEXT_TASK_ID: AtomicUsize = AtomicUsize::new(1);
+
+impl TaskData {
+    pub fn new(name: String) -> Self {
+        TaskData { id: NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed), name, state: TaskState::Pending, observed: false }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        matches!(self.state, TaskState::Pending)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -57,22 +145,185 @@ pub struct ClassData {
     pub interfaces_names: Vec<String>,
 
     pub visibilities: HashMap<String, Visibility>,
+
+    // Calculés une fois par `link` (OpCode::Class) : ancêtres du plus proche au plus lointain,
+    // et méthodes héritées aplaties (nom -> (méthode, profondeur de la classe qui la définit))
+    pub ancestors: Vec<Rc<ClassData>>,
+    pub method_table: HashMap<String, (Value, usize)>,
+    pub static_method_table: HashMap<String, (Value, usize)>,
+
+    // Disposition des champs déclarés (hérités d'abord) : nom -> index dans `InstanceData::slots`
+    pub field_slots: HashMap<String, usize>,
+    pub field_layout: Vec<String>,
+
+    pub doc: Option<String>,
+}
+
+impl ClassData {
+    /// Relie la classe à son parent et précalcule la liste de ses ancêtres et ses tables de méthodes :
+    /// la résolution d'un appel ne remonte plus la chaîne `parent_ref` à chaque fois.
+    pub fn link(mut self, parent: Option<Rc<ClassData>>) -> ClassData {
+        let mut ancestors = Vec::new();
+        let mut method_table = HashMap::new();
+        let mut static_method_table = HashMap::new();
+        let mut field_layout = Vec::new();
+
+        if let Some(parent) = &parent {
+            ancestors.push(parent.clone());
+            ancestors.extend(parent.ancestors.iter().cloned());
+
+            for (name, (method, depth)) in &parent.method_table {
+                method_table.insert(name.clone(), (method.clone(), depth + 1));
+            }
+            for (name, (method, depth)) in &parent.static_method_table {
+                static_method_table.insert(name.clone(), (method.clone(), depth + 1));
+            }
+            field_layout.extend(parent.field_layout.iter().cloned());
+        }
+
+        for (name, method) in &self.methods {
+            method_table.insert(name.clone(), (method.clone(), 0));
+        }
+        for (name, method) in &self.static_methods {
+            static_method_table.insert(name.clone(), (method.clone(), 0));
+        }
+
+        // Un champ redéclaré par la sous-classe garde le slot du parent
+        let mut own_fields: Vec<&String> = self.fields.keys()
+            .filter(|name| !field_layout.contains(*name))
+            .collect();
+        own_fields.sort();
+        field_layout.extend(own_fields.into_iter().cloned());
+
+        self.parent_ref = parent;
+        self.ancestors = ancestors;
+        self.method_table = method_table;
+        self.static_method_table = static_method_table;
+        self.field_slots = field_layout.iter().enumerate().map(|(i, name)| (name.clone(), i)).collect();
+        self.field_layout = field_layout;
+        self
+    }
+
+    // Classe située `depth` niveaux au-dessus (0 = elle-même)
+    fn at_depth(self: &Rc<Self>, depth: usize) -> Rc<ClassData> {
+        if depth == 0 { self.clone() } else { self.ancestors[depth - 1].clone() }
+    }
+
+    /// Méthode d'instance (propre ou héritée) et classe qui la définit.
+    pub fn lookup_method(self: &Rc<Self>, name: &str) -> Option<(Value, Rc<ClassData>)> {
+        let (method, depth) = self.method_table.get(name)?;
+        Some((method.clone(), self.at_depth(*depth)))
+    }
+
+    /// Constructeur à appeler avec `arg_count` arguments, et classe qui le définit : celle
+    /// qui déclare `init` la plus proche. Si elle en déclare plusieurs, celui dont l'arité
+    /// accepte `arg_count` ; un seul `init` est appelé tel quel (l'appel vérifie l'arité).
+    pub fn lookup_constructor(self: &Rc<Self>, arg_count: usize) -> Result<Option<(Value, Rc<ClassData>)>, String> {
+        let Some((init, owner)) = self.lookup_method(CONSTRUCTOR) else { return Ok(None) };
+        let mut overloads: Vec<(&String, &Value)> = owner.methods.iter()
+            .filter(|(name, _)| is_constructor(name))
+            .collect();
+        if overloads.len() < 2 {
+            return Ok(Some((init, owner)));
+        }
+        let found = overloads.iter().find_map(|(_, method)| match method {
+            Value::Function(f) if f.accepts(arg_count + 1) => Some((*method).clone()),
+            _ => None,
+        });
+        match found {
+            Some(method) => Ok(Some((method, owner))),
+            None => {
+                overloads.sort_by_key(|(name, _)| name.as_str());
+                let arities: Vec<String> = overloads.iter().filter_map(|(_, method)| match method {
+                    Value::Function(f) => Some(f.arity().to_string()),
+                    _ => None,
+                }).collect();
+                Err(format!("Class '{}' has no constructor taking {} arguments (constructors take {})",
+                    self.name, arg_count, arities.join(", ")))
+            },
+        }
+    }
+
+    /// Méthode statique (propre ou héritée) et classe qui la définit.
+    pub fn lookup_static_method(self: &Rc<Self>, name: &str) -> Option<(Value, Rc<ClassData>)> {
+        let (method, depth) = self.static_method_table.get(name)?;
+        Some((method.clone(), self.at_depth(*depth)))
+    }
+
+    /// La classe elle-même puis ses ancêtres, du plus proche au plus lointain.
+    pub fn lineage(self: &Rc<Self>) -> impl Iterator<Item = Rc<ClassData>> + '_ {
+        std::iter::once(self.clone()).chain(self.ancestors.iter().cloned())
+    }
+
+    /// Vrai si `other` est cette classe ou l'un de ses ancêtres.
+    pub fn is_subclass_of(&self, other: &Rc<ClassData>) -> bool {
+        std::ptr::eq(self, Rc::as_ptr(other)) || self.ancestors.iter().any(|a| Rc::ptr_eq(a, other))
+    }
+
+    /// Vrai si cette classe ou l'un de ses ancêtres déclare `implements` pour `interface`.
+    pub fn implements(&self, interface: &Rc<InterfaceData>) -> bool {
+        std::iter::once(self).chain(self.ancestors.iter().map(|a| a.as_ref()))
+            .any(|c| c.interfaces.iter().any(|i| Rc::ptr_eq(i, interface)))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct InstanceData {
     pub class: Rc<ClassData>, 
+    // Champs déclarés, indexés par `class.field_slots`
+    pub slots: Vec<Value>,
+    // Champs ajoutés dynamiquement (absents de la déclaration de la classe)
     pub fields: HashMap<String, Value>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl InstanceData {
+    /// Instance dont tous les champs déclarés valent `null` en attendant leurs initialiseurs.
+    pub fn new(class: Rc<ClassData>) -> Self {
+        InstanceData {
+            slots: vec![Value::Null; class.field_layout.len()],
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<Value> {
+        match self.class.field_slots.get(name) {
+            Some(&slot) => Some(self.slots[slot].clone()),
+            None => self.fields.get(name).cloned(),
+        }
+    }
+
+    pub fn set_field(&mut self, name: String, value: Value) {
+        match self.class.field_slots.get(&name) {
+            Some(&slot) => self.slots[slot] = value,
+            None => { self.fields.insert(name, value); }
+        }
+    }
+
+    /// Champs déclarés dans l'ordre des slots, puis champs dynamiques.
+    pub fn field_names(&self) -> Vec<String> {
+        self.class.field_layout.iter().chain(self.fields.keys()).cloned().collect()
+    }
+}
+
+/// Fuseau d'affichage d'une date : celui de la machine, UTC, un fuseau IANA ou un décalage fixe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    Local,
+    Utc,
+    Named(chrono_tz::Tz),
+    Fixed(chrono::FixedOffset),
+}
+
+#[derive(Debug, Clone)]
 pub enum Value {
     Integer(i64),
     Float(f64),
-    String(String),
+    // Possédée ou internée (voir interner.rs)
+    String(Str),
     Boolean(bool),
     List(Rc<RefCell<Vec<Value>>>),
-    Dict(Rc<RefCell<HashMap<String, Value>>>),
+    Dict(Rc<RefCell<DictMap>>),
     Enum(Rc<HashMap<String, Value>>),
     Function(Rc<FunctionData>), 
     Class(Rc<ClassData>),
@@ -81,14 +332,487 @@ pub enum Value {
     Native(String),
     Range(i64, i64, i64),
     Bytes(Rc<RefCell<Vec<u8>>>),
+    // Float32Array / IntArray : nombres rangés bruts, sans Value par élément
+    TypedArray(Rc<RefCell<TypedArray>>),
+    Task(Rc<RefCell<TaskData>>),
+    // Durée en millisecondes (Time.seconds(5), dt2 - dt1)
+    Duration(i64),
+    // Instant (ms depuis l'epoch, UTC) et fuseau dans lequel on le lit
+    DateTime(i64, Zone),
+    // Locale capturée par une closure : la frame et l'environnement de la closure partagent
+    // cette case. Jamais visible du code Aegis : GetLocal et GetFreeVar lisent son contenu
+    Cell(Rc<RefCell<Value>>),
     Null
 }
 
+/// Contenu d'un tableau numérique. Un plugin peut lire directement le Vec (`args.want_array(i)`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedArray {
+    Float32(Vec<f32>),
+    Int(Vec<i64>),
+}
+
+impl TypedArray {
+    pub fn len(&self) -> usize {
+        match self {
+            TypedArray::Float32(items) => items.len(),
+            TypedArray::Int(items) => items.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// "Float32Array" ou "IntArray", comme renvoyé par `typeof`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            TypedArray::Float32(_) => "Float32Array",
+            TypedArray::Int(_) => "IntArray",
+        }
+    }
+
+    /// Élément `index` comme valeur Aegis, ou None au-delà de la fin.
+    pub fn get(&self, index: usize) -> Option<Value> {
+        match self {
+            TypedArray::Float32(items) => items.get(index).map(|f| Value::Float(widen_f32(*f))),
+            TypedArray::Int(items) => items.get(index).map(|i| Value::Integer(*i)),
+        }
+    }
+
+    /// Remplace l'élément `index` (qui doit exister) : un nombre pour Float32Array, un entier
+    /// pour IntArray.
+    pub fn set(&mut self, index: usize, value: &Value) -> Result<(), String> {
+        let name = self.type_name();
+        match (self, value) {
+            (TypedArray::Float32(items), Value::Integer(i)) => items[index] = *i as f32,
+            (TypedArray::Float32(items), Value::Float(f)) => items[index] = *f as f32,
+            (TypedArray::Int(items), Value::Integer(i)) => items[index] = *i,
+            (TypedArray::Float32(_), other) => return Err(format!("{} element must be a number, got {}", name, other.type_name())),
+            (TypedArray::Int(_), other) => return Err(format!("{} element must be an int, got {}", name, other.type_name())),
+        }
+        Ok(())
+    }
+}
+
+/// f32 -> f64 par son écriture décimale la plus courte : 0.1f32 donne 0.1 et non
+/// 0.10000000149011612. Le résultat redonne exactement le même f32.
+pub fn widen_f32(f: f32) -> f64 {
+    if f.fract() == 0.0 || !f.is_finite() {
+        return f as f64;
+    }
+    f.to_string().parse().unwrap_or(f as f64)
+}
+
+/// Contenu d'un dict : les clés dans leur ordre d'insertion.
+pub type DictMap = IndexMap<ValueKey, Value>;
+
+/// Clé d'un dict. Une clé garde son type : `d[1]` et `d["1"]` sont deux entrées différentes.
+/// Les clés chaînes sont internées : deux clés se comparent par pointeur.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueKey {
+    String(Str),
+    Integer(i64),
+    Boolean(bool),
+}
+
+impl ValueKey {
+    pub fn from_value(value: &Value) -> Result<ValueKey, String> {
+        match value {
+            Value::String(s) => Ok(ValueKey::String(s.clone().interned())),
+            Value::Integer(i) => Ok(ValueKey::Integer(*i)),
+            Value::Boolean(b) => Ok(ValueKey::Boolean(*b)),
+            other => Err(format!("Dict key must be a string, an int or a bool, got {}", other.type_name())),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            ValueKey::String(s) => Value::String(s.clone()),
+            ValueKey::Integer(i) => Value::Integer(*i),
+            ValueKey::Boolean(b) => Value::Boolean(*b),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ValueKey::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+}
+
+// Une clé chaîne a le hash de la chaîne seule : `map.get("name")` cherche sans construire de clé
+impl Hash for ValueKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            ValueKey::String(s) => s.hash(state),
+            ValueKey::Integer(i) => i.hash(state),
+            ValueKey::Boolean(b) => b.hash(state),
+        }
+    }
+}
+
+impl Equivalent<ValueKey> for str {
+    fn equivalent(&self, key: &ValueKey) -> bool {
+        key.as_str() == Some(self)
+    }
+}
+
+impl Equivalent<ValueKey> for String {
+    fn equivalent(&self, key: &ValueKey) -> bool {
+        key.as_str() == Some(self.as_str())
+    }
+}
+
+impl From<String> for ValueKey {
+    fn from(s: String) -> Self {
+        ValueKey::String(Str::intern(&s))
+    }
+}
+
+impl From<&str> for ValueKey {
+    fn from(s: &str) -> Self {
+        ValueKey::String(Str::intern(s))
+    }
+}
+
+impl fmt::Display for ValueKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueKey::String(s) => write!(f, "{}", s),
+            ValueKey::Integer(i) => write!(f, "{}", i),
+            ValueKey::Boolean(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+// Égalité : structurelle pour les données (nombres, chaînes, listes, dicts...), par identité
+// (même Rc) pour les fonctions, classes, interfaces et instances. Deux instances aux champs
+// identiques restent deux objets distincts, et une instance qui se référence elle-même
+// peut être comparée sans récursion infinie. Listes et dicts : voir `containers_eq`.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            // Comme pour <, <=, >, >= : un Integer est comparé à un Float par sa valeur
+            (Value::Integer(a), Value::Float(b)) | (Value::Float(b), Value::Integer(a)) => *a as f64 == *b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::List(a), Value::List(b)) => containers_eq(a, b),
+            (Value::Dict(a), Value::Dict(b)) => containers_eq(a, b),
+            (Value::Enum(a), Value::Enum(b)) => a == b,
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::Class(a), Value::Class(b)) => Rc::ptr_eq(a, b),
+            (Value::Instance(a), Value::Instance(b)) => Rc::ptr_eq(a, b),
+            (Value::Interface(a), Value::Interface(b)) => Rc::ptr_eq(a, b),
+            (Value::Native(a), Value::Native(b)) => a == b,
+            (Value::Range(s1, e1, st1), Value::Range(s2, e2, st2)) => (s1, e1, st1) == (s2, e2, st2),
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
+            (Value::TypedArray(a), Value::TypedArray(b)) => a == b,
+            (Value::Task(a), Value::Task(b)) => Rc::ptr_eq(a, b),
+            (Value::Duration(a), Value::Duration(b)) => a == b,
+            // Le même instant, quel que soit le fuseau
+            (Value::DateTime(a, _), Value::DateTime(b, _)) => a == b,
+            (Value::Null, Value::Null) => true,
+            (Value::Cell(a), b) => *a.borrow() == *b,
+            (a, Value::Cell(b)) => *a == *b.borrow(),
+            _ => false,
+        }
+    }
+}
+
+thread_local! {
+    // Paires de conteneurs (adresses) en cours de comparaison sur ce thread
+    static COMPARING: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+// Retire la paire même si la comparaison panique (conteneur emprunté en écriture...)
+struct ComparingGuard;
+
+impl Drop for ComparingGuard {
+    fn drop(&mut self) {
+        COMPARING.with(|pairs| pairs.borrow_mut().pop());
+    }
+}
+
+// Le même conteneur est égal à lui-même sans lire son contenu (comme en Python, `l == l` est
+// vrai même si l contient NaN). Pour les structures cycliques (`l.push(l)`), une paire déjà en
+// cours de comparaison plus haut est supposée égale : si rien d'autre ne diffère, les deux
+// structures se déplient à l'identique, et la comparaison termine au lieu de déborder la pile.
+fn containers_eq<T: PartialEq>(a: &Rc<RefCell<T>>, b: &Rc<RefCell<T>>) -> bool {
+    if Rc::ptr_eq(a, b) {
+        return true;
+    }
+    let pair = (Rc::as_ptr(a) as usize, Rc::as_ptr(b) as usize);
+    let cycle = COMPARING.with(|pairs| {
+        let mut pairs = pairs.borrow_mut();
+        if pairs.contains(&pair) {
+            return true;
+        }
+        pairs.push(pair);
+        false
+    });
+    if cycle {
+        return true;
+    }
+    let _guard = ComparingGuard;
+    *a.borrow() == *b.borrow()
+}
+
+// Libération à plat : détruire récursivement une longue chaîne de listes imbriquées (ou
+// d'instances qui se suivent, de closures...) ferait déborder la pile Rust, par exemple à la
+// fin du script. Quand une valeur est le dernier propriétaire d'un conteneur non vide, ses
+// éléments sont sortis dans une liste de travail et libérés un par un ; le conteneur, vide,
+// est ensuite libéré sans descendre plus loin.
+impl Drop for Value {
+    fn drop(&mut self) {
+        if !self.owns_children() {
+            return;
+        }
+        let mut pending = vec![std::mem::replace(self, Value::Null)];
+        while let Some(mut value) = pending.pop() {
+            value.take_children(&mut pending);
+        }
+    }
+}
+
+impl Value {
+    // Vrai si libérer cette valeur libérerait aussi d'autres valeurs
+    fn owns_children(&self) -> bool {
+        match self {
+            Value::List(rc) => Rc::strong_count(rc) == 1 && rc.try_borrow().is_ok_and(|l| !l.is_empty()),
+            Value::Dict(rc) => Rc::strong_count(rc) == 1 && rc.try_borrow().is_ok_and(|d| !d.is_empty()),
+            Value::Enum(rc) => Rc::strong_count(rc) == 1 && !rc.is_empty(),
+            Value::Instance(rc) => Rc::strong_count(rc) == 1
+                && rc.try_borrow().is_ok_and(|i| !i.slots.is_empty() || !i.fields.is_empty()),
+            Value::Function(rc) => Rc::strong_count(rc) == 1 && rc.env.as_ref().is_some_and(|env| {
+                Rc::strong_count(env) == 1 && env.try_borrow().is_ok_and(|e| !e.variables.is_empty() || e.parent.is_some())
+            }),
+            Value::Task(rc) => Rc::strong_count(rc) == 1 && rc.try_borrow().is_ok_and(|t| !t.is_pending()),
+            Value::Cell(rc) => Rc::strong_count(rc) == 1 && rc.try_borrow().is_ok_and(|v| v.owns_children()),
+            _ => false,
+        }
+    }
+
+    // Vide le conteneur dont cette valeur est le dernier propriétaire, dans `pending`
+    fn take_children(&mut self, pending: &mut Vec<Value>) {
+        if !self.owns_children() {
+            return;
+        }
+        match self {
+            Value::List(rc) => pending.append(&mut rc.borrow_mut()),
+            Value::Dict(rc) => pending.extend(rc.borrow_mut().drain(..).map(|(_, v)| v)),
+            Value::Enum(rc) => {
+                if let Some(entries) = Rc::get_mut(rc) {
+                    pending.extend(entries.drain().map(|(_, v)| v));
+                }
+            },
+            Value::Instance(rc) => {
+                let mut instance = rc.borrow_mut();
+                pending.append(&mut instance.slots);
+                pending.extend(instance.fields.drain().map(|(_, v)| v));
+            },
+            Value::Function(rc) => {
+                let Some(env) = &rc.env else { return };
+                let mut env = env.borrow_mut();
+                pending.extend(env.variables.drain().map(|(_, v)| v));
+                // Puis les environnements englobants, tant que personne d'autre ne les tient
+                let mut parent = env.parent.take();
+                while let Some(outer) = parent.take() {
+                    if Rc::strong_count(&outer) > 1 {
+                        break;
+                    }
+                    let mut outer = outer.borrow_mut();
+                    pending.extend(outer.variables.drain().map(|(_, v)| v));
+                    parent = outer.parent.take();
+                }
+            },
+            Value::Task(rc) => {
+                if let TaskState::Done(value) | TaskState::Failed(value) = std::mem::replace(&mut rc.borrow_mut().state, TaskState::Pending) {
+                    pending.push(value);
+                }
+            },
+            Value::Cell(rc) => pending.push(std::mem::replace(&mut *rc.borrow_mut(), Value::Null)),
+            _ => {},
+        }
+    }
+
+    /// Identité (`===`) : même objet pour les valeurs partagées par référence (listes, dicts,
+    /// instances, bytes...), même type et même valeur pour les scalaires (`1 !== 1.0`).
+    pub fn is_same(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::List(a), Value::List(b)) => Rc::ptr_eq(a, b),
+            (Value::Dict(a), Value::Dict(b)) => Rc::ptr_eq(a, b),
+            (Value::Enum(a), Value::Enum(b)) => Rc::ptr_eq(a, b),
+            (Value::Bytes(a), Value::Bytes(b)) => Rc::ptr_eq(a, b),
+            (Value::TypedArray(a), Value::TypedArray(b)) => Rc::ptr_eq(a, b),
+            // `==` compare un Integer et un Float par leur valeur, pas `===`
+            (Value::Integer(_), Value::Float(_)) | (Value::Float(_), Value::Integer(_)) => false,
+            _ => self == other,
+        }
+    }
+
+    /// Copie superficielle (`Object.copy`) : un nouveau conteneur dont les éléments restent
+    /// partagés. Fonctions, classes, enums et interfaces ne sont jamais copiées.
+    pub fn shallow_copy(&self) -> Value {
+        match self {
+            Value::List(l) => gc::list(l.borrow().clone()),
+            Value::Dict(d) => gc::dict(d.borrow().clone()),
+            Value::Bytes(b) => Value::Bytes(Rc::new(RefCell::new(b.borrow().clone()))),
+            Value::TypedArray(a) => Value::TypedArray(Rc::new(RefCell::new(a.borrow().clone()))),
+            Value::Instance(i) => {
+                let inst = i.borrow();
+                let copy = Value::Instance(Rc::new(RefCell::new(InstanceData {
+                    class: inst.class.clone(),
+                    slots: inst.slots.clone(),
+                    fields: inst.fields.clone(),
+                })));
+                gc::track(&copy);
+                copy
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Copie profonde (`Object.deep_copy`) : les conteneurs imbriqués sont copiés aussi.
+    /// Un objet référencé plusieurs fois (ou qui se contient lui-même) n'est copié qu'une fois.
+    pub fn deep_copy(&self) -> Value {
+        self.deep_copy_with(&mut HashMap::new())
+    }
+
+    fn deep_copy_with(&self, copies: &mut HashMap<i64, Value>) -> Value {
+        if !matches!(self, Value::List(_) | Value::Dict(_) | Value::Instance(_)) {
+            return self.shallow_copy();
+        }
+        if let Some(copy) = copies.get(&self.identity()) {
+            return copy.clone();
+        }
+
+        // La copie est enregistrée vide avant de copier le contenu, pour les cycles
+        let copy = match self {
+            Value::List(_) => gc::list(Vec::new()),
+            Value::Dict(_) => gc::dict(DictMap::new()),
+            Value::Instance(i) => {
+                let copy = Value::Instance(Rc::new(RefCell::new(InstanceData {
+                    class: i.borrow().class.clone(),
+                    slots: Vec::new(),
+                    fields: HashMap::new(),
+                })));
+                gc::track(&copy);
+                copy
+            },
+            _ => unreachable!(),
+        };
+        copies.insert(self.identity(), copy.clone());
+
+        match (self, &copy) {
+            (Value::List(src), Value::List(dst)) => {
+                let items: Vec<Value> = src.borrow().iter().map(|v| v.deep_copy_with(copies)).collect();
+                *dst.borrow_mut() = items;
+            },
+            (Value::Dict(src), Value::Dict(dst)) => {
+                let entries: DictMap = src.borrow().iter().map(|(k, v)| (k.clone(), v.deep_copy_with(copies))).collect();
+                *dst.borrow_mut() = entries;
+            },
+            (Value::Instance(src), Value::Instance(dst)) => {
+                let inst = src.borrow();
+                let slots: Vec<Value> = inst.slots.iter().map(|v| v.deep_copy_with(copies)).collect();
+                let fields: HashMap<String, Value> = inst.fields.iter().map(|(k, v)| (k.clone(), v.deep_copy_with(copies))).collect();
+                let mut target = dst.borrow_mut();
+                target.slots = slots;
+                target.fields = fields;
+            },
+            _ => {}
+        }
+        copy
+    }
+
+    /// Identifiant stable pendant toute la vie de la valeur (`Object.id`) : adresse de l'objet
+    /// partagé pour les types référence, hachage du contenu pour les valeurs simples.
+    /// Deux valeurs `==` par identité ont le même id.
+    pub fn identity(&self) -> i64 {
+        use std::hash::{Hash, Hasher};
+
+        let address = match self {
+            Value::List(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            Value::Dict(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            Value::Enum(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            Value::Function(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            Value::Class(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            Value::Instance(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            Value::Interface(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            Value::Bytes(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            Value::TypedArray(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            Value::Task(rc) => Some(Rc::as_ptr(rc) as *const () as usize),
+            _ => None,
+        };
+        if let Some(address) = address {
+            return address as i64;
+        }
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        std::mem::discriminant(self).hash(&mut hasher);
+        match self {
+            Value::Integer(i) => i.hash(&mut hasher),
+            Value::Float(f) => f.to_bits().hash(&mut hasher),
+            Value::String(s) => s.hash(&mut hasher),
+            Value::Native(s) => s.hash(&mut hasher),
+            Value::Boolean(b) => b.hash(&mut hasher),
+            Value::Range(s, e, step) => (s, e, step).hash(&mut hasher),
+            Value::Duration(ms) | Value::DateTime(ms, _) => ms.hash(&mut hasher),
+            _ => {}
+        }
+        hasher.finish() as i64
+    }
+}
+
+// Nombre de décimales imposé à l'affichage des Float (Float.set_display_precision)
+const SHORTEST: usize = usize::MAX;
+static FLOAT_PRECISION: AtomicUsize = AtomicUsize::new(SHORTEST);
+// Avec une précision imposée : notation scientifique (1.50e3) au lieu de décimale (1500.00)
+static FLOAT_SCIENTIFIC: AtomicBool = AtomicBool::new(false);
+
+/// Fixe le nombre de décimales affichées pour les Float (`None` = représentation la plus courte).
+pub fn set_float_precision(precision: Option<usize>) {
+    FLOAT_PRECISION.store(precision.unwrap_or(SHORTEST), Ordering::Relaxed);
+    FLOAT_SCIENTIFIC.store(false, Ordering::Relaxed);
+}
+
+/// Comme `set_float_precision`, en notation scientifique (`precision` décimales pour la mantisse).
+pub fn set_float_scientific(precision: Option<usize>) {
+    set_float_precision(precision);
+    FLOAT_SCIENTIFIC.store(precision.is_some(), Ordering::Relaxed);
+}
+
+pub fn float_precision() -> Option<usize> {
+    match FLOAT_PRECISION.load(Ordering::Relaxed) {
+        SHORTEST => None,
+        p => Some(p),
+    }
+}
+
+/// Plus courte représentation qui se relit en exactement le même f64.
+/// Les très grands et très petits nombres passent en notation scientifique (1e21, 1.5e-7).
+pub fn shortest_float(fl: f64) -> String {
+    let abs = fl.abs();
+    if fl.is_finite() && abs != 0.0 && !(1e-5..1e16).contains(&abs) {
+        format!("{:e}", fl)
+    } else {
+        format!("{}", fl)
+    }
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Value::Integer(i) => write!(f, "{}", i),
-            Value::Float(fl) => write!(f, "{}", fl),
+            Value::Float(fl) => match float_precision() {
+                Some(p) if FLOAT_SCIENTIFIC.load(Ordering::Relaxed) => write!(f, "{:.*e}", p, fl),
+                Some(p) => write!(f, "{:.*}", p, fl),
+                None => write!(f, "{}", shortest_float(*fl)),
+            },
             Value::String(s) => write!(f, "{}", s),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Null => write!(f, "null"),
@@ -118,6 +842,11 @@ impl fmt::Display for Value {
             Value::Class { 0: rc_class } => write!(f, "<Class {}>", rc_class.name),
             Value::Instance(inst) => {
                 let borrow = inst.borrow();
+                // Une erreur s'affiche comme son message : "ValueError: age must be positive"
+                if crate::native::errors::is_error_class(&borrow.class) {
+                    let message = borrow.get_field("message").unwrap_or(Value::Null);
+                    return write!(f, "{}: {}", borrow.class.name, message);
+                }
                 // Accès direct au nom de la classe
                 write!(f, "<Instance of {}>", borrow.class.name)
             },
@@ -125,11 +854,60 @@ impl fmt::Display for Value {
             Value::Native(name) => write!(f, "<Native Fn {}>", name),
             Value::Range(s, e, step) => write!(f, "{}..{} (step {})", s, e, step),
             Value::Bytes(b) => write!(f, "<Bytes size={}>", b.borrow().len()),
+            Value::TypedArray(a) => {
+                let a = a.borrow();
+                write!(f, "{}({}) [", a.type_name(), a.len())?;
+                for i in 0..a.len() {
+                    if i > 0 { write!(f, ", ")?; }
+                    write!(f, "{}", a.get(i).unwrap_or(Value::Null))?;
+                }
+                write!(f, "]")
+            },
+            Value::Task(task) => {
+                let task = task.borrow();
+                let state = match task.state {
+                    TaskState::Pending => "pending",
+                    TaskState::Done(_) => "done",
+                    TaskState::Failed(_) => "failed",
+                };
+                write!(f, "<Task {} {}>", task.name, state)
+            },
+            Value::Duration(ms) => write!(f, "{}", crate::native::date::humanize(*ms, usize::MAX)),
+            Value::DateTime(ms, zone) => match crate::native::date::iso(*ms, *zone) {
+                Ok(iso) => write!(f, "{}", iso),
+                Err(_) => write!(f, "<DateTime {}>", ms),
+            },
+            Value::Cell(cell) => write!(f, "{}", cell.borrow()),
         }
     }
 }
 
 impl Value {
+    /// Nom du type tel que renvoyé par `typeof` ("int", "list"... ou le nom de la classe d'une instance).
+    pub fn type_name(&self) -> String {
+        match self {
+            Value::Integer(_) => "int".to_string(),
+            Value::Float(_) => "float".to_string(),
+            Value::String(_) => "string".to_string(),
+            Value::Boolean(_) => "bool".to_string(),
+            Value::Null => "null".to_string(),
+            Value::List(_) => "list".to_string(),
+            Value::Dict(_) => "dict".to_string(),
+            Value::Enum(_) => "enum".to_string(),
+            Value::Range(_, _, _) => "range".to_string(),
+            Value::Function(..) | Value::Native(_) => "function".to_string(),
+            Value::Class(_) => "class".to_string(),
+            Value::Interface(_) => "interface".to_string(),
+            Value::Bytes(_) => "bytes".to_string(),
+            Value::TypedArray(a) => a.borrow().type_name().to_string(),
+            Value::Task(_) => "task".to_string(),
+            Value::Duration(_) => "duration".to_string(),
+            Value::DateTime(_, _) => "datetime".to_string(),
+            Value::Instance(i) => i.borrow().class.name.clone(),
+            Value::Cell(cell) => cell.borrow().type_name(),
+        }
+    }
+
     pub fn as_int(&self) -> Result<i64, String> {
         match self {
             Value::Integer(i) => Ok(*i),
@@ -149,7 +927,7 @@ impl Value {
 
     pub fn as_str(&self) -> Result<String, String> {
         match self {
-            Value::String(s) => Ok(s.clone()),
+            Value::String(s) => Ok(s.to_string()),
             _ => Err(format!("Expected string, got {:?}", self))
         }
     }
@@ -0,0 +1,207 @@
+// `aegis bench` / `cargo bench` : lance les programmes de benchmarks/ (fib, nbody...) avec
+// l'interpréteur, et leurs versions Python / Node quand elles existent, puis compare les temps
+// au dernier passage enregistré dans l'historique JSON.
+// Chaque programme tourne dans son propre processus : les temps incluent le démarrage et la
+// compilation, comme pour les autres langages, et les sorties doivent être identiques.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+
+pub const BENCH_DIR: &str = "benchmarks";
+pub const HISTORY: &str = "benchmarks/history.json";
+
+// Versions de référence : (colonne, extension du script à côté du .aeg, interpréteur)
+const REFERENCES: [(&str, &str, &str); 3] = [
+    ("python", "py", "python3"),
+    ("node", "js", "node"),
+    ("lua", "lua", "lua"),
+];
+
+pub struct Options {
+    pub iterations: usize,
+    pub opt_level: u8,
+    // Lance aussi les versions de référence
+    pub compare: bool,
+    pub history: PathBuf,
+    // Ajoute le passage à l'historique
+    pub save: bool,
+}
+
+/// Un passage de `aegis bench`, tel qu'il est enregistré dans l'historique.
+#[derive(Serialize, Deserialize)]
+pub struct Entry {
+    pub date: String,
+    pub version: String,
+    // "release" ou "debug" : on ne compare que des passages du même profil
+    pub profile: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub opt_level: u8,
+    pub iterations: usize,
+    // benchmark -> interpréteur ("aegis", "python"...) -> temps médian en ms
+    pub results: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+/// Programmes à mesurer : les fichiers donnés, les `.aeg` des dossiers donnés
+/// (sans descendre dans les sous-dossiers), et par défaut ceux de benchmarks/.
+pub fn discover(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    let roots: Vec<PathBuf> = if paths.is_empty() {
+        vec![PathBuf::from(BENCH_DIR)]
+    } else {
+        paths.iter().map(PathBuf::from).collect()
+    };
+
+    let mut files = Vec::new();
+    for root in roots {
+        if root.is_dir() {
+            let mut found: Vec<PathBuf> = fs::read_dir(&root)
+                .map_err(|e| format!("Impossible de lire {}: {}", root.display(), e))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "aeg"))
+                .collect();
+            found.sort();
+            files.extend(found);
+        } else if root.is_file() {
+            files.push(root);
+        } else {
+            return Err(format!("{}: no such file or directory", root.display()));
+        }
+    }
+    Ok(files)
+}
+
+/// Mesure chaque programme avec `aegis` (le binaire de l'interpréteur, compilé avec le même
+/// profil que cette bibliothèque), affiche le tableau et ajoute le passage à l'historique si
+/// `options.save`. « vs last » compare au dernier passage du même profil et du même niveau -O.
+pub fn run(aegis: &Path, files: &[PathBuf], options: &Options) -> Result<(), String> {
+    let profile = if cfg!(debug_assertions) { "debug" } else { "release" };
+    let previous = load_history(&options.history)?.into_iter()
+        .rev()
+        .find(|entry| entry.profile == profile && entry.opt_level == options.opt_level);
+    let references: Vec<(&str, &str, &str)> = if options.compare {
+        REFERENCES.iter().copied().filter(|(_, _, program)| installed(program)).collect()
+    } else {
+        Vec::new()
+    };
+
+    if let Some(previous) = &previous {
+        let commit = previous.commit.as_ref().map(|c| format!(" ({})", c)).unwrap_or_default();
+        println!("vs last: run of {}{}\n", previous.date, commit);
+    }
+    print!("{:<16} {:>11} {:>9}", "benchmark", "aegis", "vs last");
+    for (label, _, _) in &references {
+        print!(" {:>11}", label);
+    }
+    println!();
+
+    let mut results = BTreeMap::new();
+    for file in files {
+        let name = file.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let opt = format!("-O{}", options.opt_level);
+        let (aegis_ms, expected) = measure(
+            Command::new(aegis).args(["run", opt.as_str()]).arg(file),
+            options.iterations,
+        ).map_err(|e| format!("{}: {}", name, e))?;
+
+        let mut times = BTreeMap::from([("aegis".to_string(), aegis_ms)]);
+        let delta = previous.as_ref()
+            .and_then(|entry| entry.results.get(&name)?.get("aegis").copied())
+            .map(|before| format!("{:+.1}%", (aegis_ms - before) / before * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        print!("{:<16} {:>11} {:>9}", name, format_ms(aegis_ms), delta);
+
+        let mut mismatches = Vec::new();
+        for (label, ext, program) in &references {
+            let script = file.with_extension(ext);
+            if !script.is_file() {
+                print!(" {:>11}", "-");
+                continue;
+            }
+            match measure(Command::new(program).arg(&script), options.iterations) {
+                Ok((ms, output)) if output == expected => {
+                    print!(" {:>11}", format_ms(ms));
+                    times.insert(label.to_string(), ms);
+                },
+                // Un résultat différent ne mesure pas le même travail : on ne l'enregistre pas
+                Ok(_) => {
+                    print!(" {:>11}", "mismatch");
+                    mismatches.push(script.display().to_string());
+                },
+                Err(e) => return Err(format!("{}: {}", script.display(), e)),
+            }
+        }
+        println!();
+        for script in mismatches {
+            println!("  ⚠️  {} does not print the same result as {}", script, file.display());
+        }
+        results.insert(name, times);
+    }
+
+    let entry = Entry {
+        date: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        profile: profile.to_string(),
+        commit: git_commit(),
+        opt_level: options.opt_level,
+        iterations: options.iterations,
+        results,
+    };
+    if options.save {
+        save(&options.history, entry)?;
+        println!("Results saved to {}", options.history.display());
+    }
+    Ok(())
+}
+
+/// Passages enregistrés, du plus ancien au plus récent (vide si le fichier n'existe pas).
+pub fn load_history(path: &Path) -> Result<Vec<Entry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+fn save(path: &Path, entry: Entry) -> Result<(), String> {
+    let mut history = load_history(path)?;
+    history.push(entry);
+    let json = serde_json::to_string_pretty(&history).map_err(|e| e.to_string())?;
+    fs::write(path, json + "\n").map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))
+}
+
+// Temps médian (ms) de `iterations` exécutions, et la sortie standard de la première
+fn measure(command: &mut Command, iterations: usize) -> Result<(f64, String), String> {
+    let mut times = Vec::new();
+    let mut first_output = None;
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        let output = command.output().map_err(|e| e.to_string())?;
+        times.push(start.elapsed().as_secs_f64() * 1000.0);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("exited with {}\n{}", output.status, stderr.trim_end()));
+        }
+        first_output.get_or_insert_with(|| String::from_utf8_lossy(&output.stdout).to_string());
+    }
+    times.sort_by(f64::total_cmp);
+    // Au dixième de ms : l'historique reste lisible
+    let median = (times[times.len() / 2] * 10.0).round() / 10.0;
+    Ok((median, first_output.unwrap_or_default()))
+}
+
+fn installed(program: &str) -> bool {
+    Command::new(program).arg("--version").output().is_ok_and(|output| output.status.success())
+}
+
+fn git_commit() -> Option<String> {
+    let output = Command::new("git").args(["rev-parse", "--short", "HEAD"]).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn format_ms(ms: f64) -> String {
+    format!("{:.1} ms", ms)
+}
@@ -0,0 +1,199 @@
+// `aegis bundle` : un script et tous les modules qu'il importe dans un seul fichier .aegb, que
+// `aegis run app.aegb` lance sans les sources à côté. Le profil (release, debug, ou une section
+// [profile.<nom>] de aegis.toml) fixe le niveau d'optimisation, la présence de la table des
+// lignes, la compression et la part de la stdlib embarquée.
+//
+// Format : une ligne MAGIC, une ligne d'en-tête JSON (point d'entrée et profil), puis les
+// sources ({chemin: source} en JSON), compressées en gzip si le profil le demande.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use serde::{Deserialize, Serialize};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+use crate::graph::{ModuleGraph, ModuleStatus};
+
+pub const EXTENSION: &str = "aegb";
+const MAGIC: &str = "AEGISBUNDLE 1";
+
+/// Modules de la stdlib embarqués dans le bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stdlib {
+    // Ceux que le programme importe (directement ou par un autre module)
+    Used,
+    // Toute la stdlib : un module chargé par un chemin calculé à l'exécution est trouvé aussi
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub opt_level: u8,
+    // Table des lignes et noms de fichiers : sans elle, les erreurs n'ont plus de position
+    pub debug_info: bool,
+    pub compress: bool,
+    pub stdlib: Stdlib,
+}
+
+impl Profile {
+    fn release() -> Profile {
+        Profile { name: "release".to_string(), opt_level: 2, debug_info: false, compress: true, stdlib: Stdlib::Used }
+    }
+
+    fn debug() -> Profile {
+        Profile { name: "debug".to_string(), opt_level: 0, debug_info: true, compress: false, stdlib: Stdlib::All }
+    }
+}
+
+// Section [profile.<nom>] de aegis.toml : chaque clé remplace celle du profil hérité
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ProfileConfig {
+    // Profil de départ d'un profil personnalisé (release par défaut)
+    inherits: Option<String>,
+    opt_level: Option<u8>,
+    debug_info: Option<bool>,
+    compress: Option<bool>,
+    stdlib: Option<Stdlib>,
+}
+
+#[derive(Deserialize, Default)]
+struct Manifest {
+    #[serde(default)]
+    profile: HashMap<String, ProfileConfig>,
+}
+
+/// Profil `name` : release ou debug, modifiés par leur section de aegis.toml s'il y en a une,
+/// ou un profil défini seulement dans aegis.toml.
+pub fn profile(name: &str) -> Result<Profile, String> {
+    let mut manifest = match fs::read_to_string(crate::project::MANIFEST) {
+        Ok(content) => toml::from_str::<Manifest>(&content)
+            .map_err(|e| format!("Invalid {}: {}", crate::project::MANIFEST, e))?,
+        Err(_) => Manifest::default(),
+    };
+    let config = manifest.profile.remove(name);
+    let mut profile = match name {
+        "release" => Profile::release(),
+        "debug" => Profile::debug(),
+        _ => {
+            let Some(config) = &config else {
+                return Err(format!("Unknown profile '{}' (use release, debug or a [profile.{}] section in {})", name, name, crate::project::MANIFEST));
+            };
+            let mut profile = match config.inherits.as_deref() {
+                None | Some("release") => Profile::release(),
+                Some("debug") => Profile::debug(),
+                Some(other) => return Err(format!("Profile '{}' inherits from '{}': only release and debug can be inherited", name, other)),
+            };
+            profile.name = name.to_string();
+            profile
+        },
+    };
+    if let Some(config) = config {
+        if config.inherits.is_some() && matches!(name, "release" | "debug") {
+            return Err(format!("Profile '{}' is built in and cannot inherit from another profile", name));
+        }
+        profile.opt_level = config.opt_level.unwrap_or(profile.opt_level);
+        profile.debug_info = config.debug_info.unwrap_or(profile.debug_info);
+        profile.compress = config.compress.unwrap_or(profile.compress);
+        profile.stdlib = config.stdlib.unwrap_or(profile.stdlib);
+    }
+    Ok(profile)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Header {
+    // Chemin du script dans `modules`
+    pub entry: String,
+    pub profile: Profile,
+}
+
+#[derive(Debug)]
+pub struct Bundle {
+    pub header: Header,
+    // Chemin normalisé (comme pour `aegis graph`) -> source, trié pour un fichier reproductible
+    pub modules: BTreeMap<String, String>,
+}
+
+impl Bundle {
+    /// Rassemble `entry` et les modules qu'il importe, résolus comme à l'exécution.
+    pub fn build(entry: &str, profile: Profile) -> Result<Bundle, String> {
+        let graph = ModuleGraph::build(entry)?;
+        let mut modules = BTreeMap::new();
+        for module in &graph.modules {
+            match &module.status {
+                ModuleStatus::Ok => {},
+                ModuleStatus::Missing => return Err(format!("Cannot bundle {}: module '{}' not found", entry, module.path)),
+                ModuleStatus::Invalid(e) => return Err(format!("Cannot bundle {}: {}: {}", entry, module.path, e)),
+            }
+            let source = fs::read_to_string(&module.path).ok()
+                .or_else(|| crate::stdlib::source(&module.path))
+                .ok_or_else(|| format!("Cannot bundle {}: cannot read '{}'", entry, module.path))?;
+            modules.insert(module.path.clone(), source);
+        }
+        if profile.stdlib == Stdlib::All {
+            for name in crate::stdlib::StdLibAsset::iter() {
+                let path = format!("stdlib/{}", name);
+                if let Some(source) = crate::stdlib::source(&path) {
+                    modules.entry(path).or_insert(source);
+                }
+            }
+        }
+        let entry = graph.modules[0].path.clone();
+        Ok(Bundle { header: Header { entry, profile }, modules })
+    }
+
+    /// Source du script principal.
+    pub fn entry_source(&self) -> Result<&str, String> {
+        self.modules.get(&self.header.entry).map(String::as_str)
+            .ok_or_else(|| format!("Invalid bundle: entry '{}' is missing", self.header.entry))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let header = serde_json::to_string(&self.header).map_err(|e| e.to_string())?;
+        let payload = serde_json::to_vec(&self.modules).map_err(|e| e.to_string())?;
+        let mut bytes = format!("{}\n{}\n", MAGIC, header).into_bytes();
+        if self.header.profile.compress {
+            let mut encoder = GzEncoder::new(bytes, Compression::best());
+            encoder.write_all(&payload).map_err(|e| e.to_string())?;
+            bytes = encoder.finish().map_err(|e| e.to_string())?;
+        } else {
+            bytes.extend(payload);
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Bundle, String> {
+        let invalid = || "Invalid bundle: not an Aegis bundle (create one with 'aegis bundle')".to_string();
+        let mut lines = bytes.splitn(3, |&b| b == b'\n');
+        if lines.next() != Some(MAGIC.as_bytes()) {
+            return Err(invalid());
+        }
+        let header: Header = serde_json::from_slice(lines.next().ok_or_else(invalid)?)
+            .map_err(|e| format!("Invalid bundle header: {}", e))?;
+        let payload = lines.next().ok_or_else(invalid)?;
+        let modules = if header.profile.compress {
+            let mut text = Vec::new();
+            GzDecoder::new(payload).read_to_end(&mut text)
+                .map_err(|e| format!("Invalid bundle payload: {}", e))?;
+            serde_json::from_slice(&text)
+        } else {
+            serde_json::from_slice(payload)
+        }.map_err(|e| format!("Invalid bundle payload: {}", e))?;
+        Ok(Bundle { header, modules })
+    }
+
+    pub fn write(&self, path: &str) -> Result<usize, String> {
+        let bytes = self.to_bytes()?;
+        fs::write(path, &bytes).map_err(|e| format!("Impossible d'écrire {}: {}", path, e))?;
+        Ok(bytes.len())
+    }
+
+    pub fn read(path: &str) -> Result<Bundle, String> {
+        let bytes = fs::read(path).map_err(|e| format!("Impossible de lire {}: {}", path, e))?;
+        Bundle::from_bytes(&bytes)
+    }
+}
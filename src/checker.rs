@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use crate::ast::{Expression, Instruction, Statement, Value};
+
+/// Remarque produite par `aegis check` (n'empêche pas l'exécution du script).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+// Liaisons d'un nom dans le fichier (affectations, paramètres, fonctions, classes...)
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    line: usize,        // Première liaison
+    count: usize,       // Nombre total de liaisons, tous scopes confondus
+    is_variable: bool,  // La première liaison est une affectation (var/let mut/x = ...)
+}
+
+// État du parcours d'un fichier
+#[derive(Default)]
+struct Scan {
+    bindings: HashMap<String, Binding>,
+    // Ligne de l'instruction en cours (les expressions n'en portent pas)
+    line: usize,
+    // Lignes des `+` entre une chaîne et une valeur qui n'en est sûrement pas une
+    mixed_adds: Vec<usize>,
+}
+
+/// Analyse statique d'un fichier déjà chargé.
+pub fn check(statements: &[Statement]) -> Vec<Diagnostic> {
+    let mut scan = Scan::default();
+    visit_block(statements, &mut scan);
+
+    // L'AST ne distingue pas `var x = ...` de `x = ...` : la première affectation
+    // d'un nom fait office de déclaration, les suivantes sont des réaffectations.
+    let mut diagnostics: Vec<Diagnostic> = scan.bindings.into_iter()
+        .filter(|(name, b)| b.is_variable && b.count == 1 && !name.starts_with("__"))
+        .map(|(name, b)| Diagnostic {
+            line: b.line,
+            message: format!("'{}' is never reassigned, consider declaring it with `let`", name),
+        })
+        .collect();
+    diagnostics.extend(scan.mixed_adds.into_iter().map(|line| Diagnostic {
+        line,
+        message: "'+' mixes a string and a non-string value, which is converted to text (use str(x) to make it explicit)".to_string(),
+    }));
+
+    diagnostics.sort_by(|a, b| a.line.cmp(&b.line).then(a.message.cmp(&b.message)));
+    diagnostics
+}
+
+/// Nombre de fois où chaque nom est lié dans le fichier (utilisé par l'optimiseur :
+/// un nom lié une seule fois ne peut pas être masqué ni réaffecté).
+pub fn binding_counts(statements: &[Statement]) -> HashMap<String, usize> {
+    let mut scan = Scan::default();
+    visit_block(statements, &mut scan);
+    scan.bindings.into_iter().map(|(name, b)| (name, b.count)).collect()
+}
+
+fn record(scan: &mut Scan, name: &str, line: usize) {
+    bind(scan, name, line, true);
+}
+
+fn bind(scan: &mut Scan, name: &str, line: usize, is_variable: bool) {
+    scan.bindings.entry(name.to_string())
+        .or_insert(Binding { line, count: 0, is_variable })
+        .count += 1;
+}
+
+fn bind_params(scan: &mut Scan, params: &[(String, Option<String>)], line: usize) {
+    for (param, _) in params {
+        bind(scan, param, line, false);
+    }
+}
+
+fn visit_block(statements: &[Statement], scan: &mut Scan) {
+    for stmt in statements {
+        visit_statement(stmt, scan);
+    }
+}
+
+fn visit_statement(stmt: &Statement, scan: &mut Scan) {
+    scan.line = stmt.line;
+    match &stmt.kind {
+        Instruction::Set(name, _, expr) | Instruction::Assign(name, expr) => {
+            record(scan, name, stmt.line);
+            visit_expression(expr, scan);
+        },
+        Instruction::Input(name, prompt) => {
+            // input() réaffecte toujours la variable
+            record(scan, name, stmt.line);
+            record(scan, name, stmt.line);
+            visit_expression(prompt, scan);
+        },
+        Instruction::Const(name, _, expr) => {
+            bind(scan, name, stmt.line, false);
+            visit_expression(expr, scan);
+        },
+        Instruction::Print(expr) | Instruction::Return(expr) | Instruction::ExpressionStatement(expr)
+        | Instruction::Throw(expr) | Instruction::DefaultParam(_, expr) => visit_expression(expr, scan),
+        Instruction::SetAttr(obj, _, value) | Instruction::CompoundSetAttr(obj, _, _, value) => {
+            visit_expression(obj, scan);
+            visit_expression(value, scan);
+        },
+        Instruction::SetIndex(obj, index, value) | Instruction::CompoundSetIndex(obj, index, _, value) => {
+            visit_expression(obj, scan);
+            visit_expression(index, scan);
+            visit_expression(value, scan);
+        },
+        Instruction::If { condition, body, else_body } => {
+            visit_expression(condition, scan);
+            visit_block(body, scan);
+            visit_block(else_body, scan);
+        },
+        Instruction::While { condition, body } => {
+            visit_expression(condition, scan);
+            visit_block(body, scan);
+        },
+        Instruction::Defer(body) | Instruction::Group(body) => visit_block(body, scan),
+        Instruction::With { resource, name, body } => {
+            bind(scan, name, stmt.line, false);
+            visit_expression(resource, scan);
+            visit_block(body, scan);
+        },
+        Instruction::ForEach(var, iterable, body) => {
+            bind(scan, var, stmt.line, false);
+            visit_expression(iterable, scan);
+            visit_block(body, scan);
+        },
+        Instruction::Function { name, params, body, .. } => {
+            bind(scan, name, stmt.line, false);
+            bind_params(scan, params, stmt.line);
+            visit_block(body, scan);
+        },
+        Instruction::Namespace { name, body } => {
+            bind(scan, name, stmt.line, false);
+            visit_block(body, scan);
+        },
+        Instruction::TryCatch { try_body, catches } => {
+            visit_block(try_body, scan);
+            for (error_var, _, catch_body) in catches {
+                bind(scan, error_var, stmt.line, false);
+                visit_block(catch_body, scan);
+            }
+        },
+        Instruction::Switch { value, cases, default } => {
+            visit_expression(value, scan);
+            for (case, body) in cases {
+                visit_expression(case, scan);
+                visit_block(body, scan);
+            }
+            visit_block(default, scan);
+        },
+        Instruction::Class(def) => {
+            bind(scan, &def.name, stmt.line, false);
+            for (params, body, _, _) in def.methods.values() {
+                bind_params(scan, params, stmt.line);
+                visit_block(body, scan);
+            }
+            for field in &def.fields {
+                visit_expression(&field.default_value, scan);
+            }
+            for prop in &def.properties {
+                for (params, body) in prop.getter.iter().chain(prop.setter.iter()) {
+                    bind_params(scan, params, stmt.line);
+                    visit_block(body, scan);
+                }
+            }
+        },
+        Instruction::Private(inner) | Instruction::Export(inner) => {
+            let inner = Statement { kind: (**inner).clone(), line: stmt.line };
+            visit_statement(&inner, scan);
+        },
+        Instruction::Enum(name, _) => bind(scan, name, stmt.line, false),
+        Instruction::Interface(def) => bind(scan, &def.name, stmt.line, false),
+        Instruction::Import(_, alias) => {
+            if let Some(alias) = alias {
+                bind(scan, alias, stmt.line, false);
+            }
+        },
+        Instruction::FromImport(_, names) => {
+            for (_, alias) in names {
+                bind(scan, alias, stmt.line, false);
+            }
+        },
+        Instruction::Break | Instruction::Continue | Instruction::TypeAlias(..) | Instruction::Use(_)
+        | Instruction::RestParam(_) => {},
+    }
+}
+
+// On descend dans les expressions pour trouver les lambdas et les `+` douteux
+fn visit_expression(expr: &Expression, scan: &mut Scan) {
+    if let Expression::Add(a, b) = expr
+        && matches!((text_kind(a), text_kind(b)), (Some(true), Some(false)) | (Some(false), Some(true))) {
+        scan.mixed_adds.push(scan.line);
+    }
+
+    match expr {
+        Expression::Function { params, body, .. } => {
+            bind_params(scan, params, 0);
+            visit_block(body, scan);
+        },
+
+        Expression::Add(a, b) | Expression::Sub(a, b) | Expression::Mul(a, b) | Expression::Div(a, b)
+        | Expression::Modulo(a, b) | Expression::FloorDiv(a, b) | Expression::Equal(a, b) | Expression::NotEqual(a, b)
+        | Expression::Same(a, b) | Expression::NotSame(a, b)
+        | Expression::LessThan(a, b) | Expression::GreaterThan(a, b) | Expression::LessEqual(a, b)
+        | Expression::GreaterEqual(a, b) | Expression::And(a, b) | Expression::Or(a, b)
+        | Expression::NullCoalescing(a, b) | Expression::BitAnd(a, b) | Expression::BitOr(a, b)
+        | Expression::BitXor(a, b) | Expression::ShiftLeft(a, b) | Expression::ShiftRight(a, b)
+        | Expression::Range(a, b) | Expression::GetIndex(a, b) => {
+            visit_expression(a, scan);
+            visit_expression(b, scan);
+        },
+        Expression::Ternary(c, a, b) => {
+            visit_expression(c, scan);
+            visit_expression(a, scan);
+            visit_expression(b, scan);
+        },
+        Expression::Not(e) | Expression::Await(e) | Expression::GetAttr(e, _) => visit_expression(e, scan),
+        Expression::Call(callee, args) | Expression::New(callee, args) | Expression::CallMethod(callee, _, args) => {
+            visit_expression(callee, scan);
+            for arg in args {
+                visit_expression(arg, scan);
+            }
+        },
+        Expression::List(items) | Expression::SuperCall(_, items) | Expression::Interpolation(items) => {
+            for item in items {
+                visit_expression(item, scan);
+            }
+        },
+        Expression::Dict(entries) => {
+            for (_, value) in entries {
+                visit_expression(value, scan);
+            }
+        },
+        Expression::Literal(_) | Expression::Variable(_) => {},
+    }
+}
+
+// Some(true) : l'expression donne sûrement une chaîne, Some(false) : sûrement autre chose,
+// None : on ne sait pas sans exécuter (variable, appel, attribut...)
+fn text_kind(expr: &Expression) -> Option<bool> {
+    match expr {
+        Expression::Literal(Value::String(_)) | Expression::Interpolation(_) => Some(true),
+        Expression::Literal(Value::Integer(_) | Value::Float(_) | Value::Boolean(_) | Value::Null) => Some(false),
+        Expression::Call(callee, _) => match callee.as_ref() {
+            Expression::Variable(name) if matches!(name.as_str(), "str" | "to_str" | "fmt") => Some(true),
+            _ => None,
+        },
+        Expression::Add(a, b) => match (text_kind(a), text_kind(b)) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        },
+        Expression::Sub(..) | Expression::Mul(..) | Expression::Div(..) | Expression::Modulo(..) | Expression::FloorDiv(..)
+        | Expression::BitAnd(..) | Expression::BitOr(..) | Expression::BitXor(..)
+        | Expression::ShiftLeft(..) | Expression::ShiftRight(..)
+        | Expression::Equal(..) | Expression::NotEqual(..) | Expression::Same(..) | Expression::NotSame(..)
+        | Expression::LessThan(..) | Expression::GreaterThan(..) | Expression::LessEqual(..)
+        | Expression::GreaterEqual(..) | Expression::Not(_)
+        | Expression::List(_) | Expression::Dict(_) | Expression::Range(..) => Some(false),
+        _ => None,
+    }
+}
@@ -1,22 +1,85 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::rc::{Rc, Weak};
 
-use crate::ast::Value;
+use crate::ast::{Str, Value};
+use crate::ast::value::ClassData;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Chunk {
+    // Nom de la fonction compilée ("Maths.square", "User.init", "<lambda>"...), "<script>" au niveau fichier
+    pub name: String,
     pub code: Vec<u8>,
     pub constants: Vec<Value>,
-    pub locals_map: HashMap<u8, String>,
+    pub locals_map: HashMap<u16, String>,
+    // Locales pour le débogueur : (offset à partir duquel elle existe, slot, nom). Un slot
+    // réutilisé par un autre bloc a plusieurs entrées : la dernière avant l'ip courant l'emporte
+    pub local_names: Vec<(usize, u16, String)>,
     pub lines: Vec<usize>,
+    // Notes de l'optimiseur (offset -> texte), affichées par le désassembleur
+    pub annotations: HashMap<usize, String>,
+    // Locales visibles à chaque MakeClosure (offset -> (slot, nom)), y compris celles d'un bloc
+    // déjà refermé à la fin de la fonction (variable de foreach, locale d'un if...)
+    pub captures: HashMap<usize, Vec<(u16, String)>>,
+    // Variables des fonctions englobantes utilisées par cette fonction ou ses closures
+    // (GetFreeVar, SetFreeVar) : MakeClosure ne capture que celles-là
+    pub free_vars: Vec<String>,
+    // Cache en ligne de GetAttr/SetAttr, rempli à l'exécution
+    pub attr_cache: AttrCache,
+    // Fonction `async` : l'appeler crée une tâche au lieu d'exécuter le corps
+    pub is_async: bool,
+    // Compilé en mode strict (--strict ou `use strict`) : `+` entre une chaîne et une autre
+    // valeur est une erreur au lieu d'une conversion implicite
+    pub strict: bool,
+    // Fichier source (script ou module importé), None pour le REPL et le code compilé en mémoire
+    pub file: Option<Rc<str>>,
+    // Index des constantes simples déjà dans la table, pour que add_constant les retrouve
+    // sans la parcourir (un fichier généré peut compter des dizaines de milliers de littéraux)
+    constant_slots: HashMap<ConstantKey, u16>,
+}
+
+// Constante réutilisable : les flottants par leurs bits (0.0 et -0.0 restent distincts, NaN se
+// retrouve lui-même)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ConstantKey {
+    Integer(i64),
+    Float(u64),
+    String(Str),
+    Boolean(bool),
+    Null,
+}
+
+impl ConstantKey {
+    fn of(value: &Value) -> Option<ConstantKey> {
+        Some(match value {
+            Value::Integer(i) => ConstantKey::Integer(*i),
+            Value::Float(f) => ConstantKey::Float(f.to_bits()),
+            Value::String(s) => ConstantKey::String(s.clone()),
+            Value::Boolean(b) => ConstantKey::Boolean(*b),
+            Value::Null => ConstantKey::Null,
+            _ => return None,
+        })
+    }
 }
 
 impl Chunk {
     pub fn new() -> Self {
         Chunk {
+            name: "<script>".to_string(),
             code: Vec::new(),
             constants: Vec::new(),
             locals_map: HashMap::new(),
+            local_names: Vec::new(),
             lines: Vec::new(),
+            annotations: HashMap::new(),
+            captures: HashMap::new(),
+            free_vars: Vec::new(),
+            attr_cache: AttrCache::default(),
+            is_async: false,
+            strict: false,
+            file: None,
+            constant_slots: HashMap::new(),
         }
     }
 
@@ -25,8 +88,112 @@ impl Chunk {
         self.lines.push(line);
     }
 
-    pub fn add_constant(&mut self, value: Value) -> u8 {
+    /// Les valeurs simples déjà présentes sont réutilisées : la table est limitée à 65536 entrées
+    /// (opérande u16), un fichier généré répétant le même littéral ne doit pas la remplir.
+    /// Les chaînes sont internées : LoadConst les partage au lieu de les recopier, et elles se
+    /// comparent par pointeur aux noms de champs, clés de dict et autres littéraux.
+    pub fn add_constant(&mut self, mut value: Value) -> u16 {
+        if let Value::String(s) = &mut value {
+            *s = std::mem::take(s).interned();
+        }
+        let key = ConstantKey::of(&value);
+        if let Some(idx) = key.as_ref().and_then(|key| self.constant_slots.get(key)) {
+            return *idx;
+        }
+
+        let idx = self.constants.len() as u16;
         self.constants.push(value);
-        (self.constants.len() - 1) as u8
+        if let Some(key) = key {
+            self.constant_slots.insert(key, idx);
+        }
+        idx
     }
-}
\ No newline at end of file
+
+    /// Ligne dont le code commence à l'offset `ip`, None au milieu d'une ligne. Une boucle qui
+    /// revient au début de sa condition repasse par ce point.
+    pub fn line_start(&self, ip: usize) -> Option<usize> {
+        let line = *self.lines.get(ip)?;
+        (line > 0 && (ip == 0 || self.lines[ip - 1] != line)).then_some(line)
+    }
+
+    /// Ligne -> offsets où commence son code dans ce chunk (une ligne interrompue par le code
+    /// d'une autre en a plusieurs ; une ligne sans code n'y figure pas).
+    pub fn line_table(&self) -> BTreeMap<usize, Vec<usize>> {
+        let mut table: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for ip in 0..self.code.len() {
+            if let Some(line) = self.line_start(ip) {
+                table.entry(line).or_default().push(ip);
+            }
+        }
+        table
+    }
+
+    /// Chunks des fonctions, lambdas et méthodes définies dans ce chunk, récursivement.
+    pub fn nested_chunks(&self) -> Vec<&Chunk> {
+        let mut chunks = Vec::new();
+        for constant in &self.constants {
+            let functions: Vec<&Value> = match constant {
+                Value::Function(_) => vec![constant],
+                Value::Class(class) => class.methods.values()
+                    .chain(class.static_methods.values())
+                    .chain(class.properties.values().chain(class.static_properties.values())
+                        .flat_map(|property| property.getter.iter().chain(property.setter.iter())))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            for function in functions {
+                if let Value::Function(function) = function {
+                    chunks.push(&function.chunk);
+                    chunks.extend(function.chunk.nested_chunks());
+                }
+            }
+        }
+        chunks
+    }
+}
+/// Cache en ligne des accès aux champs : pour chaque constante de nom du chunk, la dernière
+/// classe rencontrée et le slot du champ chez elle. Un accès qui retombe sur la même classe
+/// lit directement `InstanceData::slots` sans vérification de visibilité ni recherche de
+/// propriété (seuls les champs publics sans propriété homonyme y entrent).
+/// La référence faible garde l'adresse de la classe réservée : pas de faux positif si elle est libérée.
+#[derive(Default)]
+pub struct AttrCache(RefCell<Vec<Option<CachedSlot>>>);
+
+type CachedSlot = (Weak<ClassData>, usize);
+
+impl AttrCache {
+    pub fn get(&self, name_idx: u16, class: &Rc<ClassData>) -> Option<usize> {
+        match self.0.borrow().get(name_idx as usize) {
+            Some(Some((cached, slot))) if std::ptr::eq(cached.as_ptr(), Rc::as_ptr(class)) => Some(*slot),
+            _ => None,
+        }
+    }
+
+    pub fn set(&self, name_idx: u16, class: &Rc<ClassData>, slot: usize) {
+        let mut entries = self.0.borrow_mut();
+        let idx = name_idx as usize;
+        if entries.len() <= idx {
+            entries.resize(idx + 1, None);
+        }
+        entries[idx] = Some((Rc::downgrade(class), slot));
+    }
+}
+
+// Le cache n'est qu'un état d'exécution : il ne participe ni à l'égalité ni aux copies
+impl Clone for AttrCache {
+    fn clone(&self) -> Self {
+        AttrCache::default()
+    }
+}
+
+impl PartialEq for AttrCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for AttrCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AttrCache")
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::fmt;
 use std::iter::Peekable;
 use std::str::Chars;
 
@@ -5,15 +7,19 @@ use std::str::Chars;
 pub enum TokenKind {
     True, False, Null,
     Try, Catch, Throw,
-    Var, If, Else, While, Func, Return, Print, Input, 
+    Var, Let, If, Else, While, Func, Return, Print, Input, 
     Class, New, Extends, Enum,
     Import, Break, Continue, Switch, Case, Default,
     Identifier(String), StringLiteral(String), Integer(i64), Float(f64),
     Plus, Minus, Star, Slash, Percent,
-    Eq, EqEq, Neq, Lt, Gt, LtEq, GtEq,
+    // `~/` : division arrondie vers -inf (`//` commence un commentaire)
+    TildeSlash,
+    Eq, EqEq, Neq, EqEqEq, NeqEq, Lt, Gt, LtEq, GtEq,
     And, Or, Bang,
     LParen, RParen, LBrace, RBrace, LBracket, RBracket,
     Comma, Dot, Colon, EOF,
+    // Sépare deux instructions, comme un retour à la ligne
+    Semicolon,
     PlusEq,   // +=
     MinusEq,  // -=
     StarEq,   // *=
@@ -30,6 +36,8 @@ pub enum TokenKind {
     Const,
     ForEach, In,
     DotDot,
+    // `...rest` dans une liste de paramètres
+    Ellipsis,
     Public, Protected, Private,
     Static,
     Final,
@@ -43,41 +51,139 @@ pub struct Token {
     pub line: usize
 }
 
-pub struct Lexer<'a> {
+/// Erreur de lexing, positionnée sur le début du token fautif (ligne et colonne à partir de 1).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Lexer error: {} at line {}, column {}", self.message, self.line, self.column)
+    }
+}
+
+impl std::error::Error for LexError {}
+
+// Le parser et `compiler::compile` remontent des String
+impl From<LexError> for String {
+    fn from(e: LexError) -> String {
+        e.to_string()
+    }
+}
+
+// Itérateur de caractères qui connaît la colonne courante (la ligne reste gérée par le Lexer)
+#[derive(Clone)]
+struct Cursor<'a> {
     chars: Peekable<Chars<'a>>,
-    line: usize
+    offset: usize,      // Nombre de caractères consommés
+    line_start: usize,  // Offset du premier caractère de la ligne courante
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.offset += 1;
+        if c == '\n' {
+            self.line_start = self.offset;
+        }
+        Some(c)
+    }
+
+    fn column(&self) -> usize {
+        self.offset - self.line_start + 1
+    }
+}
+
+// (ligne, colonne) du début d'un token
+type Position = (usize, usize);
+
+pub struct Lexer<'a> {
+    chars: Cursor<'a>,
+    line: usize,
+    // Blocs de commentaires `///` lus : (première ligne, dernière ligne, texte)
+    doc_blocks: Vec<(usize, usize, String)>,
+    /// Après `tokenize` : texte des `///` par ligne de la déclaration qu'ils précèdent
+    /// immédiatement (ligne suivante).
+    pub docs: HashMap<usize, String>,
+    /// Bloc `///` en tête de fichier séparé de la suite par une ligne vide : doc du module.
+    pub module_doc: Option<String>,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
         Lexer { 
-            chars: input.chars().peekable(),
-            line: 1 
+            chars: Cursor { chars: input.chars().peekable(), offset: 0, line_start: 0 },
+            line: 1,
+            doc_blocks: Vec::new(),
+            docs: HashMap::new(),
+            module_doc: None,
         }
     }
 
+    fn position(&self) -> Position {
+        (self.line, self.chars.column())
+    }
+
+    fn error(&self, message: String, (line, column): Position) -> LexError {
+        LexError { message, line, column }
+    }
+
     fn add_token(&self, tokens: &mut Vec<Token>, kind: TokenKind) {
         tokens.push(Token { kind, line: self.line });
     }
 
-    pub fn tokenize(&mut self) -> Vec<Token> {
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, LexError> {
         self.handle_shebang();
 
         let mut tokens = Vec::new();
         while self.chars.peek().is_some() {
             // On utilise scan_token pour lire le prochain élément
-            if let Err(e) = self.scan_token(&mut tokens) {
-                // En cas d'erreur (ex: string non fermée), on panic pour l'instant
-                // Idéalement, il faudrait retourner un Result<Vec<Token>, String>
-                panic!("Lexer error: {}", e);
-            }
+            // Une erreur (ex: string non fermée) remonte à l'appelant au lieu de faire planter le CLI
+            self.scan_token(&mut tokens)?;
         }
         self.add_token(&mut tokens, TokenKind::EOF);
-        tokens
+        self.attach_docs(&tokens);
+        Ok(tokens)
+    }
+
+    // `/// texte` (mais pas `////`) : ligne de documentation, regroupée avec les précédentes
+    // si elles se suivent
+    fn push_doc_line(&mut self, text: &str) {
+        let text = text.strip_prefix(' ').unwrap_or(text).trim_end();
+        match self.doc_blocks.last_mut() {
+            Some((_, last, block)) if *last + 1 == self.line => {
+                block.push('\n');
+                block.push_str(text);
+                *last = self.line;
+            },
+            _ => self.doc_blocks.push((self.line, self.line, text.to_string())),
+        }
+    }
+
+    // Un bloc documente le token qui commence la ligne suivante ; celui qui ouvre le fichier,
+    // suivi d'une ligne vide, documente le module
+    fn attach_docs(&mut self, tokens: &[Token]) {
+        let first_line = tokens.first().map(|t| t.line).unwrap_or(usize::MAX);
+        for (first, last, text) in std::mem::take(&mut self.doc_blocks) {
+            let next = tokens.iter().find(|t| t.line > last && t.kind != TokenKind::EOF);
+            match next {
+                Some(token) if token.line == last + 1 => { self.docs.insert(token.line, text); },
+                _ if first < first_line && self.module_doc.is_none() => self.module_doc = Some(text),
+                _ => {},
+            }
+        }
     }
 
     // Extrait la logique de lecture d'un token unique pour pouvoir la réutiliser
-    fn scan_token(&mut self, tokens: &mut Vec<Token>) -> Result<(), String> {
+    fn scan_token(&mut self, tokens: &mut Vec<Token>) -> Result<(), LexError> {
+        let start = self.position();
         if let Some(&c) = self.chars.peek() {
             match c {
                 '\n' => {
@@ -88,14 +194,19 @@ impl<'a> Lexer<'a> {
                 '/' => {
                     self.chars.next();
                     if let Some(&'/') = self.chars.peek() {
+                        let mut comment = String::new();
                         while let Some(&c) = self.chars.peek() {
                             if c == '\n' { break; }
+                            comment.push(c);
                             self.chars.next();
                         }
+                        if let Some(doc) = comment.strip_prefix("//").filter(|rest| !rest.starts_with('/')) {
+                            self.push_doc_line(doc);
+                        }
                     }
                     else if let Some(&'*') = self.chars.peek() {
                         self.chars.next(); // Consomme '*'
-                        self.skip_multiline_comment()?;
+                        self.skip_multiline_comment(start)?;
                     }
                     else if let Some(&'=') = self.chars.peek() {
                         self.chars.next();
@@ -137,7 +248,12 @@ impl<'a> Lexer<'a> {
                     self.chars.next();
                     if let Some(&'.') = self.chars.peek() {
                         self.chars.next();
-                        self.add_token(tokens, TokenKind::DotDot);
+                        if let Some(&'.') = self.chars.peek() {
+                            self.chars.next();
+                            self.add_token(tokens, TokenKind::Ellipsis);
+                        } else {
+                            self.add_token(tokens, TokenKind::DotDot);
+                        }
                     }
                     else {
                         self.add_token(tokens, TokenKind::Dot);
@@ -147,6 +263,10 @@ impl<'a> Lexer<'a> {
                     self.add_token(tokens, TokenKind::Colon);
                     self.chars.next(); 
                 }
+                ';' => {
+                    self.add_token(tokens, TokenKind::Semicolon);
+                    self.chars.next();
+                }
                 '?' => {
                     self.chars.next();
                     if let Some(&'?') = self.chars.peek() {
@@ -207,7 +327,12 @@ impl<'a> Lexer<'a> {
                     self.chars.next();
                     if let Some(&'=') = self.chars.peek() { 
                         self.chars.next(); 
-                        self.add_token(tokens, TokenKind::EqEq);
+                        if let Some(&'=') = self.chars.peek() {
+                            self.chars.next();
+                            self.add_token(tokens, TokenKind::EqEqEq);
+                        } else {
+                            self.add_token(tokens, TokenKind::EqEq);
+                        }
                     } 
                     else { 
                         self.add_token(tokens, TokenKind::Eq);
@@ -265,11 +390,26 @@ impl<'a> Lexer<'a> {
                     self.chars.next();
                     self.add_token(tokens, TokenKind::BitXor);
                 },
+                '~' => {
+                    self.chars.next();
+                    if let Some(&'/') = self.chars.peek() {
+                        self.chars.next();
+                        self.add_token(tokens, TokenKind::TildeSlash);
+                    }
+                    else {
+                        return Err(self.error("Unexpected char '~'".to_string(), start));
+                    }
+                },
                 '!' => {
                     self.chars.next();
                     if let Some(&'=') = self.chars.peek() { 
                         self.chars.next(); 
-                        self.add_token(tokens, TokenKind::Neq);
+                        if let Some(&'=') = self.chars.peek() {
+                            self.chars.next();
+                            self.add_token(tokens, TokenKind::NeqEq);
+                        } else {
+                            self.add_token(tokens, TokenKind::Neq);
+                        }
                     }
                     else { 
                         self.add_token(tokens, TokenKind::Bang);
@@ -280,28 +420,29 @@ impl<'a> Lexer<'a> {
                     self.add_token(tokens, TokenKind::At);
                 }
                 '"' => {
-                    let token = self.read_string();
+                    let token = self.read_string()?;
                     tokens.push(token);
                 },
                 '`' => {
                     self.chars.next(); // On consomme le backtick d'ouverture
-                    self.read_multiline_string(tokens)?;
+                    self.read_multiline_string(tokens, start)?;
                 },
                 c if c.is_digit(10) => {
-                    let token = self.read_number();
+                    let token = self.read_number()?;
                     tokens.push(token);
                 },
                 c if c.is_alphabetic() || c == '_' => {
                     let token = self.read_identifier();
                     tokens.push(token);
                 },
-                _ => return Err(format!("Unexpected char '{}' at line {}", c, self.line)),
+                _ => return Err(self.error(format!("Unexpected char '{}'", c), start)),
             }
         }
         Ok(())
     }
 
-    fn read_string(&mut self) -> Token {
+    fn read_string(&mut self) -> Result<Token, LexError> {
+        let start = self.position();
         self.chars.next(); // On consomme le guillemet ouvrant "
         let mut s = String::new();
         
@@ -309,10 +450,10 @@ impl<'a> Lexer<'a> {
             match c {
                 '"' => { 
                     self.chars.next(); // On consomme le guillemet fermant "
-                    return Token {
+                    return Ok(Token {
                         kind: TokenKind::StringLiteral(s), 
                         line: self.line
-                    };
+                    });
                 },
                 '\\' => {
                     self.chars.next(); // On consomme le \
@@ -332,10 +473,11 @@ impl<'a> Lexer<'a> {
                 }
             }
         }
-        panic!("Unterminated string at line {}", self.line);
+        Err(self.error("Unterminated string".to_string(), start))
     }
 
-    fn read_number(&mut self) -> Token {
+    fn read_number(&mut self) -> Result<Token, LexError> {
+        let start = self.position();
         let mut s = String::new();
         let mut has_dot = false;
         while let Some(&c) = self.chars.peek() {
@@ -360,14 +502,43 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        let kind = if has_dot { 
-            TokenKind::Float(s.parse().unwrap_or(0.0))
+        // Exposant (1e9, 2.5e-3, 6E+23) : seulement si des chiffres suivent, sinon le 'e'
+        // appartient au token suivant
+        let mut has_exponent = false;
+        if let Some('e' | 'E') = self.chars.peek() {
+            let mut lookahead = self.chars.clone();
+            lookahead.next();
+            let sign = lookahead.peek().copied().filter(|c| *c == '+' || *c == '-');
+            if sign.is_some() {
+                lookahead.next();
+            }
+            if lookahead.peek().is_some_and(|c| c.is_ascii_digit()) {
+                has_exponent = true;
+                s.push(self.chars.next().unwrap());
+                if let Some(sign) = sign {
+                    s.push(sign);
+                    self.chars.next();
+                }
+                while let Some(&c) = self.chars.peek() {
+                    if !c.is_ascii_digit() { break; }
+                    s.push(self.chars.next().unwrap());
+                }
+            }
+        }
+
+        // Un entier trop grand pour un i64 était lu silencieusement comme 0
+        let kind = if has_dot || has_exponent { 
+            let value: f64 = s.parse().map_err(|_| self.error(format!("Invalid number '{}'", s), start))?;
+            if value.is_infinite() {
+                return Err(self.error(format!("Float literal '{}' is too large", s), start));
+            }
+            TokenKind::Float(value)
         } 
         else {
-            TokenKind::Integer(s.parse().unwrap_or(0))
+            TokenKind::Integer(s.parse().map_err(|_| self.error(format!("Integer literal '{}' is too large", s), start))?)
         };
 
-        Token { kind, line: self.line }
+        Ok(Token { kind, line: self.line })
     }
 
     fn read_identifier(&mut self) -> Token {
@@ -384,6 +555,7 @@ impl<'a> Lexer<'a> {
 
         let kind = match s.as_str() {
             "var" => TokenKind::Var, 
+            "let" => TokenKind::Let,
             "if" => TokenKind::If, 
             "else" => TokenKind::Else, 
             "while" => TokenKind::While,
@@ -440,7 +612,7 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn skip_multiline_comment(&mut self) -> Result<(), String> {
+    fn skip_multiline_comment(&mut self, start: Position) -> Result<(), LexError> {
         while let Some(c) = self.chars.next() {
             if c == '*' {
                 if let Some('/') = self.chars.peek() {
@@ -452,10 +624,10 @@ impl<'a> Lexer<'a> {
             }
         }
         
-        Err(format!("Unterminated block comment at line {}", self.line))
+        Err(self.error("Unterminated block comment".to_string(), start))
     }
 
-    fn read_multiline_string(&mut self, tokens: &mut Vec<Token>) -> Result<(), String> {
+    fn read_multiline_string(&mut self, tokens: &mut Vec<Token>, start: Position) -> Result<(), LexError> {
         let mut string_content = String::new();
         
         while let Some(&c) = self.chars.peek() {
@@ -484,7 +656,7 @@ impl<'a> Lexer<'a> {
                         self.add_token(tokens, TokenKind::Plus);
                         
                         // 3. On lit l'expression intérieure
-                        self.read_interpolated_expression(tokens)?;
+                        self.read_interpolated_expression(tokens, start)?;
                         
                         // 4. Au retour, on ajoute un autre '+'
                         self.add_token(tokens, TokenKind::Plus);
@@ -512,16 +684,16 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        Err(format!("Unterminated string literal starting at line {}", self.line))
+        Err(self.error("Unterminated string literal".to_string(), start))
     }
 
     // NOUVELLE MÉTHODE : Lit une expression à l'intérieur de ${...}
-    fn read_interpolated_expression(&mut self, tokens: &mut Vec<Token>) -> Result<(), String> {
+    fn read_interpolated_expression(&mut self, tokens: &mut Vec<Token>, start: Position) -> Result<(), LexError> {
         let mut balance = 1; // On a déjà consommé le '{' ouvrant
 
         while balance > 0 {
             if self.chars.peek().is_none() {
-                return Err("Unclosed string interpolation".to_string());
+                return Err(self.error("Unclosed string interpolation".to_string(), start));
             }
 
             // Gestion manuelle des accolades pour l'imbrication
@@ -7,7 +7,7 @@ use parser::Parser;
 
 pub fn compile(source: &str) -> Result<JsonValue, String> {
     let mut lexer = Lexer::new(source);
-    let tokens = lexer.tokenize();
-    let mut parser = Parser::new(tokens);
+    let tokens = lexer.tokenize()?;
+    let mut parser = Parser::new(tokens).with_docs(lexer.docs, lexer.module_doc);
     parser.parse()
 }
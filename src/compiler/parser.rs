@@ -1,22 +1,51 @@
 use super::lexer::{ Token, TokenKind };
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use crate::ast::value::{constructor_key, is_constructor, CONSTRUCTOR};
 
 pub struct Parser {
     tokens: Vec<Token>,
     pos: usize,
+    // Commentaires `///` (Lexer::docs, Lexer::module_doc)
+    docs: HashMap<usize, String>,
+    module_doc: Option<String>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, pos: 0 }
+        Parser { tokens, pos: 0, docs: HashMap::new(), module_doc: None }
+    }
+
+    /// Docstrings tirées des commentaires `///` : elles deviennent la chaîne littérale qui ouvre
+    /// le corps documenté (fonction, méthode) ou le fichier, comme une docstring écrite à la main.
+    pub fn with_docs(mut self, docs: HashMap<usize, String>, module_doc: Option<String>) -> Self {
+        self.docs = docs;
+        self.module_doc = module_doc;
+        self
     }
 
     pub fn parse(&mut self) -> Result<Value, String> {
-        let mut instructions = Vec::new();
+        let mut instructions: Vec<Value> = Vec::new();
+        // Les directives (`use strict`) ne peuvent être précédées que d'une docstring
+        let mut in_header = true;
         while !self.is_at_end() {
-            instructions.push(self.parse_statement()?);
+            if self.match_token(TokenKind::Semicolon) {
+                continue;
+            }
+            if self.is_use_start() && in_header {
+                instructions.push(self.parse_use()?);
+                continue;
+            }
+            let stmt = self.parse_statement()?;
+            in_header = instructions.is_empty() && stmt.get(0).and_then(|k| k.as_str()) == Some("expr")
+                && stmt.get(2).is_some_and(|e| e.is_string());
+            instructions.push(stmt);
+        }
+        let mut program = json!(instructions);
+        if let Some(doc) = self.module_doc.take() {
+            prepend_docstring(&mut program, 1, doc);
         }
-        Ok(json!(instructions))
+        Ok(program)
     }
 
     // --- Helpers ---
@@ -37,6 +66,12 @@ impl Parser {
         }
     }
 
+    // `///` juste au-dessus de la ligne du token `pos` (modificateurs `export`, `async`,
+    // `static`... compris, puisqu'ils sont sur la même ligne)
+    fn doc_at(&self, pos: usize) -> Option<String> {
+        self.docs.get(&self.tokens.get(pos)?.line).cloned()
+    }
+
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.pos += 1;
@@ -44,6 +79,16 @@ impl Parser {
         &self.tokens[self.pos - 1]
     }
 
+    // Nom de membre/fonction : un identifiant, ou un mot-clé autorisé (ex: Regex.new)
+    fn advance_member_name(&mut self) -> Option<String> {
+        match &self.advance().kind {
+            TokenKind::Identifier(n) => Some(n.clone()),
+            TokenKind::New => Some("new".to_string()),
+            TokenKind::Default => Some("default".to_string()),
+            _ => None,
+        }
+    }
+
     fn match_token(&mut self, kind: TokenKind) -> bool {
         if self.check(&kind) {
             self.advance();
@@ -58,6 +103,88 @@ impl Parser {
         std::mem::discriminant(self.peek()) == std::mem::discriminant(kind)
     }
 
+    fn is_type_alias_start(&self) -> bool {
+        matches!(self.tokens.get(self.pos + 1).map(|t| &t.kind), Some(TokenKind::Identifier(_)))
+            && matches!(self.tokens.get(self.pos + 2).map(|t| &t.kind), Some(TokenKind::Eq))
+    }
+
+    // `defer` suivi d'un bloc ou du début d'une instruction (pas de `=`, `(`, `.`... : variable nommée defer)
+    fn is_defer_start(&self) -> bool {
+        matches!(
+            self.tokens.get(self.pos + 1).map(|t| &t.kind),
+            Some(TokenKind::LBrace | TokenKind::Identifier(_) | TokenKind::Print | TokenKind::Super | TokenKind::New
+                | TokenKind::If | TokenKind::While | TokenKind::ForEach | TokenKind::Try | TokenKind::Throw | TokenKind::Switch)
+        )
+    }
+
+    // `with <expression> as <nom> { ... }` : `with` seul, suivi de `=`, `(`, `.`... reste une variable
+    fn is_with_start(&self) -> bool {
+        matches!(
+            self.tokens.get(self.pos + 1).map(|t| &t.kind),
+            Some(TokenKind::Identifier(_) | TokenKind::New | TokenKind::Super
+                | TokenKind::Integer(_) | TokenKind::Float(_) | TokenKind::StringLiteral(_))
+        )
+    }
+
+    // `export func ...` : `export` seul reste une variable
+    fn is_export_start(&self) -> bool {
+        matches!(
+            self.tokens.get(self.pos + 1).map(|t| &t.kind),
+            Some(TokenKind::Func | TokenKind::Var | TokenKind::Let | TokenKind::Const | TokenKind::Class
+                | TokenKind::Final | TokenKind::Enum | TokenKind::Interface | TokenKind::Namespace)
+        )
+    }
+
+    // `from "x.aeg" import ...` : `from` suivi d'un chemin
+    fn is_from_import_start(&self) -> bool {
+        matches!(self.tokens.get(self.pos + 1).map(|t| &t.kind), Some(TokenKind::StringLiteral(_)))
+            && matches!(self.tokens.get(self.pos + 2).map(|t| &t.kind), Some(TokenKind::Import))
+    }
+
+    // `extern "libm.so.6" func ...` : `extern` seul reste une variable
+    fn is_extern_start(&self) -> bool {
+        matches!(self.tokens.get(self.pos + 1).map(|t| &t.kind), Some(TokenKind::StringLiteral(_)))
+            && matches!(self.tokens.get(self.pos + 2).map(|t| &t.kind), Some(TokenKind::Func))
+    }
+
+    // `use strict` : deux identifiants sur la même ligne (`use` seul reste une variable)
+    fn is_use_start(&self) -> bool {
+        let (Some(current), Some(next)) = (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) else {
+            return false;
+        };
+        matches!(&current.kind, TokenKind::Identifier(k) if k == "use")
+            && matches!(next.kind, TokenKind::Identifier(_))
+            && current.line == next.line
+    }
+
+    fn parse_use(&mut self) -> Result<Value, String> {
+        let line = self.current_line();
+        self.advance(); // 'use'
+        let name = match &self.advance().kind {
+            TokenKind::Identifier(name) => name.clone(),
+            _ => unreachable!("is_use_start"),
+        };
+        if name != "strict" {
+            return Err(format!("Unknown directive 'use {}' (only `use strict` exists) (Line {})", name, line));
+        }
+        Ok(json!(["use", line, name]))
+    }
+
+    // `async func ...` : `async` seul reste une variable
+    fn is_async_start(&self) -> bool {
+        matches!(self.tokens.get(self.pos + 1).map(|t| &t.kind), Some(TokenKind::Func))
+    }
+
+    // `await <expression>` : `await` suivi de `=`, `(`, `.`... reste une variable ou un appel
+    fn is_await_start(&self) -> bool {
+        matches!(
+            self.tokens.get(self.pos + 1).map(|t| &t.kind),
+            Some(TokenKind::Identifier(_) | TokenKind::New | TokenKind::Super | TokenKind::LBracket
+                | TokenKind::Integer(_) | TokenKind::Float(_) | TokenKind::StringLiteral(_)
+                | TokenKind::True | TokenKind::False | TokenKind::Null)
+        )
+    }
+
     fn is_at_end(&self) -> bool {
         self.peek() == &TokenKind::EOF
     }
@@ -76,6 +203,7 @@ impl Parser {
         match self.peek() {
             TokenKind::At => self.parse_decorated_function(),
             TokenKind::Var => self.parse_var(),
+            TokenKind::Let => self.parse_let(),
             TokenKind::Print => self.parse_print(),
             TokenKind::If => self.parse_if(),
             TokenKind::While => self.parse_while(),
@@ -102,9 +230,32 @@ impl Parser {
             TokenKind::Const => self.parse_const(),
             TokenKind::ForEach => self.parse_foreach(),
             TokenKind::Interface => self.parse_interface(),
+            TokenKind::Private => self.parse_private_declaration(),
             
+            // 'type' n'est pas un mot-clé réservé : "type Nom = ..." déclare un alias
+            TokenKind::Identifier(k) if k == "type" && self.is_type_alias_start() => self.parse_type_alias(),
+
+            // 'defer' non plus : "defer { ... }" ou "defer <instruction>"
+            TokenKind::Identifier(k) if k == "defer" && self.is_defer_start() => self.parse_defer(),
+            TokenKind::Identifier(k) if k == "with" && self.is_with_start() => self.parse_with(),
+            TokenKind::Identifier(k) if k == "export" && self.is_export_start() => self.parse_export_declaration(),
+            TokenKind::Identifier(k) if k == "from" && self.is_from_import_start() => self.parse_from_import(),
+            TokenKind::Identifier(k) if k == "extern" && self.is_extern_start() => self.parse_extern(),
+            TokenKind::Identifier(k) if k == "use" && self.is_use_start() => {
+                Err(format!("`use strict` must be at the top of the file (Line {})", self.current_line()))
+            },
+            TokenKind::Identifier(k) if k == "async" && self.is_async_start() => {
+                self.advance(); // 'async'
+                let mut func = self.parse_func()?;
+                func.as_array_mut().expect("function node").push(json!(true));
+                Ok(func)
+            },
+
             // --- GESTION DES EXPRESSIONS ET ASSIGNATIONS ---
-            TokenKind::Identifier(_) | TokenKind::Super | TokenKind::LParen => {
+            TokenKind::Identifier(_) | TokenKind::Super | TokenKind::LParen |
+            TokenKind::Integer(_) | TokenKind::Float(_) | TokenKind::StringLiteral(_) |
+            TokenKind::True | TokenKind::False | TokenKind::Null |
+            TokenKind::LBracket | TokenKind::Minus | TokenKind::Bang | TokenKind::New => {
                 let line = self.current_line();
                 // 1. On parse la partie gauche (ex: "x", "obj.prop", "list.at(0)")
                 let expr = self.parse_expression()?;
@@ -121,46 +272,32 @@ impl Parser {
                     TokenKind::PlusEq => {
                         self.advance();
                         let value = self.parse_expression()?;
-                        // Sucre : x = x + val
-                        let operation = json!(["+", expr.clone(), value]);
-                        return self.convert_to_assignment(line, expr, operation);
+                        return self.convert_to_compound_assignment(line, expr, "+", value);
                     },
                     TokenKind::MinusEq => {
                         self.advance();
                         let value = self.parse_expression()?;
-                        // Sucre : x = x - val
-                        let operation = json!(["-", expr.clone(), value]);
-                        return self.convert_to_assignment(line, expr, operation);
+                        return self.convert_to_compound_assignment(line, expr, "-", value);
                     },
                     TokenKind::StarEq => {
                         self.advance();
                         let value = self.parse_expression()?;
-                        // Sucre : x = x * val
-                        let operation = json!(["*", expr.clone(), value]);
-                        return self.convert_to_assignment(line, expr, operation);
+                        return self.convert_to_compound_assignment(line, expr, "*", value);
                     },
                     TokenKind::SlashEq => {
                         self.advance();
                         let value = self.parse_expression()?;
-                        // Sucre : x = x / val
-                        let operation = json!(["/", expr.clone(), value]);
-                        return self.convert_to_assignment(line, expr, operation);
+                        return self.convert_to_compound_assignment(line, expr, "/", value);
                     },
 
                     // Incrémentation / Décrémentation (++, --)
                     TokenKind::PlusPlus => {
                         self.advance();
-                        let one = json!(1);
-                        // Sucre : x = x + 1
-                        let operation = json!(["+", expr.clone(), one]);
-                        return self.convert_to_assignment(line, expr, operation);
+                        return self.convert_to_compound_assignment(line, expr, "+", json!(1));
                     },
                     TokenKind::MinusMinus => {
                         self.advance();
-                        let one = json!(1);
-                        // Sucre : x = x - 1
-                        let operation = json!(["-", expr.clone(), one]);
-                        return self.convert_to_assignment(line, expr, operation);
+                        return self.convert_to_compound_assignment(line, expr, "-", json!(1));
                     },
 
                     // Expression seule (appel de fonction, etc.)
@@ -181,7 +318,8 @@ impl Parser {
                                 }
                             }
                         }
-                        Ok(expr) 
+                        // Autre expression seule (ex: `x + 1`, utile au REPL) : résultat ignoré
+                        Ok(json!(["expr", line, expr]))
                     }
                 }
             },
@@ -196,21 +334,43 @@ impl Parser {
             
             if cmd == "get" {
                 let name = &arr[1];
-                return Ok(json!(["set", line, name, null, value]));
+                return Ok(json!(["assign", line, name, value]));
             }
             if cmd == "get_attr" {
                 let obj = &arr[1];
                 let attr = &arr[2];
                 return Ok(json!(["set_attr", line, obj, attr, value]));
             }
+            if cmd == "get_index" {
+                let obj = &arr[1];
+                let index = &arr[2];
+                return Ok(json!(["set_index", line, obj, index, value]));
+            }
         }
         Err(format!("Invalid assignment target (Line {})", line))
     }
 
+    // x op= val devient x = x op val ; sur un attribut ou un index, un nœud dédié évite
+    // d'évaluer deux fois l'objet et l'index (obj().n += 1, l[next()] += 1)
+    fn convert_to_compound_assignment(&self, line: usize, target: Value, op: &str, value: Value) -> Result<Value, String> {
+        if let Some(arr) = target.as_array() {
+            match arr[0].as_str().unwrap_or("") {
+                "get_attr" => return Ok(json!(["compound_set_attr", line, arr[1], arr[2], op, value])),
+                "get_index" => return Ok(json!(["compound_set_index", line, arr[1], arr[2], op, value])),
+                _ => {}
+            }
+        }
+        let operation = json!([op, target.clone(), value]);
+        self.convert_to_assignment(line, target, operation)
+    }
+
     fn parse_block(&mut self) -> Result<Value, String> {
         self.consume(TokenKind::LBrace, "Expect '{' before block")?;
         let mut block = Vec::new();
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
+            if self.match_token(TokenKind::Semicolon) {
+                continue;
+            }
             block.push(self.parse_statement()?);
         }
         self.consume(TokenKind::RBrace, "Expect '}' after block")?;
@@ -222,38 +382,7 @@ impl Parser {
         self.advance(); 
 
         if self.match_token(TokenKind::LBracket) {
-            let mut vars = Vec::new();
-            if !self.check(&TokenKind::RBracket) {
-                loop {
-                    if let TokenKind::Identifier(n) = &self.advance().kind {
-                        vars.push(n.clone());
-                    } else {
-                        return Err(format!("Expect variable name in destructuring (Line {})", line));
-                    }
-                    if !self.match_token(TokenKind::Comma) { break; }
-                }
-            }
-            self.consume(TokenKind::RBracket, "Expect ']'")?;
-            self.consume(TokenKind::Eq, "Expect '='")?;
-            
-            let expr = self.parse_expression()?;
-            
-            let mut instructions = Vec::new();
-            let temp_name = format!("__destruct_temp_{}", vars.len()); 
-            
-            instructions.push(json!(["set", line, temp_name, null, expr]));
-            
-            for (i, var_name) in vars.iter().enumerate() {
-                let access = json!([
-                    "call_method", 
-                    ["get", temp_name], 
-                    "at", 
-                    [json!(i as i64)]
-                ]);
-                instructions.push(json!(["set", line, var_name, null, access]));
-            }
-            
-            return Ok(json!(["if", line, json!(true), instructions]));
+            return self.parse_destructuring(line, false);
         }
 
         let name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Expect var name".into()); };
@@ -263,18 +392,133 @@ impl Parser {
         Ok(json!(["set", line, name, type_annot, expr]))
     }
 
-    fn parse_type_annotation(&mut self) -> Result<Option<String>, String> {
-        if self.match_token(TokenKind::Colon) {
-            if let TokenKind::Identifier(t) = &self.advance().kind {
-                Ok(Some(t.clone()))
+    // let x = ... (immuable, comme const) / let mut x = ... (équivalent à var)
+    fn parse_let(&mut self) -> Result<Value, String> {
+        let line = self.current_line();
+        self.advance(); // Eat 'let'
+
+        // 'mut' n'est pas réservé : c'est un modificateur seulement s'il précède un nom ou un '['
+        let is_mut = matches!(self.peek(), TokenKind::Identifier(m) if m == "mut")
+            && matches!(self.tokens.get(self.pos + 1).map(|t| &t.kind), Some(TokenKind::Identifier(_) | TokenKind::LBracket));
+        if is_mut {
+            self.advance();
+        }
+
+        if self.match_token(TokenKind::LBracket) {
+            return self.parse_destructuring(line, !is_mut);
+        }
+
+        let name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err(format!("Expect variable name after 'let' (Line {})", line)); };
+        let type_annot = self.parse_type_annotation()?;
+
+        if is_mut {
+            let expr = if self.match_token(TokenKind::Eq) { self.parse_expression()? } else { json!(null) };
+            return Ok(json!(["set", line, name, type_annot, expr]));
+        }
+
+        self.consume(TokenKind::Eq, "Expect '=' after immutable 'let' binding")?;
+        let expr = self.parse_expression()?;
+        Ok(json!(["const", line, name, expr, type_annot]))
+    }
+
+    // [a, b] = expr (le '[' est déjà consommé). Les noms sont des constantes si `immutable`.
+    fn parse_destructuring(&mut self, line: usize, immutable: bool) -> Result<Value, String> {
+        let mut vars = Vec::new();
+        if !self.check(&TokenKind::RBracket) {
+            loop {
+                if let TokenKind::Identifier(n) = &self.advance().kind {
+                    vars.push(n.clone());
+                } else {
+                    return Err(format!("Expect variable name in destructuring (Line {})", line));
+                }
+                if !self.match_token(TokenKind::Comma) { break; }
+            }
+        }
+        self.consume(TokenKind::RBracket, "Expect ']'")?;
+        self.consume(TokenKind::Eq, "Expect '='")?;
+
+        let expr = self.parse_expression()?;
+
+        let mut instructions = Vec::new();
+        let temp_name = format!("__destruct_temp_{}", vars.len());
+
+        instructions.push(json!(["set", line, temp_name, null, expr]));
+
+        for (i, var_name) in vars.iter().enumerate() {
+            let access = json!([
+                "call_method",
+                ["get", temp_name],
+                "at",
+                [json!(i as i64)]
+            ]);
+            if immutable {
+                instructions.push(json!(["const", line, var_name, access]));
             } else {
-                Err(format!("Expect type name after ':' (Line {})", self.current_line()))
+                instructions.push(json!(["set", line, var_name, null, access]));
             }
+        }
+
+        Ok(json!(["group", line, instructions]))
+    }
+
+    fn parse_type_annotation(&mut self) -> Result<Option<String>, String> {
+        if self.match_token(TokenKind::Colon) {
+            Ok(Some(self.parse_type_name()?))
         } else {
             Ok(None)
         }
     }
 
+    // Nom de type, éventuellement une union : int|string|null
+    fn parse_type_name(&mut self) -> Result<String, String> {
+        let mut parts = Vec::new();
+        loop {
+            match &self.advance().kind {
+                TokenKind::Identifier(t) => parts.push(t.clone()),
+                TokenKind::Null => parts.push("null".to_string()),
+                _ => return Err(format!("Expect type name (Line {})", self.current_line())),
+            }
+            if !self.match_token(TokenKind::BitOr) { break; }
+        }
+        Ok(parts.join("|"))
+    }
+
+    // private func/var/let/const/class... au premier niveau d'un module
+    fn parse_private_declaration(&mut self) -> Result<Value, String> {
+        let line = self.current_line();
+        self.advance(); // Eat 'private'
+
+        match self.peek() {
+            TokenKind::Func | TokenKind::Var | TokenKind::Let | TokenKind::Const | TokenKind::Class
+            | TokenKind::Final | TokenKind::Enum | TokenKind::Interface | TokenKind::Namespace => {
+                let decl = self.parse_statement()?;
+                Ok(json!(["private", line, decl]))
+            },
+            _ => Err(format!("Expect a declaration after 'private' (Line {})", line)),
+        }
+    }
+
+    // export func/var/let/const/class... : seuls les noms exportés sont visibles des importeurs
+    fn parse_export_declaration(&mut self) -> Result<Value, String> {
+        let line = self.current_line();
+        self.advance(); // Eat 'export'
+        let decl = self.parse_statement()?;
+        Ok(json!(["export", line, decl]))
+    }
+
+    // type Id = int|string
+    fn parse_type_alias(&mut self) -> Result<Value, String> {
+        let line = self.current_line();
+        self.advance(); // 'type'
+        let name = match &self.advance().kind {
+            TokenKind::Identifier(n) => n.clone(),
+            _ => return Err(format!("Expect alias name after 'type' (Line {})", line)),
+        };
+        self.consume(TokenKind::Eq, "Expect '=' after type alias name")?;
+        let ty = self.parse_type_name()?;
+        Ok(json!(["type_alias", line, name, ty]))
+    }
+
     fn parse_print(&mut self) -> Result<Value, String> {
         let line = self.current_line();
         self.advance();
@@ -285,7 +529,12 @@ impl Parser {
     fn parse_return(&mut self) -> Result<Value, String> {
         let line = self.current_line();
         self.advance();
-        let expr = self.parse_expression()?;
+        // `return;` ou `return }` : sans valeur
+        let expr = if matches!(self.peek(), TokenKind::Semicolon | TokenKind::RBrace | TokenKind::EOF) {
+            json!(null)
+        } else {
+            self.parse_expression()?
+        };
         Ok(json!(["return", line, expr]))
     }
 
@@ -304,19 +553,73 @@ impl Parser {
             TokenKind::StringLiteral(s) => s.clone(),
             _ => return Err("Expect path".into()),
         };
+
+        // import "x.aeg" as x : le module est aussi lié à une variable (locale dans une fonction)
+        let is_alias = matches!(self.peek(), TokenKind::Identifier(a) if a == "as")
+            && matches!(self.tokens.get(self.pos + 1).map(|t| &t.kind), Some(TokenKind::Identifier(_)));
+        if is_alias {
+            self.advance();
+            if let TokenKind::Identifier(alias) = &self.advance().kind {
+                return Ok(json!(["import", line, path, alias]));
+            }
+        }
+
         Ok(json!(["import", line, path]))
     }
 
+    // from "x.aeg" import a, b as c
+    fn parse_from_import(&mut self) -> Result<Value, String> {
+        let line = self.current_line();
+        self.advance(); // 'from'
+        let path = match &self.advance().kind {
+            TokenKind::StringLiteral(s) => s.clone(),
+            _ => return Err(format!("Expect module path after 'from' (Line {})", line)),
+        };
+        self.consume(TokenKind::Import, "Expect 'import' after module path")?;
+
+        let mut names = Vec::new();
+        loop {
+            let name = match &self.advance().kind {
+                TokenKind::Identifier(n) => n.clone(),
+                _ => return Err(format!("Expect name to import (Line {})", line)),
+            };
+            let alias = if matches!(self.peek(), TokenKind::Identifier(a) if a == "as") {
+                self.advance();
+                match &self.advance().kind {
+                    TokenKind::Identifier(a) => a.clone(),
+                    _ => return Err(format!("Expect name after 'as' (Line {})", line)),
+                }
+            } else {
+                name.clone()
+            };
+            names.push(json!([name, alias]));
+            if !self.match_token(TokenKind::Comma) { break; }
+        }
+
+        Ok(json!(["from_import", line, path, names]))
+    }
+
     fn parse_try(&mut self) -> Result<Value, String> {
         let line = self.current_line();
         self.advance();
         let try_body = self.parse_block()?;
         self.consume(TokenKind::Catch, "Expect catch")?;
-        self.consume(TokenKind::LParen, "(")?;
-        let err_var = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Expect error var".into()); };
-        self.consume(TokenKind::RParen, ")")?;
-        let catch_body = self.parse_block()?;
-        Ok(json!(["try", line, try_body, err_var, catch_body]))
+
+        // catch (e) { } ou catch (e: IOError) { } catch (e: ValueError|TypeError) { } ...
+        let mut catches = Vec::new();
+        loop {
+            self.consume(TokenKind::LParen, "(")?;
+            let err_var = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Expect error var".into()); };
+            let err_type = self.parse_type_annotation()?;
+            self.consume(TokenKind::RParen, ")")?;
+            let catch_body = self.parse_block()?;
+            catches.push(json!([err_var, err_type, catch_body]));
+
+            if !self.match_token(TokenKind::Catch) {
+                break;
+            }
+        }
+        Ok(json!(["try", line, try_body, catches]))
     }
 
     fn parse_throw(&mut self) -> Result<Value, String> {
@@ -326,6 +629,33 @@ impl Parser {
         Ok(json!(["throw", line, expr]))
     }
 
+    fn parse_defer(&mut self) -> Result<Value, String> {
+        let line = self.current_line();
+        self.advance(); // Consomme 'defer'
+        let body = if self.check(&TokenKind::LBrace) {
+            self.parse_block()?
+        } else {
+            json!([self.parse_statement()?])
+        };
+        Ok(json!(["defer", line, body]))
+    }
+
+    fn parse_with(&mut self) -> Result<Value, String> {
+        let line = self.current_line();
+        self.advance(); // Consomme 'with'
+        let resource = self.parse_expression()?;
+        if !matches!(self.peek(), TokenKind::Identifier(a) if a == "as") {
+            return Err(format!("Expect 'as' after with expression (Line {})", self.current_line()));
+        }
+        self.advance();
+        let name = match &self.advance().kind {
+            TokenKind::Identifier(n) => n.clone(),
+            _ => return Err(format!("Expect variable name after 'as' (Line {})", line)),
+        };
+        let body = self.parse_block()?;
+        Ok(json!(["with", line, resource, name, body]))
+    }
+
     fn parse_switch(&mut self) -> Result<Value, String> {
         let line = self.current_line();
         self.advance();
@@ -343,13 +673,17 @@ impl Parser {
                 self.consume(TokenKind::Colon, ":")?;
                 let mut body = Vec::new();
                 while !self.check(&TokenKind::Case) && !self.check(&TokenKind::Default) && !self.check(&TokenKind::RBrace) {
-                    body.push(self.parse_statement()?);
+                    if !self.match_token(TokenKind::Semicolon) {
+                        body.push(self.parse_statement()?);
+                    }
                 }
                 cases.push(json!([c_val, body]));
             } else if self.match_token(TokenKind::Default) {
                 self.consume(TokenKind::Colon, ":")?;
                 while !self.check(&TokenKind::Case) && !self.check(&TokenKind::Default) && !self.check(&TokenKind::RBrace) {
-                    default.push(self.parse_statement()?);
+                    if !self.match_token(TokenKind::Semicolon) {
+                        default.push(self.parse_statement()?);
+                    }
                 }
             } else {
                 return Err("Unexpected in switch".into());
@@ -379,15 +713,14 @@ impl Parser {
         };
 
         // Typage graduel optionnel (const PI: float = ...)
-        // On consomme le type mais on l'ignore pour l'instant (ou on l'utilise pour check)
-        let _type_annot = self.parse_type_annotation()?; 
+        let type_annot = self.parse_type_annotation()?; 
 
         self.consume(TokenKind::Eq, "Expect '=' after constant name")?;
         
         let expr = self.parse_expression()?;
         
-        // JSON: ["const", line, name, expr]
-        Ok(json!(["const", line, name, expr]))
+        // JSON: ["const", line, name, expr, type]
+        Ok(json!(["const", line, name, expr, type_annot]))
     }
 
     fn parse_foreach(&mut self) -> Result<Value, String> {
@@ -430,6 +763,9 @@ impl Parser {
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
             // Syntaxe : func nom(args); (Pas de corps !)
             // Ou juste : nom(args);
+            if self.match_token(TokenKind::Semicolon) {
+                continue;
+            }
             
             // On accepte 'func' optionnel pour cohérence
             self.match_token(TokenKind::Func); 
@@ -455,15 +791,7 @@ impl Parser {
         self.consume(TokenKind::Func, "Func")?;
         let func_name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Func Name".into()); };
         
-        self.consume(TokenKind::LParen, "(")?;
-        let mut params = Vec::new();
-        if !self.check(&TokenKind::RParen) {
-            loop {
-                if let TokenKind::Identifier(p) = &self.advance().kind { params.push(p.clone()); }
-                if !self.match_token(TokenKind::Comma) { break; }
-            }
-        }
-        self.consume(TokenKind::RParen, ")")?;
+        let params = self.parse_params_list()?;
         let body = self.parse_block()?;
         
         let lambda = json!(["lambda", params, body]);
@@ -473,15 +801,68 @@ impl Parser {
         Ok(json!(["set", line, func_name, null, call]))
     }
 
+    // extern "libm.so.6" func cos(x: float) -> float : fonction C d'une bibliothèque partagée.
+    // Devient `cos = ffi_bind("libm.so.6", "cos", ["float"], "float")` (voir native/ffi.rs)
+    fn parse_extern(&mut self) -> Result<Value, String> {
+        let line = self.current_line();
+        self.advance(); // 'extern'
+        let library = match &self.advance().kind {
+            TokenKind::StringLiteral(s) => s.clone(),
+            _ => unreachable!("is_extern_start"),
+        };
+        self.advance(); // 'func'
+        let name = match &self.advance().kind {
+            TokenKind::Identifier(n) => n.clone(),
+            _ => return Err(format!("Expect function name after 'extern \"{}\" func' (Line {})", library, line)),
+        };
+
+        let params = self.parse_params_list()?;
+        let mut types = vec![json!("make_list")];
+        for param in params.as_array().into_iter().flatten() {
+            // [nom, type] : ni valeur par défaut ni `...rest`, le type C est obligatoire
+            match param.as_array().map(|p| (p.len(), &p[0], &p[1])) {
+                Some((2, _, Value::String(ty))) => types.push(json!(ty)),
+                Some((_, p_name, _)) => {
+                    return Err(format!("Parameter '{}' of extern function '{}' needs a C type and no default value (Line {})",
+                        p_name.as_str().unwrap_or_default(), name, line));
+                },
+                None => unreachable!("parse_params_list"),
+            }
+        }
+        let ret_type = if self.match_token(TokenKind::Arrow) { json!(self.parse_type_name()?) } else { Value::Null };
+
+        let bind = json!(["call", ["get", "ffi_bind"], [library, name, types, ret_type]]);
+        Ok(json!(["set", line, name, null, bind]))
+    }
+
+    // (a, b: int = 10, ...rest) : [name, type], avec la valeur par défaut en 3e position,
+    // et true en 4e pour le paramètre `...rest`
     fn parse_params_list(&mut self) -> Result<Value, String> {
         self.consume(TokenKind::LParen, "(")?;
         let mut params = Vec::new();
+        let mut has_default = false;
+        let mut has_rest = false;
         if !self.check(&TokenKind::RParen) {
             loop {
+                let is_rest = self.match_token(TokenKind::Ellipsis);
                 if let TokenKind::Identifier(p) = &self.advance().kind {
                     let p_name = p.clone();
+                    if has_rest {
+                        return Err(format!("The rest parameter must be the last one (Line {})", self.current_line()));
+                    }
                     let p_type = self.parse_type_annotation()?;
-                    params.push(json!([p_name, p_type]));
+                    if is_rest {
+                        has_rest = true;
+                        params.push(json!([p_name, p_type, null, true]));
+                    } else if self.match_token(TokenKind::Eq) {
+                        has_default = true;
+                        let default = self.parse_expression()?;
+                        params.push(json!([p_name, p_type, default]));
+                    } else if has_default {
+                        return Err(format!("Parameter '{}' needs a default value: it follows a parameter that has one (Line {})", p_name, self.current_line()));
+                    } else {
+                        params.push(json!([p_name, p_type]));
+                    }
                 }
                 if !self.match_token(TokenKind::Comma) { break; }
             }
@@ -572,6 +953,7 @@ impl Parser {
 
     fn parse_class(&mut self) -> Result<Value, String> {
         let line = self.current_line();
+        let mut doc = self.doc_at(self.pos);
         let is_class_final = self.match_token(TokenKind::Final);
 
         self.consume(TokenKind::Class, "Expect 'class'")?;
@@ -616,8 +998,20 @@ impl Parser {
         let mut visibilities = serde_json::Map::new(); // Map<Nom, "public"|"private"|"protected">
         let mut fields = Vec::new(); // Liste de ["field", nom, visibilité, valeur_defaut]
 
+        // Docstring écrite en tête du corps : class Point { "Un point du plan." ... }
+        if let TokenKind::StringLiteral(text) = self.peek() {
+            let text = text.clone();
+            self.advance();
+            doc = doc.or(Some(text));
+        }
+
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
-            
+            if self.match_token(TokenKind::Semicolon) {
+                continue;
+            }
+            let member_line = self.current_line();
+            let member_doc = self.doc_at(self.pos);
+
             // 1. Visibilité
             let vis_str = if self.match_token(TokenKind::Public) { "public" }
                      else if self.match_token(TokenKind::Private) { "private" }
@@ -632,9 +1026,13 @@ impl Parser {
             
             // Cas Méthode explicite 'func'
             if self.match_token(TokenKind::Func) {
-                let m_name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Method Name".into()); };
+                let m_name = self.advance_member_name().ok_or("Method Name")?;
                 let m_params = self.parse_params_list()?;
-                let m_body = self.parse_block()?;
+                let mut m_body = self.parse_block()?;
+                if let Some(doc) = member_doc {
+                    prepend_docstring(&mut m_body, member_line, doc);
+                }
+                let m_name = overload_key(&methods, m_name, is_static);
                 
                 methods.insert(m_name.clone(), json!([m_params, m_body, is_static, is_final_method]));
                 visibilities.insert(m_name, json!(vis_str));
@@ -660,12 +1058,16 @@ impl Parser {
             }
             // Cas Implicite (identifiant...)
             else {
-                let member_name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Member name".into()); };
+                let member_name = self.advance_member_name().ok_or("Member name")?;
 
                 if self.check(&TokenKind::LParen) {
                     // Méthode
                     let m_params = self.parse_params_list()?;
-                    let m_body = self.parse_block()?;
+                    let mut m_body = self.parse_block()?;
+                    if let Some(doc) = member_doc {
+                        prepend_docstring(&mut m_body, member_line, doc);
+                    }
+                    let member_name = overload_key(&methods, member_name, is_static);
                     methods.insert(member_name.clone(), json!([m_params, m_body, is_static]));
                     visibilities.insert(member_name, json!(vis_str));
                 } else {
@@ -681,12 +1083,12 @@ impl Parser {
         self.consume(TokenKind::RBrace, "Expect '}' after class body")?;
         
         // FORMAT JSON DE SORTIE (v0.3.0)
-        // ["class", line, name, methods, parent, fields, visibilities, is_final]
+        // ["class", line, name, methods, parent, fields, visibilities, is_final, interfaces, doc]
         
         let result = if parent.is_null() {
-            json!(["class", line, name, methods, null, fields, visibilities, is_class_final, interfaces])
+            json!(["class", line, name, methods, null, fields, visibilities, is_class_final, interfaces, doc])
         } else {
-            json!(["class", line, name, methods, parent, fields, visibilities, is_class_final, interfaces])
+            json!(["class", line, name, methods, parent, fields, visibilities, is_class_final, interfaces, doc])
         };
 
         Ok(result)
@@ -728,18 +1130,20 @@ impl Parser {
 
     fn parse_func(&mut self) -> Result<Value, String> {
         let line = self.current_line();
+        let doc = self.doc_at(self.pos);
         self.advance();
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Func Name".into()); };
+        let name = self.advance_member_name().ok_or("Func Name")?;
         
         let params = self.parse_params_list()?;
         
         let mut ret_type = Value::Null;
         if self.match_token(TokenKind::Arrow) {
-             if let TokenKind::Identifier(t) = &self.advance().kind {
-                 ret_type = json!(t);
-             }
+             ret_type = json!(self.parse_type_name()?);
+        }
+        let mut body = self.parse_block()?;
+        if let Some(doc) = doc {
+            prepend_docstring(&mut body, line, doc);
         }
-        let body = self.parse_block()?;
         
         Ok(json!(["function", line, name, params, ret_type, body]))
     }
@@ -800,10 +1204,12 @@ impl Parser {
 
     fn parse_equality(&mut self) -> Result<Value, String> {
         let mut left = self.parse_relational()?;
-        while let TokenKind::EqEq | TokenKind::Neq = self.peek() {
+        while let TokenKind::EqEq | TokenKind::Neq | TokenKind::EqEqEq | TokenKind::NeqEq = self.peek() {
             let op = match self.advance().kind {
                 TokenKind::EqEq => "==",
                 TokenKind::Neq => "!=",
+                TokenKind::EqEqEq => "===",
+                TokenKind::NeqEq => "!==",
                 _ => unreachable!()
             };
             let right = self.parse_relational()?;
@@ -876,10 +1282,11 @@ impl Parser {
 
     fn parse_multiplicative(&mut self) -> Result<Value, String> {
         let mut left = self.parse_unary()?;
-        while let TokenKind::Star | TokenKind::Slash | TokenKind::Percent = self.peek() {
+        while let TokenKind::Star | TokenKind::Slash | TokenKind::TildeSlash | TokenKind::Percent = self.peek() {
             let op = match self.advance().kind {
                 TokenKind::Star => "*",
                 TokenKind::Slash => "/",
+                TokenKind::TildeSlash => "~/",
                 TokenKind::Percent => "%",
                 _ => unreachable!()
             };
@@ -898,6 +1305,11 @@ impl Parser {
             let right = self.parse_unary()?;
             return Ok(json!(["-", json!(0), right]));
         }
+        if matches!(self.peek(), TokenKind::Identifier(k) if k == "await") && self.is_await_start() {
+            self.advance();
+            let task = self.parse_unary()?;
+            return Ok(json!(["await", task]));
+        }
         self.parse_primary()
     }
 
@@ -917,17 +1329,21 @@ impl Parser {
             TokenKind::Identifier(name) => { let n = name.clone(); self.advance(); json!(["get", n]) },
             TokenKind::Func => {
                 self.advance();
-                self.consume(TokenKind::LParen, "(")?;
-                let mut params = Vec::new();
-                if !self.check(&TokenKind::RParen) {
-                    loop {
-                        if let TokenKind::Identifier(p) = &self.advance().kind { params.push(p.clone()); }
-                        if !self.match_token(TokenKind::Comma) { break; }
-                    }
-                }
-                self.consume(TokenKind::RParen, ")")?;
+                // Nom optionnel : func fact(n) { ... return n * fact(n - 1) }
+                let name = match self.peek() {
+                    TokenKind::Identifier(name) => {
+                        let name = name.clone();
+                        self.advance();
+                        Some(name)
+                    },
+                    _ => None,
+                };
+                let params = self.parse_params_list()?;
                 let body = self.parse_block()?;
-                json!(["lambda", params, body])
+                match name {
+                    Some(name) => json!(["lambda", params, body, name]),
+                    None => json!(["lambda", params, body]),
+                }
             },
             TokenKind::LParen => {
                 self.advance();
@@ -955,9 +1371,17 @@ impl Parser {
                 let mut entries = Vec::new();
                 if !self.check(&TokenKind::RBrace) {
                     loop {
+                        // Clé chaîne (ou nom nu), entier ou booléen : gardée avec son type
                         let key = match &self.advance().kind {
-                            TokenKind::StringLiteral(s) => s.clone(),
-                            TokenKind::Identifier(s) => s.clone(),
+                            TokenKind::StringLiteral(s) => json!(s),
+                            TokenKind::Identifier(s) => json!(s),
+                            TokenKind::Integer(i) => json!(i),
+                            TokenKind::Minus => match &self.advance().kind {
+                                TokenKind::Integer(i) => json!(-i),
+                                _ => return Err("Dict Key".into())
+                            },
+                            TokenKind::True => json!(true),
+                            TokenKind::False => json!(false),
                             _ => return Err("Dict Key".into())
                         };
                         self.consume(TokenKind::Colon, ":")?;
@@ -1030,7 +1454,7 @@ impl Parser {
                 self.consume(TokenKind::RParen, ")")?;
                 expr = json!(["call", expr, args]);
             } else if self.match_token(TokenKind::Dot) {
-                let member = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Member".into()); };
+                let member = self.advance_member_name().ok_or("Member")?;
                 if self.match_token(TokenKind::LParen) {
                     let mut args = Vec::new();
                     if !self.check(&TokenKind::RParen) {
@@ -1041,6 +1465,12 @@ impl Parser {
                 } else {
                     expr = json!(["get_attr", expr, member]);
                 }
+            } else if self.check(&TokenKind::LBracket) && self.tokens[self.pos - 1].line == self.current_line() {
+                // Sur la même ligne seulement : un '[' en début de ligne commence une nouvelle liste
+                self.advance();
+                let index = self.parse_expression()?;
+                self.consume(TokenKind::RBracket, "Expect ']' after index")?;
+                expr = json!(["get_index", expr, index]);
             } else {
                 break;
             }
@@ -1094,7 +1524,7 @@ impl Parser {
 
                     // Compilation du snippet
                     let mut sub_lexer = super::lexer::Lexer::new(&code_snippet);
-                    let sub_tokens = sub_lexer.tokenize();
+                    let sub_tokens = sub_lexer.tokenize()?;
                     let mut sub_parser = Parser::new(sub_tokens);
                     let expr = sub_parser.parse_expression()?;
                     
@@ -1113,12 +1543,32 @@ impl Parser {
         
         if !current_text.is_empty() { parts.push(json!(current_text)); }
         if parts.is_empty() { return Ok(json!("")); }
-        
-        let mut final_expr = parts[0].clone();
-        for i in 1..parts.len() {
-            final_expr = json!(["+", final_expr, parts[i]]);
-        }
 
-        Ok(final_expr)
+        // Chaque partie est convertie en texte : "${a}${b}" ne fait jamais d'addition, et
+        // l'interpolation reste permise en mode strict
+        let mut interpolation = vec![json!("interpolate")];
+        interpolation.extend(parts);
+        Ok(json!(interpolation))
+    }
+}
+
+// Ajoute `doc` en tête du bloc (instruction ["expr", ligne, "texte"]), sauf s'il commence déjà
+// par une chaîne littérale
+fn prepend_docstring(block: &mut Value, line: usize, doc: String) {
+    let Some(statements) = block.as_array_mut() else { return };
+    let documented = statements.first()
+        .and_then(|s| s.as_array())
+        .is_some_and(|s| s.first().and_then(|k| k.as_str()) == Some("expr") && s.get(2).is_some_and(|e| e.is_string()));
+    if !documented {
+        statements.insert(0, json!(["expr", line, doc]));
+    }
+}
+
+// Un deuxième `init` ne remplace pas le premier : c'est une surcharge, rangée sous `init#2`
+// (puis `init#3`...). La VM choisit à l'appel celle dont l'arité convient.
+fn overload_key(methods: &serde_json::Map<String, Value>, name: String, is_static: bool) -> String {
+    if is_static || name != CONSTRUCTOR {
+        return name;
     }
+    constructor_key(methods.keys().filter(|key| is_constructor(key)).count() + 1)
 }
@@ -0,0 +1,292 @@
+// Codes d'erreur stables (E0001, E0102...) et leurs explications, pour `aegis explain E0102`.
+// Les messages eux-mêmes ne changent pas (un script peut les tester dans un catch) : le code est
+// retrouvé d'après le message quand l'erreur remonte jusqu'à la ligne de commande.
+//
+// Numérotation : E00xx noms et variables, E01xx appels et types, E02xx calculs et index,
+// E03xx classes, E04xx modules. Un code attribué ne change plus de sens.
+
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    // Fragments qui identifient le message (le premier code dont un fragment apparaît l'emporte)
+    patterns: &'static [&'static str],
+    pub explanation: &'static str,
+}
+
+pub const CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "E0001",
+        title: "undefined variable",
+        patterns: &["Variable introuvable", "Fonction '"],
+        explanation: r#"A function used a name that is neither one of its variables, a global, a
+function nor a native function. (At the top level of a script, an unknown
+global reads as null instead.)
+
+    var total = 10
+    func show() {
+        print totl    // E0001: 'totl' is not defined
+    }
+
+Common fixes:
+- Check the spelling: names are case-sensitive (`Total` and `total` differ).
+- Declare the variable before using it, with `var` or `let`.
+- A variable declared with `let` inside a block (if, while, foreach...) does not
+  exist after the block ends.
+- Functions of the standard library live in namespaces: import the module and
+  use its name, e.g. `import "stdlib/math.aeg"` then `Math.sqrt(2)`."#,
+    },
+    ErrorCode {
+        code: "E0002",
+        title: "assignment to a constant",
+        patterns: &["Cannot reassign constant", "Cannot reassign local constant"],
+        explanation: r#"A `const` was given a new value. Constants are bound once.
+
+    const MAX = 10
+    MAX = 20          // E0002
+
+Common fixes:
+- Use `var` instead of `const` if the value has to change.
+- Use a new name for the derived value: `var limit = MAX * 2`.
+- A constant holding a list or dict can still be modified in place
+  (`ITEMS.push(x)`); only the binding is constant."#,
+    },
+    ErrorCode {
+        code: "E0003",
+        title: "assignment to an undeclared variable",
+        patterns: &["Assignment to undeclared variable", "creates a new local variable"],
+        explanation: r#"`x = ...` assigned a variable that was never declared. Outside strict mode this
+is a warning and creates the variable; with `--strict` or `use strict`, it is an
+error.
+
+Inside a function, assigning a global without declaring it creates a new local
+variable: the global is left unchanged.
+
+    var count = 0
+    func bump() {
+        count = count + 1    // E0003: a new local 'count'
+    }
+
+Common fixes:
+- Declare new variables with `var` or `let`.
+- To update shared state from a function, return the new value, or keep it in a
+  list, dict or object that the function modifies."#,
+    },
+    ErrorCode {
+        code: "E0101",
+        title: "value does not match its type annotation",
+        patterns: &["Erreur de Type: Attendu"],
+        explanation: r#"A value does not match the type written in an annotation: a parameter, a return
+type, a typed variable or a typed field.
+
+    func area(w: int, h: int) -> int { return w * h }
+    area("3", 4)      // E0101: expected 'int', got '3'
+
+Common fixes:
+- Convert the value first: `to_int("3")`, `str(42)`, `to_float(x)`.
+- Widen the annotation with a union (`int|float`, `string|null`) if several
+  types are valid.
+- Run `aegis check file.aeg` to find these mismatches without running the
+  program."#,
+    },
+    ErrorCode {
+        code: "E0102",
+        title: "wrong number of arguments",
+        patterns: &["Arity mismatch", "argument(s), got", "missing argument"],
+        explanation: r#"A function was called with more or fewer arguments than it declares.
+
+    func greet(name, greeting) { return greeting + ", " + name }
+    greet("Ada")      // E0102: expected 2, got 1
+
+Common fixes:
+- Pass every parameter, in the order of the declaration.
+- Give optional parameters a default value: `func greet(name, greeting = "Hello")`.
+- For a variable number of arguments, end the list with a rest parameter:
+  `func sum(...values)`.
+- Methods receive `this` implicitly: do not pass the object yourself."#,
+    },
+    ErrorCode {
+        code: "E0103",
+        title: "value is not callable",
+        patterns: &["qui n'est pas une fonction", "n'est pas une fonction", "not a function"],
+        explanation: r#"Something that is not a function was called with `(...)`.
+
+    var name = "Ada"
+    name()            // E0103
+
+Common fixes:
+- Check that the variable holds the function you expect; a later `var` with the
+  same name may have replaced it.
+- To call a method, use `object.method()`; for a field that holds a function,
+  read it into a variable first.
+- A missing function usually gives null: check the module that should define it
+  is imported."#,
+    },
+    ErrorCode {
+        code: "E0104",
+        title: "unknown method",
+        patterns: &["Unknown list method", "Unknown bytes method", "Unknown range method", "Méthode string inconnue",
+            "Instance has no method", "not supported on", "Dict has no key or method", "Unknown Float32Array method",
+            "Unknown IntArray method"],
+        explanation: r#"A method was called that the value does not have.
+
+    var items = [1, 2, 3]
+    items.size()      // E0104: the method is `len()`
+
+Common fixes:
+- Check the name against the documentation of the type (Lists, Dictionaries,
+  Strings, Bytes...).
+- `null` has no methods: check that the variable was initialized.
+- For a class, check the method is declared in the class or one of its parents."#,
+    },
+    ErrorCode {
+        code: "E0105",
+        title: "unsupported operand types",
+        patterns: &["Type error in", "Type error %", "Cannot add string and", "Type error:"],
+        explanation: r#"An operator was applied to values it does not support, like `-` between a
+string and a number.
+
+    print "total: " - 3     // E0105
+
+In strict mode, `+` between a string and another value is also refused, rather
+than converting the value to a string.
+
+Common fixes:
+- Convert explicitly: `str(n)`, `to_int(text)`, `to_float(text)`.
+- Use string interpolation for messages: "total: ${n}".
+- Check for null before doing arithmetic on a value that may be missing."#,
+    },
+    ErrorCode {
+        code: "E0201",
+        title: "division by zero",
+        patterns: &["Division by zero"],
+        explanation: r#"An integer was divided by zero with `/` or `%`, or a duration was divided by a
+zero duration.
+
+    var count = 0
+    print 10 / count  // E0201
+
+Common fixes:
+- Check the divisor before dividing: `count == 0 ? 0 : total / count`.
+- Catch the error with try/catch where a zero divisor is expected."#,
+    },
+    ErrorCode {
+        code: "E0202",
+        title: "integer overflow",
+        patterns: &["Integer overflow", "Invalid shift amount"],
+        explanation: r#"An integer operation gave a result that does not fit in 64 bits. Aegis raises
+an error rather than silently wrapping around.
+
+    var big = 9223372036854775807
+    big + 1           // E0202
+
+A shift by a negative amount or by 64 bits or more is refused for the same reason.
+
+Common fixes:
+- Use floats for values that can grow very large: `to_float(big) * 2`.
+- Check the range of inputs before multiplying or adding them."#,
+    },
+    ErrorCode {
+        code: "E0203",
+        title: "index out of range",
+        patterns: &["out of range (length"],
+        explanation: r#"A list, string or typed array was indexed outside its bounds. Valid indices go
+from 0 to len - 1, and from -len to -1 counting from the end.
+
+    var items = [1, 2, 3]
+    items[3]          // E0203: the last index is 2
+
+Common fixes:
+- Check the length first: `if (i < items.len()) { ... }`.
+- Use `items.at(i)`, which returns null past the end instead of failing.
+- Loop with `foreach` instead of managing an index by hand."#,
+    },
+    ErrorCode {
+        code: "E0204",
+        title: "value cannot be indexed",
+        patterns: &["Cannot index a value", "Cannot assign to an index", "index must be an int"],
+        explanation: r#"`value[index]` was used on a value that does not support it, or with an index
+of the wrong type. Lists, strings and typed arrays take an int; dicts take a
+string, int or bool key.
+
+    var n = 42
+    n[0]              // E0204
+
+Common fixes:
+- Check the value is the list or dict you expect (it may be null).
+- Convert the index: `items[to_int(text)]`.
+- Strings cannot be modified by index: build a new string instead."#,
+    },
+    ErrorCode {
+        code: "E0301",
+        title: "access to a private or protected member",
+        patterns: &["Accès refusé"],
+        explanation: r#"A `private` member was used outside its class, or a `protected` member outside
+its class and subclasses.
+
+    class Account {
+        private balance = 0
+    }
+    new Account().balance   // E0301
+
+Common fixes:
+- Add a public method or property that exposes what callers need.
+- Move the code that needs the member into the class."#,
+    },
+    ErrorCode {
+        code: "E0302",
+        title: "final class or method overridden",
+        patterns: &["marquée 'final'", "méthode finale"],
+        explanation: r#"A class extended a `final` class, or redefined a method marked `final` in a
+parent class.
+
+    final class Config {}
+    class MyConfig extends Config {}   // E0302
+
+Common fixes:
+- Use composition: keep an instance of the final class in a field.
+- Remove `final` from the parent if it is meant to be extended."#,
+    },
+    ErrorCode {
+        code: "E0303",
+        title: "class has no constructor",
+        patterns: &["n'a pas de constructeur"],
+        explanation: r#"`new Class(args)` passed arguments to a class that has no `init` method.
+
+    class Point {}
+    new Point(1, 2)   // E0303
+
+Common fixes:
+- Declare a constructor: `init(x, y) { this.x = x  this.y = y }`.
+- Create the object without arguments and set its fields afterwards."#,
+    },
+    ErrorCode {
+        code: "E0401",
+        title: "import failed",
+        patterns: &["Failed to import", "does not export"],
+        explanation: r#"An `import` could not load its module, or asked for a name the module does not
+export.
+
+    import "lib/utils.aeg"      // E0401 if the file does not exist
+
+Paths are resolved from the importing file, then from the project root and the
+installed packages.
+
+Common fixes:
+- Check the path and the file extension.
+- Install missing packages with `aegis add <name>`.
+- With `from "mod.aeg" import name`, check that `name` is declared with `export`
+  in the module."#,
+    },
+];
+
+/// Code d'erreur demandé à `aegis explain` ("E0102", "e0102" ou "0102").
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    let code = code.trim().to_ascii_uppercase();
+    let code = if code.starts_with('E') { code } else { format!("E{}", code) };
+    CODES.iter().find(|c| c.code == code)
+}
+
+/// Code correspondant à un message d'erreur, s'il en a un.
+pub fn classify(message: &str) -> Option<&'static ErrorCode> {
+    CODES.iter().find(|c| c.patterns.iter().any(|p| message.contains(p)))
+}
@@ -0,0 +1,272 @@
+// `aegis graph` : arbre des imports d'un script, en DOT (Graphviz) ou en Mermaid.
+// Les chemins sont résolus comme par OpCode::Import : depuis le dossier courant, en essayant
+// les motifs de `[imports] resolve` (project::import_candidates).
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use serde_json::Value as JsonValue;
+
+// Dossier où `aegis add` installe les dépendances
+const PACKAGES_DIR: &str = "packages/";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModuleStatus {
+    Ok,
+    Missing,
+    // Le fichier existe mais ne se parse pas (message de l'erreur)
+    Invalid(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub path: String,
+    // Paquet APM qui contient ce fichier (`packages/<nom>/...`)
+    pub package: Option<String>,
+    pub status: ModuleStatus,
+}
+
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    // Le point d'entrée est toujours le module 0
+    pub modules: Vec<Module>,
+    // (importeur, importé), dans l'ordre des imports
+    pub edges: Vec<(usize, usize)>,
+    // Chaque cycle liste ses modules dans l'ordre des imports, sans répéter le premier
+    pub cycles: Vec<Vec<usize>>,
+    // Dépendances de aegis.toml dont aucun fichier n'est importé
+    pub unused_packages: Vec<String>,
+}
+
+impl ModuleGraph {
+    // Parcourt les imports depuis `entry` (en largeur, dans l'ordre du source)
+    pub fn build(entry: &str) -> Result<ModuleGraph, String> {
+        let source = fs::read_to_string(entry)
+            .map_err(|e| format!("Impossible de lire {}: {}", entry, e))?;
+        let json = crate::compiler::compile(&source)?;
+
+        let mut graph = ModuleGraph::default();
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        graph.add_module(&mut ids, &normalize(entry));
+        queue.push_back((0, json));
+
+        while let Some((from, json)) = queue.pop_front() {
+            let mut paths = Vec::new();
+            collect_imports(&json, &mut paths);
+
+            for path in paths {
+                // Introuvable : le module garde le chemin écrit et sera marqué Missing
+                let path = crate::project::import_candidates(&path)?.into_iter()
+                    .find(|candidate| std::path::Path::new(candidate).is_file() || crate::stdlib::source(candidate).is_some())
+                    .unwrap_or(path);
+                let path = normalize(&path);
+                let to = match ids.get(&path) {
+                    Some(&id) => id,
+                    None => {
+                        let id = graph.add_module(&mut ids, &path);
+                        // "stdlib/..." absent du disque : la copie embarquée, comme à l'exécution
+                        match fs::read_to_string(&path).or_else(|e| crate::stdlib::source(&path).ok_or(e)) {
+                            // Fichier de données (`import "config.json" as cfg`) : n'importe rien lui-même
+                            Ok(text) if crate::native::is_data_file(&path) => {
+                                if let Err(e) = crate::native::parse_data_file(&path, &text) {
+                                    graph.modules[id].status = ModuleStatus::Invalid(e);
+                                }
+                            },
+                            Ok(source) => match crate::compiler::compile(&source) {
+                                Ok(json) => queue.push_back((id, json)),
+                                Err(e) => graph.modules[id].status = ModuleStatus::Invalid(e),
+                            },
+                            Err(_) => graph.modules[id].status = ModuleStatus::Missing,
+                        }
+                        id
+                    }
+                };
+                if !graph.edges.contains(&(from, to)) {
+                    graph.edges.push((from, to));
+                }
+            }
+        }
+
+        graph.find_cycles();
+
+        let imported: HashSet<&str> = graph.modules.iter().filter_map(|m| m.package.as_deref()).collect();
+        graph.unused_packages = declared_dependencies().into_iter()
+            .filter(|name| !imported.contains(name.as_str()))
+            .collect();
+
+        Ok(graph)
+    }
+
+    fn add_module(&mut self, ids: &mut HashMap<String, usize>, path: &str) -> usize {
+        let package = path.strip_prefix(PACKAGES_DIR)
+            .and_then(|rest| rest.split_once('/'))
+            .map(|(name, _)| name.to_string());
+        self.modules.push(Module { path: path.to_string(), package, status: ModuleStatus::Ok });
+        ids.insert(path.to_string(), self.modules.len() - 1);
+        self.modules.len() - 1
+    }
+
+    // Parcours en profondeur : chaque arête vers un module encore sur la pile ferme un cycle
+    fn find_cycles(&mut self) {
+        let mut state = vec![0u8; self.modules.len()]; // 0 : non visité, 1 : sur la pile, 2 : terminé
+        let mut stack: Vec<usize> = Vec::new();
+        let mut cycles = Vec::new();
+        self.visit(0, &mut state, &mut stack, &mut cycles);
+        self.cycles = cycles;
+    }
+
+    fn visit(&self, node: usize, state: &mut [u8], stack: &mut Vec<usize>, cycles: &mut Vec<Vec<usize>>) {
+        state[node] = 1;
+        stack.push(node);
+        for &(from, to) in &self.edges {
+            if from != node { continue; }
+            match state[to] {
+                0 => self.visit(to, state, stack, cycles),
+                1 => {
+                    let start = stack.iter().position(|&n| n == to).unwrap_or(0);
+                    cycles.push(stack[start..].to_vec());
+                },
+                _ => {},
+            }
+        }
+        stack.pop();
+        state[node] = 2;
+    }
+
+    // Vrai si l'arête fait partie d'un cycle détecté
+    pub fn in_cycle(&self, from: usize, to: usize) -> bool {
+        self.cycles.iter().any(|cycle| {
+            (0..cycle.len()).any(|i| cycle[i] == from && cycle[(i + 1) % cycle.len()] == to)
+        })
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph imports {\n    rankdir=LR;\n    node [shape=box];\n");
+
+        for (name, members) in self.packages() {
+            out.push_str(&format!("    subgraph \"cluster_{}\" {{\n        label=\"package {}\";\n", name, name));
+            for id in members {
+                out.push_str(&format!("        {}\n", self.dot_node(id)));
+            }
+            out.push_str("    }\n");
+        }
+        for (id, module) in self.modules.iter().enumerate() {
+            if module.package.is_none() {
+                out.push_str(&format!("    {}\n", self.dot_node(id)));
+            }
+        }
+        for name in &self.unused_packages {
+            out.push_str(&format!("    \"package:{}\" [label=\"package {} (not imported)\", style=dotted];\n", name, name));
+        }
+
+        for &(from, to) in &self.edges {
+            let style = if self.in_cycle(from, to) { " [color=red]" } else { "" };
+            out.push_str(&format!("    n{} -> n{}{};\n", from, to, style));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph LR\n");
+
+        for (name, members) in self.packages() {
+            out.push_str(&format!("    subgraph package_{}[\"package {}\"]\n", mermaid_id(&name), name));
+            for id in members {
+                out.push_str(&format!("        {}\n", self.mermaid_node(id)));
+            }
+            out.push_str("    end\n");
+        }
+        for (id, module) in self.modules.iter().enumerate() {
+            if module.package.is_none() {
+                out.push_str(&format!("    {}\n", self.mermaid_node(id)));
+            }
+        }
+        for name in &self.unused_packages {
+            out.push_str(&format!("    package_{}[\"package {} (not imported)\"]\n", mermaid_id(name), name));
+        }
+
+        let mut cycle_links = Vec::new();
+        for (i, &(from, to)) in self.edges.iter().enumerate() {
+            out.push_str(&format!("    n{} --> n{}\n", from, to));
+            if self.in_cycle(from, to) {
+                cycle_links.push(i.to_string());
+            }
+        }
+        if !cycle_links.is_empty() {
+            out.push_str(&format!("    linkStyle {} stroke:red\n", cycle_links.join(",")));
+        }
+        out
+    }
+
+    // Paquets importés et leurs modules, par ordre de première apparition
+    fn packages(&self) -> Vec<(String, Vec<usize>)> {
+        let mut packages: Vec<(String, Vec<usize>)> = Vec::new();
+        for (id, module) in self.modules.iter().enumerate() {
+            let Some(name) = &module.package else { continue };
+            match packages.iter_mut().find(|(n, _)| n == name) {
+                Some((_, members)) => members.push(id),
+                None => packages.push((name.clone(), vec![id])),
+            }
+        }
+        packages
+    }
+
+    fn label(&self, id: usize) -> String {
+        let module = &self.modules[id];
+        match module.status {
+            ModuleStatus::Ok => module.path.clone(),
+            ModuleStatus::Missing => format!("{} (missing)", module.path),
+            ModuleStatus::Invalid(_) => format!("{} (syntax error)", module.path),
+        }
+    }
+
+    fn dot_node(&self, id: usize) -> String {
+        let style = if self.modules[id].status == ModuleStatus::Ok { "" } else { ", style=dashed" };
+        format!("n{} [label=\"{}\"{}];", id, self.label(id).replace('"', "\\\""), style)
+    }
+
+    fn mermaid_node(&self, id: usize) -> String {
+        format!("n{}[\"{}\"]", id, self.label(id).replace('"', "#quot;"))
+    }
+}
+
+// Chemins d'import d'un AST JSON, imports paresseux (dans une fonction) compris
+fn collect_imports(json: &JsonValue, paths: &mut Vec<String>) {
+    let JsonValue::Array(items) = json else { return };
+    // ["import", ligne, chemin, ...] ou ["from_import", ligne, chemin, noms]
+    let kind = items.first().and_then(|k| k.as_str());
+    if let (Some("import" | "from_import"), Some(JsonValue::Number(_)), Some(JsonValue::String(path))) = (kind, items.get(1), items.get(2)) {
+        paths.push(path.clone());
+        return;
+    }
+    for item in items {
+        collect_imports(item, paths);
+    }
+}
+
+// `./lib/a.aeg` et `lib/a.aeg` désignent le même module
+pub(crate) fn normalize(path: &str) -> String {
+    let mut path = path.replace('\\', "/");
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest.to_string();
+    }
+    path
+}
+
+// Section [dependencies] du aegis.toml du dossier courant
+fn declared_dependencies() -> Vec<String> {
+    let Ok(content) = fs::read_to_string("aegis.toml") else { return Vec::new() };
+    let Ok(config) = content.parse::<toml::Table>() else { return Vec::new() };
+    let mut names: Vec<String> = config.get("dependencies")
+        .and_then(|deps| deps.as_table())
+        .map(|deps| deps.keys().cloned().collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+fn mermaid_id(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect()
+}
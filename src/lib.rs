@@ -1,4 +1,6 @@
 pub mod ast;
+pub mod checker;
+pub mod typecheck;
 pub mod compiler;
 pub mod loader;
 pub mod native;
@@ -8,5 +10,13 @@ pub mod vm;
 pub mod chunk;
 pub mod opcode;
 pub mod package_manager;
+pub mod graph;
+pub mod project;
+pub mod test_runner;
+pub mod bench;
+pub mod explain;
+pub mod bundle;
 
-pub use ast::{Value, NativeFn};
+pub use ast::{Value, NativeFn, Str, TypedArray};
+pub use native::{Args, Commands, NativeClass, NativeError};
+pub use plugins::{PluginContext, PluginInfo, PLUGIN_ABI_VERSION};
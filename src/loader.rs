@@ -1,12 +1,18 @@
 use serde_json::Value as JsonValue;
-use crate::ast::{ClassDefinition, Expression, Instruction, Statement, Value, nodes::{ClassField, ClassProperty, InterfaceDefinition, InterfaceMethod}, value::Visibility};
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use crate::ast::{ClassDefinition, CompoundOp, Expression, Instruction, Statement, TypeExpr, Value, DictMap, ValueKey, nodes::{ClassField, ClassProperty, InterfaceDefinition, InterfaceMethod}, value::Visibility};
+use std::{cell::RefCell, rc::Rc};
 
 pub fn parse_block(block_json: &JsonValue) -> Result<Vec<Statement>, String> {
     let array = block_json.as_array().ok_or("Block must be a JSON array")?;
     array.iter().map(|instr| parse_statement_json(instr)).collect()
 }
 
+// Élément d'un nœud JSON, `null` s'il manque (un AST tronqué donne une erreur, pas un panic)
+fn field(array: &[JsonValue], index: usize) -> &JsonValue {
+    static MISSING: JsonValue = JsonValue::Null;
+    array.get(index).unwrap_or(&MISSING)
+}
+
 fn parse_visibility(v: &str) -> Visibility {
     match v {
         "private" => Visibility::Private,
@@ -15,6 +21,45 @@ fn parse_visibility(v: &str) -> Visibility {
     }
 }
 
+fn parse_compound_op(json: &JsonValue) -> Result<CompoundOp, String> {
+    match json.as_str() {
+        Some("+") => Ok(CompoundOp::Add),
+        Some("-") => Ok(CompoundOp::Sub),
+        Some("*") => Ok(CompoundOp::Mul),
+        Some("/") => Ok(CompoundOp::Div),
+        _ => Err(format!("Unknown compound assignment operator: {}", json)),
+    }
+}
+
+// Paramètres d'une fonction : noms simples ou [nom, type, défaut?, rest?]. Renvoie la liste
+// (nom, type) et le corps précédé des DefaultParam / RestParam de la signature.
+type Params = (Vec<(String, Option<String>)>, Vec<Statement>);
+
+fn parse_params(params_json: &JsonValue, body: Vec<Statement>, line: usize) -> Result<Params, String> {
+    let mut params = Vec::new();
+    let mut prologue = Vec::new();
+    for p in params_json.as_array().ok_or("Invalid params array")? {
+        if let Some(name) = p.as_str() {
+            params.push((name.to_string(), None));
+        } else if let Some(row) = p.as_array() {
+            let name = field(row, 0).as_str().ok_or("Expected a string")?.to_string();
+            let typ = field(row, 1).as_str().map(|s| s.to_string());
+            let is_rest = field(row, 3).as_bool().unwrap_or(false);
+            // [nom, type, défaut] : la présence du 3e élément marque le défaut, qui peut être
+            // le littéral `null` (JSON null)
+            if row.len() == 3 {
+                let default = parse_expression(field(row, 2))?;
+                prologue.push(Statement { kind: Instruction::DefaultParam(name.clone(), default), line });
+            }
+            if is_rest {
+                prologue.push(Statement { kind: Instruction::RestParam(name.clone()), line });
+            }
+            params.push((name, typ));
+        }
+    }
+    Ok((params, crate::ast::nodes::with_param_prologue(body, prologue)))
+}
+
 fn json_to_value(json: &JsonValue) -> Result<Value, String> {
     match json {
         JsonValue::Number(n) => {
@@ -22,7 +67,7 @@ fn json_to_value(json: &JsonValue) -> Result<Value, String> {
             else if n.is_f64() { Ok(Value::Float(n.as_f64().unwrap())) }
             else { Ok(Value::Integer(n.as_i64().unwrap_or(0))) }
         },
-        JsonValue::String(s) => Ok(Value::String(s.clone())),
+        JsonValue::String(s) => Ok(Value::String(s.clone().into())),
         JsonValue::Bool(b) => Ok(Value::Boolean(*b)),
         JsonValue::Null => Ok(Value::Null),
         JsonValue::Array(arr) => {
@@ -31,8 +76,8 @@ fn json_to_value(json: &JsonValue) -> Result<Value, String> {
             Ok(Value::List(Rc::new(RefCell::new(list))))
         },
         JsonValue::Object(map) => {
-            let mut dict = HashMap::new();
-            for (k, v) in map { dict.insert(k.clone(), json_to_value(v)?); }
+            let mut dict = DictMap::new();
+            for (k, v) in map { dict.insert(k.as_str().into(), json_to_value(v)?); }
             Ok(Value::Dict(Rc::new(RefCell::new(dict))))
         }
     }
@@ -42,23 +87,24 @@ pub fn parse_expression(json_expr: &JsonValue) -> Result<Expression, String> {
     if let Some(array) = json_expr.as_array() {
         if array.is_empty() { return Ok(Expression::Literal(Value::List(Rc::new(RefCell::new(vec![]))))); }
         
-        if let Some(cmd_name) = array[0].as_str() {
+        if let Some(cmd_name) = field(array, 0).as_str() {
             match cmd_name {
                 // --- Variables ---
                 "get" => {
-                    let name = array[1].as_str().ok_or("Var name missing")?;
+                    let name = field(array, 1).as_str().ok_or("Var name missing")?;
                     Ok(Expression::Variable(name.to_string()))
                 },
 
                 // --- Logique ---
-                "&&" => Ok(Expression::And(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "||" => Ok(Expression::Or(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "!" => Ok(Expression::Not(Box::new(parse_expression(&array[1])?))),
+                "&&" => Ok(Expression::And(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "||" => Ok(Expression::Or(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "!" => Ok(Expression::Not(Box::new(parse_expression(field(array, 1))?))),
+                "await" => Ok(Expression::Await(Box::new(parse_expression(field(array, 1))?))),
                 "?" => {
                     // ["?", cond, true, false]
-                    let cond = parse_expression(&array[1])?;
-                    let then_branch = parse_expression(&array[2])?;
-                    let else_branch = parse_expression(&array[3])?;
+                    let cond = parse_expression(field(array, 1))?;
+                    let then_branch = parse_expression(field(array, 2))?;
+                    let else_branch = parse_expression(field(array, 3))?;
                     
                     Ok(Expression::Ternary(
                         Box::new(cond),
@@ -67,70 +113,72 @@ pub fn parse_expression(json_expr: &JsonValue) -> Result<Expression, String> {
                     ))
                 },
                 "??" => {
-                    let left = parse_expression(&array[2])?;
-                    let right = parse_expression(&array[3])?;
+                    let left = parse_expression(field(array, 2))?;
+                    let right = parse_expression(field(array, 3))?;
                     Ok(Expression::NullCoalescing(Box::new(left), Box::new(right)))
                 },
                 
                 // --- Comparaison ---
-                "==" => Ok(Expression::Equal(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "!=" => Ok(Expression::NotEqual(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "<" => Ok(Expression::LessThan(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                ">" => Ok(Expression::GreaterThan(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "<=" => Ok(Expression::LessEqual(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                ">=" => Ok(Expression::GreaterEqual(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
+                "==" => Ok(Expression::Equal(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "!=" => Ok(Expression::NotEqual(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "===" => Ok(Expression::Same(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "!==" => Ok(Expression::NotSame(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "<" => Ok(Expression::LessThan(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                ">" => Ok(Expression::GreaterThan(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "<=" => Ok(Expression::LessEqual(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                ">=" => Ok(Expression::GreaterEqual(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
                 
                 // --- Arithmétique ---
-                "+" => Ok(Expression::Add(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
+                "+" => Ok(Expression::Add(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
                 "-" => {
-                     Ok(Expression::Sub(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?)))
+                     Ok(Expression::Sub(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?)))
                 },
-                "*" => Ok(Expression::Mul(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "/" => Ok(Expression::Div(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "%" => Ok(Expression::Modulo(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
+                "*" => Ok(Expression::Mul(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "/" => Ok(Expression::Div(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "~/" => Ok(Expression::FloorDiv(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "%" => Ok(Expression::Modulo(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
                 
                 // --- Bitwise ---
-                "&" => Ok(Expression::BitAnd(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "|" => Ok(Expression::BitOr(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "^" => Ok(Expression::BitXor(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                "<<" => Ok(Expression::ShiftLeft(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
-                ">>" => Ok(Expression::ShiftRight(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
+                "&" => Ok(Expression::BitAnd(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "|" => Ok(Expression::BitOr(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "^" => Ok(Expression::BitXor(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                "<<" => Ok(Expression::ShiftLeft(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
+                ">>" => Ok(Expression::ShiftRight(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
 
                 // --- Structures & OOP ---
                 "make_list" => Ok(Expression::List(array[1..].iter().map(parse_expression).collect::<Result<_,_>>()?)),
+                "interpolate" => Ok(Expression::Interpolation(array[1..].iter().map(parse_expression).collect::<Result<_,_>>()?)),
                 "make_dict" => {
                     let mut entries = Vec::new();
                     for entry in &array[1..] {
                         let arr = entry.as_array().ok_or("Dict entry array")?;
-                        let k = arr[0].as_str().ok_or("Key string")?.to_string();
-                        let v = parse_expression(&arr[1])?;
+                        let k = match field(arr, 0) {
+                            JsonValue::String(s) => ValueKey::String(s.clone().into()),
+                            JsonValue::Bool(b) => ValueKey::Boolean(*b),
+                            key => ValueKey::Integer(key.as_i64().ok_or("Dict key")?),
+                        };
+                        let v = parse_expression(field(arr, 1))?;
                         entries.push((k, v));
                     }
                     Ok(Expression::Dict(entries))
                 },
                 "new" => {
-                    let class_name_expr = parse_expression(&array[1])?;
-                    let args_json = &array[2..];
+                    let class_name_expr = parse_expression(field(array, 1))?;
+                    let args_json = array.get(2..).unwrap_or_default();
                     let args = args_json.iter().map(parse_expression).collect::<Result<_,_>>()?;
                     Ok(Expression::New(Box::new(class_name_expr), args))
                 },
-                "get_attr" => Ok(Expression::GetAttr(Box::new(parse_expression(&array[1])?), array[2].as_str().ok_or("Attr")?.to_string())),
+                "get_attr" => Ok(Expression::GetAttr(Box::new(parse_expression(field(array, 1))?), field(array, 2).as_str().ok_or("Attr")?.to_string())),
+                "get_index" => Ok(Expression::GetIndex(Box::new(parse_expression(field(array, 1))?), Box::new(parse_expression(field(array, 2))?))),
                 
                 // --- Fonctions ---
                 "lambda" => {
-                    let params_json = array[1].as_array().ok_or("Params array")?;
-                    let mut params = Vec::new();
-                    for p in params_json {
-                        if let Some(name) = p.as_str() {
-                            params.push((name.to_string(), None));
-                        } else if let Some(pair) = p.as_array() {
-                            let name = pair[0].as_str().unwrap().to_string();
-                            let typ = pair[1].as_str().map(|s| s.to_string());
-                            params.push((name, typ));
-                        }
-                    }
-                    let body = parse_block(&array[2])?;
-                    Ok(Expression::Function { params, ret_type: None, body })
+                    let body = parse_block(field(array, 2))?;
+                    // Une expression n'a pas de ligne : celle du corps
+                    let line = body.first().map_or(0, |s| s.line);
+                    let (params, body) = parse_params(field(array, 1), body, line)?;
+                    let name = field(array, 3).as_str().map(|s| s.to_string());
+                    Ok(Expression::Function { params, ret_type: None, body, name })
                 },
 
                 // --- GESTION ROBUSTE DES APPELS (AVEC OU SANS LIGNE) ---
@@ -140,8 +188,8 @@ pub fn parse_expression(json_expr: &JsonValue) -> Result<Expression, String> {
                     // Sans Ligne: ["call", TARGET, ARGS]       -> Len 3
                     let (target_idx, args_idx) = if array.len() == 4 { (2, 3) } else { (1, 2) };
                     
-                    let target = parse_expression(&array[target_idx])?;
-                    let args_arr = array[args_idx].as_array().ok_or("Call: Args array missing")?;
+                    let target = parse_expression(field(array, target_idx))?;
+                    let args_arr = field(array, args_idx).as_array().ok_or("Call: Args array missing")?;
                     let args = args_arr.iter().map(parse_expression).collect::<Result<_,_>>()?;
                     
                     Ok(Expression::Call(Box::new(target), args))
@@ -152,9 +200,9 @@ pub fn parse_expression(json_expr: &JsonValue) -> Result<Expression, String> {
                     // Sans Ligne: ["call_method", OBJ, METHOD, ARGS]       -> Len 4
                     let (obj_idx, method_idx, args_idx) = if array.len() == 5 { (2, 3, 4) } else { (1, 2, 3) };
 
-                    let obj = parse_expression(&array[obj_idx])?;
-                    let method = array[method_idx].as_str().ok_or("CallMethod: Method name missing")?.to_string();
-                    let args_arr = array[args_idx].as_array().ok_or("CallMethod: Args array missing")?;
+                    let obj = parse_expression(field(array, obj_idx))?;
+                    let method = field(array, method_idx).as_str().ok_or("CallMethod: Method name missing")?.to_string();
+                    let args_arr = field(array, args_idx).as_array().ok_or("CallMethod: Args array missing")?;
                     let args = args_arr.iter().map(parse_expression).collect::<Result<_,_>>()?;
                     
                     Ok(Expression::CallMethod(Box::new(obj), method, args))
@@ -165,16 +213,16 @@ pub fn parse_expression(json_expr: &JsonValue) -> Result<Expression, String> {
                     // Sans Ligne: ["super_call", METHOD, ARGS]       -> Len 3
                     let (method_idx, args_idx) = if array.len() == 4 { (2, 3) } else { (1, 2) };
 
-                    let method = array[method_idx].as_str().ok_or("SuperCall: Method name missing")?.to_string();
-                    let args_arr = array[args_idx].as_array().ok_or("SuperCall: Args array missing")?;
+                    let method = field(array, method_idx).as_str().ok_or("SuperCall: Method name missing")?.to_string();
+                    let args_arr = field(array, args_idx).as_array().ok_or("SuperCall: Args array missing")?;
                     let args = args_arr.iter().map(parse_expression).collect::<Result<_,_>>()?;
                     
                     Ok(Expression::SuperCall(method, args))
                 },
 
                 "range" => {
-                    let start = parse_expression(&array[2])?;
-                    let end = parse_expression(&array[3])?;
+                    let start = parse_expression(field(array, 2))?;
+                    let end = parse_expression(field(array, 3))?;
                     // On peut créer un OpCode spécifique ou une Expression dédiée.
                     // Créons une Expression::Range dans ast/mod.rs d'abord si ce n'est pas fait.
                     Ok(Expression::Range(Box::new(start), Box::new(end)))
@@ -209,70 +257,86 @@ pub fn parse_expression(json_expr: &JsonValue) -> Result<Expression, String> {
 
 pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String> {
     let array = json_instr.as_array().ok_or("Instruction must be array")?;
-    let command = array[0].as_str().ok_or("Command must be string")?;
+    let command = field(array, 0).as_str().ok_or("Command must be string")?;
     
     // Le 2ème élément est la ligne
-    let line = array[1].as_u64().ok_or("Line number missing (Check Parser)")? as usize;
+    let line = field(array, 1).as_u64().ok_or("Line number missing (Check Parser)")? as usize;
 
     let instruction = match command {
         "set" => {
-            let name = array[2].as_str().unwrap().to_string();
-            let type_annot = array[3].as_str().map(|s| s.to_string());
-            let expr = parse_expression(&array[4])?;
+            let name = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            let type_annot = field(array, 3).as_str().map(|s| s.to_string());
+            let expr = parse_expression(field(array, 4))?;
             Ok(Instruction::Set(name, type_annot, expr)) 
         },
+        "assign" => {
+            let name = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            Ok(Instruction::Assign(name, parse_expression(field(array, 3))?))
+        },
         "set_attr" => {
-            let obj = parse_expression(&array[2])?;
-            let attr = array[3].as_str().unwrap().to_string();
-            let val = parse_expression(&array[4])?;
+            let obj = parse_expression(field(array, 2))?;
+            let attr = field(array, 3).as_str().ok_or("Expected a string")?.to_string();
+            let val = parse_expression(field(array, 4))?;
             Ok(Instruction::SetAttr(Box::new(obj), attr, val))
         },
-        "print" => Ok(Instruction::Print(parse_expression(&array[2])?)),
+        "set_index" => {
+            let obj = parse_expression(field(array, 2))?;
+            let index = parse_expression(field(array, 3))?;
+            let val = parse_expression(field(array, 4))?;
+            Ok(Instruction::SetIndex(Box::new(obj), index, val))
+        },
+        "compound_set_attr" => {
+            let obj = parse_expression(field(array, 2))?;
+            let attr = field(array, 3).as_str().ok_or("Expected a string")?.to_string();
+            let op = parse_compound_op(field(array, 4))?;
+            let val = parse_expression(field(array, 5))?;
+            Ok(Instruction::CompoundSetAttr(Box::new(obj), attr, op, val))
+        },
+        "compound_set_index" => {
+            let obj = parse_expression(field(array, 2))?;
+            let index = parse_expression(field(array, 3))?;
+            let op = parse_compound_op(field(array, 4))?;
+            let val = parse_expression(field(array, 5))?;
+            Ok(Instruction::CompoundSetIndex(Box::new(obj), index, op, val))
+        },
+        "print" => Ok(Instruction::Print(parse_expression(field(array, 2))?)),
         "input" => {
-            let var = array[2].as_str().unwrap().to_string();
-            let prompt = parse_expression(&array[3])?;
+            let var = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            let prompt = parse_expression(field(array, 3))?;
             Ok(Instruction::Input(var, prompt))
         },
         "if" => {
             Ok(Instruction::If { 
-                condition: parse_expression(&array[2])?, 
-                body: parse_block(&array[3])?, 
-                else_body: if array.len() > 4 { parse_block(&array[4])? } else { vec![] }
+                condition: parse_expression(field(array, 2))?, 
+                body: parse_block(field(array, 3))?, 
+                else_body: if array.len() > 4 { parse_block(field(array, 4))? } else { vec![] }
             })
         },
-        "while" => Ok(Instruction::While { condition: parse_expression(&array[2])?, body: parse_block(&array[3])? }),
+        "while" => Ok(Instruction::While { condition: parse_expression(field(array, 2))?, body: parse_block(field(array, 3))? }),
         
-        "return" => Ok(Instruction::Return(parse_expression(&array[2])?)),
+        "return" => Ok(Instruction::Return(parse_expression(field(array, 2))?)),
         
         "call" | "call_method" | "super_call" => {
             // Ici, parse_expression va gérer le format imbriqué
             Ok(Instruction::ExpressionStatement(parse_expression(json_instr)?))
         },
+
+        "expr" => Ok(Instruction::ExpressionStatement(parse_expression(field(array, 2))?)),
         
         "function" => {
-            let name = array[2].as_str().unwrap().to_string();
-            let params_json = array[3].as_array().unwrap();
-            let mut params = Vec::new();
-            for p in params_json {
-                if let Some(s) = p.as_str() {
-                    params.push((s.to_string(), None));
-                } else if let Some(pair) = p.as_array() {
-                    let n = pair[0].as_str().unwrap().to_string();
-                    let t = pair[1].as_str().map(|s| s.to_string());
-                    params.push((n, t));
-                }
-            }
-            let ret_type = array[4].as_str().map(|s| s.to_string());
-            let body = parse_block(&array[5])?;
-            Ok(Instruction::Function { name, params, ret_type, body })
+            let name = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            let ret_type = field(array, 4).as_str().map(|s| s.to_string());
+            let (params, body) = parse_params(field(array, 3), parse_block(field(array, 5))?, line)?;
+            let is_async = array.get(6).and_then(|a| a.as_bool()).unwrap_or(false);
+            Ok(Instruction::Function { name, params, ret_type, body, is_async })
         },
 
         "interface" => {
-            let _line = array[1].as_u64().unwrap() as usize;
-            let name = array[2].as_str().unwrap().to_string();
+            let _line = field(array, 1).as_u64().ok_or("Line number missing")? as usize;
+            let name = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
             
             // On vérifie que le tableau de méthodes est valide
-            let methods_arr = array[3].as_array().ok_or("Invalid interface methods array")?;
+            let methods_arr = field(array, 3).as_array().ok_or("Invalid interface methods array")?;
             
             let mut methods = Vec::new();
             
@@ -280,19 +344,19 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
                 let m_data = m.as_array().ok_or("Invalid method data")?;
                 
                 // Index 0 : Le nom de la méthode
-                let m_name = m_data[0].as_str().ok_or("Invalid method name")?.to_string();
+                let m_name = field(m_data, 0).as_str().ok_or("Invalid method name")?.to_string();
                 
                 // Index 1 : Les paramètres (C'est ici que tu avais l'erreur "Invalid params array")
-                let params_json = m_data[1].as_array().ok_or("Invalid params array")?;
+                let params_json = field(m_data, 1).as_array().ok_or("Invalid params array")?;
                 
                 let mut params = Vec::new();
                 for p in params_json {
                     // Chaque paramètre est un tableau [nom, type] ou [nom, null]
                     let p_arr = p.as_array().ok_or("Invalid param structure")?;
-                    let p_name = p_arr[0].as_str().unwrap().to_string();
+                    let p_name = field(p_arr, 0).as_str().ok_or("Expected a string")?.to_string();
                     
-                    let p_type = if p_arr.len() > 1 && !p_arr[1].is_null() {
-                        Some(p_arr[1].as_str().unwrap().to_string())
+                    let p_type = if p_arr.len() > 1 && !field(p_arr, 1).is_null() {
+                        Some(field(p_arr, 1).as_str().ok_or("Expected a string")?.to_string())
                     } else {
                         None
                     };
@@ -308,10 +372,10 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
         "class" => {
             // ["class", line, name, methods, parent, fields, visibilities]
             
-            let name = array[2].as_str().ok_or("Invalid class name")?.to_string();
+            let name = field(array, 2).as_str().ok_or("Invalid class name")?.to_string();
             
             // 1. Parsing des Méthodes (Adapté à ta HashMap)
-            let methods_map_json = array[3].as_object().ok_or("Invalid methods object")?;
+            let methods_map_json = field(array, 3).as_object().ok_or("Invalid methods object")?;
             let mut methods = std::collections::HashMap::new();
             
             for (m_name, m_data) in methods_map_json {
@@ -319,30 +383,19 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
                 
                 // JSON attendu : [params, body, is_static]
                 
-                // A. Params
-                let params_arr = m_arr[0].as_array().ok_or("Invalid params array")?;
-                let mut params = Vec::new();
-                for p in params_arr {
-                    if let Some(p_row) = p.as_array() {
-                         let p_name = p_row[0].as_str().unwrap().to_string();
-                         let p_type = if p_row.len() > 1 && !p_row[1].is_null() {
-                             Some(p_row[1].as_str().unwrap().to_string())
-                         } else { None };
-                         params.push((p_name, p_type));
-                    }
-                }
-                
-                // B. Body
-                let body = parse_block(&m_arr[1])?;
+                // A. Params et B. Body (précédé des valeurs par défaut de la signature)
+                let body = parse_block(field(m_arr, 1))?;
+                let method_line = body.first().map_or(line, |s| s.line);
+                let (params, body) = parse_params(field(m_arr, 0), body, method_line)?;
 
                 // C. Static (NOUVEAU)
                 // Si l'élément 2 existe et est true, c'est statique.
                 let is_static = if m_arr.len() > 2 {
-                    m_arr[2].as_bool().unwrap_or(false)
+                    field(m_arr, 2).as_bool().unwrap_or(false)
                 } else { false };
 
                 let is_final = if m_arr.len() > 3 {
-                    m_arr[3].as_bool().unwrap_or(false)
+                    field(m_arr, 3).as_bool().unwrap_or(false)
                 } else { false };
                 
                 // On insère le tuple (params, body, is_static)
@@ -350,10 +403,10 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
             }
 
             // 2. Parsing du Parent
-            let parent = if array[4].is_null() {
+            let parent = if field(array, 4).is_null() {
                 None
             } else {
-                Some(array[4].as_str().unwrap().to_string())
+                Some(field(array, 4).as_str().ok_or("Expected a string")?.to_string())
             };
 
             // 3. Parsing des Champs (Fields)
@@ -361,23 +414,23 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
             let mut properties = Vec::new();
 
             if array.len() > 5 {
-                if let Some(members_arr) = array[5].as_array() {
+                if let Some(members_arr) = field(array, 5).as_array() {
                     for m in members_arr {
                         let m_data = m.as_array().ok_or("Invalid member struct")?;
-                        let kind = m_data[0].as_str().ok_or("Invalid member kind")?;
+                        let kind = field(m_data, 0).as_str().ok_or("Invalid member kind")?;
                         // JSON: ["field", name, vis_str, default_val]
                         
                         if kind == "field" {
-                            let f_name = m_data[1].as_str().unwrap().to_string();
-                            let f_vis_str = m_data[2].as_str().unwrap();
-                            let default_expr = parse_expression(&m_data[3])?;
+                            let f_name = field(m_data, 1).as_str().ok_or("Expected a string")?.to_string();
+                            let f_vis_str = field(m_data, 2).as_str().ok_or("Expected a string")?;
+                            let default_expr = parse_expression(field(m_data, 3))?;
 
                             let is_static = if m_data.len() > 4 {
-                                m_data[4].as_bool().unwrap_or(false)
+                                field(m_data, 4).as_bool().unwrap_or(false)
                             } else { false };
 
-                            let type_annot = if m_data.len() > 5 && !m_data[5].is_null() {
-                                Some(m_data[5].as_str().unwrap().to_string())
+                            let type_annot = if m_data.len() > 5 && !field(m_data, 5).is_null() {
+                                Some(field(m_data, 5).as_str().ok_or("Expected a string")?.to_string())
                             } else {
                                 None
                             };
@@ -392,33 +445,33 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
                         }
                         else if kind == "prop" {
                             // ["prop", name, vis, is_static, getter, setter]
-                            let p_name = m_data[1].as_str().unwrap().to_string();
-                            let p_vis_str = m_data[2].as_str().unwrap();
-                            let is_static = m_data[3].as_bool().unwrap_or(false);
+                            let p_name = field(m_data, 1).as_str().ok_or("Expected a string")?.to_string();
+                            let p_vis_str = field(m_data, 2).as_str().ok_or("Expected a string")?;
+                            let is_static = field(m_data, 3).as_bool().unwrap_or(false);
                             
                             // Parsing Getter
-                            let getter_data = if !m_data[4].is_null() {
-                                let g_arr = m_data[4].as_array().unwrap();
-                                // g_arr[0] est params (vide), g_arr[1] est body
-                                let body = parse_block(&g_arr[1])?;
+                            let getter_data = if !field(m_data, 4).is_null() {
+                                let g_arr = field(m_data, 4).as_array().ok_or("Expected an array")?;
+                                // field(g_arr, 0) est params (vide), field(g_arr, 1) est body
+                                let body = parse_block(field(g_arr, 1))?;
                                 Some((vec![], body))
                             } else { None };
                             
                             // Parsing Setter
-                            let setter_data = if !m_data[5].is_null() {
-                                let s_arr = m_data[5].as_array().unwrap();
-                                let params_json = s_arr[0].as_array().unwrap();
+                            let setter_data = if !field(m_data, 5).is_null() {
+                                let s_arr = field(m_data, 5).as_array().ok_or("Expected an array")?;
+                                let params_json = field(s_arr, 0).as_array().ok_or("Expected an array")?;
                                 
                                 // On parse les params du setter (ex: [val])
                                 let mut params = Vec::new();
                                 for p in params_json {
                                     if let Some(p_row) = p.as_array() {
-                                         let p_name = p_row[0].as_str().unwrap().to_string();
-                                         let p_type = if p_row.len() > 1 && !p_row[1].is_null() { Some(p_row[1].as_str().unwrap().to_string()) } else { None };
+                                         let p_name = field(p_row, 0).as_str().ok_or("Expected a string")?.to_string();
+                                         let p_type = if p_row.len() > 1 && !field(p_row, 1).is_null() { Some(field(p_row, 1).as_str().ok_or("Expected a string")?.to_string()) } else { None };
                                          params.push((p_name, p_type));
                                     }
                                 }
-                                let body = parse_block(&s_arr[1])?;
+                                let body = parse_block(field(s_arr, 1))?;
                                 Some((params, body))
                             } else { None };
 
@@ -437,7 +490,7 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
             // 4. Parsing des Visibilités (Visibilities) - NOUVEAU
             let mut visibilities = std::collections::HashMap::new();
             if array.len() > 6 {
-                if let Some(vis_obj) = array[6].as_object() {
+                if let Some(vis_obj) = field(array, 6).as_object() {
                     for (key, val) in vis_obj {
                         let vis_str = val.as_str().unwrap_or("public");
                         visibilities.insert(key.clone(), parse_visibility(vis_str));
@@ -446,13 +499,13 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
             }
 
             let is_class_final = if array.len() > 7 {
-                array[7].as_bool().unwrap_or(false)
+                field(array, 7).as_bool().unwrap_or(false)
             } else { false };
 
-            let interfaces_json = if array.len() > 8 { array[8].as_array() } else { None };
+            let interfaces_json = if array.len() > 8 { field(array, 8).as_array() } else { None };
             let mut interfaces = Vec::new();
             if let Some(arr) = interfaces_json {
-                for v in arr { interfaces.push(v.as_str().unwrap().to_string()); }
+                for v in arr { interfaces.push(v.as_str().ok_or("Expected a string")?.to_string()); }
             }
 
             Ok(Instruction::Class(ClassDefinition {
@@ -463,66 +516,118 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
                 properties,
                 visibilities,
                 is_final: is_class_final,
-                interfaces
+                interfaces,
+                doc: array.get(9).and_then(|d| d.as_str()).map(str::to_string),
             }))
         },
 
         "enum" => {
-            let name = array[2].as_str().unwrap().to_string();
-            let variants_arr = array[3].as_array().unwrap();
+            let name = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            let variants_arr = field(array, 3).as_array().ok_or("Expected an array")?;
             
             let variants: Vec<String> = variants_arr.iter()
-                .map(|v| v.as_str().unwrap().to_string())
-                .collect();
+                .map(|v| v.as_str().map(|s| s.to_string()).ok_or("Expected a string"))
+                .collect::<Result<_, _>>()?;
                 
             Ok(Instruction::Enum(name, variants))
         },
         
-        "import" => Ok(Instruction::Import(array[2].as_str().unwrap().to_string())),
+        "import" => {
+            let path = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            let alias = array.get(3).and_then(|a| a.as_str()).map(|a| a.to_string());
+            Ok(Instruction::Import(path, alias))
+        },
+
+        "from_import" => {
+            let path = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            let names_json = field(array, 3).as_array().ok_or("Expected an array")?;
+            let mut names = Vec::new();
+            for n in names_json {
+                let pair = n.as_array().ok_or("Expected an array")?;
+                let name = field(pair, 0).as_str().ok_or("Expected a string")?.to_string();
+                let alias = field(pair, 1).as_str().ok_or("Expected a string")?.to_string();
+                names.push((name, alias));
+            }
+            Ok(Instruction::FromImport(path, names))
+        },
         
         "switch" => {
-            let val = parse_expression(&array[2])?;
-            let cases_json = array[3].as_array().unwrap();
+            let val = parse_expression(field(array, 2))?;
+            let cases_json = field(array, 3).as_array().ok_or("Expected an array")?;
             let mut cases = Vec::new();
             for c in cases_json {
-                let c_arr = c.as_array().unwrap();
-                cases.push((parse_expression(&c_arr[0])?, parse_block(&c_arr[1])?));
+                let c_arr = c.as_array().ok_or("Expected an array")?;
+                cases.push((parse_expression(field(c_arr, 0))?, parse_block(field(c_arr, 1))?));
             }
-            let def = parse_block(&array[4])?;
+            let def = parse_block(field(array, 4))?;
             Ok(Instruction::Switch { value: val, cases, default: def })
         },
         
         "try" => {
-            Ok(Instruction::TryCatch { 
-                try_body: parse_block(&array[2])?, 
-                error_var: array[3].as_str().unwrap().to_string(), 
-                catch_body: parse_block(&array[4])? 
-            })
+            // ["try", line, body, [[var, type|null, body], ...]]
+            let mut catches = Vec::new();
+            for clause in field(array, 3).as_array().ok_or("Expected an array")? {
+                let clause = clause.as_array().ok_or("Expected an array")?;
+                catches.push((
+                    field(clause, 0).as_str().ok_or("Expected a string")?.to_string(),
+                    field(clause, 1).as_str().map(|s| s.to_string()),
+                    parse_block(field(clause, 2))?,
+                ));
+            }
+            Ok(Instruction::TryCatch { try_body: parse_block(field(array, 2))?, catches })
         },
 
-        "throw" => Ok(Instruction::Throw(parse_expression(&array[2])?)),
+        "throw" => Ok(Instruction::Throw(parse_expression(field(array, 2))?)),
+        "defer" => Ok(Instruction::Defer(parse_block(field(array, 2))?)),
+        "group" => Ok(Instruction::Group(parse_block(field(array, 2))?)),
+        "with" => {
+            Ok(Instruction::With {
+                resource: parse_expression(field(array, 2))?,
+                name: field(array, 3).as_str().ok_or("Expected a string")?.to_string(),
+                body: parse_block(field(array, 4))?,
+            })
+        },
         
         "namespace" => {
             Ok(Instruction::Namespace {
-                name: array[2].as_str().unwrap().to_string(),
-                body: parse_block(&array[3])?
+                name: field(array, 2).as_str().ok_or("Expected a string")?.to_string(),
+                body: parse_block(field(array, 3))?
             })
         },
         
         "break" => Ok(Instruction::Break),
 
+        "use" => Ok(Instruction::Use(field(array, 2).as_str().ok_or("Expected a string")?.to_string())),
+
         "continue" => Ok(Instruction::Continue),
 
+        "private" => {
+            let inner = parse_statement_json(field(array, 2))?;
+            Ok(Instruction::Private(Box::new(inner.kind)))
+        },
+
+        "export" => {
+            let inner = parse_statement_json(field(array, 2))?;
+            Ok(Instruction::Export(Box::new(inner.kind)))
+        },
+
+        "type_alias" => {
+            let name = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            let ty = TypeExpr::parse(field(array, 3).as_str().ok_or("Type alias must have a type")?)?;
+            Ok(Instruction::TypeAlias(name, ty))
+        },
+
         "const" => {
-            let name = array[2].as_str().unwrap().to_string();
-            let expr = parse_expression(&array[3])?;
-            Ok(Instruction::Const(name, expr))
+            let name = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            let expr = parse_expression(field(array, 3))?;
+            let type_annot = array.get(4).and_then(|t| t.as_str()).map(|s| s.to_string());
+            Ok(Instruction::Const(name, type_annot, expr))
         },
 
         "foreach" => {
-            let var_name = array[2].as_str().unwrap().to_string();
-            let iterable = parse_expression(&array[3])?;
-            let body = parse_block(&array[4])?;
+            let var_name = field(array, 2).as_str().ok_or("Expected a string")?.to_string();
+            let iterable = parse_expression(field(array, 3))?;
+            let body = parse_block(field(array, 4))?;
                     
             Ok(Instruction::ForEach(var_name, iterable, body))
         },
@@ -1,4 +1,4 @@
-use aegis_core::{compiler, loader, native, package_manager, plugins};
+use aegis_core::{bench, bundle, checker, compiler, explain, graph, loader, native, package_manager, plugins, project, test_runner, typecheck};
 use clap::{Parser, Subcommand};
 use rustyline::DefaultEditor;
 use serde::Deserialize;
@@ -7,6 +7,7 @@ use std::fs;
 use serde_json::Value as JsonValue;
 use std::path::Path;
 use aegis_core::vm::VM;
+use aegis_core::vm::compiler::ReplSession;
 
 #[derive(Parser)]
 #[command(name = "aegis")]
@@ -20,12 +21,48 @@ struct Cli {
 enum Commands {
     /// Exécute un script Aegis
     Run {
-        /// Le chemin du fichier .aeg
-        file: String,
+        /// Le chemin du fichier .aeg (par défaut, le point d'entrée du projet dans aegis.toml)
+        file: Option<String>,
 
         /// Affiche le bytecode généré avant l'exécution
         #[arg(long, short)]
         debug: bool,
+
+        /// Niveau d'optimisation (-O2 : inlining des fonctions triviales, propagation des constantes)
+        #[arg(short = 'O', long = "opt-level", default_value_t = 0)]
+        opt_level: u8,
+
+        /// Version du langage (2 : `/` entre deux entiers renvoie un Float)
+        #[arg(long = "lang-version", default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+        lang_version: u8,
+
+        /// Refuse les affectations `x = ...` à une variable jamais déclarée avec var/let
+        /// (sans ce mode, elles produisent un avertissement) et `+` entre une chaîne et une
+        /// autre valeur (convertir avec str(x)), et n'exécute pas un programme qui a des erreurs
+        /// de type (voir `aegis check`). Un fichier peut l'activer seul avec `use strict`
+        #[arg(long)]
+        strict: bool,
+
+        /// Affiche à la fin le nombre d'exécutions de chaque opcode, les fonctions les plus
+        /// coûteuses et des conseils d'optimisation (sur la sortie d'erreur)
+        #[arg(long)]
+        stats: bool,
+
+        /// Comme --stats, avec le temps passé dans chaque opcode (le chronométrage ralentit
+        /// le script : comparer les proportions plutôt que la durée totale)
+        #[arg(long)]
+        profile: bool,
+
+        /// Point d'arrêt (fichier.aeg:LIGNE, ou LIGNE dans le script) : l'exécution s'y arrête
+        /// et une invite permet d'inspecter les variables et d'avancer pas à pas (h pour l'aide)
+        #[arg(long = "break", value_name = "FILE:LINE")]
+        breakpoints: Vec<aegis_core::vm::debugger::Breakpoint>,
+
+        /// Appelle cette fonction après le chargement (par défaut `main`), avec les arguments
+        /// du script. Sa valeur de retour (entier de 0 à 255, ou null sans `return`) devient le
+        /// code de sortie.
+        #[arg(long, num_args = 0..=1, default_missing_value = "main")]
+        call: Option<String>,
         
         /// Arguments à passer au script (accessibles via System.args())
         /// Ils capturent tout ce qui se trouve après le nom du fichier ou "--"
@@ -33,17 +70,119 @@ enum Commands {
         args: Vec<String>,
     },
 
+    /// Analyse un script sans l'exécuter : suggestions et erreurs de type
+    Check {
+        /// Le chemin du fichier .aeg
+        file: String,
+    },
+
+    /// Lance les fonctions `test_*` des fichiers `*_test.aeg`, chacune dans sa propre VM
+    /// (code de sortie 1 si un test échoue)
+    Test {
+        /// Fichiers ou dossiers de test (par défaut : tests/, sinon le dossier courant)
+        paths: Vec<String>,
+        /// Ne lance que les tests dont le nom contient ce texte
+        #[arg(long)]
+        filter: Option<String>,
+    },
+
+    /// Mesure les programmes de benchmarks/ (et leurs versions Python / Node) et compare
+    /// les temps au dernier passage enregistré
+    Bench {
+        /// Programmes .aeg ou dossiers (par défaut : benchmarks/)
+        paths: Vec<String>,
+        /// Exécutions par programme (on garde le temps médian)
+        #[arg(long, short = 'n', default_value_t = 3)]
+        iterations: usize,
+        /// Niveau d'optimisation passé à `aegis run`
+        #[arg(short = 'O', long = "opt-level", default_value_t = 0)]
+        opt_level: u8,
+        /// Ne lance pas les versions des autres langages
+        #[arg(long)]
+        no_compare: bool,
+        /// Ajoute les résultats à l'historique
+        #[arg(long)]
+        save: bool,
+        /// Fichier d'historique
+        #[arg(long, default_value = bench::HISTORY)]
+        history: String,
+    },
+
+    /// Regroupe un script et les modules qu'il importe dans un fichier .aegb, lancé ensuite
+    /// par `aegis run app.aegb` sans les sources
+    Bundle {
+        /// Le point d'entrée .aeg (par défaut, celui de aegis.toml)
+        file: Option<String>,
+        /// release (-O2, sans table des lignes, compressé, stdlib utilisée seulement), debug
+        /// (piles d'appels complètes) ou un profil défini par une section [profile.<nom>] de aegis.toml
+        #[arg(long, default_value = "debug")]
+        profile: String,
+        /// Fichier produit (par défaut : le nom du script avec l'extension .aegb)
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+
+    /// Affiche le graphe des imports d'un script (DOT ou Mermaid) et signale les cycles
+    Graph {
+        /// Le point d'entrée .aeg
+        file: String,
+        /// Format de sortie
+        #[arg(long, default_value = "dot", value_parser = ["dot", "mermaid"])]
+        format: String,
+    },
+
+    /// Liste les plugins natifs chargés (aegis.toml) et ce que chacun fournit
+    Plugins,
+
+    /// Explique un code d'erreur (ex: E0102) ; sans argument, liste tous les codes
+    Explain {
+        /// Le code d'erreur (E0102, e0102 ou 0102)
+        code: Option<String>,
+    },
+
     /// Lance le mode interactif (REPL)
-    Repl,
+    Repl {
+        /// Annule les modifications globales d'une ligne qui échoue
+        #[arg(long, short)]
+        transactional: bool,
+    },
+
+    /// Crée un nouveau projet dans le dossier <name> (aegis.toml, src/main.aeg, .gitignore)
+    New {
+        /// Nom du projet et du dossier créé
+        name: String,
+        /// Ajoute un dossier tests/ avec un exemple de test
+        #[arg(long)]
+        tests: bool,
+    },
+
+    /// Crée le squelette d'un projet dans le dossier courant
+    Init {
+        /// Ajoute un dossier tests/ avec un exemple de test
+        #[arg(long)]
+        tests: bool,
+    },
 
-    /// [APM] Installe un paquet depuis le registre
+    /// [APM] Ajoute un paquet et ses dépendances, et met à jour aegis.lock
     Add {
         /// Nom du paquet (ex: "glfw")
         name: String,
-        /// Version spécifique (optionnel)
+        /// Exigence de version semver (ex: "1.2", "~0.3", "=1.0.4")
         version: Option<String>,
     },
 
+    /// [APM] Installe les versions exactes de aegis.lock (le crée s'il n'existe pas)
+    Install,
+
+    /// [APM] Cherche des paquets dans le registre
+    Search {
+        /// Mots recherchés dans le nom et la description
+        query: String,
+        /// Affiche les résultats en JSON (pour les outils)
+        #[arg(long)]
+        json: bool,
+    },
+
     /// [APM] Publie le paquet courant
     Publish {
         /// Cible OS spécifique (ex: linux, windows)
@@ -63,7 +202,9 @@ enum Commands {
 
 #[derive(Deserialize)]
 struct ProjectConfig {
-    dependencies: Option<HashMap<String, String>>
+    dependencies: Option<HashMap<String, String>>,
+    // Natives fournies par des plugins dont le projet a besoin (vérifiées avant l'exécution)
+    requires_natives: Option<Vec<String>>,
 }
 
 #[derive(Deserialize)]
@@ -83,7 +224,7 @@ struct PackageInfo {
 // Charge les plugins natifs basés sur le fichier aegis.toml (Legacy support pour les DLLs locales)
 fn load_config() {
     if let Ok(content) = fs::read_to_string("aegis.toml") {
-        let config: ProjectConfig = toml::from_str(&content).unwrap_or_else(|_| ProjectConfig { dependencies: None });
+        let config: ProjectConfig = toml::from_str(&content).unwrap_or_else(|_| ProjectConfig { dependencies: None, requires_natives: None });
 
         if let Some(deps) = config.dependencies {
             for (name, _version_req) in deps {
@@ -106,6 +247,29 @@ fn load_config() {
     }
 }
 
+// Vérifie que les natives listées dans `requires_natives` ont bien été chargées
+fn check_required_natives() -> Result<(), String> {
+    let Ok(content) = fs::read_to_string("aegis.toml") else {
+        return Ok(());
+    };
+    let Ok(config) = toml::from_str::<ProjectConfig>(&content) else {
+        return Ok(());
+    };
+
+    for name in config.requires_natives.unwrap_or_default() {
+        if !native::is_available(&name) {
+            // "Gpu.draw" / "gpu_draw" -> plugin "Gpu" / "gpu"
+            let plugin = name.split(['.', '_']).next().unwrap_or(&name);
+            return Err(format!(
+                "Native '{}' is not available: plugin '{}' missing (required by aegis.toml)",
+                name, plugin
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 // Tente de trouver un .dll/.so dans le dossier du paquet
 fn resolve_library_path(path: &Path) -> Result<std::path::PathBuf, String> {
     // 1. Essayer via le manifest (si présent)
@@ -138,7 +302,21 @@ fn resolve_library_path(path: &Path) -> Result<std::path::PathBuf, String> {
     Err("Aucun binaire trouvé".into())
 }
 
-fn main() -> Result<(), String> {
+fn main() {
+    // Affichage brut (pas de Debug) : les erreurs d'exécution contiennent la pile d'appels sur plusieurs lignes
+    if let Err(e) = run_cli() {
+        match explain::classify(&e) {
+            Some(error) => {
+                eprintln!("Error[{}]: {}", error.code, e);
+                eprintln!("For more information about this error, try `aegis explain {}`.", error.code);
+            },
+            None => eprintln!("Error: {}", e),
+        }
+        std::process::exit(1);
+    }
+}
+
+fn run_cli() -> Result<(), String> {
     native::init_registry();
     
     // On charge les plugins natifs AVANT de lancer la VM
@@ -147,23 +325,101 @@ fn main() -> Result<(), String> {
     let cli = Cli::parse();
 
     match &cli.command {
-        Some(Commands::Run { file, debug, args }) => {
+        Some(Commands::Run { file, debug, opt_level, lang_version, strict, stats, profile, breakpoints, call, args }) => {
+            check_required_natives()?;
+
             // On passe les args (clonés pour ownership) à run_file
-            run_file(file, *debug, args.clone())
+            let options = RunOptions {
+                debug: *debug,
+                opt_level: *opt_level,
+                lang_version: *lang_version,
+                strict: *strict,
+                stats: *stats,
+                profile: *profile,
+                breakpoints: breakpoints.clone(),
+            };
+            let file = match file {
+                Some(file) => file.clone(),
+                None => project::entry_point(Path::new("."))?,
+            };
+            run_file(&file, options, call.as_deref(), args.clone())
+        }
+
+        Some(Commands::Check { file }) => check_file(file),
+
+        Some(Commands::Bundle { file, profile, output }) => {
+            let file = match file {
+                Some(file) => file.clone(),
+                None => project::entry_point(Path::new("."))?,
+            };
+            bundle_file(&file, profile, output.as_deref())
+        }
+
+        Some(Commands::Graph { file, format }) => graph_file(file, format),
+
+        Some(Commands::Explain { code }) => explain_code(code.as_deref()),
+
+        Some(Commands::Plugins) => {
+            list_plugins();
+            Ok(())
+        }
+
+        Some(Commands::Test { paths, filter }) => {
+            check_required_natives()?;
+
+            let files = test_runner::discover(paths)?;
+            if files.is_empty() {
+                return Err(format!("No test files found (files ending in {})", test_runner::TEST_SUFFIX));
+            }
+            if !test_runner::run(&files, filter.as_deref()).success() {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+
+        Some(Commands::Bench { paths, iterations, opt_level, no_compare, save, history }) => {
+            let files = bench::discover(paths)?;
+            if files.is_empty() {
+                return Err("No benchmark found (.aeg files in benchmarks/)".to_string());
+            }
+            let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+            let options = bench::Options {
+                iterations: *iterations,
+                opt_level: *opt_level,
+                compare: !no_compare,
+                history: history.into(),
+                save: *save,
+            };
+            bench::run(&exe, &files, &options)
+        }
+
+        Some(Commands::Repl { transactional }) => {
+            println!("Aegis v2.0 - REPL");
+            println!("Tapez 'exit' ou 'quit' pour quitter.");
+            run_repl(*transactional);
+            Ok(())
         }
 
-        Some(Commands::Repl) | None => {
+        None => {
             println!("Aegis v2.0 - REPL");
             println!("Tapez 'exit' ou 'quit' pour quitter.");
-            run_repl();
+            run_repl(false);
             Ok(())
         }
 
+        Some(Commands::New { name, tests }) => project::new(name, *tests),
+
+        Some(Commands::Init { tests }) => project::init(Path::new("."), *tests),
+
         Some(Commands::Add { name, version }) => {
-            // package_manager::install attend &str et Option<String>
-            package_manager::install(name, version.clone())
+            // package_manager::add attend &str et Option<String>
+            package_manager::add(name, version.clone())
         }
 
+        Some(Commands::Install) => package_manager::install(),
+
+        Some(Commands::Search { query, json }) => package_manager::search(query, *json),
+
         Some(Commands::Publish { os, arch }) => {
             // Il faut cloner les Options car `cli` est emprunté dans le match
             package_manager::publish(os.clone(), arch.clone())
@@ -175,10 +431,37 @@ fn main() -> Result<(), String> {
     }
 }
 
+// Options de `aegis run`
+struct RunOptions {
+    debug: bool,
+    opt_level: u8,
+    lang_version: u8,
+    strict: bool,
+    stats: bool,
+    profile: bool,
+    breakpoints: Vec<aegis_core::vm::debugger::Breakpoint>,
+}
+
 // Nouvelle implémentation utilisant la VM v2
-fn run_file(filename: &str, debug: bool, args: Vec<String>) -> Result<(), String> {
-    let content = fs::read_to_string(filename)
-        .map_err(|e| format!("Impossible de lire {}: {}", filename, e))?;
+fn run_file(filename: &str, options: RunOptions, entry_point: Option<&str>, args: Vec<String>) -> Result<(), String> {
+    let RunOptions { debug, opt_level, lang_version, strict, stats, profile, breakpoints } = options;
+
+    // Un bundle (`aegis bundle`) apporte le script, ses modules et son profil
+    let bundle = match Path::new(filename).extension() {
+        Some(ext) if ext == bundle::EXTENSION => Some(bundle::Bundle::read(filename)?),
+        _ => None,
+    };
+    let (content, filename, opt_level, debug_info) = match &bundle {
+        Some(bundle) => {
+            let profile = &bundle.header.profile;
+            (bundle.entry_source()?.to_string(), bundle.header.entry.as_str(), profile.opt_level, profile.debug_info)
+        },
+        None => {
+            let content = fs::read_to_string(filename)
+                .map_err(|e| format!("Impossible de lire {}: {}", filename, e))?;
+            (content, filename, opt_level, true)
+        },
+    };
 
     // 1. Frontend 
     let json_data: JsonValue = if filename.ends_with(".aeg") {
@@ -191,13 +474,24 @@ fn run_file(filename: &str, debug: bool, args: Vec<String>) -> Result<(), String
     let statements = loader::parse_block(&json_data)?;
 
     // 3. Compilation v2
-    let compiler = aegis_core::vm::compiler::Compiler::new();
-    let (chunk, global_names) = compiler.compile(statements);
+    let mut compiler = aegis_core::vm::compiler::Compiler::new();
+    compiler.opt_level = opt_level;
+    compiler.lang_version = lang_version;
+    compiler.strict = strict;
+    compiler.debug_info = debug_info;
+    if debug_info {
+        compiler.file = Some(std::rc::Rc::from(filename));
+    }
+    let warnings = compiler.warnings.clone();
+    let (chunk, global_names) = compiler.compile(statements)?;
+    for warning in warnings.borrow().iter() {
+        eprintln!("{}:{}: warning: {}", filename, warning.line, warning.message);
+    }
 
     if debug {
         use aegis_core::vm::debug;
-        println!("\n=== DEBUG: BYTECODE GENERATED ===");
-        debug::disassemble_chunk(&chunk, filename);
+        println!("\n=== DEBUG: BYTECODE GENERATED ({}) ===", filename);
+        debug::disassemble_chunk(&chunk);
         println!("=================================\n");
     }
 
@@ -210,15 +504,163 @@ fn run_file(filename: &str, debug: bool, args: Vec<String>) -> Result<(), String
     }
 
     // 5. Exécution VM avec les arguments
+    let args_list = aegis_core::Value::List(std::rc::Rc::new(std::cell::RefCell::new(
+        script_args.iter().map(|a| aegis_core::Value::String(a.into())).collect()
+    )));
     let mut vm = VM::new(chunk, global_names, script_args);
-    
-    vm.run()
+    vm.lang_version = lang_version;
+    vm.strict = strict;
+    vm.opt_level = opt_level;
+    vm.debug_info = debug_info;
+    if let Some(bundle) = bundle {
+        vm.bundled = bundle.modules.into_iter().collect();
+    }
+    if profile {
+        vm.enable_profile(opt_level);
+    } else if stats {
+        vm.enable_stats(opt_level);
+    }
+    if !breakpoints.is_empty() {
+        vm.enable_debugger(breakpoints, Box::new(std::io::stdin().lock()))?;
+    }
+
+    // Les statistiques sont affichées même si le script échoue
+    let print_stats = |vm: &VM| if let Some(report) = vm.stats_report() {
+        eprintln!("\n{}", report);
+    };
+
+    let result = vm.run();
+    if result.is_err() || entry_point.is_none() {
+        print_stats(&vm);
+    }
+    result?;
+
+    // 6. Point d'entrée optionnel : main(args)
+    if let Some(name) = entry_point {
+        let result = vm.call_global(name, vec![args_list]);
+        print_stats(&vm);
+        let result = result?;
+
+        // Sans `return`, la fonction renvoie null : sortie normale
+        let code = match result {
+            aegis_core::Value::Null => 0,
+            aegis_core::Value::Integer(code) if (0..=255).contains(&code) => code as i32,
+            aegis_core::Value::Integer(code) => {
+                return Err(format!("{}() returned {}: exit codes must be between 0 and 255", name, code));
+            },
+            other => {
+                return Err(format!("{}() must return an int exit code (0-255), not a value of type {}", name, other.type_name()));
+            },
+        };
+        std::process::exit(code);
+    }
+
+    Ok(())
+}
+
+fn check_file(filename: &str) -> Result<(), String> {
+    let content = fs::read_to_string(filename)
+        .map_err(|e| format!("Impossible de lire {}: {}", filename, e))?;
+
+    let json_data = compiler::compile(&content)?;
+    let statements = loader::parse_block(&json_data)?;
+
+    let diagnostics = checker::check(&statements);
+    for d in &diagnostics {
+        println!("{}:{}: hint: {}", filename, d.line, d.message);
+    }
+    let errors = typecheck::check(&statements);
+    for e in &errors {
+        println!("{}:{}: error: {}", filename, e.line, e.message);
+    }
+    if diagnostics.is_empty() && errors.is_empty() {
+        println!("{}: no issues found", filename);
+    }
+
+    if !errors.is_empty() {
+        return Err(format!("{} type error(s) found in {}", errors.len(), filename));
+    }
+    Ok(())
+}
+
+fn bundle_file(filename: &str, profile: &str, output: Option<&str>) -> Result<(), String> {
+    let profile = bundle::profile(profile)?;
+    let output = match output {
+        Some(output) => output.to_string(),
+        None => Path::new(filename).with_extension(bundle::EXTENSION).to_string_lossy().to_string(),
+    };
+    let bundle = bundle::Bundle::build(filename, profile)?;
+    let size = bundle.write(&output)?;
+    println!(
+        "✅ Bundled {} ({} modules, profile {}) into {} ({} bytes)",
+        filename, bundle.modules.len(), bundle.header.profile.name, output, size
+    );
+    Ok(())
 }
 
-fn run_repl() {
-    let global_names = std::rc::Rc::new(std::cell::RefCell::new(HashMap::new()));
+fn graph_file(filename: &str, format: &str) -> Result<(), String> {
+    let graph = graph::ModuleGraph::build(filename)?;
+
+    if format == "mermaid" {
+        print!("{}", graph.to_mermaid());
+    } else {
+        print!("{}", graph.to_dot());
+    }
+
+    // Sur la sortie d'erreur : la sortie standard reste un graphe valide
+    for cycle in &graph.cycles {
+        let path: Vec<&str> = cycle.iter().chain(cycle.first()).map(|&id| graph.modules[id].path.as_str()).collect();
+        eprintln!("warning: import cycle: {}", path.join(" -> "));
+    }
+    for module in &graph.modules {
+        if let graph::ModuleStatus::Invalid(error) = &module.status {
+            eprintln!("warning: {}: {}", module.path, error);
+        }
+    }
+
+    Ok(())
+}
+
+fn list_plugins() {
+    let plugins = plugins::loaded_plugins();
+    if plugins.is_empty() {
+        println!("No native plugins loaded (plugin ABI v{})", aegis_core::PLUGIN_ABI_VERSION);
+        return;
+    }
+    for plugin in plugins {
+        println!("{} {} ({})", plugin.name, plugin.version, plugin.path);
+        for (kind, names) in [("functions", &plugin.functions), ("constants", &plugin.constants), ("classes", &plugin.classes)] {
+            if !names.is_empty() {
+                println!("  {}: {}", kind, names.join(", "));
+            }
+        }
+    }
+}
+
+fn explain_code(code: Option<&str>) -> Result<(), String> {
+    let Some(code) = code else {
+        for error in explain::CODES {
+            println!("{}  {}", error.code, error.title);
+        }
+        return Ok(());
+    };
+    let error = explain::lookup(code)
+        .ok_or_else(|| format!("Unknown error code '{}' (run `aegis explain` to list them)", code))?;
+    println!("{}: {}\n\n{}", error.code, error.title, error.explanation);
+    Ok(())
+}
+
+fn run_repl(transactional: bool) {
+    // Les natives occupent les premiers slots globaux (même ordre que Compiler::new)
+    let native_names: HashMap<String, u16> = native::get_all_names().into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, i as u16))
+        .collect();
+    let global_names = std::rc::Rc::new(std::cell::RefCell::new(native_names));
     let empty_chunk = aegis_core::chunk::Chunk::new();
     let mut vm = VM::new(empty_chunk, global_names.clone(), vec![]);
+    // Constantes, alias de type et `use strict` des saisies précédentes
+    let mut session = ReplSession::new(global_names);
 
     let mut rl = DefaultEditor::new().unwrap();
 
@@ -230,20 +672,62 @@ fn run_repl() {
                 rl.add_history_entry(line.as_str()).unwrap();
                 let source = line.trim();
                 if source == "exit" || source == "quit" { break; }
+                if let Some(target) = source.strip_prefix(":help") {
+                    repl_help(&mut vm, &mut session, target.trim());
+                    continue;
+                }
+                // Chaque saisie s'exécute jusqu'au bout : seule une erreur laisse des valeurs sur la pile
+                if source == ":stack" {
+                    print!("{}", vm.render_value_stack());
+                    continue;
+                }
+
+                // Mode transactionnel : on photographie l'état avant de compiler
+                // (la compilation enregistre déjà les nouveaux noms globaux)
+                let snapshot = if transactional { Some((vm.snapshot(), session.clone())) } else { None };
                 
                 // Pipeline v2 pour REPL
                 match compiler::compile(source) {
                     Ok(json_ast) => {
                         match loader::parse_block(&json_ast) {
                             Ok(statements) => {
-                                // Important: préserver le contexte global
-                                let mut repl_compiler = aegis_core::vm::compiler::Compiler::new_with_globals(global_names.clone());
-                                repl_compiler.scope_depth = 0; 
-                                
-                                let (chunk, _) = repl_compiler.compile(statements);
-
-                                if let Err(e) = vm.execute_chunk(chunk) {
-                                    println!("Runtime Error: {}", e);
+                                let chunk = match session.compile(statements) {
+                                    Ok((chunk, warnings)) => {
+                                        for warning in warnings {
+                                            println!("warning: {}", warning.message);
+                                        }
+                                        chunk
+                                    },
+                                    Err(e) => {
+                                        println!("{}", e);
+                                        if let Some((snapshot, _)) = snapshot {
+                                            vm.restore(snapshot);
+                                        }
+                                        continue;
+                                    }
+                                };
+
+                                match vm.execute_chunk(chunk) {
+                                    Ok(Some(value)) => {
+                                        // Comme Python : on affiche la valeur de l'expression (sauf null)
+                                        if value != aegis_core::Value::Null {
+                                            println!("{}", value);
+                                        }
+                                        vm.set_global("_", value);
+                                    },
+                                    Ok(None) => {},
+                                    Err(e) => {
+                                        match explain::classify(&e) {
+                                            Some(error) => println!("Runtime Error[{}]: {}", error.code, e),
+                                            None => println!("Runtime Error: {}", e),
+                                        }
+
+                                        if let Some((snapshot, previous)) = snapshot {
+                                            vm.restore(snapshot);
+                                            session = previous;
+                                            println!("(état global restauré)");
+                                        }
+                                    }
                                 }
                             },
                             Err(e) => println!("Loader Error: {}", e)
@@ -259,3 +743,36 @@ fn run_repl() {
         }
     }
 }
+
+// `:help expr` : docstring d'une fonction, d'une classe, d'une instance ou d'un module,
+// et `:help Classe.methode` pour une méthode (qui n'est pas une valeur en soi)
+fn repl_help(vm: &mut VM, session: &mut ReplSession, target: &str) {
+    if target.is_empty() {
+        println!("Usage: :help <function | class | module | Class.method>");
+        return;
+    }
+    let value = repl_eval(vm, session, target);
+    let doc = value.as_ref().ok().and_then(|value| vm.doc_of(value)).or_else(|| {
+        let (owner, member) = target.rsplit_once('.')?;
+        let owner = repl_eval(vm, session, owner).ok()?;
+        vm.member_doc(&owner, member.trim())
+    });
+    match (doc, &value) {
+        (Some(doc), Ok(aegis_core::Value::Function(func))) => {
+            let params: Vec<&str> = func.params.iter().map(|(name, _)| name.as_str()).filter(|name| *name != "this").collect();
+            println!("{}({})\n\n{}", func.chunk.name, params.join(", "), doc);
+        },
+        (Some(doc), Ok(aegis_core::Value::Class(class))) => println!("class {}\n\n{}", class.name, doc),
+        (Some(doc), _) => println!("{}", doc),
+        (None, Err(e)) if !target.contains('.') => println!("{}", e),
+        (None, _) => println!("No documentation for '{}'", target),
+    }
+}
+
+// Évalue une expression dans le contexte de la session, sans l'afficher ni toucher à `_`
+fn repl_eval(vm: &mut VM, session: &mut ReplSession, source: &str) -> Result<aegis_core::Value, String> {
+    let json_ast = compiler::compile(source)?;
+    let statements = loader::parse_block(&json_ast)?;
+    let (chunk, _) = session.compile(statements)?;
+    Ok(vm.execute_chunk(chunk)?.unwrap_or(aegis_core::Value::Null))
+}
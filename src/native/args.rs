@@ -0,0 +1,227 @@
+use crate::ast::{DictMap, TypedArray, Value, ValueKey};
+use crate::native::Commands;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Arguments reçus par une native, avec la signature affichée aux scripts :
+///
+/// ```ignore
+/// fn path_join(args: Vec<Value>) -> Result<Value, NativeError> {
+///     let args = Args::new("Path.join(a, b)", &args);
+///     let (a, b) = (args.want_str(0)?, args.want_str(1)?);
+/// ```
+///
+/// Un argument absent ou du mauvais type donne une erreur qui cite la signature,
+/// par exemple `Path.join(a, b): argument 'b' must be a string, got int`.
+/// Les `opt_*` renvoient la valeur par défaut si l'argument est absent ou null.
+pub struct Args<'a> {
+    signature: &'static str,
+    values: &'a [Value],
+}
+
+impl<'a> Args<'a> {
+    pub fn new(signature: &'static str, values: &'a [Value]) -> Self {
+        Self { signature, values }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Argument `index`, ou None s'il est absent (sans erreur).
+    pub fn get(&self, index: usize) -> Option<&'a Value> {
+        self.values.get(index)
+    }
+
+    /// Erreur `Signature(...): message`, pour les vérifications propres à une native.
+    pub fn error(&self, message: impl std::fmt::Display) -> String {
+        format!("{}: {}", self.signature, message)
+    }
+
+    /// Refuse les arguments au-delà de `max`.
+    pub fn at_most(&self, max: usize) -> Result<(), String> {
+        if self.values.len() > max {
+            let plural = if max == 1 { "" } else { "s" };
+            return Err(self.error(format!("expected at most {} argument{}, got {}", max, plural, self.values.len())));
+        }
+        Ok(())
+    }
+
+    pub fn want(&self, index: usize) -> Result<&'a Value, String> {
+        self.values.get(index)
+            .ok_or_else(|| self.error(format!("missing argument {}", self.name(index))))
+    }
+
+    pub fn want_str(&self, index: usize) -> Result<String, String> {
+        match self.want(index)? {
+            Value::String(s) => Ok(s.to_string()),
+            other => Err(self.mismatch(index, "a string", other)),
+        }
+    }
+
+    pub fn want_int(&self, index: usize) -> Result<i64, String> {
+        match self.want(index)? {
+            Value::Integer(i) => Ok(*i),
+            other => Err(self.mismatch(index, "an int", other)),
+        }
+    }
+
+    /// Entier ou flottant, converti en f64.
+    pub fn want_number(&self, index: usize) -> Result<f64, String> {
+        match self.want(index)? {
+            Value::Integer(i) => Ok(*i as f64),
+            Value::Float(f) => Ok(*f),
+            other => Err(self.mismatch(index, "a number", other)),
+        }
+    }
+
+    pub fn want_bool(&self, index: usize) -> Result<bool, String> {
+        match self.want(index)? {
+            Value::Boolean(b) => Ok(*b),
+            other => Err(self.mismatch(index, "a bool", other)),
+        }
+    }
+
+    pub fn want_list(&self, index: usize) -> Result<Rc<RefCell<Vec<Value>>>, String> {
+        match self.want(index)? {
+            Value::List(list) => Ok(list.clone()),
+            other => Err(self.mismatch(index, "a list", other)),
+        }
+    }
+
+    /// Clé de dict : chaîne, entier ou booléen.
+    pub fn want_key(&self, index: usize) -> Result<ValueKey, String> {
+        let value = self.want(index)?;
+        ValueKey::from_value(value).map_err(|_| self.mismatch(index, "a string, an int or a bool", value))
+    }
+
+    pub fn want_dict(&self, index: usize) -> Result<Rc<RefCell<DictMap>>, String> {
+        match self.want(index)? {
+            Value::Dict(dict) => Ok(dict.clone()),
+            other => Err(self.mismatch(index, "a dict", other)),
+        }
+    }
+
+    pub fn want_bytes(&self, index: usize) -> Result<Rc<RefCell<Vec<u8>>>, String> {
+        match self.want(index)? {
+            Value::Bytes(bytes) => Ok(bytes.clone()),
+            other => Err(self.mismatch(index, "bytes", other)),
+        }
+    }
+
+    /// Bytes, ou String encodée en UTF-8.
+    pub fn want_data(&self, index: usize) -> Result<Vec<u8>, String> {
+        match self.want(index)? {
+            Value::Bytes(bytes) => Ok(bytes.borrow().clone()),
+            Value::String(s) => Ok(s.as_bytes().to_vec()),
+            other => Err(self.mismatch(index, "bytes or a string", other)),
+        }
+    }
+
+    /// Float32Array ou IntArray, partagé avec le script : la native lit (ou remplit) le Vec
+    /// sans copie.
+    pub fn want_array(&self, index: usize) -> Result<Rc<RefCell<TypedArray>>, String> {
+        match self.want(index)? {
+            Value::TypedArray(array) => Ok(array.clone()),
+            other => Err(self.mismatch(index, "a Float32Array or an IntArray", other)),
+        }
+    }
+
+    /// Flottants 32 bits (sommets, matrices...) : Float32Array, Bytes lus en little-endian
+    /// (4 octets par valeur) ou liste de nombres. Un Float32Array ou des Bytes remplis par
+    /// `list.to_f32_bytes()` passent la frontière sans conversion élément par élément.
+    pub fn want_f32s(&self, index: usize) -> Result<Vec<f32>, String> {
+        match self.want(index)? {
+            Value::TypedArray(array) => match &*array.borrow() {
+                TypedArray::Float32(items) => Ok(items.clone()),
+                TypedArray::Int(items) => Ok(items.iter().map(|i| *i as f32).collect()),
+            },
+            Value::Bytes(bytes) => {
+                let bytes = bytes.borrow();
+                f32s_from_bytes(&bytes).ok_or_else(|| self.error(format!(
+                    "argument {} has {} bytes, which is not a whole number of 4-byte floats", self.name(index), bytes.len()
+                )))
+            },
+            Value::List(list) => list.borrow().iter().enumerate()
+                .map(|(i, item)| f32_from_number(item).ok_or_else(|| self.error(format!(
+                    "argument {} must only hold numbers, got {} at index {}", self.name(index), item.type_name(), i
+                ))))
+                .collect(),
+            other => Err(self.mismatch(index, "a Float32Array, bytes or a list of numbers", other)),
+        }
+    }
+
+    /// CommandBuffer rempli par le script, pour une native qui exécute toute une frame d'un coup.
+    pub fn want_commands(&self, index: usize) -> Result<Commands, String> {
+        let value = self.want(index)?;
+        Commands::of(value).ok_or_else(|| self.mismatch(index, "a CommandBuffer", value))
+    }
+
+    pub fn opt_str(&self, index: usize, default: &str) -> Result<String, String> {
+        if self.is_missing(index) { Ok(default.to_string()) } else { self.want_str(index) }
+    }
+
+    pub fn opt_int(&self, index: usize, default: i64) -> Result<i64, String> {
+        if self.is_missing(index) { Ok(default) } else { self.want_int(index) }
+    }
+
+    pub fn opt_number(&self, index: usize, default: f64) -> Result<f64, String> {
+        if self.is_missing(index) { Ok(default) } else { self.want_number(index) }
+    }
+
+    pub fn opt_bool(&self, index: usize, default: bool) -> Result<bool, String> {
+        if self.is_missing(index) { Ok(default) } else { self.want_bool(index) }
+    }
+
+    fn is_missing(&self, index: usize) -> bool {
+        matches!(self.values.get(index), None | Some(Value::Null))
+    }
+
+    /// Erreur pour un argument d'un type que les `want_*` ne couvrent pas :
+    /// `args.expected(1, "a function")`.
+    pub fn expected(&self, index: usize, expected: &str) -> String {
+        match self.values.get(index) {
+            Some(got) => self.mismatch(index, expected, got),
+            None => self.error(format!("missing argument {}", self.name(index))),
+        }
+    }
+
+    fn mismatch(&self, index: usize, expected: &str, got: &Value) -> String {
+        self.error(format!("argument {} must be {}, got {}", self.name(index), expected, got.type_name()))
+    }
+
+    // Nom du paramètre d'après la signature (`'b'`), ou sa position si elle ne le donne pas
+    fn name(&self, index: usize) -> String {
+        let params = self.signature.split_once('(')
+            .and_then(|(_, rest)| rest.rsplit_once(')'))
+            .map(|(params, _)| params)
+            .unwrap_or("");
+        let param = params.split(',')
+            .nth(index)
+            .map(|p| p.split(['=', ':']).next().unwrap_or("").trim().trim_start_matches("...").trim_end_matches('?'))
+            .unwrap_or("");
+        if param.is_empty() { format!("{}", index + 1) } else { format!("'{}'", param) }
+    }
+}
+
+/// Flottants 32 bits little-endian, ou None si la taille n'est pas un multiple de 4.
+pub(crate) fn f32s_from_bytes(bytes: &[u8]) -> Option<Vec<f32>> {
+    let chunks = bytes.chunks_exact(4);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    Some(chunks.map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect())
+}
+
+/// Entier ou flottant réduit en f32, ou None pour les autres valeurs.
+pub(crate) fn f32_from_number(value: &Value) -> Option<f32> {
+    match value {
+        Value::Integer(i) => Some(*i as f32),
+        Value::Float(f) => Some(*f as f32),
+        _ => None,
+    }
+}
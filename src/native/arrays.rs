@@ -0,0 +1,250 @@
+use crate::ast::value::widen_f32;
+use crate::ast::{TypedArray, Value};
+use super::{Args, NativeError};
+use super::args::{f32_from_number, f32s_from_bytes};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Float32Array et IntArray : tableaux de nombres contigus (f32 / i64) pour les calculs et les
+// plugins graphiques. Les opérations en bloc (fill, add, sum...) bouclent en Rust sur le Vec,
+// sans passer par une Value par élément ; `a[i]` et `foreach` marchent comme sur une liste.
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("float32array_zeros".to_string(), float32array_zeros);
+    map.insert("float32array_from".to_string(), float32array_from);
+    map.insert("intarray_zeros".to_string(), intarray_zeros);
+    map.insert("intarray_from".to_string(), intarray_from);
+}
+
+fn new_array(array: TypedArray) -> Value {
+    Value::TypedArray(Rc::new(RefCell::new(array)))
+}
+
+fn want_len(args: &Args, index: usize) -> Result<usize, String> {
+    let n = args.want_int(index)?;
+    usize::try_from(n).map_err(|_| args.error(format!("length must not be negative, got {}", n)))
+}
+
+fn float32array_zeros(args: Vec<Value>) -> Result<Value, NativeError> {
+    let n = want_len(&Args::new("Float32Array.zeros(n)", &args), 0)?;
+    Ok(new_array(TypedArray::Float32(vec![0.0; n])))
+}
+
+fn intarray_zeros(args: Vec<Value>) -> Result<Value, NativeError> {
+    let n = want_len(&Args::new("IntArray.zeros(n)", &args), 0)?;
+    Ok(new_array(TypedArray::Int(vec![0; n])))
+}
+
+fn float32array_from(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Float32Array.from(values)", &args);
+    Ok(new_array(TypedArray::Float32(floats(&args, 0)?)))
+}
+
+fn intarray_from(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("IntArray.from(values)", &args);
+    Ok(new_array(TypedArray::Int(ints(&args, 0)?)))
+}
+
+// Éléments d'un Float32Array : liste de nombres, tableau typé, ou Bytes de f32 little-endian
+fn floats(args: &Args, index: usize) -> Result<Vec<f32>, String> {
+    match args.want(index)? {
+        Value::List(list) => list.borrow().iter().enumerate()
+            .map(|(i, item)| f32_from_number(item)
+                .ok_or_else(|| args.error(format!("element {} must be a number, got {}", i, item.type_name()))))
+            .collect(),
+        Value::TypedArray(array) => Ok(match &*array.borrow() {
+            TypedArray::Float32(items) => items.clone(),
+            TypedArray::Int(items) => items.iter().map(|i| *i as f32).collect(),
+        }),
+        Value::Bytes(bytes) => {
+            let bytes = bytes.borrow();
+            f32s_from_bytes(&bytes)
+                .ok_or_else(|| args.error(format!("{} bytes is not a whole number of 4-byte floats", bytes.len())))
+        },
+        _ => Err(args.expected(index, "a list, a typed array or bytes")),
+    }
+}
+
+// Éléments d'un IntArray : liste d'entiers, IntArray, ou Bytes d'i64 little-endian
+fn ints(args: &Args, index: usize) -> Result<Vec<i64>, String> {
+    match args.want(index)? {
+        Value::List(list) => list.borrow().iter().enumerate()
+            .map(|(i, item)| match item {
+                Value::Integer(n) => Ok(*n),
+                other => Err(args.error(format!("element {} must be an int, got {}", i, other.type_name()))),
+            })
+            .collect(),
+        Value::TypedArray(array) => match &*array.borrow() {
+            TypedArray::Int(items) => Ok(items.clone()),
+            TypedArray::Float32(_) => Err(args.error("a Float32Array cannot be read as ints")),
+        },
+        Value::Bytes(bytes) => {
+            let bytes = bytes.borrow();
+            let chunks = bytes.chunks_exact(8);
+            if !chunks.remainder().is_empty() {
+                return Err(args.error(format!("{} bytes is not a whole number of 8-byte ints", bytes.len())));
+            }
+            Ok(chunks.map(|chunk| i64::from_le_bytes(chunk.try_into().unwrap())).collect())
+        },
+        _ => Err(args.expected(index, "a list, an IntArray or bytes")),
+    }
+}
+
+fn overflow(a: i64, op: &str, b: i64) -> String {
+    format!("Integer overflow: {} {} {} does not fit in an int", a, op, b)
+}
+
+// a.add(x) / a.mul(x) : x est un nombre, ou des valeurs de même longueur que `a`
+fn combine(array: &Rc<RefCell<TypedArray>>, args: &Args, op: &str) -> Result<(), String> {
+    let len = array.borrow().len();
+    let same_len = |n: usize| if n == len {
+        Ok(())
+    } else {
+        Err(args.error(format!("expected {} values, got {}", len, n)))
+    };
+    let is_float = matches!(&*array.borrow(), TypedArray::Float32(_));
+
+    if is_float {
+        // Les opérandes sont lus avant d'emprunter `a` : a.add(a) est permis
+        let operand = match args.want(0)? {
+            Value::Integer(_) | Value::Float(_) => None,
+            _ => Some(floats(args, 0)?),
+        };
+        let mut array = array.borrow_mut();
+        let TypedArray::Float32(items) = &mut *array else { unreachable!() };
+        let apply = |a: &mut f32, b: f32| if op == "+" { *a += b } else { *a *= b };
+        match operand {
+            None => {
+                let b = args.want_number(0)? as f32;
+                items.iter_mut().for_each(|a| apply(a, b));
+            },
+            Some(values) => {
+                same_len(values.len())?;
+                items.iter_mut().zip(values).for_each(|(a, b)| apply(a, b));
+            },
+        }
+    } else {
+        let operand = match args.want(0)? {
+            Value::Integer(_) => None,
+            _ => Some(ints(args, 0)?),
+        };
+        let mut array = array.borrow_mut();
+        let TypedArray::Int(items) = &mut *array else { unreachable!() };
+        let apply = |a: &mut i64, b: i64| {
+            let result = if op == "+" { a.checked_add(b) } else { a.checked_mul(b) };
+            *a = result.ok_or_else(|| overflow(*a, op, b))?;
+            Ok::<(), String>(())
+        };
+        match operand {
+            None => {
+                let b = args.want_int(0)?;
+                items.iter_mut().try_for_each(|a| apply(a, b))?;
+            },
+            Some(values) => {
+                same_len(values.len())?;
+                items.iter_mut().zip(values).try_for_each(|(a, b)| apply(a, b))?;
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Méthodes de Float32Array et IntArray (`a.fill(0)`, `a.sum()`...).
+pub fn array_method(array: &Rc<RefCell<TypedArray>>, method: &str, args: &[Value]) -> Result<Value, String> {
+    let name = array.borrow().type_name();
+    let this = Value::TypedArray(array.clone());
+    Ok(match method {
+        "len" => Value::Integer(array.borrow().len() as i64),
+
+        "is_empty" => Value::Boolean(array.borrow().is_empty()),
+
+        "at" => {
+            let index = Args::new("array.at(index)", args).want_int(0)?;
+            usize::try_from(index).ok().and_then(|i| array.borrow().get(i)).unwrap_or(Value::Null)
+        },
+
+        "fill" => {
+            let spec = Args::new("array.fill(value)", args);
+            match &mut *array.borrow_mut() {
+                TypedArray::Float32(items) => items.fill(spec.want_number(0)? as f32),
+                TypedArray::Int(items) => items.fill(spec.want_int(0)?),
+            }
+            this
+        },
+
+        // Copie des valeurs à partir de la position `offset` ; elles doivent tenir dans le tableau
+        "copy_from" => {
+            let spec = Args::new("array.copy_from(values, offset?)", args);
+            let offset = spec.opt_int(1, 0)?;
+            let len = array.borrow().len();
+            let is_float = matches!(&*array.borrow(), TypedArray::Float32(_));
+            let fits = |n: usize| usize::try_from(offset).ok()
+                .filter(|start| start.checked_add(n).is_some_and(|end| end <= len))
+                .ok_or_else(|| spec.error(format!("{} values at offset {} do not fit in {} elements", n, offset, len)));
+            if is_float {
+                let values = floats(&spec, 0)?;
+                let start = fits(values.len())?;
+                if let TypedArray::Float32(items) = &mut *array.borrow_mut() {
+                    items[start..start + values.len()].copy_from_slice(&values);
+                }
+            } else {
+                let values = ints(&spec, 0)?;
+                let start = fits(values.len())?;
+                if let TypedArray::Int(items) = &mut *array.borrow_mut() {
+                    items[start..start + values.len()].copy_from_slice(&values);
+                }
+            }
+            this
+        },
+
+        "add" => {
+            combine(array, &Args::new("array.add(values)", args), "+")?;
+            this
+        },
+
+        "mul" => {
+            combine(array, &Args::new("array.mul(values)", args), "*")?;
+            this
+        },
+
+        // Somme d'un Float32Array cumulée en f64, puis arrondie en f32 comme ses éléments
+        "sum" => match &*array.borrow() {
+            TypedArray::Float32(items) => Value::Float(widen_f32(items.iter().map(|f| *f as f64).sum::<f64>() as f32)),
+            TypedArray::Int(items) => {
+                let mut total: i64 = 0;
+                for i in items {
+                    total = total.checked_add(*i).ok_or_else(|| overflow(total, "+", *i))?;
+                }
+                Value::Integer(total)
+            },
+        },
+
+        "slice" => {
+            let len = array.borrow().len();
+            let spec = Args::new("array.slice(start?, end?)", args);
+            let start = (spec.opt_int(0, 0)?.max(0) as usize).min(len);
+            let end = (spec.opt_int(1, len as i64)?.max(0) as usize).min(len).max(start);
+            new_array(match &*array.borrow() {
+                TypedArray::Float32(items) => TypedArray::Float32(items[start..end].to_vec()),
+                TypedArray::Int(items) => TypedArray::Int(items[start..end].to_vec()),
+            })
+        },
+
+        "to_list" => {
+            let array = array.borrow();
+            crate::vm::gc::list((0..array.len()).filter_map(|i| array.get(i)).collect())
+        },
+
+        // Little-endian : 4 octets par élément d'un Float32Array, 8 pour un IntArray
+        "to_bytes" => {
+            let bytes = match &*array.borrow() {
+                TypedArray::Float32(items) => items.iter().flat_map(|f| f.to_le_bytes()).collect(),
+                TypedArray::Int(items) => items.iter().flat_map(|i| i.to_le_bytes()).collect(),
+            };
+            Value::Bytes(Rc::new(RefCell::new(bytes)))
+        },
+
+        _ => return Err(format!("Unknown {} method '{}'", name, method)),
+    })
+}
@@ -0,0 +1,305 @@
+use crate::ast::value::widen_f32;
+use crate::ast::Value;
+use crate::vm::gc;
+use super::{Args, NativeError};
+use super::args::f32s_from_bytes;
+use base64::{Engine as _, engine::general_purpose};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Bytes : tampon d'octets modifiable (fichiers binaires, sockets, hachage). Les méthodes
+// read_u16, write_i32... lisent et écrivent des nombres à une position en octets, en
+// little-endian par défaut ("be" pour les formats réseau).
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("bytes_new".to_string(), bytes_new);
+    map.insert("bytes_from".to_string(), bytes_from);
+    map.insert("bytes_from_hex".to_string(), bytes_from_hex);
+    map.insert("bytes_from_base64".to_string(), bytes_from_base64);
+}
+
+fn new_bytes(bytes: Vec<u8>) -> Value {
+    Value::Bytes(Rc::new(RefCell::new(bytes)))
+}
+
+fn want_byte(args: &Args, index: usize) -> Result<u8, String> {
+    let n = args.want_int(index)?;
+    u8::try_from(n).map_err(|_| args.error(format!("a byte must be between 0 and 255, got {}", n)))
+}
+
+fn bytes_new(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Bytes.new(size, fill?)", &args);
+    let size = args.want_int(0)?;
+    let size = usize::try_from(size).map_err(|_| args.error(format!("size must not be negative, got {}", size)))?;
+    let fill = if args.get(1).is_some_and(|v| *v != Value::Null) { want_byte(&args, 1)? } else { 0 };
+    Ok(new_bytes(vec![fill; size]))
+}
+
+// Liste d'octets (0-255), chaîne (UTF-8) ou copie d'un autre Bytes
+fn bytes_from(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Bytes.from(values)", &args);
+    let bytes = match args.want(0)? {
+        Value::List(list) => list.borrow().iter().enumerate()
+            .map(|(i, item)| match item {
+                Value::Integer(n) => u8::try_from(*n)
+                    .map_err(|_| args.error(format!("element {} must be between 0 and 255, got {}", i, n))),
+                other => Err(args.error(format!("element {} must be an int, got {}", i, other.type_name()))),
+            })
+            .collect::<Result<Vec<u8>, String>>()?,
+        Value::String(s) => s.as_bytes().to_vec(),
+        Value::Bytes(bytes) => bytes.borrow().clone(),
+        _ => return Err(args.expected(0, "a list of ints, a string or bytes").into()),
+    };
+    Ok(new_bytes(bytes))
+}
+
+// "48656c6c6f" ou "48 65 6C 6C 6F" : les espaces sont ignorés
+fn bytes_from_hex(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Bytes.from_hex(text)", &args);
+    let text = args.want_str(0)?;
+    let digits: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(args.error(format!("odd number of hex digits ({})", digits.len())).into());
+    }
+    let bytes = digits.chunks(2)
+        .map(|pair| std::str::from_utf8(pair).ok().and_then(|pair| u8::from_str_radix(pair, 16).ok())
+            .ok_or_else(|| args.error(format!("invalid hex digits '{}'", String::from_utf8_lossy(pair)))))
+        .collect::<Result<Vec<u8>, String>>()?;
+    Ok(new_bytes(bytes))
+}
+
+fn bytes_from_base64(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Bytes.from_base64(text)", &args);
+    let bytes = general_purpose::STANDARD.decode(args.want_str(0)?.trim())
+        .map_err(|e| args.error(format!("invalid base64: {}", e)))?;
+    Ok(new_bytes(bytes))
+}
+
+// Nombre lu ou écrit par read_<type> / write_<type>
+#[derive(Clone, Copy)]
+enum Number { U8, I8, U16, I16, U32, I32, I64, F32, F64 }
+
+impl Number {
+    fn parse(name: &str) -> Option<Number> {
+        Some(match name {
+            "u8" => Number::U8,
+            "i8" => Number::I8,
+            "u16" => Number::U16,
+            "i16" => Number::I16,
+            "u32" => Number::U32,
+            "i32" => Number::I32,
+            "i64" => Number::I64,
+            "f32" => Number::F32,
+            "f64" => Number::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Number::U8 | Number::I8 => 1,
+            Number::U16 | Number::I16 => 2,
+            Number::U32 | Number::I32 | Number::F32 => 4,
+            Number::I64 | Number::F64 => 8,
+        }
+    }
+
+    // Octets lus, big-endian et alignés à droite sur 8 octets
+    fn decode(self, raw: [u8; 8]) -> Value {
+        let unsigned = u64::from_be_bytes(raw);
+        match self {
+            Number::U8 | Number::U16 | Number::U32 => Value::Integer(unsigned as i64),
+            Number::I8 => Value::Integer(unsigned as u8 as i8 as i64),
+            Number::I16 => Value::Integer(unsigned as u16 as i16 as i64),
+            Number::I32 => Value::Integer(unsigned as u32 as i32 as i64),
+            Number::I64 => Value::Integer(unsigned as i64),
+            Number::F32 => Value::Float(widen_f32(f32::from_bits(unsigned as u32))),
+            Number::F64 => Value::Float(f64::from_bits(unsigned)),
+        }
+    }
+
+    // Octets à écrire, big-endian ; l'entier doit tenir dans le type
+    fn encode(self, args: &Args, index: usize) -> Result<Vec<u8>, String> {
+        let int = |min: i64, max: i64, name: &str| {
+            let n = args.want_int(index)?;
+            if n < min || n > max {
+                return Err(args.error(format!("{} does not fit in {} ({}..={})", n, name, min, max)));
+            }
+            Ok(n)
+        };
+        let bytes = match self {
+            Number::U8 => vec![int(0, u8::MAX as i64, "u8")? as u8],
+            Number::I8 => (int(i8::MIN as i64, i8::MAX as i64, "i8")? as i8).to_be_bytes().to_vec(),
+            Number::U16 => (int(0, u16::MAX as i64, "u16")? as u16).to_be_bytes().to_vec(),
+            Number::I16 => (int(i16::MIN as i64, i16::MAX as i64, "i16")? as i16).to_be_bytes().to_vec(),
+            Number::U32 => (int(0, u32::MAX as i64, "u32")? as u32).to_be_bytes().to_vec(),
+            Number::I32 => (int(i32::MIN as i64, i32::MAX as i64, "i32")? as i32).to_be_bytes().to_vec(),
+            Number::I64 => args.want_int(index)?.to_be_bytes().to_vec(),
+            Number::F32 => (args.want_number(index)? as f32).to_be_bytes().to_vec(),
+            Number::F64 => args.want_number(index)?.to_be_bytes().to_vec(),
+        };
+        Ok(bytes)
+    }
+}
+
+// "le" (défaut) ou "be" : vrai pour big-endian
+fn big_endian(args: &Args, index: usize) -> Result<bool, String> {
+    match args.opt_str(index, "le")?.as_str() {
+        "le" => Ok(false),
+        "be" => Ok(true),
+        other => Err(args.error(format!("unknown byte order '{}' (expected \"le\" or \"be\")", other))),
+    }
+}
+
+fn read_number(bytes: &[u8], number: Number, args: &Args) -> Result<Value, String> {
+    let offset = args.want_int(0)?;
+    let be = big_endian(args, 1)?;
+    let size = number.size();
+    let Some(chunk) = usize::try_from(offset).ok().and_then(|o| bytes.get(o..)?.get(..size)) else {
+        return Ok(Value::Null);
+    };
+    let mut raw = [0u8; 8];
+    raw[8 - size..].copy_from_slice(chunk);
+    if !be {
+        raw[8 - size..].reverse();
+    }
+    Ok(number.decode(raw))
+}
+
+fn write_number(bytes: &mut [u8], number: Number, args: &Args) -> Result<(), String> {
+    let offset = args.want_int(0)?;
+    let mut encoded = number.encode(args, 1)?;
+    if !big_endian(args, 2)? {
+        encoded.reverse();
+    }
+    let len = bytes.len();
+    let slot = usize::try_from(offset).ok()
+        .and_then(|o| bytes.get_mut(o..)?.get_mut(..encoded.len()))
+        .ok_or_else(|| args.error(format!("{} bytes at offset {} do not fit in {} bytes", encoded.len(), offset, len)))?;
+    slot.copy_from_slice(&encoded);
+    Ok(())
+}
+
+// Signatures citées dans les erreurs de read_<type> / write_<type>
+fn number_signatures(name: &str) -> (&'static str, &'static str) {
+    match name {
+        "u8" => ("bytes.read_u8(offset)", "bytes.write_u8(offset, value)"),
+        "i8" => ("bytes.read_i8(offset)", "bytes.write_i8(offset, value)"),
+        "u16" => ("bytes.read_u16(offset, endian?)", "bytes.write_u16(offset, value, endian?)"),
+        "i16" => ("bytes.read_i16(offset, endian?)", "bytes.write_i16(offset, value, endian?)"),
+        "u32" => ("bytes.read_u32(offset, endian?)", "bytes.write_u32(offset, value, endian?)"),
+        "i32" => ("bytes.read_i32(offset, endian?)", "bytes.write_i32(offset, value, endian?)"),
+        "i64" => ("bytes.read_i64(offset, endian?)", "bytes.write_i64(offset, value, endian?)"),
+        "f32" => ("bytes.read_f32(offset, endian?)", "bytes.write_f32(offset, value, endian?)"),
+        _ => ("bytes.read_f64(offset, endian?)", "bytes.write_f64(offset, value, endian?)"),
+    }
+}
+
+/// Méthodes de Bytes (`b.len()`, `b.read_u32(0)`, `b.to_string("latin1")`...).
+pub fn bytes_method(b: &Rc<RefCell<Vec<u8>>>, method: &str, args: &[Value]) -> Result<Value, String> {
+    if let Some((op, name)) = method.split_once('_')
+        && let Some(number) = Number::parse(name)
+        && (op == "read" || op == "write") {
+        let (read_sig, write_sig) = number_signatures(name);
+        if op == "read" {
+            return read_number(&b.borrow(), number, &Args::new(read_sig, args));
+        }
+        write_number(&mut b.borrow_mut(), number, &Args::new(write_sig, args))?;
+        return Ok(Value::Bytes(b.clone()));
+    }
+
+    Ok(match method {
+        "len" => Value::Integer(b.borrow().len() as i64),
+
+        "is_empty" => Value::Boolean(b.borrow().is_empty()),
+
+        "at" => {
+            // Retourne l'octet sous forme d'entier (0-255)
+            let idx = Args::new("bytes.at(index)", args).want_int(0)?;
+            match usize::try_from(idx).ok().and_then(|i| b.borrow().get(i).copied()) {
+                Some(byte) => Value::Integer(byte as i64),
+                None => Value::Null,
+            }
+        },
+
+        "slice" => {
+            // Extrait une sous-partie des octets
+            let len = b.borrow().len();
+            let spec = Args::new("bytes.slice(start?, end?)", args);
+            let start = (spec.opt_int(0, 0)?.max(0) as usize).min(len);
+            let end = (spec.opt_int(1, len as i64)?.max(0) as usize).min(len).max(start);
+            new_bytes(b.borrow()[start..end].to_vec())
+        },
+
+        // Ajoute un octet, ou le contenu d'un autre Bytes / d'une chaîne, à la fin
+        "push" => {
+            let byte = want_byte(&Args::new("bytes.push(byte)", args), 0)?;
+            b.borrow_mut().push(byte);
+            Value::Bytes(b.clone())
+        },
+
+        "extend" => {
+            let data = Args::new("bytes.extend(data)", args).want_data(0)?;
+            b.borrow_mut().extend_from_slice(&data);
+            Value::Bytes(b.clone())
+        },
+
+        // Texte décodé : "utf-8" (null si invalide), "latin1", "ascii" (null au-delà de 127),
+        // ou représentation "hex" / "base64"
+        "to_string" => {
+            let spec = Args::new("bytes.to_string(encoding?)", args);
+            let encoding = spec.opt_str(0, "utf-8")?.to_ascii_lowercase();
+            let bytes = b.borrow();
+            match encoding.as_str() {
+                "utf-8" | "utf8" => String::from_utf8(bytes.clone()).map(|s| Value::String(s.into())).unwrap_or(Value::Null),
+                "latin1" | "iso-8859-1" => Value::String(bytes.iter().map(|&byte| byte as char).collect()),
+                "ascii" if bytes.is_ascii() => Value::String(bytes.iter().map(|&byte| byte as char).collect()),
+                "ascii" => Value::Null,
+                "hex" => Value::String(bytes.iter().map(|byte| format!("{:02x}", byte)).collect()),
+                "base64" => Value::String(general_purpose::STANDARD.encode(&*bytes).into()),
+                other => return Err(spec.error(format!(
+                    "unknown encoding '{}' (expected utf-8, latin1, ascii, hex or base64)", other))),
+            }
+        },
+
+        "to_hex" => {
+            // Debug : Affiche en hexadécimal "1FA2..."
+            Value::String(b.borrow().iter().map(|byte| format!("{:02X}", byte)).collect())
+        },
+
+        "to_list" => gc::list(b.borrow().iter().map(|&byte| Value::Integer(byte as i64)).collect()),
+
+        // Vue en flottants 32 bits : `index` compte des flottants, pas des octets
+        "f32_at" => {
+            let idx = Args::new("bytes.f32_at(index)", args).want_int(0)?;
+            let bytes = b.borrow();
+            match usize::try_from(idx).ok().and_then(|i| bytes.get(i.checked_mul(4)?..)?.get(..4)) {
+                Some(chunk) => Value::Float(widen_f32(f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))),
+                None => Value::Null,
+            }
+        },
+
+        "set_f32" => {
+            let spec = Args::new("bytes.set_f32(index, value)", args);
+            let (idx, value) = (spec.want_int(0)?, spec.want_number(1)?);
+            let mut bytes = b.borrow_mut();
+            let count = bytes.len() / 4;
+            let slot = usize::try_from(idx).ok()
+                .and_then(|i| bytes.get_mut(i.checked_mul(4)?..)?.get_mut(..4))
+                .ok_or_else(|| spec.error(format!("index {} is out of range for {} floats", idx, count)))?;
+            slot.copy_from_slice(&(value as f32).to_le_bytes());
+            Value::Null
+        },
+
+        "to_f32_list" => {
+            let bytes = b.borrow();
+            let floats = f32s_from_bytes(&bytes).ok_or_else(|| {
+                format!("bytes.to_f32_list(): {} bytes is not a whole number of 4-byte floats", bytes.len())
+            })?;
+            gc::list(floats.into_iter().map(|f| Value::Float(widen_f32(f))).collect())
+        },
+
+        _ => return Err(format!("Unknown bytes method '{}'", method)),
+    })
+}
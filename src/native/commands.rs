@@ -0,0 +1,185 @@
+use crate::ast::value::{ClassData, Visibility};
+use crate::ast::Value;
+use crate::native::{self, Args, NativeError, NativeFn};
+use crate::vm::gc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// CommandBuffer : file de commandes natives remplie par le script puis exécutée d'un seul coup.
+// Un plugin graphique (GLFW...) appelé des centaines de fois par frame peut ainsi recevoir
+// toute la frame en un appel (`gfx_submit(buffer)`, via `Args::want_commands`) et regrouper
+// son travail, au lieu de payer un aller-retour par petite native.
+//
+// Les commandes sont rangées à plat dans le champ privé `commands` :
+// [nom, nombre d'arguments, arguments..., nom, nombre d'arguments, ...]
+// Pas de liste par commande : `push` n'alloue que le nom.
+
+const NAME: &str = "CommandBuffer";
+const FIELD: &str = "commands";
+
+thread_local! {
+    // Comme les classes d'erreur : une par thread, remplacée par sa valeur à la compilation
+    static CLASS: Rc<ClassData> = build_class();
+}
+
+pub fn register(map: &mut HashMap<String, NativeFn>) {
+    map.insert("CommandBuffer.init".to_string(), buffer_init);
+    map.insert("CommandBuffer.push".to_string(), buffer_push);
+    map.insert("CommandBuffer.len".to_string(), buffer_len);
+    map.insert("CommandBuffer.clear".to_string(), buffer_clear);
+    map.insert("CommandBuffer.submit".to_string(), buffer_submit);
+}
+
+/// La classe CommandBuffer, si c'est le nom demandé.
+pub fn class(name: &str) -> Option<Rc<ClassData>> {
+    (name == NAME).then(|| CLASS.with(Rc::clone))
+}
+
+fn build_class() -> Rc<ClassData> {
+    let methods = ["init", "push", "len", "clear", "submit"].into_iter()
+        .map(|method| (method.to_string(), Value::Native(format!("{}.{}", NAME, method))))
+        .collect();
+    let data = ClassData {
+        name: NAME.to_string(),
+        parent: None,
+        parent_ref: None,
+        methods,
+        fields: HashMap::new(),
+        field_types: HashMap::new(),
+        properties: HashMap::new(),
+        static_methods: HashMap::new(),
+        static_fields: RefCell::new(HashMap::new()),
+        static_field_types: HashMap::new(),
+        static_properties: HashMap::new(),
+        is_final: true,
+        final_methods: Default::default(),
+        interfaces: Vec::new(),
+        interfaces_names: Vec::new(),
+        // La disposition à plat n'est pas une API : seules les méthodes y touchent
+        visibilities: HashMap::from([(FIELD.to_string(), Visibility::Private)]),
+        ancestors: Vec::new(),
+        method_table: HashMap::new(),
+        static_method_table: HashMap::new(),
+        field_slots: HashMap::new(),
+        field_layout: Vec::new(),
+        doc: None,
+    }.link(None);
+    Rc::new(data)
+}
+
+/// Commandes d'un CommandBuffer, telles qu'un plugin les reçoit (`args.want_commands(0)`).
+pub struct Commands(Rc<RefCell<Vec<Value>>>);
+
+impl Commands {
+    /// Les commandes de `value`, si c'est un CommandBuffer.
+    pub fn of(value: &Value) -> Option<Commands> {
+        let Value::Instance(instance) = value else { return None };
+        let instance = instance.borrow();
+        if instance.class.name != NAME {
+            return None;
+        }
+        match instance.fields.get(FIELD) {
+            Some(Value::List(list)) => Some(Commands(list.clone())),
+            _ => None,
+        }
+    }
+
+    /// Nombre de commandes.
+    pub fn len(&self) -> usize {
+        self.entries().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// Appelle `f(nom, arguments)` pour chaque commande, dans l'ordre où elles ont été ajoutées.
+    /// La première erreur arrête le parcours.
+    pub fn for_each(&self, mut f: impl FnMut(&str, &[Value]) -> Result<(), String>) -> Result<(), String> {
+        let items = self.0.borrow();
+        for (start, end) in self.entries() {
+            let Value::String(name) = &items[start] else { continue };
+            f(name, &items[start + 2..end])?;
+        }
+        Ok(())
+    }
+
+    // Bornes de chaque commande dans la liste à plat : (position du nom, fin des arguments)
+    fn entries(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut position = 0;
+        std::iter::from_fn(move || {
+            let items = self.0.borrow();
+            let Some(Value::Integer(count)) = items.get(position + 1) else { return None };
+            let start = position;
+            position = (start + 2 + *count as usize).min(items.len());
+            Some((start, position))
+        })
+    }
+}
+
+fn this<'a>(args: &'a [Value], signature: &'static str) -> Result<(Commands, Args<'a>), String> {
+    let commands = args.first().and_then(Commands::of)
+        .ok_or_else(|| format!("{} must be called on a CommandBuffer", signature))?;
+    Ok((commands, Args::new(signature, &args[1..])))
+}
+
+fn buffer_init(args: Vec<Value>) -> Result<Value, NativeError> {
+    let Some(Value::Instance(instance)) = args.first() else {
+        return Err("CommandBuffer.init() must be called on an instance".into());
+    };
+    instance.borrow_mut().set_field(FIELD.to_string(), gc::list(Vec::new()));
+    Ok(Value::Null)
+}
+
+// buffer.push("gl_draw_rect", x, y, w, h) : renvoie le buffer pour enchaîner les appels
+fn buffer_push(args: Vec<Value>) -> Result<Value, NativeError> {
+    let (commands, spec) = this(&args, "CommandBuffer.push(command, ...args)")?;
+    let name = spec.want_str(0)?;
+    let mut items = commands.0.borrow_mut();
+    items.push(Value::String(name.into()));
+    items.push(Value::Integer(spec.len() as i64 - 1));
+    items.extend_from_slice(&args[2..]);
+    Ok(args[0].clone())
+}
+
+fn buffer_len(args: Vec<Value>) -> Result<Value, NativeError> {
+    let (commands, _) = this(&args, "CommandBuffer.len()")?;
+    Ok(Value::Integer(commands.len() as i64))
+}
+
+// Garde la capacité : un buffer vidé puis rempli à chaque frame n'alloue plus
+fn buffer_clear(args: Vec<Value>) -> Result<Value, NativeError> {
+    let (commands, _) = this(&args, "CommandBuffer.clear()")?;
+    commands.0.borrow_mut().clear();
+    Ok(args[0].clone())
+}
+
+// Exécute les commandes dans l'ordre, chacune avec la native qui porte son nom, et renvoie leur
+// nombre. Le buffer n'est pas vidé : une liste de commandes fixe peut être rejouée.
+fn buffer_submit(args: Vec<Value>) -> Result<Value, NativeError> {
+    let (commands, _) = this(&args, "CommandBuffer.submit()")?;
+    let mut resolved: HashMap<String, NativeFn> = HashMap::new();
+    let mut calls = Vec::with_capacity(commands.len());
+    commands.for_each(|name, args| {
+        let func = match resolved.get(name) {
+            Some(func) => *func,
+            None => {
+                let func = native::find(name)
+                    .or_else(|| native::find(&name.replace('.', "_").to_lowercase()))
+                    .ok_or_else(|| format!("CommandBuffer.submit(): command {} ({}): unknown native function", calls.len() + 1, name))?;
+                resolved.insert(name.to_string(), func);
+                func
+            },
+        };
+        calls.push((name.to_string(), func, args.to_vec()));
+        Ok(())
+    })?;
+
+    // Le buffer n'est plus emprunté : une native peut y ajouter des commandes pour le prochain submit
+    let count = calls.len();
+    for (index, (name, func, args)) in calls.into_iter().enumerate() {
+        func(args).map_err(|e| format!("CommandBuffer.submit(): command {} ({}): {}", index + 1, name, e))?;
+    }
+    Ok(Value::Integer(count as i64))
+}
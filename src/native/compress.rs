@@ -0,0 +1,135 @@
+use crate::ast::{DictMap, Value};
+use crate::vm::gc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+use std::rc::Rc;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use super::{Args, NativeError};
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("gzip_compress".to_string(), gzip_compress);
+    map.insert("gzip_decompress".to_string(), gzip_decompress);
+    map.insert("zip_list".to_string(), zip_list);
+    map.insert("zip_extract".to_string(), zip_extract);
+    map.insert("zip_add".to_string(), zip_add);
+}
+
+fn gzip_compress(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Gzip.compress(data, level?)", &args);
+    args.at_most(2)?;
+    let data = args.want_data(0)?;
+    let level = args.opt_int(1, 6)?.clamp(0, 9) as u32;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level));
+    encoder.write_all(&data).map_err(|e| e.to_string())?;
+    let out = encoder.finish().map_err(|e| e.to_string())?;
+
+    Ok(Value::Bytes(Rc::new(RefCell::new(out))))
+}
+
+fn gzip_decompress(args: Vec<Value>) -> Result<Value, NativeError> {
+    let data = Args::new("Gzip.decompress(data)", &args).want_data(0)?;
+    let mut decoder = GzDecoder::new(&data[..]);
+    let mut out = Vec::new();
+    decoder.read_to_end(&mut out).map_err(|e| format!("Invalid gzip data: {}", e))?;
+
+    Ok(Value::Bytes(Rc::new(RefCell::new(out))))
+}
+
+fn zip_list(args: Vec<Value>) -> Result<Value, NativeError> {
+    let path = Args::new("Zip.list(archive)", &args).want_str(0)?;
+    let file = File::open(&path).map_err(|e| super::io_error(&e, &path))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| e.to_string())?;
+
+        let mut info = DictMap::new();
+        info.insert("name".into(), Value::String(entry.name().to_string().into()));
+        info.insert("size".into(), Value::Integer(entry.size() as i64));
+        info.insert("compressed_size".into(), Value::Integer(entry.compressed_size() as i64));
+        info.insert("is_dir".into(), Value::Boolean(entry.is_dir()));
+        entries.push(gc::dict(info));
+    }
+
+    Ok(gc::list(entries))
+}
+
+fn zip_extract(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Zip.extract(archive, dest)", &args);
+    let path = args.want_str(0)?;
+    let dest = args.want_str(1)?;
+    let file = File::open(&path).map_err(|e| super::io_error(&e, &path))?;
+
+    extract_archive(file, Path::new(&dest))?;
+    Ok(Value::Boolean(true))
+}
+
+fn zip_add(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Zip.add(archive, file, name?)", &args);
+    args.at_most(3)?;
+    let archive_path = args.want_str(0)?;
+    let file_path = args.want_str(1)?;
+    let entry_name = match args.get(2) {
+        Some(Value::Null) | None => Path::new(&file_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| format!("Invalid file path '{}'", file_path))?,
+        Some(_) => args.want_str(2)?,
+    };
+
+    let content = fs::read(&file_path).map_err(|e| super::io_error(&e, &file_path))?;
+
+    // Si l'archive existe déjà, on ajoute à la suite, sinon on la crée
+    let mut writer = if Path::new(&archive_path).exists() {
+        let file = OpenOptions::new().read(true).write(true).open(&archive_path)
+            .map_err(|e| e.to_string())?;
+        zip::ZipWriter::new_append(file).map_err(|e| e.to_string())?
+    } else {
+        let file = File::create(&archive_path).map_err(|e| e.to_string())?;
+        zip::ZipWriter::new(file)
+    };
+
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    writer.start_file(entry_name, options).map_err(|e| e.to_string())?;
+    writer.write_all(&content).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+
+    Ok(Value::Boolean(true))
+}
+
+/// Extrait une archive zip dans `dest` (utilisé aussi par le gestionnaire de paquets).
+pub(crate) fn extract_archive<R: Read + Seek>(reader: R, dest: &Path) -> Result<(), String> {
+    let mut archive = zip::ZipArchive::new(reader).map_err(|e| e.to_string())?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+
+        // enclosed_name refuse les chemins qui sortent du dossier cible (../)
+        let relative = match entry.enclosed_name() {
+            Some(p) => p.to_owned(),
+            None => continue,
+        };
+        let outpath = dest.join(relative);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
+        } else {
+            if let Some(p) = outpath.parent() {
+                fs::create_dir_all(p).map_err(|e| e.to_string())?;
+            }
+            let mut outfile = File::create(&outpath).map_err(|e| e.to_string())?;
+            io::copy(&mut entry, &mut outfile).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
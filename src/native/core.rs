@@ -1,8 +1,12 @@
 use crate::ast::Value;
+use crate::ast::value::{set_float_precision, set_float_scientific, float_precision, shortest_float};
+use crate::vm::gc;
+use super::{Args, NativeError};
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 pub fn register(map: &mut HashMap<String, super::NativeFn>) {
     map.insert("to_str".to_string(), to_str);
+    map.insert("str".to_string(), to_str);
     map.insert("to_int".to_string(), to_int);
     map.insert("to_float".to_string(), to_float);
     map.insert("to_bytes".to_string(), to_bytes);
@@ -10,43 +14,59 @@ pub fn register(map: &mut HashMap<String, super::NativeFn>) {
     map.insert("ord".to_string(), ord);
     map.insert("len".to_string(), len);
     map.insert("fmt".to_string(), fmt);
+    map.insert("divmod".to_string(), divmod);
+    map.insert("ceil_div".to_string(), ceil_div);
     map.insert("typeof".to_string(), type_of);
     map.insert("is_instance".to_string(), is_instance);
+    map.insert("float_set_display_precision".to_string(), float_set_display_precision);
+    map.insert("float_set_display_scientific".to_string(), float_set_display_scientific);
+    map.insert("float_display_precision".to_string(), float_display_precision);
+    map.insert("debug_name_of".to_string(), debug_name_of);
+    map.insert("object_id".to_string(), object_id);
+    map.insert("object_same".to_string(), object_same);
+    map.insert("object_copy".to_string(), object_copy);
+    map.insert("object_deep_copy".to_string(), object_deep_copy);
+    map.insert("reflect_doc".to_string(), reflect_doc_stub);
 }
 
-fn to_str(args: Vec<Value>) -> Result<Value, String> {
-    Ok(Value::String(format!("{}", args[0])))
+// La doc d'un module est gardée par la VM (cache des imports) : l'appel est intercepté (call_value)
+fn reflect_doc_stub(_args: Vec<Value>) -> Result<Value, NativeError> {
+    Err("reflect_doc() can only be called by the VM".into())
 }
 
-fn to_int(args: Vec<Value>) -> Result<Value, String> {
-    Ok(Value::Integer(args[0].as_int()?))
+fn to_str(args: Vec<Value>) -> Result<Value, NativeError> {
+    let value = Args::new("to_str(value)", &args).want(0)?;
+    Ok(Value::String(format!("{}", value).into()))
 }
 
-fn to_float(args: Vec<Value>) -> Result<Value, String> {
-    Ok(Value::Float(args[0].as_float()?))
+fn to_int(args: Vec<Value>) -> Result<Value, NativeError> {
+    // Conversion : accepte aussi les flottants et les chaînes numériques
+    let args = Args::new("to_int(value)", &args);
+    args.want(0)?.as_int().map(Value::Integer).map_err(|e| args.error(e).into())
 }
 
-fn to_bytes(args: Vec<Value>) -> Result<Value, String> {
-    let s = args[0].as_str()?;
+fn to_float(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("to_float(value)", &args);
+    args.want(0)?.as_float().map(Value::Float).map_err(|e| args.error(e).into())
+}
+
+fn to_bytes(args: Vec<Value>) -> Result<Value, NativeError> {
+    let s = Args::new("to_bytes(str)", &args).want_str(0)?;
     Ok(Value::Bytes(Rc::new(RefCell::new(s.as_bytes().to_vec()))))
 }
 
-fn chr(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("chr attend 1 argument (int)".into()); }
-    
-    let code = args[0].as_int()?;
+fn chr(args: Vec<Value>) -> Result<Value, NativeError> {
+    let code = Args::new("chr(code)", &args).want_int(0)?;
     // Conversion sécurisée u32 -> char
     if let Some(c) = std::char::from_u32(code as u32) {
-        Ok(Value::String(c.to_string()))
+        Ok(Value::String(c.to_string().into()))
     } else {
-        Err(format!("Code caractère invalide : {}", code))
+        Err(format!("Code caractère invalide : {}", code).into())
     }
 }
 
-fn ord(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("ord attend 1 argument (string)".into()); }
-    
-    let s = args[0].as_str()?;
+fn ord(args: Vec<Value>) -> Result<Value, NativeError> {
+    let s = Args::new("ord(char)", &args).want_str(0)?;
     // On prend le premier caractère
     if let Some(c) = s.chars().next() {
         Ok(Value::Integer(c as i64))
@@ -55,105 +75,167 @@ fn ord(args: Vec<Value>) -> Result<Value, String> {
     }
 }
 
-fn len(args: Vec<Value>) -> Result<Value, String> {
-    match &args[0] {
-        Value::String(s) => return Ok(Value::Integer(s.len() as i64)),
-        Value::List(l) => return Ok(Value::Integer(l.borrow().len() as i64)),
-        Value::Dict(d) => return Ok(Value::Integer(d.borrow().len() as i64)),
-        _ => return Err("Type not supported for len()".into())
+fn len(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("len(value)", &args);
+    match args.want(0)? {
+        Value::String(s) => Ok(Value::Integer(s.len() as i64)),
+        Value::List(l) => Ok(Value::Integer(l.borrow().len() as i64)),
+        Value::Dict(d) => Ok(Value::Integer(d.borrow().len() as i64)),
+        Value::TypedArray(a) => Ok(Value::Integer(a.borrow().len() as i64)),
+        other => Err(args.error(format!("argument 'value' must be a string, a list, a dict or a typed array, got {}", other.type_name())).into()),
     }
 }
 
-fn fmt(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 2 { return Err("fmt attend 2 arguments (valeur, format)".into()); }
-    
-    let val = &args[0];
-    let format_str = args[1].as_str()?;
-                                
-    // Parsing basique du format (ex: ".2f")
-    if format_str.ends_with("f") {
-        // Gestion des Floats
-        let precision = format_str.trim_start_matches('.').trim_end_matches('f')
-            .parse::<usize>().unwrap_or(2); // defaut 2
-                                    
-        let num = match val {
-            Value::Integer(i) => *i as f64,
-            Value::Float(f) => *f,
-            _ => return Ok(Value::String(format!("{}", val))) // Fallback
-        };
-                                    
+fn fmt(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("fmt(value, format)", &args);
+    args.at_most(2)?;
+    let val = args.want(0)?;
+    let format_str = args.want_str(1)?;
+
+    let num = match val {
+        Value::Integer(i) => *i as f64,
+        Value::Float(f) => *f,
+        _ => return Ok(Value::String(format!("{}", val).into())) // Fallback
+    };
+
+    // "r" : représentation la plus courte, même si une précision globale est définie
+    if format_str == "r" {
+        return Ok(Value::String(shortest_float(num).into()));
+    }
+
+    // Parsing basique du format (ex: ".2f", ".3e", ".3E" pour un exposant en majuscule)
+    if format_str.ends_with('f') || format_str.ends_with('e') || format_str.ends_with('E') {
+        let spec = format_str.trim_start_matches('.');
+        let precision = spec[..spec.len() - 1].parse::<usize>().ok();
+
         // Astuce Rust pour précision dynamique
-        return Ok(Value::String(format!("{:.1$}", num, precision)));
+        let out = match (format_str.ends_with('f'), precision) {
+            (false, Some(p)) => format!("{:.1$e}", num, p),
+            (false, None) => format!("{:e}", num),
+            (true, p) => format!("{:.1$}", num, p.unwrap_or(2)), // defaut 2
+        };
+        let out = if format_str.ends_with('E') { out.to_uppercase() } else { out };
+        return Ok(Value::String(out.into()));
     } 
                                 
     // Tu peux ajouter d'autres formats ici (ex: "b" pour binaire, "x" pour hexa...)
-    Ok(Value::String(format!("{}", val)))
+    Ok(Value::String(format!("{}", val).into()))
 }
 
-fn type_of(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("typeof attend 1 argument".into()); }
-                                
-    // On détermine le nom du type (String)
-    let type_name = match &args[0] {
-        Value::Integer(_) => "int".to_string(),
-        Value::Float(_) => "float".to_string(),
-        Value::String(_) => "string".to_string(),
-        Value::Boolean(_) => "bool".to_string(),
-        Value::Null => "null".to_string(),
-        Value::List(_) => "list".to_string(),
-        Value::Dict(_) => "dict".to_string(),
-        Value::Enum(_) => "enum".to_string(),
-        Value::Range(_, _, _) => "range".to_string(),
-                                    
-        Value::Function(..) => "function".to_string(),
-        Value::Class { .. } => "class".to_string(),
-        Value::Interface(_) => "interface".to_string(),
-        Value::Bytes(_) => "bytes".to_string(),
-                                    
-        // Pour l'instance, on récupère le nom dynamiquement
-        Value::Instance(i) => {
-            let borrow = i.borrow();
-            borrow.class.name.clone()
+// Précision d'affichage des Float pour print / str (null ou négatif = la plus courte)
+fn float_set_display_precision(args: Vec<Value>) -> Result<Value, NativeError> {
+    let precision = usize::try_from(Args::new("Float.set_display_precision(n)", &args).opt_int(0, -1)?).ok();
+    set_float_precision(precision);
+    Ok(Value::Null)
+}
+
+// Même chose en notation scientifique : 1234.5 -> 1.23e3 avec n = 2
+fn float_set_display_scientific(args: Vec<Value>) -> Result<Value, NativeError> {
+    let precision = usize::try_from(Args::new("Float.set_display_scientific(n)", &args).opt_int(0, -1)?).ok();
+    set_float_scientific(precision);
+    Ok(Value::Null)
+}
+
+fn float_display_precision(_: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(float_precision().map_or(Value::Null, |p| Value::Integer(p as i64)))
+}
+
+// [quotient, reste] arrondis vers -inf (comme Python) : divmod(-7, 2) -> [-4, 1]
+fn divmod(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("divmod(a, b)", &args);
+    args.at_most(2)?;
+    let (q, r) = match (args.want(0)?, args.want(1)?) {
+        (Value::Integer(_), Value::Integer(0)) => return Err("Division by zero".into()),
+        (Value::Integer(a), Value::Integer(b)) => {
+            let q = a.checked_div(*b)
+                .ok_or_else(|| format!("Integer overflow: divmod({}, {}) does not fit in an int", a, b))?;
+            let r = a.wrapping_rem(*b);
+            // Rust tronque vers zéro : le reste doit avoir le signe du diviseur
+            if r != 0 && (r < 0) != (*b < 0) {
+                (Value::Integer(q - 1), Value::Integer(r + b))
+            } else {
+                (Value::Integer(q), Value::Integer(r))
+            }
         },
-        Value::Native(_) => "function".to_string()
+        _ => {
+            let (a, b) = (args.want_number(0)?, args.want_number(1)?);
+            if b == 0.0 { return Err("Division by zero".into()); }
+            let q = (a / b).floor();
+            (Value::Float(q), Value::Float(a - q * b))
+        }
     };
 
-    Ok(Value::String(type_name))
+    Ok(gc::list(vec![q, r]))
+}
+
+// Pendant de `~/` arrondi vers +inf : ceil_div(7, 2) -> 4, ceil_div(-7, 2) -> -3
+fn ceil_div(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("ceil_div(a, b)", &args);
+    args.at_most(2)?;
+    match (args.want(0)?, args.want(1)?) {
+        (Value::Integer(_), Value::Integer(0)) => Err("Division by zero".into()),
+        (Value::Integer(a), Value::Integer(b)) => {
+            let q = a.checked_div(*b)
+                .ok_or_else(|| format!("Integer overflow: ceil_div({}, {}) does not fit in an int", a, b))?;
+            let r = a.wrapping_rem(*b);
+            // Reste non nul de même signe que le diviseur : le quotient exact était au-dessus
+            if r != 0 && (r < 0) == (*b < 0) { Ok(Value::Integer(q + 1)) } else { Ok(Value::Integer(q)) }
+        },
+        _ => {
+            let (a, b) = (args.want_number(0)?, args.want_number(1)?);
+            if b == 0.0 { return Err("Division by zero".into()); }
+            Ok(Value::Float((a / b).ceil()))
+        }
+    }
 }
 
-fn is_instance(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 2 { return Err("is_instance(obj, class)".into()); }
+// Nom enregistré par le compilateur dans le chunk ("Maths.square", "User.init", "<lambda>"...)
+fn debug_name_of(args: Vec<Value>) -> Result<Value, NativeError> {
+    match Args::new("Debug.name_of(f)", &args).want(0)? {
+        Value::Function(f) => Ok(Value::String(f.chunk.name.clone().into())),
+        Value::Native(name) => Ok(Value::String(name.clone().into())),
+        Value::Class(c) => Ok(Value::String(c.name.clone().into())),
+        other => Err(format!("Debug.name_of expects a function, got {}", other).into()),
+    }
+}
 
-    let instance = &args[0];
-    let target_class = &args[1];
+fn object_id(args: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Value::Integer(Args::new("Object.id(v)", &args).want(0)?.identity()))
+}
 
-    // 1. Récupération "tolérante" de la classe cible
-    // Si le 2ème argument n'est pas une classe (ex: "String" qui est une fonction), 
-    // on retourne 'false' au lieu de crasher. C'est le comportement de 'instanceof' en JS.
-    let target_rc = match target_class {
-        Value::Class(c) => c,
-        _ => return Ok(Value::Boolean(false)), 
-    };
+fn object_same(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Object.same(a, b)", &args);
+    Ok(Value::Boolean(args.want(0)?.is_same(args.want(1)?)))
+}
+
+fn object_copy(args: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Args::new("Object.copy(v)", &args).want(0)?.shallow_copy())
+}
 
-    if let Value::Instance(inst) = instance {
-        // On récupère le pointeur vers la classe de l'instance
-        let mut current_class = inst.borrow().class.clone();
+fn object_deep_copy(args: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Args::new("Object.deep_copy(v)", &args).want(0)?.deep_copy())
+}
 
-        loop {
-            // 2. Comparaison par POINTEUR (Reference Equality)
-            // Beaucoup plus rapide et fiable que '=='
-            if Rc::ptr_eq(&current_class, target_rc) {
-                return Ok(Value::Boolean(true));
-            }
+fn type_of(args: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Value::String(Args::new("typeof(value)", &args).want(0)?.type_name().into()))
+}
 
-            // 3. Remontée via la référence forte
-            if let Some(parent) = &current_class.parent_ref {
-                current_class = parent.clone();
-            } else {
-                break;
-            }
-        }
-    }
+fn is_instance(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("is_instance(obj, class)", &args);
+    let instance = args.want(0)?;
+    let target = args.want(1)?;
 
-    Ok(Value::Boolean(false))
+    let Value::Instance(inst) = instance else {
+        return Ok(Value::Boolean(false));
+    };
+    let class = &inst.borrow().class;
+
+    // Comparaison par POINTEUR avec la classe et ses ancêtres (liste précalculée).
+    // Si le 2ème argument n'est ni une classe ni une interface (ex: "String" qui est une fonction),
+    // on retourne 'false' au lieu de crasher. C'est le comportement de 'instanceof' en JS.
+    Ok(Value::Boolean(match target {
+        Value::Class(target_class) => class.is_subclass_of(target_class),
+        Value::Interface(interface) => class.implements(interface),
+        _ => false,
+    }))
 }
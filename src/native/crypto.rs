@@ -1,31 +1,162 @@
 use crate::{Value, NativeFn};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use base64::{Engine as _, engine::general_purpose};
-use sha2::{Sha256, Digest};
+use sha2::{Sha224, Sha256, Sha384, Sha512, Digest};
+use std::fs::File;
+use std::io::Read;
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use super::{Args, NativeError};
+
+// Un hasher incrémental, quel que soit l'algorithme choisi
+#[derive(Clone)]
+enum Hasher {
+    Sha224(Sha224),
+    Sha256(Sha256),
+    Sha384(Sha384),
+    Sha512(Sha512),
+}
+
+impl Hasher {
+    fn new(algo: &str) -> Result<Self, String> {
+        match algo.to_lowercase().as_str() {
+            "sha224" => Ok(Hasher::Sha224(Sha224::new())),
+            "sha256" => Ok(Hasher::Sha256(Sha256::new())),
+            "sha384" => Ok(Hasher::Sha384(Sha384::new())),
+            "sha512" => Ok(Hasher::Sha512(Sha512::new())),
+            _ => Err(format!("Unknown hash algorithm '{}' (expected sha224, sha256, sha384 or sha512)", algo)),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha224(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Sha384(h) => h.update(data),
+            Hasher::Sha512(h) => h.update(data),
+        }
+    }
+
+    fn hex(self) -> String {
+        match self {
+            Hasher::Sha224(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha256(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha384(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha512(h) => format!("{:x}", h.finalize()),
+        }
+    }
+}
+
+struct HashState {
+    hashers: HashMap<usize, Hasher>,
+    next_id: usize
+}
+
+lazy_static! {
+    static ref HASH_STATE: Mutex<HashState> = Mutex::new(HashState {
+        hashers: HashMap::new(),
+        next_id: 1,
+    });
+}
+
+// Taille des blocs lus lors du hachage d'un fichier
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
 
 pub fn register(map: &mut HashMap<String, NativeFn>) {
     map.insert("b64_encode".to_string(), b64_encode);
     map.insert("b64_decode".to_string(), b64_decode);
+    map.insert("b64_decode_bytes".to_string(), b64_decode_bytes);
     map.insert("hash_sha256".to_string(), hash_sha256);
+    map.insert("hash_file".to_string(), hash_file);
+    map.insert("hash_new".to_string(), hash_new);
+    map.insert("hash_update".to_string(), hash_update);
+    map.insert("hash_hex".to_string(), hash_hex);
+    map.insert("hash_free".to_string(), hash_free);
 }
 
-fn b64_encode(args: Vec<Value>) -> Result<Value, String> {
-    let input = args[0].as_str()?;
+// Chaîne (UTF-8) ou Bytes
+fn b64_encode(args: Vec<Value>) -> Result<Value, NativeError> {
+    let input = Args::new("Base64.encode(data)", &args).want_data(0)?;
     let encoded = general_purpose::STANDARD.encode(input);
-    Ok(Value::String(encoded))
+    Ok(Value::String(encoded.into()))
 }
 
-fn b64_decode(args: Vec<Value>) -> Result<Value, String> {
-    let input = args[0].as_str()?;
+fn b64_decode(args: Vec<Value>) -> Result<Value, NativeError> {
+    let input = Args::new("Base64.decode(str)", &args).want_str(0)?;
     let decoded_bytes = general_purpose::STANDARD.decode(input).map_err(|e| e.to_string())?;
     let decoded_str = String::from_utf8(decoded_bytes).map_err(|_| "Invalid UTF-8".to_string())?;
-    Ok(Value::String(decoded_str))
+    Ok(Value::String(decoded_str.into()))
+}
+
+// Données binaires (images, clés...) qui ne sont pas du texte UTF-8
+fn b64_decode_bytes(args: Vec<Value>) -> Result<Value, NativeError> {
+    let input = Args::new("Base64.decode_bytes(str)", &args).want_str(0)?;
+    let decoded = general_purpose::STANDARD.decode(input).map_err(|e| e.to_string())?;
+    Ok(Value::Bytes(Rc::new(RefCell::new(decoded))))
 }
 
-fn hash_sha256(args: Vec<Value>) -> Result<Value, String> {
-    let input = args[0].as_str()?;
+fn hash_sha256(args: Vec<Value>) -> Result<Value, NativeError> {
+    let input = Args::new("Hash.sha256(data)", &args).want_data(0)?;
     let mut hasher = Sha256::new();
     hasher.update(input);
     let result = hasher.finalize();
-    Ok(Value::String(format!("{:x}", result)))
+    Ok(Value::String(format!("{:x}", result).into()))
+}
+
+fn hash_file(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Hash.file(algo, path)", &args);
+    let mut hasher = Hasher::new(&args.want_str(0)?)?;
+    let path = args.want_str(1)?;
+    let mut file = File::open(&path).map_err(|e| super::io_error(&e, &path))?;
+
+    // On lit le fichier par blocs pour ne jamais le charger entièrement en mémoire
+    let mut buffer = vec![0u8; FILE_CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buffer).map_err(|e| super::io_error(&e, &path))?;
+        if n == 0 { break; }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(Value::String(hasher.hex().into()))
+}
+
+fn hash_new(args: Vec<Value>) -> Result<Value, NativeError> {
+    let hasher = Hasher::new(&Args::new("Hash.new(algo)", &args).want_str(0)?)?;
+
+    let mut state = HASH_STATE.lock().unwrap();
+    let id = state.next_id;
+    state.hashers.insert(id, hasher);
+    state.next_id += 1;
+
+    Ok(Value::Integer(id as i64))
+}
+
+fn hash_update(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("hasher.update(hasher, data)", &args);
+    let id = args.want_int(0)? as usize;
+    let data = args.want_data(1)?;
+
+    let mut state = HASH_STATE.lock().unwrap();
+    let hasher = state.hashers.get_mut(&id).ok_or("Hasher ID not found")?;
+    hasher.update(&data);
+
+    Ok(Value::Null)
+}
+
+fn hash_hex(args: Vec<Value>) -> Result<Value, NativeError> {
+    let id = Args::new("hasher.hex(hasher)", &args).want_int(0)? as usize;
+
+    let state = HASH_STATE.lock().unwrap();
+    let hasher = state.hashers.get(&id).ok_or("Hasher ID not found")?;
+
+    // On finalise une copie : le hasher reste utilisable après hex()
+    Ok(Value::String(hasher.clone().hex().into()))
+}
+
+fn hash_free(args: Vec<Value>) -> Result<Value, NativeError> {
+    let id = Args::new("hasher.free(hasher)", &args).want_int(0)? as usize;
+    let removed = HASH_STATE.lock().unwrap().hashers.remove(&id).is_some();
+    Ok(Value::Boolean(removed))
 }
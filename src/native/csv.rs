@@ -0,0 +1,427 @@
+use crate::ast::{Value, ValueKey};
+use crate::vm::gc;
+use super::{Args, NativeError};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+
+// Fichiers CSV ouverts par Csv.open / Csv.writer, lus ou écrits ligne à ligne
+struct CsvState {
+    readers: HashMap<usize, CsvReader>,
+    writers: HashMap<usize, CsvWriter>,
+    next_id: usize,
+}
+
+lazy_static! {
+    static ref CSV_STATE: Mutex<CsvState> = Mutex::new(CsvState {
+        readers: HashMap::new(),
+        writers: HashMap::new(),
+        next_id: 1,
+    });
+}
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("csv_parse".to_string(), csv_parse);
+    map.insert("csv_stringify".to_string(), csv_stringify);
+    map.insert("csv_open".to_string(), csv_open);
+    map.insert("csv_next".to_string(), csv_next);
+    map.insert("csv_writer".to_string(), csv_writer);
+    map.insert("csv_write".to_string(), csv_write);
+    map.insert("csv_close".to_string(), csv_close);
+}
+
+#[derive(Clone)]
+pub(crate) struct CsvOptions {
+    header: bool,
+    delimiter: char,
+    quote: char,
+    // Noms de colonnes imposés (à la place de l'en-tête, ou pour choisir l'ordre à l'écriture)
+    columns: Option<Vec<String>>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self { header: true, delimiter: ',', quote: '"', columns: None }
+    }
+}
+
+// `null` (défaut), un booléen (l'ancien paramètre `header`) ou un dict
+// { header, delimiter, quote, columns }
+fn options(args: &Args, index: usize) -> Result<CsvOptions, String> {
+    let mut options = CsvOptions::default();
+    match args.get(index) {
+        None | Some(Value::Null) => {},
+        Some(Value::Boolean(header)) => options.header = *header,
+        Some(Value::Dict(dict)) => {
+            for (key, value) in dict.borrow().iter() {
+                let name = match key {
+                    ValueKey::String(name) => name.as_str(),
+                    other => return Err(args.error(format!("unknown option {}", other))),
+                };
+                match (name, value) {
+                    ("header", Value::Boolean(header)) => options.header = *header,
+                    ("delimiter", Value::String(s)) => options.delimiter = single_char(args, name, s)?,
+                    ("quote", Value::String(s)) => options.quote = single_char(args, name, s)?,
+                    ("columns", Value::List(list)) => {
+                        let columns = list.borrow().iter().map(|column| match column {
+                            Value::String(s) => Ok(s.to_string()),
+                            other => Err(args.error(format!("option 'columns' must only hold strings, got {}", other.type_name()))),
+                        }).collect::<Result<Vec<_>, _>>()?;
+                        options.columns = Some(columns);
+                    },
+                    ("header", _) | ("delimiter", _) | ("quote", _) | ("columns", _) => {
+                        let expected = match name {
+                            "header" => "a bool",
+                            "columns" => "a list of strings",
+                            _ => "a string",
+                        };
+                        return Err(args.error(format!("option '{}' must be {}, got {}", name, expected, value.type_name())));
+                    },
+                    _ => return Err(args.error(format!(
+                        "unknown option '{}' (expected header, delimiter, quote or columns)", name))),
+                }
+            }
+        },
+        Some(_) => return Err(args.expected(index, "a dict of options")),
+    }
+    if options.delimiter == options.quote {
+        return Err(args.error("the delimiter and the quote must be different characters"));
+    }
+    Ok(options)
+}
+
+fn single_char(args: &Args, name: &str, s: &str) -> Result<char, String> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '\n' && c != '\r' => Ok(c),
+        _ => Err(args.error(format!("option '{}' must be a single character, got \"{}\"", name, s))),
+    }
+}
+
+fn csv_parse(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Csv.parse(str, options?)", &args);
+    args.at_most(2)?;
+    let text = args.want_str(0)?;
+    let options = options(&args, 1)?;
+    parse(&text, &options).map_err(NativeError::from)
+}
+
+// Aussi utilisé par `import "x.csv" as x` (avec en-tête). Les champs restent des chaînes.
+// Avec en-tête : une liste de dicts indexés par les noms de colonnes ; sinon une liste de listes.
+pub(crate) fn parse(text: &str, options: &CsvOptions) -> Result<Value, String> {
+    let mut parser = Parser::new(options);
+    for chunk in text.split_inclusive('\n') {
+        parser.feed(chunk);
+    }
+    parser.finish()?;
+
+    let mut columns = options.columns.clone();
+    let mut header = options.header;
+    let mut items = Vec::new();
+    for (line, row) in parser.records {
+        if std::mem::take(&mut header) {
+            columns.get_or_insert(row);
+            continue;
+        }
+        items.push(to_row(columns.as_deref(), line, row)?);
+    }
+    Ok(gc::list(items))
+}
+
+// Une ligne plus courte que l'en-tête laisse les dernières colonnes à null
+fn to_row(columns: Option<&[String]>, line: usize, row: Vec<String>) -> Result<Value, String> {
+    let Some(columns) = columns else {
+        return Ok(gc::list(row.into_iter().map(|s| Value::String(s.into())).collect()));
+    };
+    if row.len() > columns.len() {
+        return Err(format!("Invalid CSV: line {} has {} fields, the header has {}", line, row.len(), columns.len()));
+    }
+    let mut values = row.into_iter().map(|s| Value::String(s.into()));
+    let dict = columns.iter()
+        .map(|column| (column.as_str().into(), values.next().unwrap_or(Value::Null)))
+        .collect();
+    Ok(gc::dict(dict))
+}
+
+// Enregistrements (ligne de début, champs) au format RFC 4180 : champs séparés par le délimiteur,
+// entre guillemets s'ils contiennent le délimiteur, un guillemet (doublé) ou un retour à la ligne.
+// Les lignes vides sont ignorées. Le texte arrive ligne par ligne : un champ entre guillemets
+// peut s'étendre sur plusieurs lignes.
+struct Parser {
+    delimiter: char,
+    quote: char,
+    records: VecDeque<(usize, Vec<String>)>,
+    row: Vec<String>,
+    field: String,
+    quoted: bool,
+    // Le champ courant a commencé par un guillemet (même refermé)
+    was_quoted: bool,
+    line: usize,
+    row_line: usize,
+}
+
+impl Parser {
+    fn new(options: &CsvOptions) -> Self {
+        Parser {
+            delimiter: options.delimiter,
+            quote: options.quote,
+            records: VecDeque::new(),
+            row: Vec::new(),
+            field: String::new(),
+            quoted: false,
+            was_quoted: false,
+            line: 1,
+            row_line: 1,
+        }
+    }
+
+    fn feed(&mut self, chunk: &str) {
+        let mut chars = chunk.chars().peekable();
+        while let Some(c) = chars.next() {
+            if self.quoted {
+                match c {
+                    c if c == self.quote && chars.peek() == Some(&self.quote) => {
+                        chars.next();
+                        self.field.push(self.quote);
+                    },
+                    c if c == self.quote => self.quoted = false,
+                    '\n' => {
+                        self.line += 1;
+                        self.field.push(c);
+                    },
+                    _ => self.field.push(c),
+                }
+                continue;
+            }
+            match c {
+                c if c == self.quote && self.field.is_empty() && !self.was_quoted => {
+                    self.quoted = true;
+                    self.was_quoted = true;
+                },
+                c if c == self.delimiter => {
+                    self.row.push(std::mem::take(&mut self.field));
+                    self.was_quoted = false;
+                },
+                '\r' if chars.peek() == Some(&'\n') => {},
+                '\n' => {
+                    self.end_row();
+                    self.line += 1;
+                    self.row_line = self.line;
+                },
+                _ => self.field.push(c),
+            }
+        }
+    }
+
+    fn end_row(&mut self) {
+        if !self.row.is_empty() || !self.field.is_empty() || self.was_quoted {
+            self.row.push(std::mem::take(&mut self.field));
+            self.records.push_back((self.row_line, std::mem::take(&mut self.row)));
+        }
+        self.was_quoted = false;
+    }
+
+    // Fin du texte : la dernière ligne n'a pas forcément de retour à la ligne
+    fn finish(&mut self) -> Result<(), String> {
+        if self.quoted {
+            return Err(format!("Invalid CSV: unterminated quoted field starting on line {}", self.row_line));
+        }
+        self.end_row();
+        Ok(())
+    }
+}
+
+fn csv_stringify(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Csv.stringify(rows, options?)", &args);
+    args.at_most(2)?;
+    let rows = args.want_list(0)?;
+    let options = options(&args, 1)?;
+
+    let mut writer = Writer::new(options);
+    let mut out = String::new();
+    for (i, row) in rows.borrow().iter().enumerate() {
+        writer.write(row, &mut out).map_err(|e| args.error(format!("row {}: {}", i, e)))?;
+    }
+    Ok(Value::String(out.into()))
+}
+
+// Écrit des lignes (listes, ou dicts dont les colonnes viennent de `columns` ou des clés de la
+// première ligne). L'en-tête est écrit avant la première ligne de dicts si `header` est vrai.
+struct Writer {
+    options: CsvOptions,
+    columns: Option<Vec<String>>,
+    started: bool,
+}
+
+impl Writer {
+    fn new(options: CsvOptions) -> Self {
+        let columns = options.columns.clone();
+        Writer { options, columns, started: false }
+    }
+
+    fn write(&mut self, row: &Value, out: &mut String) -> Result<(), String> {
+        let fields: Vec<String> = match row {
+            Value::List(list) => list.borrow().iter().map(field_text).collect(),
+            Value::Dict(dict) => {
+                let dict = dict.borrow();
+                let columns = self.columns.get_or_insert_with(|| dict.keys().map(|key| key.to_string()).collect()).clone();
+                if !self.started && self.options.header {
+                    self.line(&columns, out);
+                }
+                columns.iter()
+                    .map(|column| dict.get(&ValueKey::from(column.as_str())).map(field_text).unwrap_or_default())
+                    .collect()
+            },
+            other => return Err(format!("a row must be a list or a dict, got {}", other.type_name())),
+        };
+        self.started = true;
+        self.line(&fields, out);
+        Ok(())
+    }
+
+    fn line(&self, fields: &[String], out: &mut String) {
+        let quote = self.options.quote;
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                out.push(self.options.delimiter);
+            }
+            let needs_quotes = field.chars().any(|c| c == self.options.delimiter || c == quote || c == '\n' || c == '\r');
+            if needs_quotes {
+                out.push(quote);
+                for c in field.chars() {
+                    if c == quote {
+                        out.push(quote);
+                    }
+                    out.push(c);
+                }
+                out.push(quote);
+            } else {
+                out.push_str(field);
+            }
+        }
+        out.push('\n');
+    }
+}
+
+// null donne un champ vide, les autres valeurs leur texte
+fn field_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+// Lecture en flux : le fichier est lu ligne à ligne, sans être chargé en entier
+struct CsvReader {
+    path: String,
+    reader: BufReader<File>,
+    parser: Parser,
+    columns: Option<Vec<String>>,
+    header: bool,
+    done: bool,
+}
+
+struct CsvWriter {
+    path: String,
+    file: BufWriter<File>,
+    writer: Writer,
+}
+
+fn csv_open(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Csv.open(path, options?)", &args);
+    args.at_most(2)?;
+    let path = args.want_str(0)?;
+    let options = options(&args, 1)?;
+    let file = File::open(&path).map_err(|e| super::io_error(&e, &path))?;
+
+    let reader = CsvReader {
+        path,
+        reader: BufReader::new(file),
+        parser: Parser::new(&options),
+        columns: options.columns.clone(),
+        header: options.header,
+        done: false,
+    };
+    let mut state = CSV_STATE.lock().unwrap();
+    let id = state.next_id;
+    state.readers.insert(id, reader);
+    state.next_id += 1;
+    Ok(Value::Integer(id as i64))
+}
+
+// Ligne suivante (dict ou liste), null à la fin du fichier
+fn csv_next(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("reader.next(handle)", &args);
+    let id = args.want_int(0)? as usize;
+    let mut state = CSV_STATE.lock().unwrap();
+    let reader = state.readers.get_mut(&id).ok_or("CSV reader is closed")?;
+
+    loop {
+        if let Some((line, row)) = reader.parser.records.pop_front() {
+            // `header` : la première ligne reste à lire comme en-tête
+            if std::mem::take(&mut reader.header) {
+                reader.columns.get_or_insert(row);
+                continue;
+            }
+            return to_row(reader.columns.as_deref(), line, row)
+                .map_err(|e| format!("{} ({})", e, reader.path).into());
+        }
+        if reader.done {
+            return Ok(Value::Null);
+        }
+        let mut chunk = String::new();
+        let read = reader.reader.read_line(&mut chunk).map_err(|e| super::io_error(&e, &reader.path))?;
+        if read == 0 {
+            reader.done = true;
+            reader.parser.finish().map_err(|e| format!("{} ({})", e, reader.path))?;
+        } else {
+            reader.parser.feed(&chunk);
+        }
+    }
+}
+
+fn csv_writer(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Csv.writer(path, options?)", &args);
+    args.at_most(2)?;
+    let path = args.want_str(0)?;
+    let options = options(&args, 1)?;
+    let file = File::create(&path).map_err(|e| super::io_error(&e, &path))?;
+
+    let writer = CsvWriter { path, file: BufWriter::new(file), writer: Writer::new(options) };
+    let mut state = CSV_STATE.lock().unwrap();
+    let id = state.next_id;
+    state.writers.insert(id, writer);
+    state.next_id += 1;
+    Ok(Value::Integer(id as i64))
+}
+
+fn csv_write(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("writer.write(handle, row)", &args);
+    let id = args.want_int(0)? as usize;
+    let row = args.want(1)?;
+    let mut state = CSV_STATE.lock().unwrap();
+    let csv = state.writers.get_mut(&id).ok_or("CSV writer is closed")?;
+
+    let mut out = String::new();
+    csv.writer.write(row, &mut out).map_err(|e| args.error(e))?;
+    csv.file.write_all(out.as_bytes()).map_err(|e| super::io_error(&e, &csv.path))?;
+    Ok(Value::Null)
+}
+
+// Ferme un lecteur ou un écrivain (les données en attente sont écrites)
+fn csv_close(args: Vec<Value>) -> Result<Value, NativeError> {
+    let id = Args::new("csv.close(handle)", &args).want_int(0)? as usize;
+    let mut state = CSV_STATE.lock().unwrap();
+    if state.readers.remove(&id).is_some() {
+        return Ok(Value::Boolean(true));
+    }
+    match state.writers.remove(&id) {
+        Some(mut csv) => {
+            csv.file.flush().map_err(|e| super::io_error(&e, &csv.path))?;
+            Ok(Value::Boolean(true))
+        },
+        None => Ok(Value::Boolean(false)),
+    }
+}
@@ -1,20 +1,191 @@
 use crate::{Value, NativeFn};
+use crate::ast::Zone;
 use std::collections::HashMap;
-use chrono::Local;
+use std::str::FromStr;
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Timelike, Utc};
+use super::{Args, NativeError};
+
+// Unités de l'affichage des durées, de la plus grande à la plus petite (en ms)
+const UNITS: [(&str, u64); 5] = [("d", 86_400_000), ("h", 3_600_000), ("m", 60_000), ("s", 1000), ("ms", 1)];
 
 pub fn register(map: &mut HashMap<String, NativeFn>) {
     map.insert("date_now".to_string(), date_now);
     map.insert("date_format".to_string(), date_format);
+    map.insert("date_current".to_string(), date_current);
+    map.insert("date_parse".to_string(), date_parse);
+    map.insert("date_parse_in".to_string(), date_parse_in);
+    map.insert("date_from_timestamp".to_string(), date_from_timestamp);
 }
 
-fn date_now(_: Vec<Value>) -> Result<Value, String> {
+fn date_now(_: Vec<Value>) -> Result<Value, NativeError> {
     // Retourne le timestamp ISO 8601
-    Ok(Value::String(Local::now().to_rfc3339()))
+    Ok(Value::String(Local::now().to_rfc3339().into()))
 }
 
-fn date_format(args: Vec<Value>) -> Result<Value, String> {
-    // args: [format_str] (utilise l'heure actuelle) ou [timestamp_iso, format_str]
+fn date_format(args: Vec<Value>) -> Result<Value, NativeError> {
+    // Formate l'heure actuelle
     let now = Local::now();
-    let fmt = args[0].as_str()?;
-    Ok(Value::String(now.format(&fmt).to_string()))
+    let fmt = Args::new("Date.format(fmt)", &args).want_str(0)?;
+    Ok(Value::String(now.format(&fmt).to_string().into()))
+}
+
+fn date_current(_: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Value::DateTime(Utc::now().timestamp_millis(), Zone::Local))
+}
+
+// ISO 8601 avec décalage ("2024-03-10T12:00:00+01:00", "...Z"), sinon heure locale
+fn date_parse(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Date.parse(text)", &args);
+    let text = args.want_str(0)?;
+    if let Ok(dt) = DateTime::parse_from_rfc3339(&text) {
+        let zone = if dt.offset().local_minus_utc() == 0 && text.ends_with(['Z', 'z']) { Zone::Utc } else { Zone::Fixed(*dt.offset()) };
+        return Ok(Value::DateTime(dt.timestamp_millis(), zone));
+    }
+    let naive = parse_naive(&text).ok_or_else(|| args.error(format!("invalid date '{}'", text)))?;
+    Ok(Value::DateTime(wall_clock(naive, Zone::Local).map_err(|e| args.error(e))?, Zone::Local))
+}
+
+// Heure murale d'un fuseau : Date.parse_in("2024-03-31 09:00", "Europe/Paris")
+fn date_parse_in(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Date.parse_in(text, tz)", &args);
+    let text = args.want_str(0)?;
+    let zone = parse_zone(&args.want_str(1)?).map_err(|e| args.error(e))?;
+    let naive = parse_naive(&text).ok_or_else(|| args.error(format!("invalid date '{}'", text)))?;
+    Ok(Value::DateTime(wall_clock(naive, zone).map_err(|e| args.error(e))?, zone))
+}
+
+fn date_from_timestamp(args: Vec<Value>) -> Result<Value, NativeError> {
+    let ms = Args::new("Date.from_timestamp(ms)", &args).want_int(0)?;
+    Ok(Value::DateTime(ms, Zone::Local))
+}
+
+fn parse_naive(text: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"].iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(text, fmt).ok())
+        .or_else(|| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)))
+}
+
+// Instant d'une heure murale ; au recul de l'heure (heure ambiguë), la première occurrence
+fn wall_clock(naive: NaiveDateTime, zone: Zone) -> Result<i64, String> {
+    let instant = match zone {
+        Zone::Local => Local.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp_millis()),
+        Zone::Utc => Some(Utc.from_utc_datetime(&naive).timestamp_millis()),
+        Zone::Named(tz) => tz.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp_millis()),
+        Zone::Fixed(offset) => offset.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp_millis()),
+    };
+    instant.ok_or_else(|| format!("{} does not exist in {} (clock change)", naive, zone_name(zone)))
+}
+
+/// "local", "UTC", un nom IANA ("Europe/Paris") ou un décalage ("+02:00").
+pub fn parse_zone(name: &str) -> Result<Zone, String> {
+    match name {
+        "local" => Ok(Zone::Local),
+        "UTC" | "utc" | "Z" => Ok(Zone::Utc),
+        _ => chrono_tz::Tz::from_str(name).map(Zone::Named)
+            .or_else(|_| FixedOffset::from_str(name).map(Zone::Fixed))
+            .map_err(|_| format!("unknown time zone '{}'", name)),
+    }
+}
+
+pub fn zone_name(zone: Zone) -> String {
+    match zone {
+        Zone::Local => "local".to_string(),
+        Zone::Utc => "UTC".to_string(),
+        Zone::Named(tz) => tz.name().to_string(),
+        Zone::Fixed(offset) => offset.to_string(),
+    }
+}
+
+/// L'instant `ms` vu dans son fuseau.
+pub fn local_time(ms: i64, zone: Zone) -> Result<DateTime<FixedOffset>, String> {
+    let utc = DateTime::<Utc>::from_timestamp_millis(ms).ok_or_else(|| format!("date out of range ({} ms)", ms))?;
+    Ok(match zone {
+        Zone::Local => utc.with_timezone(&Local).fixed_offset(),
+        Zone::Utc => utc.fixed_offset(),
+        Zone::Named(tz) => utc.with_timezone(&tz).fixed_offset(),
+        Zone::Fixed(offset) => utc.with_timezone(&offset),
+    })
+}
+
+pub fn iso(ms: i64, zone: Zone) -> Result<String, String> {
+    Ok(local_time(ms, zone)?.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+/// "2h 3m" : au plus `max_units` unités consécutives à partir de la plus grande (tronqué).
+pub fn humanize(ms: i64, max_units: usize) -> String {
+    let mut rest = ms.unsigned_abs();
+    let mut parts = Vec::new();
+    let mut first = None;
+    for (i, (unit, size)) in UNITS.iter().enumerate() {
+        let count = rest / size;
+        rest %= size;
+        if count > 0 && first.is_none() {
+            first = Some(i);
+        }
+        match first {
+            Some(first) if i - first >= max_units => break,
+            Some(_) if count > 0 => parts.push(format!("{}{}", count, unit)),
+            _ => {},
+        }
+    }
+    if parts.is_empty() {
+        return "0s".to_string();
+    }
+    let sign = if ms < 0 { "-" } else { "" };
+    format!("{}{}", sign, parts.join(" "))
+}
+
+// Millisecondes d'une quantité flottante, arrondies
+pub fn millis(amount: f64) -> Result<i64, String> {
+    let ms = amount.round();
+    if !ms.is_finite() || ms.abs() >= i64::MAX as f64 {
+        return Err(format!("duration out of range ({} ms)", amount));
+    }
+    Ok(ms as i64)
+}
+
+/// Méthodes d'une durée : d.seconds(), d.humanize()...
+pub fn duration_method(ms: i64, method: &str, args: &[Value]) -> Result<Value, String> {
+    Ok(match method {
+        "millis" => Value::Integer(ms),
+        "seconds" => Value::Float(ms as f64 / 1000.0),
+        "minutes" => Value::Float(ms as f64 / 60_000.0),
+        "hours" => Value::Float(ms as f64 / 3_600_000.0),
+        "days" => Value::Float(ms as f64 / 86_400_000.0),
+        "abs" => Value::Duration(ms.saturating_abs()),
+        "humanize" => {
+            let units = Args::new("duration.humanize(units?)", args).opt_int(0, 2)?;
+            Value::String(humanize(ms, units.max(1) as usize).into())
+        },
+        _ => return Err(format!("Unknown duration method '{}'", method)),
+    })
+}
+
+/// Méthodes d'une date : dt.in_tz("Europe/Paris"), dt.year()...
+pub fn datetime_method(ms: i64, zone: Zone, method: &str, args: &[Value]) -> Result<Value, String> {
+    let local = local_time(ms, zone)?;
+    Ok(match method {
+        "in_tz" => {
+            let args = Args::new("datetime.in_tz(tz)", args);
+            Value::DateTime(ms, parse_zone(&args.want_str(0)?).map_err(|e| args.error(e))?)
+        },
+        "timestamp" => Value::Integer(ms),
+        "timezone" => Value::String(zone_name(zone).into()),
+        "offset" => Value::Duration(local.offset().local_minus_utc() as i64 * 1000),
+        "iso" => Value::String(iso(ms, zone)?.into()),
+        "format" => {
+            let fmt = Args::new("datetime.format(fmt)", args).want_str(0)?;
+            Value::String(local.format(&fmt).to_string().into())
+        },
+        "year" => Value::Integer(local.year() as i64),
+        "month" => Value::Integer(local.month() as i64),
+        "day" => Value::Integer(local.day() as i64),
+        "hour" => Value::Integer(local.hour() as i64),
+        "minute" => Value::Integer(local.minute() as i64),
+        "second" => Value::Integer(local.second() as i64),
+        "millisecond" => Value::Integer(ms.rem_euclid(1000)),
+        // ISO : 1 = lundi ... 7 = dimanche
+        "weekday" => Value::Integer(local.weekday().number_from_monday() as i64),
+        _ => return Err(format!("Unknown datetime method '{}'", method)),
+    })
 }
@@ -0,0 +1,113 @@
+use crate::ast::value::ClassData;
+use crate::ast::{InstanceData, Value};
+use crate::vm::gc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use super::NativeError;
+
+// Classes d'erreur fournies par le langage : Error et ses sous-classes. Comme les constantes
+// des plugins, elles ne prennent pas de slot global : le compilateur les remplace par leur
+// valeur (find_constant), et un script peut en hériter (class ParseError extends ValueError).
+//
+// Une instance porte `message`, `kind` (le nom de sa classe), `stack` (les appels en cours
+// quand elle a été levée, rempli par la VM) et `data` (détails libres, ou null).
+const HIERARCHY: [(&str, Option<&str>); 5] = [
+    ("Error", None),
+    ("RuntimeError", Some("Error")),
+    ("TypeError", Some("Error")),
+    ("ValueError", Some("Error")),
+    ("IOError", Some("Error")),
+];
+
+thread_local! {
+    // Construites à la première utilisation, une fois par thread (les Rc ne se partagent pas)
+    static CLASSES: HashMap<String, Rc<ClassData>> = build_classes();
+}
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("Error.init".to_string(), error_init);
+}
+
+fn build_classes() -> HashMap<String, Rc<ClassData>> {
+    let mut classes: HashMap<String, Rc<ClassData>> = HashMap::new();
+    for (name, parent) in HIERARCHY {
+        let mut methods = HashMap::new();
+        if parent.is_none() {
+            methods.insert("init".to_string(), Value::Native("Error.init".to_string()));
+        }
+        let data = ClassData {
+            name: name.to_string(),
+            parent: parent.map(str::to_string),
+            parent_ref: None,
+            methods,
+            fields: HashMap::new(),
+            field_types: HashMap::new(),
+            properties: HashMap::new(),
+            static_methods: HashMap::new(),
+            static_fields: RefCell::new(HashMap::new()),
+            static_field_types: HashMap::new(),
+            static_properties: HashMap::new(),
+            is_final: false,
+            final_methods: Default::default(),
+            interfaces: Vec::new(),
+            interfaces_names: Vec::new(),
+            visibilities: HashMap::new(),
+            ancestors: Vec::new(),
+            method_table: HashMap::new(),
+            static_method_table: HashMap::new(),
+            field_slots: HashMap::new(),
+            field_layout: Vec::new(),
+            doc: None,
+        }.link(parent.map(|p| classes[p].clone()));
+        classes.insert(name.to_string(), Rc::new(data));
+    }
+    classes
+}
+
+/// Classe d'erreur intégrée portant ce nom (Error, IOError...).
+pub fn class(name: &str) -> Option<Rc<ClassData>> {
+    CLASSES.with(|classes| classes.get(name).cloned())
+}
+
+/// Vrai si `class` est Error ou en hérite (classes des scripts comprises).
+pub fn is_error_class(class: &Rc<ClassData>) -> bool {
+    CLASSES.with(|classes| class.is_subclass_of(&classes["Error"]))
+}
+
+/// Crée une instance de la classe d'erreur intégrée `kind`, avec des champs en plus (code...).
+pub fn new_error(kind: &str, message: &str, data: Value, extra: Vec<(&str, Value)>) -> Value {
+    let class = class(kind).unwrap_or_else(|| class("Error").expect("Error class"));
+    let mut instance = InstanceData::new(class);
+    fill(&mut instance, Value::String(message.to_string().into()), data);
+    for (name, value) in extra {
+        instance.set_field(name.to_string(), value);
+    }
+
+    let value = Value::Instance(Rc::new(RefCell::new(instance)));
+    gc::track(&value);
+    value
+}
+
+fn fill(instance: &mut InstanceData, message: Value, data: Value) {
+    let kind = instance.class.name.clone();
+    instance.set_field("message".to_string(), message);
+    instance.set_field("kind".to_string(), Value::String(kind.into()));
+    instance.set_field("stack".to_string(), Value::Null);
+    instance.set_field("data".to_string(), data);
+}
+
+// Constructeur commun : new ValueError(message) ou new ValueError(message, data)
+fn error_init(args: Vec<Value>) -> Result<Value, NativeError> {
+    let Some(Value::Instance(instance)) = args.first() else {
+        return Err("Error.init() must be called on an instance".into());
+    };
+    let message = match args.get(1) {
+        Some(Value::String(s)) => Value::String(s.clone()),
+        Some(Value::Null) | None => Value::String(String::new().into()),
+        Some(other) => Value::String(other.to_string().into()),
+    };
+    let data = args.get(2).cloned().unwrap_or(Value::Null);
+    fill(&mut instance.borrow_mut(), message, data);
+    Ok(Value::Null)
+}
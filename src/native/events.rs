@@ -0,0 +1,118 @@
+use crate::ast::{InstanceData, Value};
+use crate::vm::gc;
+use super::{Args, NativeError};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+// Un abonnement : le callback est gardé tant que `off` n'est pas appelé,
+// sauf s'il est lié à un propriétaire (référence faible) qui a disparu entre-temps.
+struct Subscription {
+    id: i64,
+    topic: String,
+    callback: Value,
+    owner: Option<Weak<RefCell<InstanceData>>>,
+}
+
+impl Subscription {
+    fn is_alive(&self) -> bool {
+        self.owner.as_ref().is_none_or(|w| w.strong_count() > 0)
+    }
+}
+
+#[derive(Default)]
+struct EventBus {
+    subscriptions: Vec<Subscription>,
+    next_id: i64,
+}
+
+thread_local! {
+    static BUS: RefCell<EventBus> = RefCell::new(EventBus::default());
+}
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("events_on".to_string(), events_on);
+    map.insert("events_off".to_string(), events_off);
+    map.insert("events_listeners".to_string(), events_listeners);
+    map.insert("events_clear".to_string(), events_clear);
+}
+
+/// Callbacks encore valides pour `topic`, dans l'ordre d'abonnement : (callback, propriétaire).
+/// Les abonnements dont le propriétaire a été libéré sont retirés au passage.
+pub(crate) fn listeners(topic: &str) -> Vec<(Value, Option<Value>)> {
+    BUS.with(|bus| {
+        let mut bus = bus.borrow_mut();
+        bus.subscriptions.retain(|s| s.is_alive());
+
+        bus.subscriptions.iter()
+            .filter(|s| s.topic == topic)
+            .map(|s| {
+                let owner = s.owner.as_ref().and_then(|w| w.upgrade()).map(Value::Instance);
+                (s.callback.clone(), owner)
+            })
+            .collect()
+    })
+}
+
+fn events_on(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Events.on(topic, callback, owner?)", &args);
+    let topic = args.want_str(0)?;
+    let callback = match args.want(1)? {
+        f @ (Value::Function(_) | Value::Native(_)) => f.clone(),
+        _ => return Err(args.expected(1, "a function").into()),
+    };
+    let owner = match args.get(2) {
+        None | Some(Value::Null) => None,
+        Some(Value::Instance(inst)) => Some(Rc::downgrade(inst)),
+        Some(_) => return Err(args.expected(2, "an object").into()),
+    };
+
+    let id = BUS.with(|bus| {
+        let mut bus = bus.borrow_mut();
+        bus.next_id += 1;
+        let id = bus.next_id;
+        bus.subscriptions.push(Subscription { id, topic, callback, owner });
+        id
+    });
+
+    Ok(Value::Integer(id))
+}
+
+fn events_off(args: Vec<Value>) -> Result<Value, NativeError> {
+    let id = Args::new("Events.off(handle)", &args).want_int(0)?;
+    let removed = BUS.with(|bus| {
+        let mut bus = bus.borrow_mut();
+        let before = bus.subscriptions.len();
+        bus.subscriptions.retain(|s| s.id != id);
+        bus.subscriptions.len() != before
+    });
+
+    Ok(Value::Boolean(removed))
+}
+
+// Liste de [callback, propriétaire ou null]
+fn events_listeners(args: Vec<Value>) -> Result<Value, NativeError> {
+    let topic = Args::new("Events.listeners(topic)", &args).want_str(0)?;
+    let entries = listeners(&topic).into_iter()
+        .map(|(callback, owner)| {
+            gc::list(vec![callback, owner.unwrap_or(Value::Null)])
+        })
+        .collect();
+
+    Ok(gc::list(entries))
+}
+
+// Retire tous les abonnements d'un topic (ou tous si topic est null)
+fn events_clear(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Events.clear(topic?)", &args);
+    let topic = match args.get(0) {
+        None | Some(Value::Null) => None,
+        Some(_) => Some(args.want_str(0)?),
+    };
+
+    BUS.with(|bus| {
+        bus.borrow_mut().subscriptions.retain(|s| topic.as_ref().is_some_and(|t| &s.topic != t));
+    });
+
+    Ok(Value::Null)
+}
@@ -0,0 +1,199 @@
+use crate::ast::Value;
+use super::{Args, NativeError};
+use libffi::middle::{arg, Arg, Cif, CodePtr, Type};
+use libloading::Library;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::sync::{Mutex, OnceLock};
+
+// Fonctions C appelées directement : `extern "libm.so.6" func cos(x: float) -> float` devient
+// `cos = ffi_bind(...)`, qui charge la bibliothèque, trouve le symbole et renvoie une native
+// "extern libm.so.6:cos". La VM l'appelle via `call`, qui convertit les arguments selon la
+// signature déclarée.
+//
+// L'appel passe par libffi : un `Cif` décrit le prototype déclaré (types des paramètres et du
+// retour), et libffi place chaque argument selon la convention d'appel de la plateforme. Les
+// fonctions variadiques (printf...) n'ont pas de prototype fixe et ne sont pas prises en charge.
+
+/// Préfixe des natives qui désignent une fonction C liée par `extern`.
+pub const PREFIX: &str = "extern ";
+
+// Type C d'un paramètre ou du retour
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CType {
+    // int C (32 bits)
+    Int,
+    // double
+    Float,
+    // const char* : chaîne UTF-8 terminée par un zéro (null pour NULL)
+    String,
+    // void* ou entier de 64 bits (size_t...), passé comme un int Aegis
+    Pointer,
+    Void,
+}
+
+impl CType {
+    fn parse(name: &str) -> Option<CType> {
+        Some(match name {
+            "int" => CType::Int,
+            "float" => CType::Float,
+            "string" => CType::String,
+            "pointer" => CType::Pointer,
+            "null" | "void" => CType::Void,
+            _ => return None,
+        })
+    }
+
+    fn ffi_type(self) -> Type {
+        match self {
+            CType::Int => Type::c_int(),
+            CType::Float => Type::f64(),
+            CType::String | CType::Pointer => Type::pointer(),
+            CType::Void => Type::void(),
+        }
+    }
+}
+
+// Argument converti, gardé en vie jusqu'au retour de l'appel (libffi en lit l'adresse)
+enum CArg {
+    Int(i32),
+    Float(f64),
+    Pointer(*const c_void),
+}
+
+impl CArg {
+    fn as_arg(&self) -> Arg {
+        match self {
+            CArg::Int(n) => arg(n),
+            CArg::Float(f) => arg(f),
+            CArg::Pointer(p) => arg(p),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Binding {
+    address: usize,
+    params: Vec<CType>,
+    ret: CType,
+}
+
+// Bibliothèques ouvertes (jamais refermées : leurs symboles restent liés) et fonctions liées,
+// partagées par tous les threads pour que les workers puissent les appeler
+static LIBRARIES: OnceLock<Mutex<HashMap<String, Library>>> = OnceLock::new();
+static BINDINGS: OnceLock<Mutex<HashMap<String, Binding>>> = OnceLock::new();
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("ffi_bind".to_string(), ffi_bind);
+}
+
+/// Vrai si la native `name` est une fonction C liée par `extern`.
+pub fn is_extern(name: &str) -> bool {
+    name.starts_with(PREFIX)
+}
+
+fn ffi_bind(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("extern \"library\" func name(...)", &args);
+    let library = args.want_str(0)?;
+    let symbol = args.want_str(1)?;
+    let context = format!("extern func {}", symbol);
+
+    let types = |value: &Value| match value {
+        Value::String(name) => CType::parse(name)
+            .ok_or_else(|| format!("{}: unknown C type '{}' (expected int, float, string or pointer)", context, name)),
+        other => Err(format!("{}: expected a C type name, got {}", context, other.type_name())),
+    };
+    let params = args.want_list(2)?.borrow().iter().map(types).collect::<Result<Vec<_>, _>>()?;
+    let ret = match args.get(3) {
+        None | Some(Value::Null) => CType::Void,
+        Some(value) => types(value)?,
+    };
+    if params.contains(&CType::Void) {
+        return Err(format!("{}: a parameter cannot be void", context).into());
+    }
+
+    let mut libraries = LIBRARIES.get_or_init(Default::default).lock().map_err(|e| e.to_string())?;
+    if !libraries.contains_key(&library) {
+        // Le chargement exécute le code d'initialisation de la bibliothèque : inhérent au FFI
+        let lib = unsafe { Library::new(&library) }
+            .map_err(|e| format!("{}: cannot load '{}': {}", context, library, e))?;
+        libraries.insert(library.clone(), lib);
+    }
+    let address = unsafe {
+        libraries[&library].get::<*const ()>(format!("{}\0", symbol).as_bytes())
+            .map(|sym| *sym as usize)
+            .map_err(|e| format!("{}: symbol not found in '{}': {}", context, library, e))?
+    };
+
+    let name = format!("{}{}:{}", PREFIX, library, symbol);
+    BINDINGS.get_or_init(Default::default).lock().map_err(|e| e.to_string())?
+        .insert(name.clone(), Binding { address, params, ret });
+    Ok(Value::Native(name))
+}
+
+/// Appelle la fonction C liée sous le nom `name`, en vérifiant ses arguments.
+pub fn call(name: &str, args: Vec<Value>) -> Result<Value, NativeError> {
+    let binding = BINDINGS.get()
+        .and_then(|bindings| bindings.lock().ok()?.get(name).cloned())
+        .ok_or_else(|| format!("Extern function '{}' is not bound", name))?;
+    let symbol = name.rsplit(':').next().unwrap_or(name);
+    if args.len() != binding.params.len() {
+        return Err(format!("{}() expects {} argument(s), got {}", symbol, binding.params.len(), args.len()).into());
+    }
+
+    // Les chaînes C doivent vivre jusqu'au retour de l'appel
+    let mut strings = Vec::new();
+    let mut values = Vec::with_capacity(args.len());
+    for (i, (param, arg)) in binding.params.iter().zip(&args).enumerate() {
+        let mismatch = |expected: &str| format!("{}(): argument {} must be {}, got {}", symbol, i + 1, expected, arg.type_name());
+        values.push(match (param, arg) {
+            (CType::Float, Value::Integer(n)) => CArg::Float(*n as f64),
+            (CType::Float, Value::Float(f)) => CArg::Float(*f),
+            (CType::Float, _) => return Err(mismatch("a number").into()),
+            (CType::Int, Value::Integer(n)) => CArg::Int(i32::try_from(*n)
+                .map_err(|_| format!("{}(): argument {} ({}) does not fit in a C int", symbol, i + 1, n))?),
+            (CType::Int, _) => return Err(mismatch("an int").into()),
+            (CType::String, Value::String(s)) => {
+                let s = CString::new(s.as_str())
+                    .map_err(|_| format!("{}(): argument {} contains a NUL byte", symbol, i + 1))?;
+                strings.push(s);
+                CArg::Pointer(strings[strings.len() - 1].as_ptr().cast())
+            },
+            (CType::String, Value::Null) | (CType::Pointer, Value::Null) => CArg::Pointer(std::ptr::null()),
+            (CType::String, _) => return Err(mismatch("a string or null").into()),
+            (CType::Pointer, Value::Integer(n)) => CArg::Pointer(*n as usize as *const c_void),
+            (CType::Pointer, _) => return Err(mismatch("an int or null").into()),
+            (CType::Void, _) => unreachable!("checked by ffi_bind"),
+        });
+    }
+
+    // Le Cif garde des pointeurs bruts (il n'est pas Send) : il est reconstruit à chaque appel
+    // plutôt que partagé avec les workers
+    let cif = Cif::new(binding.params.iter().map(|param| param.ffi_type()), binding.ret.ffi_type());
+    let ffi_args: Vec<Arg> = values.iter().map(CArg::as_arg).collect();
+    let code = CodePtr::from_ptr(binding.address as *const c_void);
+
+    // SAFETY : le Cif suit le prototype déclaré par `extern` et les arguments ont été convertis
+    // vers ces types ; le symbole est une fonction C de la bibliothèque chargée.
+    Ok(unsafe {
+        match binding.ret {
+            CType::Float => Value::Float(cif.call::<f64>(code, &ffi_args)),
+            // libffi élargit un retour entier plus petit qu'un registre à un ffi_arg (64 bits) ;
+            // seuls les 32 bits de poids faible d'un int C sont définis
+            CType::Int => Value::Integer(cif.call::<u64>(code, &ffi_args) as i32 as i64),
+            CType::Pointer => Value::Integer(cif.call::<*const c_void>(code, &ffi_args) as usize as i64),
+            CType::String => {
+                let ptr = cif.call::<*const c_char>(code, &ffi_args);
+                if ptr.is_null() {
+                    Value::Null
+                } else {
+                    Value::String(CStr::from_ptr(ptr).to_string_lossy().into_owned().into())
+                }
+            },
+            CType::Void => {
+                cif.call::<u64>(code, &ffi_args);
+                Value::Null
+            },
+        }
+    })
+}
@@ -1,26 +1,32 @@
 use crate::ast::Value;
+use super::jobs::{self, Detached};
+use super::{Args, NativeError};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 pub fn register(map: &mut HashMap<String, super::NativeFn>) {
     map.insert("http_get".to_string(), http_get);
     map.insert("http_post".to_string(), http_post);
+    map.insert("http_get_async".to_string(), http_get_async);
+    map.insert("http_post_async".to_string(), http_post_async);
+    map.insert("http_get_bytes".to_string(), http_get_bytes);
+    map.insert("http_get_bytes_async".to_string(), http_get_bytes_async);
 }
 
-fn http_get(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("http_get attend une url".into());
-    }
-
-    let url = args[0].as_str()?;
-
-    // 1. Création d'un client avec User-Agent (Indispensable pour beaucoup d'API)
-    let client = reqwest::blocking::Client::builder()
+// 1. Création d'un client avec User-Agent (Indispensable pour beaucoup d'API)
+fn client() -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
         .user_agent("Aegis-Lang/2.0")
         .build()
-        .map_err(|e| format!("Erreur création client HTTP: {}", e))?;
+        .map_err(|e| format!("Erreur création client HTTP: {}", e))
+}
 
+// Les requêtes elles-mêmes ne touchent à aucune valeur Aegis : les versions `_async`
+// les exécutent telles quelles sur un thread d'arrière-plan
+fn send_get(url: &str) -> Result<reqwest::blocking::Response, String> {
     // 2. Envoi de la requête
-    let response = client.get(&url)
+    let response = client()?.get(url)
         .send()
         .map_err(|e| format!("Erreur connexion: {}", e))?;
 
@@ -28,29 +34,25 @@ fn http_get(args: Vec<Value>) -> Result<Value, String> {
     if !response.status().is_success() {
         return Err(format!("Erreur API: Code {}", response.status()));
     }
+    Ok(response)
+}
 
+fn fetch_get(url: &str) -> Result<String, String> {
     // 4. Lecture du corps
-    let text = response.text()
-        .map_err(|e| format!("Erreur lecture body: {}", e))?;
-                                    
-    Ok(Value::String(text))
+    send_get(url)?.text()
+        .map_err(|e| format!("Erreur lecture body: {}", e))
 }
 
-fn http_post(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 3 { 
-        return Err("http_post attend 3 arguments (url, body, content_type)".into()); 
-    }
-
-    let url = args[0].as_str()?;
-    let body = args[1].as_str()?;
-    let content_type = args[2].as_str()?;
-                                
-    let client = reqwest::blocking::Client::builder()
-        .user_agent("Aegis-Lang/2.0")
-        .build()
-        .map_err(|e| format!("Erreur création client HTTP: {}", e))?;
+// Corps brut (images, archives...), sans conversion en texte
+fn fetch_get_bytes(url: &str) -> Result<Vec<u8>, String> {
+    send_get(url)?.bytes()
+        .map(|body| body.to_vec())
+        .map_err(|e| format!("Erreur lecture body: {}", e))
+}
 
-    let res = client.post(&url)
+// Le corps est une chaîne ou des Bytes envoyés tels quels
+fn fetch_post(url: &str, body: Vec<u8>, content_type: &str) -> Result<String, String> {
+    let res = client()?.post(url)
         .header("Content-Type", content_type)
         .body(body)
         .send()
@@ -60,5 +62,41 @@ fn http_post(args: Vec<Value>) -> Result<Value, String> {
         return Err(format!("Erreur API: {}", res.status()));
     }
                                 
-    Ok(Value::String(res.text().unwrap_or_default()))
-}
\ No newline at end of file
+    Ok(res.text().unwrap_or_default())
+}
+
+fn http_get(args: Vec<Value>) -> Result<Value, NativeError> {
+    let url = Args::new("Http.get(url)", &args).want_str(0)?;
+    Ok(Value::String(fetch_get(&url)?.into()))
+}
+
+fn http_get_bytes(args: Vec<Value>) -> Result<Value, NativeError> {
+    let url = Args::new("Http.get_bytes(url)", &args).want_str(0)?;
+    Ok(Value::Bytes(Rc::new(RefCell::new(fetch_get_bytes(&url)?))))
+}
+
+fn http_post(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Http.post(url, body, content_type)", &args);
+    let url = args.want_str(0)?;
+    let body = args.want_data(1)?;
+    let content_type = args.want_str(2)?;
+    Ok(Value::String(fetch_post(&url, body, &content_type)?.into()))
+}
+
+fn http_get_async(args: Vec<Value>) -> Result<Value, NativeError> {
+    let url = Args::new("Http.get_async(url)", &args).want_str(0)?;
+    Ok(jobs::spawn("http_get", move || fetch_get(&url).map(Detached::Str)))
+}
+
+fn http_get_bytes_async(args: Vec<Value>) -> Result<Value, NativeError> {
+    let url = Args::new("Http.get_bytes_async(url)", &args).want_str(0)?;
+    Ok(jobs::spawn("http_get_bytes", move || fetch_get_bytes(&url).map(Detached::Bytes)))
+}
+
+fn http_post_async(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Http.post_async(url, body, content_type)", &args);
+    let url = args.want_str(0)?;
+    let body = args.want_data(1)?;
+    let content_type = args.want_str(2)?;
+    Ok(jobs::spawn("http_post", move || fetch_post(&url, body, &content_type).map(Detached::Str)))
+}
@@ -0,0 +1,244 @@
+use crate::ast::Value;
+use super::{Args, NativeError};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+// Conventions d'affichage d'une locale (sous-ensemble volontairement réduit, sans ICU)
+struct Locale {
+    tag: &'static str,
+    decimal: &'static str,
+    group: &'static str,
+    // true : "1 234,56 €" / false : "€1,234.56"
+    currency_after: bool,
+    months: [&'static str; 12],
+    short_months: [&'static str; 12],
+    weekdays: [&'static str; 7], // Lundi en premier
+}
+
+const EN_MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"];
+const EN_SHORT: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const EN_DAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+const LOCALES: &[Locale] = &[
+    Locale { tag: "en-US", decimal: ".", group: ",", currency_after: false, months: EN_MONTHS, short_months: EN_SHORT, weekdays: EN_DAYS },
+    Locale { tag: "en-GB", decimal: ".", group: ",", currency_after: false, months: EN_MONTHS, short_months: EN_SHORT, weekdays: EN_DAYS },
+    Locale {
+        tag: "fr-FR", decimal: ",", group: "\u{202F}", currency_after: true,
+        months: ["janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre", "octobre", "novembre", "décembre"],
+        short_months: ["janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.", "déc."],
+        weekdays: ["lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche"],
+    },
+    Locale {
+        tag: "de-DE", decimal: ",", group: ".", currency_after: true,
+        months: ["Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober", "November", "Dezember"],
+        short_months: ["Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.", "Dez."],
+        weekdays: ["Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag", "Sonntag"],
+    },
+    Locale {
+        tag: "es-ES", decimal: ",", group: ".", currency_after: true,
+        months: ["enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre", "octubre", "noviembre", "diciembre"],
+        short_months: ["ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic"],
+        weekdays: ["lunes", "martes", "miércoles", "jueves", "viernes", "sábado", "domingo"],
+    },
+    Locale {
+        tag: "it-IT", decimal: ",", group: ".", currency_after: true,
+        months: ["gennaio", "febbraio", "marzo", "aprile", "maggio", "giugno", "luglio", "agosto", "settembre", "ottobre", "novembre", "dicembre"],
+        short_months: ["gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic"],
+        weekdays: ["lunedì", "martedì", "mercoledì", "giovedì", "venerdì", "sabato", "domenica"],
+    },
+];
+
+lazy_static! {
+    // Locale utilisée quand aucune n'est précisée (initialisée depuis $LANG)
+    static ref DEFAULT_LOCALE: Mutex<&'static str> = Mutex::new(
+        std::env::var("LANG").ok()
+            .and_then(|l| find_locale(&l).ok())
+            .map(|l| l.tag)
+            .unwrap_or("en-US")
+    );
+}
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("intl_set_locale".to_string(), intl_set_locale);
+    map.insert("intl_get_locale".to_string(), intl_get_locale);
+    map.insert("intl_format_number".to_string(), intl_format_number);
+    map.insert("intl_format_currency".to_string(), intl_format_currency);
+    map.insert("intl_format_date".to_string(), intl_format_date);
+}
+
+// Accepte "fr-FR", "fr_FR.UTF-8", ou juste "fr"
+fn find_locale(name: &str) -> Result<&'static Locale, String> {
+    let normalized = name.split('.').next().unwrap_or("").replace('_', "-").to_lowercase();
+
+    LOCALES.iter()
+        .find(|l| l.tag.to_lowercase() == normalized)
+        .or_else(|| {
+            let lang = normalized.split('-').next().unwrap_or("");
+            LOCALES.iter().find(|l| l.tag.to_lowercase().starts_with(&format!("{}-", lang)))
+        })
+        .ok_or_else(|| format!("Unsupported locale '{}'", name))
+}
+
+// Locale passée en argument `idx`, ou la locale par défaut si elle est absente ou null
+fn locale_arg(args: &Args, idx: usize) -> Result<&'static Locale, String> {
+    let default = DEFAULT_LOCALE.lock().unwrap().to_string();
+    find_locale(&args.opt_str(idx, &default)?)
+}
+
+// Insère les séparateurs de milliers dans la partie entière
+fn group_digits(digits: &str, sep: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push_str(sep);
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn format_with(value: f64, decimals: usize, trim: bool, locale: &Locale) -> String {
+    let raw = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = match raw.split_once('.') {
+        Some((i, f)) => (i.to_string(), f.to_string()),
+        None => (raw.clone(), String::new()),
+    };
+    let frac_part = if trim { frac_part.trim_end_matches('0').to_string() } else { frac_part };
+
+    let mut out = String::new();
+    if value < 0.0 && raw.chars().any(|c| c != '0' && c != '.') {
+        out.push('-');
+    }
+    out.push_str(&group_digits(&int_part, locale.group));
+    if !frac_part.is_empty() {
+        out.push_str(locale.decimal);
+        out.push_str(&frac_part);
+    }
+    out
+}
+
+fn intl_set_locale(args: Vec<Value>) -> Result<Value, NativeError> {
+    let locale = find_locale(&Args::new("Intl.set_locale(locale)", &args).want_str(0)?)?;
+    *DEFAULT_LOCALE.lock().unwrap() = locale.tag;
+    Ok(Value::String(locale.tag.to_string().into()))
+}
+
+fn intl_get_locale(_: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Value::String(DEFAULT_LOCALE.lock().unwrap().to_string().into()))
+}
+
+fn intl_format_number(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Intl.format_number(value, locale?, decimals?)", &args);
+    let value = args.want_number(0)?;
+    let locale = locale_arg(&args, 1)?;
+    let formatted = match (args.want(0)?, args.opt_int(2, -1)?) {
+        // Nombre de décimales imposé
+        (_, decimals) if decimals >= 0 => format_with(value, decimals as usize, false, locale),
+        (Value::Integer(_), _) => format_with(value, 0, false, locale),
+        // Par défaut : jusqu'à 3 décimales, zéros inutiles retirés
+        _ => format_with(value, 3, true, locale),
+    };
+
+    Ok(Value::String(formatted.into()))
+}
+
+// (symbole, nombre de décimales)
+fn currency_info(code: &str) -> (String, usize) {
+    match code {
+        "EUR" => ("€".into(), 2),
+        "USD" => ("$".into(), 2),
+        "GBP" => ("£".into(), 2),
+        "JPY" => ("¥".into(), 0),
+        "CNY" => ("CN¥".into(), 2),
+        "INR" => ("₹".into(), 2),
+        "KRW" => ("₩".into(), 0),
+        "BRL" => ("R$".into(), 2),
+        "CAD" => ("CA$".into(), 2),
+        "AUD" => ("A$".into(), 2),
+        "CHF" => ("CHF".into(), 2),
+        other => (other.to_string(), 2),
+    }
+}
+
+fn intl_format_currency(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Intl.format_currency(value, currency, locale?)", &args);
+    let value = args.want_number(0)?;
+    let code = args.want_str(1)?.to_uppercase();
+    let locale = locale_arg(&args, 2)?;
+    let (symbol, decimals) = currency_info(&code);
+
+    let amount = format_with(value.abs(), decimals, false, locale);
+    let sign = if value < 0.0 { "-" } else { "" };
+
+    let formatted = if locale.currency_after {
+        format!("{}{}\u{A0}{}", sign, amount, symbol)
+    } else if symbol.chars().all(|c| c.is_ascii_alphabetic()) {
+        // Code sans symbole (ex: "CHF 12.00")
+        format!("{}{}\u{A0}{}", sign, symbol, amount)
+    } else {
+        format!("{}{}{}", sign, symbol, amount)
+    };
+
+    Ok(Value::String(formatted.into()))
+}
+
+// Accepte un timestamp en millisecondes (Time.now()) ou une date ISO 8601 (Date.now())
+fn parse_datetime(args: &Args, idx: usize) -> Result<NaiveDateTime, String> {
+    match args.want(idx)? {
+        Value::Integer(ms) => Local.timestamp_millis_opt(*ms).single()
+            .map(|dt| dt.naive_local())
+            .ok_or_else(|| format!("Invalid timestamp: {}", ms)),
+        Value::String(s) => {
+            if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+                return Ok(dt.naive_local());
+            }
+            if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+                return Ok(dt);
+            }
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .map(|d| d.and_hms_opt(0, 0, 0).unwrap())
+                .map_err(|_| format!("Invalid date: '{}'", s))
+        },
+        Value::DateTime(ms, zone) => super::date::local_time(*ms, *zone).map(|dt| dt.naive_local()),
+        Value::Null => Ok(Local::now().naive_local()),
+        _ => Err(args.expected(idx, "a timestamp (int), an ISO date (string) or a datetime")),
+    }
+}
+
+fn intl_format_date(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Intl.format_date(date, locale?, style?)", &args);
+    let dt = parse_datetime(&args, 0)?;
+    let locale = locale_arg(&args, 1)?;
+    let style = args.opt_str(2, "medium")?;
+
+    let (d, m, y) = (dt.day(), dt.month() as usize, dt.year());
+    let month = locale.months[m - 1];
+    let short_month = locale.short_months[m - 1];
+    let weekday = locale.weekdays[dt.weekday().num_days_from_monday() as usize];
+
+    let formatted = match (style.as_str(), locale.tag) {
+        ("short", "en-US") => format!("{}/{}/{}", m, d, y),
+        ("short", "de-DE") => format!("{:02}.{:02}.{}", d, m, y),
+        ("short", _) => format!("{:02}/{:02}/{}", d, m, y),
+
+        ("medium", "en-US") => format!("{} {}, {}", short_month, d, y),
+        ("medium", "de-DE") => format!("{:02}.{:02}.{}", d, m, y),
+        ("medium", _) => format!("{} {} {}", d, short_month, y),
+
+        ("long", "en-US") => format!("{} {}, {}", month, d, y),
+        ("long", "de-DE") => format!("{}. {} {}", d, month, y),
+        ("long", "es-ES") => format!("{} de {} de {}", d, month, y),
+        ("long", _) => format!("{} {} {}", d, month, y),
+
+        ("full", "en-US") => format!("{}, {} {}, {}", weekday, month, d, y),
+        ("full", "de-DE") => format!("{}, {}. {} {}", weekday, d, month, y),
+        ("full", "es-ES") => format!("{}, {} de {} de {}", weekday, d, month, y),
+        ("full", _) => format!("{} {} {} {}", weekday, d, month, y),
+
+        (other, _) => return Err(format!("Unknown date style '{}' (expected short, medium, long or full)", other).into()),
+    };
+
+    Ok(Value::String(formatted.into()))
+}
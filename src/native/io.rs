@@ -1,96 +1,227 @@
 use crate::ast::Value;
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::fs::{self, OpenOptions};
-use std::io::Write;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::rc::Rc;
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use super::{Args, NativeError};
+
+// Fichiers ouverts par File.open(path, mode), fermés par close() (ou à la sortie d'un `with`)
+struct FileState {
+    handles: HashMap<usize, (String, BufReader<File>)>,
+    next_id: usize,
+}
+
+lazy_static! {
+    static ref FILE_STATE: Mutex<FileState> = Mutex::new(FileState {
+        handles: HashMap::new(),
+        next_id: 1,
+    });
+}
 
 pub fn register(map: &mut HashMap<String, super::NativeFn>) {
     map.insert("io_read".to_string(), io_read);
     map.insert("io_read_bytes".to_string(), io_read_bytes);
     map.insert("io_write".to_string(), io_write);
+    map.insert("io_write_bytes".to_string(), io_write_bytes);
     map.insert("io_append".to_string(), io_append);
     map.insert("io_exists".to_string(), io_exists);
     map.insert("io_delete".to_string(), io_delete);
+    map.insert("io_open".to_string(), io_open);
+    map.insert("io_handle_read".to_string(), io_handle_read);
+    map.insert("io_handle_read_line".to_string(), io_handle_read_line);
+    map.insert("io_handle_read_bytes".to_string(), io_handle_read_bytes);
+    map.insert("io_handle_write".to_string(), io_handle_write);
+    map.insert("io_handle_seek".to_string(), io_handle_seek);
+    map.insert("io_handle_tell".to_string(), io_handle_tell);
+    map.insert("io_close".to_string(), io_close);
 }
 
-fn io_read(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("io_read attend 1 argument".into());
-    }
-
-    let path = args[0].as_str()?;
+fn io_read(args: Vec<Value>) -> Result<Value, NativeError> {
+    let path = Args::new("File.read(path)", &args).want_str(0)?;
 
     match fs::read_to_string(&path) {
-        Ok(content) => Ok(Value::String(content)),
+        Ok(content) => Ok(Value::String(content.into())),
         Err(_) => Ok(Value::Null)
     }
 }
 
-fn io_read_bytes(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("Usage: File.read_bytes(path)".into());
-    }
+fn io_read_bytes(args: Vec<Value>) -> Result<Value, NativeError> {
+    let path_str = Args::new("File.read_bytes(path)", &args).want_str(0)?;
 
-    let path_str = args[0].as_str().map_err(|_| "Path must be a string")?;
-    
     // std::fs::read lit tout le fichier dans un Vec<u8>
     match std::fs::read(&path_str) {
         Ok(bytes) => Ok(Value::Bytes(Rc::new(RefCell::new(bytes)))),
-        Err(e) => Err(format!("Failed to read file '{}': {}", path_str, e)),
+        Err(e) => Err(super::io_error(&e, &path_str)),
     }
 }
 
-fn io_write(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 2 {
-        return Err("io_write attend 2 arguments".into());
-    }
-
-    let path = args[0].as_str()?;
-    let content = args[1].as_str()?;
-    fs::write(path, content).map_err(|e| e.to_string())?;
+fn io_write(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("File.write(path, content)", &args);
+    let path = args.want_str(0)?;
+    let content = args.want_str(1)?;
+    fs::write(&path, content).map_err(|e| super::io_error(&e, &path))?;
     Ok(Value::Boolean(true))
 }
 
-fn io_append(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 2 {
-        return Err("io_append attend 2 arguments.".into());
-    }
+fn io_write_bytes(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("File.write_bytes(path, bytes)", &args);
+    let path = args.want_str(0)?;
+    let bytes = args.want_bytes(1)?;
+    fs::write(&path, &*bytes.borrow()).map_err(|e| super::io_error(&e, &path))?;
+    Ok(Value::Boolean(true))
+}
 
-    let path = args[0].as_str()?;
-    let content = args[1].as_str()?;
+fn io_append(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("File.append(path, content)", &args);
+    let path = args.want_str(0)?;
+    let content = args.want_str(1)?;
 
     let mut file = OpenOptions::new()
         .write(true)
         .append(true)
         .create(true)
         .open(&path)
-        .map_err(|e| format!("Erreur ouverture fichier: {}", e))?;
+        .map_err(|e| super::io_error(&e, &path))?;
 
-    write!(file, "{}", content).map_err(|e| format!("Erreur append: {}", e))?;
+    write!(file, "{}", content).map_err(|e| super::io_error(&e, &path))?;
     Ok(Value::Boolean(true))
 }
 
-fn io_exists(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("io_exists attend 1 argument (path).".into());
-    }
-
-    let path = args[0].as_str()?;
-
+fn io_exists(args: Vec<Value>) -> Result<Value, NativeError> {
+    let path = Args::new("File.exists(path)", &args).want_str(0)?;
     Ok(Value::Boolean(Path::new(&path).exists()))
 }
 
-fn io_delete(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("io_delete attend 1 argument (path).".into());
-    }
-
-    let path = args[0].as_str()?;
+fn io_delete(args: Vec<Value>) -> Result<Value, NativeError> {
+    let path = Args::new("File.delete(path)", &args).want_str(0)?;
     if Path::new(&path).exists() {
-        fs::remove_file(&path).map_err(|e| e.to_string())?;
+        fs::remove_file(&path).map_err(|e| super::io_error(&e, &path))?;
         return Ok(Value::Boolean(true));
     }
     return Ok(Value::Boolean(false));
-}
\ No newline at end of file
+}
+// Ouvre un fichier : "r" (lecture), "w" (écrase), "a" (ajout), "r+" (lecture et écriture, sans
+// effacer). Retourne l'id du handle.
+fn io_open(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("File.open(path, mode?)", &args);
+    args.at_most(2)?;
+    let path = args.want_str(0)?;
+    let mode = args.opt_str(1, "r")?;
+
+    let mut options = OpenOptions::new();
+    match mode.as_str() {
+        "r" => options.read(true),
+        "w" => options.write(true).create(true).truncate(true),
+        "a" => options.append(true).create(true),
+        "r+" => options.read(true).write(true),
+        _ => return Err(format!("File.open: unknown mode '{}' (expected \"r\", \"w\", \"a\" or \"r+\")", mode).into()),
+    };
+    let file = options.open(&path).map_err(|e| super::io_error(&e, &path))?;
+
+    let mut state = FILE_STATE.lock().unwrap();
+    let id = state.next_id;
+    state.handles.insert(id, (path, BufReader::new(file)));
+    state.next_id += 1;
+
+    Ok(Value::Integer(id as i64))
+}
+
+// Le premier argument est l'id du handle (champ `id` de _FileHandle)
+fn with_handle<T>(args: &Args, action: impl FnOnce(&str, &mut BufReader<File>) -> Result<T, NativeError>) -> Result<T, NativeError> {
+    let id = args.want_int(0)? as usize;
+    let mut state = FILE_STATE.lock().unwrap();
+    let (path, reader) = state.handles.get_mut(&id).ok_or("File handle is closed")?;
+    action(path, reader)
+}
+
+// Lit tout ce qui reste dans le fichier
+fn io_handle_read(args: Vec<Value>) -> Result<Value, NativeError> {
+    with_handle(&Args::new("file.read(handle)", &args), |path, reader| {
+        let mut content = String::new();
+        reader.read_to_string(&mut content).map_err(|e| super::io_error(&e, path))?;
+        Ok(Value::String(content.into()))
+    })
+}
+
+// Ligne suivante sans son retour à la ligne, null à la fin du fichier
+fn io_handle_read_line(args: Vec<Value>) -> Result<Value, NativeError> {
+    with_handle(&Args::new("file.read_line(handle)", &args), |path, reader| {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).map_err(|e| super::io_error(&e, path))?;
+        if read == 0 {
+            return Ok(Value::Null);
+        }
+        let trimmed = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(trimmed);
+        Ok(Value::String(line.into()))
+    })
+}
+
+// Jusqu'à `size` octets (moins à la fin du fichier), null quand il n'y a plus rien à lire
+fn io_handle_read_bytes(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("file.read_bytes(handle, size)", &args);
+    let size = args.want_int(1)?;
+    let size = usize::try_from(size).map_err(|_| args.error(format!("size must not be negative, got {}", size)))?;
+    with_handle(&args, |path, reader| {
+        let mut buffer = Vec::with_capacity(size.min(1 << 20));
+        reader.by_ref().take(size as u64).read_to_end(&mut buffer).map_err(|e| super::io_error(&e, path))?;
+        if buffer.is_empty() && size > 0 {
+            return Ok(Value::Null);
+        }
+        Ok(Value::Bytes(Rc::new(RefCell::new(buffer))))
+    })
+}
+
+// Chaîne écrite en UTF-8, Bytes tels quels, autre valeur convertie en texte
+fn io_handle_write(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("file.write(handle, content)", &args);
+    let content = match args.want(1)? {
+        Value::Bytes(bytes) => bytes.borrow().clone(),
+        Value::String(text) => text.as_bytes().to_vec(),
+        other => other.to_string().into_bytes(),
+    };
+    with_handle(&args, |path, reader| {
+        // En "r+", des octets déjà lus d'avance par le tampon : on revient à la position du script
+        if !reader.buffer().is_empty() {
+            let position = reader.stream_position().map_err(|e| super::io_error(&e, path))?;
+            reader.seek(SeekFrom::Start(position)).map_err(|e| super::io_error(&e, path))?;
+        }
+        reader.get_mut().write_all(&content).map_err(|e| super::io_error(&e, path))?;
+        Ok(Value::Boolean(true))
+    })
+}
+
+// Déplace la position (depuis "start", "current" ou "end") et retourne la nouvelle
+fn io_handle_seek(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("file.seek(handle, offset, from?)", &args);
+    let offset = args.want_int(1)?;
+    let from = match args.opt_str(2, "start")?.as_str() {
+        "start" => SeekFrom::Start(u64::try_from(offset)
+            .map_err(|_| args.error(format!("offset from the start must not be negative, got {}", offset)))?),
+        "current" => SeekFrom::Current(offset),
+        "end" => SeekFrom::End(offset),
+        other => return Err(args.error(format!("unknown origin '{}' (expected \"start\", \"current\" or \"end\")", other)).into()),
+    };
+    with_handle(&args, |path, reader| {
+        let position = reader.seek(from).map_err(|e| super::io_error(&e, path))?;
+        Ok(Value::Integer(position as i64))
+    })
+}
+
+fn io_handle_tell(args: Vec<Value>) -> Result<Value, NativeError> {
+    with_handle(&Args::new("file.tell(handle)", &args), |path, reader| {
+        let position = reader.stream_position().map_err(|e| super::io_error(&e, path))?;
+        Ok(Value::Integer(position as i64))
+    })
+}
+
+// Ferme le fichier (false s'il l'était déjà)
+fn io_close(args: Vec<Value>) -> Result<Value, NativeError> {
+    let id = Args::new("file.close(handle)", &args).want_int(0)? as usize;
+    let closed = FILE_STATE.lock().unwrap().handles.remove(&id).is_some();
+    Ok(Value::Boolean(closed))
+}
@@ -0,0 +1,117 @@
+use crate::ast::Value;
+use crate::ast::value::{TaskData, TaskState};
+use super::{Args, NativeError};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+// Opérations natives asynchrones (Http.get_async, Socket.read_async, Async.sleep...).
+// Chacune tourne sur son propre thread et renvoie un résultat `Send` (les valeurs Aegis ne le
+// sont pas) dans un canal commun. L'ordonnanceur de la VM relève ce canal : la tâche
+// correspondante est alors terminée, et le code qui l'attend peut reprendre.
+
+/// Résultat d'une opération d'arrière-plan, transmissible entre threads.
+pub enum Detached {
+    Null,
+    Int(i64),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+impl Detached {
+    fn into_value(self) -> Value {
+        match self {
+            Detached::Null => Value::Null,
+            Detached::Int(i) => Value::Integer(i),
+            Detached::Str(s) => Value::String(s.into()),
+            Detached::Bytes(b) => Value::Bytes(Rc::new(RefCell::new(b))),
+        }
+    }
+}
+
+type JobResult = (usize, Result<Detached, String>);
+
+struct Jobs {
+    pending: HashMap<usize, Rc<RefCell<TaskData>>>,
+    sender: Sender<JobResult>,
+    receiver: Receiver<JobResult>,
+}
+
+thread_local! {
+    static JOBS: RefCell<Jobs> = RefCell::new({
+        let (sender, receiver) = mpsc::channel();
+        Jobs { pending: HashMap::new(), sender, receiver }
+    });
+}
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("async_sleep".to_string(), async_sleep);
+    map.insert("async_is_done".to_string(), async_is_done);
+}
+
+/// Lance `job` sur un thread d'arrière-plan et renvoie la tâche qui recevra son résultat.
+pub fn spawn<F>(name: &str, job: F) -> Value
+where
+    F: FnOnce() -> Result<Detached, String> + Send + 'static,
+{
+    let task = Rc::new(RefCell::new(TaskData::new(name.to_string())));
+    let id = task.borrow().id;
+    let sender = JOBS.with(|jobs| {
+        let mut jobs = jobs.borrow_mut();
+        jobs.pending.insert(id, task.clone());
+        jobs.sender.clone()
+    });
+
+    std::thread::spawn(move || {
+        // Un panic dans l'opération devient une erreur : la tâche se termine quand même
+        let result = panic::catch_unwind(AssertUnwindSafe(job))
+            .unwrap_or_else(|_| Err("background operation panicked".to_string()));
+        let _ = sender.send((id, result));
+    });
+    Value::Task(task)
+}
+
+/// Nombre d'opérations d'arrière-plan pas encore terminées.
+pub fn pending_count() -> usize {
+    JOBS.with(|jobs| jobs.borrow().pending.len())
+}
+
+/// Termine les tâches dont le résultat est arrivé. Avec `block`, attend le premier résultat
+/// si aucun n'est encore là (et qu'une opération est en cours).
+pub fn complete(block: bool) {
+    JOBS.with(|jobs| {
+        let mut jobs = jobs.borrow_mut();
+        let mut received: Vec<JobResult> = jobs.receiver.try_iter().collect();
+        if received.is_empty() && block && !jobs.pending.is_empty()
+            && let Ok(first) = jobs.receiver.recv() {
+            received.push(first);
+        }
+
+        for (id, result) in received {
+            let Some(task) = jobs.pending.remove(&id) else { continue };
+            task.borrow_mut().state = match result {
+                Ok(value) => TaskState::Done(value.into_value()),
+                Err(message) => TaskState::Failed(Value::String(message.into())),
+            };
+        }
+    });
+}
+
+fn async_sleep(args: Vec<Value>) -> Result<Value, NativeError> {
+    let ms = Args::new("Async.sleep(ms)", &args).want_number(0)?.max(0.0) as u64;
+    Ok(spawn("sleep", move || {
+        std::thread::sleep(Duration::from_millis(ms));
+        Ok(Detached::Null)
+    }))
+}
+
+fn async_is_done(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Async.is_done(task)", &args);
+    match args.want(0)? {
+        Value::Task(task) => Ok(Value::Boolean(!task.borrow().is_pending())),
+        _ => Err(args.expected(0, "a task").into()),
+    }
+}
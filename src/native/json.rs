@@ -1,81 +1,109 @@
-use crate::ast::Value;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use crate::ast::{DictMap, Value};
+use crate::vm::gc;
+use super::{Args, NativeError};
+use std::collections::HashMap;
 
 pub fn register(map: &mut HashMap<String, super::NativeFn>) {
     map.insert("json_parse".to_string(), json_parse);
     map.insert("json_stringify".to_string(), json_stringify);
 }
 
+// Couche de conversion partagée avec les modules toml et yaml : leurs documents passent par un
+// serde_json::Value, dans un sens comme dans l'autre.
+
 // Conversion : serde_json::Value (Externe) -> crate::ast::Value (Interne Aegis)
-fn serde_to_aegis(v: serde_json::Value) -> Value {
+pub(crate) fn from_json(v: serde_json::Value) -> Value {
     match v {
         serde_json::Value::Null => Value::Null,
         serde_json::Value::Bool(b) => Value::Boolean(b),
-        serde_json::Value::Number(n) => {
-            if n.is_i64() { Value::Integer(n.as_i64().unwrap()) }
-            else { Value::Float(n.as_f64().unwrap()) }
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            // Au-delà de i64 (u64 ou décimal) : float
+            None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
         },
-        serde_json::Value::String(s) => Value::String(s),
+        serde_json::Value::String(s) => Value::String(s.into()),
         serde_json::Value::Array(arr) => {
-            let list = arr.into_iter().map(serde_to_aegis).collect();
-            Value::List(Rc::new(RefCell::new(list)))
+            let list = arr.into_iter().map(from_json).collect();
+            gc::list(list)
         },
         serde_json::Value::Object(map) => {
-            let mut dict = HashMap::new();
+            let mut dict = DictMap::new();
             for (k, v) in map {
-                dict.insert(k, serde_to_aegis(v));
+                dict.insert(k.into(), from_json(v));
             }
-            Value::Dict(Rc::new(RefCell::new(dict)))
+            gc::dict(dict)
         }
     }
 }
 
-// Conversion inverse (pour envoyer du JSON ou stringify) : Aegis -> Serde
-// (Version simplifiée qui retourne string direct pour l'instant)
-#[allow(dead_code)]
-fn aegis_to_json_string(v: &Value) -> String {
-    match v {
-        Value::Null => "null".to_string(),
-        Value::Boolean(b) => b.to_string(),
-        Value::Integer(i) => i.to_string(),
-        Value::Float(f) => f.to_string(),
-        Value::String(s) => format!("\"{}\"", s), // Ajout des quotes pour JSON valide
-        Value::List(l) => {
-            let items: Vec<String> = l.borrow().iter().map(|i| aegis_to_json_string(i)).collect();
-            format!("[{}]", items.join(", "))
+// Conversion inverse : Aegis -> serde_json::Value. Seules les données (null, bool, nombres,
+// chaînes, listes, dicts) passent ; `format` nomme le format cible dans les erreurs.
+pub(crate) fn to_json(value: &Value, format: &str) -> Result<serde_json::Value, String> {
+    convert(value, format, &mut Vec::new())
+}
+
+// `path` : conteneurs en cours de conversion, pour refuser un cycle au lieu de boucler
+fn convert(value: &Value, format: &str, path: &mut Vec<usize>) -> Result<serde_json::Value, String> {
+    Ok(match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Boolean(b) => serde_json::Value::Bool(*b),
+        Value::Integer(i) => serde_json::Value::from(*i),
+        Value::Float(f) => serde_json::Number::from_f64(*f).map(serde_json::Value::Number)
+            .ok_or_else(|| format!("{} cannot be converted to {}", f, format))?,
+        Value::String(s) => serde_json::Value::String(s.to_string()),
+        Value::List(list) => {
+            let address = list.as_ptr() as usize;
+            enter(path, address, format)?;
+            let items = list.borrow().iter()
+                .map(|item| convert(item, format, path))
+                .collect::<Result<Vec<_>, _>>()?;
+            path.pop();
+            serde_json::Value::Array(items)
         },
-        Value::Dict(d) => {
-            let items: Vec<String> = d.borrow().iter().map(|(k, v)| {
-                format!("\"{}\": {}", k, aegis_to_json_string(v))
-            }).collect();
-            format!("{{{}}}", items.join(", "))
+        Value::Dict(dict) => {
+            let address = dict.as_ptr() as usize;
+            enter(path, address, format)?;
+            let mut object = serde_json::Map::new();
+            for (key, item) in dict.borrow().iter() {
+                object.insert(key.to_string(), convert(item, format, path)?);
+            }
+            path.pop();
+            serde_json::Value::Object(object)
         },
-        _ => "\"unsupported\"".to_string()
-    }
+        other => return Err(format!("a {} cannot be converted to {}", other.type_name(), format)),
+    })
 }
 
-
-fn json_parse(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("json_parse attend une chaine".into());
+fn enter(path: &mut Vec<usize>, address: usize, format: &str) -> Result<(), String> {
+    if path.contains(&address) {
+        return Err(format!("a cyclic structure cannot be converted to {}", format));
     }
+    path.push(address);
+    Ok(())
+}
 
-    let json_str = args[0].as_str()?;
+fn json_parse(args: Vec<Value>) -> Result<Value, NativeError> {
+    let json_str = Args::new("Json.parse(str)", &args).want_str(0)?;
+    parse(&json_str).map_err(NativeError::from)
+}
 
-    let serde_val: serde_json::Value = serde_json::from_str(&json_str)
+// Aussi utilisé par `import "x.json" as x`
+pub(crate) fn parse(text: &str) -> Result<Value, String> {
+    let serde_val: serde_json::Value = serde_json::from_str(text)
         .map_err(|e| format!("Erreur Parsing JSON: {}", e))?;
 
-    Ok(serde_to_aegis(serde_val))
+    Ok(from_json(serde_val))
 }
 
-fn json_stringify(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("json_parse attend une chaine".into());
-    }
-    let json_str = args[0].as_str()?;
-
-    let serde_val: serde_json::Value = serde_json::from_str(&json_str)
-        .map_err(|e| format!("Erreur Parsing JSON: {}", e))?;
-
-    Ok(serde_to_aegis(serde_val))
+// Texte JSON compact, ou indenté de deux espaces avec `pretty`
+fn json_stringify(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Json.stringify(value, pretty = false)", &args);
+    args.at_most(2)?;
+    let json = to_json(args.want(0)?, "JSON").map_err(|e| args.error(e))?;
+    let text = if args.opt_bool(1, false)? {
+        serde_json::to_string_pretty(&json)
+    } else {
+        serde_json::to_string(&json)
+    };
+    text.map(|s| Value::String(s.into())).map_err(|e| args.error(e).into())
 }
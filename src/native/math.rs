@@ -1,4 +1,5 @@
 use crate::ast::Value;
+use super::{Args, NativeError};
 use std::collections::HashMap;
 
 // Enregistrement des fonctions dans la VM
@@ -17,90 +18,71 @@ pub fn register(map: &mut HashMap<String, super::NativeFn>) {
     map.insert("math_atan".to_string(), atan);
 }
 
-// Helper pour convertir Value (Int ou Float) en f64
-fn get_number(val: &Value) -> Result<f64, String> {
-    match val {
-        Value::Integer(i) => Ok(*i as f64),
-        Value::Float(f) => Ok(*f),
-        _ => Err(format!("Expected number, got {}", val)),
-    }
+// Fonction d'un seul nombre : vérifie l'argument (int ou float) et applique `f`
+fn unary(signature: &'static str, args: &[Value], f: fn(f64) -> Value) -> Result<Value, NativeError> {
+    let args = Args::new(signature, args);
+    args.at_most(1)?;
+    Ok(f(args.want_number(0)?))
 }
 
 // --- Implémentations ---
 
-fn abs(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_abs attend 1 argument".into()); }
-    match &args[0] {
-        Value::Integer(i) => Ok(Value::Integer(i.abs())),
-        Value::Float(f) => Ok(Value::Float(f.abs())),
-        _ => Err("math_abs attend un nombre".into()),
+fn abs(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Math.abs(n)", &args);
+    args.at_most(1)?;
+    match args.want(0)? {
+        Value::Integer(i) => i.checked_abs().map(Value::Integer)
+            .ok_or_else(|| format!("Integer overflow: Math.abs({}) does not fit in an int", i).into()),
+        _ => Ok(Value::Float(args.want_number(0)?.abs())),
     }
 }
 
-fn ceil(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_ceil attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    Ok(Value::Integer(n.ceil() as i64))
+fn ceil(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.ceil(n)", &args, |n| Value::Integer(n.ceil() as i64))
 }
 
-fn floor(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_floor attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    Ok(Value::Integer(n.floor() as i64))
+fn floor(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.floor(n)", &args, |n| Value::Integer(n.floor() as i64))
 }
 
-fn round(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_round attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    Ok(Value::Integer(n.round() as i64))
+fn round(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.round(n)", &args, |n| Value::Integer(n.round() as i64))
 }
 
-fn sqrt(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_sqrt attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    if n < 0.0 { return Ok(Value::Null); } // Ou erreur, au choix
-    Ok(Value::Float(n.sqrt()))
+fn sqrt(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.sqrt(n)", &args, |n| {
+        if n < 0.0 { Value::Null } else { Value::Float(n.sqrt()) } // Ou erreur, au choix
+    })
 }
 
-fn pow(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 2 { return Err("math_pow attend 2 arguments".into()); }
-    let base = get_number(&args[0])?;
-    let exp = get_number(&args[1])?;
+fn pow(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Math.pow(base, exp)", &args);
+    args.at_most(2)?;
+    let base = args.want_number(0)?;
+    let exp = args.want_number(1)?;
     Ok(Value::Float(base.powf(exp)))
 }
 
-fn sin(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_sin attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    Ok(Value::Float(n.sin()))
+fn sin(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.sin(rad)", &args, |n| Value::Float(n.sin()))
 }
 
-fn cos(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_cos attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    Ok(Value::Float(n.cos()))
+fn cos(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.cos(rad)", &args, |n| Value::Float(n.cos()))
 }
 
-fn tan(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_tan attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    Ok(Value::Float(n.tan()))
+fn tan(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.tan(rad)", &args, |n| Value::Float(n.tan()))
 }
 
-fn acos(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_acos attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    Ok(Value::Float(n.acos()))
+fn acos(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.acos(n)", &args, |n| Value::Float(n.acos()))
 }
 
-fn asin(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_asin attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    Ok(Value::Float(n.asin()))
+fn asin(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.asin(n)", &args, |n| Value::Float(n.asin()))
 }
 
-fn atan(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 { return Err("math_atan attend 1 argument".into()); }
-    let n = get_number(&args[0])?;
-    Ok(Value::Float(n.atan()))
-}
\ No newline at end of file
+fn atan(args: Vec<Value>) -> Result<Value, NativeError> {
+    unary("Math.atan(n)", &args, |n| Value::Float(n.atan()))
+}
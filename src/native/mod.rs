@@ -1,9 +1,23 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::{OnceLock, RwLock};
 use crate::ast::environment::NativeFn;
+use crate::ast::value::ClassData;
+use crate::ast::{DictMap, Value};
+use crate::vm::gc;
+
+pub use args::Args;
+pub use commands::Commands;
 
 static REGISTRY: OnceLock<RwLock<HashMap<String, NativeFn>>> = OnceLock::new();
 
+thread_local! {
+    // Constantes nommées fournies par les plugins (ex: GLFW_KEY_ESCAPE).
+    // Pas de slot global réservé : le compilateur les remplace par leur valeur à la première utilisation.
+    static CONSTANTS: RefCell<HashMap<String, Value>> = RefCell::new(HashMap::new());
+}
+
 pub fn init_registry() {
     let mut map = HashMap::new();
 
@@ -12,6 +26,9 @@ pub fn init_registry() {
     random::register(&mut map);
     system::register(&mut map);
     json::register(&mut map);
+    toml::register(&mut map);
+    yaml::register(&mut map);
+    csv::register(&mut map);
     http::register(&mut map);
     core::register(&mut map);
     process::register(&mut map);
@@ -21,6 +38,20 @@ pub fn init_registry() {
     date::register(&mut map);
     socket::register(&mut map);
     math::register(&mut map);
+    compress::register(&mut map);
+    term::register(&mut map);
+    intl::register(&mut map);
+    events::register(&mut map);
+    watch::register(&mut map);
+    serialize::register(&mut map);
+    jobs::register(&mut map);
+    errors::register(&mut map);
+    testing::register(&mut map);
+    commands::register(&mut map);
+    arrays::register(&mut map);
+    bytes::register(&mut map);
+    ffi::register(&mut map);
+    crate::vm::workers::register(&mut map);
 
     let _ = REGISTRY.set(RwLock::new(map));
 }
@@ -33,7 +64,37 @@ pub fn find(name: &str) -> Option<NativeFn> {
     reader.get(name).cloned()
 }
 
-pub fn extend_registry(new_funcs: HashMap<String, NativeFn>) {
+/// Extensions des fichiers de données qu'on peut importer (`import "config.json" as cfg`).
+pub const DATA_EXTENSIONS: [&str; 5] = ["json", "toml", "yaml", "yml", "csv"];
+
+/// Vrai si `import` doit lire ce fichier comme des données plutôt que l'exécuter.
+pub fn is_data_file(path: &str) -> bool {
+    std::path::Path::new(path).extension()
+        .is_some_and(|ext| DATA_EXTENSIONS.iter().any(|data| ext.eq_ignore_ascii_case(data)))
+}
+
+/// Valeur d'un fichier de données, d'après son extension : dict ou liste pour JSON et YAML,
+/// dict pour TOML, liste de dicts (une par ligne, indexés par l'en-tête) pour CSV.
+pub fn parse_data_file(path: &str, text: &str) -> Result<Value, String> {
+    let ext = std::path::Path::new(path).extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "json" => json::parse(text),
+        "toml" => toml::parse(text),
+        "yaml" | "yml" => yaml::parse(text),
+        "csv" => csv::parse(text, &csv::CsvOptions::default()),
+        _ => Err(format!("'{}' is not a data file (expected .{})", path, DATA_EXTENSIONS.join(", ."))),
+    }
+}
+
+/// Vrai si la native existe. Accepte aussi la forme qualifiée d'un wrapper
+/// (`Gpu.draw` -> `gpu_draw`), comme les natives de la stdlib.
+pub fn is_available(name: &str) -> bool {
+    find(name).is_some() || find(&name.replace('.', "_").to_lowercase()).is_some() || find_constant(name).is_some()
+}
+
+pub fn extend_registry(new_funcs: HashMap<String, NativeFn>, new_constants: HashMap<String, Value>) {
     if let Some(registry_lock) = REGISTRY.get() {
         if let Ok(mut writer) = registry_lock.write() {
             println!("[Aegis] Chargement de {} nouvelles fonctions natives...", new_funcs.len());
@@ -47,6 +108,170 @@ pub fn extend_registry(new_funcs: HashMap<String, NativeFn>) {
     else {
         eprintln!("[Aegis] Erreur : Registre non initialisé avant le chargement des plugins.");
     }
+
+    if !new_constants.is_empty() {
+        println!("[Aegis] Chargement de {} constantes natives...", new_constants.len());
+        CONSTANTS.with(|c| c.borrow_mut().extend(new_constants));
+    }
+}
+
+/// Valeur d'une constante enregistrée par un plugin, ou classe intégrée (Error, IOError...,
+/// CommandBuffer).
+pub fn find_constant(name: &str) -> Option<Value> {
+    CONSTANTS.with(|c| c.borrow().get(name).cloned())
+        .or_else(|| errors::class(name).map(Value::Class))
+        .or_else(|| commands::class(name).map(Value::Class))
+}
+
+/// Classe fournie par un plugin. Les méthodes d'instance reçoivent `this` en premier argument
+/// (`init` sert de constructeur), les méthodes statiques reçoivent la classe.
+pub struct NativeClass {
+    pub name: String,
+    pub methods: HashMap<String, NativeFn>,
+    pub static_methods: HashMap<String, NativeFn>,
+}
+
+impl NativeClass {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), methods: HashMap::new(), static_methods: HashMap::new() }
+    }
+
+    pub fn method(mut self, name: &str, func: NativeFn) -> Self {
+        self.methods.insert(name.to_string(), func);
+        self
+    }
+
+    pub fn static_method(mut self, name: &str, func: NativeFn) -> Self {
+        self.static_methods.insert(name.to_string(), func);
+        self
+    }
+}
+
+/// Construit la ClassData d'une classe native et l'expose aux scripts comme une constante.
+/// Chaque méthode est enregistrée dans le registre sous "Classe.methode".
+pub fn register_class(class: NativeClass) {
+    let mut funcs = HashMap::new();
+    let mut table = |methods: HashMap<String, NativeFn>| -> HashMap<String, Value> {
+        methods.into_iter().map(|(method, func)| {
+            let native_name = format!("{}.{}", class.name, method);
+            funcs.insert(native_name.clone(), func);
+            (method, Value::Native(native_name))
+        }).collect()
+    };
+
+    let methods = table(class.methods);
+    let static_methods = table(class.static_methods);
+
+    let data = ClassData {
+        name: class.name.clone(),
+        parent: None,
+        parent_ref: None,
+        methods,
+        fields: HashMap::new(),
+        field_types: HashMap::new(),
+        properties: HashMap::new(),
+        static_methods,
+        static_fields: RefCell::new(HashMap::new()),
+        static_field_types: HashMap::new(),
+        static_properties: HashMap::new(),
+        // Pas d'héritage depuis une classe native : elle n'est pas une globale du script
+        is_final: true,
+        final_methods: Default::default(),
+        interfaces: Vec::new(),
+        interfaces_names: Vec::new(),
+        visibilities: HashMap::new(),
+        ancestors: Vec::new(),
+        method_table: HashMap::new(),
+        static_method_table: HashMap::new(),
+        field_slots: HashMap::new(),
+        field_layout: Vec::new(),
+        doc: None,
+    }.link(None);
+
+    if let Some(registry_lock) = REGISTRY.get()
+        && let Ok(mut writer) = registry_lock.write() {
+        writer.extend(funcs);
+    }
+    CONSTANTS.with(|c| c.borrow_mut().insert(class.name, Value::Class(Rc::new(data))));
+}
+
+/// Erreur d'une native (et de la VM, qui la propage jusqu'au `catch`) : le message, et la
+/// valeur que recevra le `catch` du script quand ce n'est pas le message lui-même (instance
+/// d'Error structurée, valeur levée par `throw`). Se construit depuis une chaîne :
+/// `Err("Division by zero".into())`, ou `?` sur un `Result<_, String>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeError {
+    pub message: String,
+    pub value: Option<Value>,
+}
+
+impl NativeError {
+    /// Erreur dont le `catch` recevra `value` ; `message` est affiché si elle n'est pas attrapée.
+    pub fn with_value(message: impl Into<String>, value: Value) -> Self {
+        NativeError { message: message.into(), value: Some(value) }
+    }
+
+    /// La valeur reçue par le `catch` : celle attachée, sinon le message.
+    pub fn into_value(self) -> Value {
+        self.value.unwrap_or_else(|| Value::String(self.message.into()))
+    }
+
+    /// Même valeur pour le `catch`, message reformulé (contexte ajouté...).
+    pub fn map_message(self, f: impl FnOnce(String) -> String) -> Self {
+        NativeError { message: f(self.message), value: self.value }
+    }
+}
+
+impl From<String> for NativeError {
+    fn from(message: String) -> Self {
+        NativeError { message, value: None }
+    }
+}
+
+impl From<&str> for NativeError {
+    fn from(message: &str) -> Self {
+        NativeError { message: message.to_string(), value: None }
+    }
+}
+
+impl std::fmt::Display for NativeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Erreur structurée levée par une native : `return Err(native::error("ENOENT", msg, None))`.
+/// Un `catch` du script reçoit alors une instance d'Error avec `code`, `message` et `data`
+/// au lieu de la chaîne seule (si l'erreur n'est pas attrapée, seul le message est affiché).
+pub fn error(code: &str, message: impl Into<String>, data: Option<Value>) -> NativeError {
+    error_of("Error", code, message.into(), data)
+}
+
+// Comme `error`, avec une sous-classe d'Error précise (IOError...)
+fn error_of(kind: &str, code: &str, message: String, data: Option<Value>) -> NativeError {
+    let code = Value::String(code.to_string().into());
+    let error = errors::new_error(kind, &message, data.unwrap_or(Value::Null), vec![("code", code)]);
+    NativeError::with_value(message, error)
+}
+
+/// Traduit une erreur d'E/S en erreur structurée (ENOENT, EACCES...), avec le chemin en data.
+pub(crate) fn io_error(err: &std::io::Error, path: &str) -> NativeError {
+    use std::io::ErrorKind;
+
+    let code = match err.kind() {
+        ErrorKind::NotFound => "ENOENT",
+        ErrorKind::PermissionDenied => "EACCES",
+        ErrorKind::AlreadyExists => "EEXIST",
+        ErrorKind::IsADirectory => "EISDIR",
+        ErrorKind::NotADirectory => "ENOTDIR",
+        ErrorKind::InvalidInput | ErrorKind::InvalidData => "EINVAL",
+        _ => "EIO",
+    };
+
+    let mut data = DictMap::new();
+    data.insert("path".into(), Value::String(path.to_string().into()));
+
+    error_of("IOError", code, format!("{}: {}", path, err), Some(gc::dict(data)))
 }
 
 pub fn get_all_names() -> Vec<String> {
@@ -66,17 +291,34 @@ pub fn get_all_names() -> Vec<String> {
     names
 }
 
+pub mod args;
 mod io;
 mod time;
 mod random;
 mod system;
 mod json;
+mod toml;
+mod yaml;
+mod csv;
 mod http;
 mod core;
 mod process;
 mod path;
 mod regex;
 mod crypto;
-mod date;
+pub(crate) mod date;
 mod socket;
-mod math;
\ No newline at end of file
+mod math;
+pub(crate) mod compress;
+mod term;
+mod intl;
+mod events;
+mod watch;
+mod serialize;
+pub(crate) mod jobs;
+pub(crate) mod errors;
+pub(crate) mod testing;
+pub mod commands;
+pub(crate) mod arrays;
+pub(crate) mod bytes;
+pub(crate) mod ffi;
\ No newline at end of file
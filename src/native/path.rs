@@ -1,30 +1,197 @@
 use crate::{Value, NativeFn};
+use super::{Args, NativeError};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR};
 
 pub fn register(map: &mut HashMap<String, NativeFn>) {
     map.insert("path_join".to_string(), path_join);
+    map.insert("path_join_all".to_string(), path_join_all);
     map.insert("path_ext".to_string(), path_ext);
     map.insert("path_exists".to_string(), path_exists);
+    map.insert("path_separator".to_string(), path_separator);
+    map.insert("path_expand".to_string(), path_expand);
+    map.insert("path_normalize".to_string(), path_normalize);
+    map.insert("path_absolute".to_string(), path_absolute);
+    map.insert("path_relative".to_string(), path_relative);
+    map.insert("path_temp_dir".to_string(), path_temp_dir);
 }
 
-fn path_join(args: Vec<Value>) -> Result<Value, String> {
-    let p1 = args[0].as_str()?;
-    let p2 = args[1].as_str()?;
+fn to_value(path: &Path) -> Value {
+    Value::String(path.to_string_lossy().to_string().into())
+}
+
+// Résolution purement lexicale de `.` et `..` (le disque n'est pas consulté, les liens
+// symboliques ne sont pas suivis). Les séparateurs sont ceux de la plateforme.
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    let mut depth = 0; // Composants normaux qu'un `..` peut annuler
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                if depth > 0 {
+                    result.pop();
+                    depth -= 1;
+                } else if !result.has_root() {
+                    // Chemin relatif qui remonte au-delà de son point de départ : on garde `..`
+                    result.push("..");
+                }
+            },
+            Component::Normal(part) => {
+                result.push(part);
+                depth += 1;
+            },
+            Component::RootDir | Component::Prefix(_) => result.push(component.as_os_str()),
+        }
+    }
+    if result.as_os_str().is_empty() {
+        result.push(".");
+    }
+    result
+}
+
+fn absolute(path: &Path) -> Result<PathBuf, String> {
+    if path.is_absolute() {
+        return Ok(normalize(path));
+    }
+    let cwd = std::env::current_dir().map_err(|e| format!("Path.absolute: {}", e))?;
+    Ok(normalize(&cwd.join(path)))
+}
+
+fn home_dir() -> Result<PathBuf, String> {
+    dirs::home_dir().ok_or_else(|| "Path.expand: home directory not found".to_string())
+}
+
+fn path_join(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Path.join(a, b)", &args);
+    let p1 = args.want_str(0)?;
+    let p2 = args.want_str(1)?;
     let path = Path::new(&p1).join(p2);
-    Ok(Value::String(path.to_string_lossy().to_string()))
+    Ok(Value::String(path.to_string_lossy().to_string().into()))
+}
+
+fn path_join_all(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Path.join_all(parts)", &args);
+    let parts = args.want_list(0)?.borrow().clone();
+    let mut path = PathBuf::new();
+    for part in &parts {
+        match part {
+            Value::String(part) => path.push(part),
+            other => return Err(args.error(format!("parts must be strings, got {}", other.type_name())).into()),
+        }
+    }
+    Ok(to_value(&path))
+}
+
+fn path_separator(_args: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Value::String(MAIN_SEPARATOR.to_string().into()))
+}
+
+fn path_temp_dir(_args: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(to_value(&std::env::temp_dir()))
+}
+
+// `~` en tête devient le dossier personnel, `$VAR` et `${VAR}` la variable d'environnement.
+// Une variable absente est laissée telle quelle.
+fn path_expand(args: Vec<Value>) -> Result<Value, NativeError> {
+    let p = Args::new("Path.expand(path)", &args).want_str(0)?;
+
+    let mut expanded = String::new();
+    let rest = if p == "~" {
+        expanded.push_str(&home_dir()?.to_string_lossy());
+        ""
+    } else if let Some(rest) = p.strip_prefix("~/").or_else(|| p.strip_prefix("~\\")) {
+        expanded.push_str(&home_dir()?.join(rest).to_string_lossy());
+        ""
+    } else {
+        p.as_str()
+    };
+
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+        let braced = chars.peek() == Some(&'{');
+        if braced { chars.next(); }
+        let mut name = String::new();
+        while let Some(&n) = chars.peek() {
+            if !(n.is_ascii_alphanumeric() || n == '_') { break; }
+            name.push(n);
+            chars.next();
+        }
+        let closed = !braced || chars.peek() == Some(&'}');
+        if braced && closed { chars.next(); }
+
+        match std::env::var(&name) {
+            Ok(value) if !name.is_empty() && closed => expanded.push_str(&value),
+            _ => {
+                // Inconnue (ou mal formée) : on recopie le texte d'origine
+                expanded.push('$');
+                if braced { expanded.push('{'); }
+                expanded.push_str(&name);
+                if braced && closed { expanded.push('}'); }
+            }
+        }
+    }
+    Ok(Value::String(expanded.into()))
+}
+
+fn path_normalize(args: Vec<Value>) -> Result<Value, NativeError> {
+    let p = Args::new("Path.normalize(path)", &args).want_str(0)?;
+    Ok(to_value(&normalize(Path::new(&p))))
+}
+
+fn path_absolute(args: Vec<Value>) -> Result<Value, NativeError> {
+    let p = Args::new("Path.absolute(path)", &args).want_str(0)?;
+    Ok(to_value(&absolute(Path::new(&p))?))
+}
+
+// Chemin qui mène de `from` (un dossier) à `to`
+fn path_relative(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Path.relative(from, to)", &args);
+    let (from, to) = (args.want_str(0)?, args.want_str(1)?);
+    let (from, to) = if Path::new(&from).is_absolute() == Path::new(&to).is_absolute() {
+        (normalize(Path::new(&from)), normalize(Path::new(&to)))
+    } else {
+        (absolute(Path::new(&from))?, absolute(Path::new(&to))?)
+    };
+
+    let from_parts: Vec<Component> = from.components().filter(|c| *c != Component::CurDir).collect();
+    let to_parts: Vec<Component> = to.components().filter(|c| *c != Component::CurDir).collect();
+    let common = from_parts.iter().zip(&to_parts).take_while(|(a, b)| a == b).count();
+    if from_parts[common..].contains(&Component::ParentDir) {
+        return Err(format!("Path.relative: cannot go from '{}' to '{}'", from.display(), to.display()).into());
+    }
+    if common == 0 && from.has_root() {
+        // Racines différentes (lecteurs Windows) : pas de chemin relatif possible
+        return Ok(to_value(&to));
+    }
+
+    let mut result = PathBuf::new();
+    for _ in common..from_parts.len() {
+        result.push("..");
+    }
+    for part in &to_parts[common..] {
+        result.push(part.as_os_str());
+    }
+    if result.as_os_str().is_empty() {
+        result.push(".");
+    }
+    Ok(to_value(&result))
 }
 
-fn path_ext(args: Vec<Value>) -> Result<Value, String> {
-    let p = args[0].as_str()?;
+fn path_ext(args: Vec<Value>) -> Result<Value, NativeError> {
+    let p = Args::new("Path.extension(path)", &args).want_str(0)?;
     let path = Path::new(&p);
     match path.extension() {
-        Some(os_str) => Ok(Value::String(os_str.to_string_lossy().to_string())),
-        None => Ok(Value::String("".to_string()))
+        Some(os_str) => Ok(Value::String(os_str.to_string_lossy().to_string().into())),
+        None => Ok(Value::String("".into()))
     }
 }
 
-fn path_exists(args: Vec<Value>) -> Result<Value, String> {
-    let p = args[0].as_str()?;
+fn path_exists(args: Vec<Value>) -> Result<Value, NativeError> {
+    let p = Args::new("Path.exists(path)", &args).want_str(0)?;
     Ok(Value::Boolean(Path::new(&p).exists()))
 }
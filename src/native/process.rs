@@ -1,39 +1,41 @@
-use std::{cell::RefCell, collections::HashMap, process::Command, rc::Rc};
+use std::{collections::HashMap, process::Command};
 use crate::{NativeFn, Value};
+use crate::ast::DictMap;
+use crate::vm::gc;
+use super::{Args, NativeError};
 
 pub fn register(map: &mut HashMap<String, NativeFn>) {
     map.insert("proc_exec".to_string(), proc_exec);
 }
 
-fn proc_exec(args: Vec<Value>) -> Result<Value, String> {
-    if args.is_empty() {
-        return Err("Args: command, [args_list]".into());
-    }
-
-    let cmd_name = args[0].as_str()?;
+fn proc_exec(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Process.exec(cmd, args?)", &args);
+    args.at_most(2)?;
+    let cmd_name = args.want_str(0)?;
     let mut command = Command::new(cmd_name);
 
-    if args.len() > 1 {
-        if let Value::List(l) = &args[1] {
-            for arg in l.borrow().iter() {
-                command.arg(arg.as_str()?);
-            }
+    if !matches!(args.get(1), None | Some(Value::Null)) {
+        for arg in args.want_list(1)?.borrow().iter() {
+            match arg {
+                Value::String(arg) => command.arg(arg),
+                other => return Err(args.error(format!("command arguments must be strings, got {}", other.type_name())).into()),
+            };
         }
     }
 
     let output = command.output().map_err(|e| format!("Exec failed: {}", e))?;
     
     // On retourne un Dict { "code": int, "stdout": string, "stderr": string }
-    let mut res_map = HashMap::new();
+    let mut res_map = DictMap::new();
 
     let code = output.status.code().unwrap_or(-1) as i64;
-    res_map.insert("code".to_string(), Value::Integer(code));
+    res_map.insert("code".into(), Value::Integer(code));
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    res_map.insert("stdout".to_string(), Value::String(stdout));
+    res_map.insert("stdout".into(), Value::String(stdout.into()));
 
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    res_map.insert("stderr".to_string(), Value::String(stderr));
+    res_map.insert("stderr".into(), Value::String(stderr.into()));
 
-    Ok(Value::Dict(Rc::new(RefCell::new(res_map))))
+    Ok(gc::dict(res_map))
 }
@@ -1,31 +1,134 @@
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use crate::ast::Value;
+use crate::vm::gc;
 use std::collections::HashMap;
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use super::{Args, NativeError};
+
+// Générateurs déterministes créés par Random.seed(n), indépendants les uns des autres.
+// Chacun reste ici jusqu'à son free() (ou la fin d'un bloc `with`)
+struct RandomState {
+    generators: HashMap<usize, StdRng>,
+    next_id: usize
+}
+
+lazy_static! {
+    static ref RNG_STATE: Mutex<RandomState> = Mutex::new(RandomState {
+        generators: HashMap::new(),
+        next_id: 1,
+    });
+}
 
 pub fn register(map: &mut HashMap<String, super::NativeFn>) {
     map.insert("rand_int".to_string(), rand_int);
     map.insert("rand_float".to_string(), rand_float);
+    map.insert("rand_seed".to_string(), rand_seed);
+    map.insert("rand_free".to_string(), rand_free);
+    map.insert("rand_gauss".to_string(), rand_gauss);
+    map.insert("rand_choice".to_string(), rand_choice);
+    map.insert("rand_sample".to_string(), rand_sample);
+    map.insert("rand_shuffle".to_string(), rand_shuffle);
 }
 
-fn rand_int(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 2 {
-        return Err("rand_int attend 2 arguments (min, max)".into());
+// Exécute `f` avec le générateur passé en argument `index` (absent ou null = générateur global du thread)
+fn with_rng<T>(args: &Args, index: usize, f: impl FnOnce(&mut dyn rand::RngCore) -> T) -> Result<T, String> {
+    match args.get(index) {
+        None | Some(Value::Null) => Ok(f(&mut rand::thread_rng())),
+        Some(_) => {
+            let id = args.want_int(index)? as usize;
+            let mut state = RNG_STATE.lock().unwrap();
+            let rng = state.generators.get_mut(&id).ok_or("Random generator ID not found")?;
+            Ok(f(rng))
+        }
     }
+}
 
-    let min = args[0].as_int()?;
-    let max = args[1].as_int()?;
+fn rand_int(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Random.int(min, max)", &args);
+    let min = args.want_int(0)?;
+    let max = args.want_int(1)?;
 
     if min >= max {
-        return Err("min doit être inférieur à max".into());
+        return Err(args.error(format!("min ({}) must be less than max ({})", min, max)).into());
     }
 
-    let mut rng = rand::thread_rng();
-    let val = rng.gen_range(min..max);
+    let val = with_rng(&args, 2, |rng| rng.gen_range(min..max))?;
     Ok(Value::Integer(val))
 }
 
-fn rand_float(_: Vec<Value>) -> Result<Value, String> {
-    let mut rng = rand::thread_rng();
-    let val: f64 = rng.r#gen();
+fn rand_float(args: Vec<Value>) -> Result<Value, NativeError> {
+    let val: f64 = with_rng(&Args::new("Random.float()", &args), 0, |rng| rng.r#gen())?;
     Ok(Value::Float(val))
-}
\ No newline at end of file
+}
+
+fn rand_seed(args: Vec<Value>) -> Result<Value, NativeError> {
+    let seed = Args::new("Random.seed(n)", &args).want_int(0)? as u64;
+
+    let mut state = RNG_STATE.lock().unwrap();
+    let id = state.next_id;
+    state.generators.insert(id, StdRng::seed_from_u64(seed));
+    state.next_id += 1;
+
+    Ok(Value::Integer(id as i64))
+}
+
+fn rand_free(args: Vec<Value>) -> Result<Value, NativeError> {
+    let id = Args::new("rng.free()", &args).want_int(0)? as usize;
+    let removed = RNG_STATE.lock().unwrap().generators.remove(&id).is_some();
+    Ok(Value::Boolean(removed))
+}
+
+// Loi normale via la transformée de Box-Muller
+fn rand_gauss(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Random.gauss(mu, sigma)", &args);
+    let mu = args.want_number(0)?;
+    let sigma = args.want_number(1)?;
+
+    let z = with_rng(&args, 2, |rng| {
+        let u1: f64 = 1.0 - rng.r#gen::<f64>(); // ]0, 1] pour éviter ln(0)
+        let u2: f64 = rng.r#gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    })?;
+
+    Ok(Value::Float(mu + sigma * z))
+}
+
+fn rand_choice(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Random.choice(list)", &args);
+    let list = args.want_list(0)?;
+    let items = list.borrow();
+    let picked = with_rng(&args, 1, |rng| items.choose(rng).cloned())?;
+
+    Ok(picked.unwrap_or(Value::Null))
+}
+
+// k éléments distincts (par position), dans un ordre aléatoire
+fn rand_sample(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Random.sample(list, k)", &args);
+    let list = args.want_list(0)?;
+    let k = args.want_int(1)?;
+    let items = list.borrow();
+
+    if k < 0 || k as usize > items.len() {
+        return Err(format!("Sample size {} out of range (list has {} elements)", k, items.len()).into());
+    }
+
+    let picked: Vec<Value> = with_rng(&args, 2, |rng| {
+        items.choose_multiple(rng, k as usize).cloned().collect()
+    })?;
+
+    Ok(gc::list(picked))
+}
+
+// Mélange la liste sur place et la retourne
+fn rand_shuffle(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Random.shuffle(list)", &args);
+    let list = args.want_list(0)?;
+    with_rng(&args, 1, |rng| list.borrow_mut().shuffle(rng))?;
+
+    Ok(Value::List(list))
+}
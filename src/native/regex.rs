@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 use lazy_static::lazy_static;
 use regex::Regex;
+use super::{Args, NativeError};
 
 struct RegexState {
     patterns: HashMap<usize, Regex>,
@@ -22,8 +23,8 @@ pub fn register(map: &mut HashMap<String, NativeFn>) {
     map.insert("re_replace".to_string(), re_replace);
 }
 
-fn re_new(args: Vec<Value>) -> Result<Value, String> {
-    let pattern = args[0].as_str()?;
+fn re_new(args: Vec<Value>) -> Result<Value, NativeError> {
+    let pattern = Args::new("Regex.new(pattern)", &args).want_str(0)?;
     let re = Regex::new(&pattern).map_err(|e| format!("Invalid Regex: {}", e))?;
 
     let mut state = RE_STATE.lock().unwrap();
@@ -34,9 +35,10 @@ fn re_new(args: Vec<Value>) -> Result<Value, String> {
     Ok(Value::Integer(id as i64))
 }
 
-fn re_match(args: Vec<Value>) -> Result<Value, String> {
-    let id = args[0].as_int()? as usize;
-    let text = args[1].as_str()?;
+fn re_match(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Regex.test(re_id, text)", &args);
+    let id = args.want_int(0)? as usize;
+    let text = args.want_str(1)?;
 
     let state = RE_STATE.lock().unwrap();
     if let Some(re) = state.patterns.get(&id) {
@@ -46,15 +48,16 @@ fn re_match(args: Vec<Value>) -> Result<Value, String> {
     }
 }
 
-fn re_replace(args: Vec<Value>) -> Result<Value, String> {
-    let id = args[0].as_int()? as usize;
-    let text = args[1].as_str()?;
-    let replacement = args[2].as_str()?;
+fn re_replace(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Regex.replace(re_id, text, replacement)", &args);
+    let id = args.want_int(0)? as usize;
+    let text = args.want_str(1)?;
+    let replacement = args.want_str(2)?;
 
     let state = RE_STATE.lock().unwrap();
     if let Some(re) = state.patterns.get(&id) {
         let result = re.replace_all(&text, replacement.as_str());
-        Ok(Value::String(result.to_string()))
+        Ok(Value::String(result.to_string().into()))
     } else {
         Err("Regex ID not found".into())
     }
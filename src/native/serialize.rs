@@ -0,0 +1,327 @@
+use crate::ast::{DictMap, TypedArray, Value, ValueKey};
+use crate::vm::gc;
+use super::{Args, NativeError};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+// Format binaire de Serde.dump : en-tête "AEGS" + version, puis la valeur.
+// Chaque valeur commence par un octet de type ; entiers et flottants sur 8 octets, longueurs
+// sur 4 octets, tout en little-endian. Les clés d'un dict sont écrites comme des valeurs
+// (chaîne, entier ou booléen) dans leur ordre d'insertion, celles d'un enum triées.
+const MAGIC: &[u8; 4] = b"AEGS";
+// Version 1 : clés de dict toujours des chaînes (encore lue)
+const VERSION: u8 = 2;
+
+// Au-delà, les données sont refusées plutôt que de faire déborder la pile
+const MAX_DEPTH: usize = 1000;
+
+// Clé du dict qui remplace une instance (écrit et relu par stdlib/serde.aeg)
+const CLASS_KEY: &str = "__serde_class__";
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_FLOAT: u8 = 4;
+const TAG_STRING: u8 = 5;
+const TAG_LIST: u8 = 6;
+const TAG_DICT: u8 = 7;
+const TAG_ENUM: u8 = 8;
+const TAG_BYTES: u8 = 9;
+const TAG_RANGE: u8 = 10;
+const TAG_DURATION: u8 = 11;
+// Instant puis nom du fuseau ("local", "UTC", "Europe/Paris", "+02:00")
+const TAG_DATETIME: u8 = 12;
+// Nombre d'éléments puis leurs valeurs brutes (f32 sur 4 octets, i64 sur 8)
+const TAG_FLOAT32_ARRAY: u8 = 13;
+const TAG_INT_ARRAY: u8 = 14;
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("serde_dump".to_string(), serde_dump);
+    map.insert("serde_load".to_string(), serde_load);
+    map.insert("serde_scan".to_string(), serde_scan);
+}
+
+fn serde_dump(args: Vec<Value>) -> Result<Value, NativeError> {
+    let value = Args::new("Serde.dump(value)", &args).want(0)?;
+    let mut out = MAGIC.to_vec();
+    out.push(VERSION);
+    encode(value, &mut out, &mut Vec::new())?;
+    Ok(Value::Bytes(Rc::new(RefCell::new(out))))
+}
+
+fn serde_load(args: Vec<Value>) -> Result<Value, NativeError> {
+    let data = Args::new("Serde.load(bytes)", &args).want_bytes(0)?.borrow().clone();
+    if data.len() < 5 || &data[..4] != MAGIC {
+        return Err("Serde.load: not Serde data".into());
+    }
+    if data[4] == 0 || data[4] > VERSION {
+        return Err(format!("Serde.load: unsupported format version {}", data[4]).into());
+    }
+
+    let mut reader = Reader { data: &data, pos: 5, version: data[4] };
+    let value = reader.value(0)?;
+    if reader.pos != data.len() {
+        return Err("Serde.load: trailing bytes after the value".into());
+    }
+    Ok(value)
+}
+
+// Vrai si la valeur contient des instances (à convertir avant dump) ou des dicts qui en
+// remplacent (à reconstruire après load) : sans elles, stdlib/serde.aeg n'a rien à parcourir.
+fn serde_scan(args: Vec<Value>) -> Result<Value, NativeError> {
+    fn scan(value: &Value, path: &mut Vec<usize>) -> Result<bool, String> {
+        let (address, children): (usize, Vec<Value>) = match value {
+            Value::Instance(_) => return Ok(true),
+            Value::List(rc) => (Rc::as_ptr(rc) as *const () as usize, rc.borrow().clone()),
+            Value::Dict(rc) => {
+                if rc.borrow().contains_key(CLASS_KEY) {
+                    return Ok(true);
+                }
+                (Rc::as_ptr(rc) as *const () as usize, rc.borrow().values().cloned().collect())
+            },
+            _ => return Ok(false),
+        };
+        enter(path, address)?;
+        for child in &children {
+            if scan(child, path)? {
+                return Ok(true);
+            }
+        }
+        path.pop();
+        Ok(false)
+    }
+
+    let value = Args::new("Serde.scan(value)", &args).want(0)?;
+    Ok(Value::Boolean(scan(value, &mut Vec::new())?))
+}
+
+// `path` : conteneurs en cours d'écriture. En retrouver un, c'est boucler sur un cycle.
+// Une valeur partagée sans cycle est simplement écrite deux fois.
+fn enter(path: &mut Vec<usize>, address: usize) -> Result<(), String> {
+    if path.contains(&address) {
+        return Err("Serde.dump: cannot serialize a cyclic structure".into());
+    }
+    if path.len() >= MAX_DEPTH {
+        return Err(format!("Serde.dump: nesting deeper than {} levels", MAX_DEPTH));
+    }
+    path.push(address);
+    Ok(())
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    let len = u32::try_from(len).map_err(|_| "Serde.dump: value too large".to_string())?;
+    out.extend_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) -> Result<(), String> {
+    write_len(out, s.len())?;
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn encode_entries(entries: &HashMap<String, Value>, out: &mut Vec<u8>, path: &mut Vec<usize>) -> Result<(), String> {
+    let mut keys: Vec<&String> = entries.keys().collect();
+    keys.sort();
+    write_len(out, keys.len())?;
+    for key in keys {
+        write_str(out, key)?;
+        encode(&entries[key], out, path)?;
+    }
+    Ok(())
+}
+
+fn encode(value: &Value, out: &mut Vec<u8>, path: &mut Vec<usize>) -> Result<(), String> {
+    match value {
+        Value::Null => out.push(TAG_NULL),
+        Value::Boolean(false) => out.push(TAG_FALSE),
+        Value::Boolean(true) => out.push(TAG_TRUE),
+        Value::Integer(i) => {
+            out.push(TAG_INT);
+            out.extend_from_slice(&i.to_le_bytes());
+        },
+        Value::Float(f) => {
+            out.push(TAG_FLOAT);
+            out.extend_from_slice(&f.to_le_bytes());
+        },
+        Value::String(s) => {
+            out.push(TAG_STRING);
+            write_str(out, s)?;
+        },
+        Value::Bytes(b) => {
+            out.push(TAG_BYTES);
+            let b = b.borrow();
+            write_len(out, b.len())?;
+            out.extend_from_slice(&b);
+        },
+        Value::Range(start, end, step) => {
+            out.push(TAG_RANGE);
+            for n in [start, end, step] {
+                out.extend_from_slice(&n.to_le_bytes());
+            }
+        },
+        Value::Duration(ms) => {
+            out.push(TAG_DURATION);
+            out.extend_from_slice(&ms.to_le_bytes());
+        },
+        Value::DateTime(ms, zone) => {
+            out.push(TAG_DATETIME);
+            out.extend_from_slice(&ms.to_le_bytes());
+            write_str(out, &super::date::zone_name(*zone))?;
+        },
+        Value::TypedArray(array) => match &*array.borrow() {
+            TypedArray::Float32(items) => {
+                out.push(TAG_FLOAT32_ARRAY);
+                write_len(out, items.len())?;
+                items.iter().for_each(|f| out.extend_from_slice(&f.to_le_bytes()));
+            },
+            TypedArray::Int(items) => {
+                out.push(TAG_INT_ARRAY);
+                write_len(out, items.len())?;
+                items.iter().for_each(|i| out.extend_from_slice(&i.to_le_bytes()));
+            },
+        },
+        Value::List(rc) => {
+            enter(path, Rc::as_ptr(rc) as *const () as usize)?;
+            out.push(TAG_LIST);
+            let items = rc.borrow();
+            write_len(out, items.len())?;
+            for item in items.iter() {
+                encode(item, out, path)?;
+            }
+            path.pop();
+        },
+        Value::Dict(rc) => {
+            enter(path, Rc::as_ptr(rc) as *const () as usize)?;
+            out.push(TAG_DICT);
+            let entries = rc.borrow();
+            write_len(out, entries.len())?;
+            for (key, value) in entries.iter() {
+                encode(&key.to_value(), out, path)?;
+                encode(value, out, path)?;
+            }
+            path.pop();
+        },
+        Value::Enum(variants) => {
+            out.push(TAG_ENUM);
+            encode_entries(variants, out, path)?;
+        },
+        Value::Instance(inst) => {
+            return Err(format!("Serde.dump: cannot serialize an instance of '{}' (register its class with Serde.register)",
+                inst.borrow().class.name));
+        },
+        Value::Function(_) | Value::Native(_) => return Err("Serde.dump: cannot serialize a function".into()),
+        Value::Task(_) => return Err("Serde.dump: cannot serialize a task (await it first)".into()),
+        Value::Class(c) => return Err(format!("Serde.dump: cannot serialize class '{}'", c.name)),
+        Value::Interface(i) => return Err(format!("Serde.dump: cannot serialize interface '{}'", i.name)),
+        Value::Cell(cell) => encode(&cell.borrow(), out, path)?,
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    version: u8,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], String> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.data.len())
+            .ok_or("Serde.load: truncated data")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| "Serde.load: invalid UTF-8 string".to_string())
+    }
+
+    fn entries(&mut self, depth: usize) -> Result<HashMap<String, Value>, String> {
+        let count = self.len()?;
+        let mut entries = HashMap::new();
+        for _ in 0..count {
+            let key = self.string()?;
+            let value = self.value(depth + 1)?;
+            entries.insert(key, value);
+        }
+        Ok(entries)
+    }
+
+    fn dict_entries(&mut self, depth: usize) -> Result<DictMap, String> {
+        let count = self.len()?;
+        let mut entries = DictMap::new();
+        for _ in 0..count {
+            let key = if self.version == 1 {
+                self.string()?.into()
+            } else {
+                ValueKey::from_value(&self.value(depth + 1)?).map_err(|e| format!("Serde.load: {}", e))?
+            };
+            let value = self.value(depth + 1)?;
+            entries.insert(key, value);
+        }
+        Ok(entries)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("Serde.load: nesting deeper than {} levels", MAX_DEPTH));
+        }
+        Ok(match self.byte()? {
+            TAG_NULL => Value::Null,
+            TAG_FALSE => Value::Boolean(false),
+            TAG_TRUE => Value::Boolean(true),
+            TAG_INT => Value::Integer(self.i64()?),
+            TAG_FLOAT => Value::Float(f64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            TAG_STRING => Value::String(self.string()?.into()),
+            TAG_BYTES => {
+                let len = self.len()?;
+                Value::Bytes(Rc::new(RefCell::new(self.take(len)?.to_vec())))
+            },
+            TAG_RANGE => Value::Range(self.i64()?, self.i64()?, self.i64()?),
+            TAG_DURATION => Value::Duration(self.i64()?),
+            TAG_DATETIME => {
+                let ms = self.i64()?;
+                let zone = super::date::parse_zone(&self.string()?).map_err(|e| format!("Serde.load: {}", e))?;
+                Value::DateTime(ms, zone)
+            },
+            tag @ (TAG_FLOAT32_ARRAY | TAG_INT_ARRAY) => {
+                let size = if tag == TAG_FLOAT32_ARRAY { 4 } else { 8 };
+                let count = self.len()?;
+                let raw = self.take(count.checked_mul(size).ok_or("Serde.load: truncated data")?)?;
+                let array = if tag == TAG_FLOAT32_ARRAY {
+                    TypedArray::Float32(raw.chunks_exact(4).map(|c| f32::from_le_bytes(c.try_into().unwrap())).collect())
+                } else {
+                    TypedArray::Int(raw.chunks_exact(8).map(|c| i64::from_le_bytes(c.try_into().unwrap())).collect())
+                };
+                Value::TypedArray(Rc::new(RefCell::new(array)))
+            },
+            TAG_LIST => {
+                let count = self.len()?;
+                // Pas de réservation sur la foi de la longueur annoncée : les données peuvent mentir
+                let mut items = Vec::new();
+                for _ in 0..count {
+                    items.push(self.value(depth + 1)?);
+                }
+                gc::list(items)
+            },
+            TAG_DICT => gc::dict(self.dict_entries(depth)?),
+            TAG_ENUM => Value::Enum(Rc::new(self.entries(depth)?)),
+            tag => return Err(format!("Serde.load: invalid value tag {}", tag)),
+        })
+    }
+}
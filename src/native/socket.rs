@@ -1,16 +1,22 @@
 use crate::{Value, NativeFn};
+use super::jobs::{self, Detached};
+use super::{Args, NativeError};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::net::{TcpListener, TcpStream};
 use std::io::{Read, Write};
+use base64::{Engine as _, engine::general_purpose};
+use sha1::{Digest, Sha1};
 use lazy_static::lazy_static;
 
 // --- STATE ---
 struct SocketState {
     listeners: HashMap<usize, TcpListener>,
     streams: HashMap<usize, TcpStream>,
+    websockets: HashMap<usize, Arc<WebSocket>>,
     next_id: usize,
 }
 
@@ -21,6 +27,7 @@ lazy_static! {
     static ref STATE: Mutex<ThreadSafeState> = Mutex::new(ThreadSafeState(SocketState {
         listeners: HashMap::new(),
         streams: HashMap::new(),
+        websockets: HashMap::new(),
         next_id: 1,
     }));
 }
@@ -34,16 +41,37 @@ pub fn register(map: &mut HashMap<String, NativeFn>) {
     map.insert("sock_read_bytes".to_string(), sock_read_bytes);
     map.insert("sock_write".to_string(), sock_write);
     map.insert("sock_close".to_string(), sock_close);
+    map.insert("sock_accept_async".to_string(), sock_accept_async);
+    map.insert("sock_connect_async".to_string(), sock_connect_async);
+    map.insert("sock_read_async".to_string(), sock_read_async);
+    map.insert("sock_read_bytes_async".to_string(), sock_read_bytes_async);
+    map.insert("ws_connect".to_string(), ws_connect);
+    map.insert("ws_accept".to_string(), ws_accept);
+    map.insert("ws_send".to_string(), ws_send);
+    map.insert("ws_recv".to_string(), ws_recv);
+    map.insert("ws_close".to_string(), ws_close);
+    map.insert("ws_accept_async".to_string(), ws_accept_async);
+    map.insert("ws_recv_async".to_string(), ws_recv_async);
+}
+
+// Enregistre une nouvelle connexion et retourne son ID
+fn add_stream(stream: TcpStream) -> usize {
+    let mut guard = STATE.lock().unwrap();
+    let state = &mut guard.0;
+
+    let id = state.next_id;
+    state.streams.insert(id, stream);
+    state.next_id += 1;
+    id
 }
 
 // --- IMPLEMENTATION ---
 
 // 1. SERVEUR : Bind un port
-fn sock_bind(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() < 2 { return Err("Args: host, port".into()); }
-    
-    let host = args[0].as_str()?;
-    let port = args[1].as_int()?;
+fn sock_bind(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Socket.listen(host, port)", &args);
+    let host = args.want_str(0)?;
+    let port = args.want_int(1)?;
     let addr = format!("{}:{}", host, port);
 
     let listener = TcpListener::bind(&addr).map_err(|e| e.to_string())?;
@@ -62,8 +90,8 @@ fn sock_bind(args: Vec<Value>) -> Result<Value, String> {
 }
 
 // 2. SERVEUR : Accepter une connexion (BLOQUANT)
-fn sock_accept(args: Vec<Value>) -> Result<Value, String> {
-    let id = args[0].as_int()? as usize;
+fn sock_accept(args: Vec<Value>) -> Result<Value, NativeError> {
+    let id = Args::new("Socket.accept(server_id)", &args).want_int(0)? as usize;
     
     let mut guard = STATE.lock().unwrap();
     let state = &mut guard.0;
@@ -78,32 +106,26 @@ fn sock_accept(args: Vec<Value>) -> Result<Value, String> {
             state.next_id += 1;
             Ok(Value::Integer(stream_id as i64))
         },
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(e.to_string().into())
     }
 }
 
 // 3. CLIENT : Se connecter
-fn sock_connect(args: Vec<Value>) -> Result<Value, String> {
-    let host = args[0].as_str()?;
-    let port = args[1].as_int()?;
+fn sock_connect(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Socket.connect(host, port)", &args);
+    let host = args.want_str(0)?;
+    let port = args.want_int(1)?;
     let addr = format!("{}:{}", host, port);
 
     let stream = TcpStream::connect(&addr).map_err(|e| e.to_string())?;
-
-    let mut guard = STATE.lock().unwrap();
-    let state = &mut guard.0;
-    
-    let id = state.next_id;
-    state.streams.insert(id, stream);
-    state.next_id += 1;
-
-    Ok(Value::Integer(id as i64))
+    Ok(Value::Integer(add_stream(stream) as i64))
 }
 
 // 4. READ (Lecture de N octets)
-fn sock_read(args: Vec<Value>) -> Result<Value, String> {
-    let id = args[0].as_int()? as usize;
-    let size = args[1].as_int()? as usize; // Nombre d'octets à lire
+fn sock_read(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Socket.read(client_id, size)", &args);
+    let id = args.want_int(0)? as usize;
+    let size = args.want_int(1)?.max(0) as usize; // Nombre d'octets à lire
 
     let mut guard = STATE.lock().unwrap();
     let state = &mut guard.0;
@@ -120,13 +142,14 @@ fn sock_read(args: Vec<Value>) -> Result<Value, String> {
     // On remplace les caractères invalides pour ne pas crasher
     let s = String::from_utf8_lossy(&buffer).to_string();
     
-    Ok(Value::String(s))
+    Ok(Value::String(s.into()))
 }
 
 // Retourne les données brutes, parfait pour les images ou l'upload
-fn sock_read_bytes(args: Vec<Value>) -> Result<Value, String> {
-    let id = args[0].as_int()? as usize;
-    let size = args[1].as_int()? as usize; 
+fn sock_read_bytes(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Socket.read_bytes(client_id, size)", &args);
+    let id = args.want_int(0)? as usize;
+    let size = args.want_int(1)?.max(0) as usize;
 
     let mut guard = STATE.lock().unwrap();
     let state = &mut guard.0;
@@ -144,9 +167,10 @@ fn sock_read_bytes(args: Vec<Value>) -> Result<Value, String> {
 }
 
 // 5. WRITE
-fn sock_write(args: Vec<Value>) -> Result<Value, String> {
-    let id = args[0].as_int()? as usize;
-    let content = &args[1];
+fn sock_write(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Socket.write(client_id, data)", &args);
+    let id = args.want_int(0)? as usize;
+    let content = args.want(1)?;
 
     let mut guard = STATE.lock().unwrap();
     let state = &mut guard.0;
@@ -170,8 +194,8 @@ fn sock_write(args: Vec<Value>) -> Result<Value, String> {
 }
 
 // 6. CLOSE
-fn sock_close(args: Vec<Value>) -> Result<Value, String> {
-    let id = args[0].as_int()? as usize;
+fn sock_close(args: Vec<Value>) -> Result<Value, NativeError> {
+    let id = Args::new("Socket.close(id)", &args).want_int(0)? as usize;
     let mut guard = STATE.lock().unwrap();
     let state = &mut guard.0;
     
@@ -181,3 +205,461 @@ fn sock_close(args: Vec<Value>) -> Result<Value, String> {
     
     Ok(Value::Null)
 }
+
+// --- VERSIONS ASYNC ---
+// Même travail que les natives bloquantes, mais sur un thread d'arrière-plan : elles
+// retournent une tâche à attendre avec `await`. Le thread travaille sur un clone du
+// socket (try_clone), le verrou de STATE n'est pas gardé pendant l'attente.
+
+fn clone_listener(id: usize) -> Result<TcpListener, String> {
+    let guard = STATE.lock().unwrap();
+    let listener = guard.0.listeners.get(&id).ok_or("Invalid Listener ID")?;
+    listener.try_clone().map_err(|e| e.to_string())
+}
+
+fn clone_stream(id: usize) -> Result<TcpStream, String> {
+    let guard = STATE.lock().unwrap();
+    let stream = guard.0.streams.get(&id).ok_or("Invalid Stream ID")?;
+    stream.try_clone().map_err(|e| e.to_string())
+}
+
+fn read_some(mut stream: TcpStream, size: usize) -> Result<Vec<u8>, String> {
+    let mut buffer = vec![0; size];
+    let bytes_read = stream.read(&mut buffer).map_err(|e| e.to_string())?;
+    buffer.truncate(bytes_read);
+    Ok(buffer)
+}
+
+fn sock_accept_async(args: Vec<Value>) -> Result<Value, NativeError> {
+    let listener = clone_listener(Args::new("Socket.accept_async(server_id)", &args).want_int(0)? as usize)?;
+    Ok(jobs::spawn("sock_accept", move || {
+        let (stream, _addr) = listener.accept().map_err(|e| e.to_string())?;
+        Ok(Detached::Int(add_stream(stream) as i64))
+    }))
+}
+
+fn sock_connect_async(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Socket.connect_async(host, port)", &args);
+    let addr = format!("{}:{}", args.want_str(0)?, args.want_int(1)?);
+    Ok(jobs::spawn("sock_connect", move || {
+        let stream = TcpStream::connect(&addr).map_err(|e| e.to_string())?;
+        Ok(Detached::Int(add_stream(stream) as i64))
+    }))
+}
+
+fn sock_read_async(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Socket.read_async(client_id, size)", &args);
+    let stream = clone_stream(args.want_int(0)? as usize)?;
+    let size = args.want_int(1)?.max(0) as usize;
+    Ok(jobs::spawn("sock_read", move || {
+        let buffer = read_some(stream, size)?;
+        Ok(Detached::Str(String::from_utf8_lossy(&buffer).to_string()))
+    }))
+}
+
+fn sock_read_bytes_async(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Socket.read_bytes_async(client_id, size)", &args);
+    let stream = clone_stream(args.want_int(0)? as usize)?;
+    let size = args.want_int(1)?.max(0) as usize;
+    Ok(jobs::spawn("sock_read_bytes", move || read_some(stream, size).map(Detached::Bytes)))
+}
+
+// --- WEBSOCKET ---
+// Client et serveur WebSocket (RFC 6455) au-dessus des sockets TCP : poignée de main HTTP
+// (Upgrade: websocket), puis des messages découpés en trames. Le client masque ses trames,
+// comme l'exige le protocole. Les ping et close du pair reçoivent leur réponse dans recv.
+
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+// Taille maximale d'un message (toutes trames confondues) et des en-têtes de la poignée de main
+const WS_MAX_MESSAGE: usize = 64 * 1024 * 1024;
+const WS_MAX_HEAD: usize = 16 * 1024;
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+type TlsStream = native_tls::TlsStream<TcpStream>;
+
+// Moitié lecture ou écriture d'une connexion. En clair, deux clones du TcpStream : un recv
+// bloqué n'empêche pas un send. Un flux TLS ne se sépare pas : les deux moitiés le partagent,
+// et un send attend la fin de la lecture en cours.
+enum Half {
+    Tcp(TcpStream),
+    Tls(Arc<Mutex<TlsStream>>),
+}
+
+impl Read for Half {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Half::Tcp(stream) => stream.read(buf),
+            Half::Tls(stream) => stream.lock().unwrap().read(buf),
+        }
+    }
+}
+
+impl Write for Half {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Half::Tcp(stream) => stream.write(buf),
+            Half::Tls(stream) => stream.lock().unwrap().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Half::Tcp(stream) => stream.flush(),
+            Half::Tls(stream) => stream.lock().unwrap().flush(),
+        }
+    }
+}
+
+struct WebSocket {
+    reader: Mutex<Half>,
+    writer: Mutex<Half>,
+    // Côté client, les trames envoyées sont masquées
+    client: bool,
+    // Trame close envoyée (par close() ou en réponse au pair) : plus rien ne part
+    closed: AtomicBool,
+}
+
+enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl WebSocket {
+    fn new(reader: Half, writer: Half, client: bool) -> Self {
+        Self { reader: Mutex::new(reader), writer: Mutex::new(writer), client, closed: AtomicBool::new(false) }
+    }
+
+    fn over_tcp(stream: TcpStream, client: bool) -> Result<Self, String> {
+        let reader = stream.try_clone().map_err(|e| e.to_string())?;
+        Ok(Self::new(Half::Tcp(reader), Half::Tcp(stream), client))
+    }
+
+    fn over_tls(stream: TlsStream) -> Self {
+        let shared = Arc::new(Mutex::new(stream));
+        Self::new(Half::Tls(shared.clone()), Half::Tls(shared), true)
+    }
+
+    fn write_frame(&self, opcode: u8, payload: &[u8]) -> Result<(), String> {
+        let mut frame = vec![0x80 | opcode];
+        let mask_bit = if self.client { 0x80 } else { 0 };
+        match payload.len() {
+            len @ 0..=125 => frame.push(mask_bit | len as u8),
+            len @ 126..=0xFFFF => {
+                frame.push(mask_bit | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            },
+            len => {
+                frame.push(mask_bit | 127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            },
+        }
+        if self.client {
+            let key: [u8; 4] = rand::random();
+            frame.extend_from_slice(&key);
+            frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ key[i % 4]));
+        } else {
+            frame.extend_from_slice(payload);
+        }
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_all(&frame).and_then(|_| writer.flush()).map_err(|e| e.to_string())
+    }
+
+    fn send(&self, opcode: u8, payload: &[u8]) -> Result<(), String> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err("WebSocket is closed".to_string());
+        }
+        self.write_frame(opcode, payload)
+    }
+
+    // Envoie la trame close une seule fois ; `code` vide pour répondre sans code
+    fn send_close(&self, code: &[u8]) -> Result<(), String> {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        self.write_frame(OP_CLOSE, code)
+    }
+
+    // Prochain message, ou None quand la connexion est fermée (trame close ou fin du flux)
+    fn recv(&self) -> Result<Option<Message>, String> {
+        let mut reader = self.reader.lock().unwrap();
+        // Message fragmenté en cours : (opcode de la première trame, données reçues)
+        let mut partial: Option<(u8, Vec<u8>)> = None;
+        loop {
+            let Some((fin, opcode, payload)) = read_frame(&mut *reader)? else {
+                self.closed.store(true, Ordering::SeqCst);
+                return Ok(None);
+            };
+            match opcode {
+                OP_PING => {
+                    // Après notre close, le pair n'attend plus de pong
+                    if !self.closed.load(Ordering::SeqCst) {
+                        self.write_frame(OP_PONG, &payload)?;
+                    }
+                    continue;
+                },
+                OP_PONG => continue,
+                OP_CLOSE => {
+                    // On renvoie le code du pair ; il a peut-être déjà coupé la connexion
+                    let _ = self.send_close(payload.get(..2).unwrap_or(&[]));
+                    return Ok(None);
+                },
+                OP_TEXT | OP_BINARY if partial.is_none() => partial = Some((opcode, payload)),
+                OP_CONTINUATION => match &mut partial {
+                    Some((_, data)) if data.len() + payload.len() <= WS_MAX_MESSAGE => data.extend_from_slice(&payload),
+                    Some(_) => return Err(format!("WebSocket message larger than {} bytes", WS_MAX_MESSAGE)),
+                    None => return Err("WebSocket protocol error: continuation frame without a message".to_string()),
+                },
+                OP_TEXT | OP_BINARY => return Err("WebSocket protocol error: new message inside a fragmented one".to_string()),
+                other => return Err(format!("WebSocket protocol error: unknown opcode {:#x}", other)),
+            }
+            if fin {
+                let (opcode, data) = partial.take().unwrap();
+                return Ok(Some(if opcode == OP_TEXT {
+                    Message::Text(String::from_utf8(data).map_err(|_| "WebSocket text message is not valid UTF-8".to_string())?)
+                } else {
+                    Message::Binary(data)
+                }));
+            }
+        }
+    }
+}
+
+impl Message {
+    fn into_value(self) -> Value {
+        match self {
+            Message::Text(text) => Value::String(text.into()),
+            Message::Binary(bytes) => Value::Bytes(Rc::new(RefCell::new(bytes))),
+        }
+    }
+}
+
+// (fin, opcode, données démasquées), ou None si le pair a fermé le flux entre deux trames
+fn read_frame(reader: &mut impl Read) -> Result<Option<(bool, u8, Vec<u8>)>, String> {
+    let mut head = [0u8; 2];
+    match reader.read_exact(&mut head) {
+        Ok(()) => {},
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::UnexpectedEof | std::io::ErrorKind::ConnectionReset) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    }
+    let fin = head[0] & 0x80 != 0;
+    let opcode = head[0] & 0x0F;
+    let masked = head[1] & 0x80 != 0;
+    let len = match head[1] & 0x7F {
+        126 => {
+            let mut n = [0u8; 2];
+            reader.read_exact(&mut n).map_err(|e| e.to_string())?;
+            u16::from_be_bytes(n) as u64
+        },
+        127 => {
+            let mut n = [0u8; 8];
+            reader.read_exact(&mut n).map_err(|e| e.to_string())?;
+            u64::from_be_bytes(n)
+        },
+        n => n as u64,
+    };
+    if opcode >= OP_CLOSE && (!fin || len > 125) {
+        return Err("WebSocket protocol error: invalid control frame".to_string());
+    }
+    if len > WS_MAX_MESSAGE as u64 {
+        return Err(format!("WebSocket message larger than {} bytes", WS_MAX_MESSAGE));
+    }
+    let mut key = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut key).map_err(|e| e.to_string())?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).map_err(|e| e.to_string())?;
+    if masked {
+        payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= key[i % 4]);
+    }
+    Ok(Some((fin, opcode, payload)))
+}
+
+// En-têtes HTTP de la poignée de main, lus octet par octet pour ne rien consommer des trames
+fn read_head(stream: &mut impl Read) -> Result<String, String> {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= WS_MAX_HEAD {
+            return Err("WebSocket handshake headers are too long".to_string());
+        }
+        match stream.read(&mut byte).map_err(|e| e.to_string())? {
+            0 => return Err("Connection closed during the WebSocket handshake".to_string()),
+            _ => head.push(byte[0]),
+        }
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+fn accept_key(key: &str) -> String {
+    general_purpose::STANDARD.encode(Sha1::digest(format!("{}{}", key, WS_GUID).as_bytes()))
+}
+
+fn client_handshake(stream: &mut (impl Read + Write), host: &str, path: &str) -> Result<(), String> {
+    let key = general_purpose::STANDARD.encode(rand::random::<[u8; 16]>());
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        path, host, key
+    );
+    stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+
+    let head = read_head(stream)?;
+    let status = head.lines().next().unwrap_or("");
+    if status.split_whitespace().nth(1) != Some("101") {
+        return Err(format!("WebSocket handshake refused by the server: {}", status));
+    }
+    if header(&head, "Sec-WebSocket-Accept") != Some(accept_key(&key).as_str()) {
+        return Err("WebSocket handshake failed: invalid Sec-WebSocket-Accept".to_string());
+    }
+    Ok(())
+}
+
+fn server_handshake(stream: &mut TcpStream) -> Result<(), String> {
+    let head = read_head(stream)?;
+    let upgrade = header(&head, "Upgrade").is_some_and(|u| u.eq_ignore_ascii_case("websocket"));
+    let key = header(&head, "Sec-WebSocket-Key").filter(|_| head.starts_with("GET ") && upgrade);
+    let Some(key) = key else {
+        let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        return Err("Not a WebSocket handshake".to_string());
+    };
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    );
+    stream.write_all(response.as_bytes()).map_err(|e| e.to_string())
+}
+
+// "ws://host:port/path" -> (TLS ?, hôte, port, en-tête Host, chemin)
+fn parse_ws_url(url: &str) -> Result<(bool, String, u16, String, String), String> {
+    let (tls, rest) = if let Some(rest) = url.strip_prefix("wss://") {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix("ws://") {
+        (false, rest)
+    } else {
+        return Err(format!("WebSocket URL must start with ws:// or wss://, got '{}'", url));
+    };
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(i) if rest[i..].starts_with('/') => (&rest[..i], rest[i..].to_string()),
+        Some(i) => (&rest[..i], format!("/{}", &rest[i..])),
+        None => (rest, "/".to_string()),
+    };
+    // [::1]:8080 : le port suit le crochet fermant
+    let port_sep = authority.rfind(':').filter(|i| !authority[*i..].contains(']'));
+    let (host, port) = match port_sep {
+        Some(i) => {
+            let port = authority[i + 1..].parse::<u16>().map_err(|_| format!("Invalid port in WebSocket URL '{}'", url))?;
+            (&authority[..i], port)
+        },
+        None => (authority, if tls { 443 } else { 80 }),
+    };
+    if host.is_empty() {
+        return Err(format!("Missing host in WebSocket URL '{}'", url));
+    }
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_string();
+    Ok((tls, host, port, authority.to_string(), path))
+}
+
+fn add_websocket(ws: WebSocket) -> usize {
+    let mut guard = STATE.lock().unwrap();
+    let state = &mut guard.0;
+
+    let id = state.next_id;
+    state.websockets.insert(id, Arc::new(ws));
+    state.next_id += 1;
+    id
+}
+
+// La connexion est clonée (Arc) : le verrou de STATE n'est pas gardé pendant un recv bloquant
+fn get_websocket(id: usize) -> Result<Arc<WebSocket>, String> {
+    let guard = STATE.lock().unwrap();
+    guard.0.websockets.get(&id).cloned().ok_or_else(|| "Invalid WebSocket ID".to_string())
+}
+
+fn accept_websocket(listener: &TcpListener) -> Result<WebSocket, String> {
+    let (mut stream, _addr) = listener.accept().map_err(|e| e.to_string())?;
+    server_handshake(&mut stream)?;
+    WebSocket::over_tcp(stream, false)
+}
+
+fn ws_connect(args: Vec<Value>) -> Result<Value, NativeError> {
+    let url = Args::new("WebSocket.connect(url)", &args).want_str(0)?;
+    let (tls, host, port, authority, path) = parse_ws_url(&url)?;
+
+    let mut stream = TcpStream::connect((host.as_str(), port)).map_err(|e| e.to_string())?;
+    let ws = if tls {
+        let connector = native_tls::TlsConnector::new().map_err(|e| e.to_string())?;
+        let mut stream = connector.connect(&host, stream).map_err(|e| e.to_string())?;
+        client_handshake(&mut stream, &authority, &path)?;
+        WebSocket::over_tls(stream)
+    } else {
+        client_handshake(&mut stream, &authority, &path)?;
+        WebSocket::over_tcp(stream, true)?
+    };
+    Ok(Value::Integer(add_websocket(ws) as i64))
+}
+
+// Attend un client sur un serveur de Socket.listen et fait la poignée de main côté serveur
+fn ws_accept(args: Vec<Value>) -> Result<Value, NativeError> {
+    let listener = clone_listener(Args::new("WebSocket.accept(server_id)", &args).want_int(0)? as usize)?;
+    Ok(Value::Integer(add_websocket(accept_websocket(&listener)?) as i64))
+}
+
+// Chaîne -> message texte, Bytes -> message binaire
+fn ws_send(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("ws.send(message)", &args);
+    let ws = get_websocket(args.want_int(0)? as usize)?;
+    match args.want(1)? {
+        Value::Bytes(bytes) => ws.send(OP_BINARY, &bytes.borrow())?,
+        Value::String(text) => ws.send(OP_TEXT, text.as_bytes())?,
+        other => ws.send(OP_TEXT, other.to_string().as_bytes())?,
+    }
+    Ok(Value::Null)
+}
+
+fn ws_recv(args: Vec<Value>) -> Result<Value, NativeError> {
+    let ws = get_websocket(Args::new("ws.recv()", &args).want_int(0)? as usize)?;
+    Ok(ws.recv()?.map(Message::into_value).unwrap_or(Value::Null))
+}
+
+fn ws_close(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("ws.close(code?)", &args);
+    let id = args.want_int(0)? as usize;
+    let code = args.opt_int(1, 1000)?;
+    let code = u16::try_from(code).map_err(|_| args.error(format!("invalid close code {}", code)))?;
+
+    let ws = STATE.lock().unwrap().0.websockets.remove(&id);
+    if let Some(ws) = ws {
+        // Le pair a peut-être déjà coupé la connexion
+        let _ = ws.send_close(&code.to_be_bytes());
+    }
+    Ok(Value::Null)
+}
+
+fn ws_accept_async(args: Vec<Value>) -> Result<Value, NativeError> {
+    let listener = clone_listener(Args::new("WebSocket.accept_async(server_id)", &args).want_int(0)? as usize)?;
+    Ok(jobs::spawn("ws_accept", move || {
+        Ok(Detached::Int(add_websocket(accept_websocket(&listener)?) as i64))
+    }))
+}
+
+fn ws_recv_async(args: Vec<Value>) -> Result<Value, NativeError> {
+    let ws = get_websocket(Args::new("ws.recv_async()", &args).want_int(0)? as usize)?;
+    Ok(jobs::spawn("ws_recv", move || Ok(match ws.recv()? {
+        Some(Message::Text(text)) => Detached::Str(text),
+        Some(Message::Binary(bytes)) => Detached::Bytes(bytes),
+        None => Detached::Null,
+    })))
+}
@@ -1,47 +1,232 @@
-use crate::ast::Value;
+use crate::ast::{DictMap, Value};
+use crate::vm::gc;
+use super::{Args, NativeError};
 use std::{collections::HashMap, io::{self, Write}};
 
 pub fn register(map: &mut HashMap<String, super::NativeFn>) {
     map.insert("io_clear".to_string(), io_clear);
     map.insert("io_write".to_string(), io_write);
     map.insert("sys_env".to_string(), sys_env);
+    map.insert("sys_set_env".to_string(), sys_set_env);
+    map.insert("sys_envs".to_string(), sys_envs);
+    map.insert("sys_load_dotenv".to_string(), sys_load_dotenv);
     map.insert("sys_fail".to_string(), sys_fail);
     map.insert("sys_exit".to_string(), sys_exit);
+    map.insert("sys_has_native".to_string(), sys_has_native);
+    map.insert("sys_gc".to_string(), sys_gc);
 }
 
-fn io_clear(_: Vec<Value>) -> Result<Value, String> {
+fn io_clear(_: Vec<Value>) -> Result<Value, NativeError> {
     // Petit hack cross-platform pour nettoyer le terminal
     print!("\x1B[2J\x1B[1;1H"); 
     io::stdout().flush().unwrap();
     Ok(Value::Null)
 }
 
-fn io_write(args: Vec<Value>) -> Result<Value, String> {
-    let s = args[0].as_str()?;
+fn io_write(args: Vec<Value>) -> Result<Value, NativeError> {
+    let s = Args::new("System.write(str)", &args).want_str(0)?;
     print!("{}", s); // Pas de println!
     std::io::stdout().flush().unwrap();
     Ok(Value::Null)
 }
 
-fn sys_env(args: Vec<Value>) -> Result<Value, String> {
-    if args.len() != 1 {
-        return Err("sys_env attend le nom de la variable".into());
-    }
-
-    let key = args[0].as_str()?;
+fn sys_env(args: Vec<Value>) -> Result<Value, NativeError> {
+    let key = Args::new("System.env(key)", &args).want_str(0)?;
 
     match std::env::var(key) {
-        Ok(val) => Ok(Value::String(val)),
+        Ok(val) => Ok(Value::String(val.into())),
         Err(_) => Ok(Value::Null)
     }
 }
 
-fn sys_fail(args: Vec<Value>) -> Result<Value, String> {
-    let msg = args.get(0).and_then(|v| v.as_str().ok()).unwrap_or("Assertion failed".to_string());
-    return Err(msg);
+// `null` supprime la variable. Les processus lancés ensuite (Process.run...) en héritent.
+fn sys_set_env(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("System.set_env(name, value)", &args);
+    let name = args.want_str(0)?;
+    check_env_name(&name).map_err(|e| args.error(e))?;
+    let value = match args.want(1)? {
+        Value::Null => None,
+        other => Some(other.to_string()),
+    };
+    match value {
+        Some(value) => {
+            if value.contains('\0') {
+                return Err(args.error(format!("the value of '{}' must not contain a NUL character", name)).into());
+            }
+            set_env(&name, &value);
+        },
+        // SAFETY : voir set_env
+        None => unsafe { std::env::remove_var(&name) },
+    }
+    Ok(Value::Null)
+}
+
+// std::env::set_var panique sur ces noms au lieu de retourner une erreur
+fn check_env_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains('=') || name.contains('\0') {
+        return Err(format!("invalid environment variable name '{}'", name));
+    }
+    Ok(())
+}
+
+fn set_env(name: &str, value: &str) {
+    // SAFETY : l'environnement n'est modifié que depuis le thread de la VM, à la demande du
+    // script ; les workers (Parallel, tâches async) ne le modifient jamais.
+    unsafe { std::env::set_var(name, value) }
+}
+
+// Toutes les variables, triées par nom. Celles qui ne sont pas de l'UTF-8 valide sont ignorées.
+fn sys_envs(_: Vec<Value>) -> Result<Value, NativeError> {
+    let mut vars: Vec<(String, String)> = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+        .collect();
+    vars.sort();
+    Ok(gc::dict(vars.into_iter().map(|(name, value)| (name.into(), Value::String(value.into()))).collect()))
+}
+
+// Charge un fichier .env dans l'environnement et retourne le dict des variables qu'il définit.
+// Une variable déjà présente dans l'environnement est gardée, sauf avec `overwrite`.
+fn sys_load_dotenv(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("System.load_dotenv(path = \".env\", overwrite = false)", &args);
+    args.at_most(2)?;
+    let path = args.opt_str(0, ".env")?;
+    let overwrite = args.opt_bool(1, false)?;
+    let text = std::fs::read_to_string(&path).map_err(|e| super::io_error(&e, &path))?;
+
+    let vars = parse_dotenv(&text).map_err(|e| format!("{}: {}", path, e))?;
+    let mut dict = DictMap::new();
+    for (name, value) in vars {
+        if overwrite || std::env::var_os(&name).is_none() {
+            set_env(&name, &value);
+        }
+        dict.insert(name.into(), Value::String(value.into()));
+    }
+    Ok(gc::dict(dict))
+}
+
+// Format .env usuel, une variable par ligne :
+//   NAME=value             espaces autour de la valeur retirés, ` # commentaire` en fin de ligne ignoré
+//   export NAME=value      `export` est accepté (fichier partagé avec un shell)
+//   NAME='texte brut'      rien n'est interprété
+//   NAME="a\nb ${OTHER}"   \n \t \" \\ interprétés, peut s'étendre sur plusieurs lignes
+// `${OTHER}` (guillemets doubles ou sans guillemets) est remplacé par une variable définie plus
+// haut dans le fichier, sinon par l'environnement, sinon par une chaîne vide.
+fn parse_dotenv(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    let mut lines = text.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").map(str::trim_start).unwrap_or(line);
+        let (name, raw) = line.split_once('=')
+            .ok_or_else(|| format!("line {}: expected NAME=value", line_number))?;
+        let name = name.trim();
+        let valid_name = !name.is_empty() && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        if !valid_name {
+            return Err(format!("line {}: invalid variable name '{}'", line_number, name));
+        }
+        let raw = raw.trim();
+
+        let value = if let Some(rest) = raw.strip_prefix('\'') {
+            let end = rest.find('\'').ok_or_else(|| format!("line {}: missing closing quote (')", line_number))?;
+            rest[..end].to_string()
+        } else if let Some(rest) = raw.strip_prefix('"') {
+            // Les lignes suivantes font partie de la valeur jusqu'au guillemet fermant
+            let mut quoted = rest.to_string();
+            let end = loop {
+                if let Some(end) = closing_quote(&quoted) {
+                    break end;
+                }
+                let (_, next) = lines.next()
+                    .ok_or_else(|| format!("line {}: missing closing quote (\")", line_number))?;
+                quoted.push('\n');
+                quoted.push_str(next);
+            };
+            expand(&unescape(&quoted[..end]), &vars)
+        } else {
+            let value = match raw.find(" #") {
+                Some(comment) => raw[..comment].trim_end(),
+                None => raw,
+            };
+            expand(value, &vars)
+        };
+
+        vars.retain(|(existing, _)| existing != name);
+        vars.push((name.to_string(), value));
+    }
+    Ok(vars)
+}
+
+// Position du premier guillemet non échappé
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {},
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn expand(text: &str, vars: &[(String, String)]) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else { break };
+        out.push_str(&rest[..start]);
+        let name = &rest[start + 2..start + 2 + len];
+        let value = vars.iter().rev().find(|(existing, _)| existing == name).map(|(_, value)| value.clone())
+            .or_else(|| std::env::var(name).ok())
+            .unwrap_or_default();
+        out.push_str(&value);
+        rest = &rest[start + 3 + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn sys_fail(args: Vec<Value>) -> Result<Value, NativeError> {
+    Err(Args::new("System.fail(msg?)", &args).opt_str(0, "Assertion failed")?.into())
+}
+
+fn sys_exit(args: Vec<Value>) -> Result<Value, NativeError> {
+    let code = Args::new("System.exit(code?)", &args).opt_int(0, 0)?;
+    std::process::exit(code as i32);
+}
+
+// Passe immédiate du ramasse-miettes des cycles : nombre d'objets libérés
+fn sys_gc(_: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Value::Integer(crate::vm::gc::collect() as i64))
 }
 
-fn sys_exit(args: Vec<Value>) -> Result<Value, String> {
-    let code = if args.is_empty() { 0 } else { args[0].as_int().unwrap_or(0) as i32 };
-    std::process::exit(code);
+fn sys_has_native(args: Vec<Value>) -> Result<Value, NativeError> {
+    let name = Args::new("System.has_native(name)", &args).want_str(0)?;
+    Ok(Value::Boolean(super::is_available(&name)))
 }
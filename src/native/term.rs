@@ -0,0 +1,103 @@
+use crate::ast::{DictMap, Value};
+use crate::vm::gc;
+use super::{Args, NativeError};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::time::Duration;
+use crossterm::{cursor, execute};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("term_raw_mode".to_string(), term_raw_mode);
+    map.insert("term_poll_event".to_string(), term_poll_event);
+    map.insert("term_size".to_string(), term_size);
+    map.insert("term_move_to".to_string(), term_move_to);
+}
+
+fn make_dict(entries: Vec<(&str, Value)>) -> Value {
+    let map: DictMap = entries.into_iter()
+        .map(|(k, v)| (k.into(), v))
+        .collect();
+    gc::dict(map)
+}
+
+fn term_raw_mode(args: Vec<Value>) -> Result<Value, NativeError> {
+    if Args::new("Term.raw_mode(enabled)", &args).want_bool(0)? {
+        terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+    } else {
+        terminal::disable_raw_mode().map_err(|e| e.to_string())?;
+    }
+
+    Ok(Value::Boolean(terminal::is_raw_mode_enabled().unwrap_or(false)))
+}
+
+fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        KeyCode::Enter => "enter".into(),
+        KeyCode::Esc => "esc".into(),
+        KeyCode::Backspace => "backspace".into(),
+        KeyCode::Tab | KeyCode::BackTab => "tab".into(),
+        KeyCode::Delete => "delete".into(),
+        KeyCode::Insert => "insert".into(),
+        KeyCode::Left => "left".into(),
+        KeyCode::Right => "right".into(),
+        KeyCode::Up => "up".into(),
+        KeyCode::Down => "down".into(),
+        KeyCode::Home => "home".into(),
+        KeyCode::End => "end".into(),
+        KeyCode::PageUp => "pageup".into(),
+        KeyCode::PageDown => "pagedown".into(),
+        _ => "unknown".into(),
+    }
+}
+
+// Attend un évènement pendant `timeout_ms` au plus.
+// Retourne un dict {type: "key", ...} / {type: "resize", ...}, ou null si rien n'est arrivé.
+fn term_poll_event(args: Vec<Value>) -> Result<Value, NativeError> {
+    let timeout_ms = Args::new("Term.poll_event(timeout_ms)", &args).want_int(0)?;
+    let timeout = Duration::from_millis(timeout_ms.max(0) as u64);
+
+    if !event::poll(timeout).map_err(|e| e.to_string())? {
+        return Ok(Value::Null);
+    }
+
+    match event::read().map_err(|e| e.to_string())? {
+        // Sous Windows on reçoit aussi les relâchements : on ne garde que les appuis
+        Event::Key(key) if key.kind != KeyEventKind::Release => Ok(make_dict(vec![
+            ("type", Value::String("key".into())),
+            ("key", Value::String(key_name(key.code).into())),
+            ("ctrl", Value::Boolean(key.modifiers.contains(KeyModifiers::CONTROL))),
+            ("alt", Value::Boolean(key.modifiers.contains(KeyModifiers::ALT))),
+            ("shift", Value::Boolean(key.modifiers.contains(KeyModifiers::SHIFT) || key.code == KeyCode::BackTab)),
+        ])),
+        Event::Resize(width, height) => Ok(make_dict(vec![
+            ("type", Value::String("resize".into())),
+            ("width", Value::Integer(width as i64)),
+            ("height", Value::Integer(height as i64)),
+        ])),
+        // Souris, focus, collage... ignorés pour l'instant
+        _ => Ok(Value::Null),
+    }
+}
+
+fn term_size(_: Vec<Value>) -> Result<Value, NativeError> {
+    let (width, height) = terminal::size().map_err(|e| e.to_string())?;
+    Ok(make_dict(vec![
+        ("width", Value::Integer(width as i64)),
+        ("height", Value::Integer(height as i64)),
+    ]))
+}
+
+fn term_move_to(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Term.move_to(x, y)", &args);
+    let x = args.want_int(0)?.clamp(0, u16::MAX as i64) as u16;
+    let y = args.want_int(1)?.clamp(0, u16::MAX as i64) as u16;
+
+    let mut out = io::stdout();
+    execute!(out, cursor::MoveTo(x, y)).map_err(|e| e.to_string())?;
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(Value::Null)
+}
@@ -0,0 +1,102 @@
+use crate::Value;
+use crate::ast::value::ClassData;
+use std::collections::HashMap;
+use std::rc::Rc;
+use super::{Args, NativeError};
+
+// Assertions de `aegis test` (utilisables dans n'importe quel script). Un échec est une erreur
+// ordinaire : il arrête le test, et un try/catch peut l'attraper.
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("assert_eq".to_string(), assert_eq);
+    map.insert("assert_true".to_string(), assert_true);
+    map.insert("assert_throws".to_string(), assert_throws_stub);
+}
+
+fn assert_eq(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("assert_eq(actual, expected, message?)", &args);
+    let (actual, expected) = (args.want(0)?, args.want(1)?);
+    let message = args.opt_str(2, "")?;
+    if actual != expected {
+        return Err(failure("assert_eq", format!("expected {}, got {}", show(expected), show(actual)), &message).into());
+    }
+    Ok(Value::Null)
+}
+
+fn assert_true(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("assert_true(condition, message?)", &args);
+    let condition = args.want_bool(0)?;
+    let message = args.opt_str(1, "")?;
+    if !condition {
+        return Err(failure("assert_true", "condition is false".to_string(), &message).into());
+    }
+    Ok(Value::Null)
+}
+
+// assert_throws doit appeler une fonction du script : l'appel est intercepté par la VM (call_value)
+fn assert_throws_stub(_args: Vec<Value>) -> Result<Value, NativeError> {
+    Err("assert_throws() can only be called by the VM".into())
+}
+
+/// Vérifie l'erreur levée par la fonction passée à `assert_throws(fn, expected?)` :
+/// `expected` est une classe d'erreur (l'erreur doit en être une instance) ou un texte que
+/// son message doit contenir. `thrown` vaut None si la fonction n'a pas échoué.
+pub(crate) fn check_thrown(expected: Option<&Value>, thrown: Option<&Value>) -> Result<(), String> {
+    let Some(thrown) = thrown else {
+        return Err(failure("assert_throws", "expected an error, but none was thrown".to_string(), ""));
+    };
+    match expected {
+        None | Some(Value::Null) => Ok(()),
+        Some(Value::String(text)) => {
+            let message = message_of(thrown);
+            if message.contains(text.as_str()) {
+                Ok(())
+            } else {
+                Err(failure("assert_throws", format!("expected an error containing {}, got {}", show(&Value::String(text.clone())), show(&Value::String(message.into()))), ""))
+            }
+        },
+        Some(Value::Class(class)) => {
+            if class_of(thrown).is_some_and(|c| c.is_subclass_of(class)) {
+                Ok(())
+            } else {
+                let got = class_of(thrown).map(|c| c.name.clone()).unwrap_or_else(|| thrown.type_name().to_string());
+                Err(failure("assert_throws", format!("expected {}, got {}: {}", class.name, got, message_of(thrown)), ""))
+            }
+        },
+        Some(other) => Err(format!("assert_throws(fn, expected?): argument 'expected' must be an error class or a string, got {}", other.type_name())),
+    }
+}
+
+fn class_of(value: &Value) -> Option<Rc<ClassData>> {
+    match value {
+        Value::Instance(instance) => Some(instance.borrow().class.clone()),
+        _ => None,
+    }
+}
+
+// Message d'une erreur levée : champ `message` d'une instance d'Error, sinon la valeur affichée
+fn message_of(value: &Value) -> String {
+    match value {
+        Value::Instance(instance) => match instance.borrow().get_field("message") {
+            Some(Value::String(ref message)) => message.to_string(),
+            _ => value.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
+
+// Les chaînes entre guillemets, pour distinguer "1" de 1
+fn show(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{:?}", s),
+        other => other.to_string(),
+    }
+}
+
+fn failure(assertion: &str, detail: String, message: &str) -> String {
+    if message.is_empty() {
+        format!("{} failed: {}", assertion, detail)
+    } else {
+        format!("{} failed: {} ({})", assertion, detail, message)
+    }
+}
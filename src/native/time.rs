@@ -1,12 +1,14 @@
 use crate::ast::Value;
+use super::{Args, NativeError};
 use std::{collections::HashMap, thread, time::{self, SystemTime, UNIX_EPOCH}};
 
 pub fn register(map: &mut HashMap<String, super::NativeFn>) {
     map.insert("time_now".to_string(), time_now);
     map.insert("time_sleep".to_string(), time_sleep);
+    map.insert("time_duration".to_string(), time_duration);
 }
 
-fn time_now(_: Vec<Value>) -> Result<Value, String> {
+fn time_now(_: Vec<Value>) -> Result<Value, NativeError> {
     let start = SystemTime::now();
     let since_the_epoch = start
         .duration_since(UNIX_EPOCH)
@@ -14,8 +16,19 @@ fn time_now(_: Vec<Value>) -> Result<Value, String> {
     Ok(Value::Integer(since_the_epoch.as_millis() as i64))
 }
 
-fn time_sleep(args: Vec<Value>) -> Result<Value, String> {
-    let ms = args[0].as_int()?;
-    thread::sleep(time::Duration::from_millis(ms as u64));
+fn time_sleep(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Time.sleep(ms)", &args);
+    // Des millisecondes, ou une durée : Time.sleep(Time.seconds(2))
+    let ms = match args.get(0) {
+        Some(Value::Duration(ms)) => *ms as f64,
+        _ => args.want_number(0)?,
+    };
+    thread::sleep(time::Duration::from_millis(ms.max(0.0) as u64));
     Ok(Value::Null)
 }
+
+// Durée d'un nombre de millisecondes (Time.seconds(5) appelle time_duration(5000))
+fn time_duration(args: Vec<Value>) -> Result<Value, NativeError> {
+    let ms = Args::new("Time.millis(n)", &args).want_number(0)?;
+    Ok(Value::Duration(super::date::millis(ms)?))
+}
@@ -0,0 +1,61 @@
+use crate::ast::Value;
+use super::{Args, NativeError};
+use super::json::{from_json, to_json};
+use std::collections::HashMap;
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("toml_parse".to_string(), toml_parse);
+    map.insert("toml_stringify".to_string(), toml_stringify);
+}
+
+fn toml_parse(args: Vec<Value>) -> Result<Value, NativeError> {
+    let text = Args::new("Toml.parse(str)", &args).want_str(0)?;
+    parse(&text).map_err(NativeError::from)
+}
+
+// Aussi utilisé par `import "x.toml" as x`
+pub(crate) fn parse(text: &str) -> Result<Value, String> {
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| format!("Invalid TOML: {}", e.message()))?;
+    Ok(from_json(toml_to_json(toml::Value::Table(table))))
+}
+
+// Les dates TOML n'ont pas toujours de fuseau (`1979-05-27`, `07:32:00`) : elles restent
+// des chaînes, à passer à Date.parse au besoin
+fn toml_to_json(v: toml::Value) -> serde_json::Value {
+    match v {
+        toml::Value::String(s) => serde_json::Value::String(s),
+        toml::Value::Integer(i) => serde_json::Value::from(i),
+        toml::Value::Float(f) => serde_json::Number::from_f64(f).map_or(serde_json::Value::Null, serde_json::Value::Number),
+        toml::Value::Boolean(b) => serde_json::Value::Bool(b),
+        toml::Value::Datetime(d) => serde_json::Value::String(d.to_string()),
+        toml::Value::Array(items) => serde_json::Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => {
+            serde_json::Value::Object(table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect())
+        },
+    }
+}
+
+// Le document est un dict ; TOML n'a pas de null
+fn toml_stringify(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Toml.stringify(dict)", &args);
+    args.want_dict(0)?;
+    let json = to_json(args.want(0)?, "TOML").map_err(|e| args.error(e))?;
+    if let Some(path) = find_null(&json, String::new()) {
+        return Err(args.error(format!("TOML has no null value (at '{}')", path)).into());
+    }
+    toml::to_string(&json).map(|s| Value::String(s.into())).map_err(|e| args.error(e).into())
+}
+
+// Chemin (`server.ports[1]`) du premier null, que le sérialiseur refuserait sans dire où
+fn find_null(json: &serde_json::Value, path: String) -> Option<String> {
+    match json {
+        serde_json::Value::Null => Some(path),
+        serde_json::Value::Array(items) => items.iter().enumerate()
+            .find_map(|(i, item)| find_null(item, format!("{}[{}]", path, i))),
+        serde_json::Value::Object(map) => map.iter().find_map(|(key, item)| {
+            let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+            find_null(item, path)
+        }),
+        _ => None,
+    }
+}
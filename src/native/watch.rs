@@ -0,0 +1,136 @@
+use crate::ast::{DictMap, Value};
+use crate::vm::gc;
+use super::{Args, NativeError};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+// Les watchers de `notify` tournent sur leur propre thread et envoient leurs événements dans
+// un canal commun ; ils ne sont livrés aux callbacks que quand le script appelle Fs.poll().
+struct Watch {
+    // Gardé pour que la surveillance continue : la lâcher l'arrête
+    _watcher: RecommendedWatcher,
+    callback: Value,
+}
+
+struct WatchState {
+    watches: HashMap<i64, Watch>,
+    next_id: i64,
+    sender: Sender<(i64, notify::Result<Event>)>,
+    receiver: Receiver<(i64, notify::Result<Event>)>,
+}
+
+thread_local! {
+    static STATE: RefCell<WatchState> = RefCell::new({
+        let (sender, receiver) = mpsc::channel();
+        WatchState { watches: HashMap::new(), next_id: 0, sender, receiver }
+    });
+}
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("fs_watch".to_string(), fs_watch);
+    map.insert("fs_unwatch".to_string(), fs_unwatch);
+    map.insert("fs_watch_count".to_string(), fs_watch_count);
+    map.insert("fs_watch_poll".to_string(), fs_watch_poll);
+}
+
+fn fs_watch(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Fs.watch(path, callback)", &args);
+    let path = args.want_str(0)?;
+    let callback = match args.want(1)? {
+        f @ (Value::Function(_) | Value::Native(_)) => f.clone(),
+        _ => return Err(args.expected(1, "a function").into()),
+    };
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.next_id += 1;
+        let id = state.next_id;
+
+        let sender = state.sender.clone();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send((id, event));
+        }).map_err(|e| format!("Fs.watch: {}", e))?;
+        watcher.watch(std::path::Path::new(&path), RecursiveMode::Recursive)
+            .map_err(|e| format!("Fs.watch: cannot watch '{}': {}", path, e))?;
+
+        state.watches.insert(id, Watch { _watcher: watcher, callback });
+        Ok(Value::Integer(id))
+    })
+}
+
+fn fs_unwatch(args: Vec<Value>) -> Result<Value, NativeError> {
+    let id = Args::new("watcher.close(id)", &args).want_int(0)?;
+    let removed = STATE.with(|state| state.borrow_mut().watches.remove(&id).is_some());
+    Ok(Value::Boolean(removed))
+}
+
+fn fs_watch_count(_: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Value::Integer(STATE.with(|state| state.borrow().watches.len()) as i64))
+}
+
+// Traduit un événement `notify` en (type, chemin). Un renommage devient la suppression de
+// l'ancien nom et la création du nouveau ; les simples lectures sont ignorées.
+fn changes(event: Event) -> Vec<(&'static str, PathBuf)> {
+    let kinds: Vec<&'static str> = match event.kind {
+        EventKind::Create(_) => vec!["create"],
+        EventKind::Remove(_) => vec!["delete"],
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => vec!["delete"],
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => vec!["create"],
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => vec!["delete", "create"],
+        EventKind::Modify(_) | EventKind::Any | EventKind::Other => vec!["modify"],
+        EventKind::Access(_) => vec![],
+    };
+    // Both : paths = [ancien, nouveau] ; sinon le même type pour chaque chemin
+    if kinds.len() == event.paths.len() {
+        kinds.into_iter().zip(event.paths).collect()
+    } else {
+        kinds.first().map(|kind| event.paths.into_iter().map(|p| (*kind, p)).collect()).unwrap_or_default()
+    }
+}
+
+// Attend au plus `timeout_ms` le premier événement puis prend tous ceux déjà arrivés.
+// Retourne une liste de [callback, {"kind", "path"}] ; les doublons consécutifs d'un même
+// fichier (une écriture en produit souvent plusieurs) sont fusionnés.
+fn fs_watch_poll(args: Vec<Value>) -> Result<Value, NativeError> {
+    let timeout = Args::new("Fs.poll(timeout_ms)", &args).want_int(0)?.max(0) as u64;
+
+    STATE.with(|state| {
+        let state = state.borrow();
+        if state.watches.is_empty() {
+            return Ok(gc::list(Vec::new()));
+        }
+
+        let mut received = Vec::new();
+        match state.receiver.recv_timeout(Duration::from_millis(timeout)) {
+            Ok(first) => received.push(first),
+            Err(RecvTimeoutError::Timeout) => return Ok(gc::list(Vec::new())),
+            Err(RecvTimeoutError::Disconnected) => return Err("Fs.poll: watcher channel closed".into()),
+        }
+        received.extend(state.receiver.try_iter());
+
+        let mut seen: Vec<(i64, &'static str, PathBuf)> = Vec::new();
+        let mut entries = Vec::new();
+        for (id, event) in received {
+            // Watcher fermé entre-temps : ses derniers événements sont perdus
+            let Some(watch) = state.watches.get(&id) else { continue };
+            let event = event.map_err(|e| format!("Fs.watch: {}", e))?;
+
+            for (kind, path) in changes(event) {
+                if seen.last().is_some_and(|(i, k, p)| *i == id && *k == kind && *p == path) {
+                    continue;
+                }
+                let mut info = DictMap::new();
+                info.insert("kind".into(), Value::String(kind.to_string().into()));
+                info.insert("path".into(), Value::String(path.to_string_lossy().to_string().into()));
+                entries.push(gc::list(vec![watch.callback.clone(), gc::dict(info)]));
+                seen.push((id, kind, path));
+            }
+        }
+        Ok(gc::list(entries))
+    })
+}
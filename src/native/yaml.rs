@@ -0,0 +1,27 @@
+use crate::ast::Value;
+use super::{Args, NativeError};
+use super::json::{from_json, to_json};
+use std::collections::HashMap;
+
+pub fn register(map: &mut HashMap<String, super::NativeFn>) {
+    map.insert("yaml_parse".to_string(), yaml_parse);
+    map.insert("yaml_stringify".to_string(), yaml_stringify);
+}
+
+fn yaml_parse(args: Vec<Value>) -> Result<Value, NativeError> {
+    let text = Args::new("Yaml.parse(str)", &args).want_str(0)?;
+    parse(&text).map_err(NativeError::from)
+}
+
+// Aussi utilisé par `import "x.yaml" as x`. Un document vide donne null ; les clés sont des
+// chaînes (`1: a` donne la clé "1")
+pub(crate) fn parse(text: &str) -> Result<Value, String> {
+    let json: serde_json::Value = serde_yaml::from_str(text).map_err(|e| format!("Invalid YAML: {}", e))?;
+    Ok(from_json(json))
+}
+
+fn yaml_stringify(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("Yaml.stringify(value)", &args);
+    let json = to_json(args.want(0)?, "YAML").map_err(|e| args.error(e))?;
+    serde_yaml::to_string(&json).map(|s| Value::String(s.into())).map_err(|e| args.error(e).into())
+}
@@ -15,10 +15,12 @@ pub enum OpCode {
     Sub,
     Mul,
     Div,
+    FloatDiv, // Division "vraie" (--lang-version 2) : int / int donne un Float
 
     // Math & Logic
     Modulo,
     NotEqual, Equal, Greater, GreaterEqual, Less, LessEqual,
+    Same, NotSame, // Identité (=== / !==) : même objet, ou même valeur et même type pour les scalaires
     Not,
     BitAnd, BitOr, BitXor, ShiftLeft, ShiftRight,
     
@@ -59,9 +61,32 @@ pub enum OpCode {
     PopExcept,   // Fin du bloc Try avec succès (retire le handler)
     Throw,
 
-    Import,
+    Import, // Opérandes : chemin, puis 1 si le module est isolé (`as`, `from`), 0 sinon
+    ImportName, // Membre exporté du module au sommet de la pile (from ... import), sans le retirer
     CheckType,
-    MakeRange
+    MakeRange,
+
+    // Variantes à opérande 32 bits, utilisées quand un saut dépasse 65535 octets (code généré)
+    JumpIfFalseLong,
+    JumpLong,
+    LoopLong,
+    SetupExceptLong,
+
+    Defer, // Enregistre la closure au sommet de la pile sur la frame courante
+    WithEnter, // Vérifie la ressource au sommet de la pile et l'ajoute aux ressources ouvertes de la frame
+    WithExit, // Ferme la dernière ressource ouverte (__exit__() ou close())
+
+    Await, // Remplace la tâche au sommet de la pile par son résultat, en laissant tourner les autres tâches d'ici là
+    CatchType, // Opérande : type attendu. Pousse vrai si l'erreur au sommet de la pile lui correspond (clause `catch (e: T)`)
+
+    GetIndex, // Pile : objet, index -> élément (l[i], d["k"], s[i])
+    SetIndex, // Pile : objet, index, valeur -> valeur (a[i] = v)
+    Dup2, // Pile : a, b -> a, b, a, b (objet et index de a[i] += v)
+    Concat, // Pile : a, b -> texte de a suivi du texte de b (interpolation, permise en mode strict)
+    BindSelf, // operand: const_idx (nom). Rend la closure au sommet de la pile visible sous ce nom dans son propre corps
+    SetFreeVar, // operand: const_idx (nom). Affecte une variable capturée de la fonction englobante (dépile la valeur)
+    TailCall, // operand: arg_count. Call suivi d'un Return : l'appelé réutilise la frame courante quand c'est possible
+    FloorDiv, // a ~/ b : quotient arrondi vers -inf (int si les deux opérandes sont des int)
 }
 
 impl From<u8> for OpCode {
@@ -1,16 +1,28 @@
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File};
-use std::io;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use walkdir::WalkDir;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use reqwest::blocking::{Client, multipart};
+use semver::{Version, VersionReq};
+use sha2::{Digest, Sha256};
 use std::env;
 
 // Import toml_edit for safe TOML manipulation
 use toml_edit::{DocumentMut, value, Item, Table};
 
 const REGISTRY_URL: &str = "https://aegis.foxvoid.com/api";
+// Surcharge de l'adresse du registre (registre privé, tests)
+const REGISTRY_URL_ENV: &str = "AEGIS_REGISTRY_URL";
+// Largeur maximale de la colonne description dans `aegis search`
+const DESCRIPTION_WIDTH: usize = 50;
+// Versions exactes installées, générées par `aegis add` / `aegis install`
+const LOCKFILE: &str = "aegis.lock";
+const LOCKFILE_FORMAT: u32 = 1;
+// Au-delà, la résolution oscille entre plusieurs choix : on abandonne
+const MAX_RESOLVE_ROUNDS: usize = 100;
 
 #[derive(Deserialize)]
 struct CargoPackage {
@@ -22,10 +34,66 @@ struct CargoManifest {
     package: CargoPackage,
 }
 
-#[derive(Deserialize, Debug)]
-struct PackageInfo {
+// Une version publiée d'un paquet (/packages/<nom>/versions/)
+#[derive(Deserialize, Debug, Clone)]
+struct Release {
     version: String,
-    url: String, 
+    url: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ReleasesResponse {
+    Page { results: Vec<Release> },
+    List(Vec<Release>),
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Lockfile {
+    version: u32,
+    #[serde(default, rename = "package")]
+    packages: Vec<LockedPackage>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    url: String,
+    // "sha256:<hex>" de l'archive téléchargée
+    checksum: String,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+// Résultat de /packages/search/ (liste simple ou page { results: [...] })
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SearchResponse {
+    Page { results: Vec<SearchResult> },
+    List(Vec<SearchResult>),
+}
+
+#[derive(Deserialize, Serialize)]
+struct SearchResult {
+    name: String,
+    version: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    downloads: u64,
+    // Cibles publiées ; aucune : paquet source, utilisable partout
+    #[serde(default)]
+    platforms: Vec<Platform>,
+    // Calculé ici pour la machine courante
+    #[serde(default, skip_deserializing)]
+    compatible: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Platform {
+    os: String,
+    architecture: String,
 }
 
 #[derive(Deserialize)]
@@ -42,6 +110,10 @@ struct ProjectInfo {
 
 // --- UTILS ---
 
+fn registry_url() -> String {
+    env::var(REGISTRY_URL_ENV).unwrap_or_else(|_| REGISTRY_URL.to_string())
+}
+
 fn get_credentials_path() -> PathBuf {
     dirs::home_dir().unwrap().join(".aegis").join("credentials")
 }
@@ -86,7 +158,7 @@ fn find_library_in_dir(dir: &Path) -> Option<PathBuf> {
 }
 
 // --- UPDATED FUNCTION USING TOML_EDIT ---
-fn update_toml_dependency(name: &str, _path: &str) -> Result<(), String> {
+fn update_toml_dependency(name: &str, requirement: &str) -> Result<(), String> {
     let toml_path = "aegis.toml";
     
     // 1. Read existing content or create empty if missing
@@ -105,7 +177,7 @@ fn update_toml_dependency(name: &str, _path: &str) -> Result<(), String> {
 
     // 4. Add or update the dependency
     // We strictly use `doc["dependencies"]` now that we know it exists/is created
-    doc["dependencies"][name] = value("*");
+    doc["dependencies"][name] = value(requirement);
 
     // 5. Write back to file
     fs::write(toml_path, doc.to_string()).map_err(|e| e.to_string())?;
@@ -225,69 +297,336 @@ fn build_native_package(aegis_project_name: &str) -> Result<String, String> {
     Ok(dst_filename)
 }
 
-// --- PUBLIC COMMANDS ---
+// --- DEPENDENCIES ---
+
+// Choisit une version par paquet : celle de aegis.lock si elle satisfait encore toutes les
+// contraintes (aegis.toml et dépendances des versions retenues), sinon la plus récente qui les
+// satisfait, jusqu'à ce que le choix ne bouge plus.
+// Pas de retour arrière : un conflit est signalé tel quel.
+struct Resolver {
+    client: Client,
+    // Versions verrouillées : `aegis add` ne met pas à jour le reste du projet
+    locked: HashMap<String, Version>,
+    // Versions publiées pour cette machine, de la plus récente à la plus ancienne
+    releases: HashMap<String, Vec<(Version, Release)>>,
+    // Les dépendances d'une version se lisent dans le aegis.toml de son archive
+    archives: HashMap<(String, Version), Vec<u8>>,
+}
 
-pub fn login(token: &str) -> Result<(), String> {
-    let cred_path = get_credentials_path();
-    if let Some(parent) = cred_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+impl Resolver {
+    fn new() -> Self {
+        Resolver { client: Client::new(), locked: HashMap::new(), releases: HashMap::new(), archives: HashMap::new() }
     }
-    fs::write(&cred_path, token).map_err(|e| format!("Error writing token: {}", e))?;
-    println!("✅ Token saved in {:?}", cred_path);
+
+    // Part des versions de `lock`, sauf pour `except` (le paquet qu'on ajoute ou met à jour)
+    fn prefer_locked(&mut self, lock: &Lockfile, except: &str) {
+        self.locked = lock.packages.iter()
+            .filter(|package| package.name != except)
+            .filter_map(|package| Some((package.name.clone(), Version::parse(&package.version).ok()?)))
+            .collect();
+    }
+
+    fn resolve(&mut self, root: &[(String, VersionReq)]) -> Result<BTreeMap<String, Version>, String> {
+        let mut picked: BTreeMap<String, Version> = BTreeMap::new();
+
+        for _ in 0..MAX_RESOLVE_ROUNDS {
+            // Contraintes par paquet, avec leur origine pour les messages de conflit
+            let mut constraints: BTreeMap<String, Vec<(VersionReq, String)>> = BTreeMap::new();
+            for (name, req) in root {
+                constraints.entry(name.clone()).or_default().push((req.clone(), "aegis.toml".to_string()));
+            }
+            for (name, version) in &picked {
+                for (dep, req) in self.dependencies_of(name, version)? {
+                    constraints.entry(dep).or_default().push((req, format!("{} {}", name, version)));
+                }
+            }
+
+            let mut next = BTreeMap::new();
+            for (name, reqs) in &constraints {
+                let satisfies = |version: &Version| reqs.iter().all(|(req, _)| req.matches(version));
+                let locked = self.locked.get(name).cloned();
+                let releases = self.releases(name)?;
+                let version = locked
+                    .filter(|locked| satisfies(locked) && releases.iter().any(|(version, _)| version == locked))
+                    .or_else(|| releases.iter().map(|(version, _)| version).find(|version| satisfies(version)).cloned())
+                    .ok_or_else(|| conflict(name, reqs, releases))?;
+                next.insert(name.clone(), version);
+            }
+
+            // On oublie les paquets que plus rien n'importe
+            let mut reachable = BTreeMap::new();
+            let mut queue: Vec<String> = root.iter().map(|(name, _)| name.clone()).collect();
+            while let Some(name) = queue.pop() {
+                if reachable.contains_key(&name) { continue; }
+                let Some(version) = next.get(&name).cloned() else { continue };
+                queue.extend(self.dependencies_of(&name, &version)?.into_iter().map(|(dep, _)| dep));
+                reachable.insert(name, version);
+            }
+
+            if reachable == picked {
+                return Ok(picked);
+            }
+            picked = reachable;
+        }
+        Err(format!("Could not settle dependency versions after {} rounds", MAX_RESOLVE_ROUNDS))
+    }
+
+    fn releases(&mut self, name: &str) -> Result<&[(Version, Release)], String> {
+        if !self.releases.contains_key(name) {
+            let (os, arch) = get_system_info();
+            println!("🔍 Searching for {} ({}/{})...", name, os, arch);
+
+            let url = format!("{}/packages/{}/versions/?os={}&architecture={}", registry_url(), name, os, arch);
+            let resp = self.client.get(&url).send().map_err(|e| format!("Network error: {}", e))?;
+            if !resp.status().is_success() {
+                return Err(format!("Package '{}' not found or server error ({})", name, resp.status()));
+            }
+            let releases = match resp.json().map_err(|e| format!("JSON Error: {}", e))? {
+                ReleasesResponse::Page { results } | ReleasesResponse::List(results) => results,
+            };
+
+            // Les versions qui ne suivent pas semver ne peuvent satisfaire aucune contrainte
+            let mut releases: Vec<(Version, Release)> = releases.into_iter()
+                .filter_map(|release| Version::parse(&release.version).ok().map(|version| (version, release)))
+                .collect();
+            releases.sort_by(|a, b| b.0.cmp(&a.0));
+            self.releases.insert(name.to_string(), releases);
+        }
+        Ok(&self.releases[name])
+    }
+
+    fn release(&self, name: &str, version: &Version) -> Result<&Release, String> {
+        self.releases.get(name)
+            .and_then(|releases| releases.iter().find(|(v, _)| v == version))
+            .map(|(_, release)| release)
+            .ok_or_else(|| format!("Unknown version {} of '{}'", version, name))
+    }
+
+    fn archive(&mut self, name: &str, version: &Version) -> Result<Vec<u8>, String> {
+        let key = (name.to_string(), version.clone());
+        if !self.archives.contains_key(&key) {
+            let url = self.release(name, version)?.url.clone();
+            println!("⬇️  Downloading {} {}...", name, version);
+            let archive = download(&self.client, &url)?;
+            self.archives.insert(key.clone(), archive);
+        }
+        Ok(self.archives[&key].clone())
+    }
+
+    fn dependencies_of(&mut self, name: &str, version: &Version) -> Result<Vec<(String, VersionReq)>, String> {
+        let archive = self.archive(name, version)?;
+        let mut zip = zip::ZipArchive::new(io::Cursor::new(archive))
+            .map_err(|e| format!("Invalid archive for {} {}: {}", name, version, e))?;
+        // Un paquet sans aegis.toml n'a pas de dépendances
+        let Ok(mut manifest) = zip.by_name("aegis.toml") else { return Ok(Vec::new()) };
+        let mut content = String::new();
+        manifest.read_to_string(&mut content).map_err(|e| e.to_string())?;
+        read_dependencies(&content, &format!("{} {}", name, version))
+    }
+
+    fn lockfile(&mut self, picked: &BTreeMap<String, Version>) -> Result<Lockfile, String> {
+        let mut packages = Vec::new();
+        for (name, version) in picked {
+            let archive = self.archive(name, version)?;
+            let mut dependencies: Vec<String> = self.dependencies_of(name, version)?.into_iter().map(|(dep, _)| dep).collect();
+            dependencies.sort();
+            dependencies.dedup();
+            packages.push(LockedPackage {
+                name: name.clone(),
+                version: version.to_string(),
+                url: self.release(name, version)?.url.clone(),
+                checksum: sha256(&archive),
+                dependencies,
+            });
+        }
+        Ok(Lockfile { version: LOCKFILE_FORMAT, packages })
+    }
+}
+
+fn conflict(name: &str, reqs: &[(VersionReq, String)], releases: &[(Version, Release)]) -> String {
+    let wanted: Vec<String> = reqs.iter().map(|(req, from)| format!("{} (from {})", req, from)).collect();
+    let available: Vec<String> = releases.iter().map(|(version, _)| version.to_string()).collect();
+    format!(
+        "No version of '{}' satisfies {} (available: {})",
+        name,
+        wanted.join(" and "),
+        if available.is_empty() { "none".to_string() } else { available.join(", ") }
+    )
+}
+
+fn parse_requirement(req: &str, name: &str, origin: &str) -> Result<VersionReq, String> {
+    VersionReq::parse(req).map_err(|e| format!("Invalid version requirement '{}' for {} in {}: {}", req, name, origin, e))
+}
+
+// Section [dependencies] d'un aegis.toml : nom = "exigence semver"
+fn read_dependencies(content: &str, origin: &str) -> Result<Vec<(String, VersionReq)>, String> {
+    let config = content.parse::<toml::Table>().map_err(|e| format!("Failed to parse {}: {}", origin, e))?;
+    let Some(deps) = config.get("dependencies").and_then(|deps| deps.as_table()) else { return Ok(Vec::new()) };
+    deps.iter().map(|(name, req)| {
+        let req = req.as_str().ok_or_else(|| format!("Dependency {} in {} must be a version string", name, origin))?;
+        Ok((name.clone(), parse_requirement(req, name, origin)?))
+    }).collect()
+}
+
+fn read_lockfile() -> Result<Option<Lockfile>, String> {
+    let Ok(content) = fs::read_to_string(LOCKFILE) else { return Ok(None) };
+    let lock: Lockfile = toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", LOCKFILE, e))?;
+    if lock.version != LOCKFILE_FORMAT {
+        return Err(format!("Unsupported {} format {} (expected {})", LOCKFILE, lock.version, LOCKFILE_FORMAT));
+    }
+    Ok(Some(lock))
+}
+
+fn write_lockfile(lock: &Lockfile) -> Result<(), String> {
+    let body = toml::to_string(lock).map_err(|e| e.to_string())?;
+    fs::write(LOCKFILE, format!("# Generated by aegis. Do not edit by hand.\n{}", body)).map_err(|e| e.to_string())?;
+    println!("🔒 {} written ({} package(s))", LOCKFILE, lock.packages.len());
     Ok(())
 }
 
-pub fn install(name: &str, _version: Option<String>) -> Result<(), String> {
-    let (os, arch) = get_system_info();
-    
-    let url = format!("{}/packages/{}/latest/?os={}&architecture={}", REGISTRY_URL, name, os, arch);
-    println!("🔍 Searching for {} ({}/{})...", name, os, arch);
+// `aegis install` refuse un lockfile qui ne respecte plus aegis.toml plutôt que de le changer en silence
+fn check_lockfile(lock: &Lockfile, root: &[(String, VersionReq)]) -> Result<(), String> {
+    for (name, req) in root {
+        let locked = lock.packages.iter().find(|p| &p.name == name)
+            .and_then(|p| Version::parse(&p.version).ok());
+        match locked {
+            Some(version) if req.matches(&version) => {},
+            Some(version) => return Err(format!(
+                "{} is out of date: aegis.toml requires {} {} but {} is locked. Run 'aegis add {}' to update it.",
+                LOCKFILE, name, req, version, name
+            )),
+            None => return Err(format!(
+                "{} is out of date: {} is missing. Run 'aegis add {}' to update it.",
+                LOCKFILE, name, name
+            )),
+        }
+    }
+    Ok(())
+}
 
-    let client = Client::new();
-    let resp = client.get(&url).send().map_err(|e| format!("Network error: {}", e))?;
+// Les paquets que le nouveau lockfile ne contient plus quittent packages/ (un dossier que
+// aegis.lock n'a jamais listé, copié à la main, reste en place)
+fn prune_packages(previous: &Lockfile, lock: &Lockfile) -> Result<(), String> {
+    for package in &previous.packages {
+        let dir = Path::new("packages").join(&package.name);
+        if lock.packages.iter().all(|p| p.name != package.name) && dir.exists() {
+            fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+            println!("🗑️  Removed {} {} (no longer needed)", package.name, package.version);
+        }
+    }
+    Ok(())
+}
 
+fn download(client: &Client, url: &str) -> Result<Vec<u8>, String> {
+    let resp = client.get(url).send().map_err(|e| format!("Network error: {}", e))?;
     if !resp.status().is_success() {
-        return Err(format!("Package not found or server error ({})", resp.status()));
+        return Err(format!("Download of {} failed ({})", url, resp.status()));
     }
+    Ok(resp.bytes().map_err(|e| e.to_string())?.to_vec())
+}
 
-    let info: PackageInfo = resp.json().map_err(|e| format!("JSON Error: {}", e))?;
-    println!("⬇️  Downloading version {}...", info.version);
-
-    let zip_resp = client.get(&info.url).send().map_err(|e| e.to_string())?;
-    let zip_bytes = zip_resp.bytes().map_err(|e| e.to_string())?;
+fn sha256(data: &[u8]) -> String {
+    let digest = Sha256::digest(data);
+    format!("sha256:{}", digest.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
 
-    let packages_dir = Path::new("packages").join(name);
+fn extract_package(package: &LockedPackage, archive: &[u8]) -> Result<(), String> {
+    let packages_dir = Path::new("packages").join(&package.name);
     if packages_dir.exists() {
         fs::remove_dir_all(&packages_dir).map_err(|e| e.to_string())?;
     }
     fs::create_dir_all(&packages_dir).map_err(|e| e.to_string())?;
 
-    let reader = std::io::Cursor::new(zip_bytes);
-    let mut zip = zip::ZipArchive::new(reader).map_err(|e| e.to_string())?;
+    crate::native::compress::extract_archive(io::Cursor::new(archive), &packages_dir)?;
+
+    if find_library_in_dir(&packages_dir).is_some() {
+        println!("✅ Native package {} {} installed successfully!", package.name, package.version);
+    } else {
+        println!("✅ Source package {} {} installed successfully!", package.name, package.version);
+    }
+    Ok(())
+}
+
+// --- PUBLIC COMMANDS ---
 
-    for i in 0..zip.len() {
-        let mut file = zip.by_index(i).unwrap();
-        let outpath = packages_dir.join(file.mangled_name());
+pub fn login(token: &str) -> Result<(), String> {
+    let cred_path = get_credentials_path();
+    if let Some(parent) = cred_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&cred_path, token).map_err(|e| format!("Error writing token: {}", e))?;
+    println!("✅ Token saved in {:?}", cred_path);
+    Ok(())
+}
 
-        if file.name().ends_with('/') {
-            fs::create_dir_all(&outpath).unwrap();
-        } else {
-            if let Some(p) = outpath.parent() {
-                if !p.exists() { fs::create_dir_all(p).unwrap(); }
-            }
-            let mut outfile = File::create(&outpath).unwrap();
-            io::copy(&mut file, &mut outfile).unwrap();
+pub fn add(name: &str, version: Option<String>) -> Result<(), String> {
+    let requested = match &version {
+        Some(req) => parse_requirement(req, name, "the command line")?,
+        None => VersionReq::STAR,
+    };
+
+    let mut root = read_dependencies(&fs::read_to_string("aegis.toml").unwrap_or_default(), "aegis.toml")?;
+    root.retain(|(dep, _)| dep != name);
+    root.push((name.to_string(), requested));
+
+    let previous = read_lockfile()?.unwrap_or_default();
+    let mut resolver = Resolver::new();
+    resolver.prefer_locked(&previous, name);
+    let picked = resolver.resolve(&root)?;
+
+    // Sans version demandée, on retient la version choisie : `aegis add glfw` écrit glfw = "^1.2.0"
+    let requirement = version.unwrap_or_else(|| format!("^{}", picked[name]));
+    update_toml_dependency(name, &requirement)?;
+
+    let lock = resolver.lockfile(&picked)?;
+    for package in &lock.packages {
+        let unchanged = previous.packages.contains(package) && Path::new("packages").join(&package.name).exists();
+        if !unchanged {
+            let archive = resolver.archive(&package.name, &Version::parse(&package.version).map_err(|e| e.to_string())?)?;
+            extract_package(package, &archive)?;
         }
     }
+    prune_packages(&previous, &lock)?;
+    write_lockfile(&lock)
+}
 
-    if let Some(lib_path) = find_library_in_dir(&packages_dir) {
-        update_toml_dependency(name, lib_path.to_str().unwrap())?;
-        println!("✅ Native package {} installed successfully!", name);
-    } else {
-        update_toml_dependency(name, "")?;
-        println!("✅ Source package {} installed successfully!", name);
+// Installe exactement les versions de aegis.lock (le crée d'abord s'il n'existe pas)
+pub fn install() -> Result<(), String> {
+    let root = read_dependencies(&fs::read_to_string("aegis.toml").unwrap_or_default(), "aegis.toml")?;
+
+    let lock = match read_lockfile()? {
+        Some(lock) => {
+            check_lockfile(&lock, &root)?;
+            lock
+        },
+        None => {
+            println!("🔒 No {} found, resolving dependencies...", LOCKFILE);
+            let mut resolver = Resolver::new();
+            let picked = resolver.resolve(&root)?;
+            let lock = resolver.lockfile(&picked)?;
+            write_lockfile(&lock)?;
+            lock
+        },
+    };
+
+    if lock.packages.is_empty() {
+        println!("No dependencies to install.");
+        return Ok(());
+    }
+
+    let client = Client::new();
+    for package in &lock.packages {
+        println!("⬇️  Downloading {} {}...", package.name, package.version);
+        let archive = download(&client, &package.url)?;
+        let checksum = sha256(&archive);
+        if checksum != package.checksum {
+            return Err(format!(
+                "Checksum mismatch for {} {}: {} expects {}, downloaded archive is {}",
+                package.name, package.version, LOCKFILE, package.checksum, checksum
+            ));
+        }
+        extract_package(package, &archive)?;
     }
-    
     Ok(())
 }
 
@@ -322,7 +661,7 @@ pub fn publish(mut target_os: Option<String>, mut target_arch: Option<String>) -
     let user_excludes = manifest.project.exclude.unwrap_or_default();
     create_zip_of_directory(Path::new("."), zip_path, &user_excludes)?;
 
-    let url = format!("{}/packages/publish/", REGISTRY_URL);
+    let url = format!("{}/packages/publish/", registry_url());
 
     let form = multipart::Form::new()
         .text("name", manifest.project.name.to_string())
@@ -352,3 +691,75 @@ pub fn publish(mut target_os: Option<String>, mut target_arch: Option<String>) -
         Err(format!("Publish failed: {}", err_text))
     }
 }
+
+pub fn search(query: &str, json: bool) -> Result<(), String> {
+    let url = format!("{}/packages/search/", registry_url());
+    let client = Client::new();
+    let resp = client.get(&url).query(&[("q", query)]).send().map_err(|e| format!("Network error: {}", e))?;
+
+    if !resp.status().is_success() {
+        return Err(format!("Search failed: server error ({})", resp.status()));
+    }
+
+    let mut results = match resp.json().map_err(|e| format!("JSON Error: {}", e))? {
+        SearchResponse::Page { results } | SearchResponse::List(results) => results,
+    };
+    let (os, arch) = get_system_info();
+    for result in &mut results {
+        result.compatible = result.platforms.is_empty() || result.platforms.iter().any(|p| {
+            (p.os == "any" || p.os == os) && (p.architecture == "any" || p.architecture == arch)
+        });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+
+    if results.is_empty() {
+        println!("No package matches '{}'.", query);
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 6]> = results.iter().map(|r| [
+        r.name.clone(),
+        r.version.clone(),
+        r.downloads.to_string(),
+        if r.compatible { "yes".to_string() } else { "no".to_string() },
+        if r.platforms.is_empty() {
+            "any".to_string()
+        } else {
+            r.platforms.iter().map(|p| format!("{}/{}", p.os, p.architecture)).collect::<Vec<_>>().join(", ")
+        },
+        truncate(r.description.as_deref().unwrap_or(""), DESCRIPTION_WIDTH),
+    ]).collect();
+    let header = ["NAME", "VERSION", "DOWNLOADS", "COMPATIBLE", "PLATFORMS", "DESCRIPTION"];
+
+    let mut widths = header.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let print_row = |cells: [&str; 6]| {
+        let line: Vec<String> = cells.iter().zip(widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+
+    print_row(header);
+    for row in &rows {
+        print_row(row.each_ref().map(|cell| cell.as_str()));
+    }
+    println!("\n{} package(s) found for {}/{}. Install one with 'aegis add <name>'.", rows.len(), os, arch);
+    Ok(())
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    let text = text.lines().next().unwrap_or("");
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max - 1).collect();
+    short.push('…');
+    short
+}
@@ -1,47 +1,180 @@
 use crate::native;
+use crate::{NativeClass, NativeFn, Value};
 use libloading::{Library, Symbol};
 use std::path::Path;
 use std::collections::HashMap;
-use std::sync::{Mutex, OnceLock}; // <--- Nouveaux imports
+use std::sync::{Mutex, OnceLock};
 
-// Signature que le plugin devra implémenter
-type RegisterPluginFn = unsafe extern "C" fn(&mut HashMap<String, crate::NativeFn>);
+// Un plugin exporte deux symboles :
+// - `AEGIS_PLUGIN_INFO` : un `PluginInfo` (version d'ABI, nom et version du plugin), lu en
+//   premier pour refuser un plugin compilé contre une autre version d'aegis_core AVANT
+//   d'appeler son code (Value, NativeFn... n'ont pas la même forme d'une version à l'autre) ;
+// - `_aegis_plugin_init` : reçoit un `PluginContext` où il déclare ses natives, constantes et
+//   classes. Rien n'est enregistré si l'initialisation échoue (`ctx.fail`).
+
+/// Version de l'ABI des plugins. Incrémentée à chaque changement de forme de `Value`,
+/// `NativeFn`, `NativeClass`, `PluginInfo` ou `PluginContext`.
+pub const PLUGIN_ABI_VERSION: u32 = 3;
+
+/// Fonctionnalités de l'hôte qu'un plugin peut tester avec `ctx.supports(...)`.
+pub const HOST_FEATURES: &[&str] = &["functions", "constants", "classes", "typed_arrays", "command_buffer", "bytes"];
+
+/// Description exportée par le plugin sous le nom `AEGIS_PLUGIN_INFO`.
+/// `abi_version` reste le premier champ : c'est le seul lu avant la vérification.
+#[repr(C)]
+pub struct PluginInfo {
+    pub abi_version: u32,
+    pub name: &'static str,
+    pub version: &'static str,
+}
+
+type PluginInitFn = unsafe extern "C" fn(&mut PluginContext);
+
+/// Ce que le plugin enregistre pendant `_aegis_plugin_init`.
+pub struct PluginContext {
+    functions: HashMap<String, NativeFn>,
+    constants: HashMap<String, Value>,
+    classes: Vec<NativeClass>,
+    failure: Option<String>,
+}
+
+impl PluginContext {
+    fn new() -> Self {
+        Self { functions: HashMap::new(), constants: HashMap::new(), classes: Vec::new(), failure: None }
+    }
+
+    /// Version de l'ABI de l'hôte (égale à celle du plugin, sinon il n'est pas initialisé).
+    pub fn abi_version(&self) -> u32 {
+        PLUGIN_ABI_VERSION
+    }
+
+    /// Version d'Aegis qui charge le plugin (ex: "0.4.3").
+    pub fn host_version(&self) -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    /// Vrai si l'hôte fournit la fonctionnalité (voir `HOST_FEATURES`).
+    pub fn supports(&self, feature: &str) -> bool {
+        HOST_FEATURES.contains(&feature)
+    }
+
+    /// Vrai si une native du même nom existe déjà (stdlib ou plugin chargé avant).
+    pub fn has_native(&self, name: &str) -> bool {
+        native::is_available(name)
+    }
+
+    pub fn function(&mut self, name: &str, func: NativeFn) {
+        self.functions.insert(name.to_string(), func);
+    }
+
+    pub fn constant(&mut self, name: &str, value: Value) {
+        self.constants.insert(name.to_string(), value);
+    }
+
+    pub fn class(&mut self, class: NativeClass) {
+        self.classes.push(class);
+    }
+
+    /// Refuse le chargement (fonctionnalité manquante, version trop ancienne...) : rien n'est enregistré.
+    pub fn fail(&mut self, reason: &str) {
+        self.failure = Some(reason.to_string());
+    }
+}
+
+/// Ce qu'un plugin chargé a fourni (affiché par `aegis plugins`).
+#[derive(Debug, Clone)]
+pub struct PluginReport {
+    pub name: String,
+    pub version: String,
+    pub path: String,
+    pub functions: Vec<String>,
+    pub constants: Vec<String>,
+    pub classes: Vec<String>,
+}
 
 static LOADED_LIBS: OnceLock<Mutex<Vec<Library>>> = OnceLock::new();
+static REPORTS: OnceLock<Mutex<Vec<PluginReport>>> = OnceLock::new();
+
+/// Plugins chargés jusqu'ici, dans l'ordre de chargement.
+pub fn loaded_plugins() -> Vec<PluginReport> {
+    REPORTS.get().and_then(|reports| reports.lock().ok().map(|r| r.clone())).unwrap_or_default()
+}
 
-pub fn load_plugin(path_str: &str) -> Result<(), String> {
+pub fn load_plugin(path_str: &str) -> Result<PluginReport, String> {
     let path = Path::new(path_str);
 
     if !path.exists() {
         return Err(format!("Plugin introuvable : {}", path_str));
     }
 
-    // On prépare le conteneur global si c'est la première fois
-    let libs_mutex = LOADED_LIBS.get_or_init(|| Mutex::new(Vec::new()));
-
     // Le chargement de DLL reste unsafe (c'est inhérent aux FFI)
-    unsafe {
-        // 1. Charger la DLL
+    let report = unsafe {
         let lib = Library::new(path).map_err(|e| format!("Erreur chargement DLL: {}", e))?;
 
-        // 2. Chercher le symbole spécial "_aegis_register"
-        let func: Symbol<RegisterPluginFn> = lib.get(b"_aegis_register\0")
-            .map_err(|e| format!("Le plugin n'a pas de point d'entrée '_aegis_register': {}", e))?;
+        let info: Symbol<*const PluginInfo> = lib.get(b"AEGIS_PLUGIN_INFO\0").map_err(|_| {
+            let legacy = lib.get::<*const ()>(b"_aegis_register\0").is_ok();
+            if legacy {
+                format!("{}: built for the old plugin API (_aegis_register), rebuild it against aegis_core {} (plugin ABI v{})",
+                    path_str, env!("CARGO_PKG_VERSION"), PLUGIN_ABI_VERSION)
+            } else {
+                format!("{}: not an Aegis plugin (no AEGIS_PLUGIN_INFO symbol)", path_str)
+            }
+        })?;
+        let info: *const PluginInfo = *info;
+        // Lue seule, comme un u32 : le reste de la structure n'a un sens qu'avec la bonne ABI
+        check_abi(info.cast::<u32>().read(), path_str)?;
+        let info = &*info;
+
+        let init: Symbol<PluginInitFn> = lib.get(b"_aegis_plugin_init\0")
+            .map_err(|_| format!("{}: plugin '{}' has no _aegis_plugin_init function", path_str, info.name))?;
+        let report = register_plugin(info, path_str, |ctx| init(ctx))?;
+
+        // La bibliothèque reste chargée : ses fonctions sont dans le registre
+        LOADED_LIBS.get_or_init(|| Mutex::new(Vec::new())).lock()
+            .map_err(|e| format!("Erreur de verrouillage des plugins: {}", e))?
+            .push(lib);
+        report
+    };
+
+    Ok(report)
+}
+
+fn check_abi(abi_version: u32, path: &str) -> Result<(), String> {
+    if abi_version == PLUGIN_ABI_VERSION {
+        return Ok(());
+    }
+    Err(format!("{}: plugin ABI v{} is not supported by this Aegis ({}, plugin ABI v{}), rebuild the plugin",
+        path, abi_version, env!("CARGO_PKG_VERSION"), PLUGIN_ABI_VERSION))
+}
 
-        // 3. Récupérer le registre natif actuel
-        let mut plugin_funcs = HashMap::new();
-        func(&mut plugin_funcs);
+/// Initialise un plugin dont la version d'ABI a été vérifiée, puis enregistre ce qu'il fournit.
+/// Séparée de `load_plugin` pour les plugins liés statiquement (hôtes embarquant Aegis, tests).
+pub fn register_plugin(info: &PluginInfo, path: &str, init: impl FnOnce(&mut PluginContext)) -> Result<PluginReport, String> {
+    check_abi(info.abi_version, path)?;
+
+    let mut ctx = PluginContext::new();
+    init(&mut ctx);
+    if let Some(reason) = ctx.failure {
+        return Err(format!("plugin '{}' {} refused to load: {}", info.name, info.version, reason));
+    }
 
-        // 4. On fusionne dans le registre global
-        native::extend_registry(plugin_funcs);
+    let sorted = |mut names: Vec<String>| { names.sort(); names };
+    let report = PluginReport {
+        name: info.name.to_string(),
+        version: info.version.to_string(),
+        path: path.to_string(),
+        functions: sorted(ctx.functions.keys().cloned().collect()),
+        constants: sorted(ctx.constants.keys().cloned().collect()),
+        classes: ctx.classes.iter().map(|class| class.name.clone()).collect(),
+    };
 
-        // 5. On stocke la lib de manière sécurisée avec le Mutex
-        // On verrouille la liste juste le temps d'ajouter la lib
-        match libs_mutex.lock() {
-            Ok(mut libs) => libs.push(lib),
-            Err(e) => return Err(format!("Erreur de verrouillage des plugins: {}", e)),
-        }
+    native::extend_registry(ctx.functions, ctx.constants);
+    for class in ctx.classes {
+        native::register_class(class);
     }
 
-    Ok(())
+    REPORTS.get_or_init(|| Mutex::new(Vec::new())).lock()
+        .map_err(|e| format!("Erreur de verrouillage des plugins: {}", e))?
+        .push(report.clone());
+    Ok(report)
 }
@@ -0,0 +1,141 @@
+// `aegis new` / `aegis init` : squelette de projet (aegis.toml, src/main.aeg, .gitignore),
+// point d'entrée utilisé par `aegis run` sans fichier, et résolution des chemins d'import.
+
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+
+pub const MANIFEST: &str = "aegis.toml";
+pub const DEFAULT_ENTRY: &str = "src/main.aeg";
+/// Fichiers essayés dans l'ordre pour `import "utils"` (`{}` est le chemin écrit) quand
+/// aegis.toml n'a pas de `[imports] resolve`.
+pub const DEFAULT_RESOLVE: [&str; 3] = ["{}", "{}.aeg", "{}/index.aeg"];
+
+#[derive(Deserialize, Default)]
+struct Manifest {
+    #[serde(default)]
+    project: ProjectInfo,
+    #[serde(default)]
+    imports: ImportsConfig,
+}
+
+#[derive(Deserialize, Default)]
+struct ProjectInfo {
+    entry: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct ImportsConfig {
+    resolve: Option<Vec<String>>,
+}
+
+/// Crée le dossier `name` et son squelette. Refuse un dossier qui existe déjà.
+pub fn new(name: &str, with_tests: bool) -> Result<(), String> {
+    let dir = Path::new(name);
+    if dir.exists() {
+        return Err(format!("Destination '{}' already exists", name));
+    }
+    let project_name = dir.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid project name '{}'", name))?;
+    check_name(&project_name)?;
+
+    fs::create_dir_all(dir).map_err(|e| format!("Impossible de créer {}: {}", name, e))?;
+    scaffold(dir, &project_name, with_tests)?;
+    println!("✅ Created project '{}' in {}/", project_name, name);
+    println!("   cd {} && aegis run", name);
+    Ok(())
+}
+
+/// Squelette dans un dossier existant (nommé d'après lui). Refuse s'il a déjà un aegis.toml ;
+/// les autres fichiers déjà présents sont conservés.
+pub fn init(dir: &Path, with_tests: bool) -> Result<(), String> {
+    if dir.join(MANIFEST).exists() {
+        return Err(format!("{} already exists in this directory", MANIFEST));
+    }
+    let absolute = dir.canonicalize().map_err(|e| e.to_string())?;
+    let project_name = absolute.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or("Cannot name a project after the root directory")?;
+    check_name(&project_name)?;
+
+    scaffold(dir, &project_name, with_tests)?;
+    println!("✅ Initialized project '{}'", project_name);
+    Ok(())
+}
+
+/// Fichier lancé par `aegis run` sans argument : `[project] entry` de aegis.toml, sinon src/main.aeg.
+pub fn entry_point(dir: &Path) -> Result<String, String> {
+    let content = fs::read_to_string(dir.join(MANIFEST))
+        .map_err(|_| format!("No file given and no {} in the current directory (create a project with 'aegis init')", MANIFEST))?;
+    let manifest: Manifest = toml::from_str(&content)
+        .map_err(|e| format!("Invalid {}: {}", MANIFEST, e))?;
+    Ok(manifest.project.entry.unwrap_or_else(|| DEFAULT_ENTRY.to_string()))
+}
+
+/// Chemins candidats pour `import "<path>"`, dans l'ordre de `[imports] resolve` du aegis.toml
+/// du dossier courant (sans manifeste : DEFAULT_RESOLVE). Le module est le premier qui existe.
+pub fn import_candidates(path: &str) -> Result<Vec<String>, String> {
+    let patterns = match fs::read_to_string(MANIFEST) {
+        Ok(content) => {
+            let manifest: Manifest = toml::from_str(&content)
+                .map_err(|e| format!("Invalid {}: {}", MANIFEST, e))?;
+            manifest.imports.resolve
+        },
+        Err(_) => None,
+    };
+    let Some(patterns) = patterns else {
+        return Ok(DEFAULT_RESOLVE.iter().map(|pattern| pattern.replace("{}", path)).collect());
+    };
+    if let Some(pattern) = patterns.iter().find(|pattern| !pattern.contains("{}")) {
+        return Err(format!("Invalid {}: import pattern '{}' has no '{{}}' for the imported path", MANIFEST, pattern));
+    }
+    Ok(patterns.iter().map(|pattern| pattern.replace("{}", path)).collect())
+}
+
+// Le nom sert de nom de paquet pour `aegis publish` / `aegis add`
+fn check_name(name: &str) -> Result<(), String> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!("Invalid project name '{}': use letters, digits, '_' or '-', starting with a letter", name));
+    }
+    Ok(())
+}
+
+fn scaffold(dir: &Path, name: &str, with_tests: bool) -> Result<(), String> {
+    let manifest = format!(
+        "[project]\nname = \"{}\"\nversion = \"0.1.0\"\nentry = \"{}\"\n\n[dependencies]\n",
+        name, DEFAULT_ENTRY
+    );
+    let main = format!("func main() {{\n    print \"Hello from {}!\"\n}}\n\nmain()\n", name);
+
+    let mut files = vec![
+        (MANIFEST.to_string(), manifest),
+        (DEFAULT_ENTRY.to_string(), main),
+        // Installés par `aegis install` d'après aegis.lock, qui lui est versionné
+        (".gitignore".to_string(), "packages/\n".to_string()),
+    ];
+    if with_tests {
+        files.push(("tests/main_test.aeg".to_string(), TEST_TEMPLATE.to_string()));
+    }
+
+    for (path, content) in files {
+        let target = dir.join(&path);
+        if target.exists() {
+            println!("   skipped {} (already exists)", path);
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        fs::write(&target, content).map_err(|e| format!("Impossible d'écrire {}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+// Lancé par `aegis test`
+const TEST_TEMPLATE: &str = r#"func test_addition() {
+    assert_eq(1 + 1, 2)
+}
+"#;
@@ -3,3 +3,10 @@ use rust_embed::RustEmbed;
 #[derive(RustEmbed)]
 #[folder = "stdlib/"]
 pub struct StdLibAsset;
+
+/// Source d'un module "stdlib/..." embarqué dans l'exécutable (None pour un autre chemin).
+pub fn source(path: &str) -> Option<String> {
+    let name = path.strip_prefix("./").unwrap_or(path).strip_prefix("stdlib/")?;
+    let file = StdLibAsset::get(name)?;
+    String::from_utf8(file.data.into_owned()).ok()
+}
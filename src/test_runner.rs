@@ -0,0 +1,172 @@
+// `aegis test` : trouve les fichiers `*_test.aeg` et lance chacune de leurs fonctions `test_*`
+// dans sa propre VM (le code de premier niveau du fichier est rejoué avant chaque test).
+// Un fichier sans fonction `test_*` compte comme un seul test : il réussit s'il s'exécute sans erreur.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use crate::ast::{Instruction, Statement};
+use crate::vm::VM;
+
+pub const TEST_SUFFIX: &str = "_test.aeg";
+const TEST_PREFIX: &str = "test_";
+
+// Dossiers jamais parcourus : dépendances installées et sorties de compilation
+const SKIPPED_DIRS: [&str; 2] = ["packages", "target"];
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub passed: usize,
+    // (fichier, test, erreur)
+    pub failures: Vec<(String, String, String)>,
+}
+
+impl Summary {
+    pub fn success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Fichiers de test : ceux donnés tels quels, les `*_test.aeg` des dossiers donnés,
+/// et par défaut ceux de `tests/` (ou du dossier courant s'il n'existe pas).
+pub fn discover(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    let roots: Vec<PathBuf> = if paths.is_empty() {
+        let tests = PathBuf::from("tests");
+        vec![if tests.is_dir() { tests } else { PathBuf::from(".") }]
+    } else {
+        paths.iter().map(PathBuf::from).collect()
+    };
+
+    let mut files = Vec::new();
+    for root in roots {
+        if root.is_dir() {
+            collect(&root, &mut files)?;
+        } else if root.is_file() {
+            files.push(root);
+        } else {
+            return Err(format!("{}: no such file or directory", root.display()));
+        }
+    }
+    Ok(files)
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Impossible de lire {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect(&path, files)?;
+            }
+        } else if name.ends_with(TEST_SUFFIX) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Lance les tests des fichiers (filtrés par nom si `filter` est donné) et affiche le résumé.
+pub fn run(files: &[PathBuf], filter: Option<&str>) -> Summary {
+    let start = Instant::now();
+    let mut summary = Summary::default();
+
+    for file in files {
+        let display = file.to_string_lossy().replace('\\', "/");
+        let display = display.strip_prefix("./").unwrap_or(&display).to_string();
+
+        let tests = match test_names(file) {
+            Ok(tests) => tests,
+            Err(e) => {
+                println!("{}\n  ❌ {}", display, e);
+                summary.failures.push((display, String::new(), e));
+                continue;
+            }
+        };
+        let tests: Vec<(Option<String>, String)> = tests.into_iter()
+            .map(|test| {
+                let label = test.clone().unwrap_or_else(|| "(script)".to_string());
+                (test, label)
+            })
+            .filter(|(_, label)| filter.is_none_or(|f| label.contains(f)))
+            .collect();
+        if tests.is_empty() {
+            continue;
+        }
+        println!("{}", display);
+
+        for (test, label) in tests {
+            match run_test(file, test.as_deref()) {
+                Ok(()) => {
+                    println!("  ✅ {}", label);
+                    summary.passed += 1;
+                },
+                Err(e) => {
+                    println!("  ❌ {}", label);
+                    for line in e.lines() {
+                        println!("      {}", line);
+                    }
+                    summary.failures.push((display.clone(), label, e));
+                },
+            }
+        }
+    }
+
+    println!();
+    if !summary.failures.is_empty() {
+        println!("Failures:");
+        for (file, test, _) in &summary.failures {
+            if test.is_empty() {
+                println!("  {}", file);
+            } else {
+                println!("  {} :: {}", file, test);
+            }
+        }
+        println!();
+    }
+    println!(
+        "test result: {}. {} passed; {} failed ({:.2}s)",
+        if summary.success() { "ok" } else { "FAILED" },
+        summary.passed,
+        summary.failures.len(),
+        start.elapsed().as_secs_f64()
+    );
+    summary
+}
+
+// Fonctions `test_*` de premier niveau, dans l'ordre du fichier ; [None] s'il n'y en a aucune
+fn test_names(file: &Path) -> Result<Vec<Option<String>>, String> {
+    let statements = parse(file)?;
+    let names: Vec<Option<String>> = statements.iter()
+        .filter_map(|stmt| match &stmt.kind {
+            Instruction::Function { name, .. } if name.starts_with(TEST_PREFIX) => Some(Some(name.clone())),
+            _ => None,
+        })
+        .collect();
+    Ok(if names.is_empty() { vec![None] } else { names })
+}
+
+fn parse(file: &Path) -> Result<Vec<Statement>, String> {
+    let source = fs::read_to_string(file)
+        .map_err(|e| format!("Impossible de lire {}: {}", file.display(), e))?;
+    let json = crate::compiler::compile(&source)?;
+    crate::loader::parse_block(&json)
+}
+
+// Nouvelle VM : exécute le fichier puis appelle le test
+fn run_test(file: &Path, test: Option<&str>) -> Result<(), String> {
+    let mut compiler = crate::vm::compiler::Compiler::new();
+    compiler.file = Some(std::rc::Rc::from(file.to_string_lossy().as_ref()));
+    let (chunk, global_names) = compiler.compile(parse(file)?)?;
+
+    let mut vm = VM::new(chunk, global_names, Vec::new());
+    vm.run()?;
+    if let Some(test) = test {
+        vm.call_global(test, Vec::new())?;
+    }
+    Ok(())
+}
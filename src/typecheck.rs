@@ -0,0 +1,810 @@
+// Vérification statique des types : lancée par `aegis check`, et avant l'exécution en mode
+// strict (`aegis run --strict` ou `use strict`). Les types sont déduits des littéraux, des
+// opérateurs, des annotations (variables, paramètres, champs, retours) et des signatures des
+// fonctions et classes du fichier.
+//
+// Seules les erreurs certaines sont signalées : une expression de type inconnu (paramètre non
+// annoté, appel d'une fonction importée...) est acceptée partout, et une union est acceptée dès
+// qu'un de ses membres peut l'être.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::value::{is_constructor, method_name};
+use crate::ast::{CompoundOp, Expression, Instruction, Statement, TypeExpr, Value};
+use crate::checker::Diagnostic;
+
+// Noms de types connus de CheckType sans déclaration
+const BUILTIN_TYPES: [&str; 12] = ["int", "float", "string", "bool", "list", "dict", "func", "bytes", "Float32Array", "IntArray", "any", "null"];
+
+// Types des valeurs qui ne sont pas des instances : un nom de classe ne les accepte jamais
+const VALUE_TYPES: [&str; 12] = ["int", "float", "string", "bool", "list", "dict", "func", "bytes", "Float32Array", "IntArray", "null", "range"];
+
+// Types possibles d'une expression ("int", "string", nom de classe...) ; None : inconnu
+type Ty = Option<Vec<String>>;
+
+struct Variable {
+    ty: Ty,
+    // Le type vient d'une annotation : chaque affectation doit le respecter
+    declared: bool,
+}
+
+// Paramètres (nom, type annoté) et type de retour d'une fonction ou d'une méthode
+#[derive(Clone)]
+struct Signature {
+    params: Vec<(String, Ty)>,
+    ret: Ty,
+    // Paramètres sans valeur par défaut, et `...rest` en dernier
+    required: usize,
+    variadic: bool,
+}
+
+impl Signature {
+    fn accepts(&self, count: usize) -> bool {
+        count >= self.required && (self.variadic || count <= self.params.len())
+    }
+}
+
+struct ClassInfo {
+    parent: Option<String>,
+    interfaces: Vec<String>,
+    // Champs annotés
+    fields: HashMap<String, Vec<String>>,
+    // Méthodes d'instance
+    methods: HashMap<String, Signature>,
+}
+
+/// Erreurs de type d'un fichier déjà chargé, dans l'ordre des lignes.
+pub fn check(statements: &[Statement]) -> Vec<Diagnostic> {
+    let mut checker = TypeChecker::new(statements);
+    checker.visit_block(statements);
+    checker.diagnostics.sort_by_key(|d| d.line);
+    checker.diagnostics
+}
+
+struct TypeChecker {
+    diagnostics: Vec<Diagnostic>,
+    // Nombre de liaisons de chaque nom : une fonction ou une classe liée une seule fois
+    // ne peut être ni réaffectée ni masquée, sa signature vaut donc pour tous ses appels
+    bindings: HashMap<String, usize>,
+    aliases: HashMap<String, TypeExpr>,
+    functions: HashMap<String, Signature>,
+    classes: HashMap<String, ClassInfo>,
+    // Interfaces, enums et noms importés avec `from ... import` : utilisables comme types
+    other_types: HashSet<String>,
+    // `import "x"` sans alias peut définir n'importe quel type : pas d'« Unknown type »
+    open_types: bool,
+    // Portée du premier niveau, puis une par fonction en cours d'analyse
+    scopes: Vec<HashMap<String, Variable>>,
+    // (nom, type de retour annoté) de chaque fonction en cours d'analyse
+    returns: Vec<(String, Ty)>,
+    line: usize,
+}
+
+impl TypeChecker {
+    fn new(statements: &[Statement]) -> Self {
+        let mut checker = TypeChecker {
+            diagnostics: Vec::new(),
+            bindings: crate::checker::binding_counts(statements),
+            aliases: HashMap::new(),
+            functions: HashMap::new(),
+            classes: HashMap::new(),
+            other_types: HashSet::new(),
+            open_types: false,
+            scopes: vec![HashMap::new()],
+            returns: Vec::new(),
+            line: 0,
+        };
+
+        let declarations: Vec<&Instruction> = statements.iter()
+            .map(|stmt| match &stmt.kind {
+                Instruction::Private(inner) | Instruction::Export(inner) => inner.as_ref(),
+                other => other,
+            })
+            .collect();
+
+        // Les alias d'abord : les signatures peuvent les utiliser
+        for decl in &declarations {
+            if let Instruction::TypeAlias(name, ty) = decl {
+                let resolved = ty.resolve(&checker.aliases);
+                checker.aliases.insert(name.clone(), resolved);
+            }
+        }
+
+        for decl in declarations {
+            match decl {
+                Instruction::Function { name, params, ret_type, body, is_async } if checker.bound_once(name) => {
+                    let mut signature = checker.signature(params, ret_type, body);
+                    // L'appel d'une fonction async renvoie une tâche
+                    if *is_async {
+                        signature.ret = None;
+                    }
+                    checker.functions.insert(name.clone(), signature);
+                },
+                Instruction::Class(def) if checker.bound_once(&def.name) => {
+                    let fields = def.fields.iter()
+                        .filter(|field| !field.is_static)
+                        .filter_map(|field| Some((field.name.clone(), checker.resolve(field.type_annot.as_deref()?)?)))
+                        .collect();
+                    let methods = def.methods.iter()
+                        .filter(|(_, (_, _, is_static, _))| !is_static)
+                        .map(|(name, (params, body, _, _))| (name.clone(), checker.signature(params, &None, body)))
+                        .collect();
+                    checker.classes.insert(def.name.clone(), ClassInfo {
+                        parent: def.parent.clone(),
+                        interfaces: def.interfaces.clone(),
+                        fields,
+                        methods,
+                    });
+                },
+                Instruction::Interface(def) => { checker.other_types.insert(def.name.clone()); },
+                Instruction::Enum(name, _) => { checker.other_types.insert(name.clone()); },
+                Instruction::FromImport(_, names) => {
+                    checker.other_types.extend(names.iter().map(|(_, alias)| alias.clone()));
+                },
+                Instruction::Import(_, None) => checker.open_types = true,
+                _ => {},
+            }
+        }
+        checker
+    }
+
+    fn bound_once(&self, name: &str) -> bool {
+        self.bindings.get(name) == Some(&1)
+    }
+
+    fn error(&mut self, message: String) {
+        self.diagnostics.push(Diagnostic { line: self.line, message });
+    }
+
+    // Annotation -> noms de types acceptés, alias remplacés
+    fn resolve(&self, annot: &str) -> Ty {
+        let ty = TypeExpr::parse(annot).ok()?.resolve(&self.aliases);
+        Some(ty.names().iter().map(|name| name.to_string()).collect())
+    }
+
+    fn signature(&self, params: &[(String, Option<String>)], ret_type: &Option<String>, body: &[Statement]) -> Signature {
+        let prologue = crate::ast::nodes::param_prologue(body);
+        let optional = prologue.iter().filter(|s| matches!(s.kind, Instruction::DefaultParam(..))).count();
+        let variadic = prologue.iter().any(|s| matches!(s.kind, Instruction::RestParam(_)));
+        Signature {
+            params: params.iter()
+                .map(|(name, annot)| (name.clone(), annot.as_deref().and_then(|a| self.resolve(a))))
+                .collect(),
+            ret: ret_type.as_deref().and_then(|t| self.resolve(t)),
+            required: params.len() - optional - variadic as usize,
+            variadic,
+        }
+    }
+
+    // Signale les noms de types inconnus ; faux s'il y en a (l'annotation ne peut alors rien accepter)
+    fn check_annotation(&mut self, annot: &str) -> bool {
+        if self.open_types {
+            return true;
+        }
+        let Some(names) = self.resolve(annot) else { return true };
+        let mut valid = true;
+        for name in names {
+            let known = BUILTIN_TYPES.contains(&name.as_str())
+                || self.classes.contains_key(&name)
+                || self.other_types.contains(&name)
+                || self.bindings.contains_key(&name)
+                || crate::native::find_constant(&name).is_some();
+            if !known {
+                self.error(format!("Unknown type '{}'", name));
+                valid = false;
+            }
+        }
+        valid
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Variable> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn declare(&mut self, name: &str, ty: Ty, declared: bool) {
+        let scope = self.scopes.last_mut().expect("No scope");
+        scope.insert(name.to_string(), Variable { ty, declared });
+    }
+
+    // ------------------------------------------------------------------
+    // Compatibilité
+    // ------------------------------------------------------------------
+
+    // Vrai si aucune valeur du type `value` ne peut être acceptée par `target`
+    fn mismatch(&self, target: &Ty, value: &Ty) -> bool {
+        let (Some(target), Some(value)) = (target, value) else { return false };
+        value.iter().all(|member| self.accepts(target, member) == Some(false))
+    }
+
+    // Some(false) : `member` n'est jamais accepté par `target` ; None : on ne peut pas savoir
+    fn accepts(&self, target: &[String], member: &str) -> Option<bool> {
+        if target.iter().any(|t| t == member) || (member != "null" && target.iter().any(|t| t == "any")) {
+            return Some(true);
+        }
+        if VALUE_TYPES.contains(&member) {
+            return Some(false);
+        }
+
+        // `member` est une classe : l'une des cibles doit être un ancêtre ou une interface implémentée
+        let mut unknown = false;
+        for t in target.iter().filter(|t| !VALUE_TYPES.contains(&t.as_str())) {
+            match self.derives(member, t) {
+                Some(true) => return Some(true),
+                Some(false) => {},
+                None => unknown = true,
+            }
+        }
+        if unknown { None } else { Some(false) }
+    }
+
+    // La classe `class` hérite-t-elle de `target`, ou l'implémente-t-elle ? None dès que la
+    // lignée sort du fichier (classe importée ou fournie par un plugin)
+    fn derives(&self, class: &str, target: &str) -> Option<bool> {
+        let mut current = class;
+        // Borne : une lignée cyclique (erreur relevée à l'exécution) ne boucle pas ici
+        for _ in 0..=self.classes.len() {
+            if current == target {
+                return Some(true);
+            }
+            let info = self.classes.get(current)?;
+            if info.interfaces.iter().any(|i| i == target) {
+                return Some(true);
+            }
+            match &info.parent {
+                Some(parent) => current = parent,
+                None => return Some(false),
+            }
+        }
+        None
+    }
+
+    // Classes du fichier d'une expression dont le type est une seule classe, de la plus
+    // proche à la plus lointaine (arrêt à la première qui n'est pas déclarée dans le fichier)
+    fn lineage<'a>(&'a self, ty: &'a Ty) -> Vec<(&'a str, &'a ClassInfo)> {
+        let mut chain = Vec::new();
+        let Some([class]) = ty.as_deref() else { return chain };
+        let mut current = Some(class.as_str());
+        while let Some(name) = current
+            && let Some(info) = self.classes.get(name)
+            && chain.len() <= self.classes.len() {
+            chain.push((name, info));
+            current = info.parent.as_deref();
+        }
+        chain
+    }
+
+    fn field_type(&self, object: &Ty, field: &str) -> Option<(String, Vec<String>)> {
+        self.lineage(object).into_iter()
+            .find_map(|(class, info)| Some((format!("{}.{}", class, field), info.fields.get(field)?.clone())))
+    }
+
+    // Signature de `object.method(...)`. Ignorée si une sous-classe du fichier redéfinit la
+    // méthode : l'objet peut être une instance de cette sous-classe.
+    fn method_signature(&self, object: &Ty, method: &str) -> Option<(String, Signature)> {
+        let Some([class]) = object.as_deref() else { return None };
+        let (owner, signature) = self.lineage(object).into_iter()
+            .find_map(|(name, info)| Some((name, info.methods.get(method)?)))?;
+        let overridden = self.classes.iter()
+            .any(|(name, info)| name != class && info.methods.contains_key(method) && self.derives(name, class) == Some(true));
+        (!overridden).then(|| (format!("method '{}.{}'", owner, method), signature.clone()))
+    }
+
+    fn check_call(&mut self, callee: &str, signature: &Signature, args: &[Ty]) {
+        let total = signature.params.len();
+        let expected = if signature.variadic {
+            format!("at least {}", signature.required)
+        } else if signature.required < total {
+            format!("{} to {}", signature.required, total)
+        } else {
+            total.to_string()
+        };
+        if !signature.accepts(args.len()) {
+            self.error(format!("{} expects {} arguments, got {}", capitalized(callee), expected, args.len()));
+            return;
+        }
+        // Le type de `...rest` s'applique à la liste des arguments en trop
+        let fixed = &signature.params[..total - signature.variadic as usize];
+        for ((param, expected), arg) in fixed.iter().zip(args) {
+            if self.mismatch(expected, arg) {
+                self.error(format!("Argument '{}' of {} must be {}, got {}", param, callee, show(expected), show(arg)));
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------
+    // Instructions
+    // ------------------------------------------------------------------
+
+    fn visit_block(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            self.visit_statement(stmt);
+        }
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement) {
+        self.line = stmt.line;
+        match &stmt.kind {
+            Instruction::Set(name, annot, expr) | Instruction::Const(name, annot, expr) => {
+                let ty = self.infer(expr);
+                self.line = stmt.line;
+                match annot {
+                    Some(annot) => {
+                        let valid = self.check_annotation(annot);
+                        let declared = self.resolve(annot);
+                        if valid && self.mismatch(&declared, &ty) {
+                            self.error(format!("'{}' is declared as {}, got {}", name, show(&declared), show(&ty)));
+                        }
+                        self.declare(name, declared, true);
+                    },
+                    // Un nom réaffecté ailleurs peut changer de type : on ne garde que les constantes
+                    None => {
+                        let is_const = matches!(stmt.kind, Instruction::Const(..));
+                        let ty = if is_const || self.bound_once(name) { ty } else { None };
+                        self.declare(name, ty, false);
+                    },
+                }
+            },
+            Instruction::Assign(name, expr) => {
+                let ty = self.infer(expr);
+                self.line = stmt.line;
+                self.assign(name, ty);
+            },
+            Instruction::Input(name, prompt) => {
+                self.infer(prompt);
+                self.assign(name, Some(vec!["string".to_string()]));
+            },
+            Instruction::Print(expr) | Instruction::ExpressionStatement(expr) | Instruction::Throw(expr) => {
+                self.infer(expr);
+            },
+            Instruction::Return(expr) => {
+                let ty = self.infer(expr);
+                self.line = stmt.line;
+                if let Some((function, expected)) = self.returns.last()
+                    && self.mismatch(expected, &ty) {
+                    let message = format!("{} must return {}, got {}", capitalized(function), show(expected), show(&ty));
+                    self.error(message);
+                }
+            },
+            Instruction::SetAttr(obj, attr, value) => {
+                let object = self.infer(obj);
+                let ty = self.infer(value);
+                self.line = stmt.line;
+                self.check_field(&object, attr, &ty);
+            },
+            Instruction::CompoundSetAttr(obj, attr, op, value) => {
+                let object = self.infer(obj);
+                let ty = self.infer(value);
+                self.line = stmt.line;
+                if let Some((_, field)) = self.field_type(&object, attr) {
+                    let result = self.arithmetic(compound_symbol(*op), &Some(field), &ty);
+                    self.check_field(&object, attr, &result);
+                }
+            },
+            Instruction::SetIndex(obj, index, value) | Instruction::CompoundSetIndex(obj, index, _, value) => {
+                self.infer(obj);
+                self.infer(index);
+                self.infer(value);
+            },
+            Instruction::If { condition, body, else_body } => {
+                self.infer(condition);
+                self.visit_block(body);
+                self.visit_block(else_body);
+            },
+            Instruction::While { condition, body } => {
+                self.infer(condition);
+                self.visit_block(body);
+            },
+            Instruction::ForEach(var, iterable, body) => {
+                let ty = self.infer(iterable);
+                let item = (ty.as_deref() == Some(&["range".to_string()])).then(|| vec!["int".to_string()]);
+                self.declare(var, item, false);
+                self.visit_block(body);
+            },
+            Instruction::Defer(body) | Instruction::Group(body) => self.visit_block(body),
+            Instruction::With { resource, name, body } => {
+                self.infer(resource);
+                self.declare(name, None, false);
+                self.visit_block(body);
+            },
+            Instruction::TryCatch { try_body, catches } => {
+                self.visit_block(try_body);
+                for (error_var, _, catch_body) in catches {
+                    self.declare(error_var, None, false);
+                    self.visit_block(catch_body);
+                }
+            },
+            Instruction::Switch { value, cases, default } => {
+                self.infer(value);
+                for (case, body) in cases {
+                    self.infer(case);
+                    self.visit_block(body);
+                }
+                self.visit_block(default);
+            },
+            Instruction::DefaultParam(name, expr) => {
+                let ty = self.infer(expr);
+                self.line = stmt.line;
+                let declared = self.scopes.last()
+                    .and_then(|scope| scope.get(name))
+                    .filter(|variable| variable.declared)
+                    .map(|variable| variable.ty.clone());
+                if let Some(declared) = declared
+                    && self.mismatch(&declared, &ty) {
+                    self.error(format!("Default value of '{}' must be {}, got {}", name, show(&declared), show(&ty)));
+                }
+            },
+            Instruction::RestParam(name) => {
+                // Sans annotation, `...rest` est une liste
+                if let Some(variable) = self.scopes.last_mut().and_then(|scope| scope.get_mut(name))
+                    && !variable.declared {
+                    variable.ty = Some(vec!["list".to_string()]);
+                }
+            },
+            Instruction::Function { name, params, ret_type, body, .. } => {
+                self.visit_function(&format!("function '{}'", name), params, ret_type, body, None);
+            },
+            Instruction::Class(def) => {
+                let mut methods: Vec<_> = def.methods.iter().collect();
+                methods.sort_by_key(|(name, _)| name.as_str());
+                for (name, (params, body, is_static, _)) in methods {
+                    let this = (!is_static).then_some(def.name.as_str());
+                    self.visit_function(&format!("method '{}.{}'", def.name, method_name(name)), params, &None, body, this);
+                }
+                for prop in &def.properties {
+                    for (params, body) in prop.getter.iter().chain(prop.setter.iter()) {
+                        let this = (!prop.is_static).then_some(def.name.as_str());
+                        self.visit_function(&format!("property '{}.{}'", def.name, prop.name), params, &None, body, this);
+                    }
+                }
+                for field in &def.fields {
+                    let ty = self.infer(&field.default_value);
+                    self.line = stmt.line;
+                    let Some(annot) = &field.type_annot else { continue };
+                    let valid = self.check_annotation(annot);
+                    // Sans valeur par défaut, le champ vaut null jusqu'à son initialisation
+                    let declared = self.resolve(annot);
+                    if valid && field.default_value != Expression::Literal(Value::Null) && self.mismatch(&declared, &ty) {
+                        self.error(format!("Field '{}.{}' is declared as {}, got {}", def.name, field.name, show(&declared), show(&ty)));
+                    }
+                }
+            },
+            Instruction::Interface(def) => {
+                for method in &def.methods {
+                    for annot in method.params.iter().filter_map(|(_, annot)| annot.as_deref()) {
+                        self.check_annotation(annot);
+                    }
+                }
+            },
+            Instruction::Namespace { body, .. } => {
+                self.scopes.push(HashMap::new());
+                self.visit_block(body);
+                self.scopes.pop();
+            },
+            Instruction::Private(inner) | Instruction::Export(inner) => {
+                let inner = Statement { kind: (**inner).clone(), line: stmt.line };
+                self.visit_statement(&inner);
+            },
+            Instruction::Import(_, Some(alias)) => self.declare(alias, None, false),
+            Instruction::FromImport(_, names) => {
+                for (_, alias) in names {
+                    self.declare(alias, None, false);
+                }
+            },
+            Instruction::Import(_, None) | Instruction::Enum(..) | Instruction::TypeAlias(..)
+            | Instruction::Break | Instruction::Continue | Instruction::Use(_) => {},
+        }
+    }
+
+    // `x = ...` ne vise que la fonction en cours : ailleurs, elle crée une nouvelle locale
+    fn assign(&mut self, name: &str, ty: Ty) {
+        let scope = self.scopes.last().expect("No scope");
+        match scope.get(name) {
+            Some(var) if var.declared => {
+                if self.mismatch(&var.ty, &ty) {
+                    let message = format!("'{}' is declared as {}, got {}", name, show(&var.ty), show(&ty));
+                    self.error(message);
+                }
+            },
+            Some(_) => {},
+            None => self.declare(name, None, false),
+        }
+    }
+
+    fn check_field(&mut self, object: &Ty, attr: &str, ty: &Ty) {
+        if let Some((field, expected)) = self.field_type(object, attr) {
+            let expected = Some(expected);
+            if self.mismatch(&expected, ty) {
+                self.error(format!("Field '{}' is declared as {}, got {}", field, show(&expected), show(ty)));
+            }
+        }
+    }
+
+    fn visit_function(&mut self, label: &str, params: &[(String, Option<String>)], ret_type: &Option<String>, body: &[Statement], this: Option<&str>) {
+        let line = self.line;
+        let mut scope = HashMap::new();
+        if let Some(class) = this {
+            scope.insert("this".to_string(), Variable { ty: Some(vec![class.to_string()]), declared: true });
+        }
+        for (name, annot) in params {
+            if let Some(annot) = annot {
+                self.check_annotation(annot);
+            }
+            let ty = annot.as_deref().and_then(|a| self.resolve(a));
+            scope.insert(name.clone(), Variable { ty, declared: annot.is_some() });
+        }
+        if let Some(annot) = ret_type {
+            self.check_annotation(annot);
+        }
+        let ret = ret_type.as_deref().and_then(|t| self.resolve(t));
+
+        self.scopes.push(scope);
+        self.returns.push((label.to_string(), ret.clone()));
+        self.visit_block(body);
+        self.returns.pop();
+        self.scopes.pop();
+        self.line = line;
+
+        // La fin du corps renvoie null
+        if let Some(ret) = &ret
+            && !ret.iter().any(|t| t == "null")
+            && !always_returns(body) {
+            self.error(format!("{} must return {} but can reach the end of its body without a return", capitalized(label), show(&Some(ret.clone()))));
+        }
+    }
+
+    // ------------------------------------------------------------------
+    // Expressions
+    // ------------------------------------------------------------------
+
+    fn infer(&mut self, expr: &Expression) -> Ty {
+        match expr {
+            Expression::Literal(value) => {
+                let name = value.type_name();
+                VALUE_TYPES.contains(&name.as_str()).then(|| vec![name])
+            },
+            Expression::Variable(name) => match self.lookup(name) {
+                Some(var) => var.ty.clone(),
+                None if self.functions.contains_key(name) => known("func"),
+                None => None,
+            },
+            Expression::Function { params, ret_type, body, name } => {
+                let label = match name {
+                    Some(name) => format!("function '{}'", name),
+                    None => "lambda".to_string(),
+                };
+                self.visit_function(&label, params, ret_type, body, None);
+                known("func")
+            },
+            Expression::Interpolation(parts) => {
+                for part in parts {
+                    self.infer(part);
+                }
+                known("string")
+            },
+            Expression::List(items) => {
+                for item in items {
+                    self.infer(item);
+                }
+                known("list")
+            },
+            Expression::Dict(entries) => {
+                for (_, value) in entries {
+                    self.infer(value);
+                }
+                known("dict")
+            },
+            Expression::Range(a, b) => {
+                self.infer(a);
+                self.infer(b);
+                known("range")
+            },
+
+            Expression::Add(a, b) => self.operator("+", a, b),
+            Expression::Sub(a, b) => self.operator("-", a, b),
+            Expression::Mul(a, b) => self.operator("*", a, b),
+            Expression::Div(a, b) => self.operator("/", a, b),
+            Expression::Modulo(a, b) => self.operator("%", a, b),
+            Expression::FloorDiv(a, b) => self.operator("~/", a, b),
+
+            Expression::Equal(a, b) | Expression::NotEqual(a, b) | Expression::Same(a, b)
+            | Expression::NotSame(a, b) | Expression::LessThan(a, b) | Expression::GreaterThan(a, b)
+            | Expression::LessEqual(a, b) | Expression::GreaterEqual(a, b) => {
+                self.infer(a);
+                self.infer(b);
+                known("bool")
+            },
+            Expression::Not(e) => {
+                self.infer(e);
+                known("bool")
+            },
+            Expression::And(a, b) | Expression::Or(a, b) => {
+                let (a, b) = (self.infer(a), self.infer(b));
+                let bool_ty = known("bool");
+                if a == bool_ty && b == bool_ty { bool_ty } else { None }
+            },
+            Expression::BitAnd(a, b) | Expression::BitOr(a, b) | Expression::BitXor(a, b)
+            | Expression::ShiftLeft(a, b) | Expression::ShiftRight(a, b) => {
+                let (a, b) = (self.infer(a), self.infer(b));
+                let int_ty = known("int");
+                if a == int_ty && b == int_ty { int_ty } else { None }
+            },
+            Expression::NullCoalescing(a, b) => {
+                let (a, b) = (self.infer(a), self.infer(b));
+                let a: Vec<String> = a?.into_iter().filter(|t| t != "null").collect();
+                Some(union(a, b?))
+            },
+            Expression::Ternary(c, a, b) => {
+                self.infer(c);
+                let (a, b) = (self.infer(a), self.infer(b));
+                Some(union(a?, b?))
+            },
+            Expression::Await(e) => {
+                self.infer(e);
+                None
+            },
+
+            Expression::Call(callee, args) => {
+                let args: Vec<Ty> = args.iter().map(|arg| self.infer(arg)).collect();
+                let Expression::Variable(name) = callee.as_ref() else {
+                    self.infer(callee);
+                    return None;
+                };
+                if self.lookup(name).is_some() {
+                    return None;
+                }
+                if let Some(signature) = self.functions.get(name).cloned() {
+                    self.check_call(&format!("function '{}'", name), &signature, &args);
+                    return signature.ret;
+                }
+                // Natives dont le résultat a toujours le même type (si le fichier ne les masque pas)
+                if self.bindings.contains_key(name) {
+                    return None;
+                }
+                match name.as_str() {
+                    "str" | "to_str" | "fmt" | "typeof" | "chr" => known("string"),
+                    "to_int" | "len" | "ord" => known("int"),
+                    "to_float" => known("float"),
+                    "is_instance" => known("bool"),
+                    _ => None,
+                }
+            },
+            Expression::New(callee, args) => {
+                let args: Vec<Ty> = args.iter().map(|arg| self.infer(arg)).collect();
+                let Expression::Variable(name) = callee.as_ref() else {
+                    self.infer(callee);
+                    return None;
+                };
+                let ty = known(name);
+                if self.classes.contains_key(name) {
+                    // Constructeur : `init` de la classe ou hérité. Avec plusieurs surcharges, celle
+                    // qui accepte ce nombre d'arguments (la VM fait le même choix)
+                    let overloads: Vec<Signature> = self.lineage(&ty).into_iter()
+                        .map(|(_, info)| info.methods.iter()
+                            .filter(|(key, _)| is_constructor(key))
+                            .map(|(_, signature)| signature.clone())
+                            .collect::<Vec<_>>())
+                        .find(|overloads| !overloads.is_empty())
+                        .unwrap_or_default();
+                    let label = format!("constructor '{}'", name);
+                    match overloads.as_slice() {
+                        [] => {},
+                        [signature] => self.check_call(&label, signature, &args),
+                        _ => match overloads.iter().find(|s| s.accepts(args.len())) {
+                            Some(signature) => self.check_call(&label, signature, &args),
+                            None => self.error(format!("{} has no overload taking {} arguments", capitalized(&label), args.len())),
+                        },
+                    }
+                    ty
+                } else if matches!(crate::native::find_constant(name), Some(Value::Class(_))) {
+                    ty
+                } else {
+                    None
+                }
+            },
+            Expression::CallMethod(obj, method, args) => {
+                let object = self.infer(obj);
+                let args: Vec<Ty> = args.iter().map(|arg| self.infer(arg)).collect();
+                if let Some((label, signature)) = self.method_signature(&object, method) {
+                    self.check_call(&label, &signature, &args);
+                }
+                None
+            },
+            Expression::SuperCall(_, args) => {
+                for arg in args {
+                    self.infer(arg);
+                }
+                None
+            },
+            Expression::GetAttr(obj, attr) => {
+                let object = self.infer(obj);
+                self.field_type(&object, attr).map(|(_, ty)| ty)
+            },
+            Expression::GetIndex(obj, index) => {
+                let object = self.infer(obj);
+                self.infer(index);
+                let string_ty = known("string");
+                if object == string_ty { string_ty } else { None }
+            },
+        }
+    }
+
+    fn operator(&mut self, op: &str, a: &Expression, b: &Expression) -> Ty {
+        let a = self.infer(a);
+        let b = self.infer(b);
+        self.arithmetic(op, &a, &b)
+    }
+
+    // Type du résultat de `a op b`, et erreur si l'opération échoue forcément
+    fn arithmetic(&mut self, op: &str, a: &Ty, b: &Ty) -> Ty {
+        let (Some([a]), Some([b])) = (a.as_deref(), b.as_deref()) else { return None };
+        let numeric = |t: &str| t == "int" || t == "float";
+        let result = match (op, a.as_str(), b.as_str()) {
+            ("+", "string", _) | ("+", _, "string") => "string",
+            ("%", "int", "int") => "int",
+            ("%", _, _) => "",
+            // Division entière ou non selon --lang-version
+            ("/", "int", "int") => return None,
+            (_, "int", "int") => "int",
+            (_, x, y) if numeric(x) && numeric(y) => "float",
+            _ => "",
+        };
+        if result.is_empty() {
+            self.error(format!("Cannot apply '{}' to {} and {}", op, a, b));
+            return None;
+        }
+        known(result)
+    }
+}
+
+fn known(name: &str) -> Ty {
+    Some(vec![name.to_string()])
+}
+
+fn union(mut a: Vec<String>, b: Vec<String>) -> Vec<String> {
+    for name in b {
+        if !a.contains(&name) {
+            a.push(name);
+        }
+    }
+    a
+}
+
+fn capitalized(label: &str) -> String {
+    let mut chars = label.chars();
+    chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn show(ty: &Ty) -> String {
+    ty.as_ref().map(|names| names.join("|")).unwrap_or_else(|| "any".to_string())
+}
+
+fn compound_symbol(op: CompoundOp) -> &'static str {
+    match op {
+        CompoundOp::Add => "+",
+        CompoundOp::Sub => "-",
+        CompoundOp::Mul => "*",
+        CompoundOp::Div => "/",
+    }
+}
+
+// Vrai si l'exécution du bloc ne peut pas atteindre sa fin (return, throw, boucle infinie...)
+fn always_returns(body: &[Statement]) -> bool {
+    body.iter().any(|stmt| exits(&stmt.kind))
+}
+
+fn exits(kind: &Instruction) -> bool {
+    match kind {
+        Instruction::Return(_) | Instruction::Throw(_) => true,
+        Instruction::If { body, else_body, .. } => always_returns(body) && always_returns(else_body),
+        // while (true) : on n'en sort que par return / throw (ou break, non suivi ici)
+        Instruction::While { condition: Expression::Literal(Value::Boolean(true)), .. } => true,
+        Instruction::TryCatch { try_body, catches } => {
+            always_returns(try_body) && catches.iter().all(|(_, _, body)| always_returns(body))
+        },
+        Instruction::Switch { cases, default, .. } => {
+            always_returns(default) && cases.iter().all(|(_, body)| always_returns(body))
+        },
+        Instruction::With { body, .. } | Instruction::Group(body) => always_returns(body),
+        _ => false,
+    }
+}
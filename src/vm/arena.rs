@@ -0,0 +1,86 @@
+// Arena de travail des temporaires : les chaînes produites puis consommées dans une même
+// expression (constante comparée, clé d'un dict, opérande de droite d'une concaténation, valeur
+// jetée par Pop...) rendent leur tampon ici, et les chaînes suivantes sont construites dedans
+// au lieu d'allouer. Dans une boucle `color == "red"` / `name + ":" + i`, chaque tour réutilise
+// les mêmes tampons : plus de paire malloc/free par temporaire.
+//
+// Les valeurs possèdent leur String : un bump allocator remis à zéro en sortie de frame
+// obligerait à recopier tout ce qui s'en échappe (valeur de retour, champ, élément de liste).
+// L'arena recycle donc des tampons, et une seule sert toute la VM : une arena par frame
+// repartirait vide à chaque appel d'une petite fonction appelée en boucle.
+
+use crate::ast::{Str, Value};
+
+// Tampons gardés au plus : une expression n'a que quelques temporaires vivants à la fois
+const MAX_BUFFERS: usize = 32;
+// Un tampon plus grand est rendu à l'allocateur : l'arena ne garde pas de grosses chaînes en vie.
+// Un tampon plus petit que MIN_CAPACITY aussi : il ne servirait qu'aux chaînes encore plus courtes
+const MAX_CAPACITY: usize = 4096;
+// Un tampon recyclé sert une chaîne s'il fait au plus ce multiple de sa taille
+const FIT_FACTOR: usize = 4;
+// Taille minimale d'un nouveau tampon : les petites chaînes (mots, clés) peuvent alors
+// s'échanger leurs tampons, et l'allocateur arrondit de toute façon les petits blocs
+const MIN_CAPACITY: usize = 16;
+
+#[derive(Default)]
+pub(super) struct Scratch {
+    free: Vec<String>,
+    // Chaînes construites dans un tampon recyclé / qui ont dû allouer
+    pub(super) reused: u64,
+    pub(super) allocated: u64,
+}
+
+impl Scratch {
+    /// Copie d'une valeur poussée sur la pile : une chaîne possédée est recopiée dans un tampon
+    /// recyclé, le reste (dont les chaînes internées, partagées) est cloné normalement.
+    #[inline]
+    pub(super) fn clone_value(&mut self, value: &Value) -> Value {
+        match value {
+            Value::String(Str::Owned(s)) if !s.is_empty() => {
+                let mut buffer = self.buffer(s.len());
+                buffer.push_str(s);
+                Value::String(buffer.into())
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Tampon de la chaîne, pour la compléter sur place (il reste `extra` octets de marge) : une
+    /// chaîne possédée cède le sien, une chaîne internée est recopiée dans un tampon recyclé.
+    #[inline]
+    pub(super) fn take_string(&mut self, s: &mut Str, extra: usize) -> String {
+        match s {
+            Str::Owned(s) => std::mem::take(s),
+            Str::Interned(shared) => {
+                let mut buffer = self.buffer(shared.len() + extra);
+                buffer.push_str(shared);
+                buffer
+            },
+        }
+    }
+
+    /// Chaîne vide d'au moins `capacity` octets, prise dans le dernier tampon rendu s'il convient.
+    pub(super) fn buffer(&mut self, capacity: usize) -> String {
+        // Trop petit, l'agrandir coûterait une allocation ; trop grand, la chaîne peut finir dans
+        // une liste et y garder toute la place. Dans les deux cas il attend un autre temporaire.
+        let fits = |buffer: &String| (capacity..=capacity.max(MIN_CAPACITY) * FIT_FACTOR).contains(&buffer.capacity());
+        if self.free.last().is_some_and(fits) {
+            self.reused += 1;
+            return self.free.pop().unwrap_or_default();
+        }
+        self.allocated += 1;
+        String::with_capacity(capacity.max(MIN_CAPACITY))
+    }
+
+    /// Reprend le tampon d'une valeur qui n'est plus utilisée ; les autres valeurs sont libérées.
+    #[inline]
+    pub(super) fn recycle(&mut self, mut value: Value) {
+        if let Value::String(Str::Owned(s)) = &mut value
+            && (MIN_CAPACITY..=MAX_CAPACITY).contains(&s.capacity())
+            && self.free.len() < MAX_BUFFERS {
+            let mut buffer = std::mem::take(s);
+            buffer.clear();
+            self.free.push(buffer);
+        }
+    }
+}
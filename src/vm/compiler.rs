@@ -1,37 +1,80 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use crate::ast::value::{ClassData, FunctionData, InterfaceData};
-use crate::ast::{Instruction, Expression, Value};
+use crate::ast::value::{is_constructor, method_name, ClassData, FunctionData, InterfaceData};
+use crate::ast::{CompoundOp, Instruction, Expression, Statement, TypeExpr, Value};
+use crate::checker::Diagnostic;
 use crate::chunk::Chunk;
 use crate::opcode::OpCode;
 
-#[derive(Debug)]
+/// Erreur de compilation (AST valide mais programme incorrect : constante réaffectée,
+/// `break` hors d'une boucle...), avec la ligne de l'instruction fautive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileError {
+    pub message: String,
+    pub line: usize,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Compile error: {} at line {}", self.message, self.line)
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+const JUMP_TOO_LARGE: &str = "Too much code to jump over";
+const LOOP_TOO_LARGE: &str = "Loop body too large";
+
+impl CompileError {
+    // Saut 16 bits insuffisant : Compiler::compile recommence alors en sauts 32 bits
+    fn is_jump_overflow(&self) -> bool {
+        self.message == JUMP_TOO_LARGE || self.message == LOOP_TOO_LARGE
+    }
+}
+
+impl From<CompileError> for String {
+    fn from(e: CompileError) -> String {
+        e.to_string()
+    }
+}
+
+/// Table nom -> slot des globales, partagée entre le script, ses modules et le REPL.
+pub type GlobalNames = Rc<RefCell<HashMap<String, u16>>>;
+
+// Constantes, globales et locales sont désignées par un opérande u16
+const MAX_INDEX: usize = u16::MAX as usize + 1;
+
+#[derive(Debug, Clone)]
 pub enum LoopState {
     While { 
         start_ip: usize,
         break_jumps: Vec<usize>,
         try_depth_at_start: usize,
+        with_depth_at_start: usize,
         locals_count_at_start: usize
     },
     For { 
         continue_patches: Vec<usize>,
         break_jumps: Vec<usize>,
         try_depth_at_start: usize,
+        with_depth_at_start: usize,
         locals_count_at_start: usize
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct LocalInfo {
-    index: u8,
+    index: u16,
     is_const: bool
 }
 
+#[derive(Clone)]
 pub struct Compiler {
     pub chunk: Chunk,
-    pub globals: Rc<RefCell<HashMap<String, u8>>>, 
+    pub globals: Rc<RefCell<HashMap<String, u16>>>, 
     pub locals: HashMap<String, LocalInfo>,
     pub global_constants: Vec<String>,
     pub scope_depth: usize,
@@ -40,6 +83,37 @@ pub struct Compiler {
     pub loop_stack: Vec<LoopState>,
     pub context_parent_name: Option<String>,
     pub try_depth: usize,
+    // Blocs `with` ouverts dans la fonction courante (fermés par break/continue)
+    pub with_depth: usize,
+    // Alias de type (type Id = int), partagés avec les compilateurs des fonctions/classes du fichier
+    pub type_aliases: Rc<RefCell<HashMap<String, TypeExpr>>>,
+    // Globales privées d'un module importé : nom source -> nom interne propre au module
+    pub private_globals: Rc<RefCell<HashMap<String, String>>>,
+    // Niveau d'optimisation (-O2 : inlining et propagation des constantes)
+    pub opt_level: u8,
+    pub inline_functions: Rc<RefCell<HashMap<String, super::optimizer::InlineBody>>>,
+    pub known_constants: Rc<RefCell<HashMap<String, Value>>>,
+    // Version du langage (2 : `/` entre deux entiers donne un Float)
+    pub lang_version: u8,
+    // Sauts à opérande 32 bits (JumpLong...), activés quand un saut 16 bits déborde
+    pub wide_jumps: bool,
+    // Namespace englobant (ex: "Maths"), préfixe des noms de fonctions stockés dans les chunks
+    pub scope_name: Option<String>,
+    // --strict : une affectation sans var/let qui crée une variable est une erreur, pas un avertissement
+    pub strict: bool,
+    // Avertissements de compilation (affichés par `aegis run`), partagés avec les compilateurs enfants
+    pub warnings: Rc<RefCell<Vec<Diagnostic>>>,
+    // Globales déclarées dans le fichier (var, let, func, class...) ou déjà connues (natives, REPL)
+    pub declared_globals: Rc<RefCell<HashSet<String>>>,
+    // Locales des fonctions englobantes (nom -> constante), partagées avec les closures
+    pub enclosing_locals: HashMap<String, bool>,
+    // Fichier compilé, recopié dans chaque chunk pour les piles d'appels
+    pub file: Option<Rc<str>>,
+    // Table des lignes du bytecode. Sans elle (profil release de `aegis bundle`), chaque
+    // instruction est notée à la ligne 0 et les erreurs ne donnent plus de position
+    pub debug_info: bool,
+    // Position du dernier Call émis : s'il précède directement le Return, c'est un appel terminal
+    pub last_call: Option<usize>,
 }
 
 impl Compiler {
@@ -51,7 +125,7 @@ impl Compiler {
             let mut g = globals.borrow_mut();
             for (i, name) in natives.into_iter().enumerate() {
                 // On assigne les ID 0, 1, 2... dans l'ordre alphabétique
-                g.insert(name, i as u8);
+                g.insert(name, i as u16);
             }
         }
 
@@ -65,11 +139,27 @@ impl Compiler {
             current_line: 1,
             loop_stack: Vec::new(),
             context_parent_name: None,
-            try_depth: 0
+            try_depth: 0,
+            with_depth: 0,
+            type_aliases: Rc::new(RefCell::new(HashMap::new())),
+            private_globals: Rc::new(RefCell::new(HashMap::new())),
+            opt_level: 0,
+            inline_functions: Rc::new(RefCell::new(HashMap::new())),
+            known_constants: Rc::new(RefCell::new(HashMap::new())),
+            lang_version: 1,
+            wide_jumps: false,
+            scope_name: None,
+            strict: false,
+            warnings: Rc::new(RefCell::new(Vec::new())),
+            declared_globals: Rc::new(RefCell::new(HashSet::new())),
+            enclosing_locals: HashMap::new(),
+            file: None,
+            debug_info: true,
+            last_call: None,
         }
     }
 
-    pub fn new_with_globals(globals: Rc<RefCell<HashMap<String, u8>>>) -> Self {
+    pub fn new_with_globals(globals: Rc<RefCell<HashMap<String, u16>>>) -> Self {
          Self {
             chunk: Chunk::new(),
             globals, 
@@ -80,22 +170,441 @@ impl Compiler {
             current_line: 1,
             loop_stack: Vec::new(),
             context_parent_name: None,
-            try_depth: 0
+            try_depth: 0,
+            with_depth: 0,
+            type_aliases: Rc::new(RefCell::new(HashMap::new())),
+            private_globals: Rc::new(RefCell::new(HashMap::new())),
+            opt_level: 0,
+            inline_functions: Rc::new(RefCell::new(HashMap::new())),
+            known_constants: Rc::new(RefCell::new(HashMap::new())),
+            lang_version: 1,
+            wide_jumps: false,
+            scope_name: None,
+            strict: false,
+            warnings: Rc::new(RefCell::new(Vec::new())),
+            declared_globals: Rc::new(RefCell::new(HashSet::new())),
+            enclosing_locals: HashMap::new(),
+            file: None,
+            debug_info: true,
+            last_call: None,
         }
     }
 
-    pub fn compile(mut self, statements: Vec<crate::ast::Statement>) -> (Chunk, Rc<RefCell<HashMap<String, u8>>>) {
+    // -O2 : repère les fonctions triviales à inliner et les constantes propageables.
+    // Un nom n'est retenu que s'il est lié une seule fois dans le fichier (ni réaffecté, ni masqué).
+    fn prepare_optimizations(&mut self, statements: &[crate::ast::Statement]) {
+        let bindings = crate::checker::binding_counts(statements);
+        let bound_once = |name: &str| bindings.get(name) == Some(&1);
+
         for stmt in statements {
-            self.current_line = stmt.line;
-            self.compile_instruction(stmt.kind);
+            match &stmt.kind {
+                Instruction::Const(name, None, expr) if bound_once(name) => {
+                    if let Some(val) = self.evaluate_constant(expr) {
+                        self.known_constants.borrow_mut().insert(name.clone(), val);
+                    }
+                },
+                Instruction::Function { name, params, ret_type, body, is_async: false } if bound_once(name) => {
+                    // La docstring n'est pas compilée : elle ne compte pas dans le corps
+                    let body = if crate::ast::nodes::docstring(body).is_some() { &body[1..] } else { &body[..] };
+                    let candidate = super::optimizer::inline_candidate(params, ret_type, body, &self.known_constants.borrow());
+                    if let Some(candidate) = candidate {
+                        self.inline_functions.borrow_mut().insert(name.clone(), candidate);
+                    }
+                },
+                Instruction::Import(path, None) => {
+                    // Constantes publiques du module, si le fichier courant ne lie jamais ces noms
+                    for (name, val) in self.module_constants(path) {
+                        if !bindings.contains_key(&name) {
+                            self.known_constants.borrow_mut().insert(name, val);
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    // Lit un module importé et évalue ses `const` littérales de premier niveau (hors noms privés)
+    fn module_constants(&self, path: &str) -> Vec<(String, Value)> {
+        let Ok(candidates) = crate::project::import_candidates(path) else { return Vec::new() };
+        let Some(source) = candidates.iter().find_map(|candidate| std::fs::read_to_string(candidate).ok()) else { return Vec::new() };
+        let Ok(json_ast) = crate::compiler::compile(&source) else { return Vec::new() };
+        let Ok(statements) = crate::loader::parse_block(&json_ast) else { return Vec::new() };
+
+        let bindings = crate::checker::binding_counts(&statements);
+        let module_compiler = Compiler::new_with_globals(Rc::new(RefCell::new(HashMap::new())));
+
+        // Un module qui utilise `export` ne rend publiques que les constantes exportées
+        let has_exports = statements.iter().any(|stmt| matches!(stmt.kind, Instruction::Export(_)));
+        let mut constants = Vec::new();
+        for stmt in &statements {
+            let decl = match &stmt.kind {
+                Instruction::Export(inner) => inner.as_ref(),
+                other if !has_exports => other,
+                _ => continue,
+            };
+            if let Instruction::Const(name, None, expr) = decl
+                && !name.starts_with('_')
+                && bindings.get(name) == Some(&1)
+                && let Some(val) = module_compiler.evaluate_constant(expr) {
+                module_compiler.known_constants.borrow_mut().insert(name.clone(), val.clone());
+                constants.push((name.clone(), val));
+            }
+        }
+        constants
+    }
+
+    // Appel d'une fonction triviale : retourne (nom, corps avec les arguments substitués)
+    fn inline_call(&self, callee: &Expression, args: &[Expression]) -> Option<(String, Expression)> {
+        if self.opt_level < 2 {
+            return None;
+        }
+
+        let Expression::Variable(name) = callee else { return None };
+        if self.locals.contains_key(name) {
+            return None;
+        }
+
+        let functions = self.inline_functions.borrow();
+        let (params, body) = functions.get(name)?;
+
+        // Les arguments peuvent être dupliqués ou réordonnés : ils doivent être purs
+        if params.len() != args.len() || !args.iter().all(|a| super::optimizer::is_pure(a, None)) {
+            return None;
+        }
+
+        let bindings: HashMap<String, Expression> = params.iter().cloned().zip(args.iter().cloned()).collect();
+        Some((name.clone(), super::optimizer::substitute(body, &bindings)))
+    }
+
+    // Compilateur d'un corps de fonction/méthode : mêmes globales, mêmes alias de type
+    fn child_compiler(&self) -> Compiler {
+        let mut c = Compiler::new_with_globals(self.globals.clone());
+        c.type_aliases = self.type_aliases.clone();
+        c.private_globals = self.private_globals.clone();
+        c.opt_level = self.opt_level;
+        c.inline_functions = self.inline_functions.clone();
+        c.known_constants = self.known_constants.clone();
+        c.lang_version = self.lang_version;
+        c.wide_jumps = self.wide_jumps;
+        c.scope_name = self.scope_name.clone();
+        c.strict = self.strict;
+        c.chunk.strict = self.strict;
+        c.warnings = self.warnings.clone();
+        c.declared_globals = self.declared_globals.clone();
+        c.enclosing_locals = self.enclosing_locals.clone();
+        c.enclosing_locals.extend(self.locals.iter().map(|(name, info)| (name.clone(), info.is_const)));
+        c.file = self.file.clone();
+        c.chunk.file = self.file.clone();
+        c.debug_info = self.debug_info;
+        c
+    }
+
+    // Nouvelle locale. Son nom est aussi noté dans le chunk à partir de l'offset courant :
+    // le débogueur retrouve ainsi le nom d'un slot réutilisé par un autre bloc
+    fn declare_local(&mut self, name: String, info: LocalInfo) {
+        self.chunk.local_names.push((self.chunk.code.len(), info.index, name.clone()));
+        self.locals.insert(name, info);
+    }
+
+    // Variable d'une fonction englobante utilisée ici : MakeClosure devra la capturer
+    fn note_free_var(&mut self, name: &str) {
+        if self.enclosing_locals.contains_key(name) && !self.chunk.free_vars.iter().any(|n| n == name) {
+            self.chunk.free_vars.push(name.to_string());
         }
-        (self.chunk, self.globals)
+    }
+
+    // Une closure imbriquée ne peut capturer que ce que sa fonction englobante a capturé :
+    // ses variables libres qui ne sont pas des locales d'ici le sont aussi ici
+    fn inherit_free_vars(&mut self, inner: &Chunk) {
+        for name in &inner.free_vars {
+            if !self.locals.contains_key(name) {
+                self.note_free_var(name);
+            }
+        }
+    }
+
+    // Plusieurs `init` : à chaque nombre d'arguments correspond au plus un constructeur
+    fn check_constructor_overloads(&self, class_name: &str, methods: &HashMap<String, Value>) -> Result<(), CompileError> {
+        let mut ranges: Vec<(usize, usize)> = methods.iter()
+            .filter(|(name, _)| is_constructor(name))
+            .filter_map(|(_, method)| match method {
+                // Sans `this`
+                Value::Function(f) => Some((f.required() - 1, if f.variadic { usize::MAX } else { f.params.len() - 1 })),
+                _ => None,
+            })
+            .collect();
+        ranges.sort();
+        for pair in ranges.windows(2) {
+            let ((_, first_max), (second_min, _)) = (pair[0], pair[1]);
+            if second_min <= first_max {
+                return Err(self.error(format!(
+                    "Constructors of '{}' overlap: more than one accepts {} arguments", class_name, second_min
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Locales visibles au MakeClosure qui suit : la VM y prend les variables à capturer
+    fn note_captures(&mut self) {
+        let visible = self.locals.iter().map(|(name, info)| (info.index, name.clone())).collect();
+        self.chunk.captures.insert(self.chunk.code.len(), visible);
+    }
+
+    fn emit_bind_self(&mut self, name: &str) {
+        let name_idx = self.chunk.add_constant(Value::String(name.to_string().into()));
+        self.emit_op(OpCode::BindSelf);
+        self.emit_short(name_idx);
+    }
+
+    // Même opération que l'expression binaire correspondante (/ suit --lang-version)
+    fn emit_compound_op(&mut self, op: CompoundOp) {
+        self.emit_op(match op {
+            CompoundOp::Add => OpCode::Add,
+            CompoundOp::Sub => OpCode::Sub,
+            CompoundOp::Mul => OpCode::Mul,
+            CompoundOp::Div if self.lang_version >= 2 => OpCode::FloatDiv,
+            CompoundOp::Div => OpCode::Div,
+        });
+    }
+
+    // Nom complet d'une fonction déclarée ici (ex: "Maths.square" dans un namespace)
+    fn qualified_name(&self, name: &str) -> String {
+        match &self.scope_name {
+            Some(scope) => format!("{}.{}", scope, name),
+            None => name.to_string(),
+        }
+    }
+
+    // Nom sous lequel une globale est réellement stockée (renommée si elle est privée au module)
+    fn global_key(&self, name: &str) -> String {
+        self.private_globals.borrow().get(name).cloned().unwrap_or_else(|| name.to_string())
+    }
+
+    // Constante de type pour CheckType, alias résolus (ex: "Id" -> "int|string")
+    fn type_constant(&mut self, annotation: &str) -> u16 {
+        let resolved = match TypeExpr::parse(annotation) {
+            Ok(ty) => ty.resolve(&self.type_aliases.borrow()).names().iter()
+                .map(|n| self.global_key(n))
+                .collect::<Vec<_>>()
+                .join("|"),
+            Err(_) => annotation.to_string(),
+        };
+        self.chunk.add_constant(Value::String(resolved.into()))
+    }
+
+    pub fn compile(self, statements: Vec<crate::ast::Statement>) -> Result<(Chunk, GlobalNames), CompileError> {
+        self.with_wide_jumps_fallback(statements, Self::compile_pass)
+    }
+
+    fn compile_pass(mut self, statements: Vec<crate::ast::Statement>) -> Result<(Chunk, GlobalNames), CompileError> {
+        self.chunk.file = self.file.clone();
+        self.chunk.strict = self.strict;
+
+        // Mode strict : une erreur de type trouvée statiquement empêche l'exécution
+        if self.strict || statements.iter().any(|stmt| matches!(stmt.kind, Instruction::Use(_))) {
+            let errors = crate::typecheck::check(&statements);
+            if let Some(first) = errors.first() {
+                let more = match errors.len() {
+                    1 => String::new(),
+                    n => format!(" (and {} more, see `aegis check`)", n - 1),
+                };
+                return Err(CompileError { message: format!("Type error: {}{}", first.message, more), line: first.line });
+            }
+        }
+        self.collect_declared_globals(&statements);
+        if self.opt_level >= 2 {
+            self.prepare_optimizations(&statements);
+        }
+
+        for stmt in statements {
+            self.compile_statement(stmt)?;
+        }
+        Ok((self.chunk, self.globals))
     } 
 
+    // Variante REPL : si la dernière instruction est une expression, on ne la dépile pas.
+    // Sa valeur reste sur la pile et VM::execute_chunk la retourne.
+    pub fn compile_repl(self, statements: Vec<crate::ast::Statement>) -> Result<(Chunk, GlobalNames), CompileError> {
+        self.with_wide_jumps_fallback(statements, Self::compile_repl_pass)
+    }
+
+    fn compile_repl_pass(mut self, mut statements: Vec<crate::ast::Statement>) -> Result<(Chunk, GlobalNames), CompileError> {
+        let trailing = match statements.last() {
+            Some(stmt) if matches!(stmt.kind, Instruction::ExpressionStatement(_)) => statements.pop(),
+            _ => None,
+        };
+
+        self.chunk.strict = self.strict;
+        self.collect_declared_globals(&statements);
+        for stmt in statements {
+            self.compile_statement(stmt)?;
+        }
+
+        if let Some(stmt) = trailing
+            && let Instruction::ExpressionStatement(expr) = stmt.kind {
+            self.current_line = stmt.line;
+            self.compile_expression(expr)?;
+        }
+
+        Ok((self.chunk, self.globals))
+    }
+
+    // var x = ... / x = ... : locale connue, nouvelle locale dans une fonction, sinon globale
+    fn compile_set(&mut self, var_name: String, type_annot: Option<String>, expr: Expression, block_local: bool) -> Result<(), CompileError> {
+        // A. Check Locals
+        if let Some(info) = self.locals.get(&var_name) {
+            if info.is_const {
+                return Err(self.error(format!("Cannot reassign local constant '{}'", var_name)));
+            }
+        }
+        
+        // B. Check Globals (Scope courant)
+        if self.global_constants.contains(&var_name) {
+            return Err(self.error(format!("Cannot reassign constant '{}'", var_name)));
+        }
+
+        // `var fact = func(n) { ... fact(n - 1) }` : la locale n'existe pas encore quand la
+        // closure est créée, elle ne peut pas la capturer. Comme une fonction nommée, elle se voit
+        // alors sous ce nom dans son propre corps
+        let binds_self = matches!(expr, Expression::Function { name: None, .. })
+            && !self.locals.contains_key(&var_name)
+            && (self.scope_depth > 0 || block_local);
+
+        self.compile_expression(expr)?; // La valeur calculée est maintenant sur la pile [val]
+        if binds_self {
+            self.emit_bind_self(&var_name);
+        }
+
+        if let Some(type_name) = type_annot {
+            let type_idx = self.type_constant(&type_name);
+            self.emit_op(OpCode::CheckType);
+            self.emit_short(type_idx);
+        }
+
+        // CAS 1 : C'est une variable locale DÉJÀ connue (Assignation : x = 5)
+        if let Some(info) = self.locals.get(&var_name) {
+            let idx = info.index;
+            self.emit_op(OpCode::SetLocal);
+            self.emit_short(idx);
+            self.emit_op(OpCode::Pop); // Nettoyage : On retire la valeur car c'est une instruction (statement)
+        } 
+        // CAS 2 : On est dans une fonction (ou un corps de boucle), c'est une NOUVELLE variable (Déclaration : var res = ...)
+        else if self.scope_depth > 0 || block_local {
+            let idx = self.locals.len() as u16; // Le prochain slot libre sur la pile
+            self.declare_local(var_name.clone(), LocalInfo {
+                index: idx,
+                is_const: false
+            });
+            
+            // ASTUCE MAGIQUE DE LA PILE :
+            // On ne fait RIEN d'autre. La valeur [val] est déjà au sommet de la pile.
+            // En l'enregistrant dans 'self.locals' à l'index 'idx', on dit au compilateur :
+            // "La valeur qui est actuellement sur la pile est maintenant la variable 'res'".
+            // Elle y restera jusqu'à la fin de la fonction.
+        } 
+        // CAS 3 : C'est une Globale (Assignation ou Déclaration globale)
+        else {
+            let id = self.resolve_global(&var_name);
+            self.emit_op(OpCode::SetGlobal); // SetGlobal fait déjà un Pop dans la VM
+            self.emit_short(id);
+        }
+
+        Ok(())
+    }
+    // Noms qu'une affectation `x = ...` au premier niveau peut cibler sans avertissement :
+    // globales déjà connues (natives, lignes précédentes du REPL) et déclarations du fichier.
+    // Appelé à chaque passe : les avertissements d'une passe abandonnée sont oubliés.
+    fn collect_declared_globals(&mut self, statements: &[crate::ast::Statement]) {
+        self.warnings.borrow_mut().clear();
+        let mut declared = self.declared_globals.borrow_mut();
+        declared.extend(self.globals.borrow().keys().cloned());
+        for stmt in statements {
+            if !matches!(stmt.kind, Instruction::Assign(..)) {
+                declared.extend(super::bound_names(&stmt.kind));
+            }
+        }
+    }
+
+    // `x = ...` crée une variable : avertissement, ou erreur en mode strict
+    fn implicit_declaration(&mut self, name: &str, is_global: bool) -> Result<(), CompileError> {
+        let message = if is_global {
+            format!("Assignment to '{}' inside a function creates a new local variable instead of updating the global (declare it with `var` or `let`)", name)
+        } else {
+            format!("Assignment to undeclared variable '{}' (declare it with `var` or `let`)", name)
+        };
+        if self.strict {
+            return Err(self.error(message));
+        }
+        self.warnings.borrow_mut().push(Diagnostic { line: self.current_line, message });
+        Ok(())
+    }
+
+    // Les sauts sont émis sur 16 bits. Si l'un d'eux déborde (fichier généré, fonction géante),
+    // on recompile tout en sauts 32 bits : la taille des sauts n'est connue qu'une fois le code émis.
+    fn with_wide_jumps_fallback<F>(self, statements: Vec<crate::ast::Statement>, pass: F) -> Result<(Chunk, GlobalNames), CompileError>
+    where
+        F: Fn(Compiler, Vec<crate::ast::Statement>) -> Result<(Chunk, GlobalNames), CompileError>,
+    {
+        if self.wide_jumps {
+            return pass(self, statements);
+        }
+
+        let fallback = (self.clone(), statements.clone());
+        match pass(self, statements) {
+            Err(e) if e.is_jump_overflow() => {
+                let (mut compiler, statements) = fallback;
+                compiler.wide_jumps = true;
+                pass(compiler, statements)
+            },
+            result => result,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> CompileError {
+        CompileError { message: message.into(), line: self.current_line }
+    }
+
+    // Chaque instruction, même imbriquée, met à jour la ligne (messages d'erreur et table des lignes)
+    fn compile_statement(&mut self, stmt: crate::ast::Statement) -> Result<(), CompileError> {
+        self.current_line = stmt.line;
+        self.compile_instruction(stmt.kind)?;
+
+        // Au-delà, les index u16 de LoadConst, GetGlobal & co reboucleraient silencieusement
+        if self.chunk.constants.len() > MAX_INDEX {
+            return Err(self.error(format!("Too many constants in one function (max {})", MAX_INDEX)));
+        }
+        if self.locals.len() > MAX_INDEX {
+            return Err(self.error(format!("Too many local variables in one function (max {})", MAX_INDEX)));
+        }
+        if self.globals.borrow().len() > MAX_INDEX {
+            return Err(self.error(format!("Too many global variables (max {})", MAX_INDEX)));
+        }
+        Ok(())
+    }
+
     fn emit_byte(&mut self, byte: u8) {
-        self.chunk.write(byte, self.current_line);
+        let line = if self.debug_info { self.current_line } else { 0 };
+        self.chunk.write(byte, line);
+    }
+
+    // Opérande sur 2 octets (index de constante, de globale ou de locale), poids fort en premier
+    fn emit_short(&mut self, value: u16) {
+        for byte in value.to_be_bytes() {
+            self.emit_byte(byte);
+        }
     }
     
+    // Nombre d'arguments ou d'éléments (Call, Method, Super, MakeList, MakeDict, MakeEnum) sur
+    // 2 octets, comme les index : au-delà, erreur de compilation plutôt qu'un compte tronqué
+    fn emit_count(&mut self, count: usize, what: &str) -> Result<(), CompileError> {
+        let count = u16::try_from(count)
+            .map_err(|_| self.error(format!("Too many {} (max {})", what, u16::MAX)))?;
+        self.emit_short(count);
+        Ok(())
+    }
+
     fn emit_op(&mut self, op: OpCode) {
         self.emit_byte(op as u8);
     }
@@ -103,78 +612,105 @@ impl Compiler {
     fn emit_constant(&mut self, val: Value) {
         let idx = self.chunk.add_constant(val);
         self.emit_op(OpCode::LoadConst);
-        self.emit_byte(idx);
+        self.emit_short(idx);
     }
 
-    fn resolve_global(&mut self, name: &str) -> u8 {
+    // Lie la valeur au sommet de la pile (module ou membre importé) à `name`
+    fn bind_imported(&mut self, name: String) {
+        if let Some(info) = self.locals.get(&name) {
+            let idx = info.index;
+            self.emit_op(OpCode::SetLocal);
+            self.emit_short(idx);
+            self.emit_op(OpCode::Pop);
+        } else if self.scope_depth > 0 {
+            // Comme pour 'var' : la valeur sur la pile devient la locale
+            let idx = self.locals.len() as u16;
+            self.declare_local(name, LocalInfo { index: idx, is_const: false });
+        } else {
+            let id = self.resolve_global(&name);
+            self.emit_op(OpCode::SetGlobal);
+            self.emit_short(id);
+        }
+    }
+
+    fn resolve_global(&mut self, name: &str) -> u16 {
+        let key = self.global_key(name);
         let mut globals = self.globals.borrow_mut();
-        if let Some(&id) = globals.get(name) {
+        if let Some(&id) = globals.get(&key) {
             return id;
         }
-        let id = globals.len() as u8;
-        globals.insert(name.to_string(), id);
+        let id = globals.len() as u16;
+        globals.insert(key, id);
         id
     }
 
-    fn compile_expression(&mut self, expr: Expression) {
+    fn compile_expression(&mut self, expr: Expression) -> Result<(), CompileError> {
+        if let Expression::Call(callee, args) = &expr
+            && let Some((name, inlined)) = self.inline_call(callee, args) {
+            self.chunk.annotations.insert(self.chunk.code.len(), format!("inlined {}()", name));
+            self.compile_expression(inlined)?;
+            return Ok(());
+        }
+
         if let Some(val) = self.evaluate_constant(&expr) {
             self.emit_constant(val);
-            return;
+            return Ok(());
         }
 
         match expr {
             Expression::Literal(val) => self.emit_constant(val),
             Expression::Add(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::Add);
             },
             Expression::Sub(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::Sub);
             },
             Expression::Mul(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::Mul);
             },
             Expression::Div(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
-                self.emit_op(OpCode::Div);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
+                self.emit_op(if self.lang_version >= 2 { OpCode::FloatDiv } else { OpCode::Div });
             },
             Expression::Variable(name) => {
                 // 1. On cherche d'abord dans les locales (si on est dans une fonction)
                 if let Some(info) = self.locals.get(&name) {
                     let idx = info.index;
                     self.emit_op(OpCode::GetLocal);
-                    self.emit_byte(idx);
+                    self.emit_short(idx);
                 } else {
-                    if self.scope_depth > 0 {
-                        let name_idx = self.chunk.add_constant(Value::String(name.clone()));
+                    if self.scope_depth > 0 && !self.private_globals.borrow().contains_key(&name) {
+                        self.note_free_var(&name);
+                        let name_idx = self.chunk.add_constant(Value::String(name.clone().into()));
                         self.emit_op(OpCode::GetFreeVar);
-                        self.emit_byte(name_idx);
+                        self.emit_short(name_idx);
                     } else {
                         let id = self.resolve_global(&name);
                         self.emit_op(OpCode::GetGlobal);
-                        self.emit_byte(id);
+                        self.emit_short(id);
                     }
                 }
             },
             Expression::LessThan(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::Less);
             },
             Expression::GreaterThan(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::Greater);
             },
             Expression::Equal(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::Equal);
             },
             Expression::Call(target, args) => {
@@ -182,119 +718,139 @@ impl Compiler {
                 let arg_count = args.len(); 
     
                 // A. D'abord on compile la fonction (pour qu'elle soit au fond de la pile)
-                self.compile_expression(*target);
+                self.compile_expression(*target)?;
 
                 // B. Ensuite on compile les arguments (qui s'empilent par-dessus)
                 for arg in args {
-                    self.compile_expression(arg);
+                    self.compile_expression(arg)?;
                 }
     
                 // ----------------------------------
     
                 // 4. Émettre CALL
+                self.last_call = Some(self.chunk.code.len());
                 self.emit_op(OpCode::Call);
-                self.emit_byte(arg_count as u8);
+                self.emit_count(arg_count, "arguments in one call")?;
             }
 
             Expression::Modulo(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::Modulo);
             },
+            Expression::FloorDiv(left, right) => {
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
+                self.emit_op(OpCode::FloorDiv);
+            },
             Expression::NotEqual(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::NotEqual);
             },
+            Expression::Same(left, right) => {
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
+                self.emit_op(OpCode::Same);
+            },
+            Expression::NotSame(left, right) => {
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
+                self.emit_op(OpCode::NotSame);
+            },
             Expression::LessEqual(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::LessEqual);
             },
             Expression::GreaterEqual(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::GreaterEqual);
             },
             // Bitwise
             Expression::BitAnd(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::BitAnd);
             },
              Expression::BitOr(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::BitOr);
             },
             Expression::BitXor(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::BitXor);
             },
             Expression::ShiftLeft(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::ShiftLeft);
             },
             Expression::ShiftRight(left, right) => {
-                self.compile_expression(*left);
-                self.compile_expression(*right);
+                self.compile_expression(*left)?;
+                self.compile_expression(*right)?;
                 self.emit_op(OpCode::ShiftRight);
             },
             Expression::Not(expr) => {
-                self.compile_expression(*expr);
+                self.compile_expression(*expr)?;
                 self.emit_op(OpCode::Not);
             },
+            Expression::Await(task) => {
+                self.compile_expression(*task)?;
+                self.emit_op(OpCode::Await);
+            },
 
             Expression::And(left, right) => {
-                self.compile_expression(*left);
+                self.compile_expression(*left)?;
                 // Si gauche est Faux, on saute tout de suite à la fin (résultat = Faux)
                 let end_jump = self.emit_jump(OpCode::JumpIfFalse);
                 self.emit_op(OpCode::Pop); // On pop le résultat de gauche
-                self.compile_expression(*right);
-                self.patch_jump(end_jump);
+                self.compile_expression(*right)?;
+                self.patch_jump(end_jump)?;
             },
             Expression::Or(left, right) => {
-                self.compile_expression(*left);
+                self.compile_expression(*left)?;
                 // Si gauche est Faux, on saute au "else" (qui évalue droite)
                 let else_jump = self.emit_jump(OpCode::JumpIfFalse);
                 let end_jump = self.emit_jump(OpCode::Jump); // Si Vrai, on saute à la fin
                 
-                self.patch_jump(else_jump);
+                self.patch_jump(else_jump)?;
                 self.emit_op(OpCode::Pop); // Pop le faux
-                self.compile_expression(*right);
-                self.patch_jump(end_jump);
+                self.compile_expression(*right)?;
+                self.patch_jump(end_jump)?;
             },
 
             Expression::Ternary(cond, then_expr, else_expr) => {
                 // 1. Condition
-                self.compile_expression(*cond);
+                self.compile_expression(*cond)?;
                 
                 // 2. Saut vers le Else si Faux
                 let else_jump = self.emit_jump(OpCode::JumpIfFalse);
                 
                 // 3. Si Vrai : On pop la condition (true) et on évalue le Then
                 self.emit_op(OpCode::Pop);
-                self.compile_expression(*then_expr);
+                self.compile_expression(*then_expr)?;
                 
                 // 4. Saut vers la fin (pour ne pas faire le Else)
                 let end_jump = self.emit_jump(OpCode::Jump);
                 
                 // 5. Label Else
-                self.patch_jump(else_jump);
+                self.patch_jump(else_jump)?;
                 self.emit_op(OpCode::Pop); // On pop la condition (false)
                 
                 // 6. Si Faux : On évalue le Else
-                self.compile_expression(*else_expr);
+                self.compile_expression(*else_expr)?;
                 
                 // 7. Label Fin
-                self.patch_jump(end_jump);
+                self.patch_jump(end_jump)?;
             },
 
             Expression::NullCoalescing(left, right) => {
                 // 1. Evaluer Gauche
-                self.compile_expression(*left); // Pile: [val]
+                self.compile_expression(*left)?; // Pile: [val]
                 
                 // 2. Dupliquer pour le test
                 self.emit_op(OpCode::Dup);      // Pile: [val, val]
@@ -302,7 +858,7 @@ impl Compiler {
                 // 3. Charger Null et Comparer
                 let null_idx = self.chunk.add_constant(Value::Null);
                 self.emit_op(OpCode::LoadConst);
-                self.emit_byte(null_idx);       // Pile: [val, val, null]
+                self.emit_short(null_idx);       // Pile: [val, val, null]
                 self.emit_op(OpCode::Equal);    // Pile: [val, is_null]
                 
                 // 4. Si c'est FAUX (donc pas null), on saute le bloc "Remplacement"
@@ -317,76 +873,96 @@ impl Compiler {
                 self.emit_op(OpCode::Pop); // On retire la valeur 'null'
                 
                 // On évalue la partie droite
-                self.compile_expression(*right); // Pile: [res_droite]
+                self.compile_expression(*right)?; // Pile: [res_droite]
                 
                 // On doit sauter par-dessus le code de nettoyage de l'autre branche
                 let jump_end = self.emit_jump(OpCode::Jump);
                 
                 // --- CHEMIN : CE N'EST PAS NULL (is_null était Faux) ---
-                self.patch_jump(jump_over); // On atterrit ici si le jump a été pris
+                self.patch_jump(jump_over)?; // On atterrit ici si le jump a été pris
                 
                 // Pile : [val, is_null (false)]
                 self.emit_op(OpCode::Pop); // On retire le booléen 'false'
                 // Pile : [val] -> C'est ce qu'on veut !
                 
                 // --- FIN ---
-                self.patch_jump(jump_end);
+                self.patch_jump(jump_end)?;
             },
 
             Expression::List(exprs) => {
                 for expr in exprs.iter() {
-                    self.compile_expression(expr.clone());
+                    self.compile_expression(expr.clone())?;
                 }
                 self.emit_op(OpCode::MakeList);
-                self.emit_byte(exprs.len() as u8);
+                self.emit_count(exprs.len(), "elements in a list literal")?;
+            },
+            Expression::Interpolation(parts) => {
+                // Une seule partie ("${x}") : concaténée à "" pour donner une chaîne
+                let single = parts.len() == 1;
+                if single {
+                    let empty_idx = self.chunk.add_constant(Value::String(String::new().into()));
+                    self.emit_op(OpCode::LoadConst);
+                    self.emit_short(empty_idx);
+                }
+                for (i, part) in parts.into_iter().enumerate() {
+                    self.compile_expression(part)?;
+                    if i > 0 || single {
+                        self.emit_op(OpCode::Concat);
+                    }
+                }
             },
             Expression::Dict(items) => {
                 let count = items.len(); // Sauvegarde avant consommation
 
                 for (key, val) in items {
-                    let key_idx = self.chunk.add_constant(Value::String(key.clone()));
+                    let key_idx = self.chunk.add_constant(key.to_value());
                     self.emit_op(OpCode::LoadConst);
-                    self.emit_byte(key_idx);
-                    self.compile_expression(val.clone());
+                    self.emit_short(key_idx);
+                    self.compile_expression(val.clone())?;
                 }
                 self.emit_op(OpCode::MakeDict);
-                self.emit_byte((count * 2) as u8); // Utilisation de la variable sauvegardée
+                self.emit_count(count * 2, "values in a dict literal (keys and values)")?;
             },
 
             Expression::GetAttr(obj, name) => {
-                self.compile_expression(*obj);
-                let name_idx = self.chunk.add_constant(Value::String(name));
+                self.compile_expression(*obj)?;
+                let name_idx = self.chunk.add_constant(Value::String(name.into()));
                 self.emit_op(OpCode::GetAttr);
-                self.emit_byte(name_idx);
+                self.emit_short(name_idx);
+            },
+            Expression::GetIndex(obj, index) => {
+                self.compile_expression(*obj)?;
+                self.compile_expression(*index)?;
+                self.emit_op(OpCode::GetIndex);
             },
             Expression::CallMethod(obj, name, args) => {
                 let arg_count = args.len(); // Sauvegarde
 
                 // 1. Compiler l'objet
-                self.compile_expression(*obj);
+                self.compile_expression(*obj)?;
                 
                 // 2. Compiler les arguments
                 for arg in args {
-                    self.compile_expression(arg.clone());
+                    self.compile_expression(arg.clone())?;
                 }
                 
                 // 3. Émettre l'instruction
-                let name_idx = self.chunk.add_constant(Value::String(name));
+                let name_idx = self.chunk.add_constant(Value::String(name.into()));
                 self.emit_op(OpCode::Method);
-                self.emit_byte(name_idx);
-                self.emit_byte(arg_count as u8); // Utilisation
+                self.emit_short(name_idx);
+                self.emit_count(arg_count, "arguments in one call")?;
             },
             Expression::New(class_expr, args) => {
                 let arg_count = args.len(); // Sauvegarde
 
-                self.compile_expression(*class_expr);
+                self.compile_expression(*class_expr)?;
                 
                 for arg in args {
-                    self.compile_expression(arg.clone());
+                    self.compile_expression(arg.clone())?;
                 }
                 
                 self.emit_op(OpCode::Call); // Ou OpCode::New si tu en as créé un
-                self.emit_byte(arg_count as u8); // Utilisation
+                self.emit_count(arg_count, "arguments in one call")?;
             },
 
             Expression::SuperCall(method, args) => {
@@ -394,211 +970,266 @@ impl Compiler {
                 let parent_name = if let Some(p) = &self.context_parent_name {
                     p.clone()
                 } else {
-                    panic!("'super' utilisé hors d'une classe avec héritage.");
+                    return Err(self.error("'super' used outside of a subclass method"));
                 };
 
                 // 2. On empile 'this' (toujours l'argument 0 d'une méthode)
                 self.emit_op(OpCode::GetLocal);
-                self.emit_byte(0);
+                self.emit_short(0);
 
                 // 3. On empile les arguments
                 let arg_count = args.len();
                 for arg in args {
-                    self.compile_expression(arg);
+                    self.compile_expression(arg)?;
                 }
 
                 // 4. On émet l'instruction SUPER
-                let name_idx = self.chunk.add_constant(Value::String(method));
-                let parent_idx = self.chunk.add_constant(Value::String(parent_name));
+                let name_idx = self.chunk.add_constant(Value::String(method.into()));
+                let parent_idx = self.chunk.add_constant(Value::String(parent_name.into()));
 
                 self.emit_op(OpCode::Super);
-                self.emit_byte(name_idx);
-                self.emit_byte(arg_count as u8);
-                self.emit_byte(parent_idx);
+                self.emit_short(name_idx);
+                self.emit_count(arg_count, "arguments in one call")?;
+                self.emit_short(parent_idx);
             },
 
-            Expression::Function { params, ret_type, body } => {
-                let mut func_compiler = Compiler::new_with_globals(self.globals.clone());
-                func_compiler.scope_depth = 1;
-
-                for (i, (param_name, _)) in params.iter().enumerate() {
-                    func_compiler.locals.insert(param_name.clone(), LocalInfo {
-                        index: i as u8,
-                        is_const: false
-                    });
-                }
-                for stmt in body {
-                    func_compiler.compile_instruction(stmt.kind);
-                }
-                func_compiler.emit_op(OpCode::LoadConst);
-                let null_idx = func_compiler.chunk.add_constant(Value::Null);
-                func_compiler.emit_byte(null_idx);
-                func_compiler.emit_op(OpCode::Return);
-
-                for (name, info) in &func_compiler.locals {
-                    func_compiler.chunk.locals_map.insert(info.index, name.clone());
+            Expression::Function { params, ret_type, body, name } => {
+                self.compile_closure(name.as_deref().unwrap_or("<lambda>"), params, ret_type, body)?;
+                if let Some(name) = name {
+                    self.emit_bind_self(&name);
                 }
-
-                let func_chunk = func_compiler.chunk;
-                let compiled_val = Value::Function(Rc::new(FunctionData {
-                    params: params.clone(),
-                    ret_type: ret_type.clone(),
-                    chunk: func_chunk,
-                    env: None
-                }));
-                let const_idx = self.chunk.add_constant(compiled_val);
-
-                self.emit_op(OpCode::LoadConst);
-                self.emit_byte(const_idx);
-
-                self.emit_op(OpCode::MakeClosure);
             },
             Expression::Range(start, end) => {
-                self.compile_expression(*start);
-                self.compile_expression(*end);
+                self.compile_expression(*start)?;
+                self.compile_expression(*end)?;
                 self.emit_op(OpCode::MakeRange);
             },
         }
+
+        Ok(())
+    }
+
+    // Fonction anonyme (lambda, corps d'un defer) : compilée à part puis capturée par MakeClosure
+    fn compile_closure(&mut self, name: &str, params: Vec<(String, Option<String>)>, ret_type: Option<String>, mut body: Vec<Statement>) -> Result<(), CompileError> {
+        let doc = crate::ast::nodes::take_docstring(&mut body);
+        let prologue = crate::ast::nodes::take_param_prologue(&mut body);
+        let mut func_compiler = self.child_compiler();
+        func_compiler.scope_depth = 1;
+        func_compiler.chunk.name = self.qualified_name(name);
+        func_compiler.current_return_type = ret_type.clone();
+
+        let (variadic, entry_points) = func_compiler.compile_params(&params, prologue)?;
+        for stmt in body {
+            func_compiler.compile_statement(stmt)?;
+        }
+        func_compiler.emit_op(OpCode::LoadConst);
+        let null_idx = func_compiler.chunk.add_constant(Value::Null);
+        func_compiler.emit_short(null_idx);
+        func_compiler.emit_op(OpCode::Return);
+
+        for (name, info) in &func_compiler.locals {
+            func_compiler.chunk.locals_map.insert(info.index, name.clone());
+        }
+
+        self.inherit_free_vars(&func_compiler.chunk);
+        let func_chunk = func_compiler.chunk;
+        let compiled_val = Value::Function(Rc::new(FunctionData {
+            params,
+            ret_type,
+            chunk: func_chunk,
+            env: None,
+            doc,
+            variadic,
+            entry_points,
+        }));
+        let const_idx = self.chunk.add_constant(compiled_val);
+
+        self.emit_op(OpCode::LoadConst);
+        self.emit_short(const_idx);
+
+        self.note_captures();
+        self.emit_op(OpCode::MakeClosure);
+
+        Ok(())
+    }
+
+    // Début d'une fonction : les paramètres sont ses premières locales. Le code des valeurs par
+    // défaut vient d'abord (la VM y entre après celles des arguments passés), puis la
+    // vérification des paramètres typés. Renvoie (variadic, entry_points) pour FunctionData.
+    fn compile_params(&mut self, params: &[(String, Option<String>)], prologue: Vec<Statement>) -> Result<(bool, Vec<usize>), CompileError> {
+        for (i, (param_name, _)) in params.iter().enumerate() {
+            self.declare_local(param_name.clone(), LocalInfo {
+                index: i as u16,
+                is_const: false
+            });
+        }
+
+        let mut variadic = false;
+        let mut entry_points = Vec::new();
+        for stmt in prologue {
+            self.current_line = stmt.line;
+            match stmt.kind {
+                Instruction::DefaultParam(name, expr) => {
+                    let index = params.iter().position(|(param, _)| *param == name)
+                        .ok_or_else(|| self.error(format!("Unknown parameter '{}'", name)))?;
+                    entry_points.push(self.chunk.code.len());
+                    self.compile_expression(expr)?;
+                    self.emit_op(OpCode::SetLocal);
+                    self.emit_short(index as u16);
+                    self.emit_op(OpCode::Pop);
+                },
+                Instruction::RestParam(_) => variadic = true,
+                _ => {},
+            }
+        }
+        if !entry_points.is_empty() {
+            entry_points.push(self.chunk.code.len());
+        }
+
+        for (i, (_, param_type)) in params.iter().enumerate() {
+            // Au début de la fonction, les arguments sont déjà sur la pile (locales) :
+            // on les charge, on les vérifie, puis on nettoie la copie
+            if let Some(t) = param_type {
+                self.emit_op(OpCode::GetLocal);
+                self.emit_short(i as u16);
+                let type_idx = self.type_constant(t);
+                self.emit_op(OpCode::CheckType);
+                self.emit_short(type_idx);
+                self.emit_op(OpCode::Pop);
+            }
+        }
+        Ok((variadic, entry_points))
     }
 
-    pub fn compile_instruction(&mut self, instr: Instruction) {
+    pub fn compile_instruction(&mut self, instr: Instruction) -> Result<(), CompileError> {
         match instr {
             Instruction::Print(expr) => {
-                self.compile_expression(expr);
+                self.compile_expression(expr)?;
                 self.emit_op(OpCode::Print);
             },
             Instruction::Return(expr) => {
-                self.compile_expression(expr); // 1. Calcule la valeur de retour
+                self.compile_expression(expr)?; // 1. Calcule la valeur de retour
 
-                if let Some(ret_type) = &self.current_return_type {
-                    let type_idx = self.chunk.add_constant(Value::String(ret_type.clone()));
+                if let Some(ret_type) = self.current_return_type.clone() {
+                    let type_idx = self.type_constant(&ret_type);
                     self.emit_op(OpCode::CheckType);
-                    self.emit_byte(type_idx);
+                    self.emit_short(type_idx);
                 }
 
+                // `return f(x)` : l'appel remplace la frame au lieu de s'empiler dessus.
+                // Le Return reste derrière pour les cas où la VM ne peut pas réutiliser la frame
+                if self.try_depth == 0 && self.with_depth == 0
+                    && self.last_call.is_some_and(|at| at + 3 == self.chunk.code.len()) {
+                    let at = self.last_call.take().unwrap_or_default();
+                    self.chunk.code[at] = OpCode::TailCall as u8;
+                }
                 self.emit_op(OpCode::Return);  // 2. Quitte la fonction
             },
-            Instruction::Set(var_name, type_annot, expr) => {
-                // A. Check Locals
-                if let Some(info) = self.locals.get(&var_name) {
-                    if info.is_const {
-                        panic!("Erreur: Impossible de modifier la constante locale '{}'", var_name);
+            Instruction::Assign(var_name, expr) => {
+                // Locale d'une fonction englobante (x = ... dans une closure) : la même variable
+                // que là-bas, partagée par MakeClosure
+                if !self.locals.contains_key(&var_name) && let Some(&is_const) = self.enclosing_locals.get(&var_name) {
+                    if is_const {
+                        return Err(self.error(format!("Cannot reassign local constant '{}'", var_name)));
                     }
+                    self.compile_expression(expr)?;
+                    self.note_free_var(&var_name);
+                    let name_idx = self.chunk.add_constant(Value::String(var_name.into()));
+                    self.emit_op(OpCode::SetFreeVar); // Dépile la valeur, comme SetGlobal
+                    self.emit_short(name_idx);
+                    return Ok(());
                 }
-                
-                // B. Check Globals (Scope courant)
-                if self.global_constants.contains(&var_name) {
-                    panic!("Erreur: Impossible de modifier la constante globale '{}'", var_name);
-                }
-
-                self.compile_expression(expr); // La valeur calculée est maintenant sur la pile [val]
-
-                if let Some(type_name) = type_annot {
-                    let type_idx = self.chunk.add_constant(Value::String(type_name));
-                    self.emit_op(OpCode::CheckType);
-                    self.emit_byte(type_idx);
+                // Ni locale ni globale déclarée : l'affectation déclare une variable (souvent une
+                // faute de frappe). Vérifié avant de compiler la valeur, qui peut lire ce nom.
+                if !self.locals.contains_key(&var_name) {
+                    let is_global = self.declared_globals.borrow().contains(&var_name);
+                    if self.scope_depth > 0 || !is_global {
+                        self.implicit_declaration(&var_name, is_global)?;
+                    }
                 }
-
-                // CAS 1 : C'est une variable locale DÉJÀ connue (Assignation : x = 5)
-                if let Some(info) = self.locals.get(&var_name) {
-                    let idx = info.index;
-                    self.emit_op(OpCode::SetLocal);
-                    self.emit_byte(idx);
-                    self.emit_op(OpCode::Pop); // Nettoyage : On retire la valeur car c'est une instruction (statement)
-                } 
-                // CAS 2 : On est dans une fonction, c'est une NOUVELLE variable (Déclaration : var res = ...)
-                else if self.scope_depth > 0 {
-                    let idx = self.locals.len() as u8; // Le prochain slot libre sur la pile
-                    self.locals.insert(var_name.clone(), LocalInfo {
-                        index: idx,
-                        is_const: false
-                    });
-                    
-                    // ASTUCE MAGIQUE DE LA PILE :
-                    // On ne fait RIEN d'autre. La valeur [val] est déjà au sommet de la pile.
-                    // En l'enregistrant dans 'self.locals' à l'index 'idx', on dit au compilateur :
-                    // "La valeur qui est actuellement sur la pile est maintenant la variable 'res'".
-                    // Elle y restera jusqu'à la fin de la fonction.
-                } 
-                // CAS 3 : C'est une Globale (Assignation ou Déclaration globale)
-                else {
-                    let id = self.resolve_global(&var_name);
-                    self.emit_op(OpCode::SetGlobal); // SetGlobal fait déjà un Pop dans la VM
-                    self.emit_byte(id);
+                self.compile_set(var_name, None, expr, false)?;
+            },
+            Instruction::Set(var_name, type_annot, expr) => {
+                // `var` dans un corps de boucle de premier niveau : locale au tour de boucle, pour
+                // que chaque closure créée dans la boucle garde sa propre valeur
+                let block_local = self.scope_depth == 0 && !self.loop_stack.is_empty();
+                // Déclaration globale dans un bloc de premier niveau (if...)
+                if self.scope_depth == 0 && !block_local && !self.locals.contains_key(&var_name) {
+                    self.declared_globals.borrow_mut().insert(var_name.clone());
                 }
+                self.compile_set(var_name, type_annot, expr, block_local)?;
             },
 
             Instruction::If { condition, body, else_body } => {
-                self.compile_if(condition, body, else_body);
+                self.compile_if(condition, body, else_body)?;
+            },
+
+            Instruction::Group(body) => {
+                for stmt in body {
+                    self.compile_statement(stmt)?;
+                }
             },
 
             Instruction::While { condition, body } => {
-                self.compile_while(condition, body);
+                self.compile_while(condition, body)?;
             },
             
-            Instruction::Function { name, params, ret_type, body } => {
+            Instruction::Function { name, params, ret_type, mut body, is_async } => {
+                let doc = crate::ast::nodes::take_docstring(&mut body);
+                let prologue = crate::ast::nodes::take_param_prologue(&mut body);
                 // 1. Compilation du corps de la fonction (Inchangé)
-                let mut func_compiler = Compiler::new_with_globals(self.globals.clone());
+                let mut func_compiler = self.child_compiler();
                 func_compiler.scope_depth = 1;
+                func_compiler.chunk.name = self.qualified_name(&name);
+                func_compiler.chunk.is_async = is_async;
+                func_compiler.current_return_type = ret_type.clone();
 
-                for (i, (param_name, param_type)) in params.iter().enumerate() {
-                    func_compiler.locals.insert(param_name.clone(), LocalInfo {
-                        index: i as u8,
-                        is_const: false
-                    });
-
-                    if let Some(t) = param_type {
-                        // Au début de la fonction, les arguments sont déjà sur la pile (locales).
-                        // On doit les charger, les checker, et les poper (juste pour le check).
-                        
-                        // 1. Lire la variable locale
-                        func_compiler.emit_op(OpCode::GetLocal);
-                        func_compiler.emit_byte(i as u8);
-                        
-                        // 2. Checker
-                        let type_idx = func_compiler.chunk.add_constant(Value::String(t.clone()));
-                        func_compiler.emit_op(OpCode::CheckType);
-                        func_compiler.emit_byte(type_idx);
-                        
-                        // 3. Nettoyer la pile (on a dupliqué via GetLocal)
-                        func_compiler.emit_op(OpCode::Pop);
-                    }
-                }
+                let (variadic, entry_points) = func_compiler.compile_params(&params, prologue)?;
 
                 for stmt in body {
-                    func_compiler.compile_instruction(stmt.kind);
+                    func_compiler.compile_statement(stmt)?;
                 }
 
                 func_compiler.emit_op(OpCode::LoadConst);
                 let null_idx = func_compiler.chunk.add_constant(Value::Null);
-                func_compiler.emit_byte(null_idx);
+                func_compiler.emit_short(null_idx);
                 func_compiler.emit_op(OpCode::Return);
 
                 for (name, info) in &func_compiler.locals {
                     func_compiler.chunk.locals_map.insert(info.index, name.clone());
                 }
 
+                self.inherit_free_vars(&func_compiler.chunk);
                 let func_chunk = func_compiler.chunk;
                 let compiled_val = Value::Function(Rc::new(FunctionData {
                     params: params.clone(),
                     ret_type: ret_type.clone(),
                     chunk: func_chunk,
-                    env: None
+                    env: None,
+                    doc,
+                    variadic,
+                    entry_points,
                 }));
 
                 // 2. Chargement de la fonction sur la pile (Inchangé)
                 let const_idx = self.chunk.add_constant(compiled_val);
                 self.emit_op(OpCode::LoadConst);
-                self.emit_byte(const_idx);
+                self.emit_short(const_idx);
                 
                 // On la transforme en closure (pour capturer l'env si besoin)
+                self.note_captures();
                 self.emit_op(OpCode::MakeClosure);
+                // Imbriquée, elle ne se voit pas dans les locales qu'elle a capturées : pour
+                // qu'elle puisse s'appeler récursivement, on l'ajoute à son environnement
+                if self.scope_depth > 0 {
+                    self.emit_bind_self(&name);
+                }
 
                 // 3. --- MODIFICATION : Stockage (Global ou Local) ---
                 if self.scope_depth > 0 {
                     // Cas Namespace ou Fonction imbriquée : C'est une locale
-                    let idx = self.locals.len() as u8;
-                    self.locals.insert(name.clone(), LocalInfo {
+                    let idx = self.locals.len() as u16;
+                    self.declare_local(name.clone(), LocalInfo {
                         index: idx,
                         is_const: false
                     });
@@ -608,55 +1239,62 @@ impl Compiler {
                     // Cas Script Principal : C'est une globale
                     let global_id = self.resolve_global(&name);
                     self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(global_id);
+                    self.emit_short(global_id);
                 }
             },
 
             Instruction::Switch { value, cases, default } => {
-                self.compile_expression(value); // La valeur à tester est sur la pile
+                self.compile_expression(value)?; // La valeur à tester est sur la pile
+
+                // Slot caché : les variables des cas prennent les slots suivants, et un `break`
+                // vers la boucle englobante retire la valeur avec les autres locales
+                let switch_var = format!("__switch_{}", self.locals.len());
+                let switch_idx = self.locals.len() as u16;
+                self.declare_local(switch_var.clone(), LocalInfo { index: switch_idx, is_const: true });
 
                 let mut end_jumps = Vec::new();
 
                 for (case_val, case_body) in cases {
                     self.emit_op(OpCode::Dup);
                     
-                    self.compile_expression(case_val);
+                    self.compile_expression(case_val)?;
                     self.emit_op(OpCode::Equal);
                     
                     let next_case_jump = self.emit_jump(OpCode::JumpIfFalse);
                     self.emit_op(OpCode::Pop); // Pop le booléen true
                     
                     // Body
-                    self.compile_scope(case_body);
+                    self.compile_scope(case_body)?;
                     
                     // Si on a exécuté un cas, on saute à la fin (break implicite)
                     end_jumps.push(self.emit_jump(OpCode::Jump));
                     
-                    self.patch_jump(next_case_jump);
+                    self.patch_jump(next_case_jump)?;
                     self.emit_op(OpCode::Pop); // Pop le booléen false
                 }
 
                 // Default
-                self.compile_scope(default);
+                self.compile_scope(default)?;
 
                 // Patch de toutes les sorties
-                for jump in end_jumps { self.patch_jump(jump); }
+                for jump in end_jumps { self.patch_jump(jump)?; }
                 
                 self.emit_op(OpCode::Pop); // On nettoie la valeur testée originale
+                self.locals.remove(&switch_var);
             },
 
             Instruction::ExpressionStatement(expr) => {
-                self.compile_expression(expr);
+                self.compile_expression(expr)?;
                 self.emit_op(OpCode::Pop); // On jette le résultat
             },
             
             Instruction::Input(var_name, prompt) => {
-                self.compile_expression(prompt);
+                self.compile_expression(prompt)?;
                 self.emit_op(OpCode::Input); // VM devra gérer l'affichage + lecture
                 // Le résultat de Input est sur la pile, on le stocke
                 let id = self.resolve_global(&var_name); // Ou local
                 self.emit_op(OpCode::SetGlobal);
-                self.emit_byte(id);
+                self.emit_short(id);
             },
 
             Instruction::Interface(def) => {
@@ -677,14 +1315,18 @@ impl Compiler {
                 
                 // On utilise LoadConst + SetGlobal pour définir l'interface
                 self.emit_op(OpCode::LoadConst);
-                self.emit_byte(const_idx);
+                self.emit_short(const_idx);
                 
                 let global_id = self.resolve_global(&def.name);
                 self.emit_op(OpCode::SetGlobal);
-                self.emit_byte(global_id);
+                self.emit_short(global_id);
             },
 
-            Instruction::Class(def) => {
+            Instruction::Class(mut def) => {
+                // Le parent et les interfaces sont résolus par nom dans la VM
+                def.parent = def.parent.map(|p| self.global_key(&p));
+                def.interfaces = def.interfaces.iter().map(|i| self.global_key(i)).collect();
+
                 // 1. COMPILATION DES MÉTHODES
                 // On va stocker les méthodes compilées (Value::Function) dans une HashMap
                 let mut compiled_methods = HashMap::new();
@@ -692,10 +1334,15 @@ impl Compiler {
 
                 let mut final_methods_set = std::collections::HashSet::new();
 
-                for (m_name, (m_params, m_body, is_static, is_final)) in def.methods {
-                    // Chaque méthode a son propre compilateur (scope isolé)
-                    let mut method_compiler = Compiler::new_with_globals(self.globals.clone());
+                for (m_name, (m_params, mut m_body, is_static, is_final)) in def.methods {
+                    let doc = crate::ast::nodes::take_docstring(&mut m_body);
+                    let prologue = crate::ast::nodes::take_param_prologue(&mut m_body);
+                    // Chaque méthode a son propre compilateur (scope isolé). Ce n'est pas une
+                    // closure : les locales de la fonction englobante ne lui sont pas visibles
+                    let mut method_compiler = self.child_compiler();
+                    method_compiler.enclosing_locals.clear();
                     method_compiler.scope_depth = 1;
+                    method_compiler.chunk.name = self.qualified_name(&format!("{}.{}", def.name, method_name(&m_name)));
                     
                     // On transmet le nom du parent (utile pour 'super' qui vérifie context_parent_name)
                     method_compiler.context_parent_name = def.parent.clone();
@@ -705,34 +1352,18 @@ impl Compiler {
                     let mut actual_params = vec![("this".to_string(), None)];
                     actual_params.extend(m_params.clone());
 
-                    for (i, (param_name, param_type)) in actual_params.iter().enumerate() {
-                        method_compiler.locals.insert(param_name.clone(), LocalInfo {
-                            index: i as u8,
-                            is_const: false
-                        });
-                        
-                        // Si le paramètre est typé, on ajoute une vérification runtime au début du bytecode
-                        if let Some(t) = param_type {
-                            method_compiler.emit_op(OpCode::GetLocal);
-                            method_compiler.emit_byte(i as u8);
-                            
-                            let type_idx = method_compiler.chunk.add_constant(Value::String(t.clone()));
-                            method_compiler.emit_op(OpCode::CheckType);
-                            method_compiler.emit_byte(type_idx);
-                            
-                            method_compiler.emit_op(OpCode::Pop); // Nettoyage après check
-                        }
-                    }
+                    // Valeurs par défaut, puis vérification des paramètres typés
+                    let (variadic, entry_points) = method_compiler.compile_params(&actual_params, prologue)?;
 
                     // B. Corps de la méthode
                     for stmt in m_body {
-                        method_compiler.compile_instruction(stmt.kind);
+                        method_compiler.compile_statement(stmt)?;
                     }
                     
                     // C. Retour implicite (Null) si on arrive au bout
                     method_compiler.emit_op(OpCode::LoadConst);
                     let null_idx = method_compiler.chunk.add_constant(Value::Null);
-                    method_compiler.emit_byte(null_idx);
+                    method_compiler.emit_short(null_idx);
                     method_compiler.emit_op(OpCode::Return);
 
                     // D. Debug info pour les variables locales
@@ -746,6 +1377,9 @@ impl Compiler {
                         ret_type: None, // Tu pourrais ajouter le support du type de retour ici
                         chunk: method_compiler.chunk,
                         env: None, // Les méthodes ne capturent pas l'environnement extérieur (pas des closures)
+                        doc,
+                        variadic,
+                        entry_points,
                         // Note : owner_class sera rempli par la VM ou est implicite via le CallFrame
                     }));
 
@@ -759,6 +1393,7 @@ impl Compiler {
                         compiled_methods.insert(m_name, method_val);
                     }
                 }
+                self.check_constructor_overloads(&def.name, &compiled_methods)?;
 
                 // 2. COMPILATION DES CHAMPS (Initialiseurs) - NOUVEAU
                 // Chaque champ qui a une valeur par défaut devient une mini-fonction d'initialisation
@@ -778,11 +1413,12 @@ impl Compiler {
                     }
 
                     // On compile l'expression par défaut dans un contexte isolé
-                    let mut field_compiler = Compiler::new_with_globals(self.globals.clone());
+                    let mut field_compiler = self.child_compiler();
+                    field_compiler.chunk.name = self.qualified_name(&format!("{}.{} (initializer)", def.name, field.name));
                     // Pas de scope depth particulier, c'est comme une fonction statique
                     
                     // On compile l'expression (ex: "10 + 5")
-                    field_compiler.compile_expression(field.default_value);
+                    field_compiler.compile_expression(field.default_value)?;
                     
                     // On retourne le résultat
                     field_compiler.emit_op(OpCode::Return);
@@ -793,6 +1429,9 @@ impl Compiler {
                         ret_type: None,
                         chunk: field_compiler.chunk,
                         env: None,
+                        doc: None,
+                        variadic: false,
+                        entry_points: Vec::new(),
                     }));
                     
                     if field.is_static {
@@ -812,48 +1451,55 @@ impl Compiler {
 
                     // A. Compile Getter
                     if let Some((_, body)) = prop.getter {
-                        let mut c = Compiler::new_with_globals(self.globals.clone());
+                        let mut c = self.child_compiler();
+                        c.enclosing_locals.clear();
                         c.scope_depth = 1;
                         c.context_parent_name = def.parent.clone();
+                        c.chunk.name = self.qualified_name(&format!("{}.{} (getter)", def.name, prop.name));
                         
                         // Param 'this' implicite
-                        c.locals.insert("this".to_string(), LocalInfo { index: 0, is_const: false });
+                        c.declare_local("this".to_string(), LocalInfo { index: 0, is_const: false });
                         
-                        for stmt in body { c.compile_instruction(stmt.kind); }
+                        for stmt in body { c.compile_statement(stmt)?; }
                         
                         // Retour par défaut (Null) si pas de return explicite
                         c.emit_op(OpCode::LoadConst);
                         let null_idx = c.chunk.add_constant(Value::Null);
-                        c.emit_byte(null_idx);
+                        c.emit_short(null_idx);
                         c.emit_op(OpCode::Return);
                         
                         comp_getter = Some(Value::Function(Rc::new(FunctionData {
                             params: vec![("this".to_string(), None)],
                             ret_type: None,
                             chunk: c.chunk,
-                            env: None
+                            env: None,
+                            doc: None,
+                            variadic: false,
+                            entry_points: Vec::new(),
                         })));
                     }
 
                     // B. Compile Setter
                     if let Some((params, body)) = prop.setter {
-                        let mut c = Compiler::new_with_globals(self.globals.clone());
+                        let mut c = self.child_compiler();
+                        c.enclosing_locals.clear();
                         c.scope_depth = 1;
                         c.context_parent_name = def.parent.clone();
+                        c.chunk.name = self.qualified_name(&format!("{}.{} (setter)", def.name, prop.name));
                         
                         // Params: 0=this, 1=value
-                        c.locals.insert("this".to_string(), LocalInfo { index: 0, is_const: false });
+                        c.declare_local("this".to_string(), LocalInfo { index: 0, is_const: false });
                         
                         // On récupère le nom de l'argument du setter (ex: "val")
                         if let Some((p_name, _)) = params.first() {
-                            c.locals.insert(p_name.clone(), LocalInfo { index: 1, is_const: false });
+                            c.declare_local(p_name.clone(), LocalInfo { index: 1, is_const: false });
                         }
 
-                        for stmt in body { c.compile_instruction(stmt.kind); }
+                        for stmt in body { c.compile_statement(stmt)?; }
                         
                         c.emit_op(OpCode::LoadConst);
                         let null_idx = c.chunk.add_constant(Value::Null);
-                        c.emit_byte(null_idx);
+                        c.emit_short(null_idx);
                         c.emit_op(OpCode::Return);
 
                         // Signature de la fonction pour la VM
@@ -864,7 +1510,10 @@ impl Compiler {
                             params: final_params,
                             ret_type: None,
                             chunk: c.chunk,
-                            env: None
+                            env: None,
+                            doc: None,
+                            variadic: false,
+                            entry_points: Vec::new(),
                         })));
                     }
                     
@@ -905,6 +1554,14 @@ impl Compiler {
                     
                     // Nouveaux champs v0.3.0
                     visibilities: def.visibilities, // HashMap<String, Visibility>
+
+                    // Remplis par ClassData::link quand la VM crée la classe
+                    ancestors: Vec::new(),
+                    method_table: HashMap::new(),
+                    static_method_table: HashMap::new(),
+                    field_slots: HashMap::new(),
+                    field_layout: Vec::new(),
+                    doc: def.doc,
                 }));
 
                 // Hack: On injecte les initialiseurs statiques dans static_fields pour le transport
@@ -916,13 +1573,13 @@ impl Compiler {
                 // 4. ÉMISSION DU BYTECODE DE CRÉATION
                 let const_idx = self.chunk.add_constant(class_val);
                 self.emit_op(OpCode::Class); // Instruction spéciale qui résout parent_ref
-                self.emit_byte(const_idx);
+                self.emit_short(const_idx);
                 
                 // 5. ENREGISTREMENT (Global ou Local)
                 // Par défaut, les classes sont souvent globales, mais Aegis permet des classes locales
                 if self.scope_depth > 0 {
-                    let idx = self.locals.len() as u8;
-                    self.locals.insert(def.name.clone(), LocalInfo {
+                    let idx = self.locals.len() as u16;
+                    self.declare_local(def.name.clone(), LocalInfo {
                         index: idx,
                         is_const: false
                     });
@@ -931,29 +1588,63 @@ impl Compiler {
                 } else {
                     let global_id = self.resolve_global(&def.name);
                     self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(global_id);
+                    self.emit_short(global_id);
                 }
             },
 
             Instruction::SetAttr(obj, attr, val) => {
-                self.compile_expression(*obj); // 1. L'objet
-                self.compile_expression(val);  // 2. La valeur
+                self.compile_expression(*obj)?; // 1. L'objet
+                self.compile_expression(val)?;  // 2. La valeur
                 
-                let name_idx = self.chunk.add_constant(Value::String(attr));
+                let name_idx = self.chunk.add_constant(Value::String(attr.into()));
                 self.emit_op(OpCode::SetAttr);
-                self.emit_byte(name_idx);
+                self.emit_short(name_idx);
                 // SetAttr laisse généralement la valeur sur la pile (comme une assignation),
                 // mais comme c'est une instruction ici, on POP pour nettoyer.
                 self.emit_op(OpCode::Pop); 
             },
 
-            Instruction::TryCatch { try_body, error_var, catch_body } => {
+            Instruction::SetIndex(obj, index, val) => {
+                self.compile_expression(*obj)?;
+                self.compile_expression(index)?;
+                self.compile_expression(val)?;
+                self.emit_op(OpCode::SetIndex);
+                self.emit_op(OpCode::Pop);
+            },
+
+            // obj.attr op= val : [obj, obj] -> [obj, ancienne] -> [obj, nouvelle] -> SetAttr
+            Instruction::CompoundSetAttr(obj, attr, op, val) => {
+                self.compile_expression(*obj)?;
+                self.emit_op(OpCode::Dup);
+                let name_idx = self.chunk.add_constant(Value::String(attr.into()));
+                self.emit_op(OpCode::GetAttr);
+                self.emit_short(name_idx);
+                self.compile_expression(val)?;
+                self.emit_compound_op(op);
+                self.emit_op(OpCode::SetAttr);
+                self.emit_short(name_idx);
+                self.emit_op(OpCode::Pop);
+            },
+
+            // obj[i] op= val : [obj, i, obj, i] -> [obj, i, ancienne] -> [obj, i, nouvelle] -> SetIndex
+            Instruction::CompoundSetIndex(obj, index, op, val) => {
+                self.compile_expression(*obj)?;
+                self.compile_expression(index)?;
+                self.emit_op(OpCode::Dup2);
+                self.emit_op(OpCode::GetIndex);
+                self.compile_expression(val)?;
+                self.emit_compound_op(op);
+                self.emit_op(OpCode::SetIndex);
+                self.emit_op(OpCode::Pop);
+            },
+
+            Instruction::TryCatch { try_body, catches } => {
                 // 1. Setup Exception Handler
                 let catch_jump = self.emit_jump(OpCode::SetupExcept);
 
                 // 2. Compile Try Block
                 self.try_depth += 1;
-                self.compile_scope(try_body);
+                self.compile_scope(try_body)?;
                 self.try_depth -= 1;
 
                 // 3. Pop Exception (Success Path)
@@ -961,42 +1652,104 @@ impl Compiler {
                 let end_jump = self.emit_jump(OpCode::Jump);
 
                 // 4. Start of Catch
-                self.patch_jump(catch_jump);
+                self.patch_jump(catch_jump)?;
+
+                let mut end_jumps = vec![end_jump];
+                let mut catches_all = false;
+                for (error_var, error_type, catch_body) in catches {
+                    // Clause typée : CatchType laisse un booléen au-dessus de l'erreur
+                    // (comme Equal dans un switch) ; si elle ne correspond pas, clause suivante
+                    let next_clause = match &error_type {
+                        Some(type_name) => {
+                            let type_idx = self.type_constant(type_name);
+                            self.emit_op(OpCode::CatchType);
+                            self.emit_short(type_idx);
+                            let jump = self.emit_jump(OpCode::JumpIfFalse);
+                            self.emit_op(OpCode::Pop); // Pop le booléen true
+                            Some(jump)
+                        },
+                        None => None,
+                    };
 
-                // 5. Variable Binding (CORRIGÉ)
-                self.scope_depth += 1;
-                
-                // On déclare que la variable 'e' existe et qu'elle est située au sommet actuel de la pile.
-                let catch_var_idx = self.locals.len() as u8;
-                self.locals.insert(error_var.clone(), LocalInfo {
-                    index: catch_var_idx,
-                    is_const: true
-                });
-                
-                // --- MODIFICATION ICI ---
-                // On ne fait NI SetLocal, NI Pop. 
-                // La valeur est déjà sur la pile, c'est notre variable locale.
-                // ------------------------
+                    // 5. Variable Binding (CORRIGÉ)
+                    self.scope_depth += 1;
 
-                self.compile_scope(catch_body);
-                
-                // 6. Cleanup (OPTIONNEL MAIS RECOMMANDÉ)
-                // À la fin du catch, on retire la variable 'e' de la pile pour revenir à l'état propre
-                self.emit_op(OpCode::Pop); 
-                
-                self.locals.remove(&error_var);
-                self.scope_depth -= 1;
+                    // On déclare que la variable 'e' existe et qu'elle est située au sommet actuel de la pile.
+                    let catch_var_idx = self.locals.len() as u16;
+                    self.declare_local(error_var.clone(), LocalInfo {
+                        index: catch_var_idx,
+                        is_const: true
+                    });
+
+                    // --- MODIFICATION ICI ---
+                    // On ne fait NI SetLocal, NI Pop.
+                    // La valeur est déjà sur la pile, c'est notre variable locale.
+                    // ------------------------
+
+                    self.compile_scope(catch_body)?;
+
+                    // 6. Cleanup (OPTIONNEL MAIS RECOMMANDÉ)
+                    // À la fin du catch, on retire la variable 'e' de la pile pour revenir à l'état propre
+                    self.emit_op(OpCode::Pop);
+
+                    self.locals.remove(&error_var);
+                    self.scope_depth -= 1;
+
+                    match next_clause {
+                        Some(jump) => {
+                            end_jumps.push(self.emit_jump(OpCode::Jump));
+                            self.patch_jump(jump)?;
+                            self.emit_op(OpCode::Pop); // Pop le booléen false
+                        },
+                        None => {
+                            catches_all = true;
+                            break; // Les clauses suivantes ne seraient jamais atteintes
+                        },
+                    }
+                }
+
+                // Aucune clause typée ne correspond : l'erreur continue sa route
+                if !catches_all {
+                    self.emit_op(OpCode::Throw);
+                }
 
                 // 7. End
-                self.patch_jump(end_jump);
+                for jump in end_jumps {
+                    self.patch_jump(jump)?;
+                }
             },
             Instruction::Throw(expr) => {
                 // 1. On compile l'expression (l'erreur) pour la mettre sur la pile
-                self.compile_expression(expr);
+                self.compile_expression(expr)?;
                 
                 // 2. On émet l'OpCode qui va déclencher la panique contrôlée dans la VM
                 self.emit_op(OpCode::Throw);
             },
+            Instruction::Defer(body) => {
+                // Le corps devient une closure sans paramètre (variables capturées maintenant),
+                // enregistrée sur la frame courante et appelée quand elle se termine
+                self.compile_closure("<defer>", vec![], None, body)?;
+                self.emit_op(OpCode::Defer);
+            },
+            Instruction::With { resource, name, body } => {
+                // La ressource reste sur la pile comme locale du bloc ; la VM la garde aussi dans
+                // les ressources ouvertes de la frame pour la fermer sur return ou erreur
+                self.scope_depth += 1;
+                self.compile_expression(resource)?;
+                self.emit_op(OpCode::WithEnter);
+                let resource_idx = self.locals.len() as u16;
+                self.declare_local(name.clone(), LocalInfo { index: resource_idx, is_const: false });
+
+                self.with_depth += 1;
+                self.compile_scope(body)?;
+                self.with_depth -= 1;
+
+                // Sortie normale du bloc : fermeture puis retrait de la locale
+                self.emit_op(OpCode::WithExit);
+                self.emit_op(OpCode::Pop);
+                self.locals.remove(&name);
+                self.scope_depth -= 1;
+            },
 
             Instruction::Namespace { name, body } => {
                 // 1. RÉSERVATION DU NOM (Crucial pour l'auto-référence "Maths.square")
@@ -1008,10 +1761,10 @@ impl Compiler {
                 };
 
                 let local_idx = if self.scope_depth > 0 {
-                    let idx = self.locals.len() as u8;
+                    let idx = self.locals.len() as u16;
                     // On "réserve" le slot local. Attention: la valeur n'y est pas encore !
                     // Mais cela permet à 'resolve_local' de savoir que la variable existe.
-                    self.locals.insert(name.clone(), LocalInfo {
+                    self.declare_local(name.clone(), LocalInfo {
                         index: idx,
                         is_const: false
                     });
@@ -1021,30 +1774,32 @@ impl Compiler {
                 };
 
                 // 2. COMPILATION DU CORPS (IIFE Pattern)
-                let mut ns_compiler = Compiler::new_with_globals(self.globals.clone());
+                let mut ns_compiler = self.child_compiler();
                 ns_compiler.scope_depth = 1; 
+                ns_compiler.scope_name = Some(self.qualified_name(&name));
+                ns_compiler.chunk.name = format!("<namespace {}>", self.qualified_name(&name));
 
                 for stmt in body {
-                    ns_compiler.compile_instruction(stmt.kind);
+                    ns_compiler.compile_statement(stmt)?;
                 }
 
                 // 3. CONSTRUCTION DU DICTIONNAIRE (Exports)
-                let exports: Vec<(String, u8)> = ns_compiler.locals.iter()
+                let exports: Vec<(String, u16)> = ns_compiler.locals.iter()
                     .map(|(k, info)| (k.clone(), info.index))
                     .collect();
                 
                 let count = exports.len();
 
                 for (var_name, slot_idx) in exports {
-                    let key_idx = ns_compiler.chunk.add_constant(Value::String(var_name));
+                    let key_idx = ns_compiler.chunk.add_constant(Value::String(var_name.into()));
                     ns_compiler.emit_op(OpCode::LoadConst);
-                    ns_compiler.emit_byte(key_idx);
+                    ns_compiler.emit_short(key_idx);
                     ns_compiler.emit_op(OpCode::GetLocal);
-                    ns_compiler.emit_byte(slot_idx);
+                    ns_compiler.emit_short(slot_idx);
                 }
 
                 ns_compiler.emit_op(OpCode::MakeDict);
-                ns_compiler.emit_byte((count * 2) as u8);
+                ns_compiler.emit_count(count * 2, "exported names in a namespace (keys and values)")?;
                 ns_compiler.emit_op(OpCode::Return);
 
                 for (name, info) in &ns_compiler.locals {
@@ -1052,56 +1807,103 @@ impl Compiler {
                 }
 
                 // 4. EMBALLAGE (Closure)
+                self.inherit_free_vars(&ns_compiler.chunk);
                 let ns_chunk = ns_compiler.chunk;
                 let ns_func = Value::Function(Rc::new(FunctionData {
                     params: vec![],
                     ret_type: None,
                     chunk: ns_chunk,
-                    env: None
+                    env: None,
+                    doc: None,
+                    variadic: false,
+                    entry_points: Vec::new(),
                 }));
                 
                 let const_idx = self.chunk.add_constant(ns_func);
                 self.emit_op(OpCode::LoadConst);
-                self.emit_byte(const_idx);
+                self.emit_short(const_idx);
+                self.note_captures();
                 self.emit_op(OpCode::MakeClosure);
 
                 self.emit_op(OpCode::Call);
-                self.emit_byte(0);
+                self.emit_short(0);
 
                 // 5. STOCKAGE FINAL
                 // On utilise les ID calculés à l'étape 1
                 if let Some(id) = global_id {
                     self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(id);
+                    self.emit_short(id);
                 } else if let Some(idx) = local_idx {
                     // Pour une locale, la valeur est maintenant sur le sommet de la pile.
                     // SetLocal la copie dans le slot réservé.
                     self.emit_op(OpCode::SetLocal);
-                    self.emit_byte(idx);
+                    self.emit_short(idx);
                     // Namespace est une instruction, pas une expression, donc on pop le résultat de la pile
                     // (La valeur est maintenant en sécurité dans la variable locale)
                     self.emit_op(OpCode::Pop); 
                 }
             },
 
-            Instruction::Import(path) => {
+            Instruction::Import(path, alias) => {
+                // Un fichier de données ne définit aucun nom : sans `as`, sa valeur serait perdue
+                if alias.is_none() && crate::native::is_data_file(&path) {
+                    return Err(self.error(format!("Importing data file '{}' needs a name: import \"{}\" as name", path, path)));
+                }
+
                 // Store the path as a constant string
-                let path_idx = self.chunk.add_constant(Value::String(path));
+                let path_idx = self.chunk.add_constant(Value::String(path.into()));
                 
-                // Emit the IMPORT opcode
+                // Emit the IMPORT opcode (le module n'est chargé qu'à la première exécution).
+                // Avec `as`, le module est isolé : ses noms ne sont pas copiés dans nos globales.
                 self.emit_op(OpCode::Import);
-                self.emit_byte(path_idx);
+                self.emit_short(path_idx);
+                self.emit_byte(alias.is_some() as u8);
+
+                match alias {
+                    // import "x" as m : l'objet module devient une variable
+                    Some(name) => self.bind_imported(name),
+                    // L'import est une instruction : on jette sa valeur
+                    None => self.emit_op(OpCode::Pop),
+                }
+            },
+
+            Instruction::FromImport(path, names) => {
+                let path_idx = self.chunk.add_constant(Value::String(path.into()));
+                self.emit_op(OpCode::Import);
+                self.emit_short(path_idx);
+                self.emit_byte(1);
+
+                // Dans une fonction, le module reste dans un slot caché sous les noms importés
+                let module_local = self.scope_depth > 0;
+                if module_local {
+                    let module_var = format!("__module_{}", self.locals.len());
+                    let idx = self.locals.len() as u16;
+                    self.declare_local(module_var, LocalInfo { index: idx, is_const: true });
+                }
+
+                for (name, alias) in names {
+                    let name_idx = self.chunk.add_constant(Value::String(name.into()));
+                    self.emit_op(OpCode::ImportName);
+                    self.emit_short(name_idx);
+                    self.bind_imported(alias);
+                }
+
+                if !module_local {
+                    self.emit_op(OpCode::Pop);
+                }
             },
 
             Instruction::Break => {
                 // ÉTAPE 1 : EXTRACTION
-                let (start_try, start_locals) = if let Some(state) = self.loop_stack.last() {
+                let (start_try, start_with, start_locals) = if let Some(state) = self.loop_stack.last() {
                     match state {
-                        LoopState::While { try_depth_at_start, locals_count_at_start, .. } => (*try_depth_at_start, *locals_count_at_start),
-                        LoopState::For { try_depth_at_start, locals_count_at_start, .. } => (*try_depth_at_start, *locals_count_at_start),
+                        LoopState::While { try_depth_at_start, with_depth_at_start, locals_count_at_start, .. }
+                            => (*try_depth_at_start, *with_depth_at_start, *locals_count_at_start),
+                        LoopState::For { try_depth_at_start, with_depth_at_start, locals_count_at_start, .. }
+                            => (*try_depth_at_start, *with_depth_at_start, *locals_count_at_start),
                     }
                 } else {
-                    panic!("'break' utilisé hors d'une boucle.");
+                    return Err(self.error("'break' used outside of a loop"));
                 };
 
                 // ÉTAPE 2 : ACTIONS
@@ -1112,22 +1914,27 @@ impl Compiler {
                     self.emit_op(OpCode::PopExcept);
                 }
 
-                // B. Fermeture des Variables Locales
+                // B. Fermeture des ressources `with` ouvertes dans la boucle
+                for _ in start_with..self.with_depth {
+                    self.emit_op(OpCode::WithExit);
+                }
+
+                // C. Fermeture des Variables Locales
                 let current_locals = self.locals.len();
                 let pop_locals_count = current_locals - start_locals;
                 for _ in 0..pop_locals_count {
                     self.emit_op(OpCode::Pop);
                 }
 
-                // C. --- FIX SEGFAULT : Dummy Value ---
+                // D. --- FIX SEGFAULT : Dummy Value ---
                 // La sortie de boucle s'attend à trouver la condition (booléen) sur la pile 
                 // pour faire un POP final. Break doit simuler cette valeur pour garder la pile alignée.
                 let null_idx = self.chunk.add_constant(Value::Null);
                 self.emit_op(OpCode::LoadConst);
-                self.emit_byte(null_idx);
+                self.emit_short(null_idx);
                 // -------------------------------------
 
-                // D. Saut
+                // E. Saut
                 let jump_op = self.emit_jump(OpCode::Jump);
                 
                 // ÉTAPE 3 : STOCKAGE
@@ -1141,16 +1948,16 @@ impl Compiler {
                 // ÉTAPE 1 : EXTRACTION
                 // On détermine où on est et ce qu'on doit faire
                 // jump_target : Some(ip) pour While, None pour For (car on doit patcher plus tard)
-                let (start_try, start_locals, jump_target) = if let Some(state) = self.loop_stack.last() {
+                let (start_try, start_with, start_locals, jump_target) = if let Some(state) = self.loop_stack.last() {
                     match state {
-                        LoopState::While { try_depth_at_start, locals_count_at_start, start_ip, .. } 
-                            => (*try_depth_at_start, *locals_count_at_start, Some(*start_ip)),
+                        LoopState::While { try_depth_at_start, with_depth_at_start, locals_count_at_start, start_ip, .. } 
+                            => (*try_depth_at_start, *with_depth_at_start, *locals_count_at_start, Some(*start_ip)),
                         
-                        LoopState::For { try_depth_at_start, locals_count_at_start, .. } 
-                            => (*try_depth_at_start, *locals_count_at_start, None),
+                        LoopState::For { try_depth_at_start, with_depth_at_start, locals_count_at_start, .. } 
+                            => (*try_depth_at_start, *with_depth_at_start, *locals_count_at_start, None),
                     }
                 } else {
-                    panic!("'continue' utilisé hors d'une boucle.");
+                    return Err(self.error("'continue' used outside of a loop"));
                 };
 
                 // ÉTAPE 2 : ACTIONS
@@ -1159,6 +1966,10 @@ impl Compiler {
                     self.emit_op(OpCode::PopExcept);
                 }
 
+                for _ in start_with..self.with_depth {
+                    self.emit_op(OpCode::WithExit);
+                }
+
                 let current_locals = self.locals.len();
                 let pop_locals_count = current_locals - start_locals;
                 for _ in 0..pop_locals_count {
@@ -1168,7 +1979,7 @@ impl Compiler {
                 // ÉTAPE 3 : SAUT
                 if let Some(ip) = jump_target {
                     // While : saut direct au début (on connait l'IP)
-                    self.emit_loop(ip);
+                    self.emit_loop(ip)?;
                 } else {
                     // For : saut vers l'incrément (on ne connait pas encore l'IP, il faudra patcher)
                     let jump = self.emit_jump(OpCode::Jump);
@@ -1183,33 +1994,33 @@ impl Compiler {
             Instruction::Enum(name, variants) => {
                 for (i, variant_name) in variants.iter().enumerate() {
                     // Clé
-                    let key_idx = self.chunk.add_constant(Value::String(variant_name.clone()));
+                    let key_idx = self.chunk.add_constant(Value::String(variant_name.clone().into()));
                     self.emit_op(OpCode::LoadConst);
-                    self.emit_byte(key_idx);
+                    self.emit_short(key_idx);
                     
                     // Valeur (i)
                     let val_idx = self.chunk.add_constant(Value::Integer(i as i64));
                     self.emit_op(OpCode::LoadConst);
-                    self.emit_byte(val_idx);
+                    self.emit_short(val_idx);
                 }
                 
                 // On crée l'enum
                 self.emit_op(OpCode::MakeEnum);
-                self.emit_byte((variants.len() * 2) as u8);
+                self.emit_count(variants.len() * 2, "enum variants (names and values)")?;
                 
                 // On le stocke dans la variable (Globale ou Locale selon le scope)
                 if self.scope_depth > 0 {
-                    let idx = self.locals.len() as u8;
-                    self.locals.insert(name.clone(), LocalInfo {
+                    let idx = self.locals.len() as u16;
+                    self.declare_local(name.clone(), LocalInfo {
                         index: idx,
                         is_const: false
                     });
                     self.emit_op(OpCode::SetLocal);
-                    self.emit_byte(idx);
+                    self.emit_short(idx);
                 } else {
                     let id = self.resolve_global(&name);
                     self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(id);
+                    self.emit_short(id);
                 }
                 // SetGlobal/SetLocal ne popent pas toujours selon ton implémentation.
                 // Si SetGlobal consomme la valeur (ce qui est le cas dans ta VM v2), c'est bon.
@@ -1217,13 +2028,19 @@ impl Compiler {
                 // Vérification VM v2 : OpCode::SetGlobal => let val = self.pop(); ...
                 // C'est bon, la pile est propre.
             },
-            Instruction::Const(name, expr) => {
-                self.compile_expression(expr); // Valeur sur la pile
+            Instruction::Const(name, type_annot, expr) => {
+                self.compile_expression(expr)?; // Valeur sur la pile
+
+                if let Some(type_name) = type_annot {
+                    let type_idx = self.type_constant(&type_name);
+                    self.emit_op(OpCode::CheckType);
+                    self.emit_short(type_idx);
+                }
                 
                 if self.scope_depth > 0 {
                     // --- LOCALE ---
-                    let idx = self.locals.len() as u8;
-                    self.locals.insert(name.clone(), LocalInfo { 
+                    let idx = self.locals.len() as u16;
+                    self.declare_local(name.clone(), LocalInfo { 
                         index: idx, 
                         is_const: true 
                     });
@@ -1232,36 +2049,56 @@ impl Compiler {
                     // --- GLOBALE ---
                     let id = self.resolve_global(&name);
                     self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(id);
+                    self.emit_short(id);
                     
                     // On la marque comme constante pour empêcher la modif dans ce fichier
                     self.global_constants.push(name);
                 }
             },
             
+            // La visibilité est appliquée par l'import (voir VM::module_private_names)
+            Instruction::Private(inner) | Instruction::Export(inner) => self.compile_instruction(*inner)?,
+
+            // Purement statique : l'alias est substitué dans les CheckType suivants
+            // Le reste du fichier (fonctions et classes comprises) est compilé en mode strict
+            // Retirés du corps par compile_params
+            Instruction::DefaultParam(name, _) | Instruction::RestParam(name) => {
+                return Err(self.error(format!("Parameter '{}' declared outside of a function signature", name)));
+            },
+
+            Instruction::Use(_) => {
+                self.strict = true;
+                self.chunk.strict = true;
+            },
+
+            Instruction::TypeAlias(name, ty) => {
+                let resolved = ty.resolve(&self.type_aliases.borrow());
+                self.type_aliases.borrow_mut().insert(name, resolved);
+            },
+
             Instruction::ForEach(iter_var_name, iterable, body) => {
                 self.scope_depth += 1;
                 
                 // 1. Init __seq et __idx (Code inchangé...)
                 let seq_var = format!("__seq_{}", self.locals.len());
-                self.compile_expression(iterable);
-                let seq_idx = self.locals.len() as u8;
-                self.locals.insert(seq_var.clone(), LocalInfo { index: seq_idx, is_const: true });
+                self.compile_expression(iterable)?;
+                let seq_idx = self.locals.len() as u16;
+                self.declare_local(seq_var.clone(), LocalInfo { index: seq_idx, is_const: true });
                 
                 let idx_var = format!("__idx_{}", self.locals.len());
                 self.emit_op(OpCode::LoadConst);
                 let zero_const = self.chunk.add_constant(Value::Integer(0));
-                self.emit_byte(zero_const);
-                let idx_idx = self.locals.len() as u8;
-                self.locals.insert(idx_var.clone(), LocalInfo { index: idx_idx, is_const: false });
+                self.emit_short(zero_const);
+                let idx_idx = self.locals.len() as u16;
+                self.declare_local(idx_var.clone(), LocalInfo { index: idx_idx, is_const: false });
                 
                 let loop_start = self.chunk.code.len();
                 
                 // 2. Condition (Code inchangé...)
-                self.emit_op(OpCode::GetLocal); self.emit_byte(idx_idx);
-                self.emit_op(OpCode::GetLocal); self.emit_byte(seq_idx);
-                let len_str_idx = self.chunk.add_constant(Value::String("len".to_string()));
-                self.emit_op(OpCode::Method); self.emit_byte(len_str_idx); self.emit_byte(0);
+                self.emit_op(OpCode::GetLocal); self.emit_short(idx_idx);
+                self.emit_op(OpCode::GetLocal); self.emit_short(seq_idx);
+                let len_str_idx = self.chunk.add_constant(Value::String("len".into()));
+                self.emit_op(OpCode::Method); self.emit_short(len_str_idx); self.emit_short(0);
                 self.emit_op(OpCode::Less);
                 
                 let exit_jump = self.emit_jump(OpCode::JumpIfFalse);
@@ -1272,24 +2109,25 @@ impl Compiler {
                     continue_patches: Vec::new(),
                     break_jumps: Vec::new(),
                     try_depth_at_start: self.try_depth,
+                    with_depth_at_start: self.with_depth,
                     locals_count_at_start: self.locals.len()
                 });
 
                 // 4. Variable utilisateur 'elem'
                 self.scope_depth += 1; 
-                self.emit_op(OpCode::GetLocal); self.emit_byte(seq_idx);
-                self.emit_op(OpCode::GetLocal); self.emit_byte(idx_idx);
-                let at_str_idx = self.chunk.add_constant(Value::String("at".to_string()));
-                self.emit_op(OpCode::Method); self.emit_byte(at_str_idx); self.emit_byte(1);
+                self.emit_op(OpCode::GetLocal); self.emit_short(seq_idx);
+                self.emit_op(OpCode::GetLocal); self.emit_short(idx_idx);
+                let at_str_idx = self.chunk.add_constant(Value::String("at".into()));
+                self.emit_op(OpCode::Method); self.emit_short(at_str_idx); self.emit_short(1);
                 
-                let user_var_idx = self.locals.len() as u8;
-                self.locals.insert(iter_var_name.clone(), LocalInfo { index: user_var_idx, is_const: false });
+                let user_var_idx = self.locals.len() as u16;
+                self.declare_local(iter_var_name.clone(), LocalInfo { index: user_var_idx, is_const: false });
                 
                 // 5. CORPS DE LA BOUCLE AVEC NETTOYAGE (FIX MEMORY LEAK)
                 let locals_count_before_body = self.locals.len(); // Snapshot
                 
                 for stmt in body {
-                    self.compile_instruction(stmt.kind);
+                    self.compile_statement(stmt)?;
                 }
                 
                 // --- NETTOYAGE MANUEL DES VARIABLES DU CORPS ---
@@ -1299,7 +2137,7 @@ impl Compiler {
                 for _ in 0..vars_created {
                     self.emit_op(OpCode::Pop);
                 }
-                self.locals.retain(|_, info| info.index < locals_count_before_body as u8);
+                self.locals.retain(|_, info| info.index < locals_count_before_body as u16);
                 // ------------------------------------------------
                 
                 // 6. Fin scope utilisateur 'elem'
@@ -1309,23 +2147,23 @@ impl Compiler {
                 
                 // 7. Patch Continue & Incrément
                 if let Some(LoopState::For { continue_patches, break_jumps , ..}) = self.loop_stack.pop() {
-                    for patch in continue_patches { self.patch_jump(patch); }
+                    for patch in continue_patches { self.patch_jump(patch)?; }
                     
                     // Increment __idx
-                    self.emit_op(OpCode::GetLocal); self.emit_byte(idx_idx);
+                    self.emit_op(OpCode::GetLocal); self.emit_short(idx_idx);
                     self.emit_op(OpCode::LoadConst);
                     let one_const = self.chunk.add_constant(Value::Integer(1));
-                    self.emit_byte(one_const);
+                    self.emit_short(one_const);
                     self.emit_op(OpCode::Add);
-                    self.emit_op(OpCode::SetLocal); self.emit_byte(idx_idx);
+                    self.emit_op(OpCode::SetLocal); self.emit_short(idx_idx);
                     self.emit_op(OpCode::Pop);
                     
-                    self.emit_loop(loop_start);
+                    self.emit_loop(loop_start)?;
                     
-                    self.patch_jump(exit_jump); // Sortie normale
+                    self.patch_jump(exit_jump)?; // Sortie normale
                     
                     // 8. Patch Breaks (On atterrit aussi ici, donc le nettoyage final se fera !)
-                    for jump in break_jumps { self.patch_jump(jump); }
+                    for jump in break_jumps { self.patch_jump(jump)?; }
                 }
 
                 self.emit_op(OpCode::Pop); // Pop condition
@@ -1336,11 +2174,27 @@ impl Compiler {
                 self.scope_depth -= 1;
             },
         }
+
+        Ok(())
     }
 
     // Emits a jump instruction with a placeholder operand.
     // Returns the offset of the placeholder so we can patch it later.
     fn emit_jump(&mut self, instruction: OpCode) -> usize {
+        if self.wide_jumps {
+            let wide = match instruction {
+                OpCode::Jump => OpCode::JumpLong,
+                OpCode::JumpIfFalse => OpCode::JumpIfFalseLong,
+                OpCode::SetupExcept => OpCode::SetupExceptLong,
+                other => other,
+            };
+            self.emit_op(wide);
+            for _ in 0..4 {
+                self.emit_byte(0xff);
+            }
+            return self.chunk.code.len() - 4;
+        }
+
         self.emit_op(instruction);
         self.emit_byte(0xff); // Placeholder high
         self.emit_byte(0xff); // Placeholder low
@@ -1348,23 +2202,37 @@ impl Compiler {
     }
 
     // Goes back to 'offset' and writes the current distance
-    fn patch_jump(&mut self, offset: usize) {
+    fn patch_jump(&mut self, offset: usize) -> Result<(), CompileError> {
+        let wide = matches!(
+            OpCode::from(self.chunk.code[offset - 1]),
+            OpCode::JumpLong | OpCode::JumpIfFalseLong | OpCode::SetupExceptLong
+        );
+
+        if wide {
+            let jump = self.chunk.code.len() - offset - 4;
+            let jump = u32::try_from(jump).map_err(|_| self.error(JUMP_TOO_LARGE))?;
+            self.chunk.code[offset..offset + 4].copy_from_slice(&jump.to_be_bytes());
+            return Ok(());
+        }
+
         // -2 to adjust for the jump offset itself
         let jump = self.chunk.code.len() - offset - 2;
 
         if jump > u16::MAX as usize {
-            panic!("Too much code to jump over!");
+            return Err(self.error(JUMP_TOO_LARGE));
         }
 
         self.chunk.code[offset] = ((jump >> 8) & 0xff) as u8;
         self.chunk.code[offset + 1] = (jump & 0xff) as u8;
+
+        Ok(())
     }
 
     // Compile an IF statement
     // if (cond) { then } else { else }
-    fn compile_if(&mut self, condition: Expression, then_body: Vec<crate::ast::Statement>, else_body: Vec<crate::ast::Statement>) {
+    fn compile_if(&mut self, condition: Expression, then_body: Vec<crate::ast::Statement>, else_body: Vec<crate::ast::Statement>) -> Result<(), CompileError> {
         // 1. Compile condition
-        self.compile_expression(condition);
+        self.compile_expression(condition)?;
 
         // 2. Jump over 'then' if false
         let then_jump = self.emit_jump(OpCode::JumpIfFalse);
@@ -1372,39 +2240,53 @@ impl Compiler {
         // 3. Compile 'then' block
         self.emit_op(OpCode::Pop); // Clean up condition result (optional optimization)
 
-        self.compile_scope(then_body);
+        self.compile_scope(then_body)?;
 
         // 4. Jump over 'else'
         let else_jump = self.emit_jump(OpCode::Jump);
 
         // 5. Patch the first jump (target is here, start of else)
-        self.patch_jump(then_jump);
+        self.patch_jump(then_jump)?;
         
         self.emit_op(OpCode::Pop); // Clean up condition for the else path
 
         // 6. Compile 'else' block
-        self.compile_scope(else_body);
+        self.compile_scope(else_body)?;
 
         // 7. Patch the second jump (target is end)
-        self.patch_jump(else_jump);
+        self.patch_jump(else_jump)?;
+
+        Ok(())
     }
 
     // Émet une instruction de saut en arrière
-    fn emit_loop(&mut self, loop_start: usize) {
+    fn emit_loop(&mut self, loop_start: usize) -> Result<(), CompileError> {
+        if self.wide_jumps {
+            self.emit_op(OpCode::LoopLong);
+            let offset = self.chunk.code.len() - loop_start + 4;
+            let offset = u32::try_from(offset).map_err(|_| self.error(LOOP_TOO_LARGE))?;
+            for byte in offset.to_be_bytes() {
+                self.emit_byte(byte);
+            }
+            return Ok(());
+        }
+
         self.emit_op(OpCode::Loop);
 
         // Calcul du saut : position actuelle - début de la boucle + 2 (taille des opérandes)
         let offset = self.chunk.code.len() - loop_start + 2;
         
         if offset > u16::MAX as usize {
-            panic!("Loop body too large!");
+            return Err(self.error(LOOP_TOO_LARGE));
         }
 
         self.emit_byte(((offset >> 8) & 0xff) as u8);
         self.emit_byte((offset & 0xff) as u8);
+
+        Ok(())
     }
 
-    fn compile_while(&mut self, condition: Expression, body: Vec<crate::ast::Statement>) {
+    fn compile_while(&mut self, condition: Expression, body: Vec<crate::ast::Statement>) -> Result<(), CompileError> {
         // 1. Marquer le début de la boucle (pour y revenir après)
         let loop_start = self.chunk.code.len();
 
@@ -1412,35 +2294,39 @@ impl Compiler {
             start_ip: loop_start,
             break_jumps: Vec::new(),
             try_depth_at_start: self.try_depth,
+            with_depth_at_start: self.with_depth,
             locals_count_at_start: self.locals.len()
         });
 
         // 2. Compiler la condition
-        self.compile_expression(condition);
+        self.compile_expression(condition)?;
 
         // 3. Sauter à la fin si la condition est fausse
         let exit_jump = self.emit_jump(OpCode::JumpIfFalse);
         self.emit_op(OpCode::Pop); // Nettoyer la condition de la pile
 
         // 4. Compiler le corps
-        self.compile_scope(body);
+        self.compile_scope(body)?;
 
         // 5. Remonter au début !
-        self.emit_loop(loop_start);
+        self.emit_loop(loop_start)?;
 
-        // 6. Patcher le saut de sortie
-        self.patch_jump(exit_jump);
+        // 6. Patcher le saut de sortie, et les `break` qui y atterrissent avec leur valeur factice
+        self.patch_jump(exit_jump)?;
+        if let Some(LoopState::While { break_jumps, .. }) = self.loop_stack.pop() {
+            for jump in break_jumps { self.patch_jump(jump)?; }
+        }
         self.emit_op(OpCode::Pop); // Nettoyer la condition finale
 
-        self.loop_stack.pop();
+        Ok(())
     }
 
     // Compile une liste d'instructions en gérant le nettoyage des variables locales (Scope)
-    fn compile_scope(&mut self, statements: Vec<crate::ast::Statement>) {
+    fn compile_scope(&mut self, statements: Vec<crate::ast::Statement>) -> Result<(), CompileError> {
         let initial_locals_count = self.locals.len();
         
         for stmt in statements {
-            self.compile_instruction(stmt.kind);
+            self.compile_statement(stmt)?;
         }
         
         let final_locals_count = self.locals.len();
@@ -1453,7 +2339,9 @@ impl Compiler {
         
         // 2. On nettoie la table des symboles (Compile-time)
         // On retire toutes les variables qui ont un index >= initial_locals_count
-        self.locals.retain(|_, &mut info| info.index < initial_locals_count as u8);
+        self.locals.retain(|_, &mut info| info.index < initial_locals_count as u16);
+
+        Ok(())
     }
 
     // Tente de réduire une expression constante
@@ -1461,20 +2349,34 @@ impl Compiler {
         match expr {
             // 1. Valeurs littérales (Feuilles de l'arbre)
             Expression::Literal(v) => Some(v.clone()),
+
+            // Constante connue à la compilation (-O2), sauf si une locale la masque
+            Expression::Variable(name) if !self.locals.contains_key(name) => {
+                let known = self.known_constants.borrow().get(name).cloned();
+                known.or_else(|| {
+                    // Constante d'un plugin, sauf si le script a sa propre globale de ce nom
+                    if self.globals.borrow().contains_key(&self.global_key(name)) {
+                        None
+                    } else {
+                        crate::native::find_constant(name)
+                    }
+                })
+            },
             
             // 2. Arithmétique de base
             Expression::Add(left, right) => {
-                match (self.evaluate_constant(left), self.evaluate_constant(right)) {
-                    (Some(Value::Integer(a)), Some(Value::Integer(b))) => Some(Value::Integer(a + b)),
+                match (self.evaluate_constant(left).as_ref(), self.evaluate_constant(right).as_ref()) {
+                    // Un débordement n'est pas plié : l'erreur est levée à l'exécution, avec sa ligne
+                    (Some(Value::Integer(a)), Some(Value::Integer(b))) => a.checked_add(*b).map(Value::Integer),
                     (Some(Value::Float(a)), Some(Value::Float(b))) => Some(Value::Float(a + b)),
-                    (Some(Value::String(a)), Some(Value::String(b))) => Some(Value::String(format!("{}{}", a, b))),
+                    (Some(Value::String(a)), Some(Value::String(b))) => Some(Value::String(format!("{}{}", a, b).into())),
                     _ => None
                 }
             },
             
             Expression::Sub(left, right) => {
                 match (self.evaluate_constant(left), self.evaluate_constant(right)) {
-                    (Some(Value::Integer(a)), Some(Value::Integer(b))) => Some(Value::Integer(a - b)),
+                    (Some(Value::Integer(a)), Some(Value::Integer(b))) => a.checked_sub(b).map(Value::Integer),
                     (Some(Value::Float(a)), Some(Value::Float(b))) => Some(Value::Float(a - b)),
                     _ => None
                 }
@@ -1482,7 +2384,7 @@ impl Compiler {
 
             Expression::Mul(left, right) => {
                 match (self.evaluate_constant(left), self.evaluate_constant(right)) {
-                    (Some(Value::Integer(a)), Some(Value::Integer(b))) => Some(Value::Integer(a * b)),
+                    (Some(Value::Integer(a)), Some(Value::Integer(b))) => a.checked_mul(b).map(Value::Integer),
                     (Some(Value::Float(a)), Some(Value::Float(b))) => Some(Value::Float(a * b)),
                     _ => None
                 }
@@ -1491,7 +2393,9 @@ impl Compiler {
             Expression::Div(left, right) => {
                 match (self.evaluate_constant(left), self.evaluate_constant(right)) {
                     (Some(Value::Integer(a)), Some(Value::Integer(b))) => {
-                        if b == 0 { None } else { Some(Value::Integer(a / b)) }
+                        if b == 0 { None }
+                        else if self.lang_version >= 2 { Some(Value::Float(a as f64 / b as f64)) }
+                        else { a.checked_div(b).map(Value::Integer) }
                     },
                     (Some(Value::Float(a)), Some(Value::Float(b))) => Some(Value::Float(a / b)),
                     _ => None
@@ -1502,13 +2406,21 @@ impl Compiler {
             Expression::Modulo(left, right) => {
                 match (self.evaluate_constant(left), self.evaluate_constant(right)) {
                     (Some(Value::Integer(a)), Some(Value::Integer(b))) => {
-                        if b == 0 { None } else { Some(Value::Integer(a % b)) }
+                        if b == 0 { None } else { Some(Value::Integer(a.wrapping_rem(b))) }
                     },
                     (Some(Value::Float(a)), Some(Value::Float(b))) => Some(Value::Float(a % b)),
                     _ => None
                 }
             },
 
+            Expression::FloorDiv(left, right) => {
+                match (self.evaluate_constant(left), self.evaluate_constant(right)) {
+                    (Some(Value::Integer(a)), Some(Value::Integer(b))) => super::floor_div(a, b).map(Value::Integer),
+                    (Some(Value::Float(a)), Some(Value::Float(b))) => Some(Value::Float((a / b).floor())),
+                    _ => None
+                }
+            },
+
             // 4. Opérateurs Bitwise (Entiers uniquement)
             Expression::BitAnd(left, right) => {
                 match (self.evaluate_constant(left), self.evaluate_constant(right)) {
@@ -1536,7 +2448,7 @@ impl Compiler {
                         // Rust panic si shift < 0 ou shift >= bits du type.
                         // On ne fold que si le shift est sûr.
                         if let Ok(shift) = u32::try_from(b) {
-                            if shift < 64 { return Some(Value::Integer(a << shift)); }
+                            if shift < 64 && (a << shift) >> shift == a { return Some(Value::Integer(a << shift)); }
                         }
                         None
                     },
@@ -1569,4 +2481,51 @@ impl Compiler {
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
+/// Compilation incrémentale du REPL : chaque saisie a son propre chunk, mais ce qu'elle déclare
+/// pour le compilateur (constantes, alias de type, `use strict`) vaut pour les suivantes, comme
+/// les lignes d'un même fichier. Les globales sont la table partagée avec la VM.
+#[derive(Clone)]
+pub struct ReplSession {
+    globals: GlobalNames,
+    constants: Vec<String>,
+    type_aliases: HashMap<String, TypeExpr>,
+    strict: bool,
+}
+
+impl ReplSession {
+    pub fn new(globals: GlobalNames) -> Self {
+        ReplSession { globals, constants: Vec::new(), type_aliases: HashMap::new(), strict: false }
+    }
+
+    /// Compile une saisie et renvoie son chunk avec ses avertissements. Une saisie refusée ne
+    /// change pas la session ; pour annuler une saisie qui échoue à l'exécution, restaurer une
+    /// copie de la session prise avant.
+    pub fn compile(&mut self, statements: Vec<Statement>) -> Result<(Chunk, Vec<Diagnostic>), CompileError> {
+        let mut constants = self.constants.clone();
+        for stmt in &statements {
+            let mut kind = &stmt.kind;
+            while let Instruction::Private(inner) | Instruction::Export(inner) = kind {
+                kind = inner;
+            }
+            if let Instruction::Const(name, ..) = kind
+                && !constants.contains(name) {
+                constants.push(name.clone());
+            }
+        }
+        let strict = self.strict || statements.iter().any(|stmt| matches!(stmt.kind, Instruction::Use(_)));
+
+        let mut compiler = Compiler::new_with_globals(self.globals.clone());
+        compiler.global_constants = self.constants.clone();
+        compiler.strict = self.strict;
+        let type_aliases = Rc::new(RefCell::new(self.type_aliases.clone()));
+        compiler.type_aliases = type_aliases.clone();
+        let warnings = compiler.warnings.clone();
+        let (chunk, _) = compiler.compile_repl(statements)?;
+
+        self.constants = constants;
+        self.type_aliases = type_aliases.take();
+        self.strict = strict;
+        Ok((chunk, warnings.take()))
+    }
+}
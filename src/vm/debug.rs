@@ -1,16 +1,54 @@
+use crate::ast::Value;
 use crate::chunk::Chunk;
 use crate::opcode::OpCode;
 
-pub fn disassemble_chunk(chunk: &Chunk, name: &str) {
-    println!("== {} ==", name);
+/// Désassemble un chunk puis, à la suite, chaque fonction qu'il contient en constante
+/// (méthodes, lambdas, corps de namespace...), chacune sous un en-tête portant son nom.
+pub fn disassemble_chunk(chunk: &Chunk) {
+    println!("== {} ==", chunk.name);
 
     let mut offset = 0;
     while offset < chunk.code.len() {
         offset = disassemble_instruction(chunk, offset);
     }
+
+    for constant in &chunk.constants {
+        match constant {
+            Value::Function(func) => {
+                println!();
+                disassemble_chunk(&func.chunk);
+            }
+            Value::Class(class) => {
+                let mut members: Vec<&Value> = class.methods.values()
+                    .chain(class.static_methods.values())
+                    .chain(class.fields.values())
+                    .chain(class.properties.values().flat_map(|p| p.getter.iter().chain(p.setter.iter())))
+                    .collect();
+                members.sort_by_key(|m| function_name(m));
+                for member in members {
+                    if let Value::Function(func) = member {
+                        println!();
+                        disassemble_chunk(&func.chunk);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn function_name(value: &Value) -> String {
+    match value {
+        Value::Function(func) => func.chunk.name.clone(),
+        _ => String::new(),
+    }
 }
 
 pub fn disassemble_instruction(chunk: &Chunk, offset: usize) -> usize {
+    if let Some(note) = chunk.annotations.get(&offset) {
+        println!("     ; {}", note);
+    }
+
     print!("{:04} ", offset); // Affiche l'adresse (ex: 0000)
 
     let instruction: OpCode = chunk.code[offset].into();
@@ -22,26 +60,34 @@ pub fn disassemble_instruction(chunk: &Chunk, offset: usize) -> usize {
         OpCode::Sub => simple_instruction("SUB", offset),
         OpCode::Mul => simple_instruction("MUL", offset),
         OpCode::Div => simple_instruction("DIV", offset),
+        OpCode::FloatDiv => simple_instruction("FLOAT_DIV", offset),
 
         OpCode::Pop => simple_instruction("POP", offset),
         
-        // Instructions avec opérandes (1 octet de plus)
+        // Instructions avec opérandes (index u16 : 2 octets de plus)
         OpCode::LoadConst => constant_instruction("LOAD_CONST", chunk, offset),
 
         // --- Affichage des Globales ---
-        OpCode::GetGlobal => byte_instruction("GET_GLOBAL", chunk, offset),
-        OpCode::SetGlobal => byte_instruction("SET_GLOBAL", chunk, offset),
-        OpCode::GetLocal => byte_instruction("GET_LOCAL", chunk, offset),
-        OpCode::SetLocal => byte_instruction("SET_LOCAL", chunk, offset),
+        OpCode::GetGlobal => slot_instruction("GET_GLOBAL", chunk, offset),
+        OpCode::SetGlobal => slot_instruction("SET_GLOBAL", chunk, offset),
+        OpCode::GetLocal => slot_instruction("GET_LOCAL", chunk, offset),
+        OpCode::SetLocal => slot_instruction("SET_LOCAL", chunk, offset),
 
         OpCode::Jump => jump_instruction("JUMP", 1, chunk, offset),
         OpCode::JumpIfFalse => jump_instruction("JUMP_IF_FALSE", 1, chunk, offset),
         OpCode::Loop => jump_instruction("LOOP", -1, chunk, offset), // -1 pour indiquer arrière
-        OpCode::Call => byte_instruction("CALL", chunk, offset),
+        OpCode::JumpLong => long_jump_instruction("JUMP_LONG", 1, chunk, offset),
+        OpCode::JumpIfFalseLong => long_jump_instruction("JUMP_IF_FALSE_LONG", 1, chunk, offset),
+        OpCode::LoopLong => long_jump_instruction("LOOP_LONG", -1, chunk, offset),
+        OpCode::Call => count_instruction("CALL", chunk, offset),
+        OpCode::TailCall => count_instruction("TAIL_CALL", chunk, offset),
 
         OpCode::Modulo => simple_instruction("MOD", offset),
+        OpCode::FloorDiv => simple_instruction("FLOOR_DIV", offset),
         OpCode::Equal => simple_instruction("EQUAL", offset),
         OpCode::NotEqual => simple_instruction("NOT_EQUAL", offset),
+        OpCode::Same => simple_instruction("SAME", offset),
+        OpCode::NotSame => simple_instruction("NOT_SAME", offset),
         OpCode::Greater => simple_instruction("GREATER", offset),
         OpCode::GreaterEqual => simple_instruction("GREATER_EQUAL", offset),
         OpCode::Less => simple_instruction("LESS", offset),
@@ -54,27 +100,32 @@ pub fn disassemble_instruction(chunk: &Chunk, offset: usize) -> usize {
         OpCode::ShiftLeft => simple_instruction("SHIFT_LEFT", offset),
         OpCode::ShiftRight => simple_instruction("SHIFT_RIGHT", offset),
 
-        OpCode::MakeList => byte_instruction("MAKE_LIST", chunk, offset),
-        OpCode::MakeDict => byte_instruction("MAKE_DICT", chunk, offset),
+        OpCode::MakeList => count_instruction("MAKE_LIST", chunk, offset),
+        OpCode::MakeDict => count_instruction("MAKE_DICT", chunk, offset),
         
         OpCode::Class => constant_instruction("CLASS", chunk, offset),
-        OpCode::MakeEnum => byte_instruction("ENUM", chunk, offset),
+        OpCode::MakeEnum => count_instruction("ENUM", chunk, offset),
         OpCode::MakeRange => simple_instruction("MAKE_RANGE", offset),
-        OpCode::Method => constant_instruction("METHOD", chunk, offset),
+        OpCode::Method => {
+            let name_idx = read_short(chunk, offset + 1);
+            let arg_count = read_short(chunk, offset + 3);
+            println!("{:<16} {:4} '{}' ({} args)", "METHOD", name_idx, chunk.constants[name_idx as usize], arg_count);
+            offset + 5
+        },
         OpCode::GetAttr => constant_instruction("GET_ATTR", chunk, offset),
         OpCode::SetAttr => constant_instruction("SET_ATTR", chunk, offset),
         OpCode::Super => {
-            let method_idx = chunk.code[offset + 1];
-            let arg_count = chunk.code[offset + 2];
-            let parent_idx = chunk.code[offset + 3];
+            let method_idx = read_short(chunk, offset + 1);
+            let arg_count = read_short(chunk, offset + 3);
+            let parent_idx = read_short(chunk, offset + 5);
 
             let method_name = &chunk.constants[method_idx as usize];
             let parent_name = &chunk.constants[parent_idx as usize];
 
             println!("{:-16} '{}' ({} args) super-> '{}'", "SUPER", method_name, arg_count, parent_name);
             
-            // On avance de 4 (1 OpCode + 3 Args)
-            offset + 4
+            // On avance de 7 (1 OpCode + 2 index u16 + le nombre d'arguments u16)
+            offset + 7
         },
         
         OpCode::Input => simple_instruction("INPUT", offset),
@@ -84,10 +135,29 @@ pub fn disassemble_instruction(chunk: &Chunk, offset: usize) -> usize {
         OpCode::Dup => simple_instruction("DUP", offset),
 
         OpCode::SetupExcept => jump_instruction("SETUP_EXCEPT", 1, chunk, offset),
+        OpCode::SetupExceptLong => long_jump_instruction("SETUP_EXCEPT_LONG", 1, chunk, offset),
+        OpCode::Defer => simple_instruction("DEFER", offset),
+        OpCode::WithEnter => simple_instruction("WITH_ENTER", offset),
+        OpCode::WithExit => simple_instruction("WITH_EXIT", offset),
         OpCode::PopExcept => simple_instruction("POP_EXCEPT", offset),
         OpCode::Throw => simple_instruction("THROW", offset),
 
-        OpCode::Import => constant_instruction("IMPORT", chunk, offset),
+        OpCode::Import => {
+            let path_idx = read_short(chunk, offset + 1);
+            let isolated = chunk.code[offset + 3];
+            println!("{:<16} {:4} '{}'{}", "IMPORT", path_idx, chunk.constants[path_idx as usize],
+                if isolated == 1 { " (isolated)" } else { "" });
+            offset + 4
+        },
+        OpCode::ImportName => constant_instruction("IMPORT_NAME", chunk, offset),
+        OpCode::Await => simple_instruction("AWAIT", offset),
+        OpCode::CatchType => constant_instruction("CATCH_TYPE", chunk, offset),
+        OpCode::GetIndex => simple_instruction("GET_INDEX", offset),
+        OpCode::SetIndex => simple_instruction("SET_INDEX", offset),
+        OpCode::Dup2 => simple_instruction("DUP2", offset),
+        OpCode::Concat => simple_instruction("CONCAT", offset),
+        OpCode::BindSelf => constant_instruction("BIND_SELF", chunk, offset),
+        OpCode::SetFreeVar => constant_instruction("SET_FREE_VAR", chunk, offset),
         OpCode::CheckType => constant_instruction("CHECK_TYPE", chunk, offset),
     }
 }
@@ -98,18 +168,30 @@ fn simple_instruction(name: &str, offset: usize) -> usize {
 }
 
 fn constant_instruction(name: &str, chunk: &Chunk, offset: usize) -> usize {
-    // L'octet suivant contient l'index de la constante
-    let constant_idx = chunk.code[offset + 1];
+    // Les 2 octets suivants contiennent l'index de la constante
+    let constant_idx = read_short(chunk, offset + 1);
     print!("{:<16} {:4} '", name, constant_idx);
     print!("{}", chunk.constants[constant_idx as usize]);
     println!("'");
-    offset + 2 // On a lu l'opcode + l'index
+    offset + 3 // On a lu l'opcode + l'index
 }
 
-fn byte_instruction(name: &str, chunk: &Chunk, offset: usize) -> usize {
-    let slot = chunk.code[offset + 1];
+// Slot de globale ou de locale (u16)
+fn slot_instruction(name: &str, chunk: &Chunk, offset: usize) -> usize {
+    let slot = read_short(chunk, offset + 1);
     println!("{:<16} {:4}", name, slot);
-    offset + 2
+    offset + 3
+}
+
+// Nombre d'arguments ou d'éléments sur 2 octets
+fn count_instruction(name: &str, chunk: &Chunk, offset: usize) -> usize {
+    let count = read_short(chunk, offset + 1);
+    println!("{:<16} {:4}", name, count);
+    offset + 3
+}
+
+fn read_short(chunk: &Chunk, offset: usize) -> u16 {
+    u16::from_be_bytes([chunk.code[offset], chunk.code[offset + 1]])
 }
 
 fn jump_instruction(name: &str, sign: i8, chunk: &Chunk, offset: usize) -> usize {
@@ -122,3 +204,12 @@ fn jump_instruction(name: &str, sign: i8, chunk: &Chunk, offset: usize) -> usize
     println!("{:<16} {:4} -> {}", name, offset, dest);
     offset + 3 // Opcode + 2 bytes
 }
+
+fn long_jump_instruction(name: &str, sign: i8, chunk: &Chunk, offset: usize) -> usize {
+    let bytes = [chunk.code[offset + 1], chunk.code[offset + 2], chunk.code[offset + 3], chunk.code[offset + 4]];
+    let jump = u32::from_be_bytes(bytes);
+    let dest = offset as isize + 5 + (sign as isize * jump as isize);
+
+    println!("{:<16} {:4} -> {}", name, offset, dest);
+    offset + 5 // Opcode + 4 bytes
+}
@@ -0,0 +1,408 @@
+// `aegis run --break fichier.aeg:12` : avant chaque instruction qui commence une ligne, la VM
+// consulte la table des points d'arrêt et le mode de reprise (step, next...). À l'arrêt, une
+// invite lit des commandes sur l'entrée standard : variables locales et globales, pile d'appels,
+// source autour de la ligne, points d'arrêt, reprise.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{location, CallFrame, VM};
+use crate::ast::Value;
+use crate::chunk::Chunk;
+
+const HELP: &str = "\
+Commands:
+  c, continue        run until the next breakpoint
+  s, step            stop at the next line, entering calls
+  n, next            stop at the next line of this function (or of its caller)
+  o, out             run until the current function returns
+  l, locals          local variables of the current function
+  g, globals         global variables of the script
+  bt, stack          call stack
+  :stack             value stack, frame by frame, with slot names
+  p <name>           value of a variable (p user.name for a field or a key)
+  list               source around the current line
+  b [file:]<line>    add a breakpoint (b alone lists them)
+  d <number>         delete a breakpoint
+  q, quit            stop the program
+  (an empty line repeats the last command)";
+
+/// Point d'arrêt : une ligne d'un fichier (sans fichier, celui du script principal).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    pub file: Option<String>,
+    pub line: usize,
+}
+
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    /// "12" ou "lib/util.aeg:12"
+    fn from_str(text: &str) -> Result<Self, String> {
+        let (file, line) = match text.rsplit_once(':') {
+            Some((file, line)) if !file.is_empty() => (Some(file.to_string()), line),
+            _ => (None, text),
+        };
+        let line = line.trim().parse::<usize>().ok().filter(|line| *line > 0)
+            .ok_or_else(|| format!("Invalid breakpoint '{}' (expected file.aeg:LINE or LINE)", text))?;
+        Ok(Breakpoint { file, line })
+    }
+}
+
+impl Breakpoint {
+    // Le chemin du chunk se termine par celui du point d'arrêt ("./src/main.aeg" et "main.aeg")
+    fn matches(&self, file: Option<&str>, line: usize) -> bool {
+        match (&self.file, file) {
+            (Some(wanted), Some(file)) => self.line == line && Path::new(file).ends_with(wanted.trim_start_matches("./")),
+            _ => false,
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("{}:{}", self.file.as_deref().unwrap_or("?"), self.line)
+    }
+}
+
+// Reprise demandée à la dernière invite
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Continue,
+    Step,
+    // S'arrête à la prochaine ligne d'une frame de profondeur <= depth
+    Next(usize),
+    // S'arrête dès que la profondeur passe sous depth
+    Out(usize),
+}
+
+pub(super) struct Debugger {
+    // Numérotés à partir de 1 ; None une fois supprimé (les numéros ne changent pas)
+    breakpoints: Vec<Option<Breakpoint>>,
+    mode: Mode,
+    input: Box<dyn BufRead>,
+    last_command: String,
+    // Sources déjà lues, pour afficher les lignes
+    sources: HashMap<String, Vec<String>>,
+}
+
+impl VM {
+    /// Active le débogueur : l'exécution s'arrête sur ces points d'arrêt et attend des commandes
+    /// sur `input`. Les lignes du script principal sans code sont reportées sur la suivante qui
+    /// en a ; un point d'arrêt sur un autre fichier est vérifié quand ce module s'exécute.
+    pub fn enable_debugger(&mut self, breakpoints: Vec<Breakpoint>, input: Box<dyn BufRead>) -> Result<(), String> {
+        let main = self.frames.first().map(|frame| frame.chunk()).expect("No code to execute");
+        let main_file = main.file.as_deref().map(str::to_string);
+        let mut resolved = Vec::new();
+        for Breakpoint { file, line } in breakpoints {
+            let file = file.or_else(|| main_file.clone()).ok_or("Breakpoints need a script file")?;
+            let in_main = main_file.as_deref().is_some_and(|main| Path::new(main).ends_with(file.trim_start_matches("./")));
+            let line = if in_main {
+                next_code_line(main, line).ok_or_else(|| format!("No code at or after line {} of {}", line, file))?
+            } else {
+                line
+            };
+            resolved.push(Some(Breakpoint { file: Some(file), line }));
+        }
+        self.debugger = Some(Box::new(Debugger {
+            breakpoints: resolved,
+            mode: Mode::Continue,
+            input,
+            last_command: String::new(),
+            sources: HashMap::new(),
+        }));
+        Ok(())
+    }
+
+    // Appelé au safepoint quand le débogueur est actif
+    pub(super) fn debug_hook(&mut self) -> Result<(), String> {
+        let Some(frame) = self.frames.last() else { return Ok(()) };
+        let chunk = frame.chunk();
+        let Some(line) = chunk.line_start(frame.ip) else { return Ok(()) };
+        let file = chunk.file.clone();
+        let depth = self.frames.len();
+
+        let Some(mut debugger) = self.debugger.take() else { return Ok(()) };
+        let breakpoint = debugger.breakpoints.iter()
+            .position(|bp| bp.as_ref().is_some_and(|bp| bp.matches(file.as_deref(), line)));
+        let stop = breakpoint.is_some() || match debugger.mode {
+            Mode::Continue => false,
+            Mode::Step => true,
+            Mode::Next(limit) => depth <= limit,
+            Mode::Out(limit) => depth < limit,
+        };
+        if !stop {
+            self.debugger = Some(debugger);
+            return Ok(());
+        }
+
+        let chunk = self.frames.last().unwrap().chunk();
+        match breakpoint {
+            Some(index) => println!("Breakpoint {}, {} in {}", index + 1, location(chunk, line), chunk.name),
+            None => println!("{} in {}", location(chunk, line), chunk.name),
+        }
+        debugger.print_source(file.as_deref(), line, 0);
+
+        let result = self.debug_prompt(&mut debugger, file.as_deref(), line);
+        // `quit` laisse le débogueur désactivé
+        if result.is_ok() {
+            self.debugger = Some(debugger);
+        }
+        result
+    }
+
+    // Lit des commandes jusqu'à une reprise. Fin de l'entrée : le programme continue sans débogueur.
+    fn debug_prompt(&mut self, debugger: &mut Debugger, file: Option<&str>, line: usize) -> Result<(), String> {
+        loop {
+            print!("(debug) ");
+            let _ = std::io::stdout().flush();
+            let mut input = String::new();
+            if debugger.input.read_line(&mut input).map_err(|e| e.to_string())? == 0 {
+                println!();
+                debugger.breakpoints.clear();
+                debugger.mode = Mode::Continue;
+                return Ok(());
+            }
+            let mut command = input.trim().to_string();
+            if command.is_empty() {
+                command = debugger.last_command.clone();
+            }
+            debugger.last_command = command.clone();
+            let (name, argument) = command.split_once(' ').map(|(n, a)| (n, a.trim())).unwrap_or((command.as_str(), ""));
+
+            let depth = self.frames.len();
+            match name {
+                "c" | "continue" => debugger.mode = Mode::Continue,
+                "s" | "step" => debugger.mode = Mode::Step,
+                "n" | "next" => debugger.mode = Mode::Next(depth),
+                "o" | "out" => debugger.mode = Mode::Out(depth),
+                "q" | "quit" => {
+                    // Aucun catch du script ne doit intercepter l'arrêt
+                    self.handlers.clear();
+                    return Err("Execution stopped by the debugger".to_string());
+                },
+                "l" | "locals" => {
+                    let locals = self.debug_locals();
+                    if locals.is_empty() {
+                        println!("No local variables");
+                    }
+                    for (name, value) in locals {
+                        println!("  {} = {}", name, show(&value));
+                    }
+                    continue;
+                },
+                "g" | "globals" => {
+                    for (name, value) in self.debug_globals() {
+                        println!("  {} = {}", name, show(&value));
+                    }
+                    continue;
+                },
+                "bt" | "stack" => {
+                    for (i, frame) in self.frames.iter().rev().enumerate() {
+                        println!("  #{} {}", i, self.describe_frame(i, frame));
+                    }
+                    continue;
+                },
+                ":stack" => {
+                    print!("{}", self.render_value_stack());
+                    continue;
+                },
+                "p" | "print" => {
+                    match self.debug_lookup(argument) {
+                        Some(value) => println!("{}", show(&value)),
+                        None => println!("Unknown variable '{}'", argument),
+                    }
+                    continue;
+                },
+                "list" => {
+                    debugger.print_source(file, line, 5);
+                    continue;
+                },
+                "b" | "break" if argument.is_empty() => {
+                    for (i, breakpoint) in debugger.breakpoints.iter().enumerate() {
+                        if let Some(breakpoint) = breakpoint {
+                            println!("  {} {}", i + 1, breakpoint.describe());
+                        }
+                    }
+                    continue;
+                },
+                "b" | "break" => {
+                    match argument.parse::<Breakpoint>() {
+                        Ok(mut breakpoint) => {
+                            if breakpoint.file.is_none() {
+                                breakpoint.file = file.map(str::to_string);
+                            }
+                            debugger.breakpoints.push(Some(breakpoint.clone()));
+                            println!("Breakpoint {} at {}", debugger.breakpoints.len(), breakpoint.describe());
+                        },
+                        Err(e) => println!("{}", e),
+                    }
+                    continue;
+                },
+                "d" | "delete" => {
+                    match argument.parse::<usize>().ok().and_then(|n| debugger.breakpoints.get_mut(n.wrapping_sub(1))) {
+                        Some(slot @ Some(_)) => {
+                            *slot = None;
+                            println!("Deleted breakpoint {}", argument);
+                        },
+                        _ => println!("No breakpoint number '{}'", argument),
+                    }
+                    continue;
+                },
+                "h" | "help" => {
+                    println!("{}", HELP);
+                    continue;
+                },
+                _ => {
+                    println!("Unknown command '{}' (h for help)", command);
+                    continue;
+                },
+            }
+            return Ok(());
+        }
+    }
+
+    // Locales de la frame courante, dans l'ordre des slots : pour chaque slot, le dernier nom
+    // déclaré avant l'ip ; un slot au-delà de la pile appartient à un bloc refermé ou pas encore ouvert
+    fn debug_locals(&self) -> Vec<(String, Value)> {
+        let Some(frame) = self.frames.last() else { return Vec::new() };
+        slot_names(frame).into_iter()
+            .filter(|(_, name)| !name.starts_with("__"))
+            .filter_map(|(slot, name)| {
+                let value = self.stack.get(frame.slot_offset + slot as usize)?;
+                Some((name.to_string(), value.clone()))
+            })
+            .collect()
+    }
+
+    /// Pile de valeurs, de la frame la plus récente à la plus ancienne : les slots de chaque
+    /// frame du sommet vers la base, nommés quand ils portent une variable ; les autres sont
+    /// des valeurs intermédiaires (opérandes, fonction et arguments d'un appel en préparation).
+    pub fn render_value_stack(&self) -> String {
+        let mut out = String::new();
+        let mut end = self.stack.len();
+        for (i, frame) in self.frames.iter().rev().enumerate() {
+            let chunk = frame.chunk();
+            out.push_str(&format!("#{} {}", i, self.describe_frame(i, frame)));
+            if let Some(class) = &frame.class_context {
+                out.push_str(&format!(" in class {}", class.name));
+            }
+            out.push('\n');
+
+            let start = frame.slot_offset.min(end);
+            if start == end {
+                out.push_str("    (no values)\n");
+            }
+            let names = slot_names(frame);
+            for index in (start..end).rev() {
+                let slot = (index - start) as u16;
+                let name = names.get(&slot).copied()
+                    .or_else(|| chunk.locals_map.get(&slot).map(String::as_str))
+                    .unwrap_or("(temp)");
+                out.push_str(&format!("  {:>4}  {} = {}\n", index, name, truncate(&show(&self.stack[index]), 60)));
+            }
+            end = start;
+        }
+        // Sous la première frame : valeurs qu'aucune frame ne possède (restes d'une erreur)
+        for index in (0..end).rev() {
+            out.push_str(&format!("  {:>4}  (no frame) = {}\n", index, truncate(&show(&self.stack[index]), 60)));
+        }
+        out
+    }
+
+    // "f (main.aeg:12)" ; `depth` 0 est la frame courante
+    fn describe_frame(&self, depth: usize, frame: &CallFrame) -> String {
+        let chunk = frame.chunk();
+        // L'ip d'un appelant pointe après l'appel en cours
+        let ip = if depth == 0 { frame.ip } else { frame.ip.saturating_sub(1) };
+        let line = chunk.lines.get(ip).copied().unwrap_or(0);
+        format!("{} ({})", chunk.name, location(chunk, line))
+    }
+
+    // Globales définies par le script (ni natives, ni noms internes), par ordre alphabétique
+    fn debug_globals(&self) -> Vec<(String, Value)> {
+        let natives = crate::native::get_all_names().len();
+        let mut globals: Vec<(String, Value)> = self.global_names.borrow().iter()
+            .filter(|(name, id)| **id as usize >= natives && !name.starts_with("__"))
+            .filter_map(|(name, id)| {
+                let value = self.globals.get(*id as usize)?;
+                (*value != Value::Null).then(|| (name.clone(), value.clone()))
+            })
+            .collect();
+        globals.sort_by(|a, b| a.0.cmp(&b.0));
+        globals
+    }
+
+    // `p user.name` : locale puis globale, puis champs d'instance ou clés de dictionnaire
+    fn debug_lookup(&self, path: &str) -> Option<Value> {
+        let mut parts = path.split('.').map(str::trim);
+        let first = parts.next().filter(|name| !name.is_empty())?;
+        let mut value = self.debug_locals().into_iter()
+            .rev()
+            .find(|(name, _)| name == first)
+            .map(|(_, value)| value)
+            .or_else(|| {
+                let id = *self.global_names.borrow().get(first)?;
+                self.globals.get(id as usize).cloned()
+            })?;
+        for part in parts {
+            value = match &value {
+                Value::Instance(instance) => instance.borrow().get_field(part)?,
+                Value::Dict(map) => map.borrow().get(part)?.clone(),
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+}
+
+impl Debugger {
+    // Ligne courante, avec `context` lignes de part et d'autre
+    fn print_source(&mut self, file: Option<&str>, line: usize, context: usize) {
+        let Some(file) = file else { return };
+        let lines = self.sources.entry(file.to_string()).or_insert_with(|| {
+            std::fs::read_to_string(file).map(|source| source.lines().map(str::to_string).collect()).unwrap_or_default()
+        });
+        let first = line.saturating_sub(context).max(1);
+        for number in first..=line + context {
+            let Some(text) = lines.get(number - 1) else { break };
+            let marker = if number == line { "->" } else { "  " };
+            println!("{} {:>4} | {}", marker, number, text);
+        }
+    }
+}
+
+// Première ligne >= line qui a du code, dans le chunk ou les fonctions qu'il définit
+fn next_code_line(chunk: &Chunk, line: usize) -> Option<usize> {
+    std::iter::once(chunk).chain(chunk.nested_chunks())
+        .filter_map(|chunk| chunk.line_table().range(line..).next().map(|(line, _)| *line))
+        .min()
+}
+
+// Pour chaque slot de la frame, le dernier nom déclaré avant l'ip
+fn slot_names(frame: &CallFrame) -> BTreeMap<u16, &str> {
+    let mut names = BTreeMap::new();
+    for (ip, slot, name) in &frame.chunk().local_names {
+        if *ip <= frame.ip {
+            names.insert(*slot, name.as_str());
+        }
+    }
+    names
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => format!("{}...", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+// Les chaînes entre guillemets, pour distinguer "1" de 1
+fn show(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{:?}", s),
+        // Locale capturée par une closure : on montre son contenu
+        Value::Cell(cell) => show(&cell.borrow()),
+        other => other.to_string(),
+    }
+}
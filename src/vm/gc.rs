@@ -0,0 +1,274 @@
+// Ramasse-miettes des cycles.
+//
+// Les valeurs restent comptées par `Rc` : tout ce qui n'est pas dans un cycle est libéré
+// immédiatement, comme avant. Les conteneurs mutables (listes, dicts, instances), les
+// closures et les locales qu'elles capturent sont en plus enregistrés ici par une référence
+// faible. Quand assez d'objets ont
+// été alloués depuis la dernière passe, la VM lance `collect` à son prochain safepoint.
+//
+// La collecte ne connaît pas les racines (pile, globales, natives...) : elle procède par
+// suppression à l'essai, comme le ramasse-miettes de CPython. Pour chaque objet suivi, on
+// retire de son compteur `Rc` les références qui viennent d'autres objets suivis. Un objet
+// dont il reste des références est tenu depuis l'extérieur : il est vivant, ainsi que tout
+// ce qu'il atteint. Les autres ne sont atteignables que par des cycles ; on vide leur
+// contenu, ce qui casse les cycles et laisse `Rc` les libérer.
+//
+// Une référence que la collecte ne sait pas énumérer (constantes d'un chunk, champs
+// statiques d'une classe, objet emprunté pendant la passe...) compte comme extérieure :
+// dans le doute, l'objet est gardé, jamais libéré à tort.
+
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use crate::ast::environment::Environment;
+use crate::ast::value::FunctionData;
+use crate::ast::{DictMap, InstanceData, Value};
+
+// Nombre d'allocations entre deux passes (relevé si beaucoup d'objets survivent)
+const MIN_THRESHOLD: usize = 10_000;
+
+enum Tracked {
+    List(Weak<RefCell<Vec<Value>>>),
+    Dict(Weak<RefCell<DictMap>>),
+    Instance(Weak<RefCell<InstanceData>>),
+    Function(Weak<FunctionData>),
+    Env(Weak<RefCell<Environment>>),
+    Cell(Weak<RefCell<Value>>),
+}
+
+// Objet vivant pendant une passe (la poignée compte pour 1 dans le compteur `Rc`)
+enum Node {
+    List(Rc<RefCell<Vec<Value>>>),
+    Dict(Rc<RefCell<DictMap>>),
+    Instance(Rc<RefCell<InstanceData>>),
+    Function(Rc<FunctionData>),
+    Env(Rc<RefCell<Environment>>),
+    Cell(Rc<RefCell<Value>>),
+}
+
+thread_local! {
+    static TRACKED: RefCell<Vec<Tracked>> = const { RefCell::new(Vec::new()) };
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    static THRESHOLD: Cell<usize> = const { Cell::new(MIN_THRESHOLD) };
+}
+
+/// Enregistre un objet nouvellement créé. Les autres valeurs sont ignorées.
+pub fn track(value: &Value) {
+    let entry = match value {
+        Value::List(rc) => Tracked::List(Rc::downgrade(rc)),
+        Value::Dict(rc) => Tracked::Dict(Rc::downgrade(rc)),
+        Value::Instance(rc) => Tracked::Instance(Rc::downgrade(rc)),
+        Value::Function(rc) => {
+            // Sans environnement capturé, une fonction ne peut pas fermer de cycle
+            let Some(env) = &rc.env else { return };
+            TRACKED.with(|t| t.borrow_mut().push(Tracked::Env(Rc::downgrade(env))));
+            Tracked::Function(Rc::downgrade(rc))
+        },
+        // Une locale capturée peut contenir la closure qui la capture (fonction récursive)
+        Value::Cell(rc) => Tracked::Cell(Rc::downgrade(rc)),
+        _ => return,
+    };
+    TRACKED.with(|t| t.borrow_mut().push(entry));
+    ALLOCATED.with(|a| a.set(a.get() + 1));
+}
+
+/// Crée une liste suivie par le ramasse-miettes.
+pub fn list(items: Vec<Value>) -> Value {
+    let value = Value::List(Rc::new(RefCell::new(items)));
+    track(&value);
+    value
+}
+
+/// Crée un dict suivi par le ramasse-miettes.
+pub fn dict(entries: DictMap) -> Value {
+    let value = Value::Dict(Rc::new(RefCell::new(entries)));
+    track(&value);
+    value
+}
+
+/// Vrai quand assez d'objets ont été alloués pour justifier une passe.
+pub fn collection_due() -> bool {
+    ALLOCATED.with(|a| a.get()) >= THRESHOLD.with(|t| t.get())
+}
+
+/// Nombre d'objets actuellement suivis (vivants ou pas encore purgés).
+pub fn tracked_count() -> usize {
+    TRACKED.with(|t| t.borrow().len())
+}
+
+impl Node {
+    fn key(&self) -> usize {
+        match self {
+            Node::List(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Dict(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Instance(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Function(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Env(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Cell(rc) => Rc::as_ptr(rc) as *const () as usize,
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Node::List(rc) => Rc::strong_count(rc),
+            Node::Dict(rc) => Rc::strong_count(rc),
+            Node::Instance(rc) => Rc::strong_count(rc),
+            Node::Function(rc) => Rc::strong_count(rc),
+            Node::Env(rc) => Rc::strong_count(rc),
+            Node::Cell(rc) => Rc::strong_count(rc),
+        }
+    }
+
+    // Ajoute à `out` les adresses des objets directement référencés, une par référence `Rc`
+    // détenue. Faux si l'objet est emprunté en écriture : on ne peut pas savoir ce qu'il contient.
+    fn children(&self, out: &mut Vec<usize>) -> bool {
+        match self {
+            Node::List(rc) => match rc.try_borrow() {
+                Ok(list) => list.iter().for_each(|v| push_value(out, v)),
+                Err(_) => return false,
+            },
+            Node::Dict(rc) => match rc.try_borrow() {
+                Ok(dict) => dict.values().for_each(|v| push_value(out, v)),
+                Err(_) => return false,
+            },
+            Node::Instance(rc) => match rc.try_borrow() {
+                Ok(inst) => inst.slots.iter().chain(inst.fields.values()).for_each(|v| push_value(out, v)),
+                Err(_) => return false,
+            },
+            Node::Function(rc) => {
+                if let Some(env) = &rc.env {
+                    out.push(Rc::as_ptr(env) as *const () as usize);
+                }
+            },
+            Node::Env(rc) => match rc.try_borrow() {
+                Ok(env) => {
+                    env.variables.values().for_each(|v| push_value(out, v));
+                    if let Some(parent) = &env.parent {
+                        out.push(Rc::as_ptr(parent) as *const () as usize);
+                    }
+                },
+                Err(_) => return false,
+            },
+            Node::Cell(rc) => match rc.try_borrow() {
+                Ok(value) => push_value(out, &value),
+                Err(_) => return false,
+            },
+        }
+        true
+    }
+
+    // Vide un objet injoignable : ses références disparaissent, les cycles sont cassés
+    fn clear(&self) {
+        match self {
+            Node::List(rc) => if let Ok(mut l) = rc.try_borrow_mut() { l.clear() },
+            Node::Dict(rc) => if let Ok(mut d) = rc.try_borrow_mut() { d.clear() },
+            Node::Instance(rc) => if let Ok(mut inst) = rc.try_borrow_mut() {
+                inst.slots.iter_mut().for_each(|slot| *slot = Value::Null);
+                inst.fields.clear();
+            },
+            Node::Function(_) => {}, // Immuable : libérée quand son environnement est vidé
+            Node::Env(rc) => if let Ok(mut env) = rc.try_borrow_mut() {
+                env.variables.clear();
+                env.parent = None;
+            },
+            Node::Cell(rc) => if let Ok(mut value) = rc.try_borrow_mut() { *value = Value::Null },
+        }
+    }
+}
+
+fn push_value(out: &mut Vec<usize>, value: &Value) {
+    let address = match value {
+        Value::List(rc) => Rc::as_ptr(rc) as *const () as usize,
+        Value::Dict(rc) => Rc::as_ptr(rc) as *const () as usize,
+        Value::Instance(rc) => Rc::as_ptr(rc) as *const () as usize,
+        Value::Function(rc) => Rc::as_ptr(rc) as *const () as usize,
+        Value::Cell(rc) => Rc::as_ptr(rc) as *const () as usize,
+        _ => return,
+    };
+    out.push(address);
+}
+
+/// Cherche les cycles injoignables et les libère. Renvoie le nombre d'objets libérés.
+pub fn collect() -> usize {
+    ALLOCATED.with(|a| a.set(0));
+
+    // 1. Objets encore vivants (les entrées mortes sont purgées au passage)
+    let mut nodes: Vec<Node> = TRACKED.with(|t| {
+        let mut tracked = t.borrow_mut();
+        let mut nodes = Vec::with_capacity(tracked.len());
+        tracked.retain(|entry| {
+            let node = match entry {
+                Tracked::List(w) => w.upgrade().map(Node::List),
+                Tracked::Dict(w) => w.upgrade().map(Node::Dict),
+                Tracked::Instance(w) => w.upgrade().map(Node::Instance),
+                Tracked::Function(w) => w.upgrade().map(Node::Function),
+                Tracked::Env(w) => w.upgrade().map(Node::Env),
+                Tracked::Cell(w) => w.upgrade().map(Node::Cell),
+            };
+            match node {
+                Some(node) => { nodes.push(node); true },
+                None => false,
+            }
+        });
+        nodes
+    });
+
+    // Index trié par adresse (un même objet peut avoir été enregistré deux fois : une seule
+    // poignée par objet, sinon les compteurs seraient faux)
+    nodes.sort_unstable_by_key(Node::key);
+    nodes.dedup_by_key(|n| n.key());
+    let keys: Vec<usize> = nodes.iter().map(Node::key).collect();
+
+    // 2. Références venues de l'extérieur = compteur - poignée de la passe - références internes
+    let mut external: Vec<isize> = nodes.iter().map(|n| n.strong_count() as isize - 1).collect();
+    // Arêtes vers d'autres objets suivis, à plat : celles du nœud i sont edges[starts[i]..starts[i + 1]]
+    let mut edges: Vec<usize> = Vec::new();
+    let mut starts: Vec<usize> = Vec::with_capacity(nodes.len() + 1);
+    // Un objet emprunté pendant la passe est tenu par du code en cours : vivant
+    let mut alive = vec![false; nodes.len()];
+    let mut scratch = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        starts.push(edges.len());
+        scratch.clear();
+        if !node.children(&mut scratch) {
+            alive[i] = true;
+            continue;
+        }
+        for address in &scratch {
+            if let Ok(k) = keys.binary_search(address) {
+                external[k] -= 1;
+                edges.push(k);
+            }
+        }
+    }
+    starts.push(edges.len());
+
+    // 3. Tout ce qu'atteint un objet tenu de l'extérieur est vivant
+    let mut pending: Vec<usize> = (0..nodes.len()).filter(|&i| external[i] > 0 || alive[i]).collect();
+    for &i in &pending {
+        alive[i] = true;
+    }
+    while let Some(i) = pending.pop() {
+        for &k in &edges[starts[i]..starts[i + 1]] {
+            if !alive[k] {
+                alive[k] = true;
+                pending.push(k);
+            }
+        }
+    }
+
+    // 4. Le reste n'est tenu que par des cycles : on le vide
+    let mut freed = 0;
+    for (node, alive) in nodes.iter().zip(&alive) {
+        if !alive {
+            node.clear();
+            freed += 1;
+        }
+    }
+
+    // Les poignées de la passe tombent ici, et avec elles les objets vidés
+    let survivors = nodes.len() - freed;
+    drop(nodes);
+    THRESHOLD.with(|t| t.set(survivors.max(MIN_THRESHOLD)));
+    freed
+}
@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+// Demande de l'hôte, traitée par la VM au prochain safepoint (avant chaque instruction)
+#[derive(Debug, Clone, PartialEq)]
+enum Request {
+    None,
+    Pause,
+    Abort(String),
+}
+
+/// État de la VM au moment où elle s'est mise en pause.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PauseInfo {
+    pub line: usize,
+    pub call_depth: usize,
+    pub stack_size: usize,
+}
+
+struct State {
+    request: Request,
+    paused: Option<PauseInfo>,
+}
+
+struct Shared {
+    // Chemin rapide : la VM ne prend le verrou que si une demande est en attente
+    pending: AtomicBool,
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+/// Poignée thread-safe pour piloter une VM depuis l'hôte (débogueur, moteur de jeu, timeout).
+/// La VM tourne sur son propre thread, la poignée peut être clonée et envoyée ailleurs.
+///
+/// ```ignore
+/// let interrupt = vm.interrupt_handle();
+/// interrupt.pause();
+/// if let Some(info) = interrupt.wait_paused(Duration::from_secs(1)) {
+///     println!("En pause ligne {}", info.line);
+/// }
+/// interrupt.resume();
+/// ```
+#[derive(Clone)]
+pub struct VmInterrupt {
+    shared: Arc<Shared>,
+}
+
+impl Default for VmInterrupt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VmInterrupt {
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                pending: AtomicBool::new(false),
+                state: Mutex::new(State { request: Request::None, paused: None }),
+                wake: Condvar::new(),
+            }),
+        }
+    }
+
+    fn request(&self, request: Request) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.request = request;
+        self.shared.pending.store(state.request != Request::None, Ordering::Release);
+        self.shared.wake.notify_all();
+    }
+
+    /// Met la VM en pause au prochain safepoint (le thread de la VM est bloqué jusqu'à `resume`).
+    pub fn pause(&self) {
+        self.request(Request::Pause);
+    }
+
+    /// Relance une VM en pause (ou annule une pause pas encore atteinte).
+    pub fn resume(&self) {
+        self.request(Request::None);
+    }
+
+    /// Interrompt le script avec une erreur, qu'un `try/catch` du script peut attraper.
+    pub fn abort(&self, reason: &str) {
+        self.request(Request::Abort(reason.to_string()));
+    }
+
+    /// État de la VM si elle est actuellement en pause.
+    pub fn paused(&self) -> Option<PauseInfo> {
+        self.shared.state.lock().unwrap().paused.clone()
+    }
+
+    /// Attend que la VM atteigne la pause demandée (None si le délai expire).
+    pub fn wait_paused(&self, timeout: Duration) -> Option<PauseInfo> {
+        let state = self.shared.state.lock().unwrap();
+        let (state, _) = self.shared.wake
+            .wait_timeout_while(state, timeout, |s| s.paused.is_none() && s.request == Request::Pause)
+            .unwrap();
+        state.paused.clone()
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_pending(&self) -> bool {
+        self.shared.pending.load(Ordering::Acquire)
+    }
+
+    // Appelé par la VM quand une demande est en attente. Bloque tant que la pause dure.
+    pub(crate) fn checkpoint(&self, info: PauseInfo) -> Result<(), String> {
+        let mut state = self.shared.state.lock().unwrap();
+        let mut info = Some(info);
+
+        loop {
+            match state.request.clone() {
+                Request::None => {
+                    state.paused = None;
+                    return Ok(());
+                },
+                Request::Abort(reason) => {
+                    state.request = Request::None;
+                    state.paused = None;
+                    self.shared.pending.store(false, Ordering::Release);
+                    return Err(format!("Execution aborted: {}", reason));
+                },
+                Request::Pause => {
+                    if let Some(info) = info.take() {
+                        state.paused = Some(info);
+                        self.shared.wake.notify_all();
+                    }
+                    state = self.shared.wake.wait(state).unwrap();
+                },
+            }
+        }
+    }
+}
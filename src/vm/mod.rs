@@ -1,18 +1,35 @@
+mod arena;
 pub mod compiler;
 pub mod debug;
+pub mod debugger;
+pub mod gc;
+pub mod interrupt;
+pub mod optimizer;
+mod scheduler;
+mod snapshot;
+mod stats;
+pub(crate) mod workers;
 
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::rc::Rc;
 
-use crate::ast::value::{ClassData, FunctionData, Visibility};
-use crate::ast::{InstanceData, Value};
+use crate::ast::value::{ClassData, FunctionData, Visibility, CONSTRUCTOR};
+use crate::ast::{DictMap, InstanceData, Str, Value, ValueKey};
 use crate::chunk::Chunk;
+use crate::native::{Args, NativeError};
+use crate::native::args::f32_from_number;
 use crate::opcode::OpCode;
 use crate::ast::environment::Environment;
+use interrupt::{PauseInfo, VmInterrupt};
+use scheduler::Scheduler;
 
 const STACK_MAX: usize = 4096;
 
+// Instructions exécutées par la boucle rapide entre deux passages par les services de la VM
+const SERVICE_INTERVAL: u32 = 256;
+
 #[allow(dead_code)]
 const FRAMES_MAX: usize = 64;
 
@@ -22,16 +39,28 @@ struct CallFrame {
     ip: usize,          // Où on en est dans CETTE fonction
     slot_offset: usize, // Où commencent ses variables locales dans la pile globale (Base Pointer)
     class_context: Option<Rc<ClassData>>, // La classe dans laquelle on s'exécute (pour private/protected)
+    defers: Vec<Value>, // Closures enregistrées par `defer`, exécutées en ordre inverse à la sortie
+    resources: Vec<Value>, // Ressources des blocs `with` en cours, fermées même sur return ou erreur
 }
 
 impl CallFrame {
-    fn chunk(&self) -> &Chunk {
+    fn function(&self) -> &Rc<FunctionData> {
         match &self.closure {
             // On accède via le Rc
-            Value::Function(rc_fn) => &rc_fn.chunk,
+            Value::Function(rc_fn) => rc_fn,
             _ => panic!("CallFrame closure is not a function"),
         }
     }
+
+    fn chunk(&self) -> &Chunk {
+        &self.function().chunk
+    }
+
+    // Frame de base posée par l'hôte (REPL, tâche `async`) : chunk vide, sans ligne à montrer,
+    // absente des piles d'appels
+    fn is_host_base(&self) -> bool {
+        self.chunk().code.is_empty()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -39,32 +68,110 @@ struct ExceptionHandler {
     frame_index: usize, // L'index de la frame dans vm.frames
     catch_ip: usize,    // L'adresse du bloc catch
     stack_height: usize, // La hauteur de la pile de valeurs à restaurer
+    resources_len: usize, // Ressources `with` de la frame ouvertes avant le try (les suivantes sont fermées)
+}
+
+/// Photo de l'état global de la VM, pour annuler une évaluation ratée (REPL transactionnel).
+/// Les liaisons globales sont restaurées, ainsi que le contenu des listes, dicts, instances...
+/// qu'elles atteignent (voir snapshot.rs).
+pub struct VmSnapshot {
+    globals: Vec<Value>,
+    heap: snapshot::HeapSnapshot,
+    global_names: HashMap<String, u16>,
+    modules: HashMap<String, LoadedModule>,
+    frames_len: usize,
+    stack_len: usize,
+    handlers_len: usize,
+}
+
+// Module déjà exécuté : l'objet renvoyé aux importeurs, et les noms publics qu'il a définis
+// sous un nom renommé (chargé isolé), à recopier si un `import "x"` simple le demande ensuite
+#[derive(Clone)]
+struct LoadedModule {
+    value: Value,
+    renamed_exports: Vec<(String, String)>,
+    // Docstring du fichier (chaîne littérale ou `///` en tête)
+    doc: Option<String>,
+}
+
+/// Résultat de `VM::run_budget`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunState {
+    /// Le script est terminé.
+    Finished,
+    /// Budget épuisé : un nouvel appel à `run_budget` reprend exactement là où on s'est arrêté.
+    Suspended,
 }
 
 pub struct VM {
     frames: Vec<CallFrame>,
+    // Registres de la frame courante (la dernière de `frames`) : la boucle d'exécution y lit
+    // les instructions et leurs opérandes sans repasser par `frames`. L'ip rangée dans la frame
+    // n'est à jour qu'après save_ip() ; load_frame() recharge les registres après un appel,
+    // un retour ou un catch.
+    ip: usize,
+    function: Rc<FunctionData>,
     stack: Vec<Value>,
     globals: Vec<Value>,
-    global_names: Rc<RefCell<HashMap<String, u8>>>,
+    global_names: Rc<RefCell<HashMap<String, u16>>>,
     handlers: Vec<ExceptionHandler>,
-    modules: HashMap<String, Value>,
+    // Handlers sous cet index appartiennent à une boucle d'exécution englobante
+    // (run_callable_sync) : une erreur levée dans la boucle courante ne doit pas y sauter
+    handler_floor: usize,
+    modules: HashMap<String, LoadedModule>,
+    // Version du langage utilisée pour compiler les modules importés
+    pub lang_version: u8,
+    // --strict, appliqué aussi aux modules importés
+    pub strict: bool,
+    // Niveau d'optimisation et tables de lignes des modules importés (profil d'un bundle)
+    pub opt_level: u8,
+    pub debug_info: bool,
+    // Sources embarquées par `aegis bundle` (chemin normalisé -> source), lues avant le disque
+    pub bundled: HashMap<String, String>,
+    // Demandes de pause/arrêt venant de l'hôte
+    interrupt: VmInterrupt,
+    // Concaténations sur de longues chaînes par site (code du chunk, ip) : repère les `s += ...` en boucle
+    concat_sites: HashMap<(usize, usize), usize>,
+    // Tâches des fonctions `async` (voir scheduler.rs)
+    scheduler: Scheduler,
+    // Appels en cours (closure, ip) quand la dernière erreur attrapée a été levée : la pile
+    // d'une erreur convertie en RuntimeError par `catch (e: T)`, une fois les frames dépilées
+    caught_trace: Vec<(Value, usize)>,
+    // Tampons des chaînes temporaires, réutilisés d'une expression à l'autre (voir arena.rs)
+    scratch: arena::Scratch,
+    // Compteurs de `--stats` (voir stats.rs), None sinon
+    stats: Option<Box<stats::Stats>>,
+    // Points d'arrêt et invite de `--break` (voir debugger.rs), None sinon
+    debugger: Option<Box<debugger::Debugger>>,
 }
 
+// Méthodes natives des dicts (op_method), utilisées aussi pour les suggestions d'erreur
+const DICT_METHODS: [&str; 8] = ["insert", "keys", "get", "is_empty", "len", "remove", "values", "contains"];
+
+// Une chaîne de gauche de cette taille recopiée autant de fois au même endroit déclenche l'indice
+const CONCAT_HINT_MIN_LEN: usize = 1024;
+const CONCAT_HINT_THRESHOLD: usize = 100;
+
 impl VM {
-    pub fn new(main_chunk: Chunk, global_names: Rc<RefCell<HashMap<String, u8>>>, args: Vec<String>) -> Self {
-        let main_func = Value::Function(Rc::new(FunctionData {
+    pub fn new(main_chunk: Chunk, global_names: Rc<RefCell<HashMap<String, u16>>>, args: Vec<String>) -> Self {
+        let main_function = Rc::new(FunctionData {
             params: vec![],
             ret_type: None,
             chunk: main_chunk,
-            env: None
-        }));
+            env: None,
+            doc: None,
+            variadic: false,
+            entry_points: Vec::new(),
+        });
 
         // Le script principal est la première "fonction" exécutée
         let main_frame = CallFrame {
-            closure: main_func, // Utilise la closure
+            closure: Value::Function(main_function.clone()), // Utilise la closure
             ip: 0,
             slot_offset: 0,
-            class_context: None
+            class_context: None,
+            defers: Vec::new(),
+            resources: Vec::new(),
         };
 
         // 1. On détermine la taille nécessaire
@@ -77,35 +184,50 @@ impl VM {
 
         let mut vm = VM {
             frames: Vec::with_capacity(64),
+            ip: 0,
+            function: main_function,
             stack: Vec::with_capacity(STACK_MAX),
             globals: vec![Value::Null; initial_size],
             global_names,
             handlers: Vec::new(),
-            modules: HashMap::new()
+            handler_floor: 0,
+            modules: HashMap::new(),
+            lang_version: 1,
+            strict: false,
+            opt_level: 0,
+            debug_info: true,
+            bundled: HashMap::new(),
+            interrupt: VmInterrupt::new(),
+            concat_sites: HashMap::new(),
+            scheduler: Scheduler::default(),
+            caught_trace: Vec::new(),
+            scratch: arena::Scratch::default(),
+            stats: None,
+            debugger: None,
         };
 
         vm.frames.push(main_frame);
 
         let natives = crate::native::get_all_names();
 
-        // Sécurité : On ne peut pas avoir plus de 256 globales avec des ID sur u8
-        if natives.len() > 256 {
-            panic!("Trop de fonctions natives pour la VM v2 (>256)");
+        // Sécurité : les ID de globales sont des u16
+        if natives.len() > u16::MAX as usize + 1 {
+            panic!("Trop de fonctions natives pour la VM v2 (>65536)");
         }
 
         for (i, name) in natives.into_iter().enumerate() {
             vm.globals[i] = Value::Native(name);
         }
 
-        let args_values: Vec<Value> = args.iter().map(|s| Value::String(s.clone())).collect();
-        let args_list = Value::List(Rc::new(RefCell::new(args_values)));
+        let args_values: Vec<Value> = args.iter().map(|s| Value::String(s.clone().into())).collect();
+        let args_list = gc::list(args_values);
 
         // On doit trouver l'ID de "_ARGS" (ou un nom réservé)
         // Astuce : On l'ajoute manuellement à global_names et globals
         {
             let mut names = vm.global_names.borrow_mut();
             if !names.contains_key("__ARGS__") {
-                let id = names.len() as u8;
+                let id = names.len() as u16;
                 names.insert("__ARGS__".to_string(), id);
                 // Si jamais on dépasse la taille initiale (peu probable avec le max(..., 256))
                 if id as usize >= vm.globals.len() {
@@ -127,6 +249,37 @@ impl VM {
         self.frames.last_mut().expect("No code to execute")
     }
 
+    // Range l'ip du registre dans la frame courante, avant que la pile d'appels ne change ou
+    // ne soit lue (trace d'une erreur, débogueur, pause)
+    #[inline(always)]
+    fn save_ip(&mut self) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.ip = self.ip;
+        }
+    }
+
+    // Recharge les registres depuis la nouvelle frame courante
+    #[inline(always)]
+    fn load_frame(&mut self) {
+        if let Some(frame) = self.frames.last() {
+            self.ip = frame.ip;
+            self.function = frame.function().clone();
+        }
+    }
+
+    fn push_frame(&mut self, frame: CallFrame) {
+        self.save_ip();
+        self.frames.push(frame);
+        self.load_frame();
+    }
+
+    fn pop_frame(&mut self) -> Option<CallFrame> {
+        self.save_ip();
+        let frame = self.frames.pop();
+        self.load_frame();
+        frame
+    }
+
     // Helper pour la pile
     #[inline(always)]
     fn push(&mut self, value: Value) {
@@ -139,74 +292,343 @@ impl VM {
     }
 
     #[inline(always)]
-    fn step(&mut self) -> Result<bool, String> {
+    fn step(&mut self) -> Result<bool, NativeError> {
         // 1. Gestion des fins de Frames (Return implicite)
         // On vérifie d'abord si l'IP est au bout du code de la frame actuelle
-        if self.current_frame().ip >= self.current_frame().chunk().code.len() {
-            if self.frames.len() > 1 {
-                self.frames.pop();
-                return Ok(true); // On continue sur la frame parente
-            } else {
-                return Ok(false); // Plus de frames, fin du programme
+        let result = if self.ip >= self.function.chunk.code.len() {
+            self.implicit_return()
+        } else {
+            // 2. SAFEPOINT : pause ou arrêt demandé par l'hôte
+            // L'arrêt passe par le même chemin qu'une erreur d'instruction (donc attrapable)
+            match self.safepoint() {
+                Ok(()) => {
+                    // 3. FETCH
+                    let byte = self.read_byte();
+                    let op: OpCode = byte.into();
+                    if self.stats.is_some() {
+                        // --stats / --profile : comptage, et chronométrage de l'instruction
+                        let started = self.stats_record(op);
+                        let result = self.execute_op(op);
+                        self.stats_time(op, started);
+                        result
+                    } else {
+                        // EXECUTE WITH INTERCEPTION
+                        self.execute_op(op)
+                    }
+                },
+                Err(e) => Err(e.into()),
             }
+        };
+
+        match result {
+            Ok(keep_going) => Ok(keep_going),
+            Err(msg) => self.catch_error(msg),
         }
+    }
 
-        // 2. FETCH
-        let byte = self.read_byte();
-        let op: OpCode = byte.into();
+    // Boucle rapide de run() : l'instruction est lue directement dans les registres de la frame
+    // courante (ip et fonction), et les services (ramasse-miettes, pause ou arrêt demandés par l'hôte) ne sont
+    // consultés qu'une instruction sur SERVICE_INTERVAL au lieu de chacune.
+    // Le débogueur et --stats / --profile observent chaque instruction : ils passent par step().
+    // Rend la main à run() à la fin de la frame (Return implicite), à la fin du programme ou
+    // après une erreur.
+    fn dispatch(&mut self) -> Result<bool, NativeError> {
+        let mut budget = SERVICE_INTERVAL;
+        loop {
+            budget -= 1;
+            if budget == 0 {
+                return self.step();
+            }
 
-        // EXECUTE WITH INTERCEPTION
-        let result = self.execute_op(op);
+            let Some(&byte) = self.function.chunk.code.get(self.ip) else {
+                return self.step();
+            };
+            self.ip += 1;
 
-        match result {
-            Ok(keep_going) => Ok(keep_going),
-            Err(msg) => {
-                if let Some(handler) = self.handlers.pop() {
-                    // 1. Unwind frames
-                    while self.frames.len() > handler.frame_index + 1 {
-                        self.frames.pop();
-                    }
-                    
-                    // 2. Restore Stack - C'EST LA CLÉ
-                    // On coupe brutalement la pile à la hauteur enregistrée lors du 'try'
-                    if handler.stack_height <= self.stack.len() {
-                        self.stack.truncate(handler.stack_height);
-                    } else {
-                        // Corruption grave : la pile est plus petite qu'au début du try !
-                        return Err("Critical VM Error: Stack corrupted during unwind".into());
-                    }
-                    
-                    // 3. Push Error
-                    self.push(Value::String(msg));
-                    
-                    // 4. Jump
-                    self.current_frame().ip = handler.catch_ip;
-                    Ok(true) 
-                } else {
-                    Err(msg)
+            match self.execute_op(OpCode::from(byte)) {
+                Ok(true) => continue,
+                Ok(false) => return Ok(false),
+                Err(msg) => return self.catch_error(msg),
+            }
+        }
+    }
+
+    // Erreur levée par une instruction : saut au `catch` le plus proche de cette boucle
+    // d'exécution, ou propagation à l'appelant s'il n'y en a pas
+    fn catch_error(&mut self, mut error: NativeError) -> Result<bool, NativeError> {
+        self.save_ip();
+        let catchable = self.handlers.len() > self.handler_floor;
+        if let Some(handler) = self.handlers.pop_if(|_| catchable) {
+            self.caught_trace = self.capture_trace();
+
+            // 1. Unwind frames (leurs ressources `with` et leurs `defer` s'exécutent ; une erreur
+            // dans ce code remplace celle en cours et sera attrapée par le même handler)
+            while self.frames.len() > handler.frame_index + 1 {
+                let frame = self.pop_frame().unwrap();
+                if let Err(e) = self.close_frame(frame) {
+                    error = e;
                 }
             }
+
+            // Les blocs `with` ouverts dans le try se referment avant d'entrer dans le catch
+            let opened = self.current_frame().resources.split_off(handler.resources_len);
+            if let Err(e) = self.close_resources(opened) {
+                error = e;
+            }
+            
+            // 2. Restore Stack - C'EST LA CLÉ
+            // On coupe brutalement la pile à la hauteur enregistrée lors du 'try'
+            if handler.stack_height <= self.stack.len() {
+                self.stack.truncate(handler.stack_height);
+            } else {
+                // Corruption grave : la pile est plus petite qu'au début du try !
+                return Err("Critical VM Error: Stack corrupted during unwind".into());
+            }
+            
+            // 3. Push Error (valeur levée par `throw`, instance d'Error si une native l'a structurée)
+            let error_value = error.into_value();
+            if let Value::Instance(instance) = &error_value
+                && crate::native::errors::is_error_class(&instance.borrow().class)
+                && instance.borrow().get_field("stack").is_none_or(|s| s == Value::Null) {
+                let stack = self.format_trace(&self.caught_trace);
+                instance.borrow_mut().set_field("stack".to_string(), stack);
+            }
+            self.push(error_value);
+            
+            // 4. Jump
+            self.ip = handler.catch_ip;
+            Ok(true) 
+        } else {
+            Err(error)
+        }
+    }
+
+    // Fin du code de la frame courante sans `return` explicite
+    fn implicit_return(&mut self) -> Result<bool, NativeError> {
+        if self.frames.len() > 1 {
+            let frame = self.pop_frame().unwrap();
+            let slot_offset = frame.slot_offset;
+            self.discard_frame_handlers();
+            self.close_frame(frame)?;
+
+            // Return implicite : on nettoie la pile comme OpCode::Return et on rend null
+            // (slot_offset 0 = chunk exécuté par l'hôte, sa pile reste à l'appelant)
+            if slot_offset > 0 {
+                self.stack.truncate(slot_offset - 1);
+                self.push(Value::Null);
+            }
+            Ok(true) // On continue sur la frame parente
+        } else {
+            // Fin du script principal : ses `defer` s'exécutent avant de rendre la main
+            let (defers, context) = {
+                let frame = self.current_frame();
+                (std::mem::take(&mut frame.defers), frame.class_context.clone())
+            };
+            self.run_defers(defers, context)?;
+            Ok(false) // Plus de frames, fin du programme
+        }
+    }
+
+    // Une frame se termine : ses ressources `with` encore ouvertes (return dans le bloc) se
+    // ferment d'abord, puis ses `defer` s'exécutent. La dernière erreur est renvoyée.
+    fn close_frame(&mut self, frame: CallFrame) -> Result<(), NativeError> {
+        let closed = self.close_resources(frame.resources);
+        let deferred = self.run_defers(frame.defers, frame.class_context);
+        deferred.and(closed)
+    }
+
+    fn close_resources(&mut self, resources: Vec<Value>) -> Result<(), NativeError> {
+        let mut result = Ok(());
+        for resource in resources.into_iter().rev() {
+            if let Err(e) = self.exit_resource(resource) {
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    // Protocole de `with` : méthode __exit__() (prioritaire) ou close() d'une instance,
+    // ou fonction rangée sous l'une de ces clés dans un dictionnaire
+    fn exit_method(resource: &Value) -> Option<(Value, Vec<Value>, Option<Rc<ClassData>>)> {
+        const EXIT_METHODS: [&str; 2] = ["__exit__", "close"];
+        match resource {
+            Value::Instance(instance) => {
+                let class = instance.borrow().class.clone();
+                EXIT_METHODS.iter()
+                    .find_map(|name| class.lookup_method(name))
+                    .map(|(method, owner)| (method, vec![resource.clone()], Some(owner)))
+            },
+            Value::Dict(map) => {
+                let map = map.borrow();
+                EXIT_METHODS.iter()
+                    .find_map(|name| map.get(*name).filter(|v| matches!(v, Value::Function(_) | Value::Native(_))).cloned())
+                    .map(|function| (function, vec![], None))
+            },
+            _ => None,
+        }
+    }
+
+    fn exit_resource(&mut self, resource: Value) -> Result<(), NativeError> {
+        let (method, args, context) = Self::exit_method(&resource)
+            .ok_or_else(|| format!("'with' resource of type {} has no close() or __exit__() method", resource.type_name()))?;
+        self.run_callable_sync(method, args, context).map(|_| ())
+    }
+
+    // Exécute les `defer` d'une frame qui se termine, du dernier enregistré au premier.
+    // Tous s'exécutent même si l'un échoue ; la dernière erreur est renvoyée (comme en Go).
+    fn run_defers(&mut self, defers: Vec<Value>, context: Option<Rc<ClassData>>) -> Result<(), NativeError> {
+        let mut result = Ok(());
+        for deferred in defers.into_iter().rev() {
+            if let Err(e) = self.run_callable_sync(deferred, vec![], context.clone()) {
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    // Erreur non rattrapée : on formate le message (pile d'appels comprise) puis on exécute
+    // les `defer` de toutes les frames encore ouvertes, de la plus récente à la plus ancienne
+    fn uncaught_error(&mut self, error: NativeError) -> String {
+        self.save_ip();
+        let mut report = self.runtime_error(error.message);
+        for depth in (0..self.frames.len()).rev() {
+            let resources = std::mem::take(&mut self.frames[depth].resources);
+            if let Err(e) = self.close_resources(resources) {
+                report.push_str(&format!("\nError while closing 'with' resource: {}", e));
+            }
+            let defers = std::mem::take(&mut self.frames[depth].defers);
+            let context = self.frames[depth].class_context.clone();
+            if let Err(e) = self.run_defers(defers, context) {
+                report.push_str(&format!("\nError in deferred code: {}", e));
+            }
+        }
+        report
+    }
+
+    #[inline(always)]
+    fn safepoint(&mut self) -> Result<(), String> {
+        // Entre deux instructions, aucun objet n'est emprunté par la VM : bon moment pour les cycles
+        if gc::collection_due() {
+            gc::collect();
+        }
+        if self.debugger.is_some() {
+            self.save_ip();
+            self.debug_hook()?;
+        }
+        if !self.interrupt.is_pending() {
+            return Ok(());
+        }
+        self.save_ip();
+
+        let info = PauseInfo {
+            line: self.current_line(),
+            call_depth: self.frames.len(),
+            stack_size: self.stack.len(),
+        };
+        self.interrupt.checkpoint(info)
+    }
+
+    /// Poignée pour mettre en pause, relancer ou interrompre cette VM depuis un autre thread.
+    pub fn interrupt_handle(&self) -> VmInterrupt {
+        self.interrupt.clone()
+    }
+
+    // TailCall : la frame courante peut-elle céder sa place à l'appelé ?
+    // Un appel terminal ne remplace la frame courante que si rien ne doit s'y exécuter après
+    // l'appel (defer, with, try) et que l'appelé est une fonction Aegis qui empilera la sienne.
+    // Sinon TailCall se comporte comme Call, et le Return suivant termine la frame normalement.
+    fn can_reuse_frame(&self, target: &Value, arg_count: usize) -> bool {
+        let Value::Function(callee) = target else { return false };
+        // La frame du script principal reste : son Return termine le programme
+        if self.frames.len() < 2 || callee.chunk.is_async || !callee.accepts(arg_count) {
+            return false;
+        }
+        let depth = self.frames.len() - 1;
+        let frame = &self.frames[depth];
+        frame.defers.is_empty() && frame.resources.is_empty()
+            && self.handlers.last().is_none_or(|h| h.frame_index < depth)
+    }
+
+    // Retire les handlers des frames qui n'existent plus
+    fn discard_frame_handlers(&mut self) {
+        let depth = self.frames.len();
+        while self.handlers.last().is_some_and(|h| h.frame_index >= depth) {
+            self.handlers.pop();
         }
     }
 
     pub fn run(&mut self) -> Result<(), String> {
         loop {
-            match self.step() {
+            let result = if self.debugger.is_some() || self.stats.is_some() {
+                self.step()
+            } else {
+                self.dispatch()
+            };
+            match result {
                 Ok(true) => continue, // Continue loop
                 Ok(false) => break,   // End of program
                 Err(e) => {
                     // C'est ici qu'on enrichit l'erreur !
-                    return Err(self.runtime_error(e));
+                    return Err(self.uncaught_error(e));
                 }
             }
         }
-        Ok(())
+        // Les tâches lancées mais jamais attendues terminent avant la fin du programme
+        self.finish_tasks().map_err(|e| e.message)
+    }
+
+    /// Exécute au plus `n_ops` instructions puis rend la main (ordonnancement coopératif :
+    /// un moteur de jeu peut faire avancer plusieurs scripts à chaque tick, sans threads).
+    /// Un callback appelé depuis une instruction (map, filter, constructeur...) s'exécute
+    /// en entier dans le cadre de cette instruction. Comme pour `run`, les tâches lancées mais
+    /// jamais attendues s'exécutent jusqu'au bout avant `Finished`, hors budget.
+    pub fn run_budget(&mut self, n_ops: usize) -> Result<RunState, NativeError> {
+        for _ in 0..n_ops {
+            match self.step() {
+                Ok(true) => continue,
+                Ok(false) => {
+                    self.finish_tasks()?;
+                    return Ok(RunState::Finished);
+                },
+                Err(e) => {
+                    let value = e.value.clone();
+                    return Err(NativeError { message: self.uncaught_error(e), value });
+                },
+            }
+        }
+        Ok(RunState::Suspended)
     }
 
     // --- NOUVEAU : Helper pour MAP/FILTER ---
     // Cette fonction exécute une fonction Aegis (callback) de façon synchrone
     // C'est une "mini-vm" à l'intérieur de l'instruction
-    fn run_callable_sync(&mut self, callable: Value, args: Vec<Value>, context: Option<Rc<ClassData>>) -> Result<Value, String> {
+    fn run_callable_sync(&mut self, callable: Value, args: Vec<Value>, context: Option<Rc<ClassData>>) -> Result<Value, NativeError> {
+        // Un `await` dans ce callback ne pourra pas suspendre la tâche en cours (scheduler.rs)
+        self.scheduler.sync_depth += 1;
+        let result = self.run_callable_nested(callable, args, context);
+        self.scheduler.sync_depth -= 1;
+        result
+    }
+
+    // assert_throws(fn, expected?) : appelle fn, qui doit échouer ; renvoie la valeur levée
+    fn assert_throws(&mut self, args: Vec<Value>) -> Result<Value, NativeError> {
+        let args = crate::native::Args::new("assert_throws(fn, expected?)", &args);
+        let function = args.want(0)?;
+        if !matches!(function, Value::Function(_) | Value::Native(_)) {
+            return Err(args.expected(0, "a function").into());
+        }
+        let thrown = match self.run_callable_sync(function.clone(), Vec::new(), None) {
+            Ok(_) => None,
+            Err(error) => Some(error.into_value()),
+        };
+        crate::native::testing::check_thrown(args.get(1), thrown.as_ref())?;
+        Ok(thrown.unwrap_or(Value::Null))
+    }
+
+    fn run_callable_nested(&mut self, callable: Value, args: Vec<Value>, context: Option<Rc<ClassData>>) -> Result<Value, NativeError> {
+        // Hauteur de pile à rendre à l'appelant si le callback échoue
+        let stack_base = self.stack.len();
+
         // 1. On empile la fonction et les arguments comme un appel normal
         self.push(callable.clone());
         for arg in args.iter() {
@@ -215,15 +637,30 @@ impl VM {
 
         // 2. On prépare la Frame (comme OpCode::Call)
         // Note: call_value empile la nouvelle frame
-        self.call_value(callable, args.len(), context)?;
+        let depth_before = self.frames.len();
+        if let Err(e) = self.call_value(callable, args.len(), context) {
+            self.stack.truncate(stack_base);
+            return Err(e);
+        }
+
+        // Native, constructeur ou fonction `async` : pas de frame à exécuter, le résultat est déjà sur la pile
+        if self.frames.len() == depth_before {
+            return Ok(self.pop());
+        }
 
         // 3. On note la profondeur actuelle de la pile de frames
         let start_depth = self.frames.len();
 
+        // Seuls les try/catch ouverts PENDANT ce callback peuvent attraper ses erreurs :
+        // ceux de l'appelant seront traités par la boucle englobante, une fois l'erreur remontée.
+        let outer_floor = self.handler_floor;
+        self.handler_floor = self.handlers.len();
+
         // 4. BOUCLE SECONDAIRE : On exécute tant qu'on n'est pas revenu au niveau d'avant
         // C'est ici la magie : on fait tourner la VM "manuellement" pour ce callback
         while self.frames.len() >= start_depth {
             if self.frames.is_empty() {
+                self.handler_floor = outer_floor;
                 return Err("VM Panic: Call stack exhausted during sync execution".into());
             }
 
@@ -232,12 +669,28 @@ impl VM {
                 Ok(false) => break, // Fin normale du programme (ne devrait pas arriver ici)
                 Err(e) => {
                     // Si une erreur survient et n'est pas attrapée par un try/catch interne,
-                    // elle remonte ici. On doit propager l'erreur et arrêter la mini-VM.
-                    return Err(self.runtime_error(e));
+                    // elle remonte ici. On abandonne les frames du callback (et leurs handlers),
+                    // on rend la pile telle qu'avant l'appel, puis on propage l'erreur à la boucle
+                    // englobante : ses try/catch (autour du map, du sort...) la traitent comme
+                    // n'importe quelle erreur de l'instruction en cours.
+                    // Le message reste brut : c'est la boucle englobante qui l'attrapera ou le formatera.
+                    self.handlers.truncate(self.handler_floor);
+                    self.handler_floor = outer_floor;
+                    let mut e = e;
+                    while self.frames.len() >= start_depth {
+                        let frame = self.pop_frame().unwrap();
+                        if let Err(cleanup_error) = self.close_frame(frame) {
+                            e = cleanup_error;
+                        }
+                    }
+                    self.stack.truncate(stack_base);
+                    return Err(e);
                 }
             }
         }
 
+        self.handler_floor = outer_floor;
+
         // 5. Le résultat est sur la pile (la valeur de retour du callback)
         // Normalement, `OpCode::Return` a laissé la valeur de retour sur la pile
         if self.stack.is_empty() {
@@ -248,14 +701,19 @@ impl VM {
     }
 
     #[inline(always)]
-    fn execute_op(&mut self, op: OpCode) -> Result<bool, String> {
+    fn execute_op(&mut self, op: OpCode) -> Result<bool, NativeError> {
         // 2. EXECUTE
         match op {
             OpCode::Return => {
                 let result = self.pop(); // La valeur de retour
 
-                // On détruit la frame
-                let frame = self.frames.pop().expect("No frame to return from");
+                // On détruit la frame (et les try encore ouverts dedans, ex: return dans un try)
+                let frame = self.pop_frame().expect("No frame to return from");
+                self.discard_frame_handlers();
+
+                // Les `with` ouverts se ferment et les `defer` s'exécutent après le calcul de la valeur de retour
+                let slot_offset = frame.slot_offset;
+                self.close_frame(frame)?;
 
                 if self.frames.is_empty() {
                     // Fin du script principal
@@ -264,15 +722,15 @@ impl VM {
 
                 // Nettoyage de la pile : on enlève les arguments et les variables locales de la fonction
                 // On remet la pile à l'état "avant l'appel" + le résultat
-                self.stack.truncate(frame.slot_offset - 1);
+                self.stack.truncate(slot_offset - 1);
                 self.push(result);
             }
             OpCode::Call => {
-                let arg_count = self.read_byte() as usize;
+                let arg_count = self.read_short() as usize;
                 
                 // SÉCURITÉ : Vérifier qu'on a assez d'éléments sur la pile
                 if self.stack.len() < 1 + arg_count {
-                    return Err(format!("Stack underflow during Call (args: {})", arg_count));
+                    return Err(format!("Stack underflow during Call (args: {})", arg_count).into());
                 }
 
                 let func_idx = self.stack.len() - 1 - arg_count;
@@ -282,13 +740,30 @@ impl VM {
                 
                 self.call_value(target, arg_count, None)?;
             },
+            OpCode::TailCall => {
+                let arg_count = self.read_short() as usize;
+                if self.stack.len() < 1 + arg_count {
+                    return Err(format!("Stack underflow during Call (args: {})", arg_count).into());
+                }
+                let func_idx = self.stack.len() - 1 - arg_count;
+                let target = self.stack[func_idx].clone();
+
+                // La frame courante disparaît : l'appelé et ses arguments prennent sa place sur la
+                // pile, et le Return qui suit dans l'appelant n'est jamais atteint
+                if self.can_reuse_frame(&target, arg_count) {
+                    let frame = self.pop_frame().expect("No frame to return from");
+                    self.stack.drain(frame.slot_offset - 1..func_idx);
+                }
+                self.call_value(target, arg_count, None)?;
+            },
             OpCode::Print => {
                 let val = self.pop();
                 println!("{}", val);
             }
+            OpCode::Await => self.await_top()?,
             OpCode::LoadConst => {
-                let idx = self.read_byte();
-                let val = self.current_frame().chunk().constants[idx as usize].clone();
+                let idx = self.read_short();
+                let val = self.scratch.clone_value(&self.function.chunk.constants[idx as usize]);
                 self.push(val);
             }
             OpCode::Add => {
@@ -300,7 +775,7 @@ impl VM {
 
                 // FAST PATH : Si ce sont deux entiers, on calcule et on écrase
                 if let (Value::Integer(b), Value::Integer(a)) = (right, left) {
-                    let res = a + b;
+                    let res = a.checked_add(*b).ok_or_else(|| int_overflow(*a, "+", *b))?;
                     // On retire virtuellement un élément (pop)
                     self.stack.truncate(len - 1);
                     // On écrase le dernier élément restant par le résultat
@@ -309,29 +784,50 @@ impl VM {
                 // SLOW PATH : Le reste (String, Float...)
                 else {
                     let b = self.pop();
-                    let a = self.pop();
+                    let mut a = self.pop();
 
-                    match (a, b) {
+                    if matches!(a, Value::String(_)) != matches!(b, Value::String(_)) && self.function.chunk.strict {
+                        return Err(strict_concat_error(&a, &b));
+                    }
+
+                    match (&mut a, &b) {
                         // Float + Float
-                        (Value::Float(v1), Value::Float(v2)) => self.push(Value::Float(v1 + v2)),
+                        (Value::Float(v1), Value::Float(v2)) => self.push(Value::Float(*v1 + *v2)),
                         // Float + Int (Coercition)
                         (Value::Float(v1), Value::Integer(v2)) => {
-                            self.push(Value::Float(v1 + v2 as f64))
+                            self.push(Value::Float(*v1 + *v2 as f64))
                         }
                         (Value::Integer(v1), Value::Float(v2)) => {
-                            self.push(Value::Float(v1 as f64 + v2))
+                            self.push(Value::Float(*v1 as f64 + *v2))
                         }
 
-                        // String + N'importe quoi
+                        // String + N'importe quoi : on complète directement le tampon de gauche
                         (Value::String(s1), val2) => {
-                            self.push(Value::String(format!("{}{}", s1, val2)));
+                            let extra = if let Value::String(s2) = val2 { s2.len() } else { 16 };
+                            let mut s1 = self.scratch.take_string(s1, extra);
+                            self.record_concat(s1.len());
+                            match val2 {
+                                Value::String(s2) => s1.push_str(s2),
+                                other => {
+                                    s1.reserve(16);
+                                    let _ = write!(s1, "{}", other);
+                                }
+                            }
+                            self.push(Value::String(s1.into()));
                         }
                         (val1, Value::String(s2)) => {
-                            self.push(Value::String(format!("{}{}", val1, s2)));
+                            let mut out = self.scratch.buffer(s2.len() + 16);
+                            let _ = write!(out, "{}", val1);
+                            out.push_str(s2);
+                            self.push(Value::String(out.into()));
                         }
 
-                        _ => return Err("Type error in ADD".into()),
+                        (a, b) => match time_arithmetic('+', a, b) {
+                            Some(result) => self.push(result?),
+                            None => return Err("Type error in ADD".into()),
+                        },
                     }
+                    self.scratch.recycle(b);
                 }
             }
             OpCode::Sub => {
@@ -341,7 +837,7 @@ impl VM {
 
                 // FAST PATH : Integer - Integer
                 if let (Value::Integer(b), Value::Integer(a)) = (b_ref, a_ref) {
-                    let res = a - b;
+                    let res = a.checked_sub(*b).ok_or_else(|| int_overflow(*a, "-", *b))?;
                     // On supprime le dernier élément (b)
                     self.stack.truncate(len - 1);
                     // On remplace l'avant-dernier (a) par le résultat
@@ -355,7 +851,10 @@ impl VM {
                         (Value::Float(v1), Value::Float(v2)) => self.push(Value::Float(v1 - v2)),
                         (Value::Integer(v1), Value::Float(v2)) => self.push(Value::Float(v1 as f64 - v2)),
                         (Value::Float(v1), Value::Integer(v2)) => self.push(Value::Float(v1 - v2 as f64)),
-                        _ => return Err("Type error in SUB".into())
+                        (a, b) => match time_arithmetic('-', &a, &b) {
+                            Some(result) => self.push(result?),
+                            None => return Err("Type error in SUB".into()),
+                        },
                     }
                 }
             },
@@ -363,11 +862,17 @@ impl VM {
                 let b = self.pop();
                 let a = self.pop();
                 match (a, b) {
-                    (Value::Integer(v1), Value::Integer(v2)) => self.push(Value::Integer(v1 * v2)),
+                    (Value::Integer(v1), Value::Integer(v2)) => {
+                        let res = v1.checked_mul(v2).ok_or_else(|| int_overflow(v1, "*", v2))?;
+                        self.push(Value::Integer(res))
+                    },
                     (Value::Float(v1), Value::Float(v2)) => self.push(Value::Float(v1 * v2)),
                     (Value::Integer(v1), Value::Float(v2)) => self.push(Value::Float(v1 as f64 * v2)),
                     (Value::Float(v1), Value::Integer(v2)) => self.push(Value::Float(v1 * v2 as f64)),
-                    _ => return Err("Type error in MUL".into())
+                    (a, b) => match time_arithmetic('*', &a, &b) {
+                        Some(result) => self.push(result?),
+                        None => return Err("Type error in MUL".into()),
+                    },
                 }
             },
             OpCode::Div => {
@@ -376,16 +881,50 @@ impl VM {
                 match (a, b) {
                     (Value::Integer(v1), Value::Integer(v2)) => {
                         if v2 == 0 { return Err("Division by zero".into()); }
-                        self.push(Value::Integer(v1 / v2))
+                        // Seul cas : le plus petit int divisé par -1
+                        let res = v1.checked_div(v2).ok_or_else(|| int_overflow(v1, "/", v2))?;
+                        self.push(Value::Integer(res))
                     },
                     (Value::Float(v1), Value::Float(v2)) => self.push(Value::Float(v1 / v2)),
                     (Value::Integer(v1), Value::Float(v2)) => self.push(Value::Float(v1 as f64 / v2)),
                     (Value::Float(v1), Value::Integer(v2)) => self.push(Value::Float(v1 / v2 as f64)),
-                    _ => return Err("Type error in DIV".into())
+                    (a, b) => match time_arithmetic('/', &a, &b) {
+                        Some(result) => self.push(result?),
+                        None => return Err("Type error in DIV".into()),
+                    },
+                }
+            },
+            OpCode::FloorDiv => {
+                let b = self.pop();
+                let a = self.pop();
+                match (a, b) {
+                    (Value::Integer(_), Value::Integer(0)) => return Err("Division by zero".into()),
+                    (Value::Integer(v1), Value::Integer(v2)) => {
+                        let res = floor_div(v1, v2).ok_or_else(|| int_overflow(v1, "~/", v2))?;
+                        self.push(Value::Integer(res))
+                    },
+                    (a @ (Value::Integer(_) | Value::Float(_)), b @ (Value::Integer(_) | Value::Float(_))) => {
+                        self.push(Value::Float((a.as_float()? / b.as_float()?).floor()))
+                    },
+                    _ => return Err("Type error in FLOOR_DIV".into()),
+                }
+            },
+            OpCode::FloatDiv => {
+                let b = self.pop();
+                let a = self.pop();
+                match (a, b) {
+                    (Value::Integer(_), Value::Integer(0)) => return Err("Division by zero".into()),
+                    (a @ (Value::Integer(_) | Value::Float(_)), b @ (Value::Integer(_) | Value::Float(_))) => {
+                        self.push(Value::Float(a.as_float()? / b.as_float()?))
+                    },
+                    (a, b) => match time_arithmetic('/', &a, &b) {
+                        Some(result) => self.push(result?),
+                        None => return Err("Type error in DIV".into()),
+                    },
                 }
             },
             OpCode::SetGlobal => {
-                let idx = self.read_byte() as usize;
+                let idx = self.read_short() as usize;
                 let val = self.pop();
 
                 // Si l'index est plus grand que le tableau, on agrandit (sécurité)
@@ -396,12 +935,12 @@ impl VM {
                 self.globals[idx] = val;
             }
             OpCode::GetGlobal => {
-                let idx = self.read_byte() as usize;
+                let idx = self.read_short() as usize;
     
                 // 1. On récupère la valeur brute. 
                 // Si l'index est hors limite (ne devrait pas arriver si le compilateur est bon), on met Null.
                 let mut val = if idx < self.globals.len() {
-                    self.globals[idx].clone()
+                    self.scratch.clone_value(&self.globals[idx])
                 } else {
                     Value::Null
                 };
@@ -418,32 +957,45 @@ impl VM {
                 self.push(val);
             },
             OpCode::GetLocal => {
-                let slot_idx = self.read_byte() as usize;
+                let slot_idx = self.read_short() as usize;
                 let abs_index = self.current_frame().slot_offset + slot_idx;
                 
                 // VERSION SAFE
                 if let Some(val) = self.stack.get(abs_index) {
-                    self.push(val.clone());
+                    let val = match val {
+                        // Locale capturée par une closure : on lit la case partagée
+                        Value::Cell(cell) => cell.borrow().clone(),
+                        val => self.scratch.clone_value(val),
+                    };
+                    self.push(val);
                 } else {
                     return Err(format!("Stack access out of bounds (local: {}, abs: {}, stack_len: {})", 
-                        slot_idx, abs_index, self.stack.len()));
+                        slot_idx, abs_index, self.stack.len()).into());
                 }
             }
             OpCode::SetLocal => {
-                let slot_idx = self.read_byte() as usize;
+                let slot_idx = self.read_short() as usize;
                 let abs_index = self.current_frame().slot_offset + slot_idx;
 
                 let val = self.stack.last().expect("Stack empty").clone(); // Peek
-                self.stack[abs_index] = val;
+                let old = match &self.stack[abs_index] {
+                    Value::Cell(cell) => cell.replace(val),
+                    _ => std::mem::replace(&mut self.stack[abs_index], val),
+                };
+                self.scratch.recycle(old);
                 // Note : SetLocal ne pop pas forcément la valeur (expression),
                 // mais pour simplifier ici on peut dire qu'elle reste sur la pile.
             }
             OpCode::Jump => {
                 let offset = self.read_short();
-                self.current_frame().ip += offset as usize;
+                self.ip += offset as usize;
             }
-            OpCode::JumpIfFalse => {
-                let offset = self.read_short();
+            OpCode::JumpLong => {
+                let offset = self.read_long();
+                self.ip += offset as usize;
+            }
+            OpCode::JumpIfFalse | OpCode::JumpIfFalseLong => {
+                let offset = if matches!(op, OpCode::JumpIfFalseLong) { self.read_long() } else { self.read_short() as u32 };
                 // On peek la valeur (on ne la pop pas tout de suite pour la logique,
                 // mais dans un if simple, le compilateur a émis un POP après)
                 let condition = self.stack.last().expect("Empty stack");
@@ -457,22 +1009,29 @@ impl VM {
                 };
 
                 if is_false {
-                    self.current_frame().ip += offset as usize;
+                    self.ip += offset as usize;
                 }
             }
             OpCode::Loop => {
                 let offset = self.read_short();
                 // On soustrait l'offset à l'IP (on recule)
-                self.current_frame().ip -= offset as usize;
+                self.ip -= offset as usize;
+            }
+            OpCode::LoopLong => {
+                let offset = self.read_long();
+                self.ip -= offset as usize;
             }
             OpCode::Pop => {
-                self.pop();
+                let val = self.pop();
+                self.scratch.recycle(val);
             }
             OpCode::Modulo => {
                 let b = self.pop();
                 let a = self.pop();
                 match (a, b) {
-                    (Value::Integer(v1), Value::Integer(v2)) => self.push(Value::Integer(v1 % v2)),
+                    (Value::Integer(_), Value::Integer(0)) => return Err("Division by zero".into()),
+                    // Le plus petit int % -1 vaut 0 : wrapping_rem ne déborde jamais
+                    (Value::Integer(v1), Value::Integer(v2)) => self.push(Value::Integer(v1.wrapping_rem(v2))),
                     _ => return Err("Type error %".into()),
                 }
             }
@@ -480,29 +1039,49 @@ impl VM {
                 let b = self.pop();
                 let a = self.pop();
                 self.push(Value::Boolean(a == b));
+                self.scratch.recycle(a);
+                self.scratch.recycle(b);
             }
             OpCode::NotEqual => {
                 let b = self.pop();
                 let a = self.pop();
                 self.push(Value::Boolean(a != b));
+                self.scratch.recycle(a);
+                self.scratch.recycle(b);
+            }
+            OpCode::Same => {
+                let b = self.pop();
+                let a = self.pop();
+                self.push(Value::Boolean(a.is_same(&b)));
+            }
+            OpCode::NotSame => {
+                let b = self.pop();
+                let a = self.pop();
+                self.push(Value::Boolean(!a.is_same(&b)));
             }
             OpCode::Greater => {
                 let b = self.pop();
                 let a = self.pop();
-                if let (Value::Integer(v1), Value::Integer(v2)) = (a, b) {
+                if let (Value::Integer(v1), Value::Integer(v2)) = (&a, &b) {
                     self.push(Value::Boolean(v1 > v2));
                 } else {
-                    self.push(Value::Boolean(false));
+                    let holds = ordering_holds(&a, &b, ">", std::cmp::Ordering::is_gt)?;
+                    self.push(Value::Boolean(holds));
                 }
+                self.scratch.recycle(a);
+                self.scratch.recycle(b);
             }
             OpCode::GreaterEqual => {
                 let b = self.pop();
                 let a = self.pop();
-                if let (Value::Integer(v1), Value::Integer(v2)) = (a, b) {
+                if let (Value::Integer(v1), Value::Integer(v2)) = (&a, &b) {
                     self.push(Value::Boolean(v1 >= v2));
                 } else {
-                    self.push(Value::Boolean(false));
+                    let holds = ordering_holds(&a, &b, ">=", std::cmp::Ordering::is_ge)?;
+                    self.push(Value::Boolean(holds));
                 }
+                self.scratch.recycle(a);
+                self.scratch.recycle(b);
             }
             OpCode::Less => {let len = self.stack.len();
                 if len < 2 { return Err("Stack underflow in LESS".into()); }
@@ -523,23 +1102,26 @@ impl VM {
                     let a = self.pop();
                     if let (Value::Integer(v1), Value::Integer(v2)) = (&a, &b) {
                         self.push(Value::Boolean(v1 < v2));
-                    } else if let (Value::Float(v1), Value::Float(v2)) = (&a, &b) {
-                        self.push(Value::Boolean(v1 < v2));
                     } else {
-                        // Comparaison mixte ou autre
-                        // Note: Pour être rigoureux, il faudrait gérer Float vs Int ici aussi
-                        self.push(Value::Boolean(false));
+                        // Types non comparables (ex: Integer vs String) : TypeError
+                        let holds = ordering_holds(&a, &b, "<", std::cmp::Ordering::is_lt)?;
+                        self.push(Value::Boolean(holds));
                     }
+                    self.scratch.recycle(a);
+                    self.scratch.recycle(b);
                 }
             }
             OpCode::LessEqual => {
                 let b = self.pop();
                 let a = self.pop();
-                if let (Value::Integer(v1), Value::Integer(v2)) = (a, b) {
+                if let (Value::Integer(v1), Value::Integer(v2)) = (&a, &b) {
                     self.push(Value::Boolean(v1 <= v2));
                 } else {
-                    self.push(Value::Boolean(false));
+                    let holds = ordering_holds(&a, &b, "<=", std::cmp::Ordering::is_le)?;
+                    self.push(Value::Boolean(holds));
                 }
+                self.scratch.recycle(a);
+                self.scratch.recycle(b);
             }
             OpCode::Not => {
                 let val = self.pop();
@@ -569,65 +1151,62 @@ impl VM {
             OpCode::ShiftLeft => {
                 let b = self.pop().as_int().unwrap_or(0);
                 let a = self.pop().as_int().unwrap_or(0);
-                self.push(Value::Integer(a << b));
+                self.push(Value::Integer(shift_left(a, b)?));
             }
             OpCode::ShiftRight => {
                 let b = self.pop().as_int().unwrap_or(0);
                 let a = self.pop().as_int().unwrap_or(0);
-                self.push(Value::Integer(a >> b));
+                self.push(Value::Integer(a >> shift_amount(b)?));
             }
             OpCode::MakeList => {
-                let count = self.read_byte() as usize;
+                let count = self.read_short() as usize;
                 let mut items = Vec::new();
                 // On dépile dans l'ordre inverse pour retrouver l'ordre initial
                 for _ in 0..count {
                     items.push(self.pop());
                 }
                 items.reverse();
-                self.push(Value::List(std::rc::Rc::new(std::cell::RefCell::new(
-                    items,
-                ))));
+                self.push(gc::list(items));
             }
             OpCode::Method => self.op_method()?,
             OpCode::MakeDict => {
-                let count = self.read_byte() as usize; // Nombre d'éléments total sur la pile (clés + valeurs)
+                let count = self.read_short() as usize; // Nombre d'éléments total sur la pile (clés + valeurs)
                 let num_pairs = count / 2;
-                let mut dict = HashMap::new();
-
-                // Pile : [k1, v1, k2, v2...]
-                // Pop : v2, k2, v1, k1...
-
-                for _ in 0..num_pairs {
-                    let val = self.pop();
-                    let key_val = self.pop();
-                    let key = key_val.as_str().unwrap_or("unknown".to_string());
-                    dict.insert(key, val);
+                let mut dict = DictMap::with_capacity(num_pairs);
+
+                // Pile : [k1, v1, k2, v2...] : insérées dans l'ordre du littéral (la dernière
+                // valeur d'une clé répétée l'emporte)
+                let start = self.stack.len() - num_pairs * 2;
+                let mut entries = self.stack.drain(start..);
+                while let (Some(key_val), Some(val)) = (entries.next(), entries.next()) {
+                    dict.insert(ValueKey::from_value(&key_val)?, val);
                 }
+                drop(entries);
 
-                self.push(Value::Dict(Rc::new(RefCell::new(dict))));
+                self.push(gc::dict(dict));
             }
             OpCode::GetAttr => {
-                let name_idx = self.read_byte();
-                let attr_name = self.current_frame().chunk().constants[name_idx as usize].to_string();
+                let name_idx = self.read_short();
+                let attr_name = self.function.chunk.constants[name_idx as usize].to_string();
                 let obj = self.pop();
 
-                match obj {
+                match &obj {
                     Value::Instance(inst) => {
                         let class_rc = inst.borrow().class.clone();
+
+                        // 0. Cache en ligne : même classe qu'au dernier passage -> lecture directe du slot
+                        if let Some(slot) = self.function.chunk.attr_cache.get(name_idx, &class_rc) {
+                            let val = inst.borrow().slots[slot].clone();
+                            self.push(val);
+                            return Ok(true);
+                        }
+
                         self.check_access(&class_rc, &attr_name)?;
 
                         // 1. Check Properties (Instance)
                         // On doit chercher dans toute la hiérarchie
-                        let mut lookup_class = Some(class_rc.clone());
-                        let mut found_prop = None;
-                        
-                        while let Some(c) = lookup_class {
-                            if let Some(prop) = c.properties.get(&attr_name) {
-                                found_prop = Some((prop.clone(), c.clone()));
-                                break;
-                            }
-                            lookup_class = c.parent_ref.clone();
-                        }
+                        let found_prop = class_rc.lineage()
+                            .find_map(|c| c.properties.get(&attr_name).cloned().map(|prop| (prop, c.clone())));
 
                         if let Some((prop, owner_class)) = found_prop {
                             if let Some(getter) = &prop.getter {
@@ -637,16 +1216,17 @@ impl VM {
                                 self.call_value(getter.clone(), 1, Some(owner_class))?; 
                                 return Ok(true); // On laisse la VM exécuter le getter
                             } else {
-                                return Err(format!("Property '{}' is write-only", attr_name));
+                                return Err(format!("Property '{}' is write-only", attr_name).into());
                             }
                         }
 
-                        // 2. Champs classiques
-                        let val = inst.borrow().fields.get(&attr_name).cloned().unwrap_or(Value::Null);
+                        // 2. Champs classiques (slot déclaré ou champ dynamique)
+                        self.cache_field_slot(name_idx, &class_rc, &attr_name);
+                        let val = inst.borrow().get_field(&attr_name).unwrap_or(Value::Null);
                         self.push(val);
                     }
                     Value::Class(class_rc) => {
-                        self.check_access(&class_rc, &attr_name)?;
+                        self.check_access(class_rc, &attr_name)?;
 
                         // 1. Check Static Properties
                         // Pour l'instant on cherche juste dans la classe elle-même (pas d'héritage statique complexe)
@@ -658,7 +1238,7 @@ impl VM {
                                 self.call_value(getter.clone(), 1, Some(class_rc.clone()))?;
                                 return Ok(true);
                             } else {
-                                return Err(format!("Static Property '{}' is write-only", attr_name));
+                                return Err(format!("Static Property '{}' is write-only", attr_name).into());
                             }
                         }
 
@@ -670,7 +1250,7 @@ impl VM {
                         else if let Some(method) = class_rc.static_methods.get(&attr_name) {
                             self.push(method.clone());
                         } else {
-                            return Err(format!("Unknown static member '{}'", attr_name));
+                            return Err(format!("Unknown static member '{}'", attr_name).into());
                         }
                     }
                     Value::Dict(d) => {
@@ -693,27 +1273,28 @@ impl VM {
                 }
             }
             OpCode::SetAttr => {
-                let name_idx = self.read_byte();
-                let attr_name = self.current_frame().chunk().constants[name_idx as usize].to_string();
+                let name_idx = self.read_short();
+                let attr_name = self.function.chunk.constants[name_idx as usize].to_string();
 
                 let val = self.pop(); // La valeur à assigner
                 let obj = self.pop(); // L'objet
 
-                match obj {
+                match &obj {
                     Value::Instance(inst) => {
                         let class_rc = inst.borrow().class.clone();
+
+                        // 0. Cache en ligne : écriture directe du slot
+                        if let Some(slot) = self.function.chunk.attr_cache.get(name_idx, &class_rc) {
+                            inst.borrow_mut().slots[slot] = val.clone();
+                            self.push(val);
+                            return Ok(true);
+                        }
+
                         self.check_access(&class_rc, &attr_name)?;
 
                         // 1. Check Properties (Instance)
-                        let mut lookup_class = Some(class_rc.clone());
-                        let mut found_prop = None;
-                        while let Some(c) = lookup_class {
-                            if let Some(prop) = c.properties.get(&attr_name) {
-                                found_prop = Some((prop.clone(), c.clone()));
-                                break;
-                            }
-                            lookup_class = c.parent_ref.clone();
-                        }
+                        let found_prop = class_rc.lineage()
+                            .find_map(|c| c.properties.get(&attr_name).cloned().map(|prop| (prop, c.clone())));
 
                         if let Some((prop, owner_class)) = found_prop {
                             if let Some(setter) = &prop.setter {
@@ -726,16 +1307,17 @@ impl VM {
                                 self.call_value(setter.clone(), 2, Some(owner_class))?;
                                 return Ok(true);
                             } else {
-                                return Err(format!("Property '{}' is read-only", attr_name));
+                                return Err(format!("Property '{}' is read-only", attr_name).into());
                             }
                         }
 
-                        // 2. Champs classiques
-                        inst.borrow_mut().fields.insert(attr_name, val.clone());
+                        // 2. Champs classiques (slot déclaré ou champ dynamique)
+                        self.cache_field_slot(name_idx, &class_rc, &attr_name);
+                        inst.borrow_mut().set_field(attr_name, val.clone());
                         self.push(val);
                     }
                     Value::Class(class_rc) => {
-                        self.check_access(&class_rc, &attr_name)?;
+                        self.check_access(class_rc, &attr_name)?;
 
                         // 1. Check Static Properties
                         if let Some(prop) = class_rc.static_properties.get(&attr_name) {
@@ -746,7 +1328,7 @@ impl VM {
                                 self.call_value(setter.clone(), 2, Some(class_rc.clone()))?;
                                 return Ok(true);
                             } else {
-                                return Err(format!("Static Property '{}' is read-only", attr_name));
+                                return Err(format!("Static Property '{}' is read-only", attr_name).into());
                             }
                         }
 
@@ -755,7 +1337,7 @@ impl VM {
                         self.push(val);
                     }
                     Value::Dict(d) => {
-                        d.borrow_mut().insert(attr_name, val.clone());
+                        d.borrow_mut().insert(attr_name.into(), val.clone());
                         self.push(val);
                     }
                     Value::Enum(_) => {
@@ -777,34 +1359,34 @@ impl VM {
                 std::io::stdin().read_line(&mut buffer).unwrap();
                 let input = buffer.trim().to_string();
 
-                self.push(Value::String(input));
+                self.push(Value::String(input.into()));
             }
 
             OpCode::Class => {
-                let idx = self.read_byte();
-                let template_val = self.current_frame().chunk().constants[idx as usize].clone();
+                let idx = self.read_short();
+                let template_val = self.function.chunk.constants[idx as usize].clone();
                 
-                if let Value::Class(template_data) = template_val {
+                if let Value::Class(ref template_data) = template_val {
                     // ---------------------------------------------------------
                     // 1. RESOLUTION DU PARENT
                     // ---------------------------------------------------------
                     let mut final_parent_ref = None;
                     if let Some(parent_name) = &template_data.parent {
                         if let Some(parent_val) = self.get_global_by_name(parent_name) {
-                            if let Value::Class(parent_rc) = parent_val {
+                            if let Value::Class(ref parent_rc) = parent_val {
                                 final_parent_ref = Some(parent_rc.clone());
                             } else {
-                                return Err(format!("Parent '{}' is not a class", parent_name));
+                                return Err(format!("Parent '{}' is not a class", parent_name).into());
                             }
                         } else {
-                            return Err(format!("Parent class '{}' not found", parent_name));
+                            return Err(format!("Parent class '{}' not found", parent_name).into());
                         }
                     }
 
                     // Check Final Class (Parent)
                     if let Some(parent_rc) = &final_parent_ref {
                         if parent_rc.is_final {
-                            return Err(format!("Erreur: La classe '{}' ne peut pas hériter de '{}' car elle est marquée 'final'.", template_data.name, parent_rc.name));
+                            return Err(format!("Erreur: La classe '{}' ne peut pas hériter de '{}' car elle est marquée 'final'.", template_data.name, parent_rc.name).into());
                         }
                     }
 
@@ -814,13 +1396,13 @@ impl VM {
                     let mut resolved_interfaces = Vec::new();
                     for iface_name in &template_data.interfaces_names {
                         if let Some(val) = self.get_global_by_name(iface_name) {
-                            if let Value::Interface(iface_rc) = val {
+                            if let Value::Interface(ref iface_rc) = val {
                                 resolved_interfaces.push(iface_rc.clone());
                             } else {
-                                return Err(format!("'{}' is not an interface", iface_name));
+                                return Err(format!("'{}' is not an interface", iface_name).into());
                             }
                         } else {
-                            return Err(format!("Interface '{}' not found", iface_name));
+                            return Err(format!("Interface '{}' not found", iface_name).into());
                         }
                     }
 
@@ -830,7 +1412,7 @@ impl VM {
                     let final_class_rc = Rc::new(ClassData {
                         name: template_data.name.clone(),
                         parent: template_data.parent.clone(),
-                        parent_ref: final_parent_ref.clone(),
+                        parent_ref: None,
                         methods: template_data.methods.clone(),
                         visibilities: template_data.visibilities.clone(),
                         fields: template_data.fields.clone(),
@@ -848,7 +1430,14 @@ impl VM {
                         // On injecte les interfaces résolues
                         interfaces: resolved_interfaces.clone(),
                         interfaces_names: template_data.interfaces_names.clone(),
-                    });
+
+                        ancestors: Vec::new(),
+                        method_table: HashMap::new(),
+                        static_method_table: HashMap::new(),
+                        field_slots: HashMap::new(),
+                        field_layout: Vec::new(),
+                        doc: template_data.doc.clone(),
+                    }.link(final_parent_ref.clone()));
 
                     // ---------------------------------------------------------
                     // 4. VERIFICATIONS DE CONFORMITÉ
@@ -858,12 +1447,8 @@ impl VM {
                     if let Some(parent_rc) = &final_parent_ref {
                         let check_override = |methods_map: &HashMap<String, Value>| -> Result<(), String> {
                             for method_name in methods_map.keys() {
-                                let mut curr = Some(parent_rc.clone());
-                                while let Some(p) = curr {
-                                    if p.final_methods.contains(method_name) {
-                                        return Err(format!("Erreur: Impossible de surcharger la méthode finale '{}' de la classe '{}'.", method_name, p.name));
-                                    }
-                                    curr = p.parent_ref.clone();
+                                if let Some(p) = parent_rc.lineage().find(|p| p.final_methods.contains(method_name)) {
+                                    return Err(format!("Erreur: Impossible de surcharger la méthode finale '{}' de la classe '{}'.", method_name, p.name));
                                 }
                             }
                             Ok(())
@@ -879,7 +1464,7 @@ impl VM {
                             let found_method = self.find_method(&final_class_rc, method_name);
                             
                             if let Some(m_val) = found_method {
-                                if let Value::Function(f) = m_val {
+                                if let Value::Function(ref f) = m_val {
                                     // Arity check (params.len() inclut 'this', donc -1)
                                     let actual_arity = if f.params.len() > 0 { f.params.len() - 1 } else { 0 };
                                     
@@ -887,14 +1472,14 @@ impl VM {
                                         return Err(format!(
                                             "Class '{}' implements interface '{}' incorrectly: Method '{}' expects {} arguments, got {}.",
                                             template_data.name, iface_rc.name, method_name, expected_arity, actual_arity
-                                        ));
+                                        ).into());
                                     }
                                 }
                             } else {
                                 return Err(format!(
                                     "Class '{}' must implement method '{}' from interface '{}'.",
                                     template_data.name, method_name, iface_rc.name
-                                ));
+                                ).into());
                             }
                         }
                     }
@@ -904,9 +1489,9 @@ impl VM {
                     // ---------------------------------------------------------
                     let static_inits = template_data.static_fields.borrow().clone();
                     for (name, init_val_or_func) in static_inits {
-                        let final_val = if let Value::Function(func) = init_val_or_func {
+                        let final_val = if matches!(init_val_or_func, Value::Function(_)) {
                             self.run_callable_sync(
-                                Value::Function(func), 
+                                init_val_or_func, 
                                 vec![], 
                                 Some(final_class_rc.clone())
                             )?
@@ -921,7 +1506,7 @@ impl VM {
             },
 
             OpCode::MakeEnum => {
-                let count = self.read_byte() as usize; // Nombre total d'éléments sur la pile (clés + valeurs)
+                let count = self.read_short() as usize; // Nombre total d'éléments sur la pile (clés + valeurs)
                 let num_pairs = count / 2;
                 let mut map = HashMap::new();
 
@@ -929,8 +1514,7 @@ impl VM {
                 for _ in 0..num_pairs {
                     let val = self.pop();
                     let key_val = self.pop();
-                    let key = key_val.as_str().unwrap_or("?".to_string());
-                    map.insert(key, val);
+                    map.insert(key_val.to_string(), val);
                 }
 
                 // On crée un Value::Enum SANS RefCell
@@ -940,45 +1524,56 @@ impl VM {
             OpCode::MakeClosure => {
                 let function_val = self.pop();
                 
-                if let Value::Function(rc_fn) = function_val {
+                if let Value::Function(ref rc_fn) = function_val {
                     let env_rc = Environment::new_global();
+                    let free_vars = &rc_fn.chunk.free_vars;
                     
                     // 1. Extraction (Attention : il faut accéder aux champs du Rc)
-                    let (parent_params, parent_locals_map, slot_offset) = {
-                        let frame = self.current_frame();
+                    let (parent_params, parent_env, parent_locals_map, slot_offset) = {
+                        let frame = self.frames.last().expect("No code to execute");
                         
-                        let pp = if let Value::Function(parent_rc) = &frame.closure {
-                            Some(parent_rc.params.clone()) // On clone le Vec<Params>
+                        let (pp, pe) = if let Value::Function(parent_rc) = &frame.closure {
+                            (Some(parent_rc.params.clone()), parent_rc.env.clone()) // On clone le Vec<Params>
                         } else {
-                            None
+                            (None, None)
                         };
                         
-                        let locals = frame.chunk().locals_map.clone();
-                        (pp, locals, frame.slot_offset)
+                        // Locales visibles à cet endroit si le compilateur les a notées (lambdas, defer),
+                        // sinon toutes les locales connues de la fonction
+                        let chunk = frame.chunk();
+                        let locals: Vec<(u16, String)> = match chunk.captures.get(&(self.ip - 1)) {
+                            Some(visible) => visible.clone(),
+                            None => chunk.locals_map.iter().map(|(idx, name)| (*idx, name.clone())).collect(),
+                        };
+                        (pp, pe, locals, frame.slot_offset)
                     };
 
                     // 2. Population Phase (Fill the environment)
-                    // SCOPE START: We create a block to contain the mutable borrow
+                    // Chaque variable capturée est une case partagée (Value::Cell) : la locale est
+                    // remplacée par la case sur la pile, et la closure garde la même. Les deux
+                    // côtés voient alors les affectations de l'autre.
                     {
                         let mut env_inner = env_rc.borrow_mut();
 
-                        // A. Capture Arguments
-                        if let Some(parent_params) = parent_params {
-                            for (i, (name, _)) in parent_params.iter().enumerate() {
-                                if slot_offset + i < self.stack.len() {
-                                    let val = self.stack[slot_offset + i].clone();
-                                    env_inner.variables.insert(name.clone(), val);
+                        // A. Variables déjà capturées par la fonction courante (closures imbriquées)
+                        if let Some(parent_env) = parent_env {
+                            let parent_env = parent_env.borrow();
+                            for name in free_vars {
+                                if let Some(val) = parent_env.variables.get(name) {
+                                    env_inner.variables.insert(name.clone(), val.clone());
                                 }
                             }
                         }
 
-                        // B. Capture Locals (The fix for your "line" variable)
-                        for (idx, name) in parent_locals_map {
-                            let abs_index = slot_offset + (idx as usize);
-                            if abs_index < self.stack.len() {
-                                let val = self.stack[abs_index].clone();
-                                // We insert into the closure environment
-                                env_inner.variables.insert(name, val);
+                        // B. Arguments puis locales : elles masquent les précédentes
+                        let params = parent_params.iter().flatten().enumerate()
+                            .map(|(i, (name, _))| (i, name.clone()));
+                        let locals = parent_locals_map.into_iter().map(|(idx, name)| (idx as usize, name));
+                        for (idx, name) in params.chain(locals) {
+                            let abs_index = slot_offset + idx;
+                            if abs_index < self.stack.len() && free_vars.contains(&name) {
+                                let cell = self.capture_slot(abs_index);
+                                env_inner.variables.insert(name, cell);
                             }
                         }
                     } 
@@ -993,10 +1588,14 @@ impl VM {
                         params: rc_fn.params.clone(),
                         ret_type: rc_fn.ret_type.clone(),
                         chunk: rc_fn.chunk.clone(), // On clone le chunk (lourd, mais nécessaire pour l'instant)
-                        env: Some(env_rc)
+                        env: Some(env_rc),
+                        doc: rc_fn.doc.clone(),
+                        variadic: rc_fn.variadic,
+                        entry_points: rc_fn.entry_points.clone(),
                     };
 
                     let closure = Value::Function(Rc::new(new_data));
+                    gc::track(&closure);
                     self.push(closure);
                 } else {
                     panic!("MakeClosure on non-function value");
@@ -1004,7 +1603,7 @@ impl VM {
             },
 
             OpCode::GetFreeVar => {
-                let name_idx = self.read_byte();
+                let name_idx = self.read_short();
                 // Récupération du nom
                 let name = {
                     let frame = self.current_frame();
@@ -1023,9 +1622,10 @@ impl VM {
                     // On match le Rc
                     if let Value::Function(rc_fn) = &frame.closure {
                         if let Some(env) = &rc_fn.env { // on accède au champ .env du struct
-                            if let Some(val) = env.borrow().variables.get(&name) {
-                                val_to_push = Some(val.clone());
-                            }
+                            val_to_push = match env.borrow().variables.get(&name) {
+                                Some(Value::Cell(cell)) => Some(cell.borrow().clone()),
+                                val => val.cloned(),
+                            };
                         }
                     }
                 }
@@ -1051,7 +1651,27 @@ impl VM {
                 if let Some(val) = val_to_push {
                     self.push(val);
                 } else {
-                    return Err(format!("Variable introuvable (ni locale, ni globale) : '{}'", name));
+                    return Err(format!("Variable introuvable (ni locale, ni globale) : '{}'", name).into());
+                }
+            },
+
+            OpCode::SetFreeVar => {
+                let name_idx = self.read_short();
+                let name = self.function.chunk.constants[name_idx as usize].to_string();
+                let val = self.pop();
+                let env = match &self.current_frame().closure {
+                    Value::Function(rc_fn) => rc_fn.env.clone(),
+                    _ => None,
+                };
+                let Some(env) = env else {
+                    return Err(format!("Variable introuvable (ni locale, ni globale) : '{}'", name).into());
+                };
+                let mut env = env.borrow_mut();
+                match env.variables.get_mut(&name) {
+                    Some(Value::Cell(cell)) => { cell.replace(val); },
+                    // Copie reçue d'un autre thread (worker) : elle n'est plus partagée
+                    Some(slot) => *slot = val,
+                    None => return Err(format!("Variable introuvable (ni locale, ni globale) : '{}'", name).into()),
                 }
             },
 
@@ -1060,43 +1680,160 @@ impl VM {
                 let val = self.stack.last().expect("Stack underflow in DUP").clone();
                 self.push(val);
             },
+            OpCode::BindSelf => {
+                let name_idx = self.read_short();
+                let name = self.function.chunk.constants[name_idx as usize].to_string();
+                // Cycle closure -> environnement -> closure : le ramasse-miettes le libère
+                if let Some(Value::Function(function)) = self.stack.last()
+                    && let Some(env) = &function.env {
+                    env.borrow_mut().variables.insert(name, Value::Function(function.clone()));
+                }
+            },
+            OpCode::Concat => {
+                let b = self.pop();
+                let mut a = self.pop();
+                let mut text = match &mut a {
+                    Value::String(s) => self.scratch.take_string(s, 16),
+                    other => other.to_string(),
+                };
+                match &b {
+                    Value::String(s) => text.push_str(s),
+                    other => { let _ = write!(text, "{}", other); },
+                }
+                self.push(Value::String(text.into()));
+                self.scratch.recycle(b);
+            },
+            OpCode::Dup2 => {
+                let len = self.stack.len();
+                if len < 2 { return Err("Stack underflow in DUP2".into()); }
+                let (a, b) = (self.stack[len - 2].clone(), self.stack[len - 1].clone());
+                self.push(a);
+                self.push(b);
+            },
 
-            OpCode::SetupExcept => {
-                let offset = self.read_short();
+            OpCode::SetupExcept | OpCode::SetupExceptLong => {
+                let offset = if matches!(op, OpCode::SetupExceptLong) { self.read_long() } else { self.read_short() as u32 };
                 let handler = ExceptionHandler {
                     frame_index: self.frames.len() - 1,
-                    catch_ip: self.current_frame().ip + (offset as usize),
+                    catch_ip: self.ip + (offset as usize),
                     stack_height: self.stack.len(),
+                    resources_len: self.current_frame().resources.len(),
                 };
                 self.handlers.push(handler);
             },
             OpCode::PopExcept => {
                 self.handlers.pop();
             },
+            OpCode::Defer => {
+                let deferred = self.pop();
+                self.current_frame().defers.push(deferred);
+            },
+            OpCode::WithEnter => {
+                // Vérifié à l'entrée : une valeur sans close()/__exit__() est refusée avant le bloc
+                let resource = self.stack.last().cloned().unwrap_or(Value::Null);
+                if Self::exit_method(&resource).is_none() {
+                    return Err(format!("'with' expects a value with a close() or __exit__() method, got {}", resource.type_name()).into());
+                }
+                self.current_frame().resources.push(resource);
+            },
+            OpCode::WithExit => {
+                let resource = self.current_frame().resources.pop().expect("WithExit without open resource");
+                self.exit_resource(resource)?;
+            },
             OpCode::Throw => {
-                let msg = self.pop();
-                return Err(format!("{}", msg)); // On utilise le mécanisme standard d'erreur Rust
+                let value = self.pop();
+                return Err(self.raise(value)); // On utilise le mécanisme standard d'erreur Rust
+            },
+            OpCode::CatchType => {
+                let type_idx = self.read_short();
+                let expected_type = self.function.chunk.constants[type_idx as usize].to_string();
+                let ty = crate::ast::TypeExpr::parse(&expected_type)?;
+                let error = self.stack.last().cloned().expect("Stack underflow in CatchType");
+
+                let mut matched = ty.names().iter().any(|name| self.value_matches_type(&error, name));
+                // Une erreur qui n'est pas un objet (message de la VM, `throw "..."`) est vue
+                // comme une RuntimeError par les clauses typées
+                if !matched && !matches!(error, Value::Instance(_)) {
+                    let (message, data) = match &error {
+                        Value::String(s) => (s.to_string(), Value::Null),
+                        other => (other.to_string(), other.clone()),
+                    };
+                    let wrapped = crate::native::errors::new_error("RuntimeError", &message, data, vec![]);
+                    if ty.names().iter().any(|name| self.value_matches_type(&wrapped, name)) {
+                        if let Value::Instance(instance) = &wrapped {
+                            let stack = self.format_trace(&self.caught_trace);
+                            instance.borrow_mut().set_field("stack".to_string(), stack);
+                        }
+                        *self.stack.last_mut().unwrap() = wrapped;
+                        matched = true;
+                    }
+                }
+                self.push(Value::Boolean(matched));
+            },
+            OpCode::GetIndex => {
+                let index = self.pop();
+                let obj = self.pop();
+                self.push(get_index(&obj, &index)?);
+                self.scratch.recycle(index);
+            },
+            OpCode::SetIndex => {
+                let value = self.pop();
+                let index = self.pop();
+                let obj = self.pop();
+                set_index(&obj, &index, value.clone())?;
+                self.push(value);
             },
 
             OpCode::Import => {
-                let path_idx = self.read_byte();
-                let path = self.current_frame().chunk().constants[path_idx as usize].to_string();
+                let path_idx = self.read_short();
+                let isolated = self.read_byte() == 1;
+                let written = self.function.chunk.constants[path_idx as usize].to_string();
+                // `import "utils"` : utils, utils.aeg puis utils/index.aeg (ou `[imports] resolve`
+                // de aegis.toml). Le cache est indexé par le fichier trouvé.
+                let candidates = crate::project::import_candidates(&written)?;
+                let path = candidates.iter()
+                    .find(|candidate| self.module_exists(candidate))
+                    .cloned()
+                    .ok_or_else(|| format!("Failed to import '{}': no such file (tried {})", written, candidates.join(", ")))?;
 
                 // 1. CACHE CHECK
                 // If module is already loaded, we don't re-execute it (prevents side-effect duplication)
-                if self.modules.contains_key(&path) {
-                    self.push(Value::Null); // Import returns Null
+                if let Some(module) = self.modules.get(&path).cloned() {
+                    // Chargé isolé puis importé simplement : ses noms publics deviennent nos globales
+                    if !isolated {
+                        for (name, key) in &module.renamed_exports {
+                            let value = self.get_global_by_name(key).unwrap_or(Value::Null);
+                            self.set_global(name, value);
+                        }
+                    }
+                    self.push(module.value); // Module déjà chargé : on renvoie le même objet
+                } else if crate::native::is_data_file(&path) {
+                    // import "config.json" as cfg : les données du fichier, lues une seule fois
+                    let text = self.module_source(&path)?;
+                    let data = crate::native::parse_data_file(&path, &text)
+                        .map_err(|e| format!("Failed to import '{}': {}", path, e))?;
+                    self.modules.insert(path.clone(), LoadedModule { value: data.clone(), renamed_exports: Vec::new(), doc: None });
+                    self.push(data);
                 } else {
                     // 2. LOAD FILE
                     // Reads relative to CWD. You might want to handle absolute paths or include paths later.
-                    let source = std::fs::read_to_string(&path)
-                        .map_err(|e| format!("Failed to import '{}': {}", path, e))?;
+                    let source = self.module_source(&path)?;
 
                     // 3. FRONTEND (Source -> AST)
                     // We reuse the v1 compiler pipeline to get instructions
                     let json_ast = crate::compiler::compile(&source)?;
                     let statements = crate::loader::parse_block(&json_ast)?;
-                    let instructions: Vec<crate::ast::Instruction> = statements.into_iter().map(|s| s.kind).collect();
+                    let doc = crate::ast::nodes::docstring(&statements).map(str::to_string);
+                    let exported = module_exports(&statements);
+                    // Noms renommés pour ce module. Isolé (`as`, `from`) : tous ses noms de premier
+                    // niveau ; sinon seulement ceux qu'il n'exporte pas.
+                    let hidden: Vec<String> = if isolated {
+                        module_bindings(&statements)
+                    } else if statements.iter().any(|stmt| matches!(stmt.kind, crate::ast::Instruction::Export(_))) {
+                        module_bindings(&statements).into_iter().filter(|name| !exported.contains(name)).collect()
+                    } else {
+                        module_private_names(&statements)
+                    };
 
                     // 4. BACKEND (AST -> Bytecode)
                     // CRITICAL: We create a compiler that SHARES the global_names with the main VM.
@@ -1106,69 +1843,114 @@ impl VM {
                     
                     // CRITICAL: We force GLOBAL scope (0) so 'var' and 'func' become SET_GLOBAL
                     module_compiler.scope_depth = 0; 
+                    module_compiler.lang_version = self.lang_version;
+                    module_compiler.strict = self.strict;
+                    module_compiler.opt_level = self.opt_level;
+                    module_compiler.debug_info = self.debug_info;
+                    module_compiler.chunk.name = format!("<module {}>", path);
+                    if self.debug_info {
+                        module_compiler.file = Some(Rc::from(path.as_str()));
+                    }
+                    let module_warnings = module_compiler.warnings.clone();
 
-                    for instr in instructions {
-                        module_compiler.compile_instruction(instr);
+                    // Les noms cachés (_helper, private func, tout le module s'il est isolé) sont
+                    // renommés pour ce module : ils n'écrasent pas les globales de l'importeur.
+                    for name in &hidden {
+                        module_compiler.private_globals.borrow_mut().insert(name.clone(), format!("{}@{}", name, path));
+                    }
+
+                    let (module_chunk, _) = module_compiler.compile(statements)
+                        .map_err(|e| format!("In module '{}': {}", path, e))?;
+                    for warning in module_warnings.borrow().iter() {
+                        eprintln!("{}:{}: warning: {}", path, warning.line, warning.message);
                     }
                     
                     // 5. EXECUTION
-                    let module_chunk = module_compiler.chunk;
                     
                     // Wrap module code in a function to execute it
                     let module_func = Value::Function(Rc::new(FunctionData {
                         params: vec![],
                         ret_type: None,
                         chunk: module_chunk,
-                        env: None
+                        env: None,
+                        doc: None,
+                        variadic: false,
+                        entry_points: Vec::new(),
                     }));
                     
                     // Run the module synchronously.
                     // Its instructions (SET_GLOBAL) will write directly to 'self.globals'.
                     let module_result = self.run_callable_sync(module_func, vec![], None)?;
 
-                    // 6. UPDATE CACHE
-                    self.modules.insert(path.clone(), Value::Boolean(true));
+                    // 6. MODULE OBJECT : la valeur retournée par le module (ex: `return Math`),
+                    // sinon un dict des noms définis à son premier niveau
+                    let renamed_exports: Vec<(String, String)> = exported.into_iter()
+                        .map(|name| {
+                            let key = if hidden.contains(&name) { format!("{}@{}", name, path) } else { name.clone() };
+                            (name, key)
+                        })
+                        .collect();
+                    let module = if module_result != Value::Null {
+                        module_result
+                    } else {
+                        let mut members = DictMap::new();
+                        for (name, key) in &renamed_exports {
+                            let id = self.global_names.borrow().get(key).cloned();
+                            if let Some(id) = id {
+                                members.insert(name.as_str().into(), self.globals.get(id as usize).cloned().unwrap_or(Value::Null));
+                            }
+                        }
+                        gc::dict(members)
+                    };
+
+                    // 7. UPDATE CACHE
+                    let renamed_exports = renamed_exports.into_iter().filter(|(name, key)| name != key).collect();
+                    self.modules.insert(path.clone(), LoadedModule { value: module.clone(), renamed_exports, doc });
                     
-                    // 7. RETURN
-                    self.push(module_result);
+                    // 8. RETURN
+                    self.push(module);
+                }
+            },
+            OpCode::ImportName => {
+                let name_idx = self.read_short();
+                let name = self.function.chunk.constants[name_idx as usize].to_string();
+                let module = self.stack.last().expect("Stack underflow in ImportName").clone();
+
+                // Module en dict : ses noms exportés, ou le namespace qu'il retourne (`return Math`)
+                let member = match &module {
+                    Value::Dict(members) => members.borrow().get(&name).cloned(),
+                    _ => None,
+                };
+                match member {
+                    Some(value) => self.push(value),
+                    None => return Err(format!("Cannot import '{}': the module does not export it", name).into()),
                 }
             },
             OpCode::CheckType => {
-                let type_name_idx = self.read_byte();
-                let expected_type = self.current_frame().chunk().constants[type_name_idx as usize].to_string();
+                let type_name_idx = self.read_short();
+                let expected_type = self.function.chunk.constants[type_name_idx as usize].to_string();
                 
                 // On regarde la valeur sur le sommet de la pile (sans la pop)
                 let val = self.stack.last().expect("Stack underflow in CheckType");
                 
-                // Vérification
-                let is_valid = match (val, expected_type.as_str()) {
-                    (Value::Integer(_), "int") => true,
-                    (Value::Float(_), "float") => true,
-                    (Value::String(_), "string") => true,
-                    (Value::Boolean(_), "bool") => true,
-                    (Value::List(_), "list") => true,
-                    (Value::Dict(_), "dict") => true,
-                    (Value::Function(_), "func") => true, // Ou "function"
-                    (Value::Bytes(_), "bytes") => true,
-                    (Value::Null, _) => false, // Null n'est généralement pas le type attendu (sauf "any" ?)
-                    (_, "any") => true,
-                    _ => false,
-                };
+                // Vérification (une union est valide si l'un de ses membres l'est)
+                let ty = crate::ast::TypeExpr::parse(&expected_type)?;
+                let is_valid = ty.names().iter().any(|name| self.value_matches_type(val, name));
 
                 if !is_valid {
                     return Err(format!(
                         "Erreur de Type: Attendu '{}', recu '{}'", 
                         expected_type, val
-                    ));
+                    ).into());
                 }
             },
 
             OpCode::Super => {
-                let method_idx = self.read_byte();
-                let arg_count = self.read_byte() as usize;
-                let parent_idx = self.read_byte(); // Le 3ème argument
+                let method_idx = self.read_short();
+                let arg_count = self.read_short() as usize;
+                let parent_idx = self.read_short(); // Le 3ème argument
 
-                let chunk = self.current_frame().chunk();
+                let chunk = &self.function.chunk;
                 let method_name = chunk.constants[method_idx as usize].to_string();
                 let parent_name = chunk.constants[parent_idx as usize].to_string();
 
@@ -1185,32 +1967,29 @@ impl VM {
                     
                     // 1. DÉBALLAGE IMMÉDIAT
                     // On convertit Value::Class -> Rc<ClassData> tout de suite
-                    let mut current_class_rc = match parent_class_val {
-                        Value::Class(c) => c,
-                        _ => return Err(format!("'{}' n'est pas une classe", parent_name)),
+                    let parent_class_rc = match &parent_class_val {
+                        Value::Class(c) => c.clone(),
+                        _ => return Err(format!("'{}' n'est pas une classe", parent_name).into()),
                     };
 
-                    loop {
-                        // current_class_rc est maintenant bien un Rc<ClassData>
-                        // On a donc accès à .methods et .parent_ref
-                        if let Some(method_val) = current_class_rc.methods.get(&method_name) {
-                            self.check_access(&current_class_rc, &method_name)?;
-                            self.stack[obj_idx] = method_val.clone();
-                            self.stack.insert(obj_idx + 1, obj.clone());
-                            self.call_value(method_val.clone(), arg_count + 1, Some(current_class_rc.clone()))?;
-                            return Ok(true);
-                        }
-
-                        // Remontée via référence forte (Type correct !)
-                        if let Some(p) = &current_class_rc.parent_ref {
-                            current_class_rc = p.clone(); // Rc<ClassData> -> Rc<ClassData>
-                            continue;
-                        }
+                    // Table aplatie : méthode du parent ou d'un de ses ancêtres
+                    // (super.init(...) choisit la surcharge du constructeur d'après l'arité)
+                    let found = if method_name == CONSTRUCTOR {
+                        parent_class_rc.lookup_constructor(arg_count)?
+                    } else {
+                        parent_class_rc.lookup_method(&method_name)
+                    };
+                    let Some((method_val, owner)) = found else {
+                        return Err(format!("Méthode '{}' introuvable dans super", method_name).into());
+                    };
 
-                        return Err(format!("Méthode '{}' introuvable dans super", method_name));
-                    }
+                    self.check_access(&owner, &method_name)?;
+                    self.stack[obj_idx] = method_val.clone();
+                    self.stack.insert(obj_idx + 1, obj.clone());
+                    self.call_value(method_val, arg_count + 1, Some(owner))?;
+                    return Ok(true);
                 } else {
-                    return Err(format!("Classe parente '{}' introuvable", parent_name));
+                    return Err(format!("Classe parente '{}' introuvable", parent_name).into());
                 }
             },
             OpCode::MakeRange => {
@@ -1228,15 +2007,19 @@ impl VM {
         Ok(true)
     }
 
-    fn op_method(&mut self) -> Result<(), String> {
-        let name_idx = self.read_byte();
-        let arg_count = self.read_byte() as usize;
-
-        // Name resolution
-        let method_name_val = &self.current_frame().chunk().constants[name_idx as usize];
-        let method_name = match method_name_val {
-            Value::String(s) => s.clone(),
-            _ => method_name_val.to_string(),
+    fn op_method(&mut self) -> Result<(), NativeError> {
+        let name_idx = self.read_short();
+        let arg_count = self.read_short() as usize;
+
+        // Name resolution : le nom est emprunté à la table de constantes de la fonction en cours
+        // (les noms y sont déjà uniques), sans copie de chaîne à chaque appel de méthode
+        let closure = self.current_frame().closure.clone();
+        let method_name: &str = match &closure {
+            Value::Function(f) => match &f.chunk.constants[name_idx as usize] {
+                Value::String(s) => s,
+                other => return Err(format!("Invalid method name constant: {}", other).into()),
+            },
+            _ => return Err("CallFrame closure is not a function".into()),
         };
 
         let obj_idx = self.stack.len() - 1 - arg_count;
@@ -1246,17 +2029,16 @@ impl VM {
         if let Value::Instance(inst) = &obj {
             // --- 1. REFLECTION (MÉTHODES NATIVES) ---
             // On vérifie si c'est une méthode d'introspection avant de chercher dans les classes
-            let handled = match method_name.as_str() {
+            let handled = match method_name {
                 
                 "get_properties" => {
                     // Retourne la liste des clés du dictionnaire interne 'fields'
-                    let fields = &inst.borrow().fields;
-                    let keys: Vec<Value> = fields.keys()
-                        .map(|k| Value::String(k.clone()))
+                    let keys: Vec<Value> = inst.borrow().field_names().into_iter()
+                        .map(|s| Value::String(s.into()))
                         .collect();
                     
                     // Résultat sur la stack à la place de l'objet
-                    self.stack[obj_idx] = Value::List(Rc::new(RefCell::new(keys)));
+                    self.stack[obj_idx] = gc::list(keys);
                     // Nettoyage des arguments (bien qu'il n'y en ait pas ici)
                     self.stack.truncate(obj_idx + 1);
                     true
@@ -1268,11 +2050,7 @@ impl VM {
                     // L'argument 'name' est juste après l'objet sur la stack
                     let prop_name = self.stack[obj_idx + 1].as_str().map_err(|_| "Property name must be a string")?;
                     
-                    let val = if let Some(v) = inst.borrow().fields.get(&prop_name) {
-                        v.clone()
-                    } else {
-                        Value::Null
-                    };
+                    let val = inst.borrow().get_field(&prop_name).unwrap_or(Value::Null);
 
                     self.stack[obj_idx] = val;
                     self.stack.truncate(obj_idx + 1); // On retire l'argument
@@ -1286,7 +2064,7 @@ impl VM {
                     let val = self.stack[obj_idx + 2].clone();
 
                     // On insère directement dans le stockage interne
-                    inst.borrow_mut().fields.insert(prop_name, val);
+                    inst.borrow_mut().set_field(prop_name, val);
 
                     self.stack[obj_idx] = Value::Null;
                     self.stack.truncate(obj_idx + 1); // On retire les 2 arguments
@@ -1301,83 +2079,51 @@ impl VM {
             }
 
             // --- 2. RÉSOLUTION CLASSIQUE (HÉRITAGE) ---
-            // inst.borrow().class est maintenant directement Rc<ClassData>
-            let mut current_class_rc = inst.borrow().class.clone();
-            
-            loop {
-                // A. Méthode présente ?
-                if let Some(method_val) = current_class_rc.methods.get(&method_name) {
-                    self.check_access(&current_class_rc, &method_name)?;
-                    self.stack[obj_idx] = method_val.clone();
-                    self.stack.insert(obj_idx + 1, obj.clone()); 
-                    self.call_value(
-                        method_val.clone(), 
-                        arg_count + 1, 
-                        Some(current_class_rc.clone())
-                    )?; 
-                    return Ok(()); 
-                }
-                
-                // B. Parent ? (Via référence forte)
-                if let Some(parent_rc) = &current_class_rc.parent_ref {
-                    current_class_rc = parent_rc.clone();
-                    continue;
-                }
-                
-                break; // Non trouvé
+            // Table de méthodes aplatie à la création de la classe : une seule recherche
+            let class_rc = inst.borrow().class.clone();
+
+            if let Some((method_val, owner)) = class_rc.lookup_method(method_name) {
+                self.check_access(&owner, method_name)?;
+                self.stack[obj_idx] = method_val.clone();
+                self.stack.insert(obj_idx + 1, obj.clone()); 
+                self.call_value(method_val, arg_count + 1, Some(owner))?; 
+                return Ok(()); 
             }
 
             // --- 3. FALLBACK STATIQUE ---
             // Si on arrive ici, c'est que l'instance n'a pas la méthode.
             // On regarde si la CLASSE a une méthode statique de ce nom.
 
-            let mut current_static_lookup = inst.borrow().class.clone();
-    
-            loop {
-                if let Some(method_val) = current_static_lookup.static_methods.get(&method_name) {
-                    // A. Vérification de sécurité
-                    self.check_access(&current_static_lookup, &method_name)?;
-
-                    // B. Préparation de la Stack
-                    self.stack[obj_idx] = method_val.clone(); // On remplace l'objet par la fonction
-
-                    // C. LE TRUC MAGIQUE : SWAP DE CONTEXTE
-                    // On injecte la CLASSE à la place de l'INSTANCE comme argument 0 ('this')
-                    // Cela permet à la méthode statique de fonctionner normalement
-                    self.stack.insert(obj_idx + 1, Value::Class(current_static_lookup.clone()));
-
-                    // D. Appel
-                    self.call_value(
-                        method_val.clone(),
-                        arg_count + 1,
-                        Some(current_static_lookup.clone())
-                    )?;
-                    return Ok(());
-                }
+            // Héritage des méthodes statiques inclus (si Model a une méthode statique helper par exemple)
+            if let Some((method_val, owner)) = class_rc.lookup_static_method(method_name) {
+                // A. Vérification de sécurité
+                self.check_access(&owner, method_name)?;
 
-                // Héritage des méthodes statiques (si Model a une méthode statique helper par exemple)
-                if let Some(parent) = &current_static_lookup.parent_ref {
-                    current_static_lookup = parent.clone();
-                    continue;
-                }
-                break;
+                // B. Préparation de la Stack
+                self.stack[obj_idx] = method_val.clone(); // On remplace l'objet par la fonction
+
+                // C. LE TRUC MAGIQUE : SWAP DE CONTEXTE
+                // On injecte la CLASSE à la place de l'INSTANCE comme argument 0 ('this')
+                // Cela permet à la méthode statique de fonctionner normalement
+                self.stack.insert(obj_idx + 1, Value::Class(owner.clone()));
+
+                // D. Appel
+                self.call_value(method_val, arg_count + 1, Some(owner))?;
+                return Ok(());
             }
         }
 
         if let Value::Class(class_rc) = &obj {
-            // Search in static methods of the class itself
-            // Note: Inheritance of static methods is possible in some languages.
-            // For now, let's look in the class itself (simplification).
-            // To support static inheritance: Loop on parent_ref like in Instance.
+            // Search in static methods of the class and of its ancestors
 
             // --- REFLECTION STATIQUE ---
-            let handled = match method_name.as_str() {
+            let handled = match method_name {
                 "get_static_properties" => {
                     // Retourne les propriétés statiques
                     let keys: Vec<Value> = class_rc.static_properties.keys()
-                        .map(|k| Value::String(k.clone()))
+                        .map(|k| Value::String(k.clone().into()))
                         .collect();
-                    self.stack[obj_idx] = Value::List(Rc::new(RefCell::new(keys)));
+                    self.stack[obj_idx] = gc::list(keys);
                     self.stack.truncate(obj_idx + 1);
                     true
                 },
@@ -1389,55 +2135,60 @@ impl VM {
                 return Ok(());
             }
             
-            let mut current_lookup = class_rc.clone();
-            loop {
-                if let Some(method_val) = current_lookup.static_methods.get(&method_name) {
-                    // A. Security Check
-                    self.check_access(&current_lookup, &method_name)?;
-
-                    // B. Setup Stack
-                    self.stack[obj_idx] = method_val.clone();
-                    // We reinject the Class Object as 'this' (argument 0)
-                    self.stack.insert(obj_idx + 1, obj.clone()); 
-
-                    // C. Call with Context
-                    self.call_value(
-                        method_val.clone(), 
-                        arg_count + 1, 
-                        Some(current_lookup.clone())
-                    )?;
-                    return Ok(());
-                }
-                
-                // Static Inheritance (Optional but nice)
-                if let Some(parent) = &current_lookup.parent_ref {
-                    current_lookup = parent.clone();
-                    continue;
-                }
-                break;
+            // Static Inheritance: flattened table built by ClassData::link
+            if let Some((method_val, owner)) = class_rc.lookup_static_method(method_name) {
+                // A. Security Check
+                self.check_access(&owner, method_name)?;
+
+                // B. Setup Stack
+                self.stack[obj_idx] = method_val.clone();
+                // We reinject the Class Object as 'this' (argument 0)
+                self.stack.insert(obj_idx + 1, obj.clone()); 
+
+                // C. Call with Context
+                self.call_value(method_val, arg_count + 1, Some(owner))?;
+                return Ok(());
             }
             return Err(format!("Static method '{}' not found on class '{}'", method_name, class_rc.name).into());
         }
 
         if let Value::Dict(d) = &obj {
             // On regarde si la clé existe dans le dictionnaire
-            let field_val = d.borrow().get(&method_name).cloned();
+            let field_val = d.borrow().get(method_name).cloned();
+
+            match field_val {
+                // Opt-in : une fonction dont le premier paramètre s'appelle `this` reçoit le dict
+                // lui-même (objet-dict), sauf si l'appelant le passe déjà explicitement
+                Some(Value::Function(ref f)) if f.params.first().is_some_and(|(p, _)| p == "this")
+                    && f.accepts(arg_count + 1)
+                    && !(arg_count > 0 && matches!(&self.stack[obj_idx + 1], Value::Dict(first) if Rc::ptr_eq(first, d))) => {
+                    // Stack avant : [Dict, Arg1...] / après : [Func, Dict, Arg1...]
+                    let func = Value::Function(f.clone());
+                    self.stack.insert(obj_idx, func.clone());
+                    self.call_value(func, arg_count + 1, None)?;
+                    return Ok(());
+                }
 
-            if let Some(val) = field_val {
                 // Si la valeur trouvée est une fonction (ou native), on l'exécute
-                if matches!(val, Value::Function(..) | Value::Native(..)) {
-                    
+                Some(val @ (Value::Function(..) | Value::Native(..))) => {
                     // On remplace le Dictionnaire sur la pile par la Fonction trouvée
                     // Stack avant : [Dict, Arg1, Arg2...]
                     // Stack après : [Func, Arg1, Arg2...]
                     self.stack[obj_idx] = val.clone();
-                    
-                    // Note : Contrairement aux Instances, on n'injecte PAS 'this'.
+
+                    // Note : Contrairement aux Instances, on n'injecte PAS 'this' (sauf opt-in ci-dessus).
                     // Les fonctions de namespace sont considérées comme statiques.
-                    
+
                     self.call_value(val, arg_count, None)?;
                     return Ok(()); // L'appel est géré, on rend la main à la boucle principale
                 }
+
+                // La clé masque une méthode native du dict : on l'ignore pour l'appel
+                Some(val) if !DICT_METHODS.contains(&method_name) => {
+                    return Err(format!("Dict key '{}' holds a value of type {}, not a function", method_name, val.type_name()).into());
+                }
+
+                _ => {}
             }
         }
 
@@ -1445,13 +2196,17 @@ impl VM {
         let args: Vec<Value> = self.stack.drain((obj_idx + 1)..).collect();
         let _obj_popped = self.pop(); // Pop object
 
-        let result = match obj {
-            Value::List(l) => match method_name.as_str() {
-                "push" => { l.borrow_mut().push(args[0].clone()); Value::Null },
+        let result = match &obj {
+            Value::List(l) => match method_name {
+                "push" => {
+                    let value = Args::new("list.push(value)", &args).want(0)?.clone();
+                    l.borrow_mut().push(value);
+                    Value::Null
+                },
                 "pop" => l.borrow_mut().pop().unwrap_or(Value::Null),
                 "at" => { 
-                    let idx = args[0].as_int().unwrap_or(0) as usize;
-                    l.borrow().get(idx).cloned().unwrap_or(Value::Null) 
+                    let idx = Args::new("list.at(index)", &args).want_int(0)?;
+                    usize::try_from(idx).ok().and_then(|i| l.borrow().get(i).cloned()).unwrap_or(Value::Null)
                 },
                 "len" => Value::Integer(l.borrow().len() as i64),
 
@@ -1461,19 +2216,19 @@ impl VM {
                 },
 
                 "contains" => {
-                    let target = &args[0];
+                    let target = Args::new("list.contains(value)", &args).want(0)?;
                     let exists = l.borrow().contains(target); // Nécessite que Value implémente PartialEq (c'est le cas)
                     Value::Boolean(exists)
                 },
 
                 "join" => {
-                    let sep = if args.is_empty() { "".to_string() } else { args[0].as_str().unwrap_or_default() };
+                    let sep = Args::new("list.join(separator?)", &args).opt_str(0, "")?;
                     
                     let list_borrow = l.borrow();
                     // On convertit tout en string et on joint
                     let strings: Vec<String> = list_borrow.iter().map(|v| v.to_string()).collect();
                     
-                    Value::String(strings.join(&sep))
+                    Value::String(strings.join(&sep).into())
                 },
 
                 "is_empty" => Value::Boolean(l.borrow().is_empty()),
@@ -1489,10 +2244,9 @@ impl VM {
 
                 "reduce" => {
                     // Usage: list.reduce(func(acc, val), initial_value)
-                    if args.len() < 2 { return Err("reduce expects (callback, initial)".into()); }
-                    
-                    let callback = args[0].clone();
-                    let mut accumulator = args[1].clone();
+                    let spec = Args::new("list.reduce(callback, initial)", &args);
+                    let callback = spec.want(0)?.clone();
+                    let mut accumulator = spec.want(1)?.clone();
                     let list_data = l.borrow().clone();
 
                     for item in list_data {
@@ -1505,8 +2259,7 @@ impl VM {
 
                 "index_of" => {
                     // Usage: list.index_of(value) -> int (ou -1)
-                    if args.len() < 1 { return Err("index_of attend 1 argument".into()); }
-                    let target = &args[0];
+                    let target = Args::new("list.index_of(value)", &args).want(0)?;
                     
                     let list = l.borrow();
                     let index = list.iter().position(|x| x == target); // PartialEq fait le travail
@@ -1518,8 +2271,7 @@ impl VM {
                 },
 
                 "find" => {
-                    if args.len() < 1 { return Err("find attend 1 callback".into()); }
-                    let callback = args[0].clone();
+                    let callback = Args::new("list.find(callback)", &args).want(0)?.clone();
                     
                     let list_data = l.borrow().clone();
                     
@@ -1557,6 +2309,9 @@ impl VM {
                     // 3. Logique de Tri
                     if let Some(comp_fn) = comparator {
                         // --- CAS A : TRI PERSONNALISÉ ---
+                        // Le comparateur voit la liste d'origine ; s'il la modifie, ses changements
+                        // seraient écrasés par le résultat du tri : on le signale plutôt (voir plus bas)
+                        let original = data.clone();
                         // On utilise une variable pour capturer une erreur éventuelle survenue dans le callback Aegis
                         let mut sort_error = None;
                         
@@ -1586,6 +2341,9 @@ impl VM {
                         
                         // Si le tri a échoué à cause d'une erreur script, on la propage
                         if let Some(e) = sort_error { return Err(e); }
+                        if !same_items(&l.borrow(), &original) {
+                            return Err("List mutated during iteration: the list.sort() comparator must not modify the list".into());
+                        }
                         
                     } else {
                         // --- CAS B : TRI PAR DÉFAUT ---
@@ -1619,8 +2377,9 @@ impl VM {
                 "slice" => {
                     // Usage: list.slice(start, end_exclusive)
                     let len = l.borrow().len();
-                    let start = args.get(0).and_then(|v| v.as_int().ok()).unwrap_or(0) as usize;
-                    let end = args.get(1).and_then(|v| v.as_int().ok()).unwrap_or(len as i64) as usize;
+                    let spec = Args::new("list.slice(start?, end?)", &args);
+                    let start = spec.opt_int(0, 0)? as usize;
+                    let end = spec.opt_int(1, len as i64)? as usize;
 
                     // Clamping pour éviter les crashs
                     let start = start.min(len);
@@ -1630,13 +2389,13 @@ impl VM {
                     // On crée une nouvelle liste avec la tranche
                     let new_vec = list_borrow[start..end].to_vec();
                     
-                    Value::List(Rc::new(RefCell::new(new_vec)))
+                    gc::list(new_vec)
                 },
                 
                 // --- FUNCTIONAL PROGRAMMING ---
                 
                 "map" => {
-                    let callback = args[0].clone();
+                    let callback = Args::new("list.map(callback)", &args).want(0)?.clone();
                     let list_data = l.borrow().clone(); // Clone to avoid RefCell borrow conflict during callback
                     let mut new_list = Vec::new();
 
@@ -1645,11 +2404,11 @@ impl VM {
                         let res = self.run_callable_sync(callback.clone(), vec![item], None)?;
                         new_list.push(res);
                     }
-                    Value::List(Rc::new(RefCell::new(new_list)))
+                    gc::list(new_list)
                 },
 
                 "filter" => {
-                    let callback = args[0].clone();
+                    let callback = Args::new("list.filter(callback)", &args).want(0)?.clone();
                     let list_data = l.borrow().clone();
                     let mut new_list = Vec::new();
 
@@ -1660,11 +2419,11 @@ impl VM {
                             new_list.push(item);
                         }
                     }
-                    Value::List(Rc::new(RefCell::new(new_list)))
+                    gc::list(new_list)
                 },
 
                 "for_each" => {
-                    let callback = args[0].clone();
+                    let callback = Args::new("list.for_each(callback)", &args).want(0)?.clone();
                     let list_data = l.borrow().clone();
                     
                     for item in list_data {
@@ -1674,104 +2433,77 @@ impl VM {
                     Value::Null
                 },
 
+                // Flottants 32 bits little-endian, la forme attendue par les plugins graphiques
+                "to_f32_bytes" => {
+                    let list = l.borrow();
+                    let mut bytes = Vec::with_capacity(list.len() * 4);
+                    for (i, item) in list.iter().enumerate() {
+                        let float = f32_from_number(item).ok_or_else(|| {
+                            format!("list.to_f32_bytes(): element {} must be a number, got {}", i, item.type_name())
+                        })?;
+                        bytes.extend_from_slice(&float.to_le_bytes());
+                    }
+                    Value::Bytes(Rc::new(RefCell::new(bytes)))
+                },
+
                 _ => return Err(format!("Unknown list method '{}'", method_name).into())
             },
             
             // ... Dict methods (insert, keys, get...) inchangés ...
-            Value::Dict(d) => match method_name.as_str() {
+            Value::Dict(d) => match method_name {
                 "insert" => {
-                    if args.len() < 2 { return Err("insert needs 2 args".into()); }
-                    let key = args[0].as_str().unwrap_or("?".to_string());
-                    d.borrow_mut().insert(key, args[1].clone());
+                    let spec = Args::new("dict.insert(key, value)", &args);
+                    let key = spec.want_key(0)?;
+                    d.borrow_mut().insert(key, spec.want(1)?.clone());
                     Value::Null
                 },
                 "keys" => {
-                    let keys: Vec<Value> = d.borrow().keys().map(|k| Value::String(k.clone())).collect();
-                    Value::List(Rc::new(RefCell::new(keys)))
+                    let keys: Vec<Value> = d.borrow().keys().map(ValueKey::to_value).collect();
+                    gc::list(keys)
                 },
                 "get" => {
-                     let key = args[0].as_str().unwrap_or("?".to_string());
+                     let key = Args::new("dict.get(key)", &args).want_key(0)?;
                      d.borrow().get(&key).cloned().unwrap_or(Value::Null)
                 },
 
                 "is_empty" => Value::Boolean(d.borrow().is_empty()),
+                "len" => Value::Integer(d.borrow().len() as i64),
 
                 "remove" => {
-                    let key = args[0].as_str().unwrap_or_default();
-                    // Retourne la valeur supprimée ou Null
-                    d.borrow_mut().remove(&key).unwrap_or(Value::Null)
+                    let key = Args::new("dict.remove(key)", &args).want_key(0)?;
+                    // Retourne la valeur supprimée ou Null ; les clés suivantes gardent leur ordre
+                    d.borrow_mut().shift_remove(&key).unwrap_or(Value::Null)
                 },
 
                 "values" => {
                     // Retourne une liste des valeurs
                     let vals: Vec<Value> = d.borrow().values().cloned().collect();
-                    Value::List(Rc::new(RefCell::new(vals)))
+                    gc::list(vals)
                 },
 
                 "contains" => {
-                    if args.is_empty() { 
-                        return Err("Usage: dict.contains(key)".into()); 
-                    }
-                    
-                    // On s'attend à ce que la clé soit une String (car HashMap<String, Value>)
-                    let key = args[0].as_str().map_err(|_| "Dict key must be a string")?;
-                    
+                    let key = Args::new("dict.contains(key)", &args).want_key(0)?;
+
                     let exists = d.borrow().contains_key(&key);
                     Value::Boolean(exists)
                 }
 
-                _ => return Err(format!("Unknown dict method '{}'", method_name).into())
-            },
-
-            Value::Bytes(b) => match method_name.as_str() {
-                "len" => Value::Integer(b.borrow().len() as i64),
-                
-                "is_empty" => Value::Boolean(b.borrow().is_empty()),
-                
-                "at" => {
-                    // Retourne l'octet sous forme d'entier (0-255)
-                    let idx = args[0].as_int().unwrap_or(0) as usize;
-                    if let Some(byte) = b.borrow().get(idx) {
-                        Value::Integer(*byte as i64)
-                    } else {
-                        Value::Null
+                _ => {
+                    let dict = d.borrow();
+                    let callable_keys = dict.iter()
+                        .filter(|(_, v)| matches!(v, Value::Function(..) | Value::Native(..)))
+                        .filter_map(|(k, _)| k.as_str());
+                    let mut message = format!("Dict has no key or method '{}'", method_name);
+                    if let Some(close) = closest_name(method_name, DICT_METHODS.iter().copied().chain(callable_keys)) {
+                        message.push_str(&format!(" (did you mean '{}'?)", close));
                     }
-                },
-
-                "slice" => {
-                    // Extrait une sous-partie des octets
-                    let len = b.borrow().len();
-                    let start = args.get(0).and_then(|v| v.as_int().ok()).unwrap_or(0) as usize;
-                    let end = args.get(1).and_then(|v| v.as_int().ok()).unwrap_or(len as i64) as usize;
-                    
-                    let start = start.min(len);
-                    let end = end.min(len).max(start);
-                    
-                    let slice = b.borrow()[start..end].to_vec();
-                    Value::Bytes(Rc::new(RefCell::new(slice)))
-                },
-
-                "to_string" => {
-                    // Tente de convertir les octets en String UTF-8
-                    // Utile pour lire un fichier texte chargé en mode binaire
-                    let bytes = b.borrow();
-                    match String::from_utf8(bytes.clone()) {
-                        Ok(s) => Value::String(s),
-                        Err(_) => Value::Null, // Ou erreur, ou string partielle
-                    }
-                },
-                
-                "to_hex" => {
-                    // Debug : Affiche en hexadécimal "1F A2 ..."
-                    let bytes = b.borrow();
-                    let hex: String = bytes.iter().map(|b| format!("{:02X}", b)).collect();
-                    Value::String(hex)
-                },
-
-                _ => return Err(format!("Unknown bytes method '{}'", method_name).into())
+                    return Err(message.into());
+                }
             },
 
-            Value::Range(start, end, step) => match method_name.as_str() {
+            Value::Bytes(b) => crate::native::bytes::bytes_method(b, method_name, &args)?,
+
+            &Value::Range(start, end, step) => match method_name {
                 // Pour que foreach sache combien de tours faire
                 "len" => {
                     if step == 0 { return Err("Step cannot be zero".into()); }
@@ -1788,14 +2520,14 @@ impl VM {
                 
                 // Pour que foreach récupère l'élément courant
                 "at" => {
-                    let idx = args[0].as_int().unwrap_or(0);
+                    let idx = Args::new("range.at(index)", &args).want_int(0)?;
                     let val = start + (idx * step);
                     Value::Integer(val)
                 },
                 
                 // Méthode fluide pour changer le pas : (0..10).step(2)
                 "step" => {
-                    let new_step = args[0].as_int().unwrap_or(1);
+                    let new_step = Args::new("range.step(step)", &args).want_int(0)?;
                     if new_step == 0 { return Err("Step cannot be 0".into()); }
                     Value::Range(start, end, new_step)
                 },
@@ -1816,31 +2548,31 @@ impl VM {
                             current += step;
                         }
                     }
-                    Value::List(Rc::new(RefCell::new(list)))
+                    gc::list(list)
                 },
                 
                 _ => return Err(format!("Unknown range method '{}'", method_name).into())
             },
 
-            Value::String(s) => match method_name.as_str() {
+            Value::String(s) => match method_name {
                 "len" => Value::Integer(s.chars().count() as i64),
                 "at" => {
                     // Récupération de l'index
-                    let idx = args[0].as_int().unwrap_or(0);
+                    let idx = Args::new("string.at(index)", &args).want_int(0)?;
                     
                     if idx < 0 {
                         Value::Null
                     } else {
                         // On utilise chars().nth() pour gérer correctement l'UTF-8 (accents, emojis)
                         match s.chars().nth(idx as usize) {
-                            Some(c) => Value::String(c.to_string()),
+                            Some(c) => Value::String(c.to_string().into()),
                             None => Value::Null,
                         }
                     }
                 },
                 "index_of" => {
                     // Récupère la sous-chaîne à chercher
-                    let sub = args[0].as_str().unwrap_or_default();
+                    let sub = Args::new("string.index_of(sub)", &args).want_str(0)?;
                     
                     // s.find retourne un Option<usize> (l'index en octets)
                     match s.find(&sub) {
@@ -1852,9 +2584,10 @@ impl VM {
                 "slice" => {
                     // Usage: string.slice(start, end)
                     let len = s.chars().count();
-                    let start = args.get(0).and_then(|v| v.as_int().ok()).unwrap_or(0) as usize;
+                    let spec = Args::new("string.slice(start?, end?)", &args);
+                    let start = spec.opt_int(0, 0)? as usize;
                     // Par défaut jusqu'à la fin
-                    let end = args.get(1).and_then(|v| v.as_int().ok()).unwrap_or(len as i64) as usize;
+                    let end = spec.opt_int(1, len as i64)? as usize;
 
                     // Clamping
                     let start = start.min(len);
@@ -1867,7 +2600,7 @@ impl VM {
                         .take(end - start)
                         .collect();
                     
-                    Value::String(sub)
+                    Value::String(sub.into())
                 },
 
                 "to_bytes" => {
@@ -1877,86 +2610,82 @@ impl VM {
                 // --- Transformation ---
                 "trim" => {
                     // Rust fait ça très bien nativement
-                    Value::String(s.trim().to_string())
+                    Value::String(s.trim().to_string().into())
                 },
-                "upper" => Value::String(s.to_uppercase()),
-                "lower" => Value::String(s.to_lowercase()),
+                "upper" => Value::String(s.to_uppercase().into()),
+                "lower" => Value::String(s.to_lowercase().into()),
 
                 // --- Analyse ---
                 "contains" => { // NOUVEAU
-                    let sub = args[0].as_str().unwrap_or_default();
+                    let sub = Args::new("string.contains(sub)", &args).want_str(0)?;
                     Value::Boolean(s.contains(&sub))
                 },
                 "starts_with" => { // NOUVEAU
-                    let sub = args[0].as_str().unwrap_or_default();
+                    let sub = Args::new("string.starts_with(prefix)", &args).want_str(0)?;
                     Value::Boolean(s.starts_with(&sub))
                 },
                 "ends_with" => { // NOUVEAU
-                    let sub = args[0].as_str().unwrap_or_default();
+                    let sub = Args::new("string.ends_with(suffix)", &args).want_str(0)?;
                     Value::Boolean(s.ends_with(&sub))
                 },
 
                 // --- Modification ---
                 "replace" => {
-                    if args.len() < 2 { return Err("String.replace attend 2 arguments (old, new)".into()); }
-                    
-                    // On sécurise la récupération des chaînes
-                    let old_part = args[0].as_str().unwrap_or("".to_string());
-                    let new_part = args[1].as_str().unwrap_or("".to_string());
+                    let spec = Args::new("string.replace(old, new)", &args);
+                    let old_part = spec.want_str(0)?;
+                    let new_part = spec.want_str(1)?;
                     
-                    Value::String(s.replace(&old_part, &new_part))
+                    Value::String(s.replace(&old_part, &new_part).into())
                 },
 
                 "split" => {
-                    let delim = if args.is_empty() { 
-                        " ".to_string() 
-                    } else { 
-                        args[0].as_str().unwrap_or(" ".to_string()) 
-                    };
+                    let delim = Args::new("string.split(separator?)", &args).opt_str(0, " ")?;
 
                     // On découpe et on convertit chaque morceau en Value::String
                     let parts: Vec<Value> = s.split(&delim)
-                        .map(|sub| Value::String(sub.to_string()))
+                        .map(|sub| Value::String(sub.to_string().into()))
                         .collect();
                     
                     // On retourne une Value::List
-                    Value::List(Rc::new(RefCell::new(parts)))
+                    gc::list(parts)
                 },
 
                 "is_empty" => Value::Boolean(s.is_empty()),
 
                 "pad_start" => {
                     // Args: width, char (optionnel, defaut ' ')
-                    let width = args[0].as_int().unwrap_or(0) as usize;
-                    let pad_char = if args.len() > 1 { 
-                        args[1].as_str().unwrap_or(" ".to_string()).chars().next().unwrap_or(' ') 
-                    } else { ' ' };
+                    let spec = Args::new("string.pad_start(width, char?)", &args);
+                    let width = spec.want_int(0)?.max(0) as usize;
+                    let pad_char = spec.opt_str(1, " ")?.chars().next().unwrap_or(' ');
 
                     if s.len() >= width {
                         Value::String(s.clone())
                     } else {
                         let padding = pad_char.to_string().repeat(width - s.len());
-                        Value::String(format!("{}{}", padding, s))
+                        Value::String(format!("{}{}", padding, s).into())
                     }
                 },
 
                 "pad_end" => {
-                    let width = args[0].as_int().unwrap_or(0) as usize;
-                    let pad_char = if args.len() > 1 { 
-                        args[1].as_str().unwrap_or(" ".to_string()).chars().next().unwrap_or(' ') 
-                    } else { ' ' };
+                    let spec = Args::new("string.pad_end(width, char?)", &args);
+                    let width = spec.want_int(0)?.max(0) as usize;
+                    let pad_char = spec.opt_str(1, " ")?.chars().next().unwrap_or(' ');
 
                     if s.len() >= width {
                         Value::String(s.clone())
                     } else {
                         let padding = pad_char.to_string().repeat(width - s.len());
-                        Value::String(format!("{}{}", s, padding))
+                        Value::String(format!("{}{}", s, padding).into())
                     }
                 },
 
                 _ => return Err(format!("Méthode string inconnue '{}'", method_name).into())
             },
 
+            &Value::Duration(ms) => crate::native::date::duration_method(ms, method_name, &args)?,
+            &Value::DateTime(ms, zone) => crate::native::date::datetime_method(ms, zone, method_name, &args)?,
+            Value::TypedArray(array) => crate::native::arrays::array_method(array, method_name, &args)?,
+
             Value::Instance(_) => return Err(format!("Instance has no method '{}'", method_name).into()),
             _ => return Err(format!("Method '{}' not supported on {:?}", method_name, obj).into())
         };
@@ -1968,55 +2697,98 @@ impl VM {
     // Helper pour lire l'octet suivant et avancer IP
     #[inline(always)]
     fn read_byte(&mut self) -> u8 {
-        let frame = self.current_frame();
+        let code = &self.function.chunk.code;
         // VERSION SAFE : On vérifie les bornes
-        if frame.ip >= frame.chunk().code.len() {
+        if self.ip >= code.len() {
             panic!("VM Error: Instruction Pointer out of bounds!");
         }
-        let b = frame.chunk().code[frame.ip];
-        frame.ip += 1;
+        let b = code[self.ip];
+        self.ip += 1;
         b
     }
 
     fn read_short(&mut self) -> u16 {
-        let frame = self.current_frame();
-        let ip = frame.ip;
-        frame.ip += 2;
-        ((frame.chunk().code[ip] as u16) << 8) | frame.chunk().code[ip + 1] as u16
+        let code = &self.function.chunk.code;
+        let ip = self.ip;
+        self.ip += 2;
+        ((code[ip] as u16) << 8) | code[ip + 1] as u16
+    }
+
+    fn read_long(&mut self) -> u32 {
+        let code = &self.function.chunk.code;
+        let ip = self.ip;
+        self.ip += 4;
+        u32::from_be_bytes([code[ip], code[ip + 1], code[ip + 2], code[ip + 3]])
+    }
+
+    // Adapte les arguments empilés à la signature : null pour chaque paramètre optionnel manquant
+    // (le début du chunk calcule sa valeur par défaut), liste des arguments en trop pour
+    // `...rest`. Renvoie l'ip où commencer, après les valeurs par défaut des arguments passés.
+    fn bind_arguments(&mut self, function: &FunctionData, arg_count: usize) -> usize {
+        let fixed = function.params.len() - function.variadic as usize;
+        let rest = function.variadic.then(|| {
+            let extra = self.stack.split_off(self.stack.len() - arg_count.saturating_sub(fixed));
+            gc::list(extra)
+        });
+        for _ in arg_count..fixed {
+            self.push(Value::Null);
+        }
+        if let Some(rest) = rest {
+            self.push(rest);
+        }
+        let optional_passed = arg_count.min(fixed) - function.required();
+        function.entry_points.get(optional_passed).copied().unwrap_or(0)
     }
 
-    fn call_value(&mut self, target: Value, arg_count: usize, context: Option<Rc<ClassData>>) -> Result<(), String> {
+    fn call_value(&mut self, target: Value, arg_count: usize, context: Option<Rc<ClassData>>) -> Result<(), NativeError> {
         let func_idx = self.stack.len() - 1 - arg_count;
 
         match &target {
             // CAS 1 : Fonction Aegis
             Value::Function(rc_fn) => { 
                  // On accède aux champs via rc_fn
-                 if arg_count != rc_fn.params.len() { 
-                    return Err(format!("Arity mismatch: attendu {}, reçu {}", rc_fn.params.len(), arg_count)); 
+                 if !rc_fn.accepts(arg_count) {
+                    let expected = if rc_fn.variadic {
+                        format!("au moins {}", rc_fn.required())
+                    } else if rc_fn.required() < rc_fn.params.len() {
+                        format!("{} à {}", rc_fn.required(), rc_fn.params.len())
+                    } else {
+                        rc_fn.params.len().to_string()
+                    };
+                    return Err(format!("Arity mismatch in '{}': attendu {}, reçu {}", rc_fn.chunk.name, expected, arg_count).into());
+                 }
+                 let ip = self.bind_arguments(rc_fn, arg_count);
+
+                 // Fonction `async` : l'appel crée une tâche au lieu d'exécuter le corps
+                 if rc_fn.chunk.is_async {
+                     self.spawn_task(target.clone(), rc_fn.params.len(), ip, context);
+                     return Ok(());
                  }
                  
                  let frame = CallFrame {
                     closure: target.clone(), // Clone le Rc (rapide !)
-                    ip: 0,
+                    ip,
                     slot_offset: func_idx + 1,
-                    class_context: context
+                    class_context: context,
+                    defers: Vec::new(),
+                    resources: Vec::new(),
                  };
-                 
-                 self.frames.push(frame);
+
+                 if self.stats.is_some() {
+                     self.stats_call(&target);
+                 }
+                 self.push_frame(frame);
                  Ok(())
             },
 
             // CAS 2 : Classe
             Value::Class(rc_class) => {
                 // 1. Création de l'instance vide (avec le bon type Rc<ClassData>)
-                let instance_rc = Rc::new(RefCell::new(InstanceData {
-                    class: rc_class.clone(),
-                    fields: HashMap::new()
-                }));
+                let instance_rc = Rc::new(RefCell::new(InstanceData::new(rc_class.clone())));
 
                 // 2. On crée la Value pour la VM
                 let instance = Value::Instance(instance_rc.clone());
+                gc::track(&instance);
 
                 // --- INITIALISATION DES CHAMPS ---
                 // On doit remonter toute la chaîne de prototypes (parents d'abord)
@@ -2025,12 +2797,7 @@ impl VM {
                 // Note : Si tu veux supporter les champs hérités, il faut iterer sur les parents.
                 
                 // On collecte la chaîne d'héritage (du plus lointain parent à l'enfant)
-                let mut hierarchy = Vec::new();
-                let mut curr = Some(rc_class.clone());
-                while let Some(c) = curr {
-                    hierarchy.push(c.clone());
-                    curr = c.parent_ref.clone();
-                }
+                let mut hierarchy: Vec<Rc<ClassData>> = rc_class.lineage().collect();
                 hierarchy.reverse(); // On commence par le Grand-Père
 
                 // On exécute les initialiseurs
@@ -2047,33 +2814,23 @@ impl VM {
                             ) {
                                 Ok(val) => {
                                     // On insère dans l'instance
-                                    instance_rc.borrow_mut().fields.insert(field_name.clone(), val);
+                                    instance_rc.borrow_mut().set_field(field_name.clone(), val);
+                                },
+                                Err(e) => {
+                                    // Le catch reçoit toujours la valeur levée (ex: une ValueError)
+                                    return Err(e.map_message(|m| format!("Erreur initialisation champ '{}': {}", field_name, m)));
                                 },
-                                Err(e) => return Err(format!("Erreur initialisation champ '{}': {}", field_name, e)),
                             }
                         } else {
                             // Cas théorique (si on stockait des constantes brutes)
-                            instance_rc.borrow_mut().fields.insert(field_name.clone(), init_val_or_func.clone());
+                            instance_rc.borrow_mut().set_field(field_name.clone(), init_val_or_func.clone());
                         }
                     }
                 }
                 // -------------------------------------------
 
-                // 2. Recherche du constructeur "init" (Logique existante)
-                let mut init_method = None;
-                let mut current_class_lookup = rc_class.clone();
-
-                loop {
-                    if let Some(m) = current_class_lookup.methods.get("init") {
-                        init_method = Some(m.clone());
-                        break;
-                    }
-                    if let Some(parent_rc) = &current_class_lookup.parent_ref {
-                        current_class_lookup = parent_rc.clone();
-                        continue;
-                    }
-                    break;
-                }
+                // 2. Recherche du constructeur "init" (la surcharge qui accepte ces arguments)
+                let init_method = rc_class.lookup_constructor(arg_count)?.map(|(m, _)| m);
 
                 // 3. Appel du constructeur
                 if let Some(method_val) = init_method {
@@ -2086,7 +2843,7 @@ impl VM {
                     self.run_callable_sync(method_val, call_args, Some(rc_class.clone()))?;
                 } else {
                     if arg_count > 0 {
-                        return Err(format!("Classe '{}' n'a pas de constructeur 'init'", rc_class.name));
+                        return Err(format!("Classe '{}' n'a pas de constructeur 'init'", rc_class.name).into());
                     }
                     self.stack.truncate(func_idx + 1);
                 }
@@ -2097,13 +2854,34 @@ impl VM {
 
             // CAS 3 : Fonction Native
             Value::Native(name) => {
-                let func_ptr = crate::native::find(&name)
-                    .ok_or(format!("Fonction native '{}' introuvable", name))?;
+                let func_ptr = match crate::native::find(&name) {
+                    Some(func_ptr) => Some(func_ptr),
+                    None if crate::native::ffi::is_extern(name) => None,
+                    None => return Err(format!("Fonction native '{}' introuvable", name).into()),
+                };
 
                 let args_start = func_idx + 1;
                 let args: Vec<Value> = self.stack.drain(args_start..).collect();
 
-                let result = func_ptr(args)?;
+                // Natives qui ont besoin de la VM elle-même
+                let result = match name.as_str() {
+                    "parallel_map" => self.parallel_map(args)?,
+                    "assert_throws" => self.assert_throws(args)?,
+                    "reflect_doc" => {
+                        let args = crate::native::Args::new("Reflect.member_doc(value, name?)", &args);
+                        let value = args.want(0)?;
+                        let doc = match args.get(1) {
+                            None | Some(Value::Null) => self.doc_of(value),
+                            Some(_) => self.member_doc(value, &args.want_str(1)?),
+                        };
+                        doc.map(|s| Value::String(s.into())).unwrap_or(Value::Null)
+                    },
+                    _ => match func_ptr {
+                        Some(func_ptr) => func_ptr(args)?,
+                        // Fonction C déclarée par `extern`
+                        None => crate::native::ffi::call(name, args)?,
+                    },
+                };
 
                 self.stack.pop(); // Pop la fonction native
                 self.push(result);
@@ -2113,10 +2891,23 @@ impl VM {
             _ => Err(format!(
                 "Tentative d'appel sur {:?} qui n'est pas une fonction",
                 target
-            )),
+            ).into()),
         }
     }
 
+    // Locale capturée par une closure : remplacée sur la pile par une case partagée (une seule
+    // par locale, même si plusieurs closures la capturent), renvoyée pour l'environnement
+    fn capture_slot(&mut self, abs_index: usize) -> Value {
+        if let Value::Cell(_) = &self.stack[abs_index] {
+            return self.stack[abs_index].clone();
+        }
+        let value = std::mem::replace(&mut self.stack[abs_index], Value::Null);
+        let cell = Value::Cell(Rc::new(RefCell::new(value)));
+        gc::track(&cell);
+        self.stack[abs_index] = cell.clone();
+        cell
+    }
+
     fn resolve_lazy_native(&mut self, global_id: usize) -> Option<Value> {
         // 1. Retrouver le nom à partir de l'ID
         let name = {
@@ -2145,13 +2936,17 @@ impl VM {
     }
 
     /// Injecte et exécute un nouveau Chunk dans la VM existante (pour le REPL)
-    pub fn execute_chunk(&mut self, chunk: Chunk) -> Result<(), String> {
+    // Retourne la valeur laissée sur la pile par le chunk (expression finale du REPL), s'il y en a une
+    pub fn execute_chunk(&mut self, chunk: Chunk) -> Result<Option<Value>, String> {
         // On crée une fonction fictive pour emballer ce chunk
         let script_func = Value::Function(Rc::new(crate::ast::value::FunctionData {
             params: vec![],
             ret_type: None,
             chunk,
-            env: None
+            env: None,
+            doc: None,
+            variadic: false,
+            entry_points: Vec::new(),
         }));
 
         // On crée une nouvelle Frame au niveau 0 (comme le main)
@@ -2160,13 +2955,115 @@ impl VM {
             ip: 0,
             slot_offset: 0,
             class_context: None,
+            defers: Vec::new(),
+            resources: Vec::new(),
         };
 
         // On l'ajoute à la pile d'appels
-        self.frames.push(frame);
+        self.push_frame(frame);
 
         // Et on lance l'exécution !
-        self.run()
+        let base = self.stack.len();
+        self.run()?;
+
+        if self.stack.len() > base {
+            let result = self.pop();
+            self.stack.truncate(base);
+            Ok(Some(result))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Docstring d'une fonction, d'une classe (ou d'une de ses instances) ou d'un module importé.
+    pub fn doc_of(&self, value: &Value) -> Option<String> {
+        match value {
+            Value::Function(function) => function.doc.clone(),
+            Value::Class(class) => class.doc.clone(),
+            Value::Instance(instance) => instance.borrow().class.doc.clone(),
+            // Objet module (`import ... as m`) : retrouvé par identité dans le cache des imports
+            Value::Dict(_) => self.modules.values()
+                .find(|module| module.value.is_same(value))
+                .and_then(|module| module.doc.clone()),
+            _ => None,
+        }
+    }
+
+    /// Docstring de la méthode `name` (héritée ou statique) d'une classe ou d'une instance.
+    pub fn member_doc(&self, owner: &Value, name: &str) -> Option<String> {
+        let class = match owner {
+            Value::Class(class) => class.clone(),
+            Value::Instance(instance) => instance.borrow().class.clone(),
+            _ => return None,
+        };
+        let (method, _) = class.method_table.get(name).or_else(|| class.static_method_table.get(name))?;
+        self.doc_of(method)
+    }
+
+    // Appelle une fonction globale depuis l'hôte (ex: `aegis run --call main`)
+    // Si la fonction n'attend aucun paramètre, les arguments sont ignorés.
+    pub fn call_global(&mut self, name: &str, args: Vec<Value>) -> Result<Value, String> {
+        let idx = self.global_names.borrow().get(name).copied()
+            .ok_or_else(|| format!("Fonction '{}' introuvable", name))?;
+        let callable = self.globals.get(idx as usize).cloned().unwrap_or(Value::Null);
+
+        let args = match &callable {
+            Value::Function(f) if f.params.is_empty() => vec![],
+            Value::Function(_) => args,
+            _ => return Err(format!("'{}' n'est pas une fonction", name)),
+        };
+
+        // `async func main()` : on attend son résultat
+        let result = self.run_callable_sync(callable, args, None).and_then(|value| match value {
+            Value::Task(ref task) => {
+                task.borrow_mut().observed = true;
+                self.resolve_task(task)
+            },
+            value => Ok(value),
+        });
+        result.map_err(|e| e.message)
+    }
+
+    // Définit (ou crée) une variable globale depuis l'hôte, ex: `_` dans le REPL
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        let idx = {
+            let mut names = self.global_names.borrow_mut();
+            let next_id = names.len() as u16;
+            *names.entry(name.to_string()).or_insert(next_id) as usize
+        };
+
+        if idx >= self.globals.len() {
+            self.globals.resize(idx + 1, Value::Null);
+        }
+        self.globals[idx] = value;
+    }
+
+    pub fn snapshot(&self) -> VmSnapshot {
+        let modules = self.modules.values().map(|module| &module.value);
+        VmSnapshot {
+            heap: snapshot::HeapSnapshot::capture(self.globals.iter().chain(modules)),
+            globals: self.globals.clone(),
+            global_names: self.global_names.borrow().clone(),
+            modules: self.modules.clone(),
+            frames_len: self.frames.len(),
+            stack_len: self.stack.len(),
+            handlers_len: self.handlers.len(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: VmSnapshot) {
+        snapshot.heap.restore();
+        self.globals = snapshot.globals;
+        // Le compilateur partage cette table : on remet les noms déclarés par la ligne annulée
+        *self.global_names.borrow_mut() = snapshot.global_names;
+        self.modules = snapshot.modules;
+
+        // Une erreur laisse les frames/valeurs de l'évaluation en cours : on nettoie
+        self.save_ip();
+        self.frames.truncate(snapshot.frames_len);
+        self.load_frame();
+        self.stack.truncate(snapshot.stack_len);
+        self.handlers.truncate(snapshot.handlers_len);
     }
 
     fn runtime_error(&self, message: String) -> String {
@@ -2183,13 +3080,127 @@ impl VM {
             0
         };
 
-        format!("[Line {}] Error: {}", line, message)
+        // Ligne 0 : code compilé sans table des lignes (bundle release)
+        let mut report = match line {
+            0 => format!("Error: {}", message),
+            line => format!("[Line {}] Error: {}", line, message),
+        };
+        // Pile d'appels (la plus récente d'abord) dès qu'on est dans une fonction
+        let frames: Vec<&CallFrame> = self.frames.iter().rev().filter(|frame| !frame.is_host_base()).collect();
+        if frames.len() > 1 {
+            for frame in frames {
+                let chunk = frame.chunk();
+                let line = chunk.lines.get(frame.ip.saturating_sub(1)).copied().unwrap_or(0);
+                report.push_str(&format!("\n    at {} ({})", chunk.name, location(chunk, line)));
+            }
+        }
+        report
+    }
+
+    // Ligne de la prochaine instruction de la frame courante
+    fn current_line(&self) -> usize {
+        self.frames.last()
+            .and_then(|frame| frame.chunk().lines.get(frame.ip).copied())
+            .unwrap_or(0)
+    }
+
+    // Module trouvable par `import` : dans le bundle chargé, sur le disque ou dans la stdlib embarquée
+    fn module_exists(&self, path: &str) -> bool {
+        self.bundled.contains_key(&crate::graph::normalize(path))
+            || std::path::Path::new(path).is_file()
+            || crate::stdlib::source(path).is_some()
     }
 
+    // Source d'un module, dans le même ordre. Hors du dépôt (projet créé par `aegis new`),
+    // "stdlib/..." vient de la copie embarquée.
+    fn module_source(&self, path: &str) -> Result<String, String> {
+        if let Some(source) = self.bundled.get(&crate::graph::normalize(path)) {
+            return Ok(source.clone());
+        }
+        match std::fs::read_to_string(path) {
+            Ok(source) => Ok(source),
+            Err(e) => crate::stdlib::source(path).ok_or_else(|| format!("Failed to import '{}': {}", path, e)),
+        }
+    }
+
+    // Globale du script, sinon constante fournie par le langage ou un plugin (Error, IOError...)
     fn get_global_by_name(&self, name: &str) -> Option<Value> {
-        let global_id = self.global_names.borrow().get(name).cloned()?;
-        let val = self.globals.get(global_id as usize)?;
-        if matches!(val, Value::Null) { None } else { Some(val.clone()) }
+        let global = self.global_names.borrow().get(name).cloned()
+            .and_then(|id| self.globals.get(id as usize).cloned())
+            .filter(|val| !matches!(val, Value::Null));
+        global.or_else(|| crate::native::find_constant(name))
+    }
+
+    // `throw valeur` : la valeur elle-même parviendra au catch. Une instance d'Error reçoit
+    // la pile d'appels courante si elle n'en a pas encore. Retourne l'erreur à propager.
+    fn raise(&mut self, value: Value) -> NativeError {
+        self.save_ip();
+        if let Value::Instance(instance) = &value
+            && crate::native::errors::is_error_class(&instance.borrow().class)
+            && instance.borrow().get_field("stack").is_none_or(|s| s == Value::Null) {
+            let stack = self.format_trace(&self.capture_trace());
+            instance.borrow_mut().set_field("stack".to_string(), stack);
+        }
+        NativeError::with_value(value.to_string(), value)
+    }
+
+    // Appels en cours, du plus récent au plus ancien : (closure, ip)
+    fn capture_trace(&self) -> Vec<(Value, usize)> {
+        self.frames.iter().rev()
+            .filter(|frame| !frame.is_host_base())
+            .map(|frame| (frame.closure.clone(), frame.ip))
+            .collect()
+    }
+
+    // Champ `stack` d'une erreur : ["Maths.inverse (line 3)", "<script> (line 14)"]
+    fn format_trace(&self, trace: &[(Value, usize)]) -> Value {
+        let entries = trace.iter().filter_map(|(closure, ip)| {
+            let Value::Function(function) = closure else { return None };
+            let chunk = &function.chunk;
+            let line = chunk.lines.get(ip.saturating_sub(1)).copied().unwrap_or(0);
+            Some(Value::String(format!("{} ({})", chunk.name, location(chunk, line)).into()))
+        }).collect();
+        gc::list(entries)
+    }
+
+    // Télémétrie des concaténations : une longue chaîne allongée puis réaffectée (`s += ...`)
+    // au même endroit est recopiée à chaque tour de boucle. Au-delà du seuil, un indice unique
+    // est affiché sur stderr pour ce site.
+    fn record_concat(&mut self, left_len: usize) {
+        if left_len < CONCAT_HINT_MIN_LEN {
+            return;
+        }
+
+        let chunk = &self.function.chunk;
+        let is_assignment = chunk.code.get(self.ip).is_some_and(|&op| {
+            matches!(OpCode::from(op), OpCode::SetLocal | OpCode::SetGlobal | OpCode::SetAttr | OpCode::SetIndex)
+        });
+        if !is_assignment {
+            return;
+        }
+
+        let site = (chunk.code.as_ptr() as usize, self.ip);
+        let line = chunk.lines.get(self.ip - 1).copied().unwrap_or(0);
+        let count = self.concat_sites.entry(site).or_insert(0);
+        *count += 1;
+        if *count == CONCAT_HINT_THRESHOLD {
+            eprintln!(
+                "hint: line {}: string grown with `+=` in a loop is copied on every iteration, \
+                 collect the parts in a list and join() them instead",
+                line
+            );
+        }
+    }
+
+    // Mémorise le slot d'un champ déclaré public et sans propriété homonyme : les accès suivants
+    // depuis ce chunk sur la même classe sautent check_access et la recherche de propriété.
+    fn cache_field_slot(&mut self, name_idx: u16, class: &Rc<ClassData>, name: &str) {
+        let Some(&slot) = class.field_slots.get(name) else { return };
+        let public = matches!(class.visibilities.get(name), None | Some(Visibility::Public));
+        let has_property = class.lineage().any(|c| c.properties.contains_key(name));
+        if public && !has_property {
+            self.function.chunk.attr_cache.set(name_idx, class, slot);
+        }
     }
 
     fn check_access(&mut self, target_class: &Rc<ClassData>, member_name: &str) -> Result<(), String> {
@@ -2228,13 +3239,9 @@ impl VM {
                 if Rc::ptr_eq(ctx, target_class) { return Ok(()); }
 
                 // B. Est-ce que 'ctx' (l'appelant) hérite de 'target_class' (le propriétaire) ?
-                // On remonte la chaîne des parents de l'appelant
-                let mut curr = Some(ctx.clone());
-                while let Some(c) = curr {
-                    if Rc::ptr_eq(&c, target_class) {
-                        return Ok(());
-                    }
-                    curr = c.parent_ref.clone();
+                // Liste des ancêtres précalculée à la création de la classe
+                if ctx.is_subclass_of(target_class) {
+                    return Ok(());
                 }
                 
                 Err(format!("Accès refusé : '{}' est protégé dans '{}'", member_name, target_class.name))
@@ -2242,17 +3249,327 @@ impl VM {
         }
     }
 
-    fn find_method(&self, class: &Rc<ClassData>, name: &str) -> Option<Value> {
-        // 1. Chercher dans la classe courante
-        if let Some(m) = class.methods.get(name) {
-            return Some(m.clone());
+    fn value_matches_type(&self, val: &Value, type_name: &str) -> bool {
+        match (val, type_name) {
+            (Value::Integer(_), "int") => true,
+            (Value::Float(_), "float") => true,
+            (Value::String(_), "string") => true,
+            (Value::Boolean(_), "bool") => true,
+            (Value::List(_), "list") => true,
+            (Value::Dict(_), "dict") => true,
+            (Value::Function(_), "func") => true, // Ou "function"
+            (Value::Bytes(_), "bytes") => true,
+            (Value::TypedArray(array), name) if array.borrow().type_name() == name => true,
+            (Value::Null, "null") => true, // Pour les unions : string|null
+            (Value::Null, _) => false, // Null n'est généralement pas le type attendu (sauf "any" ?)
+            (_, "any") => true,
+            // Nom de classe ou d'interface (ex: func feed(a: Animal))
+            (Value::Instance(inst), name) => self.instance_matches_type(&inst.borrow().class, name),
+            _ => false,
         }
-        
-        // 2. Remonter au parent
-        if let Some(parent) = &class.parent_ref {
-            return self.find_method(parent, name);
+    }
+
+    // Vrai si une instance de `class` est acceptée par l'annotation `type_name`,
+    // qui doit désigner une classe (elle-même ou un ancêtre) ou une interface implémentée.
+    fn instance_matches_type(&self, class: &Rc<ClassData>, type_name: &str) -> bool {
+        let Some(target) = self.get_global_by_name(type_name) else {
+            return false;
+        };
+
+        match &target {
+            Value::Class(target_class) => class.is_subclass_of(target_class),
+            Value::Interface(iface) => class.implements(iface),
+            _ => false,
+        }
+    }
+
+    fn find_method(&self, class: &Rc<ClassData>, name: &str) -> Option<Value> {
+        class.lookup_method(name).map(|(m, _)| m)
+    }
+}
+
+// l[i] (négatif : depuis la fin), d["clé"] (null si absente), s[i] (i-ème caractère), b[i] (octet)
+fn get_index(obj: &Value, index: &Value) -> Result<Value, String> {
+    match obj {
+        Value::List(list) => {
+            let list = list.borrow();
+            let i = resolve_index("List", index, list.len())?;
+            Ok(list[i].clone())
+        },
+        Value::TypedArray(array) => {
+            let array = array.borrow();
+            let i = resolve_index(array.type_name(), index, array.len())?;
+            Ok(array.get(i).unwrap_or(Value::Null))
+        },
+        Value::Bytes(bytes) => {
+            let bytes = bytes.borrow();
+            let i = resolve_index("Bytes", index, bytes.len())?;
+            Ok(Value::Integer(bytes[i] as i64))
+        },
+        Value::Dict(dict) => {
+            let dict = dict.borrow();
+            // Clé construite à l'exécution : cherchée telle quelle, l'interner n'en garde pas trace
+            let found = match index {
+                Value::String(Str::Owned(key)) => dict.get(key.as_str()),
+                other => dict.get(&ValueKey::from_value(other)?),
+            };
+            Ok(found.cloned().unwrap_or(Value::Null))
+        },
+        Value::String(s) => {
+            let i = resolve_index("String", index, s.chars().count())?;
+            Ok(Value::String(s.chars().nth(i).map(String::from).unwrap_or_default().into()))
+        },
+        other => Err(format!("Cannot index a value of type {}", other.type_name())),
+    }
+}
+
+// l[i] = v (l'élément doit exister) et d["clé"] = v
+fn set_index(obj: &Value, index: &Value, value: Value) -> Result<(), String> {
+    match obj {
+        Value::List(list) => {
+            let mut list = list.borrow_mut();
+            let i = resolve_index("List", index, list.len())?;
+            list[i] = value;
+            Ok(())
+        },
+        Value::TypedArray(array) => {
+            let mut array = array.borrow_mut();
+            let i = resolve_index(array.type_name(), index, array.len())?;
+            array.set(i, &value)
+        },
+        Value::Bytes(bytes) => {
+            let mut bytes = bytes.borrow_mut();
+            let i = resolve_index("Bytes", index, bytes.len())?;
+            bytes[i] = match value {
+                Value::Integer(n) => u8::try_from(n).map_err(|_| format!("A byte must be between 0 and 255, got {}", n))?,
+                other => return Err(format!("A byte must be an int, got {}", other.type_name())),
+            };
+            Ok(())
+        },
+        Value::Dict(dict) => {
+            let key = ValueKey::from_value(index)?;
+            dict.borrow_mut().insert(key, value);
+            Ok(())
+        },
+        Value::String(_) => Err("Cannot assign to a string index (strings are immutable)".into()),
+        other => Err(format!("Cannot assign to an index of a value of type {}", other.type_name())),
+    }
+}
+
+// Index entier dans [-len, len), ramené dans [0, len)
+fn resolve_index(kind: &str, index: &Value, len: usize) -> Result<usize, String> {
+    let Value::Integer(i) = index else {
+        return Err(format!("{} index must be an int, got {}", kind, index.type_name()));
+    };
+    let resolved = if *i < 0 { *i + len as i64 } else { *i };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("{} index {} out of range (length {})", kind, i, len));
+    }
+    Ok(resolved as usize)
+}
+
+// Durées et dates avec + - * / : dt + d, dt2 - dt1, d * 2, d1 / d2... None pour les autres types
+fn time_arithmetic(op: char, a: &Value, b: &Value) -> Option<Result<Value, String>> {
+    use crate::native::date::millis;
+    let out_of_range = || "Duration or date out of range".to_string();
+    let number = |v: &Value| match v {
+        Value::Integer(i) => Some(*i as f64),
+        Value::Float(f) => Some(*f),
+        _ => None,
+    };
+
+    Some(match (op, a, b) {
+        ('+', Value::Duration(x), Value::Duration(y)) => x.checked_add(*y).map(Value::Duration).ok_or_else(out_of_range),
+        ('-', Value::Duration(x), Value::Duration(y)) => x.checked_sub(*y).map(Value::Duration).ok_or_else(out_of_range),
+        ('+', Value::DateTime(t, zone), Value::Duration(d)) | ('+', Value::Duration(d), Value::DateTime(t, zone)) => {
+            t.checked_add(*d).map(|t| Value::DateTime(t, *zone)).ok_or_else(out_of_range)
+        },
+        ('-', Value::DateTime(t, zone), Value::Duration(d)) => t.checked_sub(*d).map(|t| Value::DateTime(t, *zone)).ok_or_else(out_of_range),
+        ('-', Value::DateTime(t1, _), Value::DateTime(t2, _)) => t1.checked_sub(*t2).map(Value::Duration).ok_or_else(out_of_range),
+        ('*', Value::Duration(d), n) | ('*', n, Value::Duration(d)) => {
+            let factor = number(n)?;
+            millis(*d as f64 * factor).map(Value::Duration)
+        },
+        ('/', Value::Duration(_), Value::Duration(0)) => Err("Division by zero".into()),
+        ('/', Value::Duration(x), Value::Duration(y)) => Ok(Value::Float(*x as f64 / *y as f64)),
+        ('/', Value::Duration(d), n) => match number(n)? {
+            0.0 => Err("Division by zero".into()),
+            divisor => millis(*d as f64 / divisor).map(Value::Duration),
+        },
+        _ => return None,
+    })
+}
+
+// Ordre de deux durées ou de deux dates (comparées en tant qu'instants)
+// Ordre utilisé par <, <=, >, >= : nombres (Integer et Float mélangés, convertis en float),
+// chaînes (ordre lexicographique), durées et dates. None si les types ne se comparent pas,
+// ou si un NaN est en jeu : la comparaison vaut alors false.
+// Les entiers sont des i64 : un résultat hors de cette plage est une erreur, comme la division
+// par zéro, plutôt qu'une valeur tronquée
+// Quotient arrondi vers -inf (`~/`) : -7 ~/ 2 vaut -4. None si b vaut 0 ou si le résultat déborde.
+pub(crate) fn floor_div(a: i64, b: i64) -> Option<i64> {
+    let q = a.checked_div(b)?;
+    // La division de Rust tronque vers zéro : on descend d'un cran quand le reste est négatif
+    if a % b != 0 && (a < 0) != (b < 0) { Some(q - 1) } else { Some(q) }
+}
+
+fn int_overflow(a: i64, op: &str, b: i64) -> String {
+    format!("Integer overflow: {} {} {} does not fit in an int", a, op, b)
+}
+
+fn shift_amount(b: i64) -> Result<u32, String> {
+    u32::try_from(b).ok().filter(|shift| *shift < 64)
+        .ok_or_else(|| format!("Invalid shift amount {} (expected 0 to 63)", b))
+}
+
+// `<<` qui perdrait des bits (ou changerait le signe) déborde, comme une multiplication par 2^b
+fn shift_left(a: i64, b: i64) -> Result<i64, String> {
+    let shift = shift_amount(b)?;
+    let result = a << shift;
+    if result >> shift != a {
+        return Err(int_overflow(a, "<<", b));
+    }
+    Ok(result)
+}
+
+// `+` entre une chaîne et une autre valeur dans un fichier strict : TypeError attrapable
+fn strict_concat_error(a: &Value, b: &Value) -> NativeError {
+    let message = format!(
+        "Cannot add {} and {} in strict mode (convert with str(x) or use \"${{x}}\")",
+        a.type_name(), b.type_name()
+    );
+    let error = crate::native::errors::new_error("TypeError", &message, Value::Null, vec![]);
+    NativeError::with_value(message, error)
+}
+
+// <, <=, >, >= : NaN rend toutes les comparaisons fausses, deux valeurs qui ne s'ordonnent
+// pas entre elles (un nombre et une chaîne, null...) lèvent une TypeError attrapable
+fn ordering_holds(a: &Value, b: &Value, op: &str, test: fn(std::cmp::Ordering) -> bool) -> Result<bool, NativeError> {
+    let is_number = |v: &Value| matches!(v, Value::Integer(_) | Value::Float(_));
+    match compare_values(a, b) {
+        Some(ordering) => Ok(test(ordering)),
+        None if is_number(a) && is_number(b) => Ok(false),
+        None => {
+            let message = format!("Cannot compare {} and {} with '{}'", a.type_name(), b.type_name(), op);
+            let error = crate::native::errors::new_error("TypeError", &message, Value::Null, vec![]);
+            Err(NativeError::with_value(message, error))
+        },
+    }
+}
+
+fn compare_values(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+    match (a, b) {
+        (Value::Integer(x), Value::Integer(y)) => Some(x.cmp(y)),
+        (Value::Float(x), Value::Float(y)) => x.partial_cmp(y),
+        (Value::Integer(x), Value::Float(y)) => (*x as f64).partial_cmp(y),
+        (Value::Float(x), Value::Integer(y)) => x.partial_cmp(&(*y as f64)),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        (Value::Duration(x), Value::Duration(y)) | (Value::DateTime(x, _), Value::DateTime(y, _)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+// Mêmes éléments, au sens de `is`, dans le même ordre (NaN compris)
+fn same_items(a: &[Value], b: &[Value]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| match (x, y) {
+        (Value::Float(x), Value::Float(y)) => x.to_bits() == y.to_bits(),
+        _ => x.is_same(y),
+    })
+}
+
+// Position d'une frame dans une pile d'appels : "maths.aeg:3", ou "line 3" sans fichier connu
+fn location(chunk: &Chunk, line: usize) -> String {
+    match (&chunk.file, line) {
+        (Some(file), _) => format!("{}:{}", file, line),
+        (None, 0) => "no debug info".to_string(),
+        (None, line) => format!("line {}", line),
+    }
+}
+
+// Noms exposés par un module : ses déclarations `export` s'il en a, sinon tous ses noms
+// déclarés au premier niveau sauf les privés
+fn module_exports(statements: &[crate::ast::Statement]) -> Vec<String> {
+    use crate::ast::Instruction;
+
+    let explicit: Vec<String> = statements.iter().filter_map(|stmt| match &stmt.kind {
+        Instruction::Export(inner) => declared_name(inner),
+        _ => None,
+    }).collect();
+    if statements.iter().any(|stmt| matches!(stmt.kind, Instruction::Export(_))) {
+        return explicit;
+    }
+
+    let private_names = module_private_names(statements);
+    statements.iter()
+        .filter_map(|stmt| declared_name(&stmt.kind))
+        .filter(|name| !private_names.contains(name))
+        .collect()
+}
+
+// Tous les noms liés au premier niveau d'un module, imports compris
+fn module_bindings(statements: &[crate::ast::Statement]) -> Vec<String> {
+    statements.iter().flat_map(|stmt| bound_names(&stmt.kind)).collect()
+}
+
+// Noms privés d'un module : préfixés par '_' ou déclarés avec 'private'
+fn module_private_names(statements: &[crate::ast::Statement]) -> Vec<String> {
+    use crate::ast::Instruction;
+
+    statements.iter().filter_map(|stmt| match &stmt.kind {
+        Instruction::Private(inner) => declared_name(inner),
+        other => declared_name(other).filter(|name| name.starts_with('_')),
+    }).collect()
+}
+
+fn declared_name(instr: &crate::ast::Instruction) -> Option<String> {
+    use crate::ast::Instruction;
+
+    match instr {
+        Instruction::Set(name, _, _) | Instruction::Assign(name, _) | Instruction::Const(name, _, _)
+        | Instruction::Enum(name, _) => Some(name.clone()),
+        Instruction::Function { name, .. } | Instruction::Namespace { name, .. } => Some(name.clone()),
+        Instruction::Class(def) => Some(def.name.clone()),
+        Instruction::Interface(def) => Some(def.name.clone()),
+        Instruction::Private(inner) | Instruction::Export(inner) => declared_name(inner),
+        _ => None,
+    }
+}
+
+// Noms qu'une instruction de premier niveau lie : sa déclaration, ou les noms importés
+fn bound_names(instr: &crate::ast::Instruction) -> Vec<String> {
+    use crate::ast::Instruction;
+
+    match instr {
+        Instruction::Import(_, alias) => alias.iter().cloned().collect(),
+        Instruction::FromImport(_, names) => names.iter().map(|(_, alias)| alias.clone()).collect(),
+        other => declared_name(other).into_iter().collect(),
+    }
+}
+
+// Candidat le plus proche (distance d'édition) pour les messages "did you mean" :
+// au plus 1 modification pour les noms de moins de 4 caractères, 2 au-delà.
+fn closest_name<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = if name.chars().count() >= 4 { 2 } else { 1 };
+    candidates
+        .filter(|c| *c != name)
+        .map(|c| (edit_distance(name, c), c))
+        .filter(|(d, _)| *d <= max_distance)
+        .min_by(|(d1, c1), (d2, c2)| d1.cmp(d2).then(c1.cmp(c2)))
+        .map(|(_, c)| c)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb { diagonal } else { 1 + diagonal.min(above).min(row[j]) };
+            diagonal = above;
         }
-        
-        None
     }
+    row[b.len()]
 }
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use crate::ast::{Expression, Instruction, Statement, Value};
+
+// Passes d'optimisation activées par -O2 (le constant folding de base reste toujours actif).
+// Ici on reste volontairement prudent : seules des expressions sans effet de bord sont
+// dupliquées ou déplacées.
+
+/// Fonction inlinable : noms des paramètres et expression retournée.
+pub type InlineBody = (Vec<String>, Expression);
+
+/// Fonction "triviale" : un seul `return <expr>`, sans annotation de type, et dont
+/// l'expression n'utilise que ses paramètres, des constantes connues et des opérateurs purs.
+pub fn inline_candidate(
+    params: &[(String, Option<String>)],
+    ret_type: &Option<String>,
+    body: &[Statement],
+    constants: &HashMap<String, Value>,
+) -> Option<InlineBody> {
+    if ret_type.is_some() || params.iter().any(|(_, t)| t.is_some()) {
+        return None;
+    }
+
+    let [Statement { kind: Instruction::Return(expr), .. }] = body else {
+        return None;
+    };
+
+    let names: Vec<String> = params.iter().map(|(p, _)| p.clone()).collect();
+
+    // Les constantes sont remplacées par leur valeur (sauf si un paramètre les masque)
+    let literals: HashMap<String, Expression> = constants.iter()
+        .filter(|(name, _)| !names.contains(name))
+        .map(|(name, val)| (name.clone(), Expression::Literal(val.clone())))
+        .collect();
+    let expr = substitute(expr, &literals);
+
+    if is_pure(&expr, Some(&names)) {
+        Some((names, expr))
+    } else {
+        None
+    }
+}
+
+/// Vrai si l'expression n'a pas d'effet de bord (littéraux, variables, opérateurs).
+/// Avec `allowed`, seules ces variables peuvent apparaître.
+pub fn is_pure(expr: &Expression, allowed: Option<&[String]>) -> bool {
+    match expr {
+        Expression::Literal(_) => true,
+        Expression::Variable(name) => allowed.is_none_or(|names| names.contains(name)),
+
+        Expression::Add(a, b) | Expression::Sub(a, b) | Expression::Mul(a, b) | Expression::Div(a, b)
+        | Expression::Modulo(a, b) | Expression::FloorDiv(a, b) | Expression::Equal(a, b) | Expression::NotEqual(a, b)
+        | Expression::Same(a, b) | Expression::NotSame(a, b)
+        | Expression::LessThan(a, b) | Expression::GreaterThan(a, b) | Expression::LessEqual(a, b)
+        | Expression::GreaterEqual(a, b) | Expression::And(a, b) | Expression::Or(a, b)
+        | Expression::NullCoalescing(a, b) | Expression::BitAnd(a, b) | Expression::BitOr(a, b)
+        | Expression::BitXor(a, b) | Expression::ShiftLeft(a, b) | Expression::ShiftRight(a, b) => {
+            is_pure(a, allowed) && is_pure(b, allowed)
+        },
+        Expression::Ternary(c, a, b) => is_pure(c, allowed) && is_pure(a, allowed) && is_pure(b, allowed),
+        Expression::Not(e) => is_pure(e, allowed),
+        Expression::Interpolation(parts) => parts.iter().all(|part| is_pure(part, allowed)),
+
+        _ => false,
+    }
+}
+
+/// Remplace les paramètres par les arguments dans le corps d'une fonction inlinée.
+/// Le corps a été validé par `is_pure`, les autres formes n'apparaissent donc pas.
+pub fn substitute(expr: &Expression, bindings: &HashMap<String, Expression>) -> Expression {
+    let sub = |e: &Expression| Box::new(substitute(e, bindings));
+
+    match expr {
+        Expression::Variable(name) => bindings.get(name).cloned().unwrap_or_else(|| expr.clone()),
+
+        Expression::Add(a, b) => Expression::Add(sub(a), sub(b)),
+        Expression::Sub(a, b) => Expression::Sub(sub(a), sub(b)),
+        Expression::Mul(a, b) => Expression::Mul(sub(a), sub(b)),
+        Expression::Div(a, b) => Expression::Div(sub(a), sub(b)),
+        Expression::Modulo(a, b) => Expression::Modulo(sub(a), sub(b)),
+        Expression::FloorDiv(a, b) => Expression::FloorDiv(sub(a), sub(b)),
+        Expression::Equal(a, b) => Expression::Equal(sub(a), sub(b)),
+        Expression::NotEqual(a, b) => Expression::NotEqual(sub(a), sub(b)),
+        Expression::Same(a, b) => Expression::Same(sub(a), sub(b)),
+        Expression::NotSame(a, b) => Expression::NotSame(sub(a), sub(b)),
+        Expression::LessThan(a, b) => Expression::LessThan(sub(a), sub(b)),
+        Expression::GreaterThan(a, b) => Expression::GreaterThan(sub(a), sub(b)),
+        Expression::LessEqual(a, b) => Expression::LessEqual(sub(a), sub(b)),
+        Expression::GreaterEqual(a, b) => Expression::GreaterEqual(sub(a), sub(b)),
+        Expression::And(a, b) => Expression::And(sub(a), sub(b)),
+        Expression::Or(a, b) => Expression::Or(sub(a), sub(b)),
+        Expression::NullCoalescing(a, b) => Expression::NullCoalescing(sub(a), sub(b)),
+        Expression::BitAnd(a, b) => Expression::BitAnd(sub(a), sub(b)),
+        Expression::BitOr(a, b) => Expression::BitOr(sub(a), sub(b)),
+        Expression::BitXor(a, b) => Expression::BitXor(sub(a), sub(b)),
+        Expression::ShiftLeft(a, b) => Expression::ShiftLeft(sub(a), sub(b)),
+        Expression::ShiftRight(a, b) => Expression::ShiftRight(sub(a), sub(b)),
+        Expression::Ternary(c, a, b) => Expression::Ternary(sub(c), sub(a), sub(b)),
+        Expression::Not(e) => Expression::Not(sub(e)),
+        Expression::Interpolation(parts) => Expression::Interpolation(parts.iter().map(|p| substitute(p, bindings)).collect()),
+
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,273 @@
+// Tâches `async`.
+//
+// Appeler une fonction `async func` ne l'exécute pas : l'appel crée une tâche (ses propres
+// frames, pile et handlers) et renvoie aussitôt une valeur Task. Les tâches avancent quand
+// quelqu'un attend un résultat (`await`) et à la fin du script, jamais en parallèle :
+// une tâche tourne jusqu'à son prochain `await` sur une tâche pas encore terminée, puis cède
+// la place à la suivante (tourniquet). Les natives lentes (Http.get_async, Socket.read_async,
+// Async.sleep...) tournent sur un thread et terminent leur tâche via native::jobs.
+//
+// Un `await` hors d'une tâche (script principal, ou callback exécuté par run_callable_sync)
+// ne peut pas céder sa place : il fait tourner les tâches jusqu'à ce que la sienne finisse.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use super::{CallFrame, ExceptionHandler, VM};
+use crate::ast::value::{ClassData, FunctionData, TaskData, TaskState};
+use crate::ast::Value;
+use crate::chunk::Chunk;
+use crate::native::NativeError;
+use crate::native::jobs;
+
+// Une tâche en attente de son tour
+struct TaskContext {
+    task: Rc<RefCell<TaskData>>,
+    frames: Vec<CallFrame>,
+    stack: Vec<Value>,
+    handlers: Vec<ExceptionHandler>,
+    // Tâche attendue par son dernier `await` : inutile de la reprendre avant qu'elle finisse
+    waiting_on: Option<Rc<RefCell<TaskData>>>,
+}
+
+#[derive(Default)]
+pub(super) struct Scheduler {
+    ready: VecDeque<TaskContext>,
+    // Tâche en cours d'exécution (None : script principal)
+    current: Option<Rc<RefCell<TaskData>>>,
+    // Profondeur de run_callable_sync au début de la tranche : un `await` plus profond
+    // est dans un callback et ne peut pas suspendre la tâche
+    slice_depth: usize,
+    pub(super) sync_depth: usize,
+    // Posé par `await` quand la tâche courante doit céder sa place
+    parked_on: Option<Rc<RefCell<TaskData>>>,
+    // Tâches terminées en erreur, signalées à la fin si personne ne les a attendues
+    failed: Vec<Rc<RefCell<TaskData>>>,
+}
+
+enum SliceOutcome {
+    Parked(Rc<RefCell<TaskData>>),
+    Done(Value),
+    Failed(Value),
+}
+
+impl VM {
+    // Appel d'une `async func` : la fonction et ses arguments (en haut de pile, déjà adaptés
+    // par bind_arguments) deviennent la pile d'une nouvelle tâche, qui commence à `ip` ;
+    // ils sont remplacés par la valeur Task
+    pub(super) fn spawn_task(&mut self, function: Value, arg_count: usize, ip: usize, context: Option<Rc<ClassData>>) {
+        let name = match &function {
+            Value::Function(f) => f.chunk.name.clone(),
+            _ => String::from("task"),
+        };
+        let func_idx = self.stack.len() - 1 - arg_count;
+        let stack: Vec<Value> = self.stack.drain(func_idx..).collect();
+
+        // Frame de base vide sous celle de la fonction : son retour y laisse le résultat,
+        // puis la fin de la frame de base termine la tranche (comme la fin du script principal)
+        let base = CallFrame {
+            closure: Value::Function(Rc::new(FunctionData {
+                params: vec![],
+                ret_type: None,
+                chunk: Chunk::new(),
+                env: None,
+                doc: None,
+                variadic: false,
+                entry_points: Vec::new(),
+            })),
+            ip: 0,
+            slot_offset: 0,
+            class_context: None,
+            defers: Vec::new(),
+            resources: Vec::new(),
+        };
+        let frame = CallFrame {
+            closure: function,
+            ip,
+            slot_offset: 1,
+            class_context: context,
+            defers: Vec::new(),
+            resources: Vec::new(),
+        };
+
+        let task = Rc::new(RefCell::new(TaskData::new(name)));
+        self.scheduler.ready.push_back(TaskContext {
+            task: task.clone(),
+            frames: vec![base, frame],
+            stack,
+            handlers: Vec::new(),
+            waiting_on: None,
+        });
+        self.push(Value::Task(task));
+    }
+
+    // OpCode::Await : remplace la tâche en haut de pile par son résultat (une autre valeur
+    // est laissée telle quelle). Une tâche qui échoue lève son erreur ici.
+    pub(super) fn await_top(&mut self) -> Result<(), NativeError> {
+        let Some(Value::Task(task)) = self.stack.last() else {
+            return Ok(());
+        };
+        let task = task.clone();
+        task.borrow_mut().observed = true;
+
+        let can_park = self.scheduler.current.is_some() && self.scheduler.sync_depth == self.scheduler.slice_depth;
+        if task.borrow().is_pending() && can_park {
+            // L'instruction sera rejouée quand la tâche reprendra
+            self.ip -= 1;
+            self.scheduler.parked_on = Some(task);
+            return Ok(());
+        }
+
+        let value = self.resolve_task(&task)?;
+        self.pop();
+        self.push(value);
+        Ok(())
+    }
+
+    pub(super) fn resolve_task(&mut self, task: &Rc<RefCell<TaskData>>) -> Result<Value, NativeError> {
+        if task.borrow().is_pending() {
+            self.run_tasks_until(Some(task))?;
+        }
+        match &task.borrow().state {
+            TaskState::Done(value) => Ok(value.clone()),
+            TaskState::Failed(error) => Err(self.raise(error.clone())),
+            TaskState::Pending => Err(format!("Task '{}' did not finish", task.borrow().name).into()),
+        }
+    }
+
+    // Fait tourner les tâches jusqu'à ce que `target` soit terminée, ou toutes (None).
+    // L'exécution en cours (frames, pile, handlers) est mise de côté puis restaurée.
+    pub(super) fn run_tasks_until(&mut self, target: Option<&Rc<RefCell<TaskData>>>) -> Result<(), NativeError> {
+        self.save_ip();
+        let frames = std::mem::take(&mut self.frames);
+        let stack = std::mem::take(&mut self.stack);
+        let handlers = std::mem::take(&mut self.handlers);
+        let handler_floor = std::mem::replace(&mut self.handler_floor, 0);
+        let current = self.scheduler.current.take();
+        let slice_depth = self.scheduler.slice_depth;
+
+        let result = self.drive_tasks(target);
+
+        self.frames = frames;
+        self.load_frame();
+        self.stack = stack;
+        self.handlers = handlers;
+        self.handler_floor = handler_floor;
+        self.scheduler.current = current;
+        self.scheduler.slice_depth = slice_depth;
+        result
+    }
+
+    fn drive_tasks(&mut self, target: Option<&Rc<RefCell<TaskData>>>) -> Result<(), NativeError> {
+        let finished = |target: Option<&Rc<RefCell<TaskData>>>| target.is_some_and(|t| !t.borrow().is_pending());
+        loop {
+            if finished(target) {
+                return Ok(());
+            }
+            jobs::complete(false);
+
+            let mut progressed = false;
+            for _ in 0..self.scheduler.ready.len() {
+                let Some(context) = self.scheduler.ready.pop_front() else { break };
+                if context.waiting_on.as_ref().is_some_and(|w| w.borrow().is_pending()) {
+                    self.scheduler.ready.push_back(context);
+                    continue;
+                }
+                self.run_slice(context);
+                progressed = true;
+                if finished(target) {
+                    return Ok(());
+                }
+            }
+            if progressed {
+                continue;
+            }
+
+            // Plus rien à exécuter : on attend la prochaine opération d'arrière-plan
+            if jobs::pending_count() > 0 {
+                jobs::complete(true);
+                continue;
+            }
+
+            // Les tâches restantes s'attendent les unes les autres
+            if let Some(target) = target {
+                return Err(format!("Deadlock: task '{}' can never finish", target.borrow().name).into());
+            }
+            for context in self.scheduler.ready.drain(..) {
+                let name = context.task.borrow().name.clone();
+                let message = format!("Deadlock: task '{}' can never finish", name);
+                context.task.borrow_mut().state = TaskState::Failed(Value::String(message.into()));
+                self.scheduler.failed.push(context.task);
+            }
+            return Ok(());
+        }
+    }
+
+    // Exécute une tâche jusqu'à sa fin ou son prochain `await` bloquant
+    fn run_slice(&mut self, context: TaskContext) {
+        self.frames = context.frames;
+        self.load_frame();
+        self.stack = context.stack;
+        self.handlers = context.handlers;
+        self.scheduler.current = Some(context.task.clone());
+        self.scheduler.slice_depth = self.scheduler.sync_depth;
+
+        let outcome = loop {
+            match self.step() {
+                Ok(true) => {
+                    if let Some(waiting_on) = self.scheduler.parked_on.take() {
+                        break SliceOutcome::Parked(waiting_on);
+                    }
+                },
+                Ok(false) => break SliceOutcome::Done(self.stack.pop().unwrap_or(Value::Null)),
+                Err(mut error) => {
+                    // Les `with` et `defer` des frames abandonnées s'exécutent quand même
+                    while let Some(frame) = self.pop_frame() {
+                        if let Err(e) = self.close_frame(frame) {
+                            error = e;
+                        }
+                    }
+                    // L'objet levé (throw, erreur d'une native) est gardé pour le prochain `await`
+                    break SliceOutcome::Failed(error.into_value());
+                },
+            }
+        };
+        self.scheduler.current = None;
+
+        let task = context.task;
+        match outcome {
+            SliceOutcome::Parked(waiting_on) => {
+                self.save_ip();
+                self.scheduler.ready.push_back(TaskContext {
+                    task,
+                    frames: std::mem::take(&mut self.frames),
+                    stack: std::mem::take(&mut self.stack),
+                    handlers: std::mem::take(&mut self.handlers),
+                    waiting_on: Some(waiting_on),
+                });
+            },
+            SliceOutcome::Done(value) => task.borrow_mut().state = TaskState::Done(value),
+            SliceOutcome::Failed(error) => {
+                task.borrow_mut().state = TaskState::Failed(error);
+                self.scheduler.failed.push(task);
+            },
+        }
+        self.frames.clear();
+        self.stack.clear();
+        self.handlers.clear();
+    }
+
+    // Fin du script : les tâches encore en attente terminent, puis les erreurs que personne
+    // n'a attendues sont signalées (elles seraient sinon perdues en silence)
+    pub(super) fn finish_tasks(&mut self) -> Result<(), NativeError> {
+        self.run_tasks_until(None)?;
+        for task in self.scheduler.failed.drain(..) {
+            let task = task.borrow();
+            if let (false, TaskState::Failed(error)) = (task.observed, &task.state) {
+                eprintln!("Unhandled error in task '{}': {}", task.name, error);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+// Contenu des objets mutables atteignables depuis les globales, pour le REPL transactionnel.
+//
+// Remettre les anciennes liaisons globales ne suffit pas : `xs.push(4)` ou `d["k"] = v`
+// modifient sur place une liste déjà liée, et la liaison, elle, n'a pas changé. La photo
+// parcourt donc tout ce qui est atteignable (listes, dicts, instances, bytes, tableaux
+// numériques, environnements des closures et leurs cases, champs statiques) et garde une
+// copie à plat du contenu de chaque objet, avec l'objet lui-même. La restauration réécrit
+// ce contenu dans les mêmes objets : les références partagées (une closure, un module, une
+// autre globale) voient l'état d'avant, et les identités (`===`, Object.id) ne changent pas.
+// Les objets créés par l'évaluation annulée ne sont plus référencés et sont libérés.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::ast::environment::SharedEnv;
+use crate::ast::value::{ClassData, InstanceData};
+use crate::ast::{DictMap, TypedArray, Value};
+
+enum Saved {
+    List(Rc<RefCell<Vec<Value>>>, Vec<Value>),
+    Dict(Rc<RefCell<DictMap>>, DictMap),
+    Instance(Rc<RefCell<InstanceData>>, Vec<Value>, HashMap<String, Value>),
+    Bytes(Rc<RefCell<Vec<u8>>>, Vec<u8>),
+    TypedArray(Rc<RefCell<TypedArray>>, TypedArray),
+    Env(SharedEnv, HashMap<String, Value>),
+    Cell(Rc<RefCell<Value>>, Value),
+    StaticFields(Rc<ClassData>, HashMap<String, Value>),
+}
+
+pub(super) struct HeapSnapshot {
+    saved: Vec<Saved>,
+}
+
+impl HeapSnapshot {
+    pub(super) fn capture<'a>(roots: impl IntoIterator<Item = &'a Value>) -> Self {
+        let mut walk = Walk { seen: HashSet::new(), saved: Vec::new(), pending: roots.into_iter().cloned().collect() };
+        while let Some(value) = walk.pending.pop() {
+            walk.visit(value);
+        }
+        HeapSnapshot { saved: walk.saved }
+    }
+
+    pub(super) fn restore(self) {
+        for saved in self.saved {
+            match saved {
+                Saved::List(rc, items) => *rc.borrow_mut() = items,
+                Saved::Dict(rc, entries) => *rc.borrow_mut() = entries,
+                Saved::Instance(rc, slots, fields) => {
+                    let mut instance = rc.borrow_mut();
+                    instance.slots = slots;
+                    instance.fields = fields;
+                },
+                Saved::Bytes(rc, bytes) => *rc.borrow_mut() = bytes,
+                Saved::TypedArray(rc, array) => *rc.borrow_mut() = array,
+                Saved::Env(rc, variables) => rc.borrow_mut().variables = variables,
+                Saved::Cell(rc, value) => *rc.borrow_mut() = value,
+                Saved::StaticFields(class, fields) => *class.static_fields.borrow_mut() = fields,
+            }
+        }
+    }
+}
+
+struct Walk {
+    // Adresses déjà photographiées : un objet partagé (ou un cycle) n'est copié qu'une fois
+    seen: HashSet<usize>,
+    saved: Vec<Saved>,
+    pending: Vec<Value>,
+}
+
+impl Walk {
+    fn first_visit<T: ?Sized>(&mut self, rc: &Rc<T>) -> bool {
+        self.seen.insert(Rc::as_ptr(rc) as *const () as usize)
+    }
+
+    fn visit(&mut self, value: Value) {
+        match &value {
+            Value::List(rc) if self.first_visit(rc) => {
+                let items = rc.borrow().clone();
+                self.pending.extend(items.iter().cloned());
+                self.saved.push(Saved::List(rc.clone(), items));
+            },
+            Value::Dict(rc) if self.first_visit(rc) => {
+                let entries = rc.borrow().clone();
+                self.pending.extend(entries.values().cloned());
+                self.saved.push(Saved::Dict(rc.clone(), entries));
+            },
+            Value::Instance(rc) if self.first_visit(rc) => {
+                let instance = rc.borrow();
+                self.pending.extend(instance.slots.iter().chain(instance.fields.values()).cloned());
+                self.pending.push(Value::Class(instance.class.clone()));
+                self.saved.push(Saved::Instance(rc.clone(), instance.slots.clone(), instance.fields.clone()));
+            },
+            Value::Bytes(rc) if self.first_visit(rc) => {
+                self.saved.push(Saved::Bytes(rc.clone(), rc.borrow().clone()));
+            },
+            Value::TypedArray(rc) if self.first_visit(rc) => {
+                self.saved.push(Saved::TypedArray(rc.clone(), rc.borrow().clone()));
+            },
+            Value::Cell(rc) if self.first_visit(rc) => {
+                let inner = rc.borrow().clone();
+                self.pending.push(inner.clone());
+                self.saved.push(Saved::Cell(rc.clone(), inner));
+            },
+            Value::Enum(rc) if self.first_visit(rc) => {
+                self.pending.extend(rc.values().cloned());
+            },
+            Value::Function(rc) if self.first_visit(rc) => {
+                if let Some(env) = &rc.env {
+                    self.visit_env(env);
+                }
+            },
+            Value::Class(rc) if self.first_visit(rc) => {
+                let fields = rc.static_fields.borrow().clone();
+                self.pending.extend(fields.values().cloned());
+                self.pending.extend(rc.method_table.values().chain(rc.static_method_table.values()).map(|(m, _)| m.clone()));
+                if let Some(parent) = &rc.parent_ref {
+                    self.pending.push(Value::Class(parent.clone()));
+                }
+                self.saved.push(Saved::StaticFields(rc.clone(), fields));
+            },
+            _ => {},
+        }
+    }
+
+    // Environnement d'une closure (et ses parents) : les variables capturées y vivent
+    fn visit_env(&mut self, env: &SharedEnv) {
+        let mut current = Some(env.clone());
+        while let Some(env) = current {
+            if !self.first_visit(&env) {
+                return;
+            }
+            let variables = env.borrow().variables.clone();
+            self.pending.extend(variables.values().cloned());
+            current = env.borrow().parent.clone();
+            self.saved.push(Saved::Env(env, variables));
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// `aegis run --stats` : nombre d'exécutions de chaque opcode, fonctions les plus chaudes,
+// répartition des accès aux variables, et quelques indices d'optimisation tirés de ces chiffres.
+// `aegis run --profile` y ajoute le temps passé dans chaque opcode.
+//
+// Les compteurs sont rangés par fonction au moment de l'exécution : le coût reste faible
+// (une comparaison de pointeur et deux incréments par instruction), et nul sans --stats.
+// Le chronométrage lit l'horloge deux fois par instruction : il ralentit nettement le script,
+// c'est pourquoi il a son propre drapeau.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::rc::Rc;
+use std::time::Instant;
+
+use super::VM;
+use super::arena::Scratch;
+use crate::ast::Value;
+use crate::opcode::OpCode;
+
+// En dessous, un compteur est trop faible pour justifier un indice
+const HINT_MIN_COUNT: u64 = 10_000;
+// Fonction "courte" : nombre moyen d'instructions par appel en dessous duquel l'appel lui-même pèse
+const SHORT_CALL_OPS: u64 = 8;
+const TOP_FUNCTIONS: usize = 10;
+
+#[derive(Default)]
+struct FunctionStats {
+    name: String,
+    line: usize,
+    calls: u64,
+    ops: u64,
+    free_vars: u64,
+}
+
+pub(super) struct Stats {
+    started: Instant,
+    opcodes: [u64; 256],
+    // --profile seulement
+    timing: Option<Box<Timing>>,
+    functions: Vec<FunctionStats>,
+    // Une entrée par fonction (nom, ligne) : les closures d'une même lambda sont regroupées
+    by_name: HashMap<(String, usize), usize>,
+    // Closure -> entrée, et dernière closure vue (la plupart des instructions se suivent dans la même)
+    by_closure: HashMap<usize, usize>,
+    last: (usize, usize),
+    opt_level: u8,
+    // Compteurs de l'arena (réutilisés, alloués) au moment de enable_stats
+    scratch_start: (u64, u64),
+}
+
+impl Stats {
+    fn new(opt_level: u8) -> Self {
+        Stats {
+            started: Instant::now(),
+            opcodes: [0; 256],
+            timing: None,
+            functions: Vec::new(),
+            by_name: HashMap::new(),
+            by_closure: HashMap::new(),
+            last: (0, 0),
+            opt_level,
+            scratch_start: (0, 0),
+        }
+    }
+
+    fn entry(&mut self, closure: &Value) -> Option<usize> {
+        let Value::Function(function) = closure else { return None };
+        let address = Rc::as_ptr(function) as usize;
+        if self.last.0 == address {
+            return Some(self.last.1);
+        }
+
+        let name = &function.chunk.name;
+        let line = function.chunk.lines.first().copied().unwrap_or(0);
+        // L'adresse d'une closure libérée peut être reprise par une autre fonction
+        let index = match self.by_closure.get(&address) {
+            Some(&index) if self.functions[index].name == *name && self.functions[index].line == line => index,
+            _ => {
+                let index = *self.by_name.entry((name.clone(), line)).or_insert_with(|| {
+                    self.functions.push(FunctionStats { name: name.clone(), line, ..Default::default() });
+                    self.functions.len() - 1
+                });
+                self.by_closure.insert(address, index);
+                index
+            },
+        };
+        self.last = (address, index);
+        Some(index)
+    }
+
+    fn record(&mut self, op: OpCode, closure: &Value) {
+        self.opcodes[op as usize] += 1;
+        let Some(index) = self.entry(closure) else { return };
+        let function = &mut self.functions[index];
+        function.ops += 1;
+        if matches!(op, OpCode::GetFreeVar) {
+            function.free_vars += 1;
+        }
+    }
+
+    // Temps propre de l'instruction : sans celui des instructions exécutées pendant qu'elle
+    // tourne (callback d'un map, constructeur...), qui ont leur propre compteur
+    fn time(&mut self, op: OpCode, (started, outer_nested): (Instant, u64)) {
+        let Some(timing) = self.timing.as_mut() else { return };
+        let elapsed = started.elapsed().as_nanos() as u64;
+        timing.nanos[op as usize] += elapsed.saturating_sub(timing.nested);
+        timing.nested = outer_nested + elapsed;
+    }
+
+    fn call(&mut self, closure: &Value) {
+        if let Some(index) = self.entry(closure) {
+            self.functions[index].calls += 1;
+        }
+    }
+
+    fn report(&self, scratch: &Scratch) -> String {
+        let total: u64 = self.opcodes.iter().sum();
+        let percent = |n: u64| if total == 0 { 0.0 } else { n as f64 * 100.0 / total as f64 };
+        let mut out = String::new();
+        let title = if self.timing.is_some() { "PROFILE" } else { "STATS" };
+        let _ = writeln!(out, "=== {}: {} instructions in {:.3}s ===", title, total, self.started.elapsed().as_secs_f64());
+
+        let mut opcodes: Vec<(OpCode, u64)> = self.opcodes.iter().enumerate()
+            .filter(|(_, count)| **count > 0)
+            // Seuls des octets lus comme opcodes ont un compteur non nul
+            .map(|(byte, count)| (OpCode::from(byte as u8), *count))
+            .collect();
+        opcodes.sort_by(|a, b| b.1.cmp(&a.1).then((a.0 as u8).cmp(&(b.0 as u8))));
+        match &self.timing {
+            None => {
+                let _ = writeln!(out, "\nOpcodes:");
+                for (op, count) in &opcodes {
+                    let _ = writeln!(out, "  {:<18} {:>12}  {:>5.1}%", format!("{:?}", op), count, percent(*count));
+                }
+            },
+            Some(timing) => {
+                let nanos = |op: OpCode| timing.nanos[op as usize];
+                let total_nanos = timing.nanos.iter().sum::<u64>().max(1);
+                opcodes.sort_by(|a, b| nanos(b.0).cmp(&nanos(a.0)).then((a.0 as u8).cmp(&(b.0 as u8))));
+                let _ = writeln!(out, "\nOpcodes (by time spent in the instruction itself):");
+                let _ = writeln!(out, "  {:<18} {:>12}  {:>6}  {:>10}  {:>6}  {:>8}", "", "count", "", "time", "", "per op");
+                for (op, count) in &opcodes {
+                    let _ = writeln!(out, "  {:<18} {:>12}  {:>5.1}%  {:>7.1} ms  {:>5.1}%  {:>5} ns",
+                        format!("{:?}", op), count, percent(*count),
+                        nanos(*op) as f64 / 1e6, nanos(*op) as f64 * 100.0 / total_nanos as f64, nanos(*op) / count);
+                }
+            },
+        }
+
+        let _ = writeln!(out, "\nHottest functions (instructions run in the function itself):");
+        let mut functions: Vec<&FunctionStats> = self.functions.iter().filter(|f| f.ops > 0).collect();
+        functions.sort_by(|a, b| b.ops.cmp(&a.ops).then(a.name.cmp(&b.name)));
+        for f in functions.iter().take(TOP_FUNCTIONS) {
+            let _ = writeln!(out, "  {:<30} {:>12}  {:>5.1}%  calls {}", label(f), f.ops, percent(f.ops), f.calls);
+        }
+
+        let count = |ops: &[OpCode]| ops.iter().map(|op| self.opcodes[*op as usize]).sum::<u64>();
+        let locals = count(&[OpCode::GetLocal, OpCode::SetLocal]);
+        let globals = count(&[OpCode::GetGlobal, OpCode::SetGlobal]);
+        let free = count(&[OpCode::GetFreeVar, OpCode::SetFreeVar]);
+        let accesses = (locals + globals + free).max(1) as f64;
+        let _ = writeln!(out, "\nVariable access: locals {:.1}%, globals {:.1}%, captured {:.1}% ({} accesses)",
+            locals as f64 * 100.0 / accesses, globals as f64 * 100.0 / accesses, free as f64 * 100.0 / accesses,
+            locals + globals + free);
+
+        let reused = scratch.reused - self.scratch_start.0;
+        let built = reused + scratch.allocated - self.scratch_start.1;
+        let _ = writeln!(out, "String temporaries: {} built, {} in recycled buffers ({:.1}%)",
+            built, reused, if built == 0 { 0.0 } else { reused as f64 * 100.0 / built as f64 });
+
+        let hints = self.hints(&functions);
+        let _ = writeln!(out, "\nHints:");
+        if hints.is_empty() {
+            let _ = writeln!(out, "  none");
+        }
+        for hint in hints {
+            let _ = writeln!(out, "  - {}", hint);
+        }
+        out
+    }
+
+    fn hints(&self, functions: &[&FunctionStats]) -> Vec<String> {
+        let mut hints = Vec::new();
+        for f in functions {
+            // Une variable capturée est cherchée par son nom à chaque lecture, un paramètre est lu dans un slot
+            if f.free_vars >= HINT_MIN_COUNT && f.free_vars * 20 >= f.ops {
+                hints.push(format!(
+                    "function {} uses GetFreeVar {} times ({:.0}% of its instructions): consider passing these values as parameters",
+                    label(f), f.free_vars, f.free_vars as f64 * 100.0 / f.ops as f64
+                ));
+            }
+            if f.calls >= HINT_MIN_COUNT && f.ops <= f.calls * SHORT_CALL_OPS {
+                let advice = if self.opt_level >= 2 {
+                    "the call costs about as much as the body: consider inlining it in the hot loop"
+                } else {
+                    "if its body is a single `return <expr>`, -O2 inlines it"
+                };
+                hints.push(format!(
+                    "function {} is called {} times and runs ~{} instructions per call: {}",
+                    label(f), f.calls, f.ops / f.calls, advice
+                ));
+            }
+        }
+        hints
+    }
+}
+
+// Temps par opcode de --profile, en nanosecondes
+struct Timing {
+    nanos: [u64; 256],
+    // Temps des instructions imbriquées dans l'instruction en cours, à retrancher du sien
+    nested: u64,
+}
+
+fn label(f: &FunctionStats) -> String {
+    format!("{} (line {})", f.name, f.line)
+}
+
+impl VM {
+    /// Active le comptage des instructions (`aegis run --stats`). `opt_level` adapte les conseils.
+    pub fn enable_stats(&mut self, opt_level: u8) {
+        let mut stats = Stats::new(opt_level);
+        stats.scratch_start = (self.scratch.reused, self.scratch.allocated);
+        // Le script principal compte pour un appel
+        if let Some(frame) = self.frames.last() {
+            stats.call(&frame.closure);
+        }
+        self.stats = Some(Box::new(stats));
+    }
+
+    /// Comme `enable_stats`, en chronométrant aussi chaque instruction (`aegis run --profile`) :
+    /// le rapport classe les opcodes par temps passé.
+    pub fn enable_profile(&mut self, opt_level: u8) {
+        self.enable_stats(opt_level);
+        if let Some(stats) = self.stats.as_mut() {
+            stats.timing = Some(Box::new(Timing { nanos: [0; 256], nested: 0 }));
+        }
+    }
+
+    /// Rapport des compteurs accumulés depuis `enable_stats`, ou None s'ils sont désactivés.
+    pub fn stats_report(&self) -> Option<String> {
+        self.stats.as_ref().map(|stats| stats.report(&self.scratch))
+    }
+
+    /// Compte l'instruction ; avec --profile, renvoie de quoi la chronométrer (stats_time).
+    pub(super) fn stats_record(&mut self, op: OpCode) -> Option<(Instant, u64)> {
+        let stats = self.stats.as_mut()?;
+        if let Some(frame) = self.frames.last() {
+            stats.record(op, &frame.closure);
+        }
+        let timing = stats.timing.as_mut()?;
+        let outer_nested = std::mem::take(&mut timing.nested);
+        Some((Instant::now(), outer_nested))
+    }
+
+    pub(super) fn stats_time(&mut self, op: OpCode, started: Option<(Instant, u64)>) {
+        if let (Some(stats), Some(started)) = (self.stats.as_mut(), started) {
+            stats.time(op, started);
+        }
+    }
+
+    pub(super) fn stats_call(&mut self, closure: &Value) {
+        if let Some(stats) = self.stats.as_mut() {
+            stats.call(closure);
+        }
+    }
+}
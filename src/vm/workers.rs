@@ -0,0 +1,574 @@
+// Parallel.map : des workers, chacun sur son thread avec sa propre VM.
+//
+// Les valeurs Aegis (Rc, RefCell) ne passent pas d'un thread à l'autre : tout ce qu'un worker
+// reçoit est d'abord copié dans un Packet, fait uniquement de données possédées. Le code
+// voyage ainsi : la fonction appliquée, et une copie des globales (fonctions, namespaces,
+// données) pour qu'elle puisse appeler le reste du script. Les éléments et les résultats
+// sont copiés de la même façon, dans un sens puis dans l'autre.
+//
+// Un Packet est un graphe : une liste, un dict, un environnement ou une fonction partagés (ou
+// qui se référencent, comme les fonctions d'un namespace) sont recréés partagés, pas dupliqués.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::VM;
+use crate::ast::environment::{Environment, SharedEnv};
+use crate::ast::value::FunctionData;
+use crate::ast::{DictMap, TypedArray, Value, ValueKey, Zone};
+use crate::chunk::Chunk;
+use crate::native::{errors, NativeError};
+use crate::vm::gc;
+
+// Au-delà, les données sont refusées plutôt que de faire déborder la pile
+const MAX_DEPTH: usize = 1000;
+
+// Nom de la frame de base d'un worker
+const WORKER_CHUNK: &str = "<worker>";
+
+pub fn register(map: &mut HashMap<String, crate::ast::NativeFn>) {
+    map.insert("parallel_map".to_string(), parallel_map_stub);
+}
+
+// parallel_map a besoin des globales du script : l'appel est intercepté par la VM (call_value)
+fn parallel_map_stub(_args: Vec<Value>) -> Result<Value, NativeError> {
+    Err("parallel_map() can only be called by the VM".into())
+}
+
+// Valeur copiée : scalaire, ou référence à un noeud du Packet
+enum Portable {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Range(i64, i64, i64),
+    Duration(i64),
+    DateTime(i64, Zone),
+    Native(String),
+    ErrorClass(String),
+    Enum(Vec<(String, Portable)>),
+    Node(usize),
+}
+
+// Clé de dict copiée (ValueKey partage ses chaînes internées, propres à chaque thread)
+enum PortableKey {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl PortableKey {
+    fn new(key: &ValueKey) -> Self {
+        match key {
+            ValueKey::String(s) => PortableKey::Str(s.to_string()),
+            ValueKey::Integer(i) => PortableKey::Int(*i),
+            ValueKey::Boolean(b) => PortableKey::Bool(*b),
+        }
+    }
+
+    fn key(&self) -> ValueKey {
+        match self {
+            PortableKey::Str(s) => s.as_str().into(),
+            PortableKey::Int(i) => ValueKey::Integer(*i),
+            PortableKey::Bool(b) => ValueKey::Boolean(*b),
+        }
+    }
+}
+
+enum Node {
+    List(Vec<Portable>),
+    Dict(Vec<(PortableKey, Portable)>),
+    Bytes(Vec<u8>),
+    TypedArray(TypedArray),
+    Function(Box<PortableFunction>),
+    Env { parent: Option<usize>, variables: Vec<(String, Portable)> },
+    // Réservé pendant la copie de son contenu
+    Pending,
+}
+
+struct PortableFunction {
+    params: Vec<(String, Option<String>)>,
+    ret_type: Option<String>,
+    name: String,
+    code: Vec<u8>,
+    constants: Vec<Portable>,
+    locals_map: HashMap<u16, String>,
+    lines: Vec<usize>,
+    annotations: HashMap<usize, String>,
+    captures: HashMap<usize, Vec<(u16, String)>>,
+    free_vars: Vec<String>,
+    is_async: bool,
+    file: Option<String>,
+    env: Option<usize>,
+    doc: Option<String>,
+    variadic: bool,
+    entry_points: Vec<usize>,
+}
+
+struct Packet {
+    nodes: Vec<Node>,
+    roots: Vec<Portable>,
+}
+
+// Copie des valeurs vers un Packet. `lenient` : une valeur impossible à copier (classe,
+// instance, tâche) devient null au lieu d'être une erreur (globales et environnements du code)
+struct Detacher {
+    nodes: Vec<Node>,
+    seen: HashMap<usize, usize>,
+    lenient: bool,
+}
+
+impl Detacher {
+    fn new(lenient: bool) -> Self {
+        Detacher { nodes: Vec::new(), seen: HashMap::new(), lenient }
+    }
+
+    fn finish(self, roots: Vec<Portable>) -> Packet {
+        Packet { nodes: self.nodes, roots }
+    }
+
+    // Noeud déjà copié, ou réservé puis rempli par `fill`
+    fn node<F>(&mut self, address: usize, fill: F) -> Result<Portable, String>
+    where
+        F: FnOnce(&mut Self) -> Result<Node, String>,
+    {
+        if let Some(&id) = self.seen.get(&address) {
+            return Ok(Portable::Node(id));
+        }
+        let id = self.nodes.len();
+        self.nodes.push(Node::Pending);
+        self.seen.insert(address, id);
+        self.nodes[id] = fill(self)?;
+        Ok(Portable::Node(id))
+    }
+
+    fn value(&mut self, value: &Value, depth: usize) -> Result<Portable, String> {
+        if depth > MAX_DEPTH {
+            return if self.lenient { Ok(Portable::Null) } else { Err("value is nested too deeply".into()) };
+        }
+        Ok(match value {
+            Value::Null => Portable::Null,
+            Value::Boolean(b) => Portable::Bool(*b),
+            Value::Integer(i) => Portable::Int(*i),
+            Value::Float(f) => Portable::Float(*f),
+            Value::String(s) => Portable::Str(s.to_string()),
+            Value::Range(start, end, step) => Portable::Range(*start, *end, *step),
+            Value::Duration(ms) => Portable::Duration(*ms),
+            Value::DateTime(ms, zone) => Portable::DateTime(*ms, *zone),
+            Value::Native(name) => Portable::Native(name.clone()),
+            Value::Enum(entries) => {
+                let mut copied = Vec::with_capacity(entries.len());
+                for (key, v) in entries.iter() {
+                    copied.push((key.clone(), self.value(v, depth + 1)?));
+                }
+                Portable::Enum(copied)
+            },
+            Value::Class(class) if errors::class(&class.name).is_some_and(|c| Rc::ptr_eq(&c, class)) => {
+                Portable::ErrorClass(class.name.clone())
+            },
+            Value::List(rc) => self.node(Rc::as_ptr(rc) as *const () as usize, |d| {
+                let items = rc.borrow().clone();
+                let mut copied = Vec::with_capacity(items.len());
+                for item in &items {
+                    copied.push(d.value(item, depth + 1)?);
+                }
+                Ok(Node::List(copied))
+            })?,
+            Value::Dict(rc) => self.node(Rc::as_ptr(rc) as *const () as usize, |d| {
+                let entries: Vec<(ValueKey, Value)> = rc.borrow().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                let mut copied = Vec::with_capacity(entries.len());
+                for (key, v) in &entries {
+                    copied.push((PortableKey::new(key), d.value(v, depth + 1)?));
+                }
+                Ok(Node::Dict(copied))
+            })?,
+            Value::Bytes(rc) => self.node(Rc::as_ptr(rc) as *const () as usize, |_| Ok(Node::Bytes(rc.borrow().clone())))?,
+            Value::TypedArray(rc) => self.node(Rc::as_ptr(rc) as *const () as usize, |_| Ok(Node::TypedArray(rc.borrow().clone())))?,
+            Value::Function(rc) => self.node(Rc::as_ptr(rc) as *const () as usize, |d| {
+                let chunk = &rc.chunk;
+                let mut constants = Vec::with_capacity(chunk.constants.len());
+                for constant in &chunk.constants {
+                    constants.push(d.value(constant, depth + 1)?);
+                }
+                let env = match &rc.env {
+                    Some(env) => Some(d.env(env, depth + 1)?),
+                    None => None,
+                };
+                Ok(Node::Function(Box::new(PortableFunction {
+                    params: rc.params.clone(),
+                    ret_type: rc.ret_type.clone(),
+                    name: chunk.name.clone(),
+                    code: chunk.code.clone(),
+                    constants,
+                    locals_map: chunk.locals_map.clone(),
+                    lines: chunk.lines.clone(),
+                    annotations: chunk.annotations.clone(),
+                    captures: chunk.captures.clone(),
+                    free_vars: chunk.free_vars.clone(),
+                    is_async: chunk.is_async,
+                    file: chunk.file.as_deref().map(str::to_string),
+                    env,
+                    doc: rc.doc.clone(),
+                    variadic: rc.variadic,
+                    entry_points: rc.entry_points.clone(),
+                })))
+            })?,
+            // Le worker reçoit une copie : ses affectations ne reviennent pas dans ce thread
+            Value::Cell(cell) => {
+                let value = cell.borrow().clone();
+                self.value(&value, depth)?
+            },
+            _ if self.lenient => Portable::Null,
+            other => return Err(format!("{} values cannot be sent between workers", other.type_name())),
+        })
+    }
+
+    fn env(&mut self, env: &SharedEnv, depth: usize) -> Result<usize, String> {
+        let copied = self.node(Rc::as_ptr(env) as *const () as usize, |d| {
+            let (parent, variables): (Option<SharedEnv>, Vec<(String, Value)>) = {
+                let env = env.borrow();
+                (env.parent.clone(), env.variables.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            };
+            let parent = match &parent {
+                Some(parent) => Some(d.env(parent, depth + 1)?),
+                None => None,
+            };
+            let mut copied = Vec::with_capacity(variables.len());
+            for (name, v) in &variables {
+                copied.push((name.clone(), d.value(v, depth + 1)?));
+            }
+            Ok(Node::Env { parent, variables: copied })
+        })?;
+        match copied {
+            Portable::Node(id) => Ok(id),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn detach(values: &[Value], lenient: bool) -> Result<Packet, String> {
+    let mut detacher = Detacher::new(lenient);
+    let mut roots = Vec::with_capacity(values.len());
+    for value in values {
+        roots.push(detacher.value(value, 0)?);
+    }
+    Ok(detacher.finish(roots))
+}
+
+// Reconstruction des valeurs d'un Packet sur le thread qui le reçoit
+enum Built {
+    Value(Value),
+    Env(SharedEnv),
+}
+
+impl Packet {
+    fn attach(&self) -> Vec<Value> {
+        // 1. Conteneurs et environnements vides : les cycles pourront s'y refermer
+        let mut built: Vec<Option<Built>> = self.nodes.iter().map(|node| match node {
+            Node::List(_) => Some(Built::Value(Value::List(Rc::new(RefCell::new(Vec::new()))))),
+            Node::Dict(_) => Some(Built::Value(Value::Dict(Rc::new(RefCell::new(DictMap::new()))))),
+            Node::Bytes(bytes) => Some(Built::Value(Value::Bytes(Rc::new(RefCell::new(bytes.clone()))))),
+            Node::TypedArray(array) => Some(Built::Value(Value::TypedArray(Rc::new(RefCell::new(array.clone()))))),
+            Node::Env { .. } => Some(Built::Env(Environment::new_global())),
+            Node::Function(_) | Node::Pending => None,
+        }).collect();
+
+        // 2. Fonctions : elles ne dépendent que d'environnements (déjà créés) et de leurs
+        // constantes, où une fonction ne peut pas se contenir elle-même
+        for id in 0..self.nodes.len() {
+            self.function(id, &mut built);
+        }
+
+        // 3. Contenu des conteneurs et des environnements
+        for (id, node) in self.nodes.iter().enumerate() {
+            match node {
+                Node::List(items) => {
+                    let values: Vec<Value> = items.iter().map(|item| self.portable(item, &mut built)).collect();
+                    if let Some(Built::Value(Value::List(rc))) = &built[id] {
+                        *rc.borrow_mut() = values;
+                    }
+                },
+                Node::Dict(entries) => {
+                    let values: DictMap = entries.iter().map(|(k, v)| (k.key(), self.portable(v, &mut built))).collect();
+                    if let Some(Built::Value(Value::Dict(rc))) = &built[id] {
+                        *rc.borrow_mut() = values;
+                    }
+                },
+                Node::Env { parent, variables } => {
+                    let variables: HashMap<String, Value> = variables.iter().map(|(k, v)| (k.clone(), self.portable(v, &mut built))).collect();
+                    let parent = parent.and_then(|p| match &built[p] {
+                        Some(Built::Env(parent)) => Some(parent.clone()),
+                        _ => None,
+                    });
+                    if let Some(Built::Env(env)) = &built[id] {
+                        let mut env = env.borrow_mut();
+                        env.parent = parent;
+                        env.variables = variables;
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        for value in built.iter().flatten() {
+            if let Built::Value(value) = value {
+                gc::track(value);
+            }
+        }
+        self.roots.iter().map(|root| self.portable(root, &mut built)).collect()
+    }
+
+    fn function(&self, id: usize, built: &mut Vec<Option<Built>>) -> Value {
+        if let Some(Built::Value(value)) = &built[id] {
+            return value.clone();
+        }
+        let Node::Function(f) = &self.nodes[id] else { return Value::Null };
+        let constants = f.constants.iter().map(|c| self.portable(c, built)).collect();
+        let env = f.env.and_then(|e| match &built[e] {
+            Some(Built::Env(env)) => Some(env.clone()),
+            _ => None,
+        });
+
+        let mut chunk = Chunk::new();
+        chunk.name = f.name.clone();
+        chunk.code = f.code.clone();
+        chunk.constants = constants;
+        chunk.locals_map = f.locals_map.clone();
+        chunk.lines = f.lines.clone();
+        chunk.annotations = f.annotations.clone();
+        chunk.captures = f.captures.clone();
+        chunk.free_vars = f.free_vars.clone();
+        chunk.is_async = f.is_async;
+        chunk.file = f.file.as_deref().map(Rc::from);
+
+        let value = Value::Function(Rc::new(FunctionData {
+            params: f.params.clone(),
+            ret_type: f.ret_type.clone(),
+            chunk,
+            env,
+            doc: f.doc.clone(),
+            variadic: f.variadic,
+            entry_points: f.entry_points.clone(),
+        }));
+        built[id] = Some(Built::Value(value.clone()));
+        value
+    }
+
+    fn portable(&self, value: &Portable, built: &mut Vec<Option<Built>>) -> Value {
+        match value {
+            Portable::Null => Value::Null,
+            Portable::Bool(b) => Value::Boolean(*b),
+            Portable::Int(i) => Value::Integer(*i),
+            Portable::Float(f) => Value::Float(*f),
+            Portable::Str(s) => Value::String(s.clone().into()),
+            Portable::Range(start, end, step) => Value::Range(*start, *end, *step),
+            Portable::Duration(ms) => Value::Duration(*ms),
+            Portable::DateTime(ms, zone) => Value::DateTime(*ms, *zone),
+            Portable::Native(name) => Value::Native(name.clone()),
+            Portable::ErrorClass(name) => errors::class(name).map(Value::Class).unwrap_or(Value::Null),
+            Portable::Enum(entries) => {
+                Value::Enum(Rc::new(entries.iter().map(|(k, v)| (k.clone(), self.portable(v, built))).collect()))
+            },
+            Portable::Node(id) => match &built[*id] {
+                Some(Built::Value(value)) => value.clone(),
+                Some(Built::Env(_)) => Value::Null,
+                None => self.function(*id, built),
+            },
+        }
+    }
+}
+
+// Le code envoyé à chaque worker : la fonction, puis les globales du script (slot, valeur)
+struct Program {
+    names: HashMap<String, u16>,
+    slots: Vec<usize>,
+    packet: Packet,
+}
+
+// Erreur d'un élément, renvoyée par le worker qui l'a traité
+struct Failure {
+    index: usize,
+    kind: String,
+    message: String,
+    data: Option<Packet>,
+    stack: Vec<String>,
+}
+
+type WorkerResult = Result<Vec<Packet>, Box<Failure>>;
+
+impl VM {
+    // parallel_map(list, fn, options) : découpe la liste en autant de parts que de workers,
+    // applique fn à chaque élément sur un thread, puis rassemble les résultats dans l'ordre
+    pub(super) fn parallel_map(&mut self, args: Vec<Value>) -> Result<Value, NativeError> {
+        let (list, function, options) = match args.as_slice() {
+            [list, function] => (list, function, Value::Null),
+            [list, function, options] => (list, function, options.clone()),
+            _ => return Err("Usage: Parallel.map(list, fn, options)".into()),
+        };
+        let items = match list {
+            Value::List(items) => items.borrow().clone(),
+            other => return Err(format!("Parallel.map() expects a list, got {}", other.type_name()).into()),
+        };
+        if !matches!(function, Value::Function(_) | Value::Native(_)) {
+            return Err(format!("Parallel.map() expects a function, got {}", function.type_name()).into());
+        }
+        let workers = parallel_workers(&options)?.min(items.len()).max(1);
+        if items.is_empty() {
+            return Ok(gc::list(Vec::new()));
+        }
+
+        // Éléments : une erreur de copie désigne l'élément fautif
+        let mut parcels = Vec::with_capacity(items.len());
+        for (index, item) in items.iter().enumerate() {
+            let packet = detach(std::slice::from_ref(item), false)
+                .map_err(|e| format!("Parallel.map: item {}: {}", index, e))?;
+            parcels.push((index, packet));
+        }
+
+        let program = Arc::new(self.program(function));
+        let size = items.len().div_ceil(workers);
+        let mut handles = Vec::with_capacity(workers);
+        while !parcels.is_empty() {
+            let rest = parcels.split_off(size.min(parcels.len()));
+            let share = std::mem::replace(&mut parcels, rest);
+            let program = program.clone();
+            handles.push(std::thread::spawn(move || run_worker(&program, share)));
+        }
+
+        let mut results = Vec::with_capacity(items.len());
+        let mut failure: Option<Box<Failure>> = None;
+        for handle in handles {
+            match handle.join() {
+                Ok(Ok(packets)) => results.extend(packets),
+                Ok(Err(error)) => {
+                    if failure.as_ref().is_none_or(|f| error.index < f.index) {
+                        failure = Some(error);
+                    }
+                },
+                Err(_) => return Err("Parallel.map: a worker panicked".into()),
+            }
+        }
+
+        if let Some(failure) = failure {
+            let mut extra = vec![("index", Value::Integer(failure.index as i64))];
+            if !failure.stack.is_empty() {
+                extra.push(("stack", gc::list(failure.stack.into_iter().map(|s| Value::String(s.into())).collect())));
+            }
+            let data = failure.data.and_then(|p| p.attach().pop()).unwrap_or(Value::Null);
+            let message = format!("item {}: {}", failure.index, failure.message);
+            return Err(self.raise(errors::new_error(&failure.kind, &message, data, extra)));
+        }
+        let values = results.iter().map(|packet| packet.attach().pop().unwrap_or(Value::Null)).collect();
+        Ok(gc::list(values))
+    }
+
+    // Copie de la fonction et des globales du script, partagée par tous les workers
+    fn program(&self, function: &Value) -> Program {
+        let names = self.global_names.borrow().clone();
+        let mut slots = Vec::new();
+        let mut values = vec![function.clone()];
+        for (slot, value) in self.globals.iter().enumerate() {
+            if !matches!(value, Value::Null | Value::Native(_)) {
+                slots.push(slot);
+                values.push(value.clone());
+            }
+        }
+        // Mode permissif : la copie ne peut pas échouer
+        let packet = detach(&values, true).unwrap_or(Packet { nodes: Vec::new(), roots: Vec::new() });
+        Program { names, slots, packet }
+    }
+
+    // Valeur levée par un élément -> erreur à renvoyer au thread principal
+    fn failure(&self, index: usize, error: NativeError) -> Box<Failure> {
+        let error = error.into_value();
+        let lenient = |value: Option<Value>| value
+            .filter(|v| !matches!(v, Value::Null))
+            .and_then(|v| detach(&[v], true).ok());
+
+        if let Value::Instance(instance) = &error {
+            let instance = instance.borrow();
+            if errors::is_error_class(&instance.class) {
+                // Classe d'erreur intégrée la plus proche (ParseError extends ValueError -> ValueError)
+                let kind = std::iter::once(&instance.class).chain(instance.class.ancestors.iter())
+                    .find(|c| errors::class(&c.name).is_some_and(|builtin| Rc::ptr_eq(&builtin, c)))
+                    .map(|c| c.name.clone())
+                    .unwrap_or_else(|| "Error".to_string());
+                let stack = match instance.get_field("stack").as_ref() {
+                    // Sans la frame de base du worker, qui n'exécute rien
+                    Some(Value::List(entries)) => entries.borrow().iter()
+                        .map(|e| e.to_string())
+                        .filter(|e| !e.starts_with(WORKER_CHUNK))
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                return Box::new(Failure {
+                    index,
+                    kind,
+                    message: instance.get_field("message").map(|m| m.to_string()).unwrap_or_default(),
+                    data: lenient(instance.get_field("data")),
+                    stack,
+                });
+            }
+        }
+
+        let (message, data) = match &error {
+            Value::String(s) => (s.to_string(), None),
+            other => (other.to_string(), lenient(Some(other.clone()))),
+        };
+        Box::new(Failure { index, kind: "RuntimeError".to_string(), message, data, stack: Vec::new() })
+    }
+}
+
+fn parallel_workers(options: &Value) -> Result<usize, String> {
+    let default = || std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let Value::Dict(options) = options else {
+        return match options {
+            Value::Null => Ok(default()),
+            other => Err(format!("Parallel.map() expects options as a dict, got {}", other.type_name())),
+        };
+    };
+    if let Some(key) = options.borrow().keys().find(|k| k.as_str() != Some("workers")) {
+        return Err(format!("Parallel.map: unknown option '{}'", key));
+    }
+    match options.borrow().get("workers") {
+        None | Some(Value::Null) => Ok(default()),
+        Some(Value::Integer(n)) if *n >= 1 => Ok(*n as usize),
+        Some(other) => Err(format!("Parallel.map: 'workers' must be a positive integer, got {}", other)),
+    }
+}
+
+// Corps d'un worker : une VM neuve, avec les globales du script, qui traite sa part
+fn run_worker(program: &Program, share: Vec<(usize, Packet)>) -> WorkerResult {
+    let mut main = Chunk::new();
+    main.name = WORKER_CHUNK.to_string();
+    let mut vm = VM::new(main, Rc::new(RefCell::new(program.names.clone())), Vec::new());
+    let mut values = program.packet.attach().into_iter();
+    let function = values.next().unwrap_or(Value::Null);
+    for (slot, value) in program.slots.iter().zip(values) {
+        vm.globals[*slot] = value;
+    }
+
+    let mut results = Vec::with_capacity(share.len());
+    for (index, packet) in share {
+        let item = packet.attach().pop().unwrap_or(Value::Null);
+        let result = vm.run_callable_sync(function.clone(), vec![item], None)
+            .and_then(|value| match &value {
+                // Une fonction `async` : le worker attend sa tâche
+                Value::Task(task) => vm.resolve_task(&task.clone()),
+                _ => Ok(value),
+            });
+        let value = result.map_err(|error| vm.failure(index, error))?;
+        let packet = detach(&[value], false).map_err(|message| Box::new(Failure {
+            index,
+            kind: "TypeError".to_string(),
+            message: format!("result: {}", message),
+            data: None,
+            stack: Vec::new(),
+        }))?;
+        results.push(packet);
+    }
+    Ok(results)
+}
@@ -0,0 +1,66 @@
+// async func / await : opérations réseau d'arrière-plan et erreurs des tâches.
+
+mod common;
+use common::start;
+
+fn run(source: &str) -> Result<(), String> {
+    common::load(source).run()
+}
+
+#[test]
+fn server_and_client_tasks_share_the_thread() {
+    // Le serveur attend sa connexion sans bloquer le client qui doit la faire
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+
+    let mut vm = start(&format!(r#"
+import "stdlib/async.aeg"
+import "stdlib/socket.aeg"
+var server = Socket.listen("127.0.0.1", {port})
+
+async func serve() {{
+    var client = await Socket.accept_async(server)
+    var msg = await Socket.read_async(client, 64)
+    Socket.write(client, "echo: " + msg)
+    Socket.close(client)
+    return "served"
+}}
+
+async func ask() {{
+    var conn = Socket.handle(await Socket.connect_async("127.0.0.1", {port}))
+    conn.write("ping")
+    var reply = await conn.read_async(64)
+    conn.close()
+    return reply
+}}
+
+async func main() {{
+    return Async.all([serve(), ask()])
+}}
+"#));
+    let results = vm.call_global("main", vec![]).expect("échange réseau");
+    assert_eq!(results.to_string(), "[served, echo: ping]");
+}
+
+#[test]
+fn socket_errors_reach_the_caller() {
+    let mut vm = start("import \"stdlib/socket.aeg\"\nfunc read_missing() { return await Socket.read_async(999999, 8) }\n");
+    let err = vm.call_global("read_missing", vec![]).expect_err("ID inconnu");
+    assert!(err.contains("Invalid Stream ID"), "{}", err);
+
+    let mut vm = start("import \"stdlib/socket.aeg\"\nfunc refused() { return await Socket.connect_async(\"127.0.0.1\", 1) }\n");
+    assert!(vm.call_global("refused", vec![]).is_err());
+}
+
+#[test]
+fn tasks_waiting_on_each_other_deadlock() {
+    let err = run("var t = null\nasync func first() { return await t }\nt = first()\nprint await t\n")
+        .expect_err("la tâche s'attend elle-même");
+    assert!(err.contains("Deadlock: task 'first' can never finish"), "{}", err);
+}
+
+#[test]
+fn unobserved_task_errors_do_not_fail_the_script() {
+    run("async func fail() { throw \"ignored\" }\nfail()\n").expect("erreur signalée mais non fatale");
+}
@@ -0,0 +1,47 @@
+// `aegis bench` mesure les programmes de benchmarks/ et tient l'historique JSON.
+
+use std::path::PathBuf;
+
+mod common;
+use common::aegis;
+
+fn workspace(name: &str) -> PathBuf {
+    let dir = common::project(name, &[]);
+    std::fs::create_dir_all(dir.join("benchmarks")).unwrap();
+    dir
+}
+
+#[test]
+fn saved_runs_are_appended_to_the_history() {
+    let dir = workspace("history");
+    std::fs::write(dir.join("benchmarks/sum.aeg"), "var total = 0\nforeach (i in 0..1000) { total = total + i }\nprint 42\n").unwrap();
+    // Les sous-dossiers ne sont pas parcourus
+    std::fs::create_dir_all(dir.join("benchmarks/legacy")).unwrap();
+    std::fs::write(dir.join("benchmarks/legacy/old.aeg"), "throw \"not a benchmark\"\n").unwrap();
+
+    let output = aegis(&dir, &["bench", "-n", "1", "--no-compare", "--save"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("sum"), "{}", stdout);
+    assert!(!stdout.contains("old"), "{}", stdout);
+
+    let output = aegis(&dir, &["bench", "-n", "1", "--no-compare", "--save"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("vs last: run of"));
+
+    let history = aegis_core::bench::load_history(&dir.join("benchmarks/history.json")).unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history[1].results["sum"]["aegis"] > 0.0);
+    assert_eq!(history[1].iterations, 1);
+}
+
+#[test]
+fn a_failing_program_stops_the_run() {
+    let dir = workspace("failing");
+    std::fs::write(dir.join("benchmarks/broken.aeg"), "throw \"boom\"\n").unwrap();
+
+    let output = aegis(&dir, &["bench", "-n", "1", "--no-compare", "--save"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("broken"));
+    assert!(!dir.join("benchmarks/history.json").exists());
+}
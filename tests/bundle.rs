@@ -0,0 +1,109 @@
+// `aegis bundle` : un script et ses imports dans un fichier .aegb, lancé par `aegis run`
+// depuis un autre dossier. Le profil fixe l'optimisation, la table des lignes, la
+// compression et la stdlib embarquée.
+
+use std::path::Path;
+use std::process::Output;
+
+mod common;
+use common::{aegis, project};
+
+const MAIN: &str = "import \"stdlib/path.aeg\"\nimport \"lib/helpers\" as helpers\nimport \"config.json\" as config\n\
+print helpers.double(config.value)\nprint Path.extension(\"a/b.txt\")\n\
+func boom() {\n    return helpers.fail()\n}\nboom()\n";
+const HELPERS: &str = "func double(x) { return x * 2 }\nfunc fail() {\n    throw \"bad\"\n}\n";
+
+// Lance le bundle dans un dossier vide : les sources ne sont plus là
+fn run_elsewhere(dir: &Path, bundle: &str) -> Output {
+    let elsewhere = dir.join("elsewhere");
+    std::fs::create_dir_all(&elsewhere).unwrap();
+    std::fs::copy(dir.join(bundle), elsewhere.join(bundle)).unwrap();
+    aegis(&elsewhere, &["run", bundle])
+}
+
+#[test]
+fn release_bundles_run_without_sources_or_line_tables() {
+    let dir = project("release", &[
+        ("main.aeg", MAIN),
+        ("lib/helpers.aeg", HELPERS),
+        ("config.json", "{\"value\": 21}"),
+    ]);
+    let output = aegis(&dir, &["bundle", "main.aeg", "--profile", "release"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("4 modules, profile release"));
+
+    // Compressé : les sources n'apparaissent pas en clair
+    let bytes = std::fs::read(dir.join("main.aegb")).unwrap();
+    assert!(!String::from_utf8_lossy(&bytes).contains("func double"));
+
+    let output = run_elsewhere(&dir, "main.aegb");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42\ntxt\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error: bad"), "{}", stderr);
+    assert!(!stderr.contains("[Line"), "{}", stderr);
+    assert!(stderr.contains("at fail (no debug info)"), "{}", stderr);
+    assert!(!stderr.contains("helpers.aeg"), "{}", stderr);
+}
+
+#[test]
+fn debug_bundles_keep_file_and_line_in_stack_traces() {
+    let dir = project("debug", &[
+        ("main.aeg", MAIN),
+        ("lib/helpers.aeg", HELPERS),
+        ("config.json", "{\"value\": 21}"),
+    ]);
+    let output = aegis(&dir, &["bundle", "main.aeg", "-o", "app.aegb"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("profile debug"));
+
+    // Non compressé, avec toute la stdlib
+    let text = String::from_utf8_lossy(&std::fs::read(dir.join("app.aegb")).unwrap()).to_string();
+    assert!(text.contains("func double") && text.contains("stdlib/json.aeg"));
+
+    let output = run_elsewhere(&dir, "app.aegb");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42\ntxt\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[Line 3] Error: bad"), "{}", stderr);
+    assert!(stderr.contains("at fail (lib/helpers.aeg:3)"), "{}", stderr);
+    assert!(stderr.contains("at boom (main.aeg:7)"), "{}", stderr);
+}
+
+#[test]
+fn manifest_profiles_override_and_extend_the_built_in_ones() {
+    let dir = project("manifest", &[
+        ("aegis.toml", "[project]\nname = \"app\"\nentry = \"main.aeg\"\n\n\
+[profile.release]\ncompress = false\n\n[profile.staging]\ninherits = \"release\"\ndebug-info = true\n"),
+        ("main.aeg", MAIN),
+        ("lib/helpers.aeg", HELPERS),
+        ("config.json", "{\"value\": 21}"),
+    ]);
+    let output = aegis(&dir, &["bundle", "--profile", "release", "-o", "release.aegb"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let text = String::from_utf8_lossy(&std::fs::read(dir.join("release.aegb")).unwrap()).to_string();
+    assert!(text.contains("func double") && !text.contains("stdlib/json.aeg"));
+
+    let output = aegis(&dir, &["bundle", "--profile", "staging"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = run_elsewhere(&dir, "main.aegb");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("at fail (lib/helpers.aeg:3)"), "{}", stderr);
+
+    let output = aegis(&dir, &["bundle", "--profile", "fast"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown profile 'fast'"));
+}
+
+#[test]
+fn missing_modules_and_foreign_files_are_rejected() {
+    let dir = project("invalid", &[
+        ("main.aeg", "import \"nope\"\n"),
+        ("fake.aegb", "print 1\n"),
+    ]);
+    let output = aegis(&dir, &["bundle", "main.aeg"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("module 'nope' not found"));
+
+    let output = aegis(&dir, &["run", "fake.aegb"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not an Aegis bundle"));
+}
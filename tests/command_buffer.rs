@@ -0,0 +1,85 @@
+// API des plugins pour les appels groupés : une native reçoit tout un CommandBuffer
+// (Args::want_commands) et des flottants 32 bits (Args::want_f32s) en un seul appel.
+// Un tableau typé (Args::want_array) est partagé : la native l'écrit en place.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use aegis_core::{Args, NativeError, NativeFn, TypedArray, Value};
+
+mod common;
+
+// Ce que le faux plugin graphique a reçu, un élément par commande
+static DRAWN: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn gfx_submit(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("gfx_submit(commands)", &args);
+    let commands = args.want_commands(0)?;
+    let mut drawn = Vec::new();
+    commands.for_each(|name, params| {
+        let params = Args::new("gfx command", params);
+        match name {
+            "rect" => drawn.push(format!("rect {} {}", params.want_int(0)?, params.want_int(1)?)),
+            "mesh" => drawn.push(format!("mesh {:?}", params.want_f32s(0)?)),
+            other => return Err(format!("unknown command '{}'", other)),
+        }
+        Ok(())
+    })?;
+    let count = drawn.len();
+    DRAWN.lock().unwrap().extend(drawn);
+    Ok(Value::Integer(count as i64))
+}
+
+// Remplit le tableau avec 0, 1, 2... comme une lecture de pixels
+fn gfx_read(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("gfx_read(target)", &args);
+    match &mut *args.want_array(0)?.borrow_mut() {
+        TypedArray::Float32(items) => items.iter_mut().enumerate().for_each(|(i, f)| *f = i as f32),
+        TypedArray::Int(_) => return Err(args.error("target must be a Float32Array").into()),
+    }
+    Ok(Value::Null)
+}
+
+fn run(source: &str) -> Result<(), String> {
+    aegis_core::native::init_registry();
+    let plugin: HashMap<String, NativeFn> = HashMap::from([
+        ("gfx_submit".to_string(), gfx_submit as NativeFn),
+        ("gfx_read".to_string(), gfx_read as NativeFn),
+    ]);
+    aegis_core::native::extend_registry(plugin, HashMap::new());
+
+    common::load(source).run().map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[test]
+fn a_plugin_receives_the_whole_buffer_in_one_call() {
+    run(r#"
+var frame = new CommandBuffer()
+frame.push("rect", 1, 2).push("mesh", [0, 0.5, 1].to_f32_bytes()).push("mesh", [2, -1])
+if (gfx_submit(frame) != 3) { throw "wrong count" }
+"#).unwrap();
+
+    let drawn = DRAWN.lock().unwrap();
+    assert!(drawn.windows(3).any(|w| w == ["rect 1 2", "mesh [0.0, 0.5, 1.0]", "mesh [2.0, -1.0]"]), "{:?}", drawn);
+}
+
+#[test]
+fn plugin_errors_name_the_expected_argument() {
+    let error = run("gfx_submit([1, 2])").unwrap_err();
+    assert!(error.contains("gfx_submit(commands): argument 'commands' must be a CommandBuffer, got list"), "{}", error);
+
+    let error = run("gfx_submit(new CommandBuffer().push(\"mesh\", \"abcde\".to_bytes()))").unwrap_err();
+    assert!(error.contains("has 5 bytes, which is not a whole number of 4-byte floats"), "{}", error);
+}
+
+#[test]
+fn a_plugin_writes_into_a_typed_array_in_place() {
+    run(r#"
+var pixels = float32array_zeros(3)
+gfx_read(pixels)
+if (pixels.to_list() != [0.0, 1.0, 2.0]) { throw "not written: " + pixels }
+"#).unwrap();
+
+    let error = run("gfx_read([0, 0])").unwrap_err();
+    assert!(error.contains("gfx_read(target): argument 'target' must be a Float32Array or an IntArray, got list"), "{}", error);
+}
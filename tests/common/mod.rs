@@ -0,0 +1,104 @@
+// Aides partagées par les tests d'intégration. Chaque fichier de tests/ est son propre crate :
+// il les inclut avec `mod common;` et n'en utilise qu'une partie.
+//  - compilation et chargement d'un script dans une VM, pour les tests qui la pilotent ;
+//  - dossiers temporaires et lancement de l'exécutable `aegis`, pour les tests de la CLI.
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use aegis_core::ast::Statement;
+use aegis_core::chunk::Chunk;
+use aegis_core::vm::VM;
+use aegis_core::vm::compiler::Compiler;
+
+pub fn statements(source: &str) -> Vec<Statement> {
+    let json = aegis_core::compiler::compile(source).expect("erreur de syntaxe");
+    aegis_core::loader::parse_block(&json).expect("erreur du loader")
+}
+
+pub fn compile(source: &str) -> Chunk {
+    Compiler::new().compile(statements(source)).expect("erreur de compilation").0
+}
+
+/// VM prête à exécuter `source` (run() n'a pas encore été appelé).
+pub fn load(source: &str) -> VM {
+    let (chunk, globals) = Compiler::new().compile(statements(source)).expect("erreur de compilation");
+    VM::new(chunk, globals, vec![])
+}
+
+/// VM qui a exécuté le niveau principal de `source` : ses globales et tâches restent disponibles.
+pub fn start(source: &str) -> VM {
+    let mut vm = load(source);
+    vm.run().expect("erreur à l'exécution");
+    vm
+}
+
+// Chemin temporaire propre au fichier de tests, au test (`name`) et au processus
+fn temp_path(name: &str, extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("aegis_{}_{}_{}{}", env!("CARGO_CRATE_NAME"), name, std::process::id(), extension))
+}
+
+/// Dossier temporaire vidé puis rempli de `files` (chemin relatif, contenu).
+pub fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = temp_path(name, "");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (path, content) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+/// L'exécutable `aegis` lancé dans `dir`, à compléter (arguments, variables d'environnement).
+pub fn aegis_command(dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_aegis"));
+    command.current_dir(dir);
+    command
+}
+
+/// `aegis <args>` dans `dir`.
+pub fn aegis(dir: &Path, args: &[&str]) -> Output {
+    aegis_command(dir).args(args).output().expect("impossible de lancer aegis")
+}
+
+/// `aegis <args>` dans `dir`, avec `input` sur l'entrée standard (REPL, invite du débogueur).
+pub fn aegis_with_input(dir: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = aegis_command(dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("impossible de lancer aegis");
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Fichier .aeg temporaire contenant `source` (à supprimer par l'appelant).
+pub fn temp_script(name: &str, source: &str) -> PathBuf {
+    let path = temp_path(name, ".aeg");
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+/// Écrit `source` dans un fichier .aeg temporaire et lance `aegis <args> <fichier>` depuis la
+/// racine du dépôt (comme `cargo test`).
+pub fn aegis_script(name: &str, args: &[&str], source: &str) -> Output {
+    let path = temp_script(name, source);
+    let output = aegis_command(Path::new(env!("CARGO_MANIFEST_DIR")))
+        .args(args)
+        .arg(&path)
+        .output()
+        .expect("impossible de lancer aegis");
+    std::fs::remove_file(&path).ok();
+    output
+}
+
+/// `aegis run` sur `source`.
+pub fn run_script(name: &str, source: &str) -> Output {
+    aegis_script(name, &["run"], source)
+}
@@ -0,0 +1,60 @@
+// Programmes syntaxiquement valides mais refusés par le compilateur de bytecode :
+// on attend une CompileError avec la ligne de l'instruction fautive, pas un panic.
+
+use aegis_core::vm::compiler::{CompileError, Compiler};
+
+mod common;
+use common::statements;
+
+fn compile_error(source: &str) -> CompileError {
+    Compiler::new().compile(statements(source)).map(|_| ()).expect_err("la compilation aurait dû échouer")
+}
+
+fn assert_error(source: &str, message: &str, line: usize) {
+    let err = compile_error(source);
+    assert_eq!(err, CompileError { message: message.to_string(), line }, "source: {:?}", source);
+}
+
+#[test]
+fn const_reassignment() {
+    assert_error("const PI = 3.14\nprint PI\nPI = 3", "Cannot reassign constant 'PI'", 3);
+}
+
+#[test]
+fn captured_const_reassignment() {
+    assert_error("func f() {\n  const k = 1\n  var g = func() {\n    k = 2\n  }\n}", "Cannot reassign local constant 'k'", 4);
+}
+
+#[test]
+fn overlapping_constructors() {
+    assert_error(
+        "print 1\nclass P {\n  init(a) { this.a = a }\n  init(a, b = 0) { this.a = a + b }\n}",
+        "Constructors of 'P' overlap: more than one accepts 1 arguments",
+        2,
+    );
+}
+
+#[test]
+fn break_outside_loop() {
+    assert_error("print 1\nbreak", "'break' used outside of a loop", 2);
+}
+
+#[test]
+fn continue_outside_loop_in_nested_block() {
+    assert_error("func f() {\n  if (true) {\n    continue\n  }\n}", "'continue' used outside of a loop", 3);
+}
+
+#[test]
+fn super_outside_subclass() {
+    assert_error("class A {\n  foo() {\n    return super.foo()\n  }\n}", "'super' used outside of a subclass method", 3);
+}
+
+#[test]
+fn break_in_switch_outside_loop() {
+    assert_error("switch (1) {\n  case 1:\n    break\n}", "'break' used outside of a loop", 3);
+}
+
+#[test]
+fn data_file_import_without_a_name() {
+    assert_error("print 1\nimport \"config.json\"", "Importing data file 'config.json' needs a name: import \"config.json\" as name", 2);
+}
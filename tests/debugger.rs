@@ -0,0 +1,135 @@
+// `aegis run --break` : arrêt sur les points d'arrêt, commandes lues sur l'entrée standard.
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+mod common;
+use common::aegis_with_input;
+
+const SCRIPT: &str = "\
+func square(x) {
+    var result = x * x
+    return result
+}
+
+// Les lignes sans code reportent le point d'arrêt sur la suivante
+var values = []
+foreach (i in 0..2) {
+    values.push(square(i))
+}
+print values
+";
+
+fn script(name: &str) -> PathBuf {
+    common::project(name, &[("prog.aeg", SCRIPT)]).join("prog.aeg")
+}
+
+fn debug(path: &Path, breakpoints: &[&str], commands: &str) -> Output {
+    let mut args = vec!["run".to_string()];
+    for breakpoint in breakpoints {
+        args.push(format!("--break={}", breakpoint));
+    }
+    args.push(path.to_string_lossy().to_string());
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    aegis_with_input(path.parent().unwrap(), &args, commands)
+}
+
+#[test]
+fn stops_at_breakpoints_and_shows_variables() {
+    let path = script("locals");
+    let output = debug(&path, &["9"], "l\np values\nc\nl\nc\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(stdout.matches("Breakpoint 1, ").count(), 2, "{}", stdout);
+    assert!(stdout.contains("->    9 |     values.push(square(i))"), "{}", stdout);
+    assert!(stdout.contains("  i = 0\n"), "{}", stdout);
+    assert!(stdout.contains("(debug) []\n"), "{}", stdout);
+    assert!(stdout.contains("  i = 1\n"), "{}", stdout);
+    assert!(stdout.ends_with("[0, 1]\n"), "{}", stdout);
+}
+
+#[test]
+fn step_enters_calls_and_next_stays_in_the_function() {
+    let path = script("step");
+    // Ligne 6 (commentaire) -> 7
+    let output = debug(&path, &["prog.aeg:6"], "s\ns\ns\nbt\nn\nl\nq\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("Breakpoint 1, "), "{}", stdout);
+    assert!(stdout.contains("->    7 | var values = []"), "{}", stdout);
+    assert!(stdout.contains("prog.aeg:2 in square"), "{}", stdout);
+    assert!(stdout.contains("  #0 square ("), "{}", stdout);
+    assert!(stdout.contains("  #1 <script> ("), "{}", stdout);
+    assert!(stdout.contains("prog.aeg:3 in square"), "{}", stdout);
+    assert!(stdout.contains("  x = 0\n  result = 0\n"), "{}", stdout);
+
+    // quit arrête le programme
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Execution stopped by the debugger"));
+    assert!(!stdout.contains("[0, 1]"));
+}
+
+#[test]
+fn breakpoints_can_be_added_and_deleted_from_the_prompt() {
+    let path = script("manage");
+    let output = debug(&path, &["7"], "b 3\nb\nc\nd 2\nc\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("Breakpoint 2 at "), "{}", stdout);
+    assert!(stdout.contains("  1 ") && stdout.contains("prog.aeg:7"), "{}", stdout);
+    // Le point 2 ne sert qu'une fois : il est supprimé au premier arrêt
+    assert_eq!(stdout.matches("Breakpoint 2, ").count(), 1, "{}", stdout);
+    assert!(stdout.contains("Deleted breakpoint 2"), "{}", stdout);
+    assert!(stdout.ends_with("[0, 1]\n"), "{}", stdout);
+}
+
+#[test]
+fn invalid_breakpoints_are_rejected() {
+    let path = script("invalid");
+    let output = debug(&path, &["40"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No code at or after line 40"));
+
+    let output = debug(&path, &["prog.aeg:x"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid breakpoint 'prog.aeg:x'"));
+}
+
+#[test]
+fn value_stack_shows_frames_and_slot_names() {
+    let path = script("value_stack");
+    let output = debug(&path, &["3"], ":stack\nc\nc\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // Du sommet vers la base : les locales de square, puis la fonction appelée et la liste
+    // en cours de remplissage, au-dessus des variables de la boucle
+    assert!(stdout.contains("#0 square ("), "{}", stdout);
+    assert!(stdout.contains("     6  result = 0\n     5  x = 0\n#1 <script> ("), "{}", stdout);
+    assert!(stdout.contains("     4  (temp) = <Function(x)>\n     3  (temp) = []\n     2  i = 0\n"), "{}", stdout);
+    assert!(stdout.ends_with("[0, 1]\n"), "{}", stdout);
+}
+
+#[test]
+fn value_stack_names_the_class_of_a_method_frame() {
+    let path = script("value_stack_class");
+    std::fs::write(&path, "class Point {\n    init(x) { this.x = x }\n    scale(k) {\n        return this.x * k\n    }\n}\nprint new Point(2).scale(3)\n").unwrap();
+    let output = debug(&path, &["4"], ":stack\nc\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains(") in class Point\n"), "{}", stdout);
+    assert!(stdout.contains("  k = 3\n"), "{}", stdout);
+    assert!(stdout.contains("  this = <Instance of Point>\n"), "{}", stdout);
+    assert!(stdout.ends_with("6\n"), "{}", stdout);
+}
+
+#[test]
+fn repl_stack_keeps_the_frames_of_a_runtime_error() {
+    let output = aegis_with_input(&std::env::temp_dir(), &["repl"], "func half(n) { return n.nope() }\nhalf(8)\n:stack\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("#0 half (line 1)\n     1  n = 8\n#1 <script> (line 1)\n     0  (temp) = <Function(n)>\n"), "{}", stdout);
+}
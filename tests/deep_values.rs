@@ -0,0 +1,58 @@
+// Libération des structures profondes : une longue chaîne de listes, d'instances, de dicts
+// ou de closures est libérée à plat, sans déborder la pile Rust (threads de test : 2 Mo).
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use aegis_core::Value;
+
+mod common;
+use common::start;
+
+const DEPTH: usize = 100_000;
+
+// Construit `DEPTH` niveaux avec `step` (qui reçoit le niveau précédent) dans une globale
+fn nest(prelude: &str, first: &str, step: &str) -> String {
+    format!(
+        "{}func build(n) {{\n  var head = {}\n  var i = 0\n  while (i < n) {{\n    i = i + 1\n    head = {}\n  }}\n  return head\n}}\nvar deep = build({})\n",
+        prelude, first, step, DEPTH
+    )
+}
+
+#[test]
+fn nested_lists_are_dropped_with_the_vm() {
+    drop(start(&nest("", "[]", "[head]")));
+}
+
+#[test]
+fn nested_dicts_are_dropped_with_the_vm() {
+    drop(start(&nest("", "{}", "{inner: head}")));
+}
+
+#[test]
+fn instance_chains_are_dropped_with_the_vm() {
+    let prelude = "class Node {\n  init(next) {\n    this.next = next\n  }\n}\n";
+    drop(start(&nest(prelude, "null", "new Node(head)")));
+}
+
+#[test]
+fn closure_chains_are_dropped_with_the_vm() {
+    let prelude = "func wrap(inner) {\n  return func() { return inner }\n}\n";
+    drop(start(&nest(prelude, "null", "wrap(head)")));
+}
+
+#[test]
+fn overwriting_a_deep_value_releases_it() {
+    let mut source = nest("", "[]", "[head]");
+    source.push_str("deep = null\nvar after = 1\n");
+    drop(start(&source));
+}
+
+#[test]
+fn deep_values_built_by_the_host_are_dropped() {
+    let mut value = Value::Null;
+    for _ in 0..DEPTH {
+        value = Value::List(Rc::new(RefCell::new(vec![value])));
+    }
+    drop(value);
+}
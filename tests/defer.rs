@@ -0,0 +1,36 @@
+// Les blocs `defer` s'exécutent aussi quand une erreur n'est pas rattrapée : chaque frame
+// dépilée lance les siens, et une erreur dans du code différé est ajoutée au message.
+
+use aegis_core::Value;
+
+mod common;
+use common::load;
+
+#[test]
+fn uncaught_error_runs_every_pending_defer() {
+    let source = "var log = []\nfunc inner() {\n  defer log.push(\"inner\")\n  return 1 / 0\n}\nfunc outer() {\n  defer log.push(\"outer\")\n  inner()\n}\nfunc get_log() {\n  return log\n}\ndefer log.push(\"script\")\nouter()\n";
+    let mut vm = load(source);
+    let err = vm.run().map(|_| ()).expect_err("l'exécution aurait dû échouer");
+    assert!(err.starts_with("[Line 4] Error: Division by zero"), "{}", err);
+
+    let log = vm.call_global("get_log", vec![]).unwrap();
+    assert_eq!(format!("{}", log), "[inner, outer, script]");
+}
+
+#[test]
+fn error_in_deferred_code_is_reported() {
+    let source = "func main() {\n  defer throw \"cleanup failed\"\n  throw \"boom\"\n}\nmain()\n";
+    let err = load(source).run().map(|_| ()).expect_err("l'exécution aurait dû échouer");
+    assert!(err.contains("boom"), "{}", err);
+    assert!(err.contains("Error in deferred code: "), "{}", err);
+    assert!(err.contains("cleanup failed"), "{}", err);
+}
+
+#[test]
+fn defer_runs_after_return_value_is_computed() {
+    let source = "var items = [1, 2]\nfunc take() {\n  defer items.push(3)\n  return items.len()\n}\nfunc count() {\n  return items.len()\n}\n";
+    let mut vm = load(source);
+    vm.run().unwrap();
+    assert_eq!(vm.call_global("take", vec![]).unwrap(), Value::Integer(2));
+    assert_eq!(vm.call_global("count", vec![]).unwrap(), Value::Integer(3));
+}
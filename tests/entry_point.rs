@@ -0,0 +1,40 @@
+// `aegis run --call` : la valeur de retour de la fonction devient le code de sortie.
+
+use std::path::Path;
+use std::process::Output;
+
+mod common;
+
+fn call(name: &str, source: &str, args: &[&str]) -> Output {
+    let path = common::temp_script(name, source);
+    let path = path.to_string_lossy().to_string();
+    let output = common::aegis(Path::new(env!("CARGO_MANIFEST_DIR")), &[&["run", &path, "--call", "--"], args].concat());
+    std::fs::remove_file(&path).ok();
+    output
+}
+
+#[test]
+fn integer_returns_become_the_exit_code() {
+    let output = call("code", "func main(args) {\n    print len(args)\n    return 3\n}\n", &["a", "b"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2\n");
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = call("no_return", "func main(args) {\n    print \"done\"\n}\n", &[]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn out_of_range_and_non_integer_returns_are_errors() {
+    let output = call("range", "func main(args) { return 256 }\n", &[]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("main() returned 256: exit codes must be between 0 and 255"), "{}", stderr);
+
+    let output = call("negative", "func main(args) { return -1 }\n", &[]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = call("string", "func main(args) { return \"ok\" }\n", &[]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("main() must return an int exit code (0-255), not a value of type string"), "{}", stderr);
+}
@@ -0,0 +1,60 @@
+// Codes d'erreur : affichés quand une erreur arrête le script, expliqués par `aegis explain`.
+
+use std::process::Output;
+
+mod common;
+use common::run_script;
+
+fn explain(args: &[&str]) -> Output {
+    common::aegis(&std::env::temp_dir(), &[&["explain"], args].concat())
+}
+
+// Sortie d'erreur d'un script qui doit échouer
+fn run_error(name: &str, source: &str) -> String {
+    let output = run_script(name, source);
+    assert!(!output.status.success());
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn uncaught_errors_show_their_code() {
+    let err = run_error("division", "var n = 0\nprint 10 / n\n");
+    assert!(err.starts_with("Error[E0201]: [Line 2] Error: Division by zero"), "{}", err);
+    assert!(err.contains("try `aegis explain E0201`"), "{}", err);
+
+    let err = run_error("arity", "func f(a) { return a }\nf(1, 2)\n");
+    assert!(err.starts_with("Error[E0102]:"), "{}", err);
+
+    // Sans code connu, l'affichage ne change pas
+    let err = run_error("custom", "throw \"boom\"\n");
+    assert!(err.starts_with("Error: "), "{}", err);
+    assert!(!err.contains("aegis explain"), "{}", err);
+}
+
+#[test]
+fn caught_messages_are_unchanged() {
+    let output = run_script("catch", "try { print 1 / 0 } catch (e) { print e }\n");
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("Division by zero") && !out.contains("E0201"), "{}", out);
+}
+
+#[test]
+fn explain_prints_the_code_and_its_fixes() {
+    let output = explain(&["e0102"]);
+    assert!(output.status.success());
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.starts_with("E0102: wrong number of arguments"), "{}", out);
+    assert!(out.contains("Common fixes:"), "{}", out);
+
+    let output = explain(&[]);
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.lines().any(|l| l.starts_with("E0401")), "{}", out);
+}
+
+#[test]
+fn explain_rejects_unknown_codes() {
+    let output = explain(&["E9999"]);
+    assert!(!output.status.success());
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(err.contains("Unknown error code 'E9999'"), "{}", err);
+}
@@ -0,0 +1,108 @@
+// `extern "lib" func ...` : appels directs de fonctions C (libm et libc de glibc).
+#![cfg(all(target_os = "linux", target_env = "gnu", any(target_arch = "x86_64", target_arch = "aarch64")))]
+
+use std::fs;
+use std::path::Path;
+
+mod common;
+use common::run_script;
+
+#[test]
+fn calls_c_functions_with_each_type() {
+    let source = r#"
+extern "libm.so.6" func pow(x: float, y: float) -> float
+extern "libm.so.6" func ldexp(x: float, e: int) -> float
+extern "libc.so.6" func abs(n: int) -> int
+extern "libc.so.6" func strlen(s: string) -> pointer
+extern "libc.so.6" func strtol(s: string, end: pointer, base: int) -> pointer
+extern "libc.so.6" func getenv(name: string) -> string
+print pow(2, 10)
+print ldexp(1.5, 3)
+print abs(-42)
+print strlen("héllo")
+print strtol("ff", null, 16)
+print getenv("AEGIS_FFI_TEST")
+print getenv("AEGIS_FFI_UNSET")
+"#;
+    let path = common::temp_script("ffi", source);
+    let output = common::aegis_command(Path::new(env!("CARGO_MANIFEST_DIR")))
+        .arg("run")
+        .arg(&path)
+        .env("AEGIS_FFI_TEST", "set")
+        .env_remove("AEGIS_FFI_UNSET")
+        .output()
+        .expect("impossible de lancer aegis");
+    fs::remove_file(&path).ok();
+
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1024\n12\n42\n6\n255\nset\nnull\n");
+}
+
+#[test]
+fn arguments_are_checked_before_the_call() {
+    let source = r#"
+extern "libc.so.6" func abs(n: int) -> int
+try { abs("x") } catch (e) { print e }
+try { abs(1, 2) } catch (e) { print e }
+try { abs(9999999999) } catch (e) { print e }
+"#;
+    let output = run_script("ffi_args", source);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "\
+abs(): argument 1 must be an int, got string
+abs() expects 1 argument(s), got 2
+abs(): argument 1 (9999999999) does not fit in a C int
+");
+}
+
+#[test]
+fn bad_declarations_are_reported() {
+    let output = run_script("ffi_lib", "extern \"libaegis_missing.so\" func f()\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("extern func f: cannot load 'libaegis_missing.so'"));
+
+    let output = run_script("ffi_symbol", "extern \"libm.so.6\" func aegis_missing(x: float)\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("extern func aegis_missing: symbol not found"));
+
+    let output = run_script("ffi_type", "extern \"libm.so.6\" func cos(x: double) -> float\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown C type 'double'"));
+
+    let output = run_script("ffi_untyped", "extern \"libm.so.6\" func cos(x) -> float\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Parameter 'x' of extern function 'cos' needs a C type"));
+}
+
+// Prototypes que l'ancien appel à signature fixe (6 entiers, 8 flottants) ne pouvait pas
+// représenter : la bibliothèque est compilée pour le test, qui est ignoré sans compilateur C.
+#[test]
+fn prototypes_are_followed_for_any_argument_mix() {
+    let dir = std::env::temp_dir().join(format!("aegis_ffi_lib_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let c_file = dir.join("many.c");
+    let lib = dir.join("libmany.so");
+    fs::write(&c_file, r#"
+int sum9(int a, int b, int c, int d, int e, int f, int g, int h, int i) {
+    return a + 2 * b + 3 * c + 4 * d + 5 * e + 6 * f + 7 * g + 8 * h + 9 * i;
+}
+double mix(int a, double b, int c, double d, double e, double f, double g, double h,
+           double i, double j, double k, int l) {
+    return a * 1000 + b + c * 100 + d + e + f + g + h + i + j + k * 10 + l * 10000;
+}
+const char *pick(int n, const char *a, const char *b) { return n ? a : b; }
+"#).unwrap();
+    let built = std::process::Command::new("cc").args(["-shared", "-fPIC", "-o"]).arg(&lib).arg(&c_file).status();
+    if !built.is_ok_and(|status| status.success()) {
+        eprintln!("cc introuvable : test ignoré");
+        return;
+    }
+
+    let source = format!(r#"
+extern "{lib}" func sum9(a: int, b: int, c: int, d: int, e: int, f: int, g: int, h: int, i: int) -> int
+extern "{lib}" func mix(a: int, b: float, c: int, d: float, e: float, f: float, g: float, h: float, i: float, j: float, k: float, l: int) -> float
+extern "{lib}" func pick(n: int, a: string, b: string) -> string
+print sum9(1, 1, 1, 1, 1, 1, 1, 1, -1)
+print mix(1, 0.5, 2, 1, 1, 1, 1, 1, 1, 1, 3, 1)
+print pick(0, "a", "b")
+"#, lib = lib.display());
+    let output = run_script("ffi_many", &source);
+    fs::remove_dir_all(&dir).ok();
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "27\n11237.5\nb\n");
+}
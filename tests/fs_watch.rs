@@ -0,0 +1,99 @@
+// Fs.watch : les changements d'un dossier sont livrés aux callbacks par Fs.poll / Fs.run.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aegis_core::Value;
+use aegis_core::vm::VM;
+
+mod common;
+use common::start;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aegis_watch_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    // Chemin canonique : c'est celui que rapporte le système (liens symboliques de /tmp sur macOS)
+    dir.canonicalize().unwrap()
+}
+
+// Appelle Fs.poll jusqu'à ce que `expected` apparaisse dans la liste des événements vus
+fn wait_for(vm: &mut VM, expected: &str) -> Vec<String> {
+    for _ in 0..50 {
+        vm.call_global("poll", vec![]).expect("erreur dans poll");
+        let seen = vm.call_global("events", vec![]).unwrap().to_string();
+        if seen.contains(expected) {
+            return seen.trim_matches(|c| c == '[' || c == ']').split(", ").map(String::from).collect();
+        }
+    }
+    panic!("événement '{}' jamais reçu", expected);
+}
+
+fn watcher_script(dir: &Path) -> String {
+    format!(
+        "import \"stdlib/fs.aeg\"\nvar seen = []\nvar watcher = Fs.watch(\"{}\", func(e) {{ seen.push(e.get(\"kind\") + \":\" + e.get(\"path\")) }})\nfunc poll() {{ return Fs.poll(100) }}\nfunc events() {{ return seen }}\nfunc stop() {{ return watcher.close() }}\n",
+        dir.display()
+    )
+}
+
+#[test]
+fn create_modify_and_delete_are_reported() {
+    let dir = temp_dir("events");
+    let mut vm = start(&watcher_script(&dir));
+    let file = dir.join("notes.txt");
+
+    fs::write(&file, "v1").unwrap();
+    let seen = wait_for(&mut vm, &format!("create:{}", file.display()));
+    assert_eq!(seen[0], format!("create:{}", file.display()));
+
+    fs::write(&file, "v2").unwrap();
+    wait_for(&mut vm, &format!("modify:{}", file.display()));
+
+    fs::remove_file(&file).unwrap();
+    wait_for(&mut vm, &format!("delete:{}", file.display()));
+
+    assert_eq!(vm.call_global("stop", vec![]).unwrap(), Value::Boolean(true));
+    assert_eq!(vm.call_global("stop", vec![]).unwrap(), Value::Boolean(false));
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn closed_watchers_stop_delivering() {
+    let dir = temp_dir("closed");
+    let mut vm = start(&watcher_script(&dir));
+
+    vm.call_global("stop", vec![]).unwrap();
+    fs::write(dir.join("late.txt"), "x").unwrap();
+
+    assert_eq!(vm.call_global("poll", vec![]).unwrap(), Value::Integer(0));
+    assert_eq!(vm.call_global("events", vec![]).unwrap().to_string(), "[]");
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn run_returns_once_every_watcher_is_closed() {
+    let dir = temp_dir("run");
+    let file = dir.join("trigger.txt");
+    let source = format!(
+        "import \"stdlib/fs.aeg\"\nvar changes = []\nvar watcher = null\nwatcher = Fs.watch(\"{}\", func(e) {{\n  changes.push(e)\n  watcher.close()\n}})\nfunc run() {{\n  Fs.run()\n  return len(changes)\n}}\n",
+        dir.display()
+    );
+    let mut vm = start(&source);
+
+    let writer = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        fs::write(file, "go").unwrap();
+    });
+    let changes = vm.call_global("run", vec![]).unwrap();
+    writer.join().unwrap();
+
+    assert!(matches!(changes, Value::Integer(n) if n >= 1), "{:?}", changes);
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn watching_a_missing_path_is_an_error() {
+    let source = "import \"stdlib/fs.aeg\"\nFs.watch(\"/aegis/definitely/missing\", func(e) {})\n";
+    let message = common::load(source).run().expect_err("Fs.watch aurait dû échouer");
+    assert!(message.contains("Fs.watch: cannot watch '/aegis/definitely/missing'"), "{}", message);
+}
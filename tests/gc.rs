@@ -0,0 +1,81 @@
+// Ramasse-miettes des cycles : les objets qui ne sont plus tenus que par des cycles sont
+// libérés, ceux qu'on peut encore atteindre (globales, pile, natives) ne sont jamais touchés.
+
+use std::rc::Rc;
+
+use aegis_core::Value;
+use aegis_core::ast::DictMap;
+use aegis_core::vm::gc;
+
+mod common;
+use common::start;
+
+const NODES: &str = "class Node {\n  init(name) {\n    this.name = name\n    this.other = null\n  }\n}\n";
+
+#[test]
+fn unreachable_cycles_are_freed() {
+    let source = format!(
+        "{}func pair() {{\n  var a = new Node(\"a\")\n  var b = new Node(\"b\")\n  a.other = b\n  b.other = a\n}}\nfunc self_list() {{\n  var l = [1]\n  l.push(l)\n}}\nforeach (i in 0..10) {{\n  pair()\n  self_list()\n}}\n",
+        NODES
+    );
+    let _vm = start(&source);
+
+    assert_eq!(gc::collect(), 30);
+    assert_eq!(gc::collect(), 0);
+}
+
+#[test]
+fn reachable_cycles_are_kept() {
+    let source = format!(
+        "{}var keep = new Node(\"keep\")\nkeep.other = keep\nvar list = [keep]\nlist.push(list)\nfunc check() {{\n  return keep.other.other.name + \" \" + list.at(1).len()\n}}\n",
+        NODES
+    );
+    let mut vm = start(&source);
+
+    assert_eq!(gc::collect(), 0);
+    assert_eq!(vm.call_global("check", vec![]).unwrap(), Value::String("keep 2".into()));
+}
+
+#[test]
+fn closures_capturing_their_owner_are_freed() {
+    let source = "class Button {\n  init() {\n    this.handler = func() { return this }\n  }\n}\nforeach (i in 0..5) {\n  var b = new Button()\n}\nvar kept = new Button()\nfunc check() {\n  var handler = kept.handler\n  return handler() == kept\n}\n";
+    let mut vm = start(source);
+
+    // Par bouton perdu : l'instance, la closure, son environnement et la case de `this`
+    assert_eq!(gc::collect(), 20);
+    assert_eq!(vm.call_global("check", vec![]).unwrap(), Value::Boolean(true));
+}
+
+#[test]
+fn values_held_outside_the_heap_are_kept() {
+    let list = gc::list(vec![]);
+    let dict = gc::dict(DictMap::new());
+    if let (Value::List(l), Value::Dict(d)) = (&list, &dict) {
+        l.borrow_mut().push(dict.clone());
+        d.borrow_mut().insert("back".into(), list.clone());
+    }
+    let weak = match &list { Value::List(l) => Rc::downgrade(l), _ => unreachable!() };
+
+    // `list` est tenue par ce test : rien n'est libéré
+    assert_eq!(gc::collect(), 0);
+    drop(dict);
+    assert_eq!(gc::collect(), 0);
+
+    // Plus que le cycle : la liste et le dict sont libérés
+    drop(list);
+    assert!(weak.upgrade().is_some());
+    assert_eq!(gc::collect(), 2);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn allocation_pressure_triggers_collection() {
+    let source = format!(
+        "{}var i = 0\nwhile (i < 50000) {{\n  var a = new Node(\"a\")\n  a.other = a\n  i += 1\n}}\n",
+        NODES
+    );
+    let _vm = start(&source);
+
+    // Sans passe automatique, les 50 000 instances seraient toujours là
+    assert!(gc::tracked_count() < 20000, "{} objets suivis", gc::tracked_count());
+}
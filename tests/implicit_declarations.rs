@@ -0,0 +1,74 @@
+// `x = ...` sur un nom jamais déclaré avec var/let : avertissement par défaut,
+// erreur de compilation avec --strict.
+
+use aegis_core::vm::compiler::{CompileError, Compiler};
+
+mod common;
+use common::{aegis_script, run_script, statements};
+
+fn warnings(source: &str) -> Vec<(usize, String)> {
+    let compiler = Compiler::new();
+    let warnings = compiler.warnings.clone();
+    compiler.compile(statements(source)).map(|_| ()).expect("erreur de compilation");
+    warnings.borrow().iter().map(|w| (w.line, w.message.clone())).collect()
+}
+
+fn strict_error(source: &str) -> CompileError {
+    let mut compiler = Compiler::new();
+    compiler.strict = true;
+    compiler.compile(statements(source)).map(|_| ()).expect_err("la compilation aurait dû échouer")
+}
+
+#[test]
+fn declared_variables_do_not_warn() {
+    let source = "var total = 0\ntotal = total + 1\ntotal += 2\nlet mut [a, b] = [1, 2]\nb = 3\nwhile (total < 10) {\n  var step = 1\n  step += 1\n  total += step\n}\nfunc f(x) {\n  var y = x\n  y = y * 2\n  x = y\n  return x\n}\n";
+    assert_eq!(warnings(source), vec![]);
+}
+
+#[test]
+fn typo_at_top_level_warns() {
+    let source = "var total = 0\ntotl = total + 1\n";
+    assert_eq!(
+        warnings(source),
+        vec![(2, "Assignment to undeclared variable 'totl' (declare it with `var` or `let`)".to_string())]
+    );
+}
+
+#[test]
+fn assignment_to_global_in_function_warns() {
+    let source = "var count = 0\nfunc inc() {\n  count = count + 1\n}\n";
+    assert_eq!(
+        warnings(source),
+        vec![(3, "Assignment to 'count' inside a function creates a new local variable instead of updating the global (declare it with `var` or `let`)".to_string())]
+    );
+}
+
+#[test]
+fn assignment_to_captured_variable_does_not_warn() {
+    // La closure modifie la variable de la fonction englobante : rien n'est déclaré
+    let source = "func outer() {\n  var n = 1\n  var set = func() {\n    n = 2\n  }\n  return n\n}\n";
+    assert_eq!(warnings(source), vec![]);
+}
+
+#[test]
+fn strict_mode_rejects_implicit_declarations() {
+    let err = strict_error("var total = 0\nfunc add(n) {\n  totl = n\n}\n");
+    assert_eq!(err, CompileError {
+        message: "Assignment to undeclared variable 'totl' (declare it with `var` or `let`)".to_string(),
+        line: 3,
+    });
+}
+
+#[test]
+fn cli_prints_warnings_and_strict_fails() {
+    let source = "var total = 1\ntotl = 2\nprint total\n";
+    let output = run_script("implicit", source);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(":2: warning: Assignment to undeclared variable 'totl'"), "{}", stderr);
+
+    let strict = aegis_script("implicit", &["run", "--strict"], source);
+    assert!(!strict.status.success());
+    assert!(strict.stdout.is_empty());
+}
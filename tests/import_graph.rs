@@ -0,0 +1,78 @@
+// `aegis graph` suit les imports (paresseux compris) sans exécuter le script, regroupe les
+// fichiers des paquets et signale les cycles.
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+mod common;
+
+// Petit projet : main importe deux modules qui s'importent l'un l'autre, un paquet et un fichier absent
+fn project(name: &str) -> PathBuf {
+    common::project(name, &[
+        ("main.aeg", "import \"lib/a.aeg\"\nimport \"./lib/b.aeg\" as b\nfunc open() {\n  from \"packages/glfw/glfw.aeg\" import init\n}\nimport \"nope.aeg\"\n"),
+        ("lib/a.aeg", "import \"lib/b.aeg\"\n"),
+        ("lib/b.aeg", "import \"lib/a.aeg\"\n"),
+        ("packages/glfw/glfw.aeg", "var init = 1\n"),
+        ("aegis.toml", "[dependencies]\nglfw = \"1.0\"\nsqlite = \"2.0\"\n"),
+    ])
+}
+
+fn graph(dir: &Path, args: &[&str]) -> Output {
+    common::aegis(dir, &[&["graph"], args].concat())
+}
+
+#[test]
+fn dot_output_lists_modules_packages_and_cycles() {
+    let dir = project("dot");
+    let output = graph(&dir, &["main.aeg"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = "digraph imports {
+    rankdir=LR;
+    node [shape=box];
+    subgraph \"cluster_glfw\" {
+        label=\"package glfw\";
+        n3 [label=\"packages/glfw/glfw.aeg\"];
+    }
+    n0 [label=\"main.aeg\"];
+    n1 [label=\"lib/a.aeg\"];
+    n2 [label=\"lib/b.aeg\"];
+    n4 [label=\"nope.aeg (missing)\", style=dashed];
+    \"package:sqlite\" [label=\"package sqlite (not imported)\", style=dotted];
+    n0 -> n1;
+    n0 -> n2;
+    n0 -> n3;
+    n0 -> n4;
+    n1 -> n2 [color=red];
+    n2 -> n1 [color=red];
+}
+";
+    assert_eq!(stdout, expected);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "warning: import cycle: lib/a.aeg -> lib/b.aeg -> lib/a.aeg\n"
+    );
+}
+
+#[test]
+fn mermaid_output_highlights_cycle_links() {
+    let dir = project("mermaid");
+    let output = graph(&dir, &["main.aeg", "--format", "mermaid"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "graph LR");
+    assert!(lines.contains(&"    subgraph package_glfw[\"package glfw\"]"), "{}", stdout);
+    assert!(lines.contains(&"    n0 --> n3"), "{}", stdout);
+    assert_eq!(lines.last(), Some(&"    linkStyle 4,5 stroke:red"));
+}
+
+#[test]
+fn unreadable_entry_is_an_error() {
+    let dir = project("missing");
+    let output = graph(&dir, &["absent.aeg"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("absent.aeg"));
+}
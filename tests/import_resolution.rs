@@ -0,0 +1,53 @@
+// `import "utils"` essaie utils, utils.aeg puis utils/index.aeg, ou les motifs de
+// `[imports] resolve` dans aegis.toml.
+
+mod common;
+use common::{aegis, project};
+
+#[test]
+fn extensionless_imports_find_files_and_index_modules() {
+    let dir = project("default", &[
+        ("main.aeg", "import \"utils\"\nimport \"utils.aeg\"\nimport \"shapes\" as shapes\nfrom \"shapes\" import square\nprint greet()\nprint shapes.square(3)\nprint square(4)\n"),
+        ("utils.aeg", "print \"loading utils\"\nfunc greet() { return \"hi\" }\n"),
+        ("shapes/index.aeg", "func square(x) { return x * x }\n"),
+    ]);
+    let output = aegis(&dir, &["run", "main.aeg"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    // "utils" et "utils.aeg" sont le même module : il ne s'exécute qu'une fois
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "loading utils\nhi\n9\n16\n");
+}
+
+#[test]
+fn manifest_patterns_set_the_resolution_order() {
+    let dir = project("manifest", &[
+        ("aegis.toml", "[project]\nname = \"app\"\nentry = \"main.aeg\"\n\n[imports]\nresolve = [\"build/{}.aeg\", \"{}.aeg\"]\n"),
+        ("main.aeg", "import \"config\" as config\nimport \"utils\" as utils\nprint config.MODE\nprint utils.NAME\n"),
+        ("config.aeg", "var MODE = \"source\"\n"),
+        ("build/config.aeg", "var MODE = \"build\"\n"),
+        ("utils.aeg", "var NAME = \"utils\"\n"),
+    ]);
+    let output = aegis(&dir, &["run"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "build\nutils\n");
+
+    // `aegis graph` résout les chemins de la même façon
+    let output = aegis(&dir, &["graph", "main.aeg", "--format", "mermaid"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[\"build/config.aeg\"]"), "{}", stdout);
+    assert!(stdout.contains("[\"utils.aeg\"]"), "{}", stdout);
+}
+
+#[test]
+fn missing_modules_and_invalid_patterns_are_reported() {
+    let dir = project("missing", &[("main.aeg", "import \"nope\"\n")]);
+    let output = aegis(&dir, &["run", "main.aeg"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Failed to import 'nope': no such file (tried nope, nope.aeg, nope/index.aeg)"));
+
+    std::fs::write(dir.join("aegis.toml"), "[imports]\nresolve = [\"{}.aeg\", \"lib.aeg\"]\n").unwrap();
+    let output = aegis(&dir, &["run", "main.aeg"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Invalid aegis.toml: import pattern 'lib.aeg' has no '{}' for the imported path"));
+}
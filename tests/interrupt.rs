@@ -0,0 +1,120 @@
+// VmInterrupt : pause, reprise et abandon d'un script depuis un autre thread. La VM n'est pas
+// Send : elle est créée sur son thread, qui renvoie sa poignée à l'hôte.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use aegis_core::{NativeError, NativeFn, Value};
+use aegis_core::vm::interrupt::VmInterrupt;
+
+mod common;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Boucle infinie dans une fonction (ligne 3), dans un try/catch
+const SPIN: &str = "\
+func spin() {
+    var n = 0
+    while (true) { n = n + 1 }
+}
+try {
+    spin()
+} catch (e) {
+    report(e)
+}
+report(\"after\")
+";
+
+thread_local! {
+    // Ce que le script a rapporté, sur le thread de la VM
+    static REPORTED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn report(args: Vec<Value>) -> Result<Value, NativeError> {
+    let text = args.first().map(|v| v.to_string()).unwrap_or_default();
+    REPORTED.with(|r| r.borrow_mut().push(text));
+    Ok(Value::Null)
+}
+
+// Résultat de run() et ce que le script a rapporté
+type Outcome = (Result<(), String>, Vec<String>);
+
+// Lance le script sur son propre thread
+fn spawn(source: &'static str) -> (VmInterrupt, thread::JoinHandle<Outcome>) {
+    let (sender, receiver) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        aegis_core::native::init_registry();
+        let plugin: HashMap<String, NativeFn> = HashMap::from([("report".to_string(), report as NativeFn)]);
+        aegis_core::native::extend_registry(plugin, HashMap::new());
+
+        let mut vm = common::load(source);
+        sender.send(vm.interrupt_handle()).unwrap();
+
+        let result = vm.run();
+        (result, REPORTED.with(|r| r.borrow().clone()))
+    });
+    let interrupt = receiver.recv_timeout(TIMEOUT).expect("la VM n'a pas démarré");
+    (interrupt, handle)
+}
+
+#[test]
+fn a_looping_script_is_paused_and_resumed() {
+    let (interrupt, handle) = spawn(SPIN);
+
+    interrupt.pause();
+    let info = interrupt.wait_paused(TIMEOUT).expect("la VM ne s'est pas mise en pause");
+    assert_eq!(info.line, 3);
+    assert_eq!(info.call_depth, 2, "script + spin");
+    assert_eq!(interrupt.paused(), Some(info.clone()));
+
+    // Tant que personne ne la relance, la VM reste au même endroit
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(interrupt.paused(), Some(info));
+
+    // La VM efface sa pause en repartant, sur son thread
+    interrupt.resume();
+    let start = Instant::now();
+    while interrupt.paused().is_some() {
+        assert!(start.elapsed() < TIMEOUT, "la VM n'est pas repartie");
+        thread::yield_now();
+    }
+
+    // Elle tourne à nouveau : une deuxième pause est atteinte, puis on arrête le script
+    interrupt.pause();
+    assert!(interrupt.wait_paused(TIMEOUT).is_some());
+    interrupt.abort("done");
+
+    let (result, reported) = handle.join().expect("le thread de la VM a paniqué");
+    assert_eq!(result, Ok(()));
+    assert_eq!(reported.len(), 2, "{:?}", reported);
+}
+
+#[test]
+fn an_abort_is_caught_by_the_script() {
+    let (interrupt, handle) = spawn(SPIN);
+
+    // Une pause garantit que le script est dans la boucle, donc dans le try
+    interrupt.pause();
+    assert!(interrupt.wait_paused(TIMEOUT).is_some());
+    interrupt.abort("timeout");
+
+    let (result, reported) = handle.join().expect("le thread de la VM a paniqué");
+    assert_eq!(result, Ok(()));
+    assert_eq!(reported.len(), 2, "{:?}", reported);
+    assert!(reported[0].contains("Execution aborted: timeout"), "{:?}", reported);
+    assert_eq!(reported[1], "after");
+}
+
+#[test]
+fn an_uncaught_abort_is_returned_by_run() {
+    let (interrupt, handle) = spawn("var n = 0\nwhile (true) { n = n + 1 }\n");
+
+    interrupt.abort("stop");
+
+    let (result, _) = handle.join().expect("le thread de la VM a paniqué");
+    let error = result.expect_err("l'abandon doit remonter");
+    assert!(error.contains("Execution aborted: stop"), "{}", error);
+}
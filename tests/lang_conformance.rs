@@ -0,0 +1,52 @@
+// Suite de conformité du langage : chaque tests/lang/*.aeg est exécuté par le binaire `aegis`
+// (lexer -> parser -> loader -> compilateur -> VM) et sa sortie comparée au fichier .expected.
+//
+// Pour (re)générer une sortie attendue après un changement volontaire :
+//     aegis run tests/lang/foo.aeg > tests/lang/foo.expected
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn lang_programs_match_expected_output() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut scripts: Vec<_> = fs::read_dir(root.join("tests/lang"))
+        .expect("tests/lang introuvable")
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "aeg"))
+        .collect();
+    scripts.sort();
+    assert!(!scripts.is_empty(), "aucun script dans tests/lang");
+
+    let mut failures = Vec::new();
+
+    for script in &scripts {
+        let expected_path = script.with_extension("expected");
+        let expected = fs::read_to_string(&expected_path)
+            .unwrap_or_else(|_| panic!("{} manquant", expected_path.display()));
+
+        // Les imports sont relatifs à la racine du projet
+        let output = Command::new(env!("CARGO_BIN_EXE_aegis"))
+            .current_dir(root)
+            .arg("run")
+            .arg(script.strip_prefix(root).unwrap())
+            .output()
+            .expect("impossible de lancer aegis");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            failures.push(format!(
+                "{}: code de sortie {:?}\n{}",
+                script.display(), output.status.code(), String::from_utf8_lossy(&output.stderr)
+            ));
+        } else if stdout != expected {
+            failures.push(format!(
+                "{}: sortie différente\n--- attendu\n{}--- obtenu\n{}",
+                script.display(), expected, stdout
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{} script(s) en échec :\n\n{}", failures.len(), failures.join("\n"));
+}
@@ -0,0 +1,140 @@
+// Code généré : blocs de plus de 65535 octets (sauts 32 bits) et littéraux répétés
+// des milliers de fois (une seule constante chacun), et plus de 256 constantes, globales ou
+// locales (index sur 16 bits).
+
+use std::rc::Rc;
+
+use aegis_core::{Str, Value};
+use aegis_core::ast::ValueKey;
+use aegis_core::vm::compiler::Compiler;
+
+mod common;
+use common::{compile, statements};
+
+fn run(source: &str, entry: &str) -> Value {
+    common::start(source).call_global(entry, vec![]).expect("erreur à l'appel")
+}
+
+fn repeat(line: &str, count: usize) -> String {
+    format!("{}\n", line).repeat(count)
+}
+
+#[test]
+fn huge_blocks_use_long_jumps() {
+    let source = format!(
+        "func main() {{\n  var total = 0\n  var i = 0\n  while (i < 2) {{\n    if (i >= 0) {{\n{}    }} else {{\n      total = -1\n    }}\n    try {{\n{}    }} catch (e) {{\n      total = -2\n    }}\n    i = i + 1\n  }}\n  return total\n}}\n",
+        repeat("      total = total + 1", 10000),
+        repeat("      total = total + 2", 5000),
+    );
+
+    assert_eq!(run(&source, "main"), Value::Integer(40000));
+}
+
+#[test]
+fn top_level_code_uses_long_jumps() {
+    let source = format!(
+        "var total = 0\nif (true) {{\n{}}}\nfunc main() {{\n  return total\n}}\n",
+        repeat("  total = total + 3", 15000),
+    );
+
+    assert_eq!(run(&source, "main"), Value::Integer(45000));
+}
+
+#[test]
+fn many_distinct_constants() {
+    let body: String = (0..1000).map(|n| format!("  x = x + {}\n", n * 7 + 1000)).collect();
+    let source = format!("func main() {{\n  var x = 0\n{}  return x\n}}\n", body);
+
+    let expected: i64 = (0..1000).map(|n| n * 7 + 1000).sum();
+    assert_eq!(run(&source, "main"), Value::Integer(expected));
+}
+
+#[test]
+fn many_globals() {
+    let decls: String = (0..600).map(|n| format!("var g{} = {}\n", n, n)).collect();
+    let sum: String = (0..600).map(|n| format!("  total = total + g{}\n", n)).collect();
+    let source = format!("{}func main() {{\n  var total = 0\n{}  return total\n}}\n", decls, sum);
+
+    assert_eq!(run(&source, "main"), Value::Integer((0..600).sum()));
+}
+
+#[test]
+fn many_locals() {
+    let decls: String = (0..400).map(|n| format!("  var l{} = {}\n", n, n)).collect();
+    let source = format!("func main() {{\n{}  return l0 + l255 + l256 + l399\n}}\n", decls);
+
+    assert_eq!(run(&source, "main"), Value::Integer(910));
+}
+
+#[test]
+fn repeated_literals_share_one_constant() {
+    let source = format!(
+        "func main() {{\n  var s = \"\"\n{}  return s\n}}\n",
+        repeat("  s = s + \"ab\" + 1 + 2.5 + true + null", 20000),
+    );
+
+    let chunk = compile(&source);
+    let main = chunk.nested_chunks().into_iter().find(|c| c.name == "main").expect("pas de main");
+    let strings = main.constants.iter().filter(|c| **c == Value::String("ab".into())).count();
+    assert_eq!(strings, 1);
+    assert!(main.constants.len() < 20, "{} constantes", main.constants.len());
+}
+
+// Un même littéral dans deux fonctions : une seule copie internée, comparée par pointeur
+#[test]
+fn string_literals_are_interned() {
+    let chunk = compile("func a() { return \"shared\" }\nfunc b() { return {shared: 1} }\n");
+    let texts: Vec<Str> = chunk.nested_chunks().iter()
+        .flat_map(|c| c.constants.iter())
+        .filter_map(|c| match c {
+            Value::String(s) if s == "shared" => Some(s.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts.len(), 2);
+    let (Str::Interned(first), Str::Interned(second)) = (&texts[0], &texts[1]) else {
+        panic!("littéraux non internés : {:?}", texts);
+    };
+    assert!(Rc::ptr_eq(first, second));
+
+    // Les clés de dict aussi, même construites à l'exécution
+    let built = ValueKey::from_value(&Value::String(format!("sha{}", "red").into())).unwrap();
+    let ValueKey::String(Str::Interned(key)) = built else { panic!("clé non internée : {:?}", built) };
+    assert!(Rc::ptr_eq(&key, first));
+
+    // Une chaîne internée modifiée est d'abord recopiée : l'autre reste intacte
+    let mut copy = texts[0].clone();
+    copy.make_mut().push('!');
+    assert_eq!(copy, "shared!");
+    assert_eq!(texts[1], "shared");
+}
+
+// Nombres d'éléments et d'arguments sur 16 bits : au-delà de 255, plus de compte tronqué
+#[test]
+fn big_literals_and_calls() {
+    let numbers: Vec<String> = (0..300).map(|n| n.to_string()).collect();
+    let entries: Vec<String> = (0..200).map(|n| format!("k{}: {}", n, n)).collect();
+    let variants: Vec<String> = (0..300).map(|n| format!("V{}", n)).collect();
+    let args = numbers.join(", ");
+    let source = format!(r#"
+enum Big {{ {variants} }}
+class Sink {{
+    count(...xs) {{ return xs.len() }}
+}}
+func total(...xs) {{ return xs.len() }}
+func main() {{
+    var list = [{args}]
+    var dict = {{ {entries} }}
+    return [list.len(), list[299], dict.len(), dict.k199, total({args}), new Sink().count({args}), Big.V299]
+}}
+"#, variants = variants.join(", "), args = args, entries = entries.join(", "));
+
+    assert_eq!(run(&source, "main").to_string(), "[300, 299, 200, 199, 300, 300, 299]");
+}
+
+#[test]
+fn too_many_elements_is_a_compile_error() {
+    let source = format!("var xs = [{}]\n", vec!["1"; 70000].join(", "));
+    let Err(error) = Compiler::new().compile(statements(&source)) else { panic!("70000 éléments compilés") };
+    assert!(error.to_string().contains("Too many elements in a list literal (max 65535)"), "{}", error);
+}
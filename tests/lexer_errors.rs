@@ -0,0 +1,75 @@
+// Entrées mal formées : le lexer doit renvoyer une LexError positionnée (ligne, colonne
+// du début du token fautif) au lieu de paniquer.
+
+use aegis_core::compiler::{self, lexer::{LexError, Lexer, TokenKind}};
+
+fn lex_error(source: &str) -> LexError {
+    Lexer::new(source).tokenize().expect_err("le lexer aurait dû échouer")
+}
+
+fn assert_error(source: &str, message: &str, line: usize, column: usize) {
+    let err = lex_error(source);
+    assert_eq!(err, LexError { message: message.to_string(), line, column }, "source: {:?}", source);
+}
+
+#[test]
+fn unexpected_char() {
+    assert_error("var x = 1 ~ 2", "Unexpected char '~'", 1, 11);
+    assert_error("print 1\n  var y = #", "Unexpected char '#'", 2, 11);
+}
+
+#[test]
+fn unterminated_string() {
+    assert_error("var s = \"hello", "Unterminated string", 1, 9);
+    assert_error("print 1\nprint \"a\\\"", "Unterminated string", 2, 7);
+}
+
+#[test]
+fn unterminated_multiline_string() {
+    assert_error("var s = `ligne 1\nligne 2", "Unterminated string literal", 1, 9);
+}
+
+#[test]
+fn unclosed_interpolation() {
+    assert_error("print `total: ${1 + 2", "Unclosed string interpolation", 1, 7);
+}
+
+#[test]
+fn unterminated_block_comment() {
+    assert_error("var x = 1\n  /* commentaire\n sans fin", "Unterminated block comment", 2, 3);
+}
+
+#[test]
+fn integer_too_large() {
+    assert_error("var n = 99999999999999999999", "Integer literal '99999999999999999999' is too large", 1, 9);
+}
+
+#[test]
+fn float_literal_too_large() {
+    assert_error("var f = 1e999", "Float literal '1e999' is too large", 1, 9);
+}
+
+#[test]
+fn columns_count_characters_not_bytes() {
+    assert_error("var é = \"ça\" ~", "Unexpected char '~'", 1, 14);
+}
+
+#[test]
+fn valid_input_still_tokenizes() {
+    let tokens = Lexer::new("var x = `a ${1 + 2} b` /* ok */\nprint x").tokenize().unwrap();
+    assert!(!tokens.is_empty());
+}
+
+#[test]
+fn scientific_notation_literals_are_floats() {
+    let kinds: Vec<TokenKind> = Lexer::new("1e9 2.5e-3 6E+23 2e x").tokenize().unwrap().into_iter().map(|t| t.kind).collect();
+    assert_eq!(kinds[..3], [TokenKind::Float(1e9), TokenKind::Float(2.5e-3), TokenKind::Float(6e23)]);
+    // Sans chiffres après le 'e', ce n'est pas un exposant : 2 puis l'identifiant e
+    assert_eq!(kinds[3..5], [TokenKind::Integer(2), TokenKind::Identifier("e".to_string())]);
+}
+
+#[test]
+fn compile_propagates_positioned_error() {
+    let err = compiler::compile("print 1\nprint \"oops").unwrap_err();
+    assert_eq!(err, "Lexer error: Unterminated string at line 2, column 7");
+}
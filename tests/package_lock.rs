@@ -0,0 +1,244 @@
+// `aegis add` / `aegis install` : résolution des dépendances transitives (semver) et aegis.lock,
+// contre un faux registre local (AEGIS_REGISTRY_URL).
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+mod common;
+
+// (nom, exigence)
+type Deps = &'static [(&'static str, &'static str)];
+
+// a 1.2.0 dépend de c ^1.0 et b 1.0.0 de c ~1.1 : c doit rester en 1.1.x
+const CATALOG: &[(&str, &str, Deps)] = &[
+    ("a", "1.0.0", &[]),
+    ("a", "1.2.0", &[("c", "^1.0")]),
+    ("a", "2.0.0", &[]),
+    ("b", "1.0.0", &[("c", "~1.1")]),
+    ("c", "1.1.0", &[]),
+    ("c", "1.1.4", &[]),
+    ("c", "1.3.0", &[]),
+];
+
+struct Registry {
+    url: String,
+    // chemin -> corps de la réponse
+    routes: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl Registry {
+    fn start() -> Registry {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind impossible");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let registry = Registry { url, routes: Arc::default(), requests: Arc::default() };
+        for &(name, version, deps) in CATALOG {
+            registry.publish(name, version, deps);
+        }
+
+        let (routes, requests) = (registry.routes.clone(), registry.requests.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap_or(0);
+                    if n == 0 { break; }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let line = String::from_utf8_lossy(&request).lines().next().unwrap_or_default().to_string();
+                let path = line.split(' ').nth(1).unwrap_or("").split('?').next().unwrap_or("").to_string();
+                requests.lock().unwrap().push(path.clone());
+
+                let (status, body) = match routes.lock().unwrap().get(&path) {
+                    Some(body) => ("200 OK", body.clone()),
+                    None => ("404 Not Found", b"{}".to_vec()),
+                };
+                let head = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len());
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+        registry
+    }
+
+    // Ajoute l'archive d'une version et met à jour la liste des versions du paquet
+    fn publish(&self, name: &str, version: &str, deps: &[(&str, &str)]) {
+        let mut routes = self.routes.lock().unwrap();
+        routes.insert(archive_path(name, version), package_zip(name, version, deps));
+
+        let versions_path = format!("/packages/{}/versions/", name);
+        let mut versions: Vec<serde_json::Value> = routes.get(&versions_path)
+            .map(|body| serde_json::from_slice(body).unwrap())
+            .unwrap_or_default();
+        versions.push(serde_json::json!({
+            "version": version,
+            "url": format!("{}{}", self.url, archive_path(name, version)),
+        }));
+        routes.insert(versions_path, serde_json::to_vec(&versions).unwrap());
+    }
+
+    fn set(&self, path: &str, body: &[u8]) {
+        self.routes.lock().unwrap().insert(path.to_string(), body.to_vec());
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn archive_path(name: &str, version: &str) -> String {
+    format!("/files/{}-{}.zip", name, version)
+}
+
+fn package_zip(name: &str, version: &str, deps: &[(&str, &str)]) -> Vec<u8> {
+    let mut manifest = format!("[project]\nname = \"{}\"\nversion = \"{}\"\n\n[dependencies]\n", name, version);
+    for (dep, req) in deps {
+        manifest.push_str(&format!("{} = \"{}\"\n", dep, req));
+    }
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default();
+    zip.start_file("aegis.toml", options).unwrap();
+    zip.write_all(manifest.as_bytes()).unwrap();
+    zip.start_file(format!("{}.aeg", name), options).unwrap();
+    zip.write_all(format!("var VERSION = \"{}\"\n", version).as_bytes()).unwrap();
+    zip.finish().unwrap().into_inner()
+}
+
+fn project(name: &str, dependencies: &str) -> PathBuf {
+    let manifest = format!("[project]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\n{}", dependencies);
+    common::project(name, &[("aegis.toml", &manifest)])
+}
+
+fn aegis(registry: &Registry, dir: &Path, args: &[&str]) -> Output {
+    common::aegis_command(dir)
+        .args(args)
+        .env("AEGIS_REGISTRY_URL", &registry.url)
+        .env("NO_PROXY", "127.0.0.1")
+        .output()
+        .expect("impossible de lancer aegis")
+}
+
+fn installed_version(dir: &Path, name: &str) -> String {
+    std::fs::read_to_string(dir.join("packages").join(name).join(format!("{}.aeg", name))).unwrap()
+}
+
+fn locked(dir: &Path) -> Vec<(String, String)> {
+    let lock: toml::Table = std::fs::read_to_string(dir.join("aegis.lock")).unwrap().parse().unwrap();
+    lock["package"].as_array().unwrap().iter()
+        .map(|p| (p["name"].as_str().unwrap().to_string(), p["version"].as_str().unwrap().to_string()))
+        .collect()
+}
+
+fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+    items.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+}
+
+#[test]
+fn install_resolves_transitive_dependencies_and_writes_the_lockfile() {
+    let registry = Registry::start();
+    let dir = project("resolve", "a = \"^1\"\nb = \"*\"\n");
+
+    let output = aegis(&registry, &dir, &["install"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(locked(&dir), pairs(&[("a", "1.2.0"), ("b", "1.0.0"), ("c", "1.1.4")]));
+    assert_eq!(installed_version(&dir, "c"), "var VERSION = \"1.1.4\"\n");
+
+    let lock = std::fs::read_to_string(dir.join("aegis.lock")).unwrap();
+    assert!(lock.contains("checksum = \"sha256:"), "{}", lock);
+    assert!(lock.contains("dependencies = [\"c\"]"), "{}", lock);
+}
+
+#[test]
+fn install_reproduces_the_lockfile_exactly() {
+    let registry = Registry::start();
+    let dir = project("exact", "a = \"^1\"\n");
+    assert!(aegis(&registry, &dir, &["install"]).status.success());
+
+    // Une version plus récente compatible paraît : le lockfile garde 1.2.0
+    registry.publish("a", "1.9.0", &[]);
+    std::fs::remove_dir_all(dir.join("packages")).unwrap();
+    let before = registry.requests().len();
+
+    let output = aegis(&registry, &dir, &["install"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(installed_version(&dir, "a"), "var VERSION = \"1.2.0\"\n");
+    assert_eq!(installed_version(&dir, "c"), "var VERSION = \"1.3.0\"\n");
+    // Seules les archives sont téléchargées, sans nouvelle résolution
+    assert!(registry.requests()[before..].iter().all(|path| path.starts_with("/files/")), "{:?}", registry.requests());
+
+    // Une archive modifiée sur le registre est refusée
+    registry.set(&archive_path("a", "1.2.0"), &package_zip("a", "1.2.0", &[("c", "*")]));
+    let output = aegis(&registry, &dir, &["install"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Checksum mismatch for a 1.2.0"));
+}
+
+#[test]
+fn add_records_the_requirement_and_updates_the_lockfile() {
+    let registry = Registry::start();
+    let dir = project("add", "a = \"^1\"\n");
+
+    let output = aegis(&registry, &dir, &["add", "b"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let manifest = std::fs::read_to_string(dir.join("aegis.toml")).unwrap();
+    assert!(manifest.contains("b = \"^1.0.0\""), "{}", manifest);
+    assert_eq!(locked(&dir), pairs(&[("a", "1.2.0"), ("b", "1.0.0"), ("c", "1.1.4")]));
+
+    // Une exigence que le lockfile ne respecte plus est signalée par `aegis install`
+    std::fs::write(dir.join("aegis.toml"), "[dependencies]\na = \"^2\"\nb = \"*\"\n").unwrap();
+    let output = aegis(&registry, &dir, &["install"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("aegis.lock is out of date"));
+
+    let output = aegis(&registry, &dir, &["add", "a", "2"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(locked(&dir), pairs(&[("a", "2.0.0"), ("b", "1.0.0"), ("c", "1.1.4")]));
+}
+
+#[test]
+fn add_keeps_locked_versions_and_prunes_unused_packages() {
+    let registry = Registry::start();
+    let dir = project("keep", "a = \"^1\"\n");
+    assert!(aegis(&registry, &dir, &["install"]).status.success());
+    assert_eq!(locked(&dir), pairs(&[("a", "1.2.0"), ("c", "1.3.0")]));
+
+    // Des versions plus récentes paraissent : ajouter d ne les installe pas
+    registry.publish("a", "1.9.0", &[]);
+    registry.publish("c", "1.4.0", &[]);
+    registry.publish("d", "1.0.0", &[]);
+    let output = aegis(&registry, &dir, &["add", "d"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(locked(&dir), pairs(&[("a", "1.2.0"), ("c", "1.3.0"), ("d", "1.0.0")]));
+
+    // a 2.0.0 ne dépend plus de c : il quitte le lockfile et packages/, un dossier ajouté à la main reste
+    std::fs::create_dir_all(dir.join("packages/local")).unwrap();
+    let output = aegis(&registry, &dir, &["add", "a", "2"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(locked(&dir), pairs(&[("a", "2.0.0"), ("d", "1.0.0")]));
+    assert!(!dir.join("packages/c").exists());
+    assert!(dir.join("packages/local").exists());
+    assert_eq!(installed_version(&dir, "a"), "var VERSION = \"2.0.0\"\n");
+}
+
+#[test]
+fn conflicting_requirements_are_reported() {
+    let registry = Registry::start();
+    let dir = project("conflict", "b = \"*\"\nc = \"^1.3\"\n");
+
+    let output = aegis(&registry, &dir, &["install"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No version of 'c' satisfies ^1.3 (from aegis.toml) and ~1.1 (from b 1.0.0)"), "{}", stderr);
+    assert!(stderr.contains("available: 1.3.0, 1.1.4, 1.1.0"), "{}", stderr);
+    assert!(!dir.join("aegis.lock").exists());
+}
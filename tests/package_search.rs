@@ -0,0 +1,87 @@
+// `aegis search` interroge le registre (ici un faux registre local, via AEGIS_REGISTRY_URL)
+// et affiche les paquets trouvés en tableau, ou en JSON avec --json.
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::process::Output;
+use std::thread;
+
+mod common;
+
+const RESULTS: &str = r#"{"count": 2, "next": null, "previous": null, "results": [
+    {"name": "glfw", "version": "1.2.0", "description": "Windowing and input for games", "downloads": 1520,
+     "platforms": [{"os": "any", "architecture": "any"}]},
+    {"name": "glfw-legacy", "version": "0.9.1", "description": null, "downloads": 12,
+     "platforms": [{"os": "plan9", "architecture": "mips"}]}
+]}"#;
+
+// Répond une fois `body` et renvoie la ligne de requête reçue
+fn serve_once(body: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind impossible");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("aucune connexion");
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).expect("lecture de la requête");
+            if n == 0 { break; }
+            request.extend_from_slice(&buf[..n]);
+        }
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(), body
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+        String::from_utf8_lossy(&request).lines().next().unwrap_or_default().to_string()
+    });
+    (url, handle)
+}
+
+fn search(url: &str, args: &[&str]) -> Output {
+    common::aegis_command(&std::env::temp_dir())
+        .arg("search")
+        .args(args)
+        .env("AEGIS_REGISTRY_URL", url)
+        .env("NO_PROXY", "127.0.0.1")
+        .output()
+        .expect("impossible de lancer aegis")
+}
+
+#[test]
+fn results_are_shown_as_a_table() {
+    let (url, server) = serve_once(RESULTS);
+    let output = search(&url, &["glfw window"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(server.join().unwrap(), "GET /packages/search/?q=glfw+window HTTP/1.1");
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "NAME         VERSION  DOWNLOADS  COMPATIBLE  PLATFORMS   DESCRIPTION");
+    assert_eq!(lines[1], "glfw         1.2.0    1520       yes         any/any     Windowing and input for games");
+    assert_eq!(lines[2], "glfw-legacy  0.9.1    12         no          plan9/mips");
+    assert!(lines[4].starts_with("2 package(s) found for "), "{}", stdout);
+}
+
+#[test]
+fn json_output_adds_compatibility() {
+    let (url, server) = serve_once(RESULTS);
+    let output = search(&url, &["glfw", "--json"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    server.join().unwrap();
+
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).expect("sortie JSON invalide");
+    assert_eq!(results[0]["name"], "glfw");
+    assert_eq!(results[0]["downloads"], 1520);
+    assert_eq!(results[0]["compatible"], true);
+    assert_eq!(results[1]["description"], serde_json::Value::Null);
+    assert_eq!(results[1]["compatible"], false);
+}
+
+#[test]
+fn no_match_is_reported() {
+    let (url, server) = serve_once("[]");
+    let output = search(&url, &["nothing"]);
+    server.join().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "No package matches 'nothing'.\n");
+}
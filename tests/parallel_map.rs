@@ -0,0 +1,38 @@
+// Parallel.map : découpage entre workers, copies des globales et erreurs par élément.
+
+mod common;
+use common::start;
+
+#[test]
+fn results_keep_the_order_of_the_list() {
+    let mut vm = start(
+        "import \"stdlib/parallel.aeg\"\nfunc slow_square(x) {\n    var s = 0\n    foreach (i in 0..(20 - x) * 500) { s = s + 1 }\n    return x * x\n}\nfunc run_list(n) { var l = [] foreach (i in 0..n) { l.push(i) } return Parallel.map(l, slow_square, {workers: n}) }\n",
+    );
+    let result = vm.call_global("run_list", vec![aegis_core::Value::Integer(8)]).expect("Parallel.map");
+    assert_eq!(result.to_string(), "[0, 1, 4, 9, 16, 25, 36, 49]");
+}
+
+#[test]
+fn the_lowest_failing_index_is_reported() {
+    let mut vm = start(
+        "import \"stdlib/parallel.aeg\"\nfunc check(x) {\n    if (x >= 7) { throw \"too big: \" + x }\n    return x\n}\nfunc run() {\n    var l = []\n    foreach (i in 0..20) { l.push(i) }\n    try {\n        Parallel.map(l, check, {workers: 4})\n    } catch (e: RuntimeError) {\n        return e.index + \" \" + e.message\n    }\n}\n",
+    );
+    // Quel que soit le worker qui termine en premier
+    for _ in 0..5 {
+        let result = vm.call_global("run", vec![]).expect("Parallel.map");
+        assert_eq!(result.to_string(), "7 item 7: too big: 7");
+    }
+}
+
+#[test]
+fn workers_change_copies_of_the_globals() {
+    let mut vm = start(
+        "import \"stdlib/parallel.aeg\"\nvar hits = { \"n\": 0 }\nfunc count(x) {\n    hits.insert(\"n\", hits.get(\"n\") + 1)\n    return hits.get(\"n\")\n}\nfunc run() { return Parallel.map([1, 2, 3], count, {workers: 1}) }\nfunc main_hits() { return hits.get(\"n\") }\n",
+    );
+    // Un même worker garde son état d'un élément à l'autre...
+    let result = vm.call_global("run", vec![]).expect("Parallel.map");
+    assert_eq!(result.to_string(), "[1, 2, 3]");
+    // ...mais le script ne voit pas ses modifications
+    let hits = vm.call_global("main_hits", vec![]).expect("main_hits");
+    assert_eq!(hits.to_string(), "0");
+}
@@ -0,0 +1,82 @@
+// ABI des plugins : version vérifiée avant l'initialisation, enregistrement via PluginContext,
+// et rapport de ce que chaque plugin fournit.
+
+use aegis_core::plugins::{self, register_plugin};
+use aegis_core::{Args, NativeClass, NativeError, PluginContext, PluginInfo, Value, PLUGIN_ABI_VERSION};
+
+mod common;
+
+static GEO: PluginInfo = PluginInfo { abi_version: PLUGIN_ABI_VERSION, name: "geo", version: "1.2.0" };
+
+fn geo_dist(args: Vec<Value>) -> Result<Value, NativeError> {
+    let args = Args::new("geo_dist(a, b)", &args);
+    Ok(Value::Float((args.want_number(0)? - args.want_number(1)?).abs()))
+}
+
+fn point_norm(_args: Vec<Value>) -> Result<Value, NativeError> {
+    Ok(Value::Integer(5))
+}
+
+fn geo_init(ctx: &mut PluginContext) {
+    if !ctx.supports("classes") {
+        ctx.fail("needs native classes");
+        return;
+    }
+    ctx.function("geo_dist", geo_dist);
+    ctx.constant("GEO_ORIGIN", Value::Integer(0));
+    ctx.class(NativeClass::new("GeoPoint").static_method("norm", point_norm));
+}
+
+fn run(source: &str) -> Result<(), String> {
+    common::load(source).run().map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[test]
+fn a_plugin_registers_through_its_context() {
+    aegis_core::native::init_registry();
+    let report = register_plugin(&GEO, "geo.so", geo_init).unwrap();
+    assert_eq!(report.functions, ["geo_dist"]);
+    assert_eq!(report.constants, ["GEO_ORIGIN"]);
+    assert_eq!(report.classes, ["GeoPoint"]);
+    assert!(plugins::loaded_plugins().iter().any(|p| p.name == "geo" && p.version == "1.2.0"));
+
+    run(r#"
+if (geo_dist(1, 4.5) != 3.5) { throw "geo_dist" }
+if (GEO_ORIGIN != 0) { throw "GEO_ORIGIN" }
+if (GeoPoint.norm() != 5) { throw "GeoPoint.norm" }
+"#).unwrap();
+}
+
+#[test]
+fn a_mismatched_abi_is_rejected_before_init() {
+    let future = PluginInfo { abi_version: PLUGIN_ABI_VERSION + 1, name: "future", version: "9.0.0" };
+    let mut called = false;
+    let error = register_plugin(&future, "future.so", |_| called = true).unwrap_err();
+    assert!(!called);
+    assert!(error.contains(&format!("plugin ABI v{} is not supported", PLUGIN_ABI_VERSION + 1)), "{}", error);
+}
+
+#[test]
+fn a_plugin_can_refuse_to_load() {
+    aegis_core::native::init_registry();
+    let info = PluginInfo { abi_version: PLUGIN_ABI_VERSION, name: "gpu", version: "0.1.0" };
+    let error = register_plugin(&info, "gpu.so", |ctx| {
+        ctx.function("gpu_only_fn", geo_dist);
+        if !ctx.supports("raytracing") {
+            ctx.fail("host has no raytracing");
+        }
+    }).unwrap_err();
+    assert_eq!(error, "plugin 'gpu' 0.1.0 refused to load: host has no raytracing");
+    // Rien n'a été enregistré
+    assert!(!aegis_core::native::is_available("gpu_only_fn"));
+}
+
+#[test]
+#[cfg(all(target_os = "linux", target_env = "gnu", target_arch = "x86_64"))]
+fn a_library_without_plugin_info_is_not_loaded() {
+    let path = ["/lib/x86_64-linux-gnu/libm.so.6", "/usr/lib64/libm.so.6"].into_iter()
+        .find(|p| std::path::Path::new(p).exists());
+    let Some(path) = path else { return };
+    let error = plugins::load_plugin(path).unwrap_err();
+    assert!(error.contains("not an Aegis plugin (no AEGIS_PLUGIN_INFO symbol)"), "{}", error);
+}
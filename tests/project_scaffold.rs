@@ -0,0 +1,64 @@
+// `aegis new` / `aegis init` créent un projet que `aegis run` lance sans argument.
+
+mod common;
+use common::aegis;
+
+#[test]
+fn new_creates_a_project_that_runs_without_a_file_argument() {
+    let dir = common::project("new", &[]);
+    let output = aegis(&dir, &["new", "hello_app", "--tests"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let project = dir.join("hello_app");
+    let manifest = std::fs::read_to_string(project.join("aegis.toml")).unwrap();
+    assert_eq!(manifest, "[project]\nname = \"hello_app\"\nversion = \"0.1.0\"\nentry = \"src/main.aeg\"\n\n[dependencies]\n");
+    assert_eq!(std::fs::read_to_string(project.join(".gitignore")).unwrap(), "packages/\n");
+
+    let output = aegis(&project, &["run"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello from hello_app!\n");
+
+    let output = aegis(&project, &["test"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 passed; 0 failed"));
+
+    // Le dossier existe déjà
+    let output = aegis(&dir, &["new", "hello_app"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+}
+
+#[test]
+fn init_keeps_existing_files_and_refuses_a_second_manifest() {
+    let dir = common::project("init", &[]);
+    std::fs::write(dir.join(".gitignore"), "*.log\n").unwrap();
+
+    let output = aegis(&dir, &["init"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("skipped .gitignore"));
+    assert_eq!(std::fs::read_to_string(dir.join(".gitignore")).unwrap(), "*.log\n");
+    assert!(dir.join("src/main.aeg").exists());
+    assert!(!dir.join("tests").exists());
+
+    let output = aegis(&dir, &["init"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("aegis.toml already exists"));
+}
+
+#[test]
+fn run_uses_the_manifest_entry_point() {
+    let dir = common::project("entry", &[]);
+    std::fs::create_dir_all(dir.join("app")).unwrap();
+    std::fs::write(dir.join("aegis.toml"), "[project]\nname = \"x\"\nversion = \"0.1.0\"\nentry = \"app/start.aeg\"\n").unwrap();
+    std::fs::write(dir.join("app/start.aeg"), "import \"stdlib/system.aeg\"\nprint System.args()\n").unwrap();
+
+    let output = aegis(&dir, &["run", "--", "a", "b"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "[a, b]\n");
+
+    // Sans aegis.toml, `aegis run` a besoin d'un fichier
+    let empty = common::project("no_manifest", &[]);
+    let output = aegis(&empty, &["run"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no aegis.toml"));
+}
@@ -0,0 +1,66 @@
+// REPL : chaque ligne est compilée à part, mais les constantes, alias de type et `use strict`
+// des lignes précédentes restent connus du compilateur.
+
+mod common;
+
+fn repl(args: &[&str], input: &str) -> String {
+    let output = common::aegis_with_input(&std::env::temp_dir(), &[&["repl"], args].concat(), input);
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn declarations_carry_over_to_later_lines() {
+    let out = repl(&[], "const K = 1\nK = 2\nK\ntype Id = int\nvar x: Id = 3\nx + 1\nfunc f() { return 1 }\nfunc g() { return f() }\nfunc f() { return 2 }\ng()\n");
+    assert!(out.contains("Cannot reassign constant 'K'"), "{}", out);
+    assert!(!out.contains("Erreur de Type"), "{}", out);
+    let values: Vec<&str> = out.lines().filter(|l| l.len() == 1).collect();
+    // K garde sa valeur, l'alias est connu, f redéfinie est celle qu'appelle g
+    assert_eq!(values, ["1", "4", "2"], "{}", out);
+}
+
+#[test]
+fn strict_mode_applies_to_later_lines() {
+    let out = repl(&[], "use strict\nfunc f() { z = 1 }\n");
+    assert!(out.contains("Assignment to undeclared variable 'z'"), "{}", out);
+    assert!(!out.contains("warning:"), "{}", out);
+}
+
+#[test]
+fn a_rolled_back_line_forgets_its_declarations() {
+    let out = repl(&["--transactional"], "const Z = 1 null()\nZ = 4\nZ\n");
+    assert!(out.contains("(état global restauré)"), "{}", out);
+    assert!(!out.contains("Cannot reassign constant"), "{}", out);
+    assert!(out.lines().any(|l| l == "4"), "{}", out);
+}
+
+#[test]
+fn a_rolled_back_line_undoes_in_place_changes() {
+    let input = "var xs = [1, 2]\nvar d = {\"a\": 1}\nvar alias = xs\n\
+                 xs.push(3) d[\"b\"] = 2 alias.push(4) null()\nxs\nd\nalias === xs\n";
+    let out = repl(&["--transactional"], input);
+    assert!(out.contains("(état global restauré)"), "{}", out);
+    assert!(out.lines().any(|l| l == "[1, 2]"), "{}", out);
+    assert!(out.lines().any(|l| l == "{a: 1}"), "{}", out);
+    assert!(out.lines().any(|l| l == "true"), "{}", out);
+}
+
+#[test]
+fn a_rolled_back_line_restores_captured_variables() {
+    let input = "func counter() { var n = 0 return func() { n += 1 return n } }\nvar next = counter()\n\
+                 next()\nnext() null()\nnext()\n";
+    let out = repl(&["--transactional"], input);
+    let values: Vec<&str> = out.lines().filter(|l| l.chars().all(|c| c.is_ascii_digit()) && !l.is_empty()).collect();
+    assert_eq!(values, ["1", "2"], "{}", out);
+}
+
+#[test]
+fn stack_traces_leave_out_the_repl_base_frame() {
+    let out = repl(&[], "func f() { throw \"x\" }\nf()\n");
+    assert!(out.contains("Error: x\n    at f (line 1)\n    at <script> (line 1)\n"), "{}", out);
+    assert!(!out.contains("no debug info"), "{}", out);
+
+    // Sans appel de fonction, pas de pile d'appels
+    let out = repl(&[], "throw \"y\"\n");
+    assert!(out.contains("Error: y\n"), "{}", out);
+    assert!(!out.contains("    at "), "{}", out);
+}
@@ -0,0 +1,109 @@
+// VM::run_budget : exécution par tranches d'instructions. Entre deux tranches, la pile et les
+// frames sont conservées, y compris au milieu d'un appel de fonction.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use aegis_core::{NativeError, NativeFn, Value};
+use aegis_core::vm::{RunState, VM};
+
+mod common;
+
+thread_local! {
+    // Ce que le script a tracé jusqu'ici (chaque test a son thread)
+    static TRACE: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn trace(args: Vec<Value>) -> Result<Value, NativeError> {
+    let text = args.first().map(|v| v.to_string()).unwrap_or_default();
+    TRACE.with(|t| t.borrow_mut().push(text));
+    Ok(Value::Null)
+}
+
+fn traced() -> Vec<String> {
+    TRACE.with(|t| t.borrow().clone())
+}
+
+fn load(source: &str) -> VM {
+    aegis_core::native::init_registry();
+    let plugin: HashMap<String, NativeFn> = HashMap::from([("trace".to_string(), trace as NativeFn)]);
+    aegis_core::native::extend_registry(plugin, HashMap::new());
+
+    common::load(source)
+}
+
+#[test]
+fn a_script_is_suspended_resumed_and_finished() {
+    let mut vm = load("var i = 0\nwhile (i < 50) {\n    trace(i)\n    i = i + 1\n}\ntrace(\"done\")\n");
+
+    assert_eq!(vm.run_budget(0), Ok(RunState::Suspended));
+    assert!(traced().is_empty());
+
+    assert_eq!(vm.run_budget(20), Ok(RunState::Suspended));
+    let first = traced().len();
+    assert!(first > 0 && first < 50, "{:?}", traced());
+
+    // Chaque tranche reprend là où la précédente s'est arrêtée
+    let mut slices = 1;
+    while vm.run_budget(20).expect("erreur à l'exécution") == RunState::Suspended {
+        slices += 1;
+        assert!(slices < 1000, "le script ne termine pas");
+    }
+    assert!(slices > 2);
+
+    let mut expected: Vec<String> = (0..50).map(|i| i.to_string()).collect();
+    expected.push("done".to_string());
+    assert_eq!(traced(), expected);
+}
+
+#[test]
+fn the_budget_can_run_out_inside_a_nested_call() {
+    let source = "\
+func inner(n) {
+    trace(\"enter \" + n)
+    var s = 0
+    foreach (i in 0..10) { s = s + i * n }
+    trace(\"leave \" + n)
+    return s
+}
+func outer() {
+    return inner(1) + inner(2)
+}
+trace(outer())
+";
+    let mut vm = load(source);
+
+    // Une instruction à la fois jusqu'à être au milieu du premier appel à inner
+    while traced().last().map(String::as_str) != Some("enter 1") {
+        assert_eq!(vm.run_budget(1), Ok(RunState::Suspended));
+    }
+    assert_eq!(vm.run_budget(3), Ok(RunState::Suspended));
+    assert_eq!(traced(), ["enter 1"]);
+
+    // La reprise termine inner avec ses locales, puis revient dans outer
+    let mut state = RunState::Suspended;
+    while state == RunState::Suspended {
+        state = vm.run_budget(1).expect("erreur à l'exécution");
+    }
+    assert_eq!(traced(), ["enter 1", "leave 1", "enter 2", "leave 2", "135"]);
+}
+
+#[test]
+fn an_error_ends_the_slice() {
+    let mut vm = load("trace(1)\nthrow \"boom\"\n");
+    let error = vm.run_budget(1000).expect_err("le throw doit remonter");
+    assert!(error.message.contains("boom"), "{}", error);
+    // La valeur levée accompagne le message
+    assert_eq!(error.value, Some(Value::String("boom".into())));
+    assert_eq!(traced(), ["1"]);
+}
+
+#[test]
+fn unawaited_tasks_run_before_finished() {
+    let mut vm = load("async func late() {\n    trace(\"late\")\n}\nlate()\ntrace(\"main\")\n");
+    let mut state = RunState::Suspended;
+    while state == RunState::Suspended {
+        state = vm.run_budget(5).expect("erreur à l'exécution");
+    }
+    assert_eq!(traced(), ["main", "late"]);
+}
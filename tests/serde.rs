@@ -0,0 +1,62 @@
+// Serde.dump / Serde.load : cache sur disque et données corrompues.
+
+use std::fs;
+
+use aegis_core::Value;
+
+mod common;
+use common::start;
+
+fn bytes(data: &[u8]) -> Value {
+    Value::Bytes(std::rc::Rc::new(std::cell::RefCell::new(data.to_vec())))
+}
+
+#[test]
+fn results_cached_on_disk_survive_a_new_vm() {
+    let path = std::env::temp_dir().join(format!("aegis_serde_cache_{}.bin", std::process::id()));
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    start(&format!(
+        "import \"stdlib/serde.aeg\"\nimport \"stdlib/file.aeg\"\nvar result = {{ \"primes\": [2, 3, 5, 7], \"elapsed\": 1.5 }}\nFile.write_bytes(\"{}\", Serde.dump(result))\n",
+        path
+    ));
+
+    let mut reader = start(&format!(
+        "import \"stdlib/serde.aeg\"\nimport \"stdlib/file.aeg\"\nfunc cached() {{ return Serde.load(File.read_bytes(\"{}\")).get(\"primes\") }}\n",
+        path
+    ));
+    let cached = reader.call_global("cached", vec![]).expect("lecture du cache");
+    assert_eq!(cached.to_string(), "[2, 3, 5, 7]");
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn corrupted_data_is_an_error() {
+    let mut vm = start(
+        "import \"stdlib/serde.aeg\"\nfunc sample() { return Serde.dump([1, \"two\", { \"three\": 3.0 }]) }\nfunc load(bytes) { return Serde.load(bytes) }\n",
+    );
+    let Ok(Value::Bytes(ref valid)) = vm.call_global("sample", vec![]) else { panic!("dump aurait dû renvoyer des bytes") };
+    let valid = valid.borrow().clone();
+
+    // Chaque troncature est refusée proprement, jamais un panic
+    for cut in 0..valid.len() {
+        let err = vm.call_global("load", vec![bytes(&valid[..cut])]).expect_err("données tronquées acceptées");
+        assert!(err.contains("Serde.load"), "{}", err);
+    }
+
+    let mut trailing = valid.clone();
+    trailing.push(0);
+    let err = vm.call_global("load", vec![bytes(&trailing)]).unwrap_err();
+    assert!(err.contains("trailing bytes"), "{}", err);
+
+    let mut bad_tag = valid.clone();
+    bad_tag[5] = 200;
+    let err = vm.call_global("load", vec![bytes(&bad_tag)]).unwrap_err();
+    assert!(err.contains("invalid value tag 200"), "{}", err);
+
+    let mut newer = valid;
+    newer[4] = 99;
+    let err = vm.call_global("load", vec![bytes(&newer)]).unwrap_err();
+    assert!(err.contains("unsupported format version 99"), "{}", err);
+}
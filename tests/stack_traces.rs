@@ -0,0 +1,73 @@
+// Une erreur non rattrapée affiche la pile d'appels avec le nom de chaque fonction,
+// tel que le compilateur l'a enregistré dans son chunk.
+
+use aegis_core::vm::VM;
+use aegis_core::vm::compiler::Compiler;
+
+mod common;
+
+fn run_error(source: &str) -> String {
+    common::load(source).run().map(|_| ()).expect_err("l'exécution aurait dû échouer")
+}
+
+#[test]
+fn top_level_error_has_no_trace() {
+    assert_eq!(run_error("print 1\nprint 1 / 0"), "[Line 2] Error: Division by zero");
+}
+
+#[test]
+fn trace_lists_functions_methods_and_namespaces() {
+    let source = "namespace Maths {\n  func inverse(x) {\n    return 1 / x\n  }\n}\nclass Calc {\n  run(x) {\n    return Maths.inverse(x)\n  }\n}\nfunc main() {\n  return new Calc().run(0)\n}\nmain()\n";
+    assert_eq!(
+        run_error(source),
+        "[Line 3] Error: Division by zero\n    at Maths.inverse (line 3)\n    at Calc.run (line 8)\n    at main (line 12)\n    at <script> (line 14)"
+    );
+}
+
+#[test]
+fn tail_call_replaces_its_caller_in_the_trace() {
+    // `return inverse(x)` réutilise la frame de relay : seule la récursion non terminale reste
+    let source = "func inverse(x) {\n  return 1 / x\n}\nfunc relay(x) {\n  return inverse(x)\n}\nfunc main() {\n  var r = relay(0)\n  return r\n}\nmain()\n";
+    assert_eq!(
+        run_error(source),
+        "[Line 2] Error: Division by zero\n    at inverse (line 2)\n    at main (line 8)\n    at <script> (line 11)"
+    );
+}
+
+#[test]
+fn arity_error_names_the_callee() {
+    let err = run_error("var twice = func(x) { return x * 2 }\nfunc main() {\n  return twice(1, 2)\n}\nmain()\n");
+    assert!(err.starts_with("[Line 3] Error: Arity mismatch in '<lambda>': attendu 1, reçu 2"), "{}", err);
+}
+
+#[test]
+fn trace_names_the_file_of_each_frame() {
+    let dir = std::env::temp_dir().join(format!("aegis_trace_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let module = dir.join("maths.aeg").to_string_lossy().replace('\\', "/");
+    std::fs::write(&module, "namespace Maths {\n  func inverse(x) {\n    return 1 / x\n  }\n}\n").unwrap();
+
+    let start = |body: &str| {
+        let source = format!("import \"{}\"\n{}", module, body);
+        let mut compiler = Compiler::new();
+        compiler.file = Some("main.aeg".into());
+        let (chunk, globals) = compiler.compile(common::statements(&source)).expect("erreur de compilation");
+        let mut vm = VM::new(chunk, globals, vec![]);
+        let result = vm.run();
+        (vm, result)
+    };
+
+    let (_, result) = start("func main() {\n  return Maths.inverse(0)\n}\nmain()\n");
+    assert_eq!(
+        result.unwrap_err(),
+        format!("[Line 3] Error: Division by zero\n    at Maths.inverse ({}:3)\n    at main (main.aeg:3)\n    at <script> (main.aeg:5)", module)
+    );
+
+    // Le champ `stack` d'une erreur attrapée utilise les mêmes positions
+    let (mut vm, result) = start("func main() {\n  try {\n    Maths.inverse(0)\n  } catch (e: RuntimeError) {\n    return e.stack\n  }\n}\n");
+    result.expect("chargement");
+    let stack = vm.call_global("main", vec![]).expect("main").to_string();
+    assert!(stack.starts_with(&format!("[Maths.inverse ({}:3), main (main.aeg:4)", module)), "{}", stack);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,82 @@
+// `aegis run --stats` / `--profile` : compteurs d'opcodes, temps par opcode, fonctions chaudes et indices.
+
+mod common;
+
+fn run_with_stats(source: &str, opt_level: u8) -> String {
+    let mut vm = common::load(source);
+    vm.enable_stats(opt_level);
+    vm.run().expect("erreur à l'exécution");
+    vm.stats_report().expect("statistiques activées")
+}
+
+fn run_with_profile(source: &str) -> String {
+    let mut vm = common::load(source);
+    vm.enable_profile(0);
+    vm.run().expect("erreur à l'exécution");
+    vm.stats_report().expect("profil activé")
+}
+
+#[test]
+fn stats_are_off_by_default() {
+    let vm = common::start("print 1");
+    assert!(vm.stats_report().is_none());
+}
+
+#[test]
+fn opcodes_and_functions_are_counted() {
+    let report = run_with_stats(
+        "func sq(x) { return x * x }\nfunc main() {\n    var s = 0\n    foreach (i in 0..100) { s = s + sq(i) }\n    return s\n}\nmain()\n",
+        0,
+    );
+    // Une multiplication par appel de sq
+    let mul = report.lines().find(|l| l.trim_start().starts_with("Mul ")).unwrap_or_else(|| panic!("{}", report));
+    assert_eq!(mul.split_whitespace().nth(1), Some("100"), "{}", mul);
+    let sq = report.lines().find(|l| l.trim_start().starts_with("sq (line 1)")).unwrap_or_else(|| panic!("{}", report));
+    assert!(sq.ends_with("calls 100"), "{}", sq);
+    assert!(report.contains("main (line 3)"), "{}", report);
+    assert!(report.contains("Hints:\n  none"), "{}", report);
+}
+
+#[test]
+fn hot_paths_produce_hints() {
+    let source = "func sq(x) { return x * x }\nfunc make(k) {\n    return func(x) {\n        var t = 0\n        foreach (i in 0..4) { t = t + k * k }\n        return t + sq(x)\n    }\n}\nvar f = make(3)\nfunc main() {\n    var s = 0\n    foreach (i in 0..20000) { s = s + f(i) }\n    return s\n}\nmain()\n";
+
+    let report = run_with_stats(source, 0);
+    assert!(report.contains("function <lambda> (line 4) uses GetFreeVar 180000 times"), "{}", report);
+    assert!(report.contains("function sq (line 1) is called 20000 times and runs ~4 instructions per call: if its body is a single `return <expr>`, -O2 inlines it"), "{}", report);
+
+    // Avec -O2 le conseil change
+    let report = run_with_stats(source, 2);
+    assert!(report.contains("consider inlining it in the hot loop"), "{}", report);
+}
+
+#[test]
+fn string_temporaries_reuse_recycled_buffers() {
+    let report = run_with_stats(
+        "func main() {\n    var n = 0\n    foreach (i in 0..1000) {\n        var label = \"item\" + \":\" + i\n        if (label == \"item:7\") { n = n + 1 }\n    }\n    return n\n}\nmain()\n",
+        0,
+    );
+    let line = report.lines().find(|l| l.starts_with("String temporaries:")).unwrap_or_else(|| panic!("{}", report));
+    let numbers: Vec<u64> = line.split_whitespace().filter_map(|word| word.parse().ok()).collect();
+    let (built, reused) = (numbers[0], numbers[1]);
+    // Deux chaînes par tour : "item" recopié pour y ajouter la suite, et la copie de label lue
+    // par la comparaison. Les littéraux internés sont partagés, sans tampon.
+    assert!(built >= 2000, "{}", line);
+    assert!(reused * 10 >= built * 9, "{}", line);
+}
+
+#[test]
+fn profile_times_each_opcode() {
+    let report = run_with_profile(
+        "func double(x) { return [x, x + 1].map(func(v) { return v * 2 }) }\nforeach (i in 0..200) { double(i) }\n",
+    );
+    assert!(report.starts_with("=== PROFILE: "), "{}", report);
+    assert!(report.contains("Opcodes (by time spent in the instruction itself):"), "{}", report);
+
+    // count, % des instructions, temps, % du temps, temps par instruction
+    let mul = report.lines().find(|l| l.trim_start().starts_with("Mul ")).unwrap_or_else(|| panic!("{}", report));
+    let columns: Vec<&str> = mul.split_whitespace().collect();
+    assert_eq!(columns[1], "400", "{}", mul);
+    assert_eq!(columns[4], "ms", "{}", mul);
+    assert_eq!(columns.last(), Some(&"ns"), "{}", mul);
+}
@@ -0,0 +1,75 @@
+// Concaténation de chaînes dans OpCode::Add : résultat inchangé, et indice sur stderr
+// quand une longue chaîne est allongée avec `+=` dans une boucle. En mode strict,
+// `+` entre une chaîne et une autre valeur est une erreur ; `aegis check` le signale.
+
+mod common;
+use common::{aegis_script, run_script};
+
+#[test]
+fn mixed_concatenations() {
+    let output = run_script("concat_mixed", "print \"a\" + 1 + 2.5 + true + null\nprint 1 + \"b\"\nprint [1, 2] + \"c\"\n");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "a12.5truenull\n1b\n[1, 2]c\n");
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn plus_eq_in_loop_prints_one_hint() {
+    let source = "func build() {\n  var s = \"\"\n  var i = 0\n  while (i < 2000) {\n    s += \"0123456789\"\n    i += 1\n  }\n  return s\n}\nprint build().len()\n";
+    let output = run_script("concat_loop", source);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "20000\n");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("hint: line 5: string grown with `+=` in a loop").count(), 1, "stderr: {}", stderr);
+}
+
+#[test]
+fn short_strings_do_not_trigger_the_hint() {
+    let source = "var i = 0\nwhile (i < 500) {\n  var s = \"x\"\n  s += \"y\"\n  i += 1\n}\nprint i\n";
+    let output = run_script("concat_short", source);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "500\n");
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn strict_flag_rejects_implicit_conversions() {
+    let source = "let n = 3\nprint \"n = \" + str(n) + \" / ${n}\"\nprint \"n = \" + n\n";
+    let output = aegis_script("concat_strict_flag", &["run", "--strict"], source);
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "n = 3 / 3\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Cannot add string and int in strict mode"), "stderr: {}", stderr);
+
+    // Sans --strict, la conversion reste implicite
+    let output = run_script("concat_not_strict", source);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "n = 3 / 3\nn = 3\n");
+}
+
+#[test]
+fn use_strict_must_open_the_file() {
+    let output = run_script("concat_use_late", "print 1\nuse strict\n");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("`use strict` must be at the top of the file"));
+
+    let output = run_script("concat_use_unknown", "use fast\nprint 1\n");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown directive 'use fast'"));
+
+    // Seule une docstring peut le précéder
+    let output = run_script("concat_use_doc", "\"Module.\"\nuse strict\nprint \"ok\"\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
+
+#[test]
+fn check_reports_mixed_additions() {
+    let source = "var name = \"x\"\nprint \"count: \" + 3\nprint 1.5 + \"s\"\nprint \"a\" + name\nprint \"${3}\" + str(4)\n";
+    let output = aegis_script("concat_check", &["check"], source);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().filter(|l| l.contains("'+' mixes")).collect();
+    assert_eq!(lines.len(), 2, "stdout: {}", stdout);
+    assert!(lines[0].contains(":2: hint:") && lines[1].contains(":3: hint:"), "stdout: {}", stdout);
+}
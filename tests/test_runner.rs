@@ -0,0 +1,93 @@
+// `aegis test` : découverte des `*_test.aeg`, une VM par fonction `test_*`, résumé et code de sortie.
+
+use std::path::Path;
+use std::process::Output;
+
+mod common;
+use common::project;
+
+fn aegis_test(dir: &Path, args: &[&str]) -> Output {
+    common::aegis_command(dir).arg("test").args(args).output().expect("impossible de lancer aegis")
+}
+
+const MATH_TEST: &str = "var counter = 0
+
+func test_add() {
+    counter = counter + 1
+    assert_eq(counter, 1)
+}
+
+// Nouvelle VM : le test précédent n'a pas touché au compteur
+func test_isolated() {
+    counter = counter + 1
+    assert_eq(counter, 1, \"fresh globals\")
+}
+
+func test_wrong() {
+    assert_eq(2 * 2, \"4\", \"types differ\")
+}
+
+func test_throws() {
+    var e = assert_throws(func() { throw ValueError(\"bad input\") }, ValueError)
+    assert_eq(e.message, \"bad input\")
+}
+
+func helper() { return 1 }
+";
+
+#[test]
+fn runs_each_test_function_and_reports_failures() {
+    let dir = project("report", &[
+        ("tests/math_test.aeg", MATH_TEST),
+        ("tests/unit/script_test.aeg", "assert_true(1 < 2)\n"),
+        ("tests/helpers.aeg", "assert_true(false)\n"),
+        ("packages/dep/dep_test.aeg", "assert_true(false)\n"),
+    ]);
+
+    let output = aegis_test(&dir, &[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+
+    let expected_start = "tests/math_test.aeg
+  ✅ test_add
+  ✅ test_isolated
+  ❌ test_wrong
+      assert_eq failed: expected \"4\", got 4 (types differ)
+  ✅ test_throws
+tests/unit/script_test.aeg
+  ✅ (script)
+
+Failures:
+  tests/math_test.aeg :: test_wrong
+
+test result: FAILED. 4 passed; 1 failed (";
+    assert!(stdout.starts_with(expected_start), "{}", stdout);
+}
+
+#[test]
+fn filter_and_explicit_paths_select_tests() {
+    let dir = project("filter", &[("tests/math_test.aeg", MATH_TEST), ("other.aeg", "assert_eq(1, 1)\n")]);
+
+    let output = aegis_test(&dir, &["--filter", "throws"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("test result: ok. 1 passed; 0 failed"), "{}", stdout);
+
+    // Un fichier donné explicitement est lancé même sans le suffixe _test.aeg
+    let output = aegis_test(&dir, &["other.aeg"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("other.aeg\n  ✅ (script)"));
+}
+
+#[test]
+fn missing_tests_and_syntax_errors_fail() {
+    let dir = project("errors", &[("src/main.aeg", "print 1\n")]);
+    let output = aegis_test(&dir, &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No test files found"));
+
+    let dir = project("syntax", &[("broken_test.aeg", "func test_x( {\n")]);
+    let output = aegis_test(&dir, &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Failures:\n  broken_test.aeg\n"));
+}
@@ -0,0 +1,182 @@
+// Vérification statique des types : `aegis check` liste les erreurs, le mode strict
+// (--strict ou `use strict`) refuse d'exécuter le programme, le mode normal l'exécute.
+
+mod common;
+use common::aegis_script;
+
+const TYPED: &str = "\
+class Animal {
+    age: int = 0
+    init(name: string) { this.name = name }
+    speak(times: int) { return this.name }
+}
+
+func area(w: int, h: int) -> int {
+    return w * h
+}
+
+func label(id: int) -> string {
+    if (id == 0) {
+        return \"zero\"
+    }
+}
+
+var total: int = area(2, 3)
+total = \"six\"
+print area(2)
+print \"typed\"
+try { area(\"2\", 3) } catch (e) { print \"caught\" }
+var a = new Animal(3)
+a.speak(\"twice\")
+a.age = \"old\"
+var s: Strng = \"x\"
+let n = len(\"abc\") - \"1\"
+";
+
+#[test]
+fn check_lists_every_type_error() {
+    let output = aegis_script("check", &["check"], TYPED);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let found: Vec<String> = stdout.lines()
+        .filter_map(|line| {
+            let (at, message) = line.split_once(": error: ")?;
+            Some(format!("{}: {}", at.rsplit(':').next()?, message))
+        })
+        .collect();
+    assert_eq!(found, [
+        "11: Function 'label' must return string but can reach the end of its body without a return",
+        "18: 'total' is declared as int, got string",
+        "19: Function 'area' expects 2 arguments, got 1",
+        "21: Argument 'w' of function 'area' must be int, got string",
+        "22: Argument 'name' of constructor 'Animal' must be string, got int",
+        "23: Argument 'times' of method 'Animal.speak' must be int, got string",
+        "24: Field 'Animal.age' is declared as int, got string",
+        "25: Unknown type 'Strng'",
+        "26: Cannot apply '-' to int and string",
+    ], "stdout: {}", stdout);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("9 type error(s) found"));
+}
+
+#[test]
+fn overloaded_constructors_are_checked_against_the_matching_init() {
+    let source = "class P {\n  init() { this.x = 0 }\n  init(x: int, y: int) { this.x = x + y }\n}\nvar a = new P()\nvar b = new P(1, \"2\")\nvar c = new P(1)\n";
+    let output = aegis_script("overloads", &["check"], source);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let errors: Vec<&str> = stdout.lines().filter_map(|line| line.split_once(": error: ").map(|(_, m)| m)).collect();
+    assert_eq!(errors, [
+        "Argument 'y' of constructor 'P' must be int, got string",
+        "Constructor 'P' has no overload taking 1 arguments",
+    ], "stdout: {}", stdout);
+}
+
+#[test]
+fn strict_mode_refuses_to_run_a_program_with_type_errors() {
+    let output = aegis_script("strict_flag", &["run", "--strict"], TYPED);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty(), "stdout: {}", String::from_utf8_lossy(&output.stdout));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Type error: Function 'label' must return string"), "stderr: {}", stderr);
+    assert!(stderr.contains("(and 8 more, see `aegis check`) at line 11"), "stderr: {}", stderr);
+
+    let output = aegis_script("use_strict", &["run"], &format!("use strict\n{}", TYPED));
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("at line 12"));
+
+    // Sans mode strict, le programme démarre : seules les vérifications à l'exécution s'appliquent
+    let output = aegis_script("not_strict", &["run"], TYPED);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[Line 19] Error: Arity mismatch in 'area'"));
+}
+
+#[test]
+fn values_of_unknown_or_compatible_types_are_accepted() {
+    let source = "\
+type Id = int|string
+
+interface Pet {
+    name()
+}
+
+class Animal {
+    init(name: string) { this.label = name }
+    speak(times: int) { return this.label }
+}
+
+class Dog extends Animal implements Pet {
+    name() { return \"Rex\" }
+}
+
+func find(id: Id) -> Animal|null {
+    if (id == 0) {
+        return null
+    }
+    return new Dog(\"rex\")
+}
+
+func describe(id: Id) -> string {
+    switch (id) {
+        case 1:
+            return \"one\"
+        default:
+            throw \"unknown\"
+    }
+}
+
+func pick(flag: bool) -> int|string {
+    return flag ? 1 : \"one\"
+}
+
+func feed(a: Animal) { return a.speak(1) }
+
+let pet = find(1)
+if (pet != null) {
+    print feed(pet)
+}
+let p: Pet = new Dog(\"rex\")
+var total: int = to_int(\"3\")
+total = total + 1
+func shadow(total) {
+    total = \"local\"
+    return total
+}
+var x = 1
+x = \"now a string\"
+let y: string = x
+let e: Error = new ValueError(\"bad\")
+print describe(1) + str(pick(true)) + str(total)
+";
+    let output = aegis_script("clean", &["check"], source);
+    assert!(output.status.success(), "stdout: {}", String::from_utf8_lossy(&output.stdout));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("error"));
+
+    let output = aegis_script("clean_run", &["run", "--strict"], source);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "rex\none14\n");
+}
+
+#[test]
+fn default_values_and_rest_parameters_set_the_accepted_arities() {
+    let source = "\
+func area(w: int, h: int = 1) -> int { return w * h }
+func pad(s: string = 5) { return s }
+func join(sep: string, ...parts) { return parts.len() }
+print area(2) + area(2, 3)
+print join(\",\") + join(\",\", 1, \"a\", [])
+area()
+area(1, 2, 3)
+join()
+func first(...xs) { return xs - 1 }
+";
+    let output = aegis_script("defaults", &["check"], source);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let errors: Vec<&str> = stdout.lines().filter_map(|line| line.split_once(": error: ").map(|(_, m)| m)).collect();
+    assert_eq!(errors, [
+        "Default value of 's' must be string, got int",
+        "Function 'area' expects 1 to 2 arguments, got 0",
+        "Function 'area' expects 1 to 2 arguments, got 3",
+        "Function 'join' expects at least 1 arguments, got 0",
+        "Cannot apply '-' to list and int",
+    ], "stdout: {}", stdout);
+}
@@ -0,0 +1,140 @@
+// WebSocket : poignée de main, trames (masquage, fragmentation, ping/pong, close) entre un
+// script Aegis et un client écrit à la main, et un échange client/serveur dans le même script.
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use aegis_core::Value;
+
+mod common;
+use common::start;
+
+fn free_port() -> u16 {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+// Trame masquée, comme l'envoie un client
+fn client_frame(first: u8, payload: &[u8]) -> Vec<u8> {
+    let key = [0x12, 0x34, 0x56, 0x78];
+    let mut frame = vec![first, 0x80 | payload.len() as u8];
+    frame.extend_from_slice(&key);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ key[i % 4]));
+    frame
+}
+
+// Trame du serveur : jamais masquée, courte dans ce test
+fn read_server_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut head = [0u8; 2];
+    stream.read_exact(&mut head).unwrap();
+    assert_eq!(head[1] & 0x80, 0, "le serveur ne masque pas ses trames");
+    let mut payload = vec![0u8; (head[1] & 0x7F) as usize];
+    stream.read_exact(&mut payload).unwrap();
+    (head[0], payload)
+}
+
+#[test]
+fn a_server_script_speaks_the_protocol() {
+    let port = free_port();
+    let mut vm = start(&format!(r#"
+import "stdlib/socket.aeg"
+var server = Socket.listen("127.0.0.1", {port})
+
+func serve() {{
+    var ws = WebSocket.accept(server)
+    var msg = ws.recv()
+    ws.send("echo: " + msg)
+    var after = ws.recv()
+    ws.close()
+    return [msg, after]
+}}
+"#));
+
+    let client = std::thread::spawn(move || {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+        // Clé et réponse de l'exemple de la RFC 6455
+        stream.write_all(b"GET /chat HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n").unwrap();
+        let mut head = Vec::new();
+        let mut byte = [0u8; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).unwrap();
+            head.push(byte[0]);
+        }
+        let head = String::from_utf8(head).unwrap();
+        assert!(head.starts_with("HTTP/1.1 101"), "{}", head);
+        assert!(head.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="), "{}", head);
+
+        // Un ping au milieu d'un message en deux fragments
+        stream.write_all(&client_frame(0x01, b"hel")).unwrap();
+        stream.write_all(&client_frame(0x89, b"p")).unwrap();
+        stream.write_all(&client_frame(0x80, b"lo")).unwrap();
+        assert_eq!(read_server_frame(&mut stream), (0x8A, b"p".to_vec()));
+        assert_eq!(read_server_frame(&mut stream), (0x81, b"echo: hello".to_vec()));
+
+        // Le serveur renvoie le code de fermeture
+        stream.write_all(&client_frame(0x88, &1001u16.to_be_bytes())).unwrap();
+        assert_eq!(read_server_frame(&mut stream), (0x88, 1001u16.to_be_bytes().to_vec()));
+    });
+
+    let result = vm.call_global("serve", vec![]).expect("échange WebSocket");
+    client.join().unwrap();
+    assert_eq!(result.to_string(), "[hello, null]");
+}
+
+#[test]
+fn client_and_server_in_one_script() {
+    let port = free_port();
+    let mut vm = start(&format!(r#"
+import "stdlib/socket.aeg"
+var server = Socket.listen("127.0.0.1", {port})
+
+func exchange() {{
+    var pending = WebSocket.accept_async(server)
+    var client = WebSocket.connect("ws://127.0.0.1:{port}/live?room=1")
+    var peer = await pending
+    var seen = []
+
+    client.send("hi")
+    seen.push(peer.recv())
+    var blob = "x"
+    while (blob.len() < 70000) {{ blob = blob + blob }}
+    peer.send(blob.to_bytes())
+    var big = client.recv()
+    seen.push(typeof(big) + " " + big.len())
+    var reply = client.recv_async()
+    peer.send("héllo")
+    seen.push(await reply)
+
+    client.close()
+    seen.push(peer.recv())
+    try {{ peer.send("late") }} catch (e) {{ seen.push(e) }}
+    peer.close()
+    return seen
+}}
+"#));
+    let seen = vm.call_global("exchange", vec![]).expect("échange WebSocket");
+    assert_eq!(seen.to_string(), "[hi, bytes 131072, héllo, null, WebSocket is closed]");
+}
+
+#[test]
+fn connection_errors_reach_the_caller() {
+    let mut vm = start("import \"stdlib/socket.aeg\"\nfunc open(url) { return WebSocket.connect(url) }\n");
+    let err = vm.call_global("open", vec![Value::String("http://example.com".into())]).expect_err("mauvais schéma");
+    assert!(err.contains("WebSocket URL must start with ws:// or wss://"), "{}", err);
+
+    // Un serveur qui n'est pas WebSocket refuse la poignée de main
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buffer = [0u8; 1024];
+        let _ = stream.read(&mut buffer);
+        stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").unwrap();
+    });
+    let err = vm.call_global("open", vec![Value::String(format!("ws://127.0.0.1:{}/", port).into())]).expect_err("404");
+    server.join().unwrap();
+    assert!(err.contains("WebSocket handshake refused by the server: HTTP/1.1 404 Not Found"), "{}", err);
+}
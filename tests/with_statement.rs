@@ -0,0 +1,70 @@
+// `with` sur les handles de la stdlib (File, Socket) et fermeture des ressources
+// quand l'erreur n'est rattrapée par aucun try.
+
+use std::fs;
+
+mod common;
+use common::run_script;
+
+#[test]
+fn file_handles_write_append_and_read_lines() {
+    let data = std::env::temp_dir().join(format!("aegis_with_file_{}.txt", std::process::id()));
+    let data = data.to_string_lossy().replace('\\', "/");
+    let source = format!(
+        "import \"stdlib/file.aeg\"\nwith File.open(\"{0}\", \"w\") as f {{\n  f.write(\"one\\n\")\n}}\nwith File.open(\"{0}\", \"a\") as f {{\n  f.write(\"two\\n\")\n}}\nvar kept = []\nwith File.open(\"{0}\", \"r\") as f {{\n  kept.push(f)\n  print f.read_line()\n  print f.read()\n  print f.read_line()\n}}\nvar handle = kept.at(0)\nprint handle.close()\n",
+        data
+    );
+    let output = run_script("with_file", &source);
+    fs::remove_file(&data).ok();
+
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    // Le handle a déjà été fermé par `with` : close() rend false
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "one\ntwo\n\nnull\nfalse\n");
+}
+
+#[test]
+fn closed_file_handle_is_an_error() {
+    let data = std::env::temp_dir().join(format!("aegis_with_closed_{}.txt", std::process::id()));
+    let data = data.to_string_lossy().replace('\\', "/");
+    let source = format!(
+        "import \"stdlib/file.aeg\"\nvar f = File.open(\"{}\", \"w\")\nf.close()\nf.write(\"late\")\n",
+        data
+    );
+    let output = run_script("with_closed", &source);
+    fs::remove_file(&data).ok();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("File handle is closed"));
+}
+
+#[test]
+fn socket_handle_closes_on_block_exit() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let source = format!(
+        "import \"stdlib/socket.aeg\"\nwith Socket.open(\"127.0.0.1\", {}) as conn {{\n  conn.write(\"ping\")\n}}\nprint \"done\"\n",
+        port
+    );
+    let server = std::thread::spawn(move || {
+        use std::io::Read;
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut received = String::new();
+        // read_to_string ne se termine que lorsque le client a fermé la connexion
+        stream.read_to_string(&mut received).unwrap();
+        received
+    });
+
+    let output = run_script("with_socket", &source);
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(server.join().unwrap(), "ping");
+}
+
+#[test]
+fn uncaught_error_closes_open_resources() {
+    let source = "class Res {\n  init(name) { this.name = name }\n  close() { print \"close \" + this.name }\n}\nfunc work() {\n  with new Res(\"inner\") as r {\n    throw \"boom\"\n  }\n}\nwith new Res(\"outer\") as r {\n  work()\n}\n";
+    let output = run_script("with_uncaught", source);
+
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "close inner\nclose outer\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("boom"));
+}